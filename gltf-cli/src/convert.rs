@@ -0,0 +1,357 @@
+// 形式の変換（gltf-cli convert）
+//
+// 出力の形式はファイル名の拡張子で決める
+//   .glb   バッファーと画像を全て BIN チャンクに入れる
+//   .gltf  --resources embed（既定）ならバッファーと画像を data URI で埋め込み、
+//          extract なら同じディレクトリに <名前>.bin と <名前>_<画像の番号>.<拡張子> を書き出す
+// GLB はバッファーを1つしか持てないので、どちらの形式でもバッファーは1つにまとめる
+// JSON は serde_json の値のまま書き換え、バッファーと画像以外（拡張や extras など）はそのまま残す
+use std::collections::BTreeSet;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use clap::ValueEnum;
+use my_cli_errors::Failure;
+use serde_json::{Value, json};
+
+// .gltf に出力するときのバッファーと画像の置き場所
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Resources {
+    /// Data URIs inside the .gltf file
+    Embed,
+    /// Separate .bin and image files next to the .gltf file
+    Extract,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Target {
+    Glb,
+    Gltf(Resources),
+}
+
+// 書き出すもの（resources は出力と同じディレクトリに置くファイルの名前と中身）
+pub struct Output {
+    pub data: Vec<u8>,
+    pub resources: Vec<(String, Vec<u8>)>,
+}
+
+// data は入力ファイルの中身、base は外部のバッファーと画像を探すディレクトリ、
+// stem は書き出すファイルの名前の元（出力ファイル名から拡張子を除いたもの）、scale はシーン全体の倍率
+pub fn convert(data: &[u8], base: &Path, target: Target, stem: &str, scale: f64) -> Result<Output> {
+    // 読めるかどうか（JSON の規則に合うか、バッファーがあるか）は gltf クレートで確かめる
+    let gltf::Gltf { document, blob } = gltf::Gltf::from_slice(data)?;
+    let buffers = gltf::import_buffers(&document, Some(base), blob)?;
+    let mut root: Value = if gltf_core::model::is_glb(data) {
+        serde_json::from_slice(&gltf::Glb::from_slice(data)?.json)?
+    } else {
+        serde_json::from_slice(data)?
+    };
+    let images = document
+        .images()
+        .map(|image| read_image(image, base, &buffers))
+        .collect::<Result<Vec<_>>>()?;
+
+    // .gltf では画像をバッファーから出すので、画像だけが使うバッファービューは捨てる
+    let mut dropped = BTreeSet::new();
+    if target != Target::Glb {
+        let mut used = BTreeSet::new();
+        for (key, value) in root.as_object().into_iter().flatten() {
+            if key != "images" {
+                buffer_views(value, &mut used);
+            }
+        }
+        let mut in_images = BTreeSet::new();
+        buffer_views(&root["images"], &mut in_images);
+        dropped = &in_images - &used;
+    }
+
+    // 残すバッファービューを1つのバッファーに詰め直す
+    let mut bin = Vec::new();
+    let mut views = Vec::new();
+    let mut renumbered = Vec::new();
+    for view in document.views() {
+        if dropped.contains(&view.index()) {
+            renumbered.push(None);
+            continue;
+        }
+        let buffer = &buffers[view.buffer().index()];
+        let offset = append(&mut bin, &buffer[view.offset()..view.offset() + view.length()]);
+        let mut json = root["bufferViews"][view.index()].clone();
+        json["buffer"] = json!(0);
+        json["byteOffset"] = json!(offset);
+        renumbered.push(Some(views.len()));
+        views.push(json);
+    }
+
+    let mut resources = Vec::new();
+    if let Target::Gltf(mode) = target {
+        for (index, (bytes, mime, extension)) in images.iter().enumerate() {
+            let uri = match mode {
+                Resources::Embed => format!("data:{};base64,{}", mime, STANDARD.encode(bytes)),
+                Resources::Extract => {
+                    let name = format!("{}_{}.{}", stem, index, extension);
+                    resources.push((name.clone(), bytes.clone()));
+                    urlencoding::encode(&name).into_owned()
+                }
+            };
+            let image = root["images"][index].as_object_mut().context("images must be objects")?;
+            image.remove("bufferView");
+            image.remove("mimeType");
+            image.insert("uri".to_string(), json!(uri));
+        }
+    }
+    renumber(&mut root, &renumbered);
+    if scale != 1.0 {
+        scale_scenes(&mut root, scale)?;
+    }
+
+    // GLB では外部ファイルや data URI の画像も BIN チャンクに入れる
+    if target == Target::Glb {
+        for (index, (bytes, mime, _)) in images.iter().enumerate() {
+            let image = root["images"][index].as_object_mut().context("images must be objects")?;
+            if image.contains_key("bufferView") {
+                continue;
+            }
+            let offset = append(&mut bin, bytes);
+            image.remove("uri");
+            image.insert("bufferView".to_string(), json!(views.len()));
+            image.insert("mimeType".to_string(), json!(mime));
+            views.push(json!({ "buffer": 0, "byteOffset": offset, "byteLength": bytes.len() }));
+        }
+    }
+
+    let root = root.as_object_mut().context("the glTF JSON must be an object")?;
+    if views.is_empty() {
+        root.remove("bufferViews");
+        root.remove("buffers");
+    } else {
+        let mut buffer = json!({ "byteLength": bin.len() });
+        match target {
+            Target::Glb => {}
+            Target::Gltf(Resources::Embed) => {
+                buffer["uri"] = json!(format!("data:application/octet-stream;base64,{}", STANDARD.encode(&bin)));
+            }
+            Target::Gltf(Resources::Extract) => {
+                let name = format!("{}.bin", stem);
+                buffer["uri"] = json!(urlencoding::encode(&name));
+                resources.insert(0, (name, bin.clone()));
+            }
+        }
+        root.insert("bufferViews".to_string(), Value::Array(views));
+        root.insert("buffers".to_string(), json!([buffer]));
+    }
+
+    let data = match target {
+        Target::Glb => {
+            let json = serde_json::to_vec(root)?;
+            let glb = gltf::Glb {
+                // length は to_vec が計算し直す
+                header: gltf::binary::Header { magic: *b"glTF", version: 2, length: 0 },
+                json: json.into(),
+                bin: (!bin.is_empty()).then(|| bin.into()),
+            };
+            glb.to_vec()?
+        }
+        Target::Gltf(_) => {
+            let mut data = serde_json::to_vec_pretty(root)?;
+            data.push(b'\n');
+            data
+        }
+    };
+    Ok(Output { data, resources })
+}
+
+// 各シーンの一番上のノードに倍率を掛ける（頂点はそのままにし、ノードの変換で大きさを変える）
+//   TRS のノードは translation と scale に、matrix のノードは回転・拡大と平行移動の行に掛ける
+fn scale_scenes(root: &mut Value, factor: f64) -> Result<()> {
+    let mut roots = BTreeSet::new();
+    for scene in root["scenes"].as_array().into_iter().flatten() {
+        roots.extend(scene["nodes"].as_array().into_iter().flatten().filter_map(Value::as_u64));
+    }
+    for index in roots {
+        let node = root["nodes"][index as usize].as_object_mut().context("nodes must be objects")?;
+        let multiply = |values: &[Value]| -> Vec<f64> { values.iter().map(|value| value.as_f64().unwrap_or(0.0) * factor).collect() };
+        if let Some(matrix) = node.get_mut("matrix").and_then(Value::as_array_mut) {
+            // 列優先なので、4つごとの最後（4行目）以外に掛ける
+            for (i, value) in matrix.iter_mut().enumerate() {
+                if i % 4 != 3 {
+                    *value = json!(value.as_f64().unwrap_or(0.0) * factor);
+                }
+            }
+            continue;
+        }
+        if let Some(translation) = node.get("translation").and_then(Value::as_array) {
+            let translation = multiply(translation);
+            node.insert("translation".to_string(), json!(translation));
+        }
+        let scale = node.get("scale").and_then(Value::as_array).map_or_else(|| vec![factor; 3], |scale| multiply(scale));
+        node.insert("scale".to_string(), json!(scale));
+    }
+    Ok(())
+}
+
+// 4バイト境界に揃えて足し、足した位置を返す
+pub fn append(bin: &mut Vec<u8>, bytes: &[u8]) -> usize {
+    bin.resize(bin.len().next_multiple_of(4), 0);
+    let offset = bin.len();
+    bin.extend_from_slice(bytes);
+    offset
+}
+
+// value の中の全ての "bufferView" の番号（アクセサー、疎なアクセサー、拡張の中も含む）
+fn buffer_views(value: &Value, found: &mut BTreeSet<usize>) {
+    match value {
+        Value::Object(object) => {
+            for (key, value) in object {
+                match value.as_u64() {
+                    Some(index) if key == "bufferView" => {
+                        found.insert(index as usize);
+                    }
+                    _ => buffer_views(value, found),
+                }
+            }
+        }
+        Value::Array(values) => values.iter().for_each(|value| buffer_views(value, found)),
+        _ => {}
+    }
+}
+
+// バッファービューを捨てた後の番号に付け替える
+fn renumber(value: &mut Value, renumbered: &[Option<usize>]) {
+    match value {
+        Value::Object(object) => {
+            for (key, value) in object.iter_mut() {
+                match value.as_u64().and_then(|index| renumbered.get(index as usize).copied().flatten()) {
+                    Some(index) if key == "bufferView" => *value = json!(index),
+                    _ => renumber(value, renumbered),
+                }
+            }
+        }
+        Value::Array(values) => values.iter_mut().for_each(|value| renumber(value, renumbered)),
+        _ => {}
+    }
+}
+
+// 画像の元のファイルの中身と、MIME タイプ、書き出すときの拡張子
+fn read_image(image: gltf::Image, base: &Path, buffers: &[gltf::buffer::Data]) -> Result<(Vec<u8>, &'static str, &'static str)> {
+    let bytes = match image.source() {
+        gltf::image::Source::View { view, .. } => {
+            buffers[view.buffer().index()][view.offset()..view.offset() + view.length()].to_vec()
+        }
+        gltf::image::Source::Uri { uri, .. } => read_uri(uri, base)
+            .map_err(|e| Failure::new(crate::EXIT_MODEL, format!("images[{}]: cannot be read: {}", image.index(), e)))?,
+    };
+    let (mime, extension) = image_type(&bytes).ok_or_else(|| {
+        Failure::new(crate::EXIT_MODEL, format!("images[{}]: not a PNG, JPEG, WebP or KTX2 image", image.index()))
+    })?;
+    Ok((bytes, mime, extension))
+}
+
+// data URI か、base からの相対パス（gltf クレートのバッファーの読み込みと同じ規則）
+fn read_uri(uri: &str, base: &Path) -> Result<Vec<u8>> {
+    if let Some(data) = uri.strip_prefix("data:") {
+        let (_, encoded) = data.split_once(";base64,").context("only base64 data URIs are supported")?;
+        return Ok(STANDARD.decode(encoded)?);
+    }
+    let path = match uri.strip_prefix("file://").or_else(|| uri.strip_prefix("file:")) {
+        Some(path) => path.into(),
+        None if !uri.contains(':') => base.join(&*urlencoding::decode(uri)?),
+        None => anyhow::bail!("unsupported URI scheme"),
+    };
+    Ok(std::fs::read(path)?)
+}
+
+// 中身の先頭で画像の種類を見分ける
+fn image_type(bytes: &[u8]) -> Option<(&'static str, &'static str)> {
+    if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        Some(("image/png", "png"))
+    } else if bytes.starts_with(b"\xff\xd8\xff") {
+        Some(("image/jpeg", "jpg"))
+    } else if bytes.len() >= 12 && bytes.starts_with(b"RIFF") && &bytes[8..12] == b"WEBP" {
+        Some(("image/webp", "webp"))
+    } else if bytes.starts_with(b"\xabKTX 20\xbb\r\n\x1a\n") {
+        Some(("image/ktx2", "ktx2"))
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures;
+
+    // 1x1 の PNG
+    const PNG: &[u8] = b"\x89PNG\x0d\x0a\x1a\x0a\x00\x00\x00\x0dIHDR\x00\x00\x00\x01\x00\x00\x00\x01\x08\x06\x00\x00\x00\x1f\x15\xc4\x89\x00\x00\x00\x0dIDATx\x9cc\xf8\xcf\xc0\xf0\x1f\x00\x05\x00\x01\xff\x89\x99=\x1d\x00\x00\x00\x00IEND\xaeB`\x82";
+
+    fn triangle_with_image() -> Vec<u8> {
+        test_fixtures::triangle_with(|root| {
+            root["images"] = json!([{ "uri": format!("data:image/png;base64,{}", STANDARD.encode(PNG)) }]);
+            root["textures"] = json!([{ "source": 0 }]);
+        })
+        .into_bytes()
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let base = Path::new(".");
+        let glb = convert(&triangle_with_image(), base, Target::Glb, "model", 1.0).unwrap();
+        assert!(gltf_core::model::is_glb(&glb.data));
+        assert!(glb.resources.is_empty());
+        let model = gltf_core::Model::from_slice(&glb.data).unwrap();
+        assert!(model.glb);
+        assert_eq!(model.document.views().count(), 3);
+        assert!(matches!(model.document.images().next().unwrap().source(), gltf::image::Source::View { mime_type: "image/png", .. }));
+
+        // GLB から戻すと、画像だけが使っていたバッファービューはなくなる
+        let gltf = convert(&glb.data, base, Target::Gltf(Resources::Embed), "model", 1.0).unwrap();
+        // gltf::import_slice は data URI の画像も外部ファイル扱いで読まないので、バッファーだけ読む
+        let document = gltf::Gltf::from_slice(&gltf.data).unwrap().document;
+        assert_eq!(document.views().count(), 2);
+        assert_eq!(gltf::import_buffers(&document, None, None).unwrap()[0].len(), 44);
+        let root: Value = serde_json::from_slice(&gltf.data).unwrap();
+        assert!(root["images"][0]["uri"].as_str().unwrap().starts_with("data:image/png;base64,"));
+
+        let extracted = convert(&glb.data, base, Target::Gltf(Resources::Extract), "my model", 1.0).unwrap();
+        let names: Vec<_> = extracted.resources.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, ["my model.bin", "my model_0.png"]);
+        assert_eq!(extracted.resources[1].1, PNG);
+        let root: Value = serde_json::from_slice(&extracted.data).unwrap();
+        assert_eq!(root["buffers"][0]["uri"], "my%20model.bin");
+        assert_eq!(root["images"][0]["uri"], "my%20model_0.png");
+    }
+
+    #[test]
+    fn test_scale() {
+        let mut root = json!({
+            "scenes": [{ "nodes": [0, 1] }, { "nodes": [1] }],
+            "nodes": [
+                { "translation": [1, 2, 3], "children": [2] },
+                { "matrix": [1, 0, 0, 0, 0, 1, 0, 0, 0, 0, 1, 0, 4, 5, 6, 1] },
+                { "translation": [1, 1, 1] }
+            ]
+        });
+        scale_scenes(&mut root, 0.5).unwrap();
+        assert_eq!(root["nodes"][0]["translation"], json!([0.5, 1.0, 1.5]));
+        assert_eq!(root["nodes"][0]["scale"], json!([0.5, 0.5, 0.5]));
+        // 2つのシーンにあるノードも1回だけ掛ける
+        assert_eq!(root["nodes"][1]["matrix"], json!([0.5, 0.0, 0.0, 0, 0.0, 0.5, 0.0, 0, 0.0, 0.0, 0.5, 0, 2.0, 2.5, 3.0, 1]));
+        // 子のノードはそのまま
+        assert_eq!(root["nodes"][2], json!({ "translation": [1, 1, 1] }));
+    }
+
+    #[test]
+    fn test_renumber() {
+        let mut value = json!({ "accessors": [{ "bufferView": 2 }, { "sparse": { "indices": { "bufferView": 0 } } }] });
+        let mut found = BTreeSet::new();
+        buffer_views(&value, &mut found);
+        assert_eq!(found, BTreeSet::from([0, 2]));
+        renumber(&mut value, &[Some(0), None, Some(1)]);
+        assert_eq!(value["accessors"][0]["bufferView"], 1);
+        assert_eq!(value["accessors"][1]["sparse"]["indices"]["bufferView"], 0);
+        assert_eq!(image_type(PNG), Some(("image/png", "png")));
+        assert_eq!(image_type(b"GIF89a"), None);
+    }
+}
@@ -0,0 +1,10 @@
+// 数値の引数を calc の式として読む clap の value_parser（式の評価とエラーの文言は gltf_core::expression と同じ）
+pub use gltf_core::expression::{count, positive};
+
+// 0 以上の距離
+pub fn distance(input: &str) -> Result<f32, String> {
+    match gltf_core::expression::number(input)? {
+        value if value >= 0.0 => Ok(value as f32),
+        value => Err(format!("{} must be at least 0 (got {})", input.trim(), value)),
+    }
+}
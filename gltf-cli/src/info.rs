@@ -0,0 +1,353 @@
+// モデルの概要（gltf-cli info）
+//
+// 三角形の数はアクセサーの数から出すので、バッファーの中身は読まない
+use std::fmt::Write;
+use std::path::Path;
+
+use gltf::mesh::Mode;
+use gltf_core::{geometry, Model};
+use serde::Serialize;
+
+#[derive(Debug, Serialize)]
+pub struct Summary {
+    pub file: String,
+    // "glb" か "gltf"
+    pub container: &'static str,
+    pub version: String,
+    pub generator: Option<String>,
+    pub default_scene: Option<usize>,
+    pub scenes: Vec<Scene>,
+    pub nodes: usize,
+    pub meshes: Vec<Mesh>,
+    pub materials: Vec<Material>,
+    pub textures: Vec<Texture>,
+    pub extensions_used: Vec<String>,
+    pub extensions_required: Vec<String>,
+    pub buffers: Vec<Buffer>,
+    pub totals: Totals,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Scene {
+    pub index: usize,
+    pub name: Option<String>,
+    // 最上位のノードの数
+    pub nodes: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Mesh {
+    pub index: usize,
+    pub name: Option<String>,
+    pub primitives: Vec<Primitive>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Primitive {
+    pub mode: &'static str,
+    pub vertices: usize,
+    pub indices: Option<usize>,
+    pub triangles: usize,
+    pub material: Option<usize>,
+    pub attributes: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Material {
+    pub index: usize,
+    pub name: Option<String>,
+    pub alpha_mode: &'static str,
+    pub double_sided: bool,
+    // 使っているテクスチャの番号（ベースカラー、法線など）
+    pub textures: Vec<usize>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Texture {
+    pub index: usize,
+    pub name: Option<String>,
+    pub image: usize,
+    pub mime_type: Option<String>,
+    // "embedded"（GLB か data URI）か外部ファイルの URI
+    pub source: String,
+    pub width: u32,
+    pub height: u32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Buffer {
+    pub index: usize,
+    pub name: Option<String>,
+    pub byte_length: usize,
+    pub source: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Totals {
+    pub primitives: usize,
+    pub vertices: usize,
+    pub triangles: usize,
+    pub buffer_bytes: usize,
+}
+
+pub fn summarize(path: &Path, model: &Model) -> Summary {
+    let document = &model.document;
+    let asset = &document.as_json().asset;
+    let meshes: Vec<Mesh> = document.meshes().map(|mesh| Mesh {
+        index: mesh.index(),
+        name: mesh.name().map(str::to_string),
+        primitives: mesh.primitives().map(|primitive| {
+            let vertices = primitive.get(&gltf::Semantic::Positions).map_or(0, |accessor| accessor.count());
+            Primitive {
+                mode: mode_name(primitive.mode()),
+                vertices,
+                indices: primitive.indices().map(|accessor| accessor.count()),
+                triangles: geometry::triangle_count(primitive.mode(), geometry::element_count(&primitive)),
+                material: primitive.material().index(),
+                attributes: primitive.attributes().map(|(semantic, _)| semantic.to_string()).collect(),
+            }
+        }).collect(),
+    }).collect();
+    let buffers: Vec<Buffer> = document.buffers().map(|buffer| Buffer {
+        index: buffer.index(),
+        name: buffer.name().map(str::to_string),
+        byte_length: buffer.length(),
+        source: match buffer.source() {
+            gltf::buffer::Source::Bin => "embedded".to_string(),
+            gltf::buffer::Source::Uri(uri) => source_name(uri),
+        },
+    }).collect();
+    let primitives = meshes.iter().flat_map(|mesh| &mesh.primitives);
+    let totals = Totals {
+        primitives: primitives.clone().count(),
+        vertices: primitives.clone().map(|primitive| primitive.vertices).sum(),
+        triangles: primitives.map(|primitive| primitive.triangles).sum(),
+        buffer_bytes: buffers.iter().map(|buffer| buffer.byte_length).sum(),
+    };
+
+    Summary {
+        file: path.display().to_string(),
+        container: if model.glb { "glb" } else { "gltf" },
+        version: asset.version.clone(),
+        generator: asset.generator.clone(),
+        default_scene: document.default_scene().map(|scene| scene.index()),
+        scenes: document.scenes().map(|scene| Scene {
+            index: scene.index(),
+            name: scene.name().map(str::to_string),
+            nodes: scene.nodes().count(),
+        }).collect(),
+        nodes: document.nodes().count(),
+        meshes,
+        materials: document.materials().map(|material| Material {
+            index: material.index().unwrap_or_default(),
+            name: material.name().map(str::to_string),
+            alpha_mode: match material.alpha_mode() {
+                gltf::material::AlphaMode::Opaque => "OPAQUE",
+                gltf::material::AlphaMode::Mask => "MASK",
+                gltf::material::AlphaMode::Blend => "BLEND",
+            },
+            double_sided: material.double_sided(),
+            textures: material_textures(&material),
+        }).collect(),
+        textures: document.textures().map(|texture| {
+            let image = texture.source();
+            let decoded = model.images.get(image.index());
+            let (mime_type, source) = match image.source() {
+                gltf::image::Source::View { mime_type, .. } => (Some(mime_type.to_string()), "embedded".to_string()),
+                gltf::image::Source::Uri { uri, mime_type } => (mime_type.map(str::to_string), source_name(uri)),
+            };
+            Texture {
+                index: texture.index(),
+                name: texture.name().map(str::to_string),
+                image: image.index(),
+                mime_type,
+                source,
+                width: decoded.map_or(0, |data| data.width),
+                height: decoded.map_or(0, |data| data.height),
+            }
+        }).collect(),
+        extensions_used: document.extensions_used().map(str::to_string).collect(),
+        extensions_required: document.extensions_required().map(str::to_string).collect(),
+        buffers,
+        totals,
+    }
+}
+
+// 人が読む形式
+pub fn render(summary: &Summary) -> String {
+    let mut out = String::new();
+    let generator = summary.generator.as_ref().map(|generator| format!(", generator: {}", generator)).unwrap_or_default();
+    let _ = writeln!(out, "{} ({}, glTF {}{})", summary.file, summary.container.to_uppercase(), summary.version, generator);
+
+    let _ = writeln!(out, "Scenes: {}", summary.scenes.len());
+    for scene in &summary.scenes {
+        let default = if summary.default_scene == Some(scene.index) { " (default)" } else { "" };
+        let _ = writeln!(out, "  {}{}: {} root node(s){}", scene.index, label(&scene.name), scene.nodes, default);
+    }
+    let _ = writeln!(out, "Nodes: {}", summary.nodes);
+
+    let totals = &summary.totals;
+    let _ = writeln!(
+        out,
+        "Meshes: {} ({} primitive(s), {} vertices, {} triangles)",
+        summary.meshes.len(), totals.primitives, totals.vertices, totals.triangles
+    );
+    for mesh in &summary.meshes {
+        let _ = writeln!(out, "  {}{}", mesh.index, label(&mesh.name));
+        for (index, primitive) in mesh.primitives.iter().enumerate() {
+            let material = primitive.material.map_or("default material".to_string(), |material| format!("material {}", material));
+            let _ = writeln!(
+                out,
+                "    {}: {} {} vertices, {} triangles, {}, {}",
+                index, primitive.mode, primitive.vertices, primitive.triangles, material, primitive.attributes.join(" ")
+            );
+        }
+    }
+
+    let _ = writeln!(out, "Materials: {}", summary.materials.len());
+    for material in &summary.materials {
+        let sided = if material.double_sided { ", double-sided" } else { "" };
+        let textures = if material.textures.is_empty() {
+            String::new()
+        } else {
+            format!(", textures {}", material.textures.iter().map(ToString::to_string).collect::<Vec<_>>().join(" "))
+        };
+        let _ = writeln!(out, "  {}{}: {}{}{}", material.index, label(&material.name), material.alpha_mode, sided, textures);
+    }
+
+    let _ = writeln!(out, "Textures: {}", summary.textures.len());
+    for texture in &summary.textures {
+        let mime_type = texture.mime_type.as_deref().unwrap_or("unknown type");
+        let _ = writeln!(
+            out,
+            "  {}{}: image {} ({}, {}x{}, {})",
+            texture.index, label(&texture.name), texture.image, mime_type, texture.width, texture.height, texture.source
+        );
+    }
+
+    let _ = writeln!(out, "Extensions used: {}", list(&summary.extensions_used));
+    let _ = writeln!(out, "Extensions required: {}", list(&summary.extensions_required));
+
+    let _ = writeln!(out, "Buffers: {} ({})", summary.buffers.len(), byte_size(totals.buffer_bytes));
+    for buffer in &summary.buffers {
+        let _ = writeln!(out, "  {}{}: {} ({})", buffer.index, label(&buffer.name), byte_size(buffer.byte_length), buffer.source);
+    }
+    out
+}
+
+fn label(name: &Option<String>) -> String {
+    name.as_ref().map(|name| format!(" {:?}", name)).unwrap_or_default()
+}
+
+fn list(items: &[String]) -> String {
+    if items.is_empty() { "none".to_string() } else { items.join(", ") }
+}
+
+// 1536 → "1.5 KiB"
+pub fn byte_size(bytes: usize) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024 {
+        return format!("{} bytes", bytes);
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit + 1 < UNITS.len() {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", size, UNITS[unit])
+}
+
+fn mode_name(mode: Mode) -> &'static str {
+    match mode {
+        Mode::Points => "POINTS",
+        Mode::Lines => "LINES",
+        Mode::LineLoop => "LINE_LOOP",
+        Mode::LineStrip => "LINE_STRIP",
+        Mode::Triangles => "TRIANGLES",
+        Mode::TriangleStrip => "TRIANGLE_STRIP",
+        Mode::TriangleFan => "TRIANGLE_FAN",
+    }
+}
+
+// data URI は中身を出さない
+fn source_name(uri: &str) -> String {
+    if uri.starts_with("data:") { "embedded".to_string() } else { uri.to_string() }
+}
+
+fn material_textures(material: &gltf::Material) -> Vec<usize> {
+    let pbr = material.pbr_metallic_roughness();
+    let mut textures = vec![
+        pbr.base_color_texture().map(|info| info.texture().index()),
+        pbr.metallic_roughness_texture().map(|info| info.texture().index()),
+        material.normal_texture().map(|normal| normal.texture().index()),
+        material.occlusion_texture().map(|occlusion| occlusion.texture().index()),
+        material.emissive_texture().map(|info| info.texture().index()),
+    ]
+    .into_iter()
+    .flatten()
+    .collect::<Vec<_>>();
+    textures.sort_unstable();
+    textures.dedup();
+    textures
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures;
+    use serde_json::{json, Value};
+
+    // 三角形1枚と四角形（三角形ストリップ）の2つのメッシュ
+    fn model() -> Model {
+        let json = test_fixtures::triangle_with(|root| {
+            // 四角形の位置は三角形の3つの頂点に (1,1,0) を足したもの
+            let uri = root["buffers"][0]["uri"].as_str().unwrap();
+            let quad = format!("{}AACAPwAAgD8AAAAA", &uri[..uri.len() - "AAABAAIAAAA=".len()]);
+            let push = |items: &mut Value, item: Value| items.as_array_mut().unwrap().push(item);
+            push(&mut root["buffers"], json!({ "byteLength": 48, "uri": quad }));
+            push(&mut root["bufferViews"], json!({ "buffer": 1, "byteOffset": 0, "byteLength": 48 }));
+            push(&mut root["accessors"], json!({ "bufferView": 2, "componentType": 5126, "count": 4, "type": "VEC3", "min": [0, 0, 0], "max": [1, 1, 0] }));
+            push(&mut root["meshes"], json!({ "primitives": [{ "attributes": { "POSITION": 2 }, "mode": 5 }] }));
+            push(&mut root["nodes"], json!({ "mesh": 1 }));
+            root["asset"]["generator"] = json!("test");
+            root["scenes"] = json!([{ "name": "Scene", "nodes": [0, 1] }]);
+            root["meshes"][0]["name"] = json!("Triangle");
+            root["meshes"][0]["primitives"][0]["material"] = json!(0);
+            root["materials"] = json!([{ "name": "Red", "doubleSided": true }]);
+            root["extensionsUsed"] = json!(["KHR_materials_unlit"]);
+        });
+        Model::from_slice(json.as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn test_summarize() {
+        let model = model();
+        let summary = summarize(Path::new("model.gltf"), &model);
+        assert_eq!(summary.container, "gltf");
+        assert_eq!(summary.generator.as_deref(), Some("test"));
+        assert_eq!(summary.scenes[0].nodes, 2);
+        assert_eq!(summary.meshes[1].primitives[0].mode, "TRIANGLE_STRIP");
+        assert_eq!(summary.meshes[1].primitives[0].triangles, 2);
+        assert_eq!(summary.meshes[0].primitives[0].attributes, vec!["POSITION"]);
+        assert_eq!((summary.totals.primitives, summary.totals.vertices, summary.totals.triangles, summary.totals.buffer_bytes), (2, 7, 3, 92));
+        assert_eq!(summary.extensions_used, vec!["KHR_materials_unlit"]);
+        assert_eq!(summary.buffers[0].source, "embedded");
+
+        let text = render(&summary);
+        assert!(text.starts_with("model.gltf (GLTF, glTF 2.0, generator: test)\n"), "{}", text);
+        assert!(text.contains("  0 \"Scene\": 2 root node(s) (default)\n"), "{}", text);
+        assert!(text.contains("Meshes: 2 (2 primitive(s), 7 vertices, 3 triangles)\n"), "{}", text);
+        assert!(text.contains("    0: TRIANGLES 3 vertices, 1 triangles, material 0, POSITION\n"), "{}", text);
+        assert!(text.contains("  0 \"Red\": OPAQUE, double-sided\n"), "{}", text);
+        assert!(text.contains("Extensions required: none\n"), "{}", text);
+        assert!(text.ends_with("Buffers: 2 (92 bytes)\n  0: 44 bytes (embedded)\n  1: 48 bytes (embedded)\n"), "{}", text);
+    }
+
+    #[test]
+    fn test_byte_size() {
+        assert_eq!(byte_size(1023), "1023 bytes");
+        assert_eq!(byte_size(1536), "1.5 KiB");
+        assert_eq!(byte_size(3 * 1024 * 1024), "3.0 MiB");
+    }
+}
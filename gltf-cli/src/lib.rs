@@ -0,0 +1,311 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use clap::{CommandFactory, Parser, Subcommand, ValueEnum};
+use gltf_core::Model;
+use my_cli_errors::{out, outln, CliError, Failure, EXIT_USAGE};
+use my_cli_progress::Progress;
+
+mod convert;
+mod expression;
+mod info;
+mod optimize;
+mod render;
+#[cfg(test)]
+mod test_fixtures;
+mod validate;
+
+// gltf-cli の終了コード（0、1、2 は my-cli の全てのツールで同じ）
+const EXIT_MODEL: i32 = 3;
+const EXIT_INVALID: i32 = 4;
+
+// CLIコマンド構造体
+#[derive(Parser)]
+#[command(name = "gltf-cli")]
+#[command(version)]
+#[command(about = "Inspect glTF models from the command line")]
+#[command(author = "Otsuka Noboru <mopinfish@gmail.ocm>")]
+#[command(after_help = "Exit status:\n  \
+0  success\n  \
+1  other errors\n  \
+2  invalid command-line usage\n  \
+3  the file is not a readable glTF model (bad JSON or GLB, missing buffers or images)\n  \
+4  validate found errors (or warnings with --deny-warnings)")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+
+    // -v と --log-format（my-cli 共通）
+    #[command(flatten)]
+    log: my_cli_log::LogArgs,
+}
+
+// my-cli gltf でも同じサブコマンドを使う
+#[derive(Subcommand)]
+pub enum Command {
+    /// Print scenes, nodes, meshes, materials, textures, extensions and buffers of a .gltf or .glb file
+    Info {
+        /// Model file (.gltf or .glb)
+        #[arg(value_name = "FILE")]
+        file: PathBuf,
+
+        /// Output format
+        #[arg(long, value_enum, default_value_t = Format::Text)]
+        format: Format,
+    },
+
+    /// Check buffers, accessor and index ranges, positions and extensions; exits with 4 on errors
+    #[command(after_help = "Each issue has a severity (error, warning or info), a code such as index-range, \
+and the place in the glTF JSON, e.g. meshes[0].primitives[1].indices.")]
+    Validate {
+        /// Model file (.gltf or .glb)
+        #[arg(value_name = "FILE")]
+        file: PathBuf,
+
+        /// Output format (json prints {file, valid, errors, warnings, issues})
+        #[arg(long, value_enum, default_value_t = Format::Text)]
+        format: Format,
+
+        /// Fail on warnings too
+        #[arg(long)]
+        deny_warnings: bool,
+    },
+
+    /// Convert between .glb and .gltf, embedding or extracting buffers and images
+    #[command(after_help = "The output format follows the OUTPUT extension. A .glb packs every buffer and image into \
+its binary chunk. A .gltf gets data URIs (--resources embed) or, with --resources extract, \
+<name>.bin and <name>_<image>.<ext> files next to it. Buffers are always merged into one.")]
+    Convert {
+        /// Model file to read (.gltf or .glb)
+        #[arg(value_name = "INPUT")]
+        input: PathBuf,
+
+        /// File to write (.gltf or .glb)
+        #[arg(value_name = "OUTPUT")]
+        output: PathBuf,
+
+        /// Where a .gltf output keeps its buffers and images
+        #[arg(long, value_enum, default_value_t = convert::Resources::Embed)]
+        resources: convert::Resources,
+
+        /// Scale the whole scene by this factor; takes a calc expression such as 1/25.4 or 2.54/100
+        #[arg(long, value_name = "EXPR", default_value = "1", value_parser = expression::positive)]
+        scale: f64,
+    },
+
+    /// Write a smaller .glb: weld vertices, strip unused data, quantize texture coordinates, optionally decimate
+    #[command(after_help = "Only TRIANGLES primitives whose attributes are POSITION, NORMAL, TANGENT and TEXCOORD_0 \
+are rewritten; skinned, morphed and other primitives are copied as they are. Positions and normals stay \
+32-bit floats (quantizing them needs KHR_mesh_quantization, which gltf-cli and the viewer cannot read). \
+Numbers may be written as calc expressions, e.g. --max-triangles \"10^4\".")]
+    Optimize {
+        /// Model file to read (.gltf or .glb)
+        #[arg(value_name = "INPUT")]
+        input: PathBuf,
+
+        /// File to write (.glb)
+        #[arg(value_name = "OUTPUT")]
+        output: PathBuf,
+
+        /// Keep duplicate vertices
+        #[arg(long)]
+        no_weld: bool,
+
+        /// Weld positions closer than this (0 welds exact duplicates only)
+        #[arg(long, value_name = "DISTANCE", default_value = "0", value_parser = expression::distance, conflicts_with = "no_weld")]
+        weld_tolerance: f32,
+
+        /// Keep unused accessors, materials, textures, images, samplers and buffer views
+        #[arg(long)]
+        no_strip: bool,
+
+        /// Keep 32-bit float texture coordinates
+        #[arg(long)]
+        no_quantize: bool,
+
+        /// Reduce the meshes to at most this many triangles in total (by vertex clustering)
+        #[arg(long, value_name = "N", value_parser = expression::count)]
+        max_triangles: Option<usize>,
+
+        /// Scale the whole scene by this factor (a calc expression, as in convert)
+        #[arg(long, value_name = "EXPR", default_value = "1", value_parser = expression::positive)]
+        scale: f64,
+    },
+
+    /// Render a PNG preview without a browser or GPU (software rasterizer, same lighting as the viewer)
+    #[command(after_help = "The whole default scene is framed from the chosen direction. Normal maps, occlusion \
+and KHR extensions are not drawn.")]
+    Render {
+        /// Model file (.gltf or .glb)
+        #[arg(value_name = "FILE")]
+        file: PathBuf,
+
+        /// PNG file to write
+        #[arg(short, long, value_name = "PNG")]
+        out: PathBuf,
+
+        /// Where the camera looks from
+        #[arg(long, value_enum, default_value_t = render::Camera::Orbit)]
+        camera: render::Camera,
+
+        /// Image size in pixels
+        #[arg(long, value_name = "WIDTHxHEIGHT", default_value = "512x512", value_parser = render::parse_size)]
+        size: (usize, usize),
+    },
+
+    /// Print the version, git commit, build date, target and features (attach this to bug reports)
+    Version {
+        /// Print JSON
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    Text,
+    Json,
+}
+
+// gltf-cli のコマンドの定義（my-cli completions と my-cli docs で使う）
+pub fn command() -> clap::Command {
+    Cli::command()
+}
+
+// version サブコマンドの内容（my-cli version にもまとめて出す）
+pub fn build_info() -> my_cli_build_info::BuildInfo {
+    my_cli_build_info::build_info!()
+}
+
+// gltf-cli の入口（args の最初は使い方に表示するコマンド名）
+pub fn main_from<I, T>(args: I)
+where
+    I: IntoIterator<Item = T>,
+    T: Into<std::ffi::OsString> + Clone,
+{
+    let cli = Cli::parse_from(args);
+    my_cli_log::init(&cli.log);
+    if let Err(e) = run(cli.command) {
+        exit_with_error(&e);
+    }
+}
+
+pub fn run(command: Command) -> Result<()> {
+    match command {
+        Command::Info { file, format } => {
+            let model = open(&file)?;
+            let summary = info::summarize(&file, &model);
+            match format {
+                Format::Text => out!("{}", info::render(&summary)),
+                Format::Json => outln!("{}", serde_json::to_string_pretty(&summary)?),
+            }
+        }
+        Command::Validate { file, format, deny_warnings } => {
+            let base = file.parent().unwrap_or(Path::new("."));
+            let issues = std::fs::read(&file)
+                .map_err(gltf::Error::Io)
+                .and_then(|data| validate::check(&data, base))
+                .with_context(|| format!("Failed to open {}", file.display()))?;
+            let report = validate::Report::new(&file, issues);
+            match format {
+                Format::Text => out!("{}", validate::render(&report)),
+                Format::Json => outln!("{}", serde_json::to_string_pretty(&report)?),
+            }
+            if !report.valid || (deny_warnings && report.warnings > 0) {
+                let message = format!("{} failed validation ({} error(s), {} warning(s))", file.display(), report.errors, report.warnings);
+                return Err(Failure::new(EXIT_INVALID, message).into());
+            }
+        }
+        Command::Convert { input, output, resources, scale } => {
+            let extension = output.extension().and_then(|extension| extension.to_str()).map(str::to_ascii_lowercase);
+            let target = match (extension.as_deref(), resources) {
+                (Some("glb"), convert::Resources::Embed) => convert::Target::Glb,
+                (Some("glb"), convert::Resources::Extract) => {
+                    return Err(Failure::new(EXIT_USAGE, "--resources extract only applies to .gltf output").into());
+                }
+                (Some("gltf"), resources) => convert::Target::Gltf(resources),
+                _ => {
+                    let message = format!("cannot tell the output format of {} (use a .glb or .gltf file name)", output.display());
+                    return Err(Failure::new(EXIT_USAGE, message).into());
+                }
+            };
+            let base = input.parent().unwrap_or(Path::new("."));
+            let stem = output.file_stem().unwrap_or_default().to_string_lossy();
+            let progress = Progress::spinner(format!("Converting {}", input.display()), my_cli_progress::shown(false));
+            let converted = std::fs::read(&input)
+                .map_err(|e| gltf::Error::Io(e).into())
+                .and_then(|data| convert::convert(&data, base, target, &stem, scale))
+                .with_context(|| format!("Failed to open {}", input.display()))?;
+            progress.finish();
+            let files = std::iter::once((output.clone(), converted.data))
+                .chain(converted.resources.into_iter().map(|(name, data)| (output.with_file_name(name), data)));
+            for (path, data) in files {
+                std::fs::write(&path, &data).with_context(|| format!("Failed to write {}", path.display()))?;
+                outln!("Wrote {} ({})", path.display(), info::byte_size(data.len()));
+            }
+        }
+        Command::Optimize { input, output, no_weld, weld_tolerance, no_strip, no_quantize, max_triangles, scale } => {
+            if !output.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("glb")) {
+                let message = format!("optimize writes GLB; use a .glb file name instead of {}", output.display());
+                return Err(Failure::new(EXIT_USAGE, message).into());
+            }
+            let options = optimize::Options {
+                weld: (!no_weld).then_some(weld_tolerance),
+                max_triangles,
+                quantize: !no_quantize,
+                strip: !no_strip,
+                scale,
+            };
+            let base = input.parent().unwrap_or(Path::new("."));
+            let progress = Progress::spinner(format!("Optimizing {}", input.display()), my_cli_progress::shown(false));
+            let data = std::fs::read(&input).map_err(gltf::Error::Io).with_context(|| format!("Failed to open {}", input.display()))?;
+            let (glb, stats) = optimize::optimize(&data, base, &options).with_context(|| format!("Failed to open {}", input.display()))?;
+            progress.set_message(format!("Writing {}", output.display()));
+            std::fs::write(&output, &glb).with_context(|| format!("Failed to write {}", output.display()))?;
+            progress.finish();
+            out!("{}", optimize::render(&output, &stats));
+        }
+        Command::Render { file, out, camera, size: (width, height) } => {
+            let progress = Progress::spinner(format!("Rendering {}", file.display()), my_cli_progress::shown(false));
+            let model = open(&file)?;
+            let image = render::render(&model, camera, width, height);
+            image::RgbaImage::from_raw(image.width as u32, image.height as u32, image.pixels)
+                .context("the rendered image has the wrong size")?
+                .save_with_format(&out, image::ImageFormat::Png)
+                .with_context(|| format!("Failed to write {}", out.display()))?;
+            progress.finish();
+            outln!("Wrote {} ({}x{})", out.display(), width, height);
+        }
+        Command::Version { json } => {
+            let info = build_info();
+            out!("{}", if json { info.json() + "\n" } else { info.text() });
+        }
+    }
+    Ok(())
+}
+
+// my-cli 共通の形式で表示し、種類ごとの終了コードで終了する
+// パイプの先が閉じられて書けなくなったときは何も言わずに正常終了する
+pub fn exit_with_error(error: &anyhow::Error) -> ! {
+    if my_cli_errors::is_broken_pipe(&**error) {
+        std::process::exit(my_cli_errors::EXIT_SUCCESS);
+    }
+    let color = my_cli_errors::stderr_color();
+    eprintln!("{}", my_cli_errors::render(&**error, None, |label| my_cli_errors::red(label, color)));
+    std::process::exit(exit_code(error));
+}
+
+fn exit_code(error: &anyhow::Error) -> i32 {
+    my_cli_errors::exit_code(&**error, |cause| {
+        cause
+            .downcast_ref::<my_cli_errors::Failure>()
+            .map(CliError::exit_code)
+            .or_else(|| cause.downcast_ref::<gltf::Error>().map(|_| EXIT_MODEL))
+    })
+}
+
+// 外部のバッファーと画像もここで読む
+fn open(path: &Path) -> Result<Model> {
+    tracing::debug!("opening {}", path.display());
+    Model::open(path).with_context(|| format!("Failed to open {}", path.display()))
+}
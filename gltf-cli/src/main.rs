@@ -0,0 +1,4 @@
+// 処理は lib.rs にある（my-cli gltf からも同じものを呼び出す）
+fn main() {
+    gltf_cli::main_from(std::env::args_os());
+}
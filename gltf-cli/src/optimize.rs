@@ -0,0 +1,493 @@
+// モデルの軽量化（gltf-cli optimize）
+//
+// 一度 convert と同じ方法で GLB（バッファー1つ）にしてから、次の順に書き換える
+//   weld      重なった頂点をまとめる
+//   decimate  --max-triangles があれば、メッシュ全体の三角形をその数以下にする
+//   quantize  0〜1 に収まる TEXCOORD_0 を 16 ビットの正規化整数にし、インデックスも小さい型にする
+//             （位置と法線の量子化には KHR_mesh_quantization が要り、gltf クレートもビューアーも読めないのでしない）
+//   strip     使われていないアクセサー・マテリアル・テクスチャー・画像・サンプラー・バッファービューを消す
+// 頂点データを書き直しても小さくならなければ（もともと無駄のないモデルなど）、元の頂点データのまま strip だけをし、
+// それでも小さくならなければ GLB にしただけのものを書き出す（--max-triangles で三角形を減らしたときは書き直したものを使う）
+// 頂点処理は gltf-core の optimize を使う。書き換えるのは TRIANGLES で、属性が POSITION、NORMAL、TANGENT、
+// TEXCOORD_0 だけのプリミティブに限る（スキンやモーフターゲットなどを持つものはそのまま残す）
+use std::collections::BTreeSet;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use gltf::mesh::Mode;
+use gltf_core::geometry::{self, Geometry};
+use gltf_core::optimize;
+use serde_json::{Value, json};
+
+use crate::{convert, info};
+
+// glTF の componentType と bufferView の target
+const FLOAT: u32 = 5126;
+const UNSIGNED_SHORT: u32 = 5123;
+const UNSIGNED_INT: u32 = 5125;
+const ARRAY_BUFFER: u32 = 34962;
+const ELEMENT_ARRAY_BUFFER: u32 = 34963;
+
+// 書き換えられる属性
+const ATTRIBUTES: [&str; 4] = ["POSITION", "NORMAL", "TANGENT", "TEXCOORD_0"];
+
+pub struct Options {
+    // 位置をまとめる距離（None ならまとめない）
+    pub weld: Option<f32>,
+    pub max_triangles: Option<usize>,
+    pub quantize: bool,
+    pub strip: bool,
+    // シーン全体の倍率（convert --scale と同じ）
+    pub scale: f64,
+}
+
+#[derive(Debug, Default)]
+pub struct Stats {
+    // GLB にしたときの大きさ（外部のバッファーと画像も含めて比べるため）
+    pub size: (usize, usize),
+    pub vertices: (usize, usize),
+    pub triangles: (usize, usize),
+    // 書き換えなかったプリミティブの数
+    pub skipped: usize,
+    // 種類ごとの消した数（accessors など）
+    pub removed: Vec<(&'static str, usize)>,
+    // 小さくならなかったので残したもの
+    pub kept: Kept,
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub enum Kept {
+    #[default]
+    Nothing,
+    // 頂点データを書き直さなかった
+    Vertices,
+    // GLB にしただけで何も書き換えなかった
+    Input,
+}
+
+// data は入力ファイルの中身、base は外部のバッファーと画像を探すディレクトリ。GLB を返す
+pub fn optimize(data: &[u8], base: &Path, options: &Options) -> Result<(Vec<u8>, Stats)> {
+    let glb = convert::convert(data, base, convert::Target::Glb, "", options.scale)?.data;
+    let (rewritten, stats) = rewrite(&glb, options, true)?;
+    let decimated = stats.triangles.1 < stats.triangles.0;
+    if rewritten.len() < glb.len() || decimated {
+        if rewritten.len() > glb.len() {
+            tracing::warn!("the output is larger than the input as GLB ({} > {} bytes)", rewritten.len(), glb.len());
+        }
+        return Ok((rewritten, stats));
+    }
+    tracing::debug!("rewriting the vertex data did not make the model smaller ({} >= {} bytes)", rewritten.len(), glb.len());
+    let (stripped, mut stats) = rewrite(&glb, options, false)?;
+    if stripped.len() < glb.len() {
+        stats.kept = Kept::Vertices;
+        return Ok((stripped, stats));
+    }
+    stats.removed.clear();
+    stats.size.1 = glb.len();
+    stats.kept = Kept::Input;
+    Ok((glb, stats))
+}
+
+// GLB の頂点データ（rewrite_vertices が false なら書き直さない）と使われていないものを書き換える
+fn rewrite(glb: &[u8], options: &Options, rewrite_vertices: bool) -> Result<(Vec<u8>, Stats)> {
+    let gltf::Gltf { document, blob } = gltf::Gltf::from_slice(glb)?;
+    let mut root: Value = serde_json::from_slice(&gltf::Glb::from_slice(glb)?.json)?;
+    let buffers = gltf::import_buffers(&document, None, blob)?;
+    let mut bin = buffers.first().map(|buffer| buffer.0.clone()).unwrap_or_default();
+    let mut stats = Stats { size: (glb.len(), 0), ..Stats::default() };
+
+    // 書き換えるプリミティブを読む
+    let mut primitives = Vec::new();
+    for mesh in document.meshes() {
+        for primitive in mesh.primitives() {
+            let count = geometry::triangle_count(primitive.mode(), geometry::element_count(&primitive));
+            let vertices = primitive.get(&gltf::Semantic::Positions).map_or(0, |accessor| accessor.count());
+            stats.vertices.0 += vertices;
+            stats.triangles.0 += count;
+            let json = &root["meshes"][mesh.index()]["primitives"][primitive.index()];
+            let rewritable = primitive.mode() == Mode::Triangles
+                && json.get("targets").is_none()
+                && json["attributes"].as_object().is_some_and(|attributes| attributes.keys().all(|name| ATTRIBUTES.contains(&name.as_str())));
+            match geometry::read_attributes(&primitive, &buffers).filter(|_| rewritable) {
+                Some(geometry) if rewrite_vertices => primitives.push((mesh.index(), primitive.index(), geometry)),
+                geometry => {
+                    stats.skipped += usize::from(geometry.is_none());
+                    stats.vertices.1 += vertices;
+                    stats.triangles.1 += count;
+                }
+            }
+        }
+    }
+
+    if let Some(tolerance) = options.weld {
+        for (_, _, geometry) in &mut primitives {
+            *geometry = optimize::weld(geometry, tolerance);
+        }
+    }
+    // 書き換えないプリミティブの分を除いた予算を、三角形の数に比例して分ける
+    if let Some(max_triangles) = options.max_triangles {
+        let total: usize = primitives.iter().map(|(_, _, geometry)| optimize::triangle_count(geometry)).sum();
+        let budget = max_triangles.saturating_sub(stats.triangles.1);
+        if total > budget {
+            for (_, _, geometry) in &mut primitives {
+                let share = optimize::triangle_count(geometry) * budget / total;
+                *geometry = optimize::decimate(geometry, share);
+            }
+        }
+    }
+
+    for (mesh, primitive, geometry) in &primitives {
+        stats.vertices.1 += geometry.positions.len();
+        stats.triangles.1 += optimize::triangle_count(geometry);
+        let attributes = write_geometry(&mut root, &mut bin, geometry, options.quantize)?;
+        let indices = push_indices(&mut root, &mut bin, &geometry.indices, geometry.positions.len())?;
+        let json = root["meshes"][*mesh]["primitives"][*primitive].as_object_mut().context("primitives must be objects")?;
+        json.insert("attributes".to_string(), attributes);
+        json.insert("indices".to_string(), json!(indices));
+    }
+
+    if let Some(max_triangles) = options.max_triangles.filter(|&max| rewrite_vertices && stats.triangles.1 > max) {
+        tracing::warn!("{} triangles remain (budget {}): skipped primitives and small meshes are not reduced further", stats.triangles.1, max_triangles);
+    }
+
+    if options.strip {
+        // 参照する側から順に消す（マテリアルを消すとテクスチャーが、テクスチャーを消すと画像が使われなくなる）
+        for kind in [Kind::Accessor, Kind::Material, Kind::Texture, Kind::Image, Kind::Sampler, Kind::BufferView] {
+            stats.removed.push((kind.array(), strip(&mut root, kind)));
+        }
+        bin = repack(&mut root, &bin);
+    }
+    if let Some(buffer) = root.get_mut("buffers").and_then(|buffers| buffers.get_mut(0)) {
+        buffer["byteLength"] = json!(bin.len());
+    }
+
+    let glb = gltf::Glb {
+        // length は to_vec が計算し直す
+        header: gltf::binary::Header { magic: *b"glTF", version: 2, length: 0 },
+        json: serde_json::to_vec(&root)?.into(),
+        bin: (!bin.is_empty()).then(|| bin.into()),
+    };
+    let glb = glb.to_vec()?;
+    stats.size.1 = glb.len();
+    Ok((glb, stats))
+}
+
+// 結果の表示
+pub fn render(output: &Path, stats: &Stats) -> String {
+    let mut text = format!("Wrote {} ({}, {} as GLB before)\n", output.display(), info::byte_size(stats.size.1), info::byte_size(stats.size.0));
+    text += &format!("Vertices: {} -> {}\n", stats.vertices.0, stats.vertices.1);
+    text += &format!("Triangles: {} -> {}\n", stats.triangles.0, stats.triangles.1);
+    let removed: Vec<String> = stats.removed.iter().filter(|(_, count)| *count > 0).map(|(kind, count)| format!("{} {}", kind, count)).collect();
+    if !removed.is_empty() {
+        text += &format!("Removed: {}\n", removed.join(", "));
+    }
+    match stats.kept {
+        Kept::Nothing => {}
+        Kept::Vertices => text += "Kept the original vertex data: rewriting it did not make the file smaller\n",
+        Kept::Input => text += "Kept the model as it is: optimizing did not make the file smaller\n",
+    }
+    if stats.skipped > 0 {
+        text += &format!("Skipped: {} primitive(s) with other attributes, morph targets or a non-TRIANGLES mode\n", stats.skipped);
+    }
+    text
+}
+
+// 頂点属性を書き足し、primitive の attributes にする JSON を返す
+fn write_geometry(root: &mut Value, bin: &mut Vec<u8>, geometry: &Geometry, quantize: bool) -> Result<Value> {
+    let (min, max) = optimize::bounds(&geometry.positions);
+    let mut attributes = serde_json::Map::new();
+    let position = push_accessor(root, bin, floats(&geometry.positions), geometry.positions.len(), "VEC3", FLOAT, ARRAY_BUFFER)?;
+    // POSITION には min と max が必須
+    root["accessors"][position]["min"] = json!(min);
+    root["accessors"][position]["max"] = json!(max);
+    attributes.insert("POSITION".to_string(), json!(position));
+    if !geometry.normals.is_empty() {
+        let normal = push_accessor(root, bin, floats(&geometry.normals), geometry.normals.len(), "VEC3", FLOAT, ARRAY_BUFFER)?;
+        attributes.insert("NORMAL".to_string(), json!(normal));
+    }
+    if !geometry.tangents.is_empty() {
+        let tangent = push_accessor(root, bin, floats(&geometry.tangents), geometry.tangents.len(), "VEC4", FLOAT, ARRAY_BUFFER)?;
+        attributes.insert("TANGENT".to_string(), json!(tangent));
+    }
+    if !geometry.tex_coords.is_empty() {
+        let in_range = geometry.tex_coords.iter().flatten().all(|v| (0.0..=1.0).contains(v));
+        let tex_coord = if quantize && in_range {
+            let bytes = geometry.tex_coords.iter().flatten().flat_map(|v| ((v * 65535.0).round() as u16).to_le_bytes()).collect();
+            let index = push_accessor(root, bin, bytes, geometry.tex_coords.len(), "VEC2", UNSIGNED_SHORT, ARRAY_BUFFER)?;
+            root["accessors"][index]["normalized"] = json!(true);
+            index
+        } else {
+            push_accessor(root, bin, floats(&geometry.tex_coords), geometry.tex_coords.len(), "VEC2", FLOAT, ARRAY_BUFFER)?
+        };
+        attributes.insert("TEXCOORD_0".to_string(), json!(tex_coord));
+    }
+    Ok(Value::Object(attributes))
+}
+
+// 頂点が 65535 個より少なければ 16 ビット、それ以外は 32 ビット（65535 は primitive restart の値なので使わない）
+fn push_indices(root: &mut Value, bin: &mut Vec<u8>, indices: &[u32], vertices: usize) -> Result<usize> {
+    let (bytes, component_type) = if vertices < u16::MAX as usize {
+        (indices.iter().flat_map(|&i| (i as u16).to_le_bytes()).collect(), UNSIGNED_SHORT)
+    } else {
+        (indices.iter().flat_map(|&i| i.to_le_bytes()).collect(), UNSIGNED_INT)
+    };
+    push_accessor(root, bin, bytes, indices.len(), "SCALAR", component_type, ELEMENT_ARRAY_BUFFER)
+}
+
+// バッファービューとアクセサーを1つずつ書き足し、アクセサーの番号を返す
+fn push_accessor(root: &mut Value, bin: &mut Vec<u8>, bytes: Vec<u8>, count: usize, kind: &str, component_type: u32, target: u32) -> Result<usize> {
+    let offset = convert::append(bin, &bytes);
+    let view = push(root, "bufferViews", json!({ "buffer": 0, "byteOffset": offset, "byteLength": bytes.len(), "target": target }))?;
+    push(root, "accessors", json!({ "bufferView": view, "componentType": component_type, "count": count, "type": kind }))
+}
+
+fn push(root: &mut Value, array: &str, value: Value) -> Result<usize> {
+    let root = root.as_object_mut().context("the glTF JSON must be an object")?;
+    if root.get("buffers").is_none() {
+        root.insert("buffers".to_string(), json!([{ "byteLength": 0 }]));
+    }
+    let items = root.entry(array).or_insert_with(|| json!([])).as_array_mut().with_context(|| format!("{} must be an array", array))?;
+    items.push(value);
+    Ok(items.len() - 1)
+}
+
+fn floats<const N: usize>(values: &[[f32; N]]) -> Vec<u8> {
+    values.iter().flatten().flat_map(|v| v.to_le_bytes()).collect()
+}
+
+// 使われていないものを消す対象
+#[derive(Clone, Copy)]
+enum Kind {
+    Accessor,
+    Material,
+    Texture,
+    Image,
+    Sampler,
+    BufferView,
+}
+
+impl Kind {
+    fn array(self) -> &'static str {
+        match self {
+            Kind::Accessor => "accessors",
+            Kind::Material => "materials",
+            Kind::Texture => "textures",
+            Kind::Image => "images",
+            Kind::Sampler => "samplers",
+            Kind::BufferView => "bufferViews",
+        }
+    }
+}
+
+// kind を参照している全ての番号を f に渡す
+fn visit(root: &mut Value, kind: Kind, f: &mut dyn FnMut(&mut Value)) {
+    fn items<'a>(value: &'a mut Value, key: &str) -> impl Iterator<Item = &'a mut Value> {
+        value.get_mut(key).and_then(Value::as_array_mut).into_iter().flatten()
+    }
+    fn values(value: Option<&mut Value>) -> impl Iterator<Item = &mut Value> {
+        value.and_then(Value::as_object_mut).into_iter().flat_map(|object| object.values_mut())
+    }
+    // key の値として、どの深さにあっても参照しているもの
+    fn keyed(value: &mut Value, key: &str, f: &mut dyn FnMut(&mut Value)) {
+        match value {
+            Value::Object(object) => {
+                for (name, value) in object.iter_mut() {
+                    if name == key && value.is_u64() {
+                        f(value);
+                    } else {
+                        keyed(value, key, f);
+                    }
+                }
+            }
+            Value::Array(values) => values.iter_mut().for_each(|value| keyed(value, key, f)),
+            _ => {}
+        }
+    }
+
+    match kind {
+        Kind::Accessor => {
+            for primitive in items(root, "meshes").flat_map(|mesh| items(mesh, "primitives")) {
+                values(primitive.get_mut("attributes")).for_each(&mut *f);
+                primitive.get_mut("indices").into_iter().for_each(&mut *f);
+                items(primitive, "targets").for_each(|target| values(Some(target)).for_each(&mut *f));
+            }
+            for skin in items(root, "skins") {
+                skin.get_mut("inverseBindMatrices").into_iter().for_each(&mut *f);
+            }
+            for sampler in items(root, "animations").flat_map(|animation| items(animation, "samplers")) {
+                sampler.get_mut("input").into_iter().for_each(&mut *f);
+                sampler.get_mut("output").into_iter().for_each(&mut *f);
+            }
+            for node in items(root, "nodes") {
+                values(node.pointer_mut("/extensions/EXT_mesh_gpu_instancing/attributes")).for_each(&mut *f);
+            }
+        }
+        Kind::Material => {
+            for primitive in items(root, "meshes").flat_map(|mesh| items(mesh, "primitives")) {
+                primitive.get_mut("material").into_iter().for_each(&mut *f);
+                if let Some(variants) = primitive.pointer_mut("/extensions/KHR_materials_variants") {
+                    items(variants, "mappings").for_each(|mapping| mapping.get_mut("material").into_iter().for_each(&mut *f));
+                }
+            }
+        }
+        // マテリアルのテクスチャー情報（baseColorTexture、拡張の clearcoatTexture など）の index
+        Kind::Texture => {
+            fn texture_infos(value: &mut Value, f: &mut dyn FnMut(&mut Value)) {
+                match value {
+                    Value::Object(object) => {
+                        for (name, value) in object.iter_mut() {
+                            match value.get_mut("index") {
+                                Some(index) if name.ends_with("Texture") && index.is_u64() => f(index),
+                                _ => texture_infos(value, f),
+                            }
+                        }
+                    }
+                    Value::Array(values) => values.iter_mut().for_each(|value| texture_infos(value, f)),
+                    _ => {}
+                }
+            }
+            items(root, "materials").for_each(|material| texture_infos(material, f));
+        }
+        // KHR_texture_basisu などの拡張の source も含む
+        Kind::Image => items(root, "textures").for_each(|texture| keyed(texture, "source", f)),
+        Kind::Sampler => items(root, "textures").for_each(|texture| texture.get_mut("sampler").into_iter().for_each(&mut *f)),
+        Kind::BufferView => {
+            for (name, value) in root.as_object_mut().into_iter().flatten() {
+                if name != "bufferViews" {
+                    keyed(value, "bufferView", f);
+                }
+            }
+        }
+    }
+}
+
+// 使われていないものを消して番号を付け替え、消した数を返す
+fn strip(root: &mut Value, kind: Kind) -> usize {
+    let mut used = BTreeSet::new();
+    visit(root, kind, &mut |value| {
+        used.extend(value.as_u64().map(|index| index as usize));
+    });
+    let Some(items) = root.get_mut(kind.array()).and_then(Value::as_array_mut) else {
+        return 0;
+    };
+    let mut renumbered = Vec::new();
+    let mut kept = Vec::new();
+    for (index, item) in std::mem::take(items).into_iter().enumerate() {
+        if used.contains(&index) {
+            renumbered.push(Some(kept.len()));
+            kept.push(item);
+        } else {
+            renumbered.push(None);
+        }
+    }
+    let removed = renumbered.len() - kept.len();
+    if kept.is_empty() {
+        root.as_object_mut().map(|root| root.remove(kind.array()));
+    } else {
+        *items = kept;
+    }
+    visit(root, kind, &mut |value| {
+        if let Some(&Some(index)) = value.as_u64().and_then(|index| renumbered.get(index as usize)) {
+            *value = json!(index);
+        }
+    });
+    removed
+}
+
+// 残ったバッファービューの中身だけでバッファーを作り直す
+fn repack(root: &mut Value, bin: &[u8]) -> Vec<u8> {
+    let mut packed = Vec::new();
+    for view in root.get_mut("bufferViews").and_then(Value::as_array_mut).into_iter().flatten() {
+        let offset = view["byteOffset"].as_u64().unwrap_or(0) as usize;
+        let length = view["byteLength"].as_u64().unwrap_or(0) as usize;
+        view["byteOffset"] = json!(convert::append(&mut packed, &bin[offset..offset + length]));
+    }
+    if packed.is_empty() {
+        root.as_object_mut().map(|root| root.remove("buffers"));
+    }
+    packed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures;
+
+    // 同じ位置の頂点を別々に持つ四角形2枚（1枚目だけがマテリアルを使い、2枚目のマテリアルは使われない）
+    const QUADS: &str = r#"{
+      "asset": {"version": "2.0"},
+      "meshes": [{"primitives": [{"attributes": {"POSITION": 0, "TEXCOORD_0": 1}, "material": 1}]}],
+      "materials": [{"name": "unused"}, {"name": "used"}],
+      "accessors": [
+        {"bufferView": 0, "componentType": 5126, "count": 6, "type": "VEC3", "min": [0, 0, 0], "max": [1, 1, 0]},
+        {"bufferView": 1, "componentType": 5126, "count": 6, "type": "VEC2"},
+        {"bufferView": 2, "componentType": 5126, "count": 6, "type": "VEC2"}
+      ],
+      "bufferViews": [
+        {"buffer": 0, "byteOffset": 0, "byteLength": 72},
+        {"buffer": 0, "byteOffset": 72, "byteLength": 48},
+        {"buffer": 0, "byteOffset": 72, "byteLength": 48}
+      ],
+      "buffers": [{"byteLength": 120, "uri": "data:application/octet-stream;base64,AAAAAAAAAAAAAAAAAACAPwAAAAAAAAAAAACAPwAAgD8AAAAAAAAAAAAAAAAAAAAAAACAPwAAgD8AAAAAAAAAAAAAgD8AAAAAAAAAAAAAAAAAAIA/AAAAAAAAgD8AAIA/AAAAAAAAAAAAAIA/AACAPwAAAAAAAIA/"}]
+    }"#;
+
+    #[test]
+    fn test_optimize() {
+        let options = Options { weld: Some(0.0), max_triangles: None, quantize: true, strip: true, scale: 1.0 };
+        let (glb, stats) = optimize(QUADS.as_bytes(), Path::new("."), &options).unwrap();
+        assert_eq!(stats.vertices, (6, 4));
+        assert_eq!(stats.triangles, (2, 2));
+        assert_eq!(stats.skipped, 0);
+        assert!(stats.removed.contains(&("accessors", 3)) && stats.removed.contains(&("materials", 1)));
+
+        let model = gltf_core::Model::from_slice(&glb).unwrap();
+        let primitive = model.document.meshes().next().unwrap().primitives().next().unwrap();
+        assert_eq!(primitive.material().name(), Some("used"));
+        let tex_coord = primitive.get(&gltf::Semantic::TexCoords(0)).unwrap();
+        assert_eq!((tex_coord.data_type(), tex_coord.normalized()), (gltf::accessor::DataType::U16, true));
+        assert_eq!(model.document.accessors().count(), 3);
+        assert_eq!(model.document.views().count(), 3);
+        // 頂点4つ（12 x 4 と 4 x 4）とインデックス6つ（2 x 6、4バイトに揃える）
+        assert_eq!(model.buffers[0].len(), 48 + 16 + 12);
+
+        let geometry = geometry::read_attributes(&primitive, &model.buffers).unwrap();
+        assert_eq!(geometry.positions.len(), 4);
+        assert_eq!(geometry.tex_coords[1], [1.0, 0.0]);
+    }
+
+    #[test]
+    fn test_compact_model_is_not_larger() {
+        // インデックス付きで重なった頂点もない三角形は、書き直してもバッファーが小さくならない
+        let options = Options { weld: Some(0.0), max_triangles: None, quantize: true, strip: true, scale: 1.0 };
+        let (glb, stats) = optimize(test_fixtures::TRIANGLE.as_bytes(), Path::new("."), &options).unwrap();
+        assert!(stats.size.1 <= stats.size.0, "{:?}", stats.size);
+        assert_eq!(glb.len(), stats.size.1);
+        assert_ne!(stats.kept, Kept::Nothing);
+        assert_eq!((stats.vertices, stats.triangles), ((3, 3), (1, 1)));
+        assert!(render(Path::new("out.glb"), &stats).contains("did not make the file smaller"));
+        let model = gltf_core::Model::from_slice(&glb).unwrap();
+        assert_eq!(model.document.accessors().count(), 2);
+
+        // 使われていないものがあれば、頂点データはそのままで消す
+        let unused = test_fixtures::triangle_with(|root| root["materials"] = json!([{ "name": "unused" }]));
+        let (glb, stats) = optimize(unused.as_bytes(), Path::new("."), &options).unwrap();
+        assert!(stats.size.1 < stats.size.0, "{:?}", stats.size);
+        assert_eq!((&stats.kept, stats.removed.contains(&("materials", 1))), (&Kept::Vertices, true));
+        assert_eq!(gltf_core::Model::from_slice(&glb).unwrap().document.materials().count(), 0);
+    }
+
+    #[test]
+    fn test_decimate_and_skip() {
+        let options = Options { weld: None, max_triangles: Some(1), quantize: false, strip: false, scale: 1.0 };
+        let (glb, stats) = optimize(QUADS.as_bytes(), Path::new("."), &options).unwrap();
+        // 四角形2枚の頂点は寄せられないので三角形は減らない。消さずに書き足すだけでは大きくなるので元のまま
+        assert_eq!((stats.triangles, &stats.kept), ((2, 2), &Kept::Input));
+        assert!(stats.removed.is_empty());
+        assert_eq!(gltf_core::Model::from_slice(&glb).unwrap().document.accessors().count(), 3);
+
+        // TEXCOORD_1 を持つプリミティブは書き換えない
+        let other = QUADS.replace("\"TEXCOORD_0\": 1", "\"TEXCOORD_1\": 1");
+        let (_, stats) = optimize(other.as_bytes(), Path::new("."), &options).unwrap();
+        assert_eq!((stats.skipped, stats.vertices, stats.triangles), (1, (6, 6), (2, 2)));
+    }
+}
@@ -0,0 +1,484 @@
+// ブラウザーを使わない静止画の描画（gltf-cli render）
+//
+// CPU だけで三角形を塗るソフトウェアラスタライザー。GPU もディスプレイもない CI で動く
+// 見た目はビューアー（gltf-viewer の shaders.rs）に合わせる
+//   光源と背景の色、視野角 45 度、metallic-roughness の式、sRGB への変換は同じ
+//   法線マップとオクルージョンは使わない。BLEND は奥から順に重ねず、描く順に重ねる
+// ビューアーと違い、ノードの変換を反映してシーン全体を描く
+// 縦横それぞれ 2 倍の大きさで描いて縮小し、輪郭のギザギザを減らす
+use clap::ValueEnum;
+use gltf::material::AlphaMode;
+use gltf::texture::WrappingMode;
+use gltf_core::{geometry, Model};
+use nalgebra_glm as glm;
+
+// 縦横それぞれ何倍で描いてから縮小するか
+const SUPERSAMPLE: usize = 2;
+// ビューアーと同じ背景色、光の向き、視野角
+const BACKGROUND: [f32; 3] = [0.1, 0.1, 0.1];
+const LIGHT_DIRECTION: [f32; 3] = [-0.5, -1.0, -0.3];
+const FIELD_OF_VIEW: f32 = 45.0;
+
+// モデルを見る向き（どれもモデル全体が収まる距離から見る）
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Camera {
+    /// Diagonally from the front, above and to the right (the viewer's initial camera)
+    Orbit,
+    /// From +Z
+    Front,
+    /// From +X
+    Side,
+    /// From +Y, looking down
+    Top,
+}
+
+impl Camera {
+    // 注視点からカメラへの向きと、画面の上になる向き
+    fn direction(self) -> (glm::Vec3, glm::Vec3) {
+        match self {
+            Camera::Orbit => (glm::normalize(&glm::vec3(3.0, 3.0, 5.0)), glm::Vec3::y()),
+            Camera::Front => (glm::Vec3::z(), glm::Vec3::y()),
+            Camera::Side => (glm::Vec3::x(), glm::Vec3::y()),
+            Camera::Top => (glm::Vec3::y(), -glm::Vec3::z()),
+        }
+    }
+}
+
+// 描画する三角形（ワールド座標）
+struct Triangle {
+    positions: [glm::Vec3; 3],
+    normals: [glm::Vec3; 3],
+    // UV セット（TEXCOORD_0、TEXCOORD_1）ごとの3頂点の UV
+    tex_coords: [[glm::Vec2; 3]; 2],
+    material: Option<usize>,
+}
+
+// RGBA8 にしたテクスチャー
+struct Texture {
+    pixels: Vec<u8>,
+    width: usize,
+    height: usize,
+    wrap: [WrappingMode; 2],
+}
+
+// テクスチャーの番号と、使う UV セット（texCoord）
+type TextureRef = (usize, u32);
+
+struct Material {
+    base_color: glm::Vec4,
+    base_color_texture: Option<TextureRef>,
+    metallic: f32,
+    roughness: f32,
+    metallic_roughness_texture: Option<TextureRef>,
+    emissive: glm::Vec3,
+    emissive_texture: Option<TextureRef>,
+    alpha_mode: AlphaMode,
+    alpha_cutoff: f32,
+    double_sided: bool,
+}
+
+impl Default for Material {
+    // glTF でマテリアルがないときの既定値
+    fn default() -> Self {
+        Material {
+            base_color: glm::vec4(1.0, 1.0, 1.0, 1.0),
+            base_color_texture: None,
+            metallic: 1.0,
+            roughness: 1.0,
+            metallic_roughness_texture: None,
+            emissive: glm::Vec3::zeros(),
+            emissive_texture: None,
+            alpha_mode: AlphaMode::Opaque,
+            alpha_cutoff: 0.5,
+            double_sided: false,
+        }
+    }
+}
+
+// 画像（RGBA8、sRGB）
+pub struct Image {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<u8>,
+}
+
+// 描画するものがなければ背景だけの画像になる
+pub fn render(model: &Model, camera: Camera, width: usize, height: usize) -> Image {
+    let triangles = triangles(model);
+    let textures: Vec<Option<Texture>> = model.document.textures().map(|texture| load_texture(model, &texture)).collect();
+    let materials: Vec<Material> = model.document.materials().map(|material| load_material(&material)).collect();
+    let default_material = Material::default();
+
+    // モデル全体を囲む球が画面に収まる距離から見る
+    let (center, radius) = bounding_sphere(&triangles);
+    let (direction, up) = camera.direction();
+    let aspect = width as f32 / height as f32;
+    let fov_y = FIELD_OF_VIEW.to_radians();
+    let fov_x = 2.0 * ((fov_y / 2.0).tan() * aspect).atan();
+    let distance = radius / (fov_y.min(fov_x) / 2.0).sin() * 1.05;
+    let eye = center + direction * distance;
+    let near = ((distance - radius) * 0.5).max(distance * 0.001);
+    let view_projection = glm::perspective(aspect, fov_y, near, distance + radius * 2.0) * glm::look_at(&eye, &center, &up);
+
+    let mut target = Target::new(width * SUPERSAMPLE, height * SUPERSAMPLE);
+    let shading = Shading { textures: &textures, eye, light: -glm::normalize(&glm::make_vec3(&LIGHT_DIRECTION)) };
+    // 重ねる（BLEND）ものは不透明なものの後に描く
+    let (blended, opaque): (Vec<&Triangle>, Vec<&Triangle>) = triangles.iter().partition(|triangle| {
+        let material = triangle.material.and_then(|index| materials.get(index)).unwrap_or(&default_material);
+        material.alpha_mode == AlphaMode::Blend
+    });
+    for triangle in opaque.into_iter().chain(blended) {
+        let material = triangle.material.and_then(|index| materials.get(index)).unwrap_or(&default_material);
+        target.draw(triangle, material, &view_projection, &shading);
+    }
+    target.resolve(SUPERSAMPLE)
+}
+
+// 既定のシーン（なければ最初のシーン）のノードをたどり、三角形をワールド座標にする
+fn triangles(model: &Model) -> Vec<Triangle> {
+    let mut triangles = Vec::new();
+    let scene = model.document.default_scene().or_else(|| model.document.scenes().next());
+    let mut stack: Vec<(gltf::Node, glm::Mat4)> = match scene {
+        Some(scene) => scene.nodes().map(|node| (node, glm::Mat4::identity())).collect(),
+        None => Vec::new(),
+    };
+    while let Some((node, parent)) = stack.pop() {
+        let transform = parent * glm::Mat4::from(node.transform().matrix());
+        stack.extend(node.children().map(|child| (child, transform)));
+        let Some(mesh) = node.mesh() else { continue };
+        // 法線は逆転置行列で変換する（拡大縮小が軸ごとに違っても面に垂直なまま）
+        let normal_matrix = glm::mat4_to_mat3(&glm::transpose(&glm::inverse(&transform)));
+        for primitive in mesh.primitives() {
+            let Some(geometry) = geometry::read(&primitive, &model.buffers) else { continue };
+            let position = |i: usize| glm::vec4_to_vec3(&(transform * glm::make_vec3(&geometry.positions[i]).push(1.0)));
+            let normal = |i: usize| geometry.normals.get(i).map(|n| glm::normalize(&(normal_matrix * glm::make_vec3(n))));
+            let tex_coord = |set: u32, i: usize| geometry.uv_set(set).get(i).map_or(glm::Vec2::zeros(), |uv| glm::make_vec2(uv));
+            let indices = geometry::triangulate(primitive.mode(), &geometry.indices);
+            for corners in indices.chunks_exact(3) {
+                let corners = [corners[0] as usize, corners[1] as usize, corners[2] as usize];
+                if corners.iter().any(|&i| i >= geometry.positions.len()) {
+                    continue;
+                }
+                let positions = corners.map(position);
+                // 法線がなければ面の向きを使う
+                let face = glm::normalize(&glm::cross(&(positions[1] - positions[0]), &(positions[2] - positions[0])));
+                triangles.push(Triangle {
+                    positions,
+                    normals: corners.map(|i| normal(i).unwrap_or(face)),
+                    tex_coords: [0, 1].map(|set| corners.map(|i| tex_coord(set, i))),
+                    material: primitive.material().index(),
+                });
+            }
+        }
+    }
+    triangles
+}
+
+fn bounding_sphere(triangles: &[Triangle]) -> (glm::Vec3, f32) {
+    let points = triangles.iter().flat_map(|triangle| triangle.positions);
+    let (min, max) = points.fold((glm::Vec3::repeat(f32::MAX), glm::Vec3::repeat(f32::MIN)), |(min, max), p| {
+        (glm::min2(&min, &p), glm::max2(&max, &p))
+    });
+    if triangles.is_empty() {
+        return (glm::Vec3::zeros(), 1.0);
+    }
+    let center = (min + max) / 2.0;
+    let radius = triangles
+        .iter()
+        .flat_map(|triangle| triangle.positions)
+        .map(|p| glm::distance(&p, &center))
+        .fold(0.0, f32::max);
+    (center, radius.max(1e-6))
+}
+
+fn load_material(material: &gltf::Material) -> Material {
+    let pbr = material.pbr_metallic_roughness();
+    Material {
+        base_color: glm::make_vec4(&pbr.base_color_factor()),
+        base_color_texture: pbr.base_color_texture().map(|info| (info.texture().index(), info.tex_coord())),
+        metallic: pbr.metallic_factor(),
+        roughness: pbr.roughness_factor(),
+        metallic_roughness_texture: pbr.metallic_roughness_texture().map(|info| (info.texture().index(), info.tex_coord())),
+        emissive: glm::make_vec3(&material.emissive_factor()),
+        emissive_texture: material.emissive_texture().map(|info| (info.texture().index(), info.tex_coord())),
+        alpha_mode: material.alpha_mode(),
+        alpha_cutoff: material.alpha_cutoff().unwrap_or(0.5),
+        double_sided: material.double_sided(),
+    }
+}
+
+// 形式を RGBA8 にできない画像はテクスチャーなしで描く
+fn load_texture(model: &Model, texture: &gltf::Texture) -> Option<Texture> {
+    let image = model.images.get(texture.source().index())?;
+    let pixels = gltf_core::texture::to_rgba8(image)?;
+    let sampler = texture.sampler();
+    Some(Texture { pixels, width: image.width as usize, height: image.height as usize, wrap: [sampler.wrap_s(), sampler.wrap_t()] })
+}
+
+impl Texture {
+    // バイリニアで読む（0〜1 の RGBA）
+    fn sample(&self, uv: &glm::Vec2) -> glm::Vec4 {
+        let x = uv.x * self.width as f32 - 0.5;
+        let y = uv.y * self.height as f32 - 0.5;
+        let (x0, y0) = (x.floor(), y.floor());
+        let (fx, fy) = (x - x0, y - y0);
+        let texel = |x: f32, y: f32| {
+            let x = wrap(x as i64, self.width, self.wrap[0]);
+            let y = wrap(y as i64, self.height, self.wrap[1]);
+            let offset = (y * self.width + x) * 4;
+            glm::make_vec4(&self.pixels[offset..offset + 4].iter().map(|&c| c as f32 / 255.0).collect::<Vec<_>>())
+        };
+        let top = glm::lerp(&texel(x0, y0), &texel(x0 + 1.0, y0), fx);
+        let bottom = glm::lerp(&texel(x0, y0 + 1.0), &texel(x0 + 1.0, y0 + 1.0), fx);
+        glm::lerp(&top, &bottom, fy)
+    }
+}
+
+fn wrap(coordinate: i64, size: usize, mode: WrappingMode) -> usize {
+    let size = size as i64;
+    let wrapped = match mode {
+        WrappingMode::ClampToEdge => coordinate.clamp(0, size - 1),
+        WrappingMode::Repeat => coordinate.rem_euclid(size),
+        WrappingMode::MirroredRepeat => {
+            let period = coordinate.rem_euclid(size * 2);
+            if period < size { period } else { size * 2 - 1 - period }
+        }
+    };
+    wrapped as usize
+}
+
+// 画素の色を決めるのに要るもの
+struct Shading<'a> {
+    textures: &'a [Option<Texture>],
+    eye: glm::Vec3,
+    // 面から光源への向き
+    light: glm::Vec3,
+}
+
+impl Shading<'_> {
+    // テクスチャーが指す UV セットで読む（読まない TEXCOORD_2 以降は TEXCOORD_0 で代える）
+    fn sample(&self, texture: Option<TextureRef>, uv: &[glm::Vec2; 2]) -> Option<glm::Vec4> {
+        let (index, set) = texture?;
+        let texture = self.textures.get(index)?.as_ref()?;
+        Some(texture.sample(uv.get(set as usize).unwrap_or(&uv[0])))
+    }
+
+    // ビューアーの FRAGMENT_SHADER と同じ式（リニアの RGB と不透明度を返す）
+    fn shade(&self, material: &Material, position: &glm::Vec3, normal: &glm::Vec3, uv: &[glm::Vec2; 2]) -> (glm::Vec3, f32) {
+        let mut base_color = material.base_color;
+        if let Some(texel) = self.sample(material.base_color_texture, uv) {
+            // ベースカラーのテクスチャーは sRGB
+            base_color = base_color.component_mul(&glm::vec4(to_linear(texel.x), to_linear(texel.y), to_linear(texel.z), texel.w));
+        }
+        let (mut metallic, mut roughness) = (material.metallic, material.roughness);
+        if let Some(texel) = self.sample(material.metallic_roughness_texture, uv) {
+            roughness *= texel.y;
+            metallic *= texel.z;
+        }
+        let roughness = roughness.clamp(0.04, 1.0);
+
+        let n = glm::normalize(normal);
+        let v = glm::normalize(&(self.eye - position));
+        let l = self.light;
+        let h = glm::normalize(&(v + l));
+        let n_dot_l = glm::dot(&n, &l).max(0.0);
+        let n_dot_v = glm::dot(&n, &v).max(0.001);
+        let n_dot_h = glm::dot(&n, &h).max(0.0);
+        let v_dot_h = glm::dot(&v, &h).max(0.0);
+
+        let base = base_color.xyz();
+        let f0 = glm::lerp(&glm::Vec3::repeat(0.04), &base, metallic);
+        let fresnel = f0 + (glm::Vec3::repeat(1.0) - f0) * (1.0 - v_dot_h).powi(5);
+        let a2 = roughness.powi(4);
+        let d = a2 / (std::f32::consts::PI * (n_dot_h * n_dot_h * (a2 - 1.0) + 1.0).powi(2));
+        let k = (roughness + 1.0).powi(2) / 8.0;
+        let g = n_dot_v / (n_dot_v * (1.0 - k) + k) * (n_dot_l / (n_dot_l * (1.0 - k) + k));
+
+        let specular = fresnel * (d * g / (4.0 * n_dot_v * n_dot_l.max(0.001)));
+        let diffuse = (glm::Vec3::repeat(1.0) - fresnel).component_mul(&base) * ((1.0 - metallic) / std::f32::consts::PI);
+        let mut color = (diffuse + specular) * (n_dot_l * 3.0) + base * 0.15;
+
+        let mut emissive = material.emissive;
+        if let Some(texel) = self.sample(material.emissive_texture, uv) {
+            emissive = emissive.component_mul(&glm::vec3(to_linear(texel.x), to_linear(texel.y), to_linear(texel.z)));
+        }
+        color += emissive;
+        (color, base_color.w)
+    }
+}
+
+// 描き込み先（色はリニアではなく、ビューアーと同じく画面に出す値で持つ）
+struct Target {
+    width: usize,
+    height: usize,
+    color: Vec<glm::Vec3>,
+    depth: Vec<f32>,
+}
+
+impl Target {
+    fn new(width: usize, height: usize) -> Target {
+        Target {
+            width,
+            height,
+            color: vec![glm::make_vec3(&BACKGROUND); width * height],
+            depth: vec![f32::INFINITY; width * height],
+        }
+    }
+
+    fn draw(&mut self, triangle: &Triangle, material: &Material, view_projection: &glm::Mat4, shading: &Shading) {
+        let clip = triangle.positions.map(|p| view_projection * p.push(1.0));
+        // カメラはモデルを囲む球の外にあるので、手前で切れる三角形はない
+        if clip.iter().any(|c| c.w <= 0.0) {
+            return;
+        }
+        let ndc = clip.map(|c| c.xyz() / c.w);
+        let screen = ndc.map(|p| glm::vec2((p.x + 1.0) / 2.0 * self.width as f32, (1.0 - p.y) / 2.0 * self.height as f32));
+
+        // 画面の y は下向きなので、反時計回り（表）の三角形は面積が負になる
+        let area = edge(&screen[0], &screen[1], &screen[2]);
+        let front = area < 0.0;
+        if area == 0.0 || (!front && !material.double_sided) {
+            return;
+        }
+
+        let min_x = screen.iter().map(|p| p.x).fold(f32::MAX, f32::min).floor().max(0.0) as usize;
+        let max_x = (screen.iter().map(|p| p.x).fold(f32::MIN, f32::max).ceil().max(0.0) as usize).min(self.width);
+        let min_y = screen.iter().map(|p| p.y).fold(f32::MAX, f32::min).floor().max(0.0) as usize;
+        let max_y = (screen.iter().map(|p| p.y).fold(f32::MIN, f32::max).ceil().max(0.0) as usize).min(self.height);
+        let inverse_w = clip.map(|c| 1.0 / c.w);
+
+        for y in min_y..max_y {
+            for x in min_x..max_x {
+                let pixel = glm::vec2(x as f32 + 0.5, y as f32 + 0.5);
+                let weights = [
+                    edge(&screen[1], &screen[2], &pixel) / area,
+                    edge(&screen[2], &screen[0], &pixel) / area,
+                    edge(&screen[0], &screen[1], &pixel) / area,
+                ];
+                if weights.iter().any(|&w| w < 0.0) {
+                    continue;
+                }
+                let depth = weights[0] * ndc[0].z + weights[1] * ndc[1].z + weights[2] * ndc[2].z;
+                let index = y * self.width + x;
+                if depth >= self.depth[index] || depth < -1.0 {
+                    continue;
+                }
+
+                // 遠近に合わせて補間する
+                let perspective: [f32; 3] = std::array::from_fn(|i| weights[i] * inverse_w[i]);
+                let total: f32 = perspective.iter().sum();
+                let interpolate3 = |values: &[glm::Vec3; 3]| (values[0] * perspective[0] + values[1] * perspective[1] + values[2] * perspective[2]) / total;
+                let position = interpolate3(&triangle.positions);
+                let mut normal = interpolate3(&triangle.normals);
+                let uv = triangle.tex_coords.map(|uv| (uv[0] * perspective[0] + uv[1] * perspective[1] + uv[2] * perspective[2]) / total);
+                if !front {
+                    normal = -normal;
+                }
+
+                let (color, alpha) = shading.shade(material, &position, &normal, &uv);
+                let color = color.map(|c| c.max(0.0).powf(1.0 / 2.2));
+                match material.alpha_mode {
+                    AlphaMode::Mask if alpha < material.alpha_cutoff => continue,
+                    AlphaMode::Blend => {
+                        self.color[index] = glm::lerp(&self.color[index], &color, alpha.clamp(0.0, 1.0));
+                    }
+                    AlphaMode::Opaque | AlphaMode::Mask => {
+                        self.color[index] = color;
+                        self.depth[index] = depth;
+                    }
+                }
+            }
+        }
+    }
+
+    // scale x scale の画素を平均して縮小する
+    fn resolve(&self, scale: usize) -> Image {
+        let (width, height) = (self.width / scale, self.height / scale);
+        let mut pixels = Vec::with_capacity(width * height * 4);
+        for y in 0..height {
+            for x in 0..width {
+                let mut sum = glm::Vec3::zeros();
+                for dy in 0..scale {
+                    for dx in 0..scale {
+                        sum += self.color[(y * scale + dy) * self.width + x * scale + dx];
+                    }
+                }
+                let color = sum / (scale * scale) as f32;
+                pixels.extend(color.iter().map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u8));
+                pixels.push(255);
+            }
+        }
+        Image { width, height, pixels }
+    }
+}
+
+// p が辺 a→b のどちら側にあるか（符号）と、三角形 a b p の面積の2倍
+fn edge(a: &glm::Vec2, b: &glm::Vec2, p: &glm::Vec2) -> f32 {
+    (b.x - a.x) * (p.y - a.y) - (b.y - a.y) * (p.x - a.x)
+}
+
+fn to_linear(c: f32) -> f32 {
+    c.powf(2.2)
+}
+
+// 1024x1024 のような大きさ
+pub fn parse_size(text: &str) -> Result<(usize, usize), String> {
+    let (width, height) = text.split_once(['x', 'X']).ok_or_else(|| format!("expected WIDTHxHEIGHT, e.g. 1024x1024, got '{}'", text))?;
+    let parse = |value: &str| match value.trim().parse::<usize>() {
+        Ok(size @ 1..=8192) => Ok(size),
+        _ => Err(format!("'{}' is not a size from 1 to 8192", value)),
+    };
+    Ok((parse(width)?, parse(height)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures;
+    use serde_json::json;
+
+    // 赤い三角形1枚（ノードの移動つき）
+    fn triangle(flipped: bool, double_sided: bool) -> Model {
+        let json = test_fixtures::triangle_with(|root| {
+            root["nodes"][0]["translation"] = json!([10, 0, 0]);
+            root["meshes"][0]["primitives"][0]["material"] = json!(0);
+            root["materials"] = json!([{
+                "pbrMetallicRoughness": { "baseColorFactor": [1, 0, 0, 1], "metallicFactor": 0 },
+                "doubleSided": double_sided,
+            }]);
+            if flipped {
+                // インデックスを 0 2 1 にして裏向きにする
+                test_fixtures::replace_in_buffer(root, "AAABAAIA", "AAACAAEA");
+            }
+        });
+        Model::from_slice(json.as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn test_render() {
+        let model = triangle(false, false);
+        let image = render(&model, Camera::Front, 64, 32);
+        assert_eq!((image.width, image.height, image.pixels.len()), (64, 32, 64 * 32 * 4));
+        // ノードの移動があってもモデルが画面の中央に来る
+        let pixel = |x: usize, y: usize| &image.pixels[(y * 64 + x) * 4..(y * 64 + x) * 4 + 4];
+        let center = pixel(30, 16);
+        assert!(center[0] > 100 && center[1] < center[0] / 2 && center[2] < center[0] / 2, "{:?}", center);
+        assert_eq!(pixel(0, 0), [26, 26, 26, 255]);
+        // 横から見ると厚みがないので何も映らない
+        let background = |image: &Image| image.pixels.chunks_exact(4).all(|p| p == [26, 26, 26, 255]);
+        assert!(background(&render(&model, Camera::Side, 16, 16)));
+        // 裏向きの三角形は片面のマテリアルなら描かず、両面なら描く
+        assert!(background(&render(&triangle(true, false), Camera::Front, 16, 16)));
+        let model = triangle(true, true);
+        assert!(!background(&render(&model, Camera::Front, 16, 16)));
+    }
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("1024x768"), Ok((1024, 768)));
+        assert_eq!(parse_size("64X64"), Ok((64, 64)));
+        assert!(parse_size("1024").is_err());
+        assert!(parse_size("0x10").is_err());
+        assert!(parse_size("10x9000").is_err());
+    }
+}
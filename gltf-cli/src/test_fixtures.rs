@@ -0,0 +1,19 @@
+// テスト用のモデル（gltf-core のテストと同じファイルを使う）
+use serde_json::{json, Value};
+
+// 三角形1枚（バッファーは data URI で埋め込む。+Z から見て反時計回り）
+pub const TRIANGLE: &str = include_str!("../../gltf-viewer/gltf-core/tests/fixtures/triangle.gltf");
+
+// TRIANGLE の JSON を書き換えたもの
+pub fn triangle_with(edit: impl FnOnce(&mut Value)) -> String {
+    let mut root: Value = serde_json::from_str(TRIANGLE).unwrap();
+    edit(&mut root);
+    root.to_string()
+}
+
+// 埋め込みのバッファーの base64 の一部を置き換える（最初に見つかった1か所だけ）
+//   位置 (0,0,0) (1,0,0) (0,1,0) が 36 バイト、インデックス 0 1 2 が 6 バイト
+pub fn replace_in_buffer(root: &mut Value, from: &str, to: &str) {
+    let uri = root["buffers"][0]["uri"].as_str().unwrap().replacen(from, to, 1);
+    root["buffers"][0]["uri"] = json!(uri);
+}
@@ -0,0 +1,289 @@
+// モデルの検査（gltf-cli validate）
+//
+// JSON の規則（参照先があるか、必須の値があるか、必須の拡張を扱えるか）は gltf クレートの検証に任せ、
+// バッファーの中身に関わるものをここで調べる
+//   missing-buffer         バッファーが読めない、または byteLength より短い
+//   buffer-view-bounds     バッファービューがバッファーをはみ出す
+//   accessor-bounds        アクセサーがバッファービューをはみ出す
+//   index-range            インデックスが頂点の数以上
+//   nan-position           POSITION に NaN か無限大がある
+//   unsupported-extension  extensionsUsed の拡張を読み込みで扱えない（無視して表示する）
+//   incomplete-triangle    TRIANGLES の頂点数が3の倍数でない
+//   missing-bounds         POSITION に min と max がない
+// JSON の規則に反するときは、その先を調べると参照をたどれないので検査をやめる
+use std::fmt::Write;
+use std::path::Path;
+
+use gltf::accessor::Accessor;
+use gltf::json::validation::Validate;
+use gltf::mesh::Mode;
+use serde::Serialize;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Issue {
+    pub severity: Severity,
+    pub code: &'static str,
+    // glTF の JSON の中の場所（accessors[2].bufferView など）
+    pub path: String,
+    pub message: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Report {
+    pub file: String,
+    pub valid: bool,
+    pub errors: usize,
+    pub warnings: usize,
+    pub issues: Vec<Issue>,
+}
+
+impl Report {
+    pub fn new(path: &Path, issues: Vec<Issue>) -> Report {
+        let count = |severity: Severity| issues.iter().filter(|issue| issue.severity == severity).count();
+        let (errors, warnings) = (count(Severity::Error), count(Severity::Warning));
+        Report { file: path.display().to_string(), valid: errors == 0, errors, warnings, issues }
+    }
+}
+
+impl Issue {
+    fn new(severity: Severity, code: &'static str, path: impl Into<String>, message: impl Into<String>) -> Issue {
+        Issue { severity, code, path: path.into(), message: message.into() }
+    }
+}
+
+// data はファイルの中身、base は外部のバッファーを探すディレクトリ
+// JSON としても GLB としても読めなければ Err
+pub fn check(data: &[u8], base: &Path) -> Result<Vec<Issue>, gltf::Error> {
+    let gltf::Gltf { document, mut blob } = gltf::Gltf::from_slice_without_validation(data)?;
+    let mut issues = Vec::new();
+
+    let root = document.as_json();
+    root.validate(root, gltf::json::Path::new, &mut |path, error| {
+        let code = match error {
+            gltf::json::validation::Error::Unsupported => "unsupported-extension",
+            _ => "invalid-json",
+        };
+        issues.push(Issue::new(Severity::Error, code, path().to_string(), error.to_string()));
+    });
+    if !issues.is_empty() {
+        issues.push(Issue::new(Severity::Info, "skipped", "", "buffer and geometry checks skipped until the JSON errors are fixed"));
+        return Ok(issues);
+    }
+
+    for (i, extension) in document.extensions_used().enumerate() {
+        if !gltf::json::extensions::SUPPORTED_EXTENSIONS.contains(&extension) {
+            issues.push(Issue::new(
+                Severity::Warning,
+                "unsupported-extension",
+                format!("extensionsUsed[{}]", i),
+                format!("{} is not supported and will be ignored", extension),
+            ));
+        }
+    }
+
+    // 読めたバッファー（読めないものは None）
+    let buffers: Vec<Option<gltf::buffer::Data>> = document
+        .buffers()
+        .map(|buffer| {
+            let path = format!("buffers[{}]", buffer.index());
+            match gltf::buffer::Data::from_source_and_blob(buffer.source(), Some(base), &mut blob) {
+                Ok(data) if data.len() >= buffer.length() => Some(data),
+                Ok(data) => {
+                    let message = format!("has {} bytes but byteLength is {}", data.len(), buffer.length());
+                    issues.push(Issue::new(Severity::Error, "missing-buffer", path, message));
+                    None
+                }
+                Err(e) => {
+                    issues.push(Issue::new(Severity::Error, "missing-buffer", path, format!("cannot be read: {}", e)));
+                    None
+                }
+            }
+        })
+        .collect();
+
+    let views_in_bounds: Vec<bool> = document
+        .views()
+        .map(|view| {
+            let end = view.offset() + view.length();
+            let in_bounds = end <= view.buffer().length();
+            if !in_bounds {
+                let message = format!("ends at byte {} but buffer {} has {} bytes", end, view.buffer().index(), view.buffer().length());
+                issues.push(Issue::new(Severity::Error, "buffer-view-bounds", format!("bufferViews[{}]", view.index()), message));
+            }
+            in_bounds
+        })
+        .collect();
+
+    // 中身を読んでよいアクセサー（はみ出さず、バッファーも読めた）
+    let readable: Vec<bool> = document
+        .accessors()
+        .map(|accessor| {
+            let Some(view) = accessor.view() else {
+                return false;
+            };
+            let end = accessor_end(&accessor);
+            if end > view.length() {
+                let message = format!("needs {} bytes but buffer view {} has {}", end, view.index(), view.length());
+                issues.push(Issue::new(Severity::Error, "accessor-bounds", format!("accessors[{}]", accessor.index()), message));
+                return false;
+            }
+            accessor.sparse().is_none() && views_in_bounds[view.index()] && buffers[view.buffer().index()].is_some()
+        })
+        .collect();
+
+    for mesh in document.meshes() {
+        for primitive in mesh.primitives() {
+            let path = format!("meshes[{}].primitives[{}]", mesh.index(), primitive.index());
+            check_primitive(&primitive, &path, &buffers, &readable, &mut issues);
+        }
+    }
+
+    issues.sort_by_key(|issue| std::cmp::Reverse(issue.severity));
+    Ok(issues)
+}
+
+fn check_primitive(
+    primitive: &gltf::Primitive,
+    path: &str,
+    buffers: &[Option<gltf::buffer::Data>],
+    readable: &[bool],
+    issues: &mut Vec<Issue>,
+) {
+    let reader = primitive.reader(|buffer| buffers[buffer.index()].as_deref());
+    let Some(positions) = primitive.get(&gltf::Semantic::Positions) else {
+        return;
+    };
+    let vertex_count = positions.count();
+    if positions.min().is_none() || positions.max().is_none() {
+        issues.push(Issue::new(Severity::Warning, "missing-bounds", format!("{}.attributes.POSITION", path), "POSITION accessor has no min and max"));
+    }
+    if readable[positions.index()] {
+        let bad = reader.read_positions().into_iter().flatten().filter(|position| position.iter().any(|value| !value.is_finite())).count();
+        if bad > 0 {
+            let message = format!("{} of {} positions are NaN or infinite (accessor {})", bad, vertex_count, positions.index());
+            issues.push(Issue::new(Severity::Error, "nan-position", format!("{}.attributes.POSITION", path), message));
+        }
+    }
+
+    let element_count = primitive.indices().map_or(vertex_count, |indices| indices.count());
+    if primitive.mode() == Mode::Triangles && !element_count.is_multiple_of(3) {
+        let message = format!("{} vertices do not make whole triangles", element_count);
+        issues.push(Issue::new(Severity::Warning, "incomplete-triangle", path.to_string(), message));
+    }
+
+    let Some(indices) = primitive.indices() else {
+        return;
+    };
+    if !readable[indices.index()] {
+        return;
+    }
+    let out_of_range: Vec<u32> = reader
+        .read_indices()
+        .into_iter()
+        .flat_map(|indices| indices.into_u32())
+        .filter(|&index| index as usize >= vertex_count)
+        .collect();
+    if let Some(max) = out_of_range.iter().max() {
+        let message = format!(
+            "{} index(es) out of range (largest {}, but there are {} vertices; accessor {})",
+            out_of_range.len(), max, vertex_count, indices.index()
+        );
+        issues.push(Issue::new(Severity::Error, "index-range", format!("{}.indices", path), message));
+    }
+}
+
+// アクセサーの最後の要素がバッファービューの先頭から何バイト目で終わるか
+fn accessor_end(accessor: &Accessor) -> usize {
+    let size = accessor.size();
+    let stride = accessor.view().and_then(|view| view.stride()).unwrap_or(size);
+    match accessor.count() {
+        0 => accessor.offset(),
+        count => accessor.offset() + stride * (count - 1) + size,
+    }
+}
+
+// 人が読む形式（コンパイラーのエラーと同じく1件1行）
+pub fn render(report: &Report) -> String {
+    let mut out = String::new();
+    for issue in &report.issues {
+        let severity = match issue.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+            Severity::Info => "info",
+        };
+        let path = if issue.path.is_empty() { String::new() } else { format!(" {}:", issue.path) };
+        let _ = writeln!(out, "{}[{}]{} {}", severity, issue.code, path, issue.message);
+    }
+    let _ = writeln!(out, "{}: {} error(s), {} warning(s)", report.file, report.errors, report.warnings);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::{self, TRIANGLE};
+    use serde_json::json;
+
+    // 三角形1枚のバッファーを書き換えたもの
+    fn model(from: &str, to: &str) -> String {
+        test_fixtures::triangle_with(|root| test_fixtures::replace_in_buffer(root, from, to))
+    }
+
+    // インデックス 0 1 2 を 0 1 3 に
+    fn bad_indices() -> String {
+        model("AAABAAIA", "AAABAAMA")
+    }
+
+    // 2つ目の位置 (1,0,0) を (NaN,0,0) に
+    fn nan_position() -> String {
+        model("AACAPw", "AADAfw")
+    }
+
+    fn codes(json: &str) -> Vec<(Severity, &'static str)> {
+        check(json.as_bytes(), Path::new(".")).unwrap().iter().map(|issue| (issue.severity, issue.code)).collect()
+    }
+
+    #[test]
+    fn test_valid_model() {
+        assert_eq!(codes(TRIANGLE), vec![]);
+        let report = Report::new(Path::new("model.gltf"), Vec::new());
+        assert!(report.valid);
+        assert_eq!(render(&report), "model.gltf: 0 error(s), 0 warning(s)\n");
+    }
+
+    #[test]
+    fn test_geometry_errors() {
+        assert_eq!(codes(&bad_indices()), vec![(Severity::Error, "index-range")]);
+        assert_eq!(codes(&nan_position()), vec![(Severity::Error, "nan-position")]);
+        let issues = check(bad_indices().as_bytes(), Path::new(".")).unwrap();
+        assert_eq!(issues[0].path, "meshes[0].primitives[0].indices");
+        assert_eq!(issues[0].message, "1 index(es) out of range (largest 3, but there are 3 vertices; accessor 1)");
+    }
+
+    #[test]
+    fn test_buffers_and_extensions() {
+        // 外部ファイルがない
+        let missing = test_fixtures::triangle_with(|root| root["buffers"][0]["uri"] = json!("missing.bin"));
+        assert_eq!(codes(&missing), vec![(Severity::Error, "missing-buffer")]);
+        // アクセサーがバッファービューをはみ出す
+        let overflow = test_fixtures::triangle_with(|root| root["accessors"][1]["count"] = json!(4));
+        assert_eq!(codes(&overflow), vec![(Severity::Error, "accessor-bounds"), (Severity::Warning, "incomplete-triangle")]);
+        let extensions = test_fixtures::triangle_with(|root| root["extensionsUsed"] = json!(["EXT_unknown"]));
+        assert_eq!(codes(&extensions), vec![(Severity::Warning, "unsupported-extension")]);
+        // 扱えない必須の拡張は JSON の検証で見つかり、その先は調べない
+        let required = test_fixtures::triangle_with(|root| {
+            root["extensionsUsed"] = json!(["EXT_unknown"]);
+            root["extensionsRequired"] = json!(["EXT_unknown"]);
+        });
+        assert_eq!(codes(&required), vec![(Severity::Error, "unsupported-extension"), (Severity::Info, "skipped")]);
+        assert!(check(b"not gltf", Path::new(".")).is_err());
+    }
+}
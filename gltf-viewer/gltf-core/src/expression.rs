@@ -0,0 +1,47 @@
+// 数値の引数を calc の式として読む（gltf-cli の --scale "1/25.4" とビューアーの倍率で同じものを使う）
+// エラーは calc と同じ文言にし、構文エラーにヒントがあれば次の行に付ける
+use calc_core::CalcError;
+
+pub fn number(input: &str) -> Result<f64, String> {
+    calc_core::evaluate_number(input).map_err(|error| describe(&error))
+}
+
+// 0 より大きい数（倍率など）
+pub fn positive(input: &str) -> Result<f64, String> {
+    match number(input)? {
+        value if value > 0.0 => Ok(value),
+        value => Err(format!("{} must be greater than 0 (got {})", input.trim(), value)),
+    }
+}
+
+// 0 以上の整数（個数など）
+pub fn count(input: &str) -> Result<usize, String> {
+    match number(input)? {
+        value if value >= 0.0 && value.fract() == 0.0 && value <= usize::MAX as f64 => Ok(value as usize),
+        value => Err(format!("{} must be a whole number of at least 0 (got {})", input.trim(), value)),
+    }
+}
+
+fn describe(error: &CalcError) -> String {
+    match error {
+        CalcError::SyntaxError { hint: Some(hint), .. } => format!("{}\nhint: {}", error, hint),
+        _ => error.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_number() {
+        assert_eq!(number("1/25.4"), Ok(1.0 / 25.4));
+        assert_eq!(positive("2 * 0.5"), Ok(1.0));
+        assert_eq!(count("10^4"), Ok(10000));
+        assert_eq!(number("1/0"), Err("Division by zero".to_string()));
+        assert!(number("1 +").unwrap_err().starts_with("Syntax error at position"));
+        assert!(positive("1 - 1").unwrap_err().contains("greater than 0"));
+        assert!(count("2.5").is_err());
+        assert!(count("-1").is_err());
+    }
+}
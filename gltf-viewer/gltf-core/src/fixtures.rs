@@ -0,0 +1,4 @@
+// テスト用のモデル（gltf-cli のテストも同じファイルを使う）
+
+// 三角形1枚（バッファーは data URI で埋め込む。+Z から見て反時計回り）
+pub const TRIANGLE: &str = include_str!("../tests/fixtures/triangle.gltf");
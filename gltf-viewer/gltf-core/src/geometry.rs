@@ -0,0 +1,260 @@
+// プリミティブから描画に使う頂点属性を取り出す
+use std::collections::HashMap;
+
+use gltf::mesh::util::ReadIndices;
+use gltf::mesh::{Mode, Semantic};
+use gltf::Primitive;
+
+use crate::tangent;
+
+// 1つのプリミティブの頂点属性（ない属性は空）
+#[derive(Clone, Debug, Default)]
+pub struct Geometry {
+    pub positions: Vec<[f32; 3]>,
+    pub normals: Vec<[f32; 3]>,
+    // TEXCOORD_0
+    pub tex_coords: Vec<[f32; 2]>,
+    // TEXCOORD_1（オクルージョンやライトマップが別の UV を使うモデルがある）
+    pub tex_coords_1: Vec<[f32; 2]>,
+    pub tangents: Vec<[f32; 4]>,
+    pub indices: Vec<u32>,
+}
+
+// 読む UV セットの数（TEXCOORD_0 と TEXCOORD_1）
+pub const TEX_COORD_SETS: u32 = 2;
+
+impl Geometry {
+    // テクスチャーの texCoord が指す UV（読まないセットは空）
+    pub fn uv_set(&self, set: u32) -> &[[f32; 2]] {
+        match set {
+            0 => &self.tex_coords,
+            1 => &self.tex_coords_1,
+            _ => &[],
+        }
+    }
+}
+
+// 位置がなければ None（インデックスがなければ頂点の順に振る）
+pub fn read(primitive: &Primitive, buffers: &[gltf::buffer::Data]) -> Option<Geometry> {
+    let mut geometry = read_attributes(primitive, buffers)?;
+
+    // 法線マップがあるのにTANGENTがなければ、法線マップの UV から生成する（法線とUVが揃っていなければ諦める）
+    if let Some(normal_texture) = primitive.material().normal_texture() {
+        let tex_coords = geometry.uv_set(normal_texture.tex_coord());
+        let Geometry { positions, normals, tangents, indices, .. } = &geometry;
+        if tangents.is_empty() && normals.len() == positions.len() && tex_coords.len() == positions.len() {
+            let triangles = triangulate(primitive.mode(), indices);
+            let corners = tangent::generate_tangents(positions, normals, tex_coords, &triangles);
+            if primitive.mode() == Mode::Triangles {
+                geometry.split_by_tangent(&corners);
+            } else {
+                // ストリップとファンはインデックスを書き換えられないので、頂点を分けずに最初の角の接線を使う
+                let mut tangents = vec![[1.0, 0.0, 0.0, 1.0]; positions.len()];
+                for (&i, &tangent) in triangles.iter().zip(&corners).rev() {
+                    tangents[i as usize] = tangent;
+                }
+                geometry.tangents = tangents;
+            }
+        }
+    }
+    Some(geometry)
+}
+
+impl Geometry {
+    // 三角形の角ごとの接線（indices と同じ並び）を頂点の TANGENT にする
+    // 同じ頂点でも角によって接線が違えば（UV の継ぎ目や鏡像の UV）、頂点を複製してインデックスを付け替える
+    fn split_by_tangent(&mut self, corners: &[[f32; 4]]) {
+        let mut tangents: Vec<Option<[f32; 4]>> = vec![None; self.positions.len()];
+        // (元の頂点, 接線) → 使う頂点（MikkTSpace は同じ向きの角に全く同じ値を返すので、ビットで比べる）
+        let mut copies: HashMap<(u32, [u32; 4]), u32> = HashMap::new();
+        let vertex_count = self.positions.len();
+        for (corner, &tangent) in corners.iter().enumerate() {
+            let vertex = self.indices[corner];
+            if vertex as usize >= vertex_count {
+                continue;
+            }
+            let key = (vertex, tangent.map(f32::to_bits));
+            if let Some(&copy) = copies.get(&key) {
+                self.indices[corner] = copy;
+                continue;
+            }
+            let copy = if tangents[vertex as usize].is_none() {
+                vertex
+            } else {
+                self.duplicate(vertex as usize)
+            };
+            if copy as usize == tangents.len() {
+                tangents.push(None);
+            }
+            tangents[copy as usize] = Some(tangent);
+            copies.insert(key, copy);
+            self.indices[corner] = copy;
+        }
+        // どの三角形にも使われない頂点の接線は何でもよい
+        self.tangents = tangents.into_iter().map(|tangent| tangent.unwrap_or([1.0, 0.0, 0.0, 1.0])).collect();
+    }
+
+    // 頂点の属性を末尾に複製し、その番号を返す
+    fn duplicate(&mut self, vertex: usize) -> u32 {
+        fn push<T: Copy>(values: &mut Vec<T>, vertex: usize) {
+            if let Some(&value) = values.get(vertex) {
+                values.push(value);
+            }
+        }
+        push(&mut self.positions, vertex);
+        push(&mut self.normals, vertex);
+        push(&mut self.tex_coords, vertex);
+        push(&mut self.tex_coords_1, vertex);
+        (self.positions.len() - 1) as u32
+    }
+}
+
+// ファイルにある属性だけを読む（TANGENT を生成しない。最適化して書き戻すとき用）
+pub fn read_attributes(primitive: &Primitive, buffers: &[gltf::buffer::Data]) -> Option<Geometry> {
+    let reader = primitive.reader(|buffer| buffers.get(buffer.index()).map(|data| &data[..]));
+    let positions: Vec<[f32; 3]> = reader.read_positions()?.collect();
+
+    let normals: Vec<[f32; 3]> = reader.read_normals()
+        .map(|iter| iter.collect())
+        .unwrap_or_default();
+    let read_tex_coords = |set: u32| -> Vec<[f32; 2]> {
+        reader.read_tex_coords(set)
+            .map(|iter| iter.into_f32().collect())
+            .unwrap_or_default()
+    };
+    let (tex_coords, tex_coords_1) = (read_tex_coords(0), read_tex_coords(1));
+    let tangents: Vec<[f32; 4]> = reader.read_tangents()
+        .map(|iter| iter.collect())
+        .unwrap_or_default();
+
+    let indices: Vec<u32> = match reader.read_indices() {
+        Some(ReadIndices::U8(iter)) => iter.map(|i| i as u32).collect(),
+        Some(ReadIndices::U16(iter)) => iter.map(|i| i as u32).collect(),
+        Some(ReadIndices::U32(iter)) => iter.collect(),
+        None => (0..positions.len() as u32).collect(),
+    };
+
+    Some(Geometry { positions, normals, tex_coords, tex_coords_1, tangents, indices })
+}
+
+// 描画する頂点の数（インデックスがあればその数。バッファーを読まずに数える）
+pub fn element_count(primitive: &Primitive) -> usize {
+    primitive.indices()
+        .or_else(|| primitive.get(&Semantic::Positions))
+        .map_or(0, |accessor| accessor.count())
+}
+
+// 頂点の数から三角形の数を出す（点と線は 0）
+pub fn triangle_count(mode: Mode, count: usize) -> usize {
+    match mode {
+        Mode::Triangles => count / 3,
+        Mode::TriangleStrip | Mode::TriangleFan => count.saturating_sub(2),
+        Mode::Points | Mode::Lines | Mode::LineLoop | Mode::LineStrip => 0,
+    }
+}
+
+// TRIANGLE_STRIP と TRIANGLE_FAN のインデックスを TRIANGLES のものにする（点と線は空）
+pub fn triangulate(mode: Mode, indices: &[u32]) -> Vec<u32> {
+    let count = triangle_count(mode, indices.len());
+    match mode {
+        Mode::Triangles => indices[..count * 3].to_vec(),
+        // 奇数番目の三角形は向きをそろえるために入れ替える
+        Mode::TriangleStrip => (0..count)
+            .flat_map(|i| if i % 2 == 0 { [indices[i], indices[i + 1], indices[i + 2]] } else { [indices[i + 1], indices[i], indices[i + 2]] })
+            .collect(),
+        Mode::TriangleFan => (0..count).flat_map(|i| [indices[0], indices[i + 1], indices[i + 2]]).collect(),
+        Mode::Points | Mode::Lines | Mode::LineLoop | Mode::LineStrip => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Model;
+
+    #[test]
+    fn test_read() {
+        let model = Model::from_slice(crate::fixtures::TRIANGLE.as_bytes()).unwrap();
+        let primitive = model.document.meshes().next().unwrap().primitives().next().unwrap();
+        let geometry = read(&primitive, &model.buffers).unwrap();
+        assert_eq!(geometry.positions, vec![[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]]);
+        assert_eq!(geometry.indices, vec![0, 1, 2]);
+        assert!(geometry.normals.is_empty() && geometry.tangents.is_empty());
+        assert_eq!(element_count(&primitive), 3);
+    }
+
+    // TEXCOORD_0 と TEXCOORD_1 を持ち、法線マップが TEXCOORD_1 を使う三角形
+    const TWO_UV_SETS: &str = r#"{
+  "asset": {"version": "2.0"},
+  "meshes": [{"primitives": [{"attributes": {"POSITION": 0, "NORMAL": 1, "TEXCOORD_0": 2, "TEXCOORD_1": 3}, "indices": 4, "material": 0}]}],
+  "materials": [{"normalTexture": {"index": 0, "texCoord": 1}}],
+  "textures": [{"source": 0}],
+  "images": [{"uri": "normal.png"}],
+  "buffers": [{"byteLength": 128, "uri": "data:application/octet-stream;base64,AAAAAAAAAAAAAAAAAACAPwAAAAAAAAAAAAAAAAAAgD8AAAAAAAAAAAAAAAAAAIA/AAAAAAAAAAAAAIA/AAAAAAAAAAAAAIA/AAAAAAAAAAAAAIA/AAAAAAAAAAAAAIA/AAAAAAAAAAAAAAAAAACAPwAAgD8AAAAAAAABAAIAAAA="}],
+  "bufferViews": [
+    {"buffer": 0, "byteOffset": 0, "byteLength": 36},
+    {"buffer": 0, "byteOffset": 36, "byteLength": 36},
+    {"buffer": 0, "byteOffset": 72, "byteLength": 24},
+    {"buffer": 0, "byteOffset": 96, "byteLength": 24},
+    {"buffer": 0, "byteOffset": 120, "byteLength": 6}
+  ],
+  "accessors": [
+    {"bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3", "min": [0, 0, 0], "max": [1, 1, 0]},
+    {"bufferView": 1, "componentType": 5126, "count": 3, "type": "VEC3"},
+    {"bufferView": 2, "componentType": 5126, "count": 3, "type": "VEC2"},
+    {"bufferView": 3, "componentType": 5126, "count": 3, "type": "VEC2"},
+    {"bufferView": 4, "componentType": 5123, "count": 3, "type": "SCALAR"}
+  ]
+}"#;
+
+    #[test]
+    fn test_read_uv_sets() {
+        // 画像は読まなくてよいので、バッファーだけを読む
+        let gltf::Gltf { document, blob } = gltf::Gltf::from_slice(TWO_UV_SETS.as_bytes()).unwrap();
+        let buffers = gltf::import_buffers(&document, None, blob).unwrap();
+        let primitive = document.meshes().next().unwrap().primitives().next().unwrap();
+        let geometry = read(&primitive, &buffers).unwrap();
+        assert_eq!(geometry.uv_set(0), [[0.0, 0.0], [1.0, 0.0], [0.0, 1.0]]);
+        assert_eq!(geometry.uv_set(1), [[0.0, 0.0], [0.0, 1.0], [1.0, 0.0]]);
+        assert!(geometry.uv_set(2).is_empty());
+        // 接線は法線マップの UV（TEXCOORD_1 では u が +y 向き）から作る
+        for tangent in &geometry.tangents {
+            assert!((tangent[0] - 0.0).abs() < 1e-5 && (tangent[1] - 1.0).abs() < 1e-5, "{:?}", tangent);
+        }
+    }
+
+    #[test]
+    fn test_split_by_tangent() {
+        // UV を鏡像にした2つの升（継ぎ目の頂点 1 と 2 は左右で接線が違う）
+        let mut geometry = Geometry {
+            positions: vec![[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [1.0, 1.0, 0.0], [0.0, 1.0, 0.0], [2.0, 0.0, 0.0], [2.0, 1.0, 0.0]],
+            normals: vec![[0.0, 0.0, 1.0]; 6],
+            tex_coords: vec![[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0], [0.0, 0.0], [0.0, 1.0]],
+            indices: vec![0, 1, 2, 0, 2, 3, 1, 4, 5, 1, 5, 2],
+            ..Geometry::default()
+        };
+        let corners = tangent::generate_tangents(&geometry.positions, &geometry.normals, &geometry.tex_coords, &geometry.indices);
+        geometry.split_by_tangent(&corners);
+        assert_eq!(geometry.indices, vec![0, 1, 2, 0, 2, 3, 6, 4, 5, 6, 5, 7]);
+        assert_eq!(geometry.positions[6..], [[1.0, 0.0, 0.0], [1.0, 1.0, 0.0]]);
+        assert_eq!((geometry.normals.len(), geometry.tex_coords.len(), geometry.tex_coords_1.len()), (8, 8, 0));
+        assert_eq!(geometry.tex_coords[6..], [[1.0, 0.0], [1.0, 1.0]]);
+        for (corner, tangent) in corners.iter().enumerate() {
+            assert_eq!(&geometry.tangents[geometry.indices[corner] as usize], tangent);
+        }
+        assert_eq!(geometry.tangents[1][3], 1.0);
+        assert_eq!(geometry.tangents[6][3], -1.0);
+    }
+
+    #[test]
+    fn test_triangle_count() {
+        assert_eq!(triangle_count(Mode::Triangles, 36), 12);
+        assert_eq!(triangle_count(Mode::TriangleStrip, 5), 3);
+        assert_eq!(triangle_count(Mode::TriangleFan, 1), 0);
+        assert_eq!(triangle_count(Mode::Lines, 36), 0);
+        assert_eq!(triangulate(Mode::TriangleStrip, &[0, 1, 2, 3]), vec![0, 1, 2, 2, 1, 3]);
+        assert_eq!(triangulate(Mode::TriangleFan, &[0, 1, 2, 3]), vec![0, 1, 2, 0, 2, 3]);
+        assert_eq!(triangulate(Mode::Triangles, &[0, 1, 2, 3]), vec![0, 1, 2]);
+        assert!(triangulate(Mode::Points, &[0, 1, 2]).is_empty());
+    }
+}
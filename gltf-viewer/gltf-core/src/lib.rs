@@ -0,0 +1,16 @@
+// glTF の読み込みとジオメトリの取り出し（WebGL を使わない部分）
+//
+// ブラウザーのビューアー（gltf-viewer）とネイティブの gltf-cli で同じものを使う
+//   let model = gltf_core::Model::open(Path::new("model.glb"))?;
+//   for mesh in model.document.meshes() { ... }
+pub mod expression;
+pub mod geometry;
+pub mod model;
+pub mod optimize;
+pub mod tangent;
+pub mod texture;
+
+pub use model::Model;
+
+#[cfg(test)]
+mod fixtures;
@@ -0,0 +1,50 @@
+// モデルの読み込み（.gltf と .glb のどちらでもよい）
+use std::path::Path;
+
+// 解析した文書と、読み込んだバッファー・画像
+pub struct Model {
+    pub document: gltf::Document,
+    // バイナリ形式（GLB）から読んだか
+    pub glb: bool,
+    pub buffers: Vec<gltf::buffer::Data>,
+    pub images: Vec<gltf::image::Data>,
+}
+
+// 先頭4バイトが "glTF" ならバイナリ形式（GLB）
+pub fn is_glb(data: &[u8]) -> bool {
+    data.starts_with(b"glTF")
+}
+
+impl Model {
+    // メモリー上のファイルから読む（外部ファイルを参照する .gltf は読めないので、バッファーは埋め込みか GLB に限る）
+    pub fn from_slice(data: &[u8]) -> Result<Model, gltf::Error> {
+        let (document, buffers, images) = gltf::import_slice(data)?;
+        Ok(Model { document, glb: is_glb(data), buffers, images })
+    }
+
+    // ファイルから読む（外部のバッファーと画像は同じディレクトリから探す）
+    pub fn open(path: &Path) -> Result<Model, gltf::Error> {
+        let data = std::fs::read(path).map_err(gltf::Error::Io)?;
+        let gltf::Gltf { document, blob } = gltf::Gltf::from_slice(&data)?;
+        let base = path.parent().unwrap_or(Path::new("."));
+        let buffers = gltf::import_buffers(&document, Some(base), blob)?;
+        let images = gltf::import_images(&document, Some(base), &buffers)?;
+        Ok(Model { document, glb: is_glb(&data), buffers, images })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_slice() {
+        assert!(is_glb(b"glTF\x02\x00\x00\x00"));
+        assert!(!is_glb(crate::fixtures::TRIANGLE.as_bytes()));
+        let model = Model::from_slice(crate::fixtures::TRIANGLE.as_bytes()).unwrap();
+        assert_eq!(model.document.meshes().count(), 1);
+        assert_eq!(model.buffers[0].len(), 44);
+        assert!(!model.glb);
+        assert!(Model::from_slice(b"{}").is_err());
+    }
+}
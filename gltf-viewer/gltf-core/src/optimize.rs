@@ -0,0 +1,289 @@
+// メッシュの軽量化（WebGL に依存しないので、gltf-cli optimize とビューアーで同じものを使う）
+//   weld      位置が tolerance 以内で、法線・UV・TANGENT も同じ頂点を1つにまとめる
+//   decimate  三角形の数を予算以下にする（空間を格子に区切り、同じ升目の頂点をまとめる）
+// どちらも三角形（TRIANGLES）のインデックスを前提とし、使われなくなった頂点と潰れた三角形を除く
+use std::collections::HashMap;
+use std::collections::HashSet;
+
+use crate::geometry::Geometry;
+
+// 法線・UV・TANGENT はこの細かさで同じかどうかを比べる（書き出しの誤差でまとまらないのを防ぐ）
+const ATTRIBUTE_STEP: f32 = 1.0 / 65536.0;
+
+pub fn triangle_count(geometry: &Geometry) -> usize {
+    geometry.indices.len() / 3
+}
+
+// tolerance が 0 なら全く同じ頂点だけをまとめる（まとめた頂点は最初に現れたものの属性を使う）
+pub fn weld(geometry: &Geometry, tolerance: f32) -> Geometry {
+    let mut keys: HashMap<Vec<i64>, u32> = HashMap::new();
+    let mut sources = Vec::new();
+    let mut indices = Vec::with_capacity(geometry.indices.len());
+    for &index in &geometry.indices {
+        let key = vertex_key(geometry, index as usize, tolerance);
+        let welded = *keys.entry(key).or_insert_with(|| {
+            sources.push(index);
+            sources.len() as u32 - 1
+        });
+        indices.push(welded);
+    }
+    compact(&pick(geometry, &sources), &without_degenerate(&indices))
+}
+
+// 三角形を max_triangles 以下にする（1つも残らないほど小さい予算なら、残る中で一番少ないものにする）
+pub fn decimate(geometry: &Geometry, max_triangles: usize) -> Geometry {
+    if triangle_count(geometry) <= max_triangles {
+        return geometry.clone();
+    }
+    // 格子が細かいほど三角形が残るので、予算に収まる一番細かい格子を二分探索する
+    const MAX_CELLS: u32 = 1 << 16;
+    let count = |cells: u32| triangle_count(&cluster(geometry, cells));
+    let (mut low, mut high) = (1, MAX_CELLS);
+    while low < high {
+        let middle = (low + high).div_ceil(2);
+        if count(middle) <= max_triangles {
+            low = middle;
+        } else {
+            high = middle - 1;
+        }
+    }
+    let decimated = cluster(geometry, low);
+    if triangle_count(&decimated) > 0 {
+        return decimated;
+    }
+    // 全て潰れたときは、三角形が残る一番粗い格子を探す
+    let (mut low, mut high) = (low, MAX_CELLS);
+    while low < high {
+        let middle = low + (high - low) / 2;
+        if count(middle) > 0 {
+            high = middle;
+        } else {
+            low = middle + 1;
+        }
+    }
+    cluster(geometry, low)
+}
+
+// 一番長い辺を cells 個に区切った格子で頂点をまとめる（属性は升目の中の平均）
+fn cluster(geometry: &Geometry, cells: u32) -> Geometry {
+    let (min, max) = bounds(&geometry.positions);
+    let size = (0..3).map(|axis| max[axis] - min[axis]).fold(0.0, f32::max);
+    let cell = |position: &[f32; 3]| -> [u32; 3] {
+        std::array::from_fn(|axis| {
+            if size == 0.0 {
+                0
+            } else {
+                (((position[axis] - min[axis]) / size * cells as f32) as u32).min(cells - 1)
+            }
+        })
+    };
+
+    // 三角形が使う頂点だけを、それぞれ1回ずつ升目に入れる
+    let mut clusters: HashMap<[u32; 3], u32> = HashMap::new();
+    let mut members: Vec<Vec<u32>> = Vec::new();
+    let mut vertex_cluster = vec![None; geometry.positions.len()];
+    let mut indices = Vec::with_capacity(geometry.indices.len());
+    for &index in &geometry.indices {
+        let cluster = *vertex_cluster[index as usize].get_or_insert_with(|| {
+            let key = cell(&geometry.positions[index as usize]);
+            let cluster = *clusters.entry(key).or_insert_with(|| {
+                members.push(Vec::new());
+                members.len() as u32 - 1
+            });
+            members[cluster as usize].push(index);
+            cluster
+        });
+        indices.push(cluster);
+    }
+
+    let average = |values: &[[f32; 4]], members: &[u32]| -> [f32; 4] {
+        let mut sum = [0.0; 4];
+        for &member in members {
+            for (total, value) in sum.iter_mut().zip(values[member as usize]) {
+                *total += value;
+            }
+        }
+        sum.map(|total| total / members.len() as f32)
+    };
+    let widen3 = |values: &[[f32; 3]]| values.iter().map(|v| [v[0], v[1], v[2], 0.0]).collect::<Vec<_>>();
+    let widen2 = |values: &[[f32; 2]]| values.iter().map(|v| [v[0], v[1], 0.0, 0.0]).collect::<Vec<_>>();
+    let (positions, normals) = (widen3(&geometry.positions), widen3(&geometry.normals));
+    let (tex_coords, tex_coords_1) = (widen2(&geometry.tex_coords), widen2(&geometry.tex_coords_1));
+
+    let mut clustered = Geometry::default();
+    for members in &members {
+        let [x, y, z, _] = average(&positions, members);
+        clustered.positions.push([x, y, z]);
+        if !normals.is_empty() {
+            let [x, y, z, _] = average(&normals, members);
+            clustered.normals.push(normalize([x, y, z]));
+        }
+        if !tex_coords.is_empty() {
+            let [u, v, _, _] = average(&tex_coords, members);
+            clustered.tex_coords.push([u, v]);
+        }
+        if !tex_coords_1.is_empty() {
+            let [u, v, _, _] = average(&tex_coords_1, members);
+            clustered.tex_coords_1.push([u, v]);
+        }
+        if !geometry.tangents.is_empty() {
+            // w（従法線の向き）は平均すると 0 に近づくので、最初の頂点のものを使う
+            let [x, y, z, _] = average(&geometry.tangents, members);
+            let [x, y, z] = normalize([x, y, z]);
+            clustered.tangents.push([x, y, z, geometry.tangents[members[0] as usize][3]]);
+        }
+    }
+    compact(&clustered, &without_degenerate(&indices))
+}
+
+// 頂点を同じと見なすためのキー
+fn vertex_key(geometry: &Geometry, index: usize, tolerance: f32) -> Vec<i64> {
+    let quantize = |value: f32, step: f32| -> i64 {
+        if step > 0.0 {
+            (value / step).round() as i64
+        } else {
+            value.to_bits() as i64
+        }
+    };
+    let mut key: Vec<i64> = geometry.positions[index].iter().map(|&v| quantize(v, tolerance)).collect();
+    let attributes: [&[f32]; 4] = [
+        geometry.normals.get(index).map_or(&[], |v| v.as_slice()),
+        geometry.tex_coords.get(index).map_or(&[], |v| v.as_slice()),
+        geometry.tex_coords_1.get(index).map_or(&[], |v| v.as_slice()),
+        geometry.tangents.get(index).map_or(&[], |v| v.as_slice()),
+    ];
+    key.extend(attributes.iter().flat_map(|values| values.iter().map(|&v| quantize(v, ATTRIBUTE_STEP))));
+    key
+}
+
+// sources[i] 番目の頂点を新しい i 番目にする（インデックスは空のまま）
+fn pick(geometry: &Geometry, sources: &[u32]) -> Geometry {
+    fn each<T: Copy>(values: &[T], sources: &[u32]) -> Vec<T> {
+        if values.is_empty() {
+            return Vec::new();
+        }
+        sources.iter().map(|&index| values[index as usize]).collect()
+    }
+    Geometry {
+        positions: each(&geometry.positions, sources),
+        normals: each(&geometry.normals, sources),
+        tex_coords: each(&geometry.tex_coords, sources),
+        tex_coords_1: each(&geometry.tex_coords_1, sources),
+        tangents: each(&geometry.tangents, sources),
+        indices: Vec::new(),
+    }
+}
+
+// indices が使う頂点だけを、使われる順に並べ直す
+fn compact(geometry: &Geometry, indices: &[u32]) -> Geometry {
+    let mut renumbered = vec![None; geometry.positions.len()];
+    let mut sources = Vec::new();
+    let indices = indices
+        .iter()
+        .map(|&index| {
+            *renumbered[index as usize].get_or_insert_with(|| {
+                sources.push(index);
+                sources.len() as u32 - 1
+            })
+        })
+        .collect();
+    Geometry { indices, ..pick(geometry, &sources) }
+}
+
+// 2つ以上の頂点が同じ三角形と、同じ頂点を同じ向きで使う2つ目以降の三角形を除く
+fn without_degenerate(indices: &[u32]) -> Vec<u32> {
+    let mut seen = HashSet::new();
+    let mut kept = Vec::with_capacity(indices.len());
+    for triangle in indices.chunks_exact(3) {
+        let [a, b, c] = [triangle[0], triangle[1], triangle[2]];
+        if a == b || b == c || a == c {
+            continue;
+        }
+        // 回転しても同じ三角形なので、一番小さい番号から始める
+        let start = (0..3).min_by_key(|&i| triangle[i]).unwrap_or(0);
+        if seen.insert([triangle[start], triangle[(start + 1) % 3], triangle[(start + 2) % 3]]) {
+            kept.extend_from_slice(triangle);
+        }
+    }
+    kept
+}
+
+pub fn bounds(positions: &[[f32; 3]]) -> ([f32; 3], [f32; 3]) {
+    positions.iter().fold(([f32::MAX; 3], [f32::MIN; 3]), |(min, max), position| {
+        (std::array::from_fn(|i| min[i].min(position[i])), std::array::from_fn(|i| max[i].max(position[i])))
+    })
+}
+
+fn normalize([x, y, z]: [f32; 3]) -> [f32; 3] {
+    let length = (x * x + y * y + z * z).sqrt();
+    if length > 0.0 {
+        [x / length, y / length, z / length]
+    } else {
+        [0.0, 0.0, 1.0]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // n x n 升の格子（頂点は升ごとに別々に持つので、隣の升と重なる）
+    fn grid(n: usize) -> Geometry {
+        let mut geometry = Geometry::default();
+        for y in 0..n {
+            for x in 0..n {
+                let base = geometry.positions.len() as u32;
+                for (dx, dy) in [(0, 0), (1, 0), (1, 1), (0, 1)] {
+                    let (px, py) = ((x + dx) as f32, (y + dy) as f32);
+                    geometry.positions.push([px, py, 0.0]);
+                    geometry.normals.push([0.0, 0.0, 1.0]);
+                    geometry.tex_coords.push([px / n as f32, py / n as f32]);
+                }
+                geometry.indices.extend([base, base + 1, base + 2, base, base + 2, base + 3]);
+            }
+        }
+        geometry
+    }
+
+    #[test]
+    fn test_weld() {
+        let welded = weld(&grid(4), 0.0);
+        assert_eq!(welded.positions.len(), 25);
+        assert_eq!(welded.normals.len(), 25);
+        assert_eq!(triangle_count(&welded), 32);
+
+        // UV が違う頂点はまとめない
+        let mut seam = grid(1);
+        seam.positions.push([0.0, 0.0, 0.0]);
+        seam.normals.push([0.0, 0.0, 1.0]);
+        seam.tex_coords.push([0.5, 0.5]);
+        seam.indices.extend([4, 1, 2]);
+        assert_eq!(weld(&seam, 0.0).positions.len(), 5);
+
+        // 許容範囲内の位置はまとめ、潰れた三角形とそれだけが使っていた頂点は除く
+        let mut near = grid(1);
+        near.positions[2] = [1.0, 0.0001, 0.0];
+        near.tex_coords[2] = near.tex_coords[1];
+        let welded = weld(&near, 0.001);
+        assert_eq!(triangle_count(&welded), 1);
+        assert_eq!(welded.positions.len(), 3);
+    }
+
+    #[test]
+    fn test_decimate() {
+        let geometry = grid(16);
+        assert_eq!(triangle_count(&geometry), 512);
+        let decimated = decimate(&geometry, 100);
+        let triangles = triangle_count(&decimated);
+        assert!(triangles > 0 && triangles <= 100, "{}", triangles);
+        assert_eq!(decimated.positions.len(), decimated.normals.len());
+        assert!(decimated.indices.iter().all(|&index| (index as usize) < decimated.positions.len()));
+        let (min, max) = bounds(&decimated.positions);
+        assert!(min[0] >= 0.0 && max[0] <= 16.0);
+
+        // 予算に収まっていればまとめるだけ
+        assert_eq!(triangle_count(&decimate(&geometry, 1000)), 512);
+        // 1つも残らない予算でも三角形は残す
+        assert!(triangle_count(&decimate(&geometry, 0)) > 0);
+    }
+}
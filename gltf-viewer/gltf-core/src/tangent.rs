@@ -0,0 +1,235 @@
+// 法線マップ用の接線ベクトル生成
+//
+// glTF が求める MikkTSpace で計算する（mikktspace クレートは元の C の実装を移したもの）。
+// 返す形式は glTF の TANGENT 属性と同じ（接線は法線に直交化し、w 成分に従接線の向き ±1 を持つ）。
+// MikkTSpace の接線は三角形の角ごとに決まり、UV の継ぎ目や鏡像の UV では同じ頂点でも角によって違う。
+// 頂点を分けて属性にするのは呼び出し側（geometry::read）で行う。
+
+// 三角形リストの角ごとの接線を計算する（indices と同じ並びで、余りの角は除く）
+//
+// 法線と UV が揃っていない場合や UV が退化している場合でも、
+// 法線に直交する任意の接線を返すので呼び出し側で欠損を気にする必要はない。
+pub fn generate_tangents(
+    positions: &[[f32; 3]],
+    normals: &[[f32; 3]],
+    tex_coords: &[[f32; 2]],
+    indices: &[u32],
+) -> Vec<[f32; 4]> {
+    let vertex_count = positions.len().min(normals.len()).min(tex_coords.len());
+    let corner_count = indices.len() / 3 * 3;
+    let normal = |i: u32| normals.get(i as usize).copied().unwrap_or([0.0, 0.0, 1.0]);
+    let fallback = |i: u32| {
+        let normal = normal(i);
+        let [x, y, z] = any_perpendicular(normal);
+        [x, y, z, 1.0]
+    };
+    let mut tangents: Vec<[f32; 4]> = indices[..corner_count].iter().map(|&i| fallback(i)).collect();
+
+    // 頂点が範囲外の三角形は MikkTSpace に渡さない
+    let faces: Vec<usize> = (0..corner_count / 3)
+        .filter(|&face| indices[face * 3..face * 3 + 3].iter().all(|&i| (i as usize) < vertex_count))
+        .collect();
+    let mut mesh = Mesh { positions, normals, tex_coords, indices, faces: &faces, tangents: &mut tangents };
+    mikktspace::generate_tangents(&mut mesh);
+
+    // 退化した三角形などで向きが決まらなかった角は、法線に直交する任意の接線にする
+    for (tangent, &i) in tangents.iter_mut().zip(indices) {
+        let xyz = [tangent[0], tangent[1], tangent[2]];
+        let valid = tangent.iter().all(|c| c.is_finite()) && (dot(xyz, xyz) - 1.0).abs() < 1e-3;
+        if !valid {
+            *tangent = fallback(i);
+        }
+    }
+    tangents
+}
+
+// MikkTSpace に渡す三角形リスト（faces は使う三角形の番号）
+struct Mesh<'a> {
+    positions: &'a [[f32; 3]],
+    normals: &'a [[f32; 3]],
+    tex_coords: &'a [[f32; 2]],
+    indices: &'a [u32],
+    faces: &'a [usize],
+    tangents: &'a mut [[f32; 4]],
+}
+
+impl Mesh<'_> {
+    fn corner(&self, face: usize, vert: usize) -> usize {
+        self.faces[face] * 3 + vert
+    }
+
+    fn vertex(&self, face: usize, vert: usize) -> usize {
+        self.indices[self.corner(face, vert)] as usize
+    }
+}
+
+impl mikktspace::Geometry for Mesh<'_> {
+    fn num_faces(&self) -> usize {
+        self.faces.len()
+    }
+
+    fn num_vertices_of_face(&self, _face: usize) -> usize {
+        3
+    }
+
+    fn position(&self, face: usize, vert: usize) -> [f32; 3] {
+        self.positions[self.vertex(face, vert)]
+    }
+
+    fn normal(&self, face: usize, vert: usize) -> [f32; 3] {
+        self.normals[self.vertex(face, vert)]
+    }
+
+    fn tex_coord(&self, face: usize, vert: usize) -> [f32; 2] {
+        self.tex_coords[self.vertex(face, vert)]
+    }
+
+    fn set_tangent_encoded(&mut self, tangent: [f32; 4], face: usize, vert: usize) {
+        let corner = self.corner(face, vert);
+        self.tangents[corner] = tangent;
+    }
+}
+
+// 法線に直交する適当な単位ベクトルを返す
+fn any_perpendicular(normal: [f32; 3]) -> [f32; 3] {
+    let axis = if normal[0].abs() < 0.9 { [1.0, 0.0, 0.0] } else { [0.0, 1.0, 0.0] };
+    normalize(cross(axis, normal)).unwrap_or([1.0, 0.0, 0.0])
+}
+
+fn scale(a: [f32; 3], s: f32) -> [f32; 3] {
+    [a[0] * s, a[1] * s, a[2] * s]
+}
+
+fn dot(a: [f32; 3], b: [f32; 3]) -> f32 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+fn cross(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+fn normalize(a: [f32; 3]) -> Option<[f32; 3]> {
+    let length = dot(a, a).sqrt();
+    if length > 1e-8 {
+        Some(scale(a, 1.0 / length))
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::{FRAC_1_SQRT_2, FRAC_PI_2, FRAC_PI_4};
+
+    struct Quad {
+        positions: Vec<[f32; 3]>,
+        normals: Vec<[f32; 3]>,
+        tex_coords: Vec<[f32; 2]>,
+        indices: Vec<u32>,
+    }
+
+    fn quad() -> Quad {
+        let positions = vec![
+            [0.0, 0.0, 0.0],
+            [1.0, 0.0, 0.0],
+            [1.0, 1.0, 0.0],
+            [0.0, 1.0, 0.0],
+        ];
+        let normals = vec![[0.0, 0.0, 1.0]; 4];
+        let tex_coords = vec![[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0]];
+        let indices = vec![0, 1, 2, 0, 2, 3];
+        Quad { positions, normals, tex_coords, indices }
+    }
+
+    #[test]
+    fn test_tangent_follows_u_direction() {
+        let q = quad();
+        let tangents = generate_tangents(&q.positions, &q.normals, &q.tex_coords, &q.indices);
+
+        assert_eq!(tangents.len(), 6);
+        for t in tangents {
+            assert!((t[0] - 1.0).abs() < 1e-5);
+            assert!(t[1].abs() < 1e-5);
+            assert!(t[2].abs() < 1e-5);
+            assert_eq!(t[3], 1.0);
+        }
+    }
+
+    #[test]
+    fn test_mirrored_uv_flips_handedness() {
+        let mut q = quad();
+        for uv in q.tex_coords.iter_mut() {
+            uv[1] = 1.0 - uv[1];
+        }
+        let tangents = generate_tangents(&q.positions, &q.normals, &q.tex_coords, &q.indices);
+
+        assert!(tangents.iter().all(|t| t[3] == -1.0));
+    }
+
+    fn assert_tangent(actual: [f32; 4], expected: [f32; 4], corner: usize) {
+        for (a, e) in actual.iter().zip(expected) {
+            assert!((a - e).abs() < 1e-5, "corner {}: {:?}, expected {:?}", corner, actual, expected);
+        }
+    }
+
+    #[test]
+    fn test_mirrored_uv_quad() {
+        // 左右の升で UV を鏡像にし、継ぎ目（x = 1）の頂点を共有する
+        let positions = vec![
+            [0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [1.0, 1.0, 0.0], [0.0, 1.0, 0.0],
+            [2.0, 0.0, 0.0], [2.0, 1.0, 0.0],
+        ];
+        let normals = vec![[0.0, 0.0, 1.0]; 6];
+        let tex_coords = vec![[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0], [0.0, 0.0], [0.0, 1.0]];
+        let indices = vec![0, 1, 2, 0, 2, 3, 1, 4, 5, 1, 5, 2];
+        let tangents = generate_tangents(&positions, &normals, &tex_coords, &indices);
+
+        // 継ぎ目の頂点も、角ごとにそれぞれの側の向きと利き手になる
+        assert_eq!(tangents.len(), 12);
+        for (corner, &tangent) in tangents.iter().enumerate() {
+            let expected = if corner < 6 { [1.0, 0.0, 0.0, 1.0] } else { [-1.0, 0.0, 0.0, -1.0] };
+            assert_tangent(tangent, expected, corner);
+        }
+    }
+
+    #[test]
+    fn test_angle_weighted() {
+        // 辺 0-2 を共有する2つの三角形（三角形の単位接線は (1,0,0) と (1,1,0)/√2、利き手はどちらも +1）
+        let positions = vec![[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [-1.0, 1.0, 0.0]];
+        let normals = vec![[0.0, 0.0, 1.0]; 4];
+        let tex_coords = vec![[0.0, 0.0], [1.0, 0.0], [0.0, 1.0], [-1.0, 2.0]];
+        let indices = vec![0, 1, 2, 0, 2, 3];
+        let tangents = generate_tangents(&positions, &normals, &tex_coords, &indices);
+
+        // MikkTSpace の結果: 共有する頂点は三角形ごとの単位接線をその角の大きさで重み付けした和
+        // （頂点 0 の角は 90° と 45°、頂点 2 は 45° と 90°。UV の勾配の大きさで重み付けする方法とは違う向きになる）
+        let weighted = |first: f32, second: f32| {
+            let sum = [first + second * FRAC_1_SQRT_2, second * FRAC_1_SQRT_2, 0.0];
+            let [x, y, z] = normalize(sum).unwrap();
+            [x, y, z, 1.0]
+        };
+        let (vertex0, vertex2) = (weighted(FRAC_PI_2, FRAC_PI_4), weighted(FRAC_PI_4, FRAC_PI_2));
+        let expected = [vertex0, [1.0, 0.0, 0.0, 1.0], vertex2, vertex0, vertex2, [FRAC_1_SQRT_2, FRAC_1_SQRT_2, 0.0, 1.0]];
+        for (corner, (&tangent, expected)) in tangents.iter().zip(expected).enumerate() {
+            assert_tangent(tangent, expected, corner);
+        }
+    }
+
+    #[test]
+    fn test_degenerate_uv_still_orthogonal() {
+        let q = quad();
+        let tex_coords = vec![[0.0, 0.0]; 4];
+        let tangents = generate_tangents(&q.positions, &q.normals, &tex_coords, &q.indices);
+
+        for t in tangents {
+            let tangent = [t[0], t[1], t[2]];
+            assert!(dot(tangent, [0.0, 0.0, 1.0]).abs() < 1e-5);
+            assert!((dot(tangent, tangent) - 1.0).abs() < 1e-5);
+        }
+    }
+}
@@ -0,0 +1,58 @@
+// テクスチャー画像の変換（WebGL へのアップロードと gltf-cli render の両方で使う）
+
+// 画像データをRGBA8に変換（16bit画像は上位バイトのみ使用）
+pub fn to_rgba8(image: &gltf::image::Data) -> Option<Vec<u8>> {
+    use gltf::image::Format;
+
+    let (channels, bytes_per_channel) = match image.format {
+        Format::R8 => (1, 1),
+        Format::R8G8 => (2, 1),
+        Format::R8G8B8 => (3, 1),
+        Format::R8G8B8A8 => (4, 1),
+        Format::R16 => (1, 2),
+        Format::R16G16 => (2, 2),
+        Format::R16G16B16 => (3, 2),
+        Format::R16G16B16A16 => (4, 2),
+        _ => return None,
+    };
+
+    let pixel_size = channels * bytes_per_channel;
+    let rgba = image.pixels
+        .chunks_exact(pixel_size)
+        .flat_map(|pixel| {
+            // リトルエンディアンの16bit値は2バイト目が上位
+            let channel = |c: usize| pixel[c * bytes_per_channel + bytes_per_channel - 1];
+            match channels {
+                1 => [channel(0), channel(0), channel(0), 255],
+                2 => [channel(0), channel(1), 0, 255],
+                3 => [channel(0), channel(1), channel(2), 255],
+                _ => [channel(0), channel(1), channel(2), channel(3)],
+            }
+        })
+        .collect();
+    Some(rgba)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gltf::image::{Data, Format};
+
+    #[test]
+    fn test_rgb_gets_opaque_alpha() {
+        let image = Data { pixels: vec![10, 20, 30, 40, 50, 60], format: Format::R8G8B8, width: 2, height: 1 };
+        assert_eq!(to_rgba8(&image).unwrap(), vec![10, 20, 30, 255, 40, 50, 60, 255]);
+    }
+
+    #[test]
+    fn test_grayscale_is_replicated() {
+        let image = Data { pixels: vec![128], format: Format::R8, width: 1, height: 1 };
+        assert_eq!(to_rgba8(&image).unwrap(), vec![128, 128, 128, 255]);
+    }
+
+    #[test]
+    fn test_16bit_uses_high_byte() {
+        let image = Data { pixels: vec![0x34, 0x12, 0xff, 0xab], format: Format::R16G16, width: 1, height: 1 };
+        assert_eq!(to_rgba8(&image).unwrap(), vec![0x12, 0xab, 0, 255]);
+    }
+}
@@ -0,0 +1,148 @@
+// カメラ操作の感度と慣性の設定
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ControlOptions {
+    // ドラッグ1ピクセルあたりの回転量（ラジアン）
+    pub rotate_sensitivity: f32,
+    // ホイール1単位あたりの距離の変化率
+    pub zoom_sensitivity: f32,
+    // ドラッグ1ピクセルあたりの移動量（カメラ距離に対する比率）
+    pub pan_sensitivity: f32,
+    // 1フレームごとに残る速度の割合（0で慣性なし）
+    pub damping: f32,
+}
+
+impl Default for ControlOptions {
+    fn default() -> Self {
+        ControlOptions {
+            rotate_sensitivity: 0.01,
+            zoom_sensitivity: 0.001,
+            pan_sensitivity: 0.002,
+            damping: 0.0,
+        }
+    }
+}
+
+impl ControlOptions {
+    pub fn new(rotate: f32, zoom: f32, pan: f32, damping: f32) -> Result<Self, String> {
+        for (name, value) in [("rotate", rotate), ("zoom", zoom), ("pan", pan)] {
+            if !value.is_finite() || value < 0.0 {
+                return Err(format!("{} sensitivity must be a non-negative number, got {}", name, value));
+            }
+        }
+        if !(0.0..1.0).contains(&damping) {
+            return Err(format!("damping must be in the range [0, 1), got {}", damping));
+        }
+        Ok(ControlOptions {
+            rotate_sensitivity: rotate,
+            zoom_sensitivity: zoom,
+            pan_sensitivity: pan,
+            damping,
+        })
+    }
+}
+
+// ドラッグを離した後も回転を続けるための慣性
+#[derive(Debug, Default)]
+pub struct Inertia {
+    velocity: [f32; 2],
+    active: bool,
+    // 最後にドラッグで動いた時刻（ミリ秒）
+    last_move: f64,
+}
+
+// これ以下の速度になったら停止する
+const MIN_VELOCITY: f32 = 1e-4;
+
+// 最後の移動からこれより長く止めてから離したら慣性を付けない（ミリ秒）
+const RELEASE_TIMEOUT_MS: f64 = 100.0;
+
+impl Inertia {
+    // ドラッグ中の回転量と時刻を記録（ドラッグ中は慣性を止める）
+    pub fn track(&mut self, delta: [f32; 2], now: f64) {
+        self.velocity = delta;
+        self.active = false;
+        self.last_move = now;
+    }
+
+    // ドラッグ終了時に慣性を開始
+    // 止めてから離したときは、止める前の最後の移動量で回り出さないようにする
+    pub fn release(&mut self, now: f64) {
+        if now - self.last_move > RELEASE_TIMEOUT_MS {
+            self.stop();
+            return;
+        }
+        self.active = true;
+    }
+
+    pub fn stop(&mut self) {
+        self.velocity = [0.0, 0.0];
+        self.active = false;
+    }
+
+    // 1フレーム分の回転量を返し、速度を減衰させる
+    pub fn step(&mut self, damping: f32) -> Option<[f32; 2]> {
+        if !self.active || damping <= 0.0 {
+            return None;
+        }
+
+        self.velocity = [self.velocity[0] * damping, self.velocity[1] * damping];
+        if self.velocity[0].abs() < MIN_VELOCITY && self.velocity[1].abs() < MIN_VELOCITY {
+            self.stop();
+            return None;
+        }
+        Some(self.velocity)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_options_validation() {
+        assert!(ControlOptions::new(0.01, 0.001, 0.002, 0.9).is_ok());
+        assert!(ControlOptions::new(-0.01, 0.001, 0.002, 0.9).is_err());
+        assert!(ControlOptions::new(0.01, f32::NAN, 0.002, 0.9).is_err());
+        assert!(ControlOptions::new(0.01, 0.001, 0.002, 1.0).is_err());
+    }
+
+    #[test]
+    fn test_inertia_decays_and_stops() {
+        let mut inertia = Inertia::default();
+        inertia.track([0.1, 0.0], 0.0);
+        assert_eq!(inertia.step(0.5), None); // ドラッグ中は動かない
+
+        inertia.release(16.0);
+        let first = inertia.step(0.5).unwrap();
+        assert!((first[0] - 0.05).abs() < 1e-6);
+
+        let mut frames = 0;
+        while inertia.step(0.5).is_some() {
+            frames += 1;
+            assert!(frames < 100);
+        }
+    }
+
+    #[test]
+    fn test_no_inertia_without_damping() {
+        let mut inertia = Inertia::default();
+        inertia.track([0.1, 0.1], 0.0);
+        inertia.release(16.0);
+        assert_eq!(inertia.step(0.0), None);
+    }
+
+    #[test]
+    fn test_no_inertia_after_pause() {
+        // 動かしてからしばらく止めて離したら回り続けない
+        let mut inertia = Inertia::default();
+        inertia.track([0.1, 0.0], 1000.0);
+        inertia.release(1000.0 + RELEASE_TIMEOUT_MS + 400.0);
+        assert_eq!(inertia.step(0.5), None);
+
+        // 直前まで動かしていれば慣性が付く
+        inertia.track([0.1, 0.0], 2000.0);
+        inertia.release(2000.0 + RELEASE_TIMEOUT_MS / 2.0);
+        assert!(inertia.step(0.5).is_some());
+    }
+}
@@ -11,7 +11,69 @@ extern "C" {
 }
 
 macro_rules! console_log {
-    ($($t:tt)*) => (log(&format_args!($($t)*).to_string()))
+    ($($t:tt)*) => ($crate::log(&format_args!($($t)*).to_string()))
+}
+
+mod controls;
+mod material;
+mod shaders;
+mod state;
+
+use controls::{ControlOptions, Inertia};
+use gltf_core::{geometry, model, Model};
+use material::{Material, MaterialUniforms, TextureCache};
+use state::{CameraState, ModelId, SceneState, ViewerState};
+
+// 頂点レイアウト: position(3) + normal(3) + uv0(2) + uv1(2) + tangent(4)
+const VERTEX_STRIDE: usize = 14;
+
+// プリミティブから抽出したジオメトリ（インターリーブ済み頂点とインデックス）
+struct PrimitiveGeometry {
+    vertices: Vec<f32>,
+    indices: Vec<u16>,
+}
+
+// 同じマテリアルで描画するインデックス範囲
+struct DrawBatch {
+    index_start: i32,
+    index_count: i32,
+    material: Option<usize>,
+}
+
+// 頂点属性をインターリーブ形式に詰める
+fn interleave_vertices(
+    positions: &[[f32; 3]],
+    normals: &[[f32; 3]],
+    tex_coords: [&[[f32; 2]]; 2],
+    tangents: &[[f32; 4]],
+) -> Vec<f32> {
+    let mut vertices = Vec::with_capacity(positions.len() * VERTEX_STRIDE);
+    for (i, position) in positions.iter().enumerate() {
+        vertices.extend_from_slice(position);
+        vertices.extend_from_slice(normals.get(i).unwrap_or(&[0.0, 0.0, 1.0]));
+        for set in tex_coords {
+            vertices.extend_from_slice(set.get(i).unwrap_or(&[0.0, 0.0]));
+        }
+        vertices.extend_from_slice(tangents.get(i).unwrap_or(&[1.0, 0.0, 0.0, 1.0]));
+    }
+    vertices
+}
+
+// 描画に使う行列
+// 位置と法線は頂点シェーダーでワールド空間に移し、ワールド空間のカメラ位置と比べる
+struct Transforms {
+    model: glm::Mat4,
+    // 法線はモデル行列の逆転置で変換する（拡大縮小しても面に垂直なまま）
+    normal: glm::Mat3,
+    mvp: glm::Mat4,
+}
+
+impl Transforms {
+    fn new(projection: &glm::Mat4, view: &glm::Mat4, model_scale: f32) -> Transforms {
+        let model = glm::scaling(&glm::vec3(model_scale, model_scale, model_scale));
+        let normal = glm::mat4_to_mat3(&model).try_inverse().unwrap_or_else(glm::Mat3::identity).transpose();
+        Transforms { model, normal, mvp: projection * view * model }
+    }
 }
 
 // 3Dビューアの状態を管理する構造体
@@ -22,14 +84,31 @@ pub struct GltfViewer {
     vertex_buffer: WebGlBuffer,
     index_buffer: WebGlBuffer,
     index_count: i32,
+    batches: Vec<DrawBatch>,
+    // マテリアル関連
+    materials: Vec<Material>,
+    default_material: Material,
+    // 読み込み中のモデル（テストボックスの場合は None）
+    model_id: Option<ModelId>,
+    // 復元した表示の状態（まだ描画には使わず、次の保存に書き出す）
+    scene_state: SceneState,
     // カメラ関連
     view_matrix: glm::Mat4,
     projection_matrix: glm::Mat4,
     camera_position: glm::Vec3,
     camera_target: glm::Vec3,
+    // 操作感度と慣性
+    control_options: ControlOptions,
+    inertia: Inertia,
+    // モデルの表示倍率（単位の違うモデルを合わせる）
+    model_scale: f32,
     // uniform locations
     u_mvp_matrix: WebGlUniformLocation,
-    u_color: WebGlUniformLocation,
+    u_model_matrix: Option<WebGlUniformLocation>,
+    u_normal_matrix: Option<WebGlUniformLocation>,
+    u_camera_position: Option<WebGlUniformLocation>,
+    u_light_direction: Option<WebGlUniformLocation>,
+    material_uniforms: MaterialUniforms,
 }
 
 #[wasm_bindgen]
@@ -54,32 +133,16 @@ impl GltfViewer {
             .dyn_into::<WebGl2RenderingContext>()?;
         
         // シェーダープログラムを作成
-        let vertex_shader_source = r#"#version 300 es
-            in vec3 a_position;
-            uniform mat4 u_mvp_matrix;
-            
-            void main() {
-                gl_Position = u_mvp_matrix * vec4(a_position, 1.0);
-            }
-        "#;
-        
-        let fragment_shader_source = r#"#version 300 es
-            precision mediump float;
-            uniform vec3 u_color;
-            out vec4 fragColor;
-            
-            void main() {
-                fragColor = vec4(u_color, 1.0);
-            }
-        "#;
-        
-        let program = Self::create_program(&gl, vertex_shader_source, fragment_shader_source)?;
+        let program = Self::create_program(&gl, shaders::VERTEX_SHADER, shaders::FRAGMENT_SHADER)?;
         
         // uniform locationを取得
         let u_mvp_matrix = gl.get_uniform_location(&program, "u_mvp_matrix")
             .ok_or("Failed to get u_mvp_matrix uniform location")?;
-        let u_color = gl.get_uniform_location(&program, "u_color")
-            .ok_or("Failed to get u_color uniform location")?;
+        let u_model_matrix = gl.get_uniform_location(&program, "u_model_matrix");
+        let u_normal_matrix = gl.get_uniform_location(&program, "u_normal_matrix");
+        let u_camera_position = gl.get_uniform_location(&program, "u_camera_position");
+        let u_light_direction = gl.get_uniform_location(&program, "u_light_direction");
+        let material_uniforms = MaterialUniforms::new(&gl, &program);
         
         // バッファを作成
         let vertex_buffer = gl.create_buffer()
@@ -112,12 +175,24 @@ impl GltfViewer {
             vertex_buffer,
             index_buffer,
             index_count: 0,
+            batches: Vec::new(),
+            materials: Vec::new(),
+            default_material: Material::from_color([0.8, 0.4, 0.2, 1.0]), // オレンジ色
+            model_id: None,
+            scene_state: SceneState::default(),
             view_matrix,
             projection_matrix,
             camera_position,
             camera_target,
+            control_options: ControlOptions::default(),
+            inertia: Inertia::default(),
+            model_scale: 1.0,
             u_mvp_matrix,
-            u_color,
+            u_model_matrix,
+            u_normal_matrix,
+            u_camera_position,
+            u_light_direction,
+            material_uniforms,
         })
     }
     
@@ -127,19 +202,28 @@ impl GltfViewer {
         console_log!("Creating test box...");
         
         // 立方体の頂点データ
-        let vertices: [f32; 24] = [
+        let positions: [[f32; 3]; 8] = [
             // 前面
-            -1.0, -1.0,  1.0,
-             1.0, -1.0,  1.0,
-             1.0,  1.0,  1.0,
-            -1.0,  1.0,  1.0,
+            [-1.0, -1.0,  1.0],
+            [ 1.0, -1.0,  1.0],
+            [ 1.0,  1.0,  1.0],
+            [-1.0,  1.0,  1.0],
             // 後面
-            -1.0, -1.0, -1.0,
-            -1.0,  1.0, -1.0,
-             1.0,  1.0, -1.0,
-             1.0, -1.0, -1.0,
+            [-1.0, -1.0, -1.0],
+            [-1.0,  1.0, -1.0],
+            [ 1.0,  1.0, -1.0],
+            [ 1.0, -1.0, -1.0],
         ];
         
+        // 頂点を共有しているので法線は中心からの方向で近似する
+        let normals: Vec<[f32; 3]> = positions.iter()
+            .map(|p| {
+                let n = glm::normalize(&glm::vec3(p[0], p[1], p[2]));
+                [n.x, n.y, n.z]
+            })
+            .collect();
+        let vertices = interleave_vertices(&positions, &normals, [&[], &[]], &[]);
+        
         // インデックスデータ
         let indices: [u16; 36] = [
             0, 1, 2, 0, 2, 3,    // 前面
@@ -174,6 +258,10 @@ impl GltfViewer {
             );
         }
         self.index_count = 36;
+        self.batches = vec![DrawBatch { index_start: 0, index_count: 36, material: None }];
+        self.materials.clear();
+        self.model_id = None;
+        self.scene_state = SceneState::default();
         
         console_log!("Test box created");
         Ok(())
@@ -186,55 +274,161 @@ impl GltfViewer {
             return Ok(()); // ジオメトリがない場合は何もしない
         }
         
+        // ドラッグ終了後の慣性回転を適用
+        if let Some([delta_phi, delta_theta]) = self.inertia.step(self.control_options.damping) {
+            self.orbit(delta_phi, delta_theta);
+        }
+        
         // 画面をクリア
         self.gl.clear(WebGl2RenderingContext::COLOR_BUFFER_BIT | WebGl2RenderingContext::DEPTH_BUFFER_BIT);
         
         // シェーダープログラムを使用
         self.gl.use_program(Some(&self.program));
         
-        // MVP行列を計算
-        let model_matrix = glm::Mat4::identity();
-        let mvp_matrix = self.projection_matrix * self.view_matrix * model_matrix;
+        // MVP行列とモデル行列・法線行列を計算
+        let transforms = Transforms::new(&self.projection_matrix, &self.view_matrix, self.model_scale);
         
         // ユニフォームを設定
         self.gl.uniform_matrix4fv_with_f32_array(
             Some(&self.u_mvp_matrix),
             false,
-            mvp_matrix.as_slice(),
+            transforms.mvp.as_slice(),
+        );
+        self.gl.uniform_matrix4fv_with_f32_array(
+            self.u_model_matrix.as_ref(),
+            false,
+            transforms.model.as_slice(),
+        );
+        self.gl.uniform_matrix3fv_with_f32_array(
+            self.u_normal_matrix.as_ref(),
+            false,
+            transforms.normal.as_slice(),
         );
         
-        self.gl.uniform3f(Some(&self.u_color), 0.8, 0.4, 0.2); // オレンジ色
+        self.gl.uniform3f(
+            self.u_camera_position.as_ref(),
+            self.camera_position.x,
+            self.camera_position.y,
+            self.camera_position.z,
+        );
+        self.gl.uniform3f(self.u_light_direction.as_ref(), -0.5, -1.0, -0.3);
         
-        // 頂点属性を設定
+        // 頂点属性を設定（position, normal, uv0, uv1, tangent）
         self.gl.bind_buffer(WebGl2RenderingContext::ARRAY_BUFFER, Some(&self.vertex_buffer));
-        self.gl.vertex_attrib_pointer_with_i32(0, 3, WebGl2RenderingContext::FLOAT, false, 0, 0);
-        self.gl.enable_vertex_attrib_array(0);
+        let stride = (VERTEX_STRIDE * 4) as i32;
+        for (location, size, offset) in [(0, 3, 0), (1, 3, 3), (2, 2, 6), (3, 2, 8), (4, 4, 10)] {
+            self.gl.vertex_attrib_pointer_with_i32(
+                location,
+                size,
+                WebGl2RenderingContext::FLOAT,
+                false,
+                stride,
+                offset * 4,
+            );
+            self.gl.enable_vertex_attrib_array(location);
+        }
         
         // インデックスバッファをバインド
         self.gl.bind_buffer(WebGl2RenderingContext::ELEMENT_ARRAY_BUFFER, Some(&self.index_buffer));
         
-        // 描画
-        self.gl.draw_elements_with_i32(
-            WebGl2RenderingContext::TRIANGLES,
-            self.index_count,
-            WebGl2RenderingContext::UNSIGNED_SHORT,
-            0,
-        );
+        // マテリアルごとに描画
+        for batch in &self.batches {
+            let material = batch.material
+                .and_then(|i| self.materials.get(i))
+                .unwrap_or(&self.default_material);
+            material.bind(&self.gl, &self.material_uniforms);
+            
+            self.gl.draw_elements_with_i32(
+                WebGl2RenderingContext::TRIANGLES,
+                batch.index_count,
+                WebGl2RenderingContext::UNSIGNED_SHORT,
+                batch.index_start * 2,
+            );
+        }
         
         Ok(())
     }
     
-    // カメラを回転
+    // カメラを回転（ドラッグ量はピクセル単位）
     #[wasm_bindgen]
     pub fn rotate_camera(&mut self, delta_x: f32, delta_y: f32) {
+        let delta_phi = delta_x * self.control_options.rotate_sensitivity;
+        let delta_theta = delta_y * self.control_options.rotate_sensitivity;
+        self.inertia.track([delta_phi, delta_theta], js_sys::Date::now());
+        self.orbit(delta_phi, delta_theta);
+    }
+    
+    // ドラッグ終了を通知（慣性が有効なら回転を続ける）
+    #[wasm_bindgen]
+    pub fn end_drag(&mut self) {
+        self.inertia.release(js_sys::Date::now());
+    }
+    
+    // カメラを注視点に近づける/遠ざける（ホイールのdeltaY単位）
+    #[wasm_bindgen]
+    pub fn zoom_camera(&mut self, delta: f32) {
+        let offset = self.camera_position - self.camera_target;
+        let distance = glm::length(&offset);
+        let scale = (1.0 + delta * self.control_options.zoom_sensitivity).max(0.1);
+        let new_distance = (distance * scale).clamp(0.2, 90.0);
+        
+        self.camera_position = self.camera_target + offset * (new_distance / distance);
+        self.update_view_matrix();
+    }
+    
+    // カメラと注視点を画面に平行に移動（ドラッグ量はピクセル単位）
+    #[wasm_bindgen]
+    pub fn pan_camera(&mut self, delta_x: f32, delta_y: f32) {
+        let forward = glm::normalize(&(self.camera_target - self.camera_position));
+        let right = glm::normalize(&glm::cross(&forward, &glm::vec3(0.0, 1.0, 0.0)));
+        let up = glm::cross(&right, &forward);
+        
+        let distance = glm::length(&(self.camera_position - self.camera_target));
+        let scale = distance * self.control_options.pan_sensitivity;
+        let offset = right * (-delta_x * scale) + up * (delta_y * scale);
+        
+        self.camera_position += offset;
+        self.camera_target += offset;
+        self.update_view_matrix();
+    }
+    
+    // 操作感度と慣性の減衰率を設定
+    #[wasm_bindgen]
+    pub fn set_control_options(
+        &mut self,
+        rotate_sensitivity: f32,
+        zoom_sensitivity: f32,
+        pan_sensitivity: f32,
+        damping: f32,
+    ) -> Result<(), JsValue> {
+        self.control_options = ControlOptions::new(
+            rotate_sensitivity,
+            zoom_sensitivity,
+            pan_sensitivity,
+            damping,
+        ).map_err(|e| JsValue::from_str(&e))?;
+        self.inertia.stop();
+        Ok(())
+    }
+    
+    // モデルの表示倍率を calc の式（"1/25.4" など）で設定し、評価した値を返す
+    // エラーの文言は gltf-cli の --scale と同じ
+    #[wasm_bindgen]
+    pub fn set_model_scale(&mut self, expression: &str) -> Result<f64, JsValue> {
+        let scale = gltf_core::expression::positive(expression).map_err(|e| JsValue::from_str(&e))?;
+        self.model_scale = scale as f32;
+        Ok(scale)
+    }
+    
+    // 球面座標でカメラを回転
+    fn orbit(&mut self, delta_phi: f32, delta_theta: f32) {
         let distance = glm::length(&(self.camera_position - self.camera_target));
         
-        // 球面座標でカメラを回転
         let to_target = self.camera_position - self.camera_target;
-        let phi = to_target.z.atan2(to_target.x) + delta_x * 0.01;
-        let theta = (to_target.y / distance).acos() + delta_y * 0.01;
+        let phi = to_target.z.atan2(to_target.x) + delta_phi;
+        let theta = (to_target.y / distance).acos() + delta_theta;
         
-        let theta = theta.max(0.1).min(std::f32::consts::PI - 0.1);
+        let theta = theta.clamp(0.1, std::f32::consts::PI - 0.1);
         
         self.camera_position = self.camera_target + glm::vec3(
             distance * theta.sin() * phi.cos(),
@@ -242,10 +436,55 @@ impl GltfViewer {
             distance * theta.sin() * phi.sin(),
         );
         
+        self.update_view_matrix();
+    }
+    
+    fn update_view_matrix(&mut self) {
         let up = glm::vec3(0.0, 1.0, 0.0);
         self.view_matrix = glm::look_at(&self.camera_position, &self.camera_target, &up);
     }
     
+    // 現在のセッション状態をJSONで保存
+    #[wasm_bindgen]
+    pub fn save_state(&self) -> Result<String, JsValue> {
+        let state = ViewerState {
+            version: state::STATE_VERSION,
+            model: self.model_id.clone(),
+            camera: CameraState {
+                position: self.camera_position.into(),
+                target: self.camera_target.into(),
+            },
+            scene: self.scene_state.clone(),
+        };
+        state.to_json().map_err(|e| JsValue::from_str(&e))
+    }
+    
+    // 保存したJSONからセッション状態を復元
+    #[wasm_bindgen]
+    pub fn load_state(&mut self, json: &str) -> Result<(), JsValue> {
+        let state = ViewerState::from_json(json).map_err(|e| JsValue::from_str(&e))?;
+        
+        // ノードやマテリアルの番号は別のモデルでは意味がないので、カメラだけ戻す
+        if state.model != self.model_id {
+            console_log!(
+                "Warning: state was saved for a different model ({:?}), current model is {:?}",
+                state.model,
+                self.model_id
+            );
+            self.scene_state = SceneState::default();
+        } else {
+            self.scene_state = state.scene;
+        }
+        
+        self.camera_position = glm::Vec3::from(state.camera.position);
+        self.camera_target = glm::Vec3::from(state.camera.target);
+        self.inertia.stop();
+        self.update_view_matrix();
+        
+        console_log!("Viewer state restored");
+        Ok(())
+    }
+    
     // ビューポートサイズを更新
     #[wasm_bindgen]
     pub fn resize(&mut self, width: u32, height: u32) {
@@ -269,39 +508,24 @@ impl GltfViewer {
         }
         
         // GLBファイルかどうかチェック（最初の4バイトが"glTF"）
-        let is_glb = &gltf_data[0..4] == b"glTF";
+        let is_glb = model::is_glb(gltf_data);
         console_log!("File type: {}", if is_glb { "GLB (binary)" } else { "GLTF (JSON)" });
         
         // GLTFファイルをパース
-        let result = if is_glb {
-            // GLBファイルの場合
-            gltf::import_slice(gltf_data)
-        } else {
-            // JSONファイルの場合、文字列として解析を試行
-            match std::str::from_utf8(gltf_data) {
-                Ok(json_str) => {
-                    console_log!("Parsing as JSON GLTF, {} characters", json_str.len());
-                    gltf::import_slice(gltf_data)
-                }
-                Err(e) => {
-                    console_log!("Not valid UTF-8, treating as binary: {:?}", e);
-                    gltf::import_slice(gltf_data)
-                }
-            }
-        };
-        
-        let (gltf, buffers, _images) = result.map_err(|e| {
+        let Model { document: gltf, buffers, images, .. } = Model::from_slice(gltf_data).map_err(|e| {
             console_log!("GLTF import error details: {:?}", e);
-            let error_msg = format!("Failed to import GLTF file: {}", e);
-            console_log!("Error message: {}", error_msg);
-            JsValue::from_str(&error_msg)
+            JsValue::from_str(&format!("Failed to import GLTF file: {}", e))
         })?;
         
         console_log!("GLTF imported successfully!");
+        self.model_id = Some(ModelId::from_bytes(gltf_data));
+        self.scene_state = SceneState::default();
         console_log!("- Scenes: {}", gltf.scenes().count());
         console_log!("- Meshes: {}", gltf.meshes().count());
         console_log!("- Buffers: {}", buffers.len());
         console_log!("- Nodes: {}", gltf.nodes().count());
+        console_log!("- Materials: {}", gltf.materials().count());
+        console_log!("- Images: {}", images.len());
         
         if gltf.meshes().count() == 0 {
             console_log!("No meshes found in GLTF file, creating fallback box");
@@ -311,8 +535,15 @@ impl GltfViewer {
         // 既存のジオメトリをクリア
         self.clear_geometry();
         
+        // マテリアルとテクスチャをアップロード
+        let mut textures = TextureCache::new(images);
+        self.materials = gltf.materials()
+            .map(|material| Material::from_gltf(&self.gl, &material, &mut textures))
+            .collect();
+        
         let mut all_vertices = Vec::new();
         let mut all_indices = Vec::new();
+        let mut batches = Vec::new();
         let mut index_offset = 0u16;
         
         // 各メッシュを処理
@@ -322,17 +553,24 @@ impl GltfViewer {
             for (prim_index, primitive) in mesh.primitives().enumerate() {
                 console_log!("  Processing primitive {}", prim_index);
                 match self.process_primitive(&primitive, &buffers) {
-                    Ok(Some((vertices, indices))) => {
+                    Ok(Some(geometry)) => {
+                        let vertex_count = geometry.vertices.len() / VERTEX_STRIDE;
+                        
                         // インデックスをオフセット調整して追加
-                        let adjusted_indices: Vec<u16> = indices.iter()
+                        let adjusted_indices: Vec<u16> = geometry.indices.iter()
                             .map(|&i| i + index_offset)
                             .collect();
                         
-                        all_vertices.extend_from_slice(&vertices);
+                        batches.push(DrawBatch {
+                            index_start: all_indices.len() as i32,
+                            index_count: adjusted_indices.len() as i32,
+                            material: primitive.material().index(),
+                        });
+                        all_vertices.extend_from_slice(&geometry.vertices);
                         all_indices.extend_from_slice(&adjusted_indices);
-                        index_offset += (vertices.len() / 3) as u16;
+                        index_offset += vertex_count as u16;
                         
-                        console_log!("    Added {} vertices, {} indices", vertices.len() / 3, indices.len());
+                        console_log!("    Added {} vertices, {} indices", vertex_count, geometry.indices.len());
                     }
                     Ok(None) => {
                         console_log!("    Primitive {} skipped (no geometry)", prim_index);
@@ -350,10 +588,11 @@ impl GltfViewer {
             return self.create_test_box();
         }
         
-        console_log!("Total vertices: {}, Total indices: {}", all_vertices.len() / 3, all_indices.len());
+        console_log!("Total vertices: {}, Total indices: {}", all_vertices.len() / VERTEX_STRIDE, all_indices.len());
         
         // バッファにデータをアップロード
         self.upload_geometry(&all_vertices, &all_indices)?;
+        self.batches = batches;
         
         console_log!("GLTF loading completed successfully");
         Ok(())
@@ -364,21 +603,15 @@ impl GltfViewer {
         &mut self, 
         primitive: &gltf::Primitive, 
         buffers: &[gltf::buffer::Data]
-    ) -> Result<Option<(Vec<f32>, Vec<u16>)>, JsValue> {
+    ) -> Result<Option<PrimitiveGeometry>, JsValue> {
         console_log!("    Processing primitive with mode: {:?}", primitive.mode());
         
-        let reader = primitive.reader(|buffer| Some(&buffers[buffer.index()]));
-        
-        // 位置データを取得
-        let positions = match reader.read_positions() {
-            Some(pos_iter) => pos_iter.collect::<Vec<[f32; 3]>>(),
-            None => {
-                console_log!("    No position data found in primitive");
-                return Ok(None);
-            }
+        let Some(geometry) = geometry::read(primitive, buffers) else {
+            console_log!("    No position data found in primitive");
+            return Ok(None);
         };
         
-        console_log!("    Found {} positions in primitive", positions.len());
+        console_log!("    Found {} positions in primitive", geometry.positions.len());
         
         // 三角形以外のプリミティブタイプをチェック
         if primitive.mode() != gltf::mesh::Mode::Triangles {
@@ -386,39 +619,19 @@ impl GltfViewer {
             // 三角形以外でも処理を続行
         }
         
-        // 頂点データを平坦化
-        let vertices: Vec<f32> = positions.iter()
-            .flat_map(|pos| pos.iter().cloned())
-            .collect();
+        let tex_coords = [geometry.tex_coords.as_slice(), geometry.tex_coords_1.as_slice()];
+        let vertices = interleave_vertices(&geometry.positions, &geometry.normals, tex_coords, &geometry.tangents);
         
-        // インデックスデータを取得
-        let indices: Vec<u16> = if let Some(indices_reader) = reader.read_indices() {
-            match indices_reader {
-                gltf::mesh::util::ReadIndices::U8(iter) => {
-                    console_log!("    Using U8 indices");
-                    iter.map(|i| i as u16).collect()
-                },
-                gltf::mesh::util::ReadIndices::U16(iter) => {
-                    console_log!("    Using U16 indices");
-                    iter.collect()
-                },
-                gltf::mesh::util::ReadIndices::U32(iter) => {
-                    console_log!("    Using U32 indices (converting to U16)");
-                    iter.map(|i| {
-                        if i > u16::MAX as u32 {
-                            console_log!("    Warning: Index {} exceeds u16::MAX, clamping", i);
-                            u16::MAX
-                        } else {
-                            i as u16
-                        }
-                    }).collect()
-                },
-            }
-        } else {
-            // インデックスがない場合は順番に生成
-            console_log!("    No indices found, generating sequential indices");
-            (0..positions.len() as u16).collect()
-        };
+        let indices: Vec<u16> = geometry.indices.into_iter()
+            .map(|i| {
+                if i > u16::MAX as u32 {
+                    console_log!("    Warning: Index {} exceeds u16::MAX, clamping", i);
+                    u16::MAX
+                } else {
+                    i as u16
+                }
+            })
+            .collect();
         
         console_log!("    Generated {} indices for primitive", indices.len());
         
@@ -433,7 +646,7 @@ impl GltfViewer {
             return Ok(None);
         }
         
-        Ok(Some((vertices, indices)))
+        Ok(Some(PrimitiveGeometry { vertices, indices }))
     }
     
     // ジオメトリをクリア
@@ -483,7 +696,7 @@ impl GltfViewer {
         // レンダリング時に使用するインデックス数を保存
         self.index_count = indices.len() as i32;
         
-        console_log!("Uploaded geometry: {} vertices, {} indices", vertices.len() / 3, indices.len());
+        console_log!("Uploaded geometry: {} vertices, {} indices", vertices.len() / VERTEX_STRIDE, indices.len());
         
         Ok(())
     }
@@ -540,3 +753,29 @@ impl GltfViewer {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transforms() {
+        let view = glm::look_at(&glm::vec3(3.0, 3.0, 5.0), &glm::vec3(0.0, 0.0, 0.0), &glm::vec3(0.0, 1.0, 0.0));
+        let projection = glm::perspective(1.5, 45.0_f32.to_radians(), 0.1, 100.0);
+        let transforms = Transforms::new(&projection, &view, 2.5);
+
+        // 位置はワールド空間で2.5倍になり、MVP はその位置をビューと投影で変換したもの
+        let position = glm::vec4(1.0, -2.0, 0.5, 1.0);
+        let world = transforms.model * position;
+        assert!(glm::distance(&world, &glm::vec4(2.5, -5.0, 1.25, 1.0)) < 1e-6);
+        assert!(glm::distance(&(transforms.mvp * position), &(projection * view * world)) < 1e-4);
+
+        // 法線は向きを変えない
+        let normal = glm::normalize(&glm::vec3(1.0, 2.0, -2.0));
+        assert!(glm::distance(&glm::normalize(&(transforms.normal * normal)), &normal) < 1e-6);
+
+        let identity = Transforms::new(&projection, &view, 1.0);
+        assert_eq!(identity.model, glm::Mat4::identity());
+        assert_eq!(identity.normal, glm::Mat3::identity());
+    }
+}
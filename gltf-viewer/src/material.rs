@@ -0,0 +1,278 @@
+// マテリアルとテクスチャの管理
+use std::collections::HashMap;
+
+use wasm_bindgen::prelude::*;
+use web_sys::{WebGl2RenderingContext, WebGlProgram, WebGlTexture, WebGlUniformLocation};
+use gltf_core::geometry::TEX_COORD_SETS;
+use gltf_core::texture::to_rgba8;
+
+// テクスチャユニットの割り当て
+const BASE_COLOR_UNIT: u32 = 0;
+const METALLIC_ROUGHNESS_UNIT: u32 = 1;
+const NORMAL_UNIT: u32 = 2;
+const OCCLUSION_UNIT: u32 = 3;
+const EMISSIVE_UNIT: u32 = 4;
+
+// マテリアル関連のuniform location
+//
+// 最適化で使われないuniformは取得できないことがあるので Option で保持する
+pub struct MaterialUniforms {
+    base_color_factor: Option<WebGlUniformLocation>,
+    metallic_factor: Option<WebGlUniformLocation>,
+    roughness_factor: Option<WebGlUniformLocation>,
+    normal_scale: Option<WebGlUniformLocation>,
+    occlusion_strength: Option<WebGlUniformLocation>,
+    emissive_factor: Option<WebGlUniformLocation>,
+    texture_slots: [TextureSlot; 5],
+}
+
+// テクスチャ1枚分のサンプラーと有無フラグ、使うUVセット
+struct TextureSlot {
+    unit: u32,
+    sampler: Option<WebGlUniformLocation>,
+    enabled: Option<WebGlUniformLocation>,
+    uv_set: Option<WebGlUniformLocation>,
+}
+
+impl MaterialUniforms {
+    pub fn new(gl: &WebGl2RenderingContext, program: &WebGlProgram) -> Self {
+        let slot = |unit: u32, name: &str| TextureSlot {
+            unit,
+            sampler: gl.get_uniform_location(program, &format!("u_{}_texture", name)),
+            enabled: gl.get_uniform_location(program, &format!("u_has_{}_texture", name)),
+            uv_set: gl.get_uniform_location(program, &format!("u_{}_uv_set", name)),
+        };
+
+        MaterialUniforms {
+            base_color_factor: gl.get_uniform_location(program, "u_base_color_factor"),
+            metallic_factor: gl.get_uniform_location(program, "u_metallic_factor"),
+            roughness_factor: gl.get_uniform_location(program, "u_roughness_factor"),
+            normal_scale: gl.get_uniform_location(program, "u_normal_scale"),
+            occlusion_strength: gl.get_uniform_location(program, "u_occlusion_strength"),
+            emissive_factor: gl.get_uniform_location(program, "u_emissive_factor"),
+            texture_slots: [
+                slot(BASE_COLOR_UNIT, "base_color"),
+                slot(METALLIC_ROUGHNESS_UNIT, "metallic_roughness"),
+                slot(NORMAL_UNIT, "normal"),
+                slot(OCCLUSION_UNIT, "occlusion"),
+                slot(EMISSIVE_UNIT, "emissive"),
+            ],
+        }
+    }
+}
+
+// GPU上のマテリアル
+pub struct Material {
+    pub base_color_factor: [f32; 4],
+    pub metallic_factor: f32,
+    pub roughness_factor: f32,
+    pub normal_scale: f32,
+    pub occlusion_strength: f32,
+    pub emissive_factor: [f32; 3],
+    pub base_color_texture: Option<WebGlTexture>,
+    pub metallic_roughness_texture: Option<WebGlTexture>,
+    pub normal_texture: Option<WebGlTexture>,
+    pub occlusion_texture: Option<WebGlTexture>,
+    pub emissive_texture: Option<WebGlTexture>,
+    // テクスチャごとのUVセット（base color, metallic-roughness, normal, occlusion, emissive の順）
+    pub uv_sets: [u32; 5],
+}
+
+impl Material {
+    // テクスチャを持たない単色マテリアル
+    pub fn from_color(color: [f32; 4]) -> Self {
+        Material {
+            base_color_factor: color,
+            metallic_factor: 0.0,
+            roughness_factor: 0.6,
+            normal_scale: 1.0,
+            occlusion_strength: 1.0,
+            emissive_factor: [0.0, 0.0, 0.0],
+            base_color_texture: None,
+            metallic_roughness_texture: None,
+            normal_texture: None,
+            occlusion_texture: None,
+            emissive_texture: None,
+            uv_sets: [0; 5],
+        }
+    }
+
+    // glTFのマテリアルからテクスチャをアップロードして作成
+    pub fn from_gltf(
+        gl: &WebGl2RenderingContext,
+        material: &gltf::Material,
+        textures: &mut TextureCache,
+    ) -> Self {
+        let pbr = material.pbr_metallic_roughness();
+        let normal = material.normal_texture();
+        let occlusion = material.occlusion_texture();
+        let uv_sets = [
+            pbr.base_color_texture().map(|info| info.tex_coord()),
+            pbr.metallic_roughness_texture().map(|info| info.tex_coord()),
+            normal.as_ref().map(|t| t.tex_coord()),
+            occlusion.as_ref().map(|t| t.tex_coord()),
+            material.emissive_texture().map(|info| info.tex_coord()),
+        ]
+        .map(|set| match set.unwrap_or(0) {
+            // 頂点には TEXCOORD_0 と TEXCOORD_1 だけを載せる
+            set if set >= TEX_COORD_SETS => {
+                console_log!("Warning: TEXCOORD_{} is not supported, using TEXCOORD_0 instead", set);
+                0
+            }
+            set => set,
+        });
+
+        Material {
+            base_color_factor: pbr.base_color_factor(),
+            metallic_factor: pbr.metallic_factor(),
+            roughness_factor: pbr.roughness_factor(),
+            normal_scale: normal.as_ref().map(|t| t.scale()).unwrap_or(1.0),
+            occlusion_strength: occlusion.as_ref().map(|t| t.strength()).unwrap_or(1.0),
+            emissive_factor: material.emissive_factor(),
+            // 色を表すテクスチャはsRGB、それ以外はリニアとして扱う
+            base_color_texture: pbr.base_color_texture()
+                .and_then(|info| textures.get(gl, &info.texture(), true)),
+            metallic_roughness_texture: pbr.metallic_roughness_texture()
+                .and_then(|info| textures.get(gl, &info.texture(), false)),
+            normal_texture: normal
+                .and_then(|t| textures.get(gl, &t.texture(), false)),
+            occlusion_texture: occlusion
+                .and_then(|t| textures.get(gl, &t.texture(), false)),
+            emissive_texture: material.emissive_texture()
+                .and_then(|info| textures.get(gl, &info.texture(), true)),
+            uv_sets,
+        }
+    }
+
+    // uniformとテクスチャユニットに値を設定
+    pub fn bind(&self, gl: &WebGl2RenderingContext, uniforms: &MaterialUniforms) {
+        gl.uniform4fv_with_f32_array(uniforms.base_color_factor.as_ref(), &self.base_color_factor);
+        gl.uniform1f(uniforms.metallic_factor.as_ref(), self.metallic_factor);
+        gl.uniform1f(uniforms.roughness_factor.as_ref(), self.roughness_factor);
+        gl.uniform1f(uniforms.normal_scale.as_ref(), self.normal_scale);
+        gl.uniform1f(uniforms.occlusion_strength.as_ref(), self.occlusion_strength);
+        gl.uniform3fv_with_f32_array(uniforms.emissive_factor.as_ref(), &self.emissive_factor);
+
+        let textures = [
+            &self.base_color_texture,
+            &self.metallic_roughness_texture,
+            &self.normal_texture,
+            &self.occlusion_texture,
+            &self.emissive_texture,
+        ];
+        for ((slot, texture), uv_set) in uniforms.texture_slots.iter().zip(textures).zip(self.uv_sets) {
+            gl.active_texture(WebGl2RenderingContext::TEXTURE0 + slot.unit);
+            gl.bind_texture(WebGl2RenderingContext::TEXTURE_2D, texture.as_ref());
+            gl.uniform1i(slot.sampler.as_ref(), slot.unit as i32);
+            gl.uniform1i(slot.enabled.as_ref(), texture.is_some() as i32);
+            gl.uniform1i(slot.uv_set.as_ref(), uv_set as i32);
+        }
+    }
+}
+
+// アップロード済みのテクスチャをキャッシュする
+//
+// ラップモードはテクスチャに設定するので、同じ画像でもサンプラーが違えば別のテクスチャにする
+// キーは (画像, サンプラー（なければ既定）, sRGB か)
+pub struct TextureCache {
+    images: Vec<gltf::image::Data>,
+    textures: HashMap<(usize, Option<usize>, bool), WebGlTexture>,
+}
+
+impl TextureCache {
+    pub fn new(images: Vec<gltf::image::Data>) -> Self {
+        TextureCache {
+            images,
+            textures: HashMap::new(),
+        }
+    }
+
+    fn get(
+        &mut self,
+        gl: &WebGl2RenderingContext,
+        texture: &gltf::Texture,
+        srgb: bool,
+    ) -> Option<WebGlTexture> {
+        let image_index = texture.source().index();
+        let key = (image_index, texture.sampler().index(), srgb);
+        if let Some(cached) = self.textures.get(&key) {
+            return Some(cached.clone());
+        }
+
+        let image = self.images.get(image_index)?;
+        match create_texture(gl, image, &texture.sampler(), srgb) {
+            Ok(created) => {
+                self.textures.insert(key, created.clone());
+                Some(created)
+            }
+            Err(e) => {
+                console_log!("Failed to create texture for image {}: {:?}", image_index, e);
+                None
+            }
+        }
+    }
+}
+
+// 画像データをWebGLテクスチャとしてアップロード
+fn create_texture(
+    gl: &WebGl2RenderingContext,
+    image: &gltf::image::Data,
+    sampler: &gltf::texture::Sampler,
+    srgb: bool,
+) -> Result<WebGlTexture, JsValue> {
+    let pixels = to_rgba8(image)
+        .ok_or_else(|| JsValue::from_str(&format!("Unsupported image format: {:?}", image.format)))?;
+
+    let texture = gl.create_texture().ok_or("Failed to create texture")?;
+    gl.bind_texture(WebGl2RenderingContext::TEXTURE_2D, Some(&texture));
+
+    let internal_format = if srgb {
+        WebGl2RenderingContext::SRGB8_ALPHA8
+    } else {
+        WebGl2RenderingContext::RGBA8
+    };
+    gl.tex_image_2d_with_i32_and_i32_and_i32_and_format_and_type_and_opt_u8_array(
+        WebGl2RenderingContext::TEXTURE_2D,
+        0,
+        internal_format as i32,
+        image.width as i32,
+        image.height as i32,
+        0,
+        WebGl2RenderingContext::RGBA,
+        WebGl2RenderingContext::UNSIGNED_BYTE,
+        Some(&pixels),
+    )?;
+    gl.generate_mipmap(WebGl2RenderingContext::TEXTURE_2D);
+
+    gl.tex_parameteri(
+        WebGl2RenderingContext::TEXTURE_2D,
+        WebGl2RenderingContext::TEXTURE_WRAP_S,
+        wrap_mode(sampler.wrap_s()),
+    );
+    gl.tex_parameteri(
+        WebGl2RenderingContext::TEXTURE_2D,
+        WebGl2RenderingContext::TEXTURE_WRAP_T,
+        wrap_mode(sampler.wrap_t()),
+    );
+    gl.tex_parameteri(
+        WebGl2RenderingContext::TEXTURE_2D,
+        WebGl2RenderingContext::TEXTURE_MIN_FILTER,
+        WebGl2RenderingContext::LINEAR_MIPMAP_LINEAR as i32,
+    );
+    gl.tex_parameteri(
+        WebGl2RenderingContext::TEXTURE_2D,
+        WebGl2RenderingContext::TEXTURE_MAG_FILTER,
+        WebGl2RenderingContext::LINEAR as i32,
+    );
+
+    Ok(texture)
+}
+
+fn wrap_mode(mode: gltf::texture::WrappingMode) -> i32 {
+    let gl_mode = match mode {
+        gltf::texture::WrappingMode::ClampToEdge => WebGl2RenderingContext::CLAMP_TO_EDGE,
+        gltf::texture::WrappingMode::MirroredRepeat => WebGl2RenderingContext::MIRRORED_REPEAT,
+        gltf::texture::WrappingMode::Repeat => WebGl2RenderingContext::REPEAT,
+    };
+    gl_mode as i32
+}
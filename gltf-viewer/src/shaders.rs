@@ -0,0 +1,159 @@
+// PBR（metallic-roughness）シェーダー
+//
+// 頂点属性のロケーションは VERTEX_STRIDE のレイアウト
+// （position, normal, uv0, uv1, tangent）の順に対応する。
+// テクスチャーごとに u_*_uv_set で TEXCOORD_0 と TEXCOORD_1 のどちらを使うかを選ぶ。
+// 位置・法線・接線は頂点シェーダーでワールド空間に移し、ワールド空間のカメラ位置とライトで陰影を付ける。
+
+pub const VERTEX_SHADER: &str = r#"#version 300 es
+    layout(location = 0) in vec3 a_position;
+    layout(location = 1) in vec3 a_normal;
+    layout(location = 2) in vec2 a_uv0;
+    layout(location = 3) in vec2 a_uv1;
+    layout(location = 4) in vec4 a_tangent;
+
+    uniform mat4 u_mvp_matrix;
+    uniform mat4 u_model_matrix;
+    uniform mat3 u_normal_matrix;
+
+    out vec3 v_position;
+    out vec3 v_normal;
+    out vec2 v_uv0;
+    out vec2 v_uv1;
+    out vec4 v_tangent;
+
+    void main() {
+        v_position = (u_model_matrix * vec4(a_position, 1.0)).xyz;
+        v_normal = u_normal_matrix * a_normal;
+        v_uv0 = a_uv0;
+        v_uv1 = a_uv1;
+        v_tangent = vec4(mat3(u_model_matrix) * a_tangent.xyz, a_tangent.w);
+        gl_Position = u_mvp_matrix * vec4(a_position, 1.0);
+    }
+"#;
+
+pub const FRAGMENT_SHADER: &str = r#"#version 300 es
+    precision highp float;
+
+    in vec3 v_position;
+    in vec3 v_normal;
+    in vec2 v_uv0;
+    in vec2 v_uv1;
+    in vec4 v_tangent;
+
+    uniform vec3 u_camera_position;
+    uniform vec3 u_light_direction;
+
+    uniform vec4 u_base_color_factor;
+    uniform float u_metallic_factor;
+    uniform float u_roughness_factor;
+    uniform float u_normal_scale;
+    uniform float u_occlusion_strength;
+    uniform vec3 u_emissive_factor;
+
+    uniform bool u_has_base_color_texture;
+    uniform bool u_has_metallic_roughness_texture;
+    uniform bool u_has_normal_texture;
+    uniform bool u_has_occlusion_texture;
+    uniform bool u_has_emissive_texture;
+
+    uniform sampler2D u_base_color_texture;
+    uniform sampler2D u_metallic_roughness_texture;
+    uniform sampler2D u_normal_texture;
+    uniform sampler2D u_occlusion_texture;
+    uniform sampler2D u_emissive_texture;
+
+    uniform int u_base_color_uv_set;
+    uniform int u_metallic_roughness_uv_set;
+    uniform int u_normal_uv_set;
+    uniform int u_occlusion_uv_set;
+    uniform int u_emissive_uv_set;
+
+    out vec4 fragColor;
+
+    const float PI = 3.14159265359;
+
+    // テクスチャーの texCoord が指す UV
+    vec2 uv(int set) {
+        return set == 1 ? v_uv1 : v_uv0;
+    }
+
+    vec3 shading_normal() {
+        vec3 n = normalize(v_normal);
+        if (!u_has_normal_texture) {
+            return n;
+        }
+        // 接線空間の法線をTBN行列でワールド空間へ変換
+        vec3 t = normalize(v_tangent.xyz - n * dot(n, v_tangent.xyz));
+        vec3 b = cross(n, t) * v_tangent.w;
+        vec3 tangent_normal = texture(u_normal_texture, uv(u_normal_uv_set)).xyz * 2.0 - 1.0;
+        tangent_normal.xy *= u_normal_scale;
+        return normalize(mat3(t, b, n) * tangent_normal);
+    }
+
+    float distribution_ggx(float n_dot_h, float roughness) {
+        float a = roughness * roughness;
+        float a2 = a * a;
+        float d = n_dot_h * n_dot_h * (a2 - 1.0) + 1.0;
+        return a2 / (PI * d * d);
+    }
+
+    float geometry_smith(float n_dot_v, float n_dot_l, float roughness) {
+        float k = (roughness + 1.0) * (roughness + 1.0) / 8.0;
+        float gv = n_dot_v / (n_dot_v * (1.0 - k) + k);
+        float gl = n_dot_l / (n_dot_l * (1.0 - k) + k);
+        return gv * gl;
+    }
+
+    void main() {
+        vec4 base_color = u_base_color_factor;
+        if (u_has_base_color_texture) {
+            base_color *= texture(u_base_color_texture, uv(u_base_color_uv_set));
+        }
+
+        float metallic = u_metallic_factor;
+        float roughness = u_roughness_factor;
+        if (u_has_metallic_roughness_texture) {
+            vec4 mr = texture(u_metallic_roughness_texture, uv(u_metallic_roughness_uv_set));
+            roughness *= mr.g;
+            metallic *= mr.b;
+        }
+        roughness = clamp(roughness, 0.04, 1.0);
+
+        vec3 n = shading_normal();
+        vec3 v = normalize(u_camera_position - v_position);
+        vec3 l = normalize(-u_light_direction);
+        vec3 h = normalize(v + l);
+
+        float n_dot_l = max(dot(n, l), 0.0);
+        float n_dot_v = max(dot(n, v), 0.001);
+        float n_dot_h = max(dot(n, h), 0.0);
+        float v_dot_h = max(dot(v, h), 0.0);
+
+        vec3 f0 = mix(vec3(0.04), base_color.rgb, metallic);
+        vec3 fresnel = f0 + (1.0 - f0) * pow(1.0 - v_dot_h, 5.0);
+        float d = distribution_ggx(n_dot_h, roughness);
+        float g = geometry_smith(n_dot_v, n_dot_l, roughness);
+
+        vec3 specular = fresnel * d * g / (4.0 * n_dot_v * max(n_dot_l, 0.001));
+        vec3 diffuse = (1.0 - fresnel) * (1.0 - metallic) * base_color.rgb / PI;
+        vec3 color = (diffuse + specular) * n_dot_l * 3.0;
+
+        // 環境光にのみアンビエントオクルージョンを適用
+        float occlusion = 1.0;
+        if (u_has_occlusion_texture) {
+            float ao = texture(u_occlusion_texture, uv(u_occlusion_uv_set)).r;
+            occlusion = 1.0 + u_occlusion_strength * (ao - 1.0);
+        }
+        color += base_color.rgb * 0.15 * occlusion;
+
+        vec3 emissive = u_emissive_factor;
+        if (u_has_emissive_texture) {
+            emissive *= texture(u_emissive_texture, uv(u_emissive_uv_set)).rgb;
+        }
+        color += emissive;
+
+        // リニア空間からsRGBへ変換
+        fragColor = vec4(pow(color, vec3(1.0 / 2.2)), base_color.a);
+    }
+"#;
@@ -0,0 +1,180 @@
+// ビューアの状態スナップショット（セッションの保存と復元）
+//
+// バージョン 1: model と camera
+// バージョン 2: ノードの表示、マテリアルの上書き、断面、注釈（scene）を追加
+//   今のビューアにはまだこれらを操作する機能がないので、描画には使わない
+//   読み込んだ値は保持して次の保存にそのまま書き出す（新しいビューアで作った状態を失わないように）
+//   どれも省略できるので、バージョン 1 のスナップショットもそのまま読める
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+// スナップショット形式のバージョン（互換性のない変更時に上げる）
+pub const STATE_VERSION: u32 = 2;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ViewerState {
+    pub version: u32,
+    pub model: Option<ModelId>,
+    pub camera: CameraState,
+    #[serde(default)]
+    pub scene: SceneState,
+}
+
+// モデルの見せ方（バージョン 2 から。今は保存と復元だけで、描画には反映しない）
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SceneState {
+    // ノードの番号ごとの表示（書いていないノードは表示する）
+    #[serde(default)]
+    pub node_visibility: BTreeMap<usize, bool>,
+    #[serde(default)]
+    pub material_overrides: Vec<MaterialOverride>,
+    #[serde(default)]
+    pub clipping_planes: Vec<ClippingPlane>,
+    #[serde(default)]
+    pub annotations: Vec<Annotation>,
+}
+
+// マテリアルの値の上書き（None はモデルの値のまま）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MaterialOverride {
+    pub material: usize,
+    #[serde(default)]
+    pub base_color: Option<[f32; 4]>,
+    #[serde(default)]
+    pub metallic: Option<f32>,
+    #[serde(default)]
+    pub roughness: Option<f32>,
+}
+
+// normal・p + distance = 0 の平面で、normal の向きの側を切り取る
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ClippingPlane {
+    pub normal: [f32; 3],
+    pub distance: f32,
+}
+
+// モデルの位置に付けるメモ
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Annotation {
+    pub position: [f32; 3],
+    pub text: String,
+    // 付けたノード（ノードを動かしたときに一緒に動かす）
+    #[serde(default)]
+    pub node: Option<usize>,
+}
+
+// 読み込んだモデルを識別するための情報
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModelId {
+    pub byte_length: usize,
+    pub fingerprint: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CameraState {
+    pub position: [f32; 3],
+    pub target: [f32; 3],
+}
+
+impl ModelId {
+    // ファイル内容からIDを作成（FNV-1a 64bit）
+    pub fn from_bytes(data: &[u8]) -> Self {
+        let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+        for &byte in data {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+        ModelId {
+            byte_length: data.len(),
+            fingerprint: format!("{:016x}", hash),
+        }
+    }
+}
+
+impl ViewerState {
+    pub fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string_pretty(self).map_err(|e| format!("Failed to serialize state: {}", e))
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let state: ViewerState =
+            serde_json::from_str(json).map_err(|e| format!("Invalid state JSON: {}", e))?;
+        if state.version > STATE_VERSION {
+            return Err(format!(
+                "Unsupported state version {} (this viewer supports up to {})",
+                state.version, STATE_VERSION
+            ));
+        }
+        Ok(state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_state() -> ViewerState {
+        ViewerState {
+            version: STATE_VERSION,
+            model: Some(ModelId::from_bytes(b"glTF")),
+            camera: CameraState {
+                position: [3.0, 3.0, 5.0],
+                target: [0.0, 0.0, 0.0],
+            },
+            scene: SceneState::default(),
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let state = sample_state();
+        let json = state.to_json().unwrap();
+        assert_eq!(ViewerState::from_json(&json).unwrap(), state);
+    }
+
+    #[test]
+    fn test_round_trip_scene() {
+        let mut state = sample_state();
+        state.scene = SceneState {
+            node_visibility: BTreeMap::from([(0, true), (3, false)]),
+            material_overrides: vec![MaterialOverride {
+                material: 1,
+                base_color: Some([1.0, 0.0, 0.0, 0.5]),
+                metallic: None,
+                roughness: Some(0.25),
+            }],
+            clipping_planes: vec![ClippingPlane { normal: [0.0, 1.0, 0.0], distance: -0.5 }],
+            annotations: vec![Annotation { position: [0.0, 1.0, 2.0], text: "ボルト".to_string(), node: Some(3) }],
+        };
+        let json = state.to_json().unwrap();
+        assert_eq!(ViewerState::from_json(&json).unwrap(), state);
+    }
+
+    #[test]
+    fn test_reads_version_1() {
+        let json = r#"{"version": 1, "model": null, "camera": {"position": [3.0, 3.0, 5.0], "target": [0.0, 0.0, 0.0]}}"#;
+        let state = ViewerState::from_json(json).unwrap();
+        assert_eq!(state.scene, SceneState::default());
+        // 一部だけ書いたものも読める
+        let json = r#"{"version": 2, "model": null, "camera": {"position": [0.0, 0.0, 1.0], "target": [0.0, 0.0, 0.0]},
+            "scene": {"node_visibility": {"2": false}}}"#;
+        let state = ViewerState::from_json(json).unwrap();
+        assert_eq!(state.scene.node_visibility, BTreeMap::from([(2, false)]));
+        assert!(state.scene.annotations.is_empty());
+    }
+
+    #[test]
+    fn test_rejects_newer_version() {
+        let mut state = sample_state();
+        state.version = STATE_VERSION + 1;
+        let json = state.to_json().unwrap();
+        assert!(ViewerState::from_json(&json).is_err());
+    }
+
+    #[test]
+    fn test_fingerprint_depends_on_content() {
+        assert_eq!(ModelId::from_bytes(b"abc"), ModelId::from_bytes(b"abc"));
+        assert_ne!(ModelId::from_bytes(b"abc").fingerprint, ModelId::from_bytes(b"abd").fingerprint);
+    }
+}
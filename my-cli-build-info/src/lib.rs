@@ -0,0 +1,235 @@
+// my-cli のツールに共通のビルド情報（version サブコマンド）
+//
+// 不具合の報告に、どのソースをどの環境でビルドしたものかを添えられるようにする
+// 各ツールの build.rs で emit を呼び、ツールのコードで build_info! を使う
+//   // build.rs（my-cli-build-info は [build-dependencies] にも入れる）
+//   fn main() { my_cli_build_info::emit(); }
+//   // lib.rs
+//   let info = my_cli_build_info::build_info!().with_crates([Crate::new("calc-core", calc_core::VERSION, calc_core::features())]);
+//   println!("{}", if json { info.json() } else { info.text() });
+// git のないところ（crates.io のソースなど）でビルドすると commit は null（テキストでは unknown）
+// SOURCE_DATE_EPOCH があれば、ビルド日はその日付にする（再現可能なビルド）
+use std::path::Path;
+use std::process::Command;
+
+use serde::Serialize;
+
+// ビルドしたときの情報（build_info! で作る）
+#[derive(Clone, Debug, Serialize)]
+pub struct BuildInfo {
+    pub name: &'static str,
+    pub version: &'static str,
+    // 短いコミットハッシュ
+    pub commit: Option<&'static str>,
+    // コミットしていない変更があったか
+    pub dirty: bool,
+    pub build_date: &'static str,
+    pub target: &'static str,
+    // debug か release
+    pub profile: &'static str,
+    pub rustc: &'static str,
+    pub features: Vec<&'static str>,
+    // 一緒にビルドしたツールとライブラリ
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub crates: Vec<Crate>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Crate {
+    pub name: &'static str,
+    pub version: &'static str,
+    pub features: Vec<&'static str>,
+}
+
+impl Crate {
+    pub fn new(name: &'static str, version: &'static str, features: Vec<&'static str>) -> Crate {
+        Crate { name, version, features }
+    }
+}
+
+// 呼び出したクレートの情報（build.rs で emit を呼んでいないとコンパイルエラーになる）
+#[macro_export]
+macro_rules! build_info {
+    () => {
+        $crate::BuildInfo {
+            name: env!("CARGO_PKG_NAME"),
+            version: env!("CARGO_PKG_VERSION"),
+            commit: $crate::non_empty(env!("MY_CLI_BUILD_COMMIT")),
+            dirty: env!("MY_CLI_BUILD_DIRTY") == "true",
+            build_date: env!("MY_CLI_BUILD_DATE"),
+            target: env!("MY_CLI_BUILD_TARGET"),
+            profile: env!("MY_CLI_BUILD_PROFILE"),
+            rustc: env!("MY_CLI_BUILD_RUSTC"),
+            features: $crate::list(env!("MY_CLI_BUILD_FEATURES")),
+            crates: Vec::new(),
+        }
+    };
+}
+
+#[doc(hidden)]
+pub fn non_empty(value: &'static str) -> Option<&'static str> {
+    (!value.is_empty()).then_some(value)
+}
+
+#[doc(hidden)]
+pub fn list(value: &'static str) -> Vec<&'static str> {
+    value.split(',').filter(|item| !item.is_empty()).collect()
+}
+
+impl BuildInfo {
+    // 一緒にビルドしたクレートを足す（同じ名前は1つにする）
+    pub fn with_crates(mut self, crates: impl IntoIterator<Item = Crate>) -> BuildInfo {
+        for krate in crates {
+            if krate.name != self.name && !self.crates.iter().any(|known| known.name == krate.name) {
+                self.crates.push(krate);
+            }
+        }
+        self
+    }
+
+    // このクレートと、一緒にビルドしたクレート（my-cli が各ツールの分をまとめるとき用）
+    pub fn as_crates(&self) -> Vec<Crate> {
+        std::iter::once(Crate::new(self.name, self.version, self.features.clone())).chain(self.crates.iter().cloned()).collect()
+    }
+
+    pub fn text(&self) -> String {
+        let features = |features: &[&str]| if features.is_empty() { "none".to_string() } else { features.join(", ") };
+        let commit = match (self.commit, self.dirty) {
+            (Some(commit), true) => format!("{} (with uncommitted changes)", commit),
+            (Some(commit), false) => commit.to_string(),
+            (None, _) => "unknown".to_string(),
+        };
+        let mut text = format!("{} {}\n", self.name, self.version);
+        text.push_str(&format!("commit:   {}\n", commit));
+        text.push_str(&format!("built:    {} ({})\n", self.build_date, self.profile));
+        text.push_str(&format!("target:   {}\n", self.target));
+        text.push_str(&format!("rustc:    {}\n", self.rustc));
+        text.push_str(&format!("features: {}\n", features(&self.features)));
+        for (index, krate) in self.crates.iter().enumerate() {
+            let label = if index == 0 { "crates:" } else { "" };
+            let enabled = if krate.features.is_empty() { String::new() } else { format!(" ({})", krate.features.join(", ")) };
+            text.push_str(&format!("{:<10}{} {}{}\n", label, krate.name, krate.version, enabled));
+        }
+        text
+    }
+
+    pub fn json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("build info is always serializable")
+    }
+}
+
+// build.rs から呼び、build_info! が読む環境変数をコンパイラーに渡す
+pub fn emit() {
+    let var = |name: &str| std::env::var(name).unwrap_or_default();
+    let dir = var("CARGO_MANIFEST_DIR");
+    let git = |args: &[&str]| {
+        // status が .git/index を書き換えて、ビルドのたびに build.rs が動き直すのを防ぐ
+        let output = Command::new("git").arg("--no-optional-locks").args(args).current_dir(&dir).output().ok()?;
+        output.status.success().then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+    };
+
+    let commit = git(&["rev-parse", "--short=12", "HEAD"]).unwrap_or_default();
+    let dirty = !commit.is_empty() && git(&["status", "--porcelain", "--untracked-files=no"]).is_some_and(|status| !status.is_empty());
+    // コミットやブランチの切り替えで作り直す（パッケージのファイルの変更は "." で見る）
+    if let Some(git_dir) = git(&["rev-parse", "--absolute-git-dir"]) {
+        for name in ["HEAD", "refs", "packed-refs"] {
+            let path = Path::new(&git_dir).join(name);
+            if path.exists() {
+                println!("cargo:rerun-if-changed={}", path.display());
+            }
+        }
+    }
+    println!("cargo:rerun-if-changed=.");
+    println!("cargo:rerun-if-env-changed=SOURCE_DATE_EPOCH");
+
+    let seconds = var("SOURCE_DATE_EPOCH").parse::<i64>().ok().unwrap_or_else(|| {
+        std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).map_or(0, |elapsed| elapsed.as_secs() as i64)
+    });
+    let rustc = Command::new(std::env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string()))
+        .arg("--version")
+        .output()
+        .ok()
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        .filter(|version| !version.is_empty())
+        .unwrap_or_else(|| "unknown".to_string());
+    let mut features: Vec<String> = std::env::vars()
+        .filter_map(|(name, _)| name.strip_prefix("CARGO_FEATURE_").map(|feature| feature.to_lowercase().replace('_', "-")))
+        .filter(|feature| feature != "default")
+        .collect();
+    features.sort();
+
+    println!("cargo:rustc-env=MY_CLI_BUILD_COMMIT={}", commit);
+    println!("cargo:rustc-env=MY_CLI_BUILD_DIRTY={}", dirty);
+    println!("cargo:rustc-env=MY_CLI_BUILD_DATE={}", date(seconds));
+    println!("cargo:rustc-env=MY_CLI_BUILD_TARGET={}", var("TARGET"));
+    println!("cargo:rustc-env=MY_CLI_BUILD_PROFILE={}", var("PROFILE"));
+    println!("cargo:rustc-env=MY_CLI_BUILD_RUSTC={}", rustc);
+    println!("cargo:rustc-env=MY_CLI_BUILD_FEATURES={}", features.join(","));
+}
+
+// UNIX 時刻の UTC の日付（YYYY-MM-DD）
+fn date(seconds: i64) -> String {
+    // 1970-01-01 からの日数を年月日にする（Howard Hinnant の civil_from_days）
+    let days = seconds.div_euclid(86_400) + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> BuildInfo {
+        BuildInfo {
+            name: "calc-cli",
+            version: "0.1.0",
+            commit: Some("0123456789ab"),
+            dirty: false,
+            build_date: "2026-10-15",
+            target: "x86_64-unknown-linux-gnu",
+            profile: "release",
+            rustc: "rustc 1.90.0",
+            features: Vec::new(),
+            crates: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_date() {
+        assert_eq!(date(0), "1970-01-01");
+        assert_eq!(date(951_782_400), "2000-02-29");
+        assert_eq!(date(1_791_936_000), "2026-10-14");
+        assert_eq!(date(-86_400), "1969-12-31");
+    }
+
+    #[test]
+    fn test_text() {
+        let info = sample().with_crates([Crate::new("calc-core", "0.1.0", vec!["clap", "serde"]), Crate::new("calc-cli", "0.1.0", Vec::new())]);
+        assert_eq!(
+            info.text(),
+            "calc-cli 0.1.0\ncommit:   0123456789ab\nbuilt:    2026-10-15 (release)\ntarget:   x86_64-unknown-linux-gnu\n\
+rustc:    rustc 1.90.0\nfeatures: none\ncrates:   calc-core 0.1.0 (clap, serde)\n"
+        );
+        let unknown = BuildInfo { commit: None, dirty: true, ..sample() };
+        assert!(unknown.text().contains("commit:   unknown\n"));
+        assert_eq!(info.as_crates().len(), 2);
+    }
+
+    #[test]
+    fn test_json() {
+        let json: serde_json::Value = serde_json::from_str(&sample().json()).unwrap();
+        assert_eq!(json["commit"], "0123456789ab");
+        assert_eq!(json["features"], serde_json::json!([]));
+        // 一緒にビルドしたクレートがなければ crates は出さない
+        assert!(json.get("crates").is_none());
+        assert_eq!(list("clap,,serde"), ["clap", "serde"]);
+        assert_eq!(non_empty(""), None);
+    }
+}
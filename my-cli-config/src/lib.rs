@@ -0,0 +1,299 @@
+// my-cli のツールに共通の設定ファイル
+//
+// 優先順位（上ほど強い）:
+//   コマンドラインのフラグ
+//   環境変数（HELLO_NAME、CALC_ANGLE など。clap の env で読む）
+//   プロジェクトの .my-cli.toml（今のディレクトリから親へたどって最初に見つかったもの）
+//   ツールごとの設定ファイル（~/.config/hello-cli/config.toml など）
+//   ユーザーの設定ファイル（~/.config/my-cli/config.toml）
+//   システムの設定ファイル（/etc/my-cli/config.toml）
+//   組み込みの既定値
+// 共通のファイルにはツールごとの表（[hello]、[calc]）に、ツールごとのファイルには表なしで書く
+//   [hello]
+//   lang = "ja"
+// 値は clap の既定値として渡すので、値の検証もフラグと同じ規則で行われる
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use clap::{Arg, Command};
+use toml_edit::{DocumentMut, Item, Value};
+
+// プロジェクトの設定ファイルの名前
+pub const PROJECT_FILE: &str = ".my-cli.toml";
+
+// ツールの設定（tool は共通のファイルでの表の名前、keys はオプションの --long 名）
+pub struct Section {
+    pub tool: &'static str,
+    pub keys: &'static [&'static str],
+}
+
+// 設定ファイルの層（弱い順）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Layer {
+    System,
+    User,
+    Tool,
+    Project,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct File {
+    pub layer: Layer,
+    pub path: PathBuf,
+}
+
+// 使う値とそれを書いたファイル
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Setting {
+    pub key: &'static str,
+    pub value: String,
+    pub layer: Layer,
+    pub path: PathBuf,
+}
+
+#[derive(Debug)]
+pub struct ConfigError {
+    pub path: PathBuf,
+    pub message: String,
+}
+
+impl Layer {
+    pub fn name(self) -> &'static str {
+        match self {
+            Layer::System => "system",
+            Layer::User => "user",
+            Layer::Tool => "config",
+            Layer::Project => "project",
+        }
+    }
+}
+
+impl Section {
+    // 読むファイル（弱い順。tool_file はツールごとの設定ファイル）
+    pub fn files(&self, tool_file: Option<PathBuf>) -> Vec<File> {
+        let system = cfg!(unix).then(|| PathBuf::from("/etc/my-cli/config.toml"));
+        let user = dirs::config_dir().map(|dir| dir.join("my-cli").join("config.toml"));
+        let project = std::env::current_dir().ok().and_then(|dir| find_project(&dir));
+        [(Layer::System, system), (Layer::User, user), (Layer::Tool, tool_file), (Layer::Project, project)]
+            .into_iter()
+            .filter_map(|(layer, path)| path.map(|path| File { layer, path }))
+            .collect()
+    }
+
+    // 全ての層を重ねる（ないファイルは飛ばす。同じキーは強い層の値を使う）
+    pub fn load(&self, files: &[File], command: &Command) -> Result<Vec<Setting>, ConfigError> {
+        let mut settings: Vec<Setting> = Vec::new();
+        for file in files {
+            let error = |message: String| ConfigError { path: file.path.clone(), message };
+            let contents = match std::fs::read_to_string(&file.path) {
+                Ok(contents) => contents,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(error(e.to_string())),
+            };
+            for setting in self.parse(&contents, file, command).map_err(error)? {
+                settings.retain(|known| known.key != setting.key);
+                settings.push(setting);
+            }
+        }
+        settings.sort_by_key(|setting| self.keys.iter().position(|&key| key == setting.key));
+        Ok(settings)
+    }
+
+    // 1つのファイルの値（ツールごとのファイルは表なし、それ以外は [tool] の表）
+    pub fn parse(&self, contents: &str, file: &File, command: &Command) -> Result<Vec<Setting>, String> {
+        let document: DocumentMut = contents.parse().map_err(|e: toml_edit::TomlError| e.to_string().trim_end().to_string())?;
+        let table = if file.layer == Layer::Tool {
+            document.as_table()
+        } else {
+            // 他のツールの表は読まない
+            if let Some((key, _)) = document.iter().find(|(_, item)| !item.is_table()) {
+                return Err(format!("'{}' must be in a tool table such as [{}]", key, self.tool));
+            }
+            match document.get(self.tool).and_then(Item::as_table) {
+                Some(table) => table,
+                None => return Ok(Vec::new()),
+            }
+        };
+        let mut settings = Vec::new();
+        for (key, item) in table.iter() {
+            let Some(&key) = self.keys.iter().find(|&&known| known == key) else {
+                return Err(format!("unknown key '{}' (known keys: {})", key, self.keys.join(", ")));
+            };
+            let Some(value) = item.as_value().and_then(value_text) else {
+                return Err(format!("'{}' must be a string, number or boolean", key));
+            };
+            validate(command, key, &value)?;
+            settings.push(Setting { key, value, layer: file.layer, path: file.path.clone() });
+        }
+        Ok(settings)
+    }
+}
+
+// 今のディレクトリから親へたどって最初の .my-cli.toml
+pub fn find_project(start: &Path) -> Option<PathBuf> {
+    start.ancestors().map(|dir| dir.join(PROJECT_FILE)).find(|path| path.is_file())
+}
+
+// -vv のときに、どの値をどのファイルから読んだかを出す（ログの設定の後に呼ぶ）
+pub fn trace(settings: &[Setting]) {
+    for setting in settings {
+        tracing::debug!(key = setting.key, value = %setting.value, path = %setting.path.display(), "setting from the {} layer", setting.layer.name());
+    }
+}
+
+// 設定の値を各オプションの既定値にする（サブコマンドのオプションも含む）
+pub fn apply(command: Command, settings: &[Setting]) -> Command {
+    settings.iter().fold(command, |command, setting| set_default(command, setting.key, &setting.value))
+}
+
+// 値をオプションと同じ規則で検証する（環境変数は読まずに設定の値だけを確かめる）
+pub fn validate(command: &Command, key: &str, value: &str) -> Result<(), String> {
+    let command = mut_args(set_default(command.clone(), key, value), &|_| true, &|arg| arg.env(None));
+    let name = command.get_name().to_string();
+    if let Err(e) = command.try_get_matches_from([name]) {
+        let message = e.to_string();
+        let message = message.lines().next().unwrap_or_default().trim_start_matches("error: ");
+        return Err(format!("{} (config key '{}')", message, key));
+    }
+    Ok(())
+}
+
+fn set_default(command: Command, key: &str, value: &str) -> Command {
+    mut_args(command, &|arg| arg.get_long() == Some(key), &|arg| arg.default_value(value.to_string()))
+}
+
+// サブコマンドも含めて、matches に当てはまるオプションを f で変える
+fn mut_args(command: Command, matches: &dyn Fn(&Arg) -> bool, f: &dyn Fn(Arg) -> Arg) -> Command {
+    let command = command.mut_args(|arg| if matches(&arg) { f(arg) } else { arg });
+    let subcommands: Vec<String> = command.get_subcommands().map(|subcommand| subcommand.get_name().to_string()).collect();
+    subcommands.iter().fold(command, |command, name| command.mut_subcommand(name, |subcommand| mut_args(subcommand, matches, f)))
+}
+
+// 文字列は引用符なしで、数値と真偽値はそのまま（配列や表は使えない）
+pub fn value_text(value: &Value) -> Option<String> {
+    match value {
+        Value::String(text) => Some(text.value().clone()),
+        Value::Integer(_) | Value::Float(_) | Value::Boolean(_) => Some(value.clone().decorated("", "").to_string()),
+        _ => None,
+    }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path.display(), self.message)
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::value_parser;
+
+    const SECTION: Section = Section { tool: "hello", keys: &["name", "count", "loud"] };
+
+    fn command() -> Command {
+        Command::new("hello")
+            .arg(Arg::new("name").long("name"))
+            .arg(Arg::new("count").long("count").value_parser(value_parser!(u32)).default_value("1"))
+            .arg(Arg::new("loud").long("loud").value_parser(value_parser!(bool)))
+            .subcommand(Command::new("farewell").arg(Arg::new("name").long("name")))
+    }
+
+    // テストごとに別の一時ディレクトリ
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("my-cli-config-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn parse(contents: &str, layer: Layer) -> Result<Vec<(&'static str, String)>, String> {
+        let file = File { layer, path: PathBuf::from("config.toml") };
+        let settings = SECTION.parse(contents, &file, &command())?;
+        Ok(settings.into_iter().map(|setting| (setting.key, setting.value)).collect())
+    }
+
+    #[test]
+    fn test_parse_tool_and_shared_files() {
+        let values = parse("name = \"Alice\"\ncount = 3\nloud = true\n", Layer::Tool).unwrap();
+        assert_eq!(values, vec![("name", "Alice".to_string()), ("count", "3".to_string()), ("loud", "true".to_string())]);
+        let shared = "[calc]\nangle = \"deg\"\n\n[hello]\nname = \"Bob\"\n";
+        assert_eq!(parse(shared, Layer::User).unwrap(), vec![("name", "Bob".to_string())]);
+        assert_eq!(parse("[calc]\nangle = \"deg\"\n", Layer::Project).unwrap(), vec![]);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let error = |contents: &str, layer: Layer| parse(contents, layer).unwrap_err();
+        assert_eq!(error("nmae = \"Alice\"", Layer::Tool), "unknown key 'nmae' (known keys: name, count, loud)");
+        assert_eq!(error("name = \"Alice\"", Layer::User), "'name' must be in a tool table such as [hello]");
+        assert_eq!(error("name = [\"Alice\"]", Layer::Tool), "'name' must be a string, number or boolean");
+        assert!(error("count = \"many\"", Layer::Tool).ends_with("(config key 'count')"));
+        assert!(error("[hello]\ncount = 0.5", Layer::Project).contains("'0.5'"));
+        assert!(error("name = ", Layer::Tool).contains("TOML parse error"));
+    }
+
+    #[test]
+    fn test_validate_ignores_environment() {
+        // この環境変数を読むのはこのテストだけ
+        let command = Command::new("hello").arg(Arg::new("count").long("count").value_parser(value_parser!(u32)).env("MY_CLI_CONFIG_TEST_COUNT"));
+        unsafe { std::env::set_var("MY_CLI_CONFIG_TEST_COUNT", "-1") };
+        assert!(validate(&command, "count", "2").is_ok());
+        assert!(validate(&command, "count", "many").is_err());
+        unsafe { std::env::remove_var("MY_CLI_CONFIG_TEST_COUNT") };
+    }
+
+    #[test]
+    fn test_load_layers() {
+        let dir = temp_dir("load");
+        let write = |name: &str, contents: &str| {
+            let path = dir.join(name);
+            std::fs::write(&path, contents).unwrap();
+            path
+        };
+        let files = vec![
+            File { layer: Layer::System, path: write("system.toml", "[hello]\nname = \"System\"\ncount = 2\nloud = false\n") },
+            File { layer: Layer::User, path: write("user.toml", "[hello]\nname = \"User\"\ncount = 3\n") },
+            File { layer: Layer::Tool, path: write("tool.toml", "name = \"Tool\"\n") },
+            File { layer: Layer::Project, path: dir.join("missing.toml") },
+        ];
+        let settings = SECTION.load(&files, &command()).unwrap();
+        let summary: Vec<(&str, &str, Layer)> = settings.iter().map(|setting| (setting.key, setting.value.as_str(), setting.layer)).collect();
+        assert_eq!(summary, vec![("name", "Tool", Layer::Tool), ("count", "3", Layer::User), ("loud", "false", Layer::System)]);
+        assert_eq!(settings[0].path, dir.join("tool.toml"));
+
+        let files = vec![File { layer: Layer::Project, path: write("bad.toml", "[hello]\ncount = \"x\"\n") }];
+        let error = SECTION.load(&files, &command()).unwrap_err();
+        assert_eq!(error.path, dir.join("bad.toml"));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_apply_sets_defaults_everywhere() {
+        let settings = vec![
+            Setting { key: "name", value: "Config".to_string(), layer: Layer::User, path: PathBuf::new() },
+            Setting { key: "count", value: "5".to_string(), layer: Layer::Project, path: PathBuf::new() },
+        ];
+        let matches = apply(command(), &settings).try_get_matches_from(["hello"]).unwrap();
+        assert_eq!(matches.get_one::<u32>("count"), Some(&5));
+        let matches = apply(command(), &settings).try_get_matches_from(["hello", "--count", "7"]).unwrap();
+        assert_eq!(matches.get_one::<u32>("count"), Some(&7));
+        let matches = apply(command(), &settings).try_get_matches_from(["hello", "farewell"]).unwrap();
+        assert_eq!(matches.subcommand_matches("farewell").unwrap().get_one::<String>("name").map(String::as_str), Some("Config"));
+    }
+
+    #[test]
+    fn test_find_project() {
+        let dir = temp_dir("project");
+        let nested = dir.join("a").join("b");
+        std::fs::create_dir_all(&nested).unwrap();
+        assert_eq!(find_project(&nested).filter(|path| path.starts_with(&dir)), None);
+        std::fs::write(dir.join(PROJECT_FILE), "").unwrap();
+        assert_eq!(find_project(&nested), Some(dir.join(PROJECT_FILE)));
+        std::fs::write(dir.join("a").join(PROJECT_FILE), "").unwrap();
+        assert_eq!(find_project(&nested), Some(dir.join("a").join(PROJECT_FILE)));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
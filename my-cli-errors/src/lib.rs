@@ -0,0 +1,200 @@
+// my-cli のツールに共通のエラー処理
+//
+// 終了コードの決め方と、端末への表示の形式（anyhow と同じく Error: の後に原因を並べる）をそろえる
+//   Error: Failed to read data.csv
+//
+//   Caused by:
+//       No such file or directory (os error 2)
+//
+//   hint: ...
+// ツール固有のエラーは CliError を実装して、種類ごとの終了コードと直し方の案内を返す
+use std::error::Error;
+use std::fmt;
+use std::io::{IsTerminal, Write};
+
+// どのツールでも同じ意味の終了コード（3〜125 はツールごとに決める）
+pub const EXIT_SUCCESS: i32 = 0;
+pub const EXIT_FAILURE: i32 = 1;
+// clap がコマンドラインの使い方の誤りに使う
+pub const EXIT_USAGE: i32 = 2;
+// Ctrl-C（128 + SIGINT）
+pub const EXIT_INTERRUPTED: i32 = 130;
+
+// 終了コードと直し方の案内を持つエラー
+pub trait CliError: Error {
+    fn exit_code(&self) -> i32 {
+        EXIT_FAILURE
+    }
+
+    // 表示の最後に「hint: ...」として出す
+    fn hint(&self) -> Option<String> {
+        None
+    }
+}
+
+// メッセージと終了コードだけのエラー
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Failure {
+    pub code: i32,
+    pub message: String,
+}
+
+impl Failure {
+    pub fn new(code: i32, message: impl fmt::Display) -> Failure {
+        Failure { code, message: message.to_string() }
+    }
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl Error for Failure {}
+
+impl CliError for Failure {
+    fn exit_code(&self) -> i32 {
+        self.code
+    }
+}
+
+// 原因をたどって最初に分かった終了コード（classify が None を返すものは飛ばす。なければ 1）
+pub fn exit_code<'a>(error: &'a (dyn Error + 'static), classify: impl Fn(&'a (dyn Error + 'static)) -> Option<i32>) -> i32 {
+    let mut cause = Some(error);
+    while let Some(error) = cause {
+        if let Some(code) = classify(error) {
+            return code;
+        }
+        cause = error.source();
+    }
+    EXIT_FAILURE
+}
+
+// 表示する文字列（paint は「Error:」に色を付ける）
+pub fn render(error: &dyn Error, hint: Option<&str>, paint: impl Fn(&str) -> String) -> String {
+    let mut text = format!("{} {}", paint("Error:"), error);
+    let causes: Vec<String> = std::iter::successors(error.source(), |&cause| cause.source()).map(ToString::to_string).collect();
+    if !causes.is_empty() {
+        text.push_str("\n\nCaused by:");
+        for (i, cause) in causes.iter().enumerate() {
+            // 原因が1つなら番号を付けない
+            let cause = if causes.len() == 1 { cause.clone() } else { format!("{}: {}", i, cause) };
+            text.push_str(&format!("\n    {}", cause));
+        }
+    }
+    if let Some(hint) = hint {
+        text.push_str(&format!("\n\nhint: {}", hint));
+    }
+    text
+}
+
+// 標準エラー出力に色を付けるか（https://no-color.org/ : 空でない NO_COLOR があれば付けない）
+pub fn stderr_color() -> bool {
+    std::env::var_os("NO_COLOR").is_none_or(|value| value.is_empty()) && std::io::stderr().is_terminal()
+}
+
+// 太字の赤（色を付けないときはそのまま）
+pub fn red(text: &str, color: bool) -> String {
+    if color { format!("\x1b[1;31m{}\x1b[0m", text) } else { text.to_string() }
+}
+
+// エラーを表示して、その終了コードで終了する
+pub fn exit(error: &dyn CliError) -> ! {
+    let color = stderr_color();
+    eprintln!("{}", render(error, error.hint().as_deref(), |label| red(label, color)));
+    std::process::exit(error.exit_code());
+}
+
+// 原因のどこかがパイプの先が閉じられた（`| head -1` など）ことによる書き込みの失敗か
+pub fn is_broken_pipe(error: &(dyn Error + 'static)) -> bool {
+    std::iter::successors(Some(error), |&error| error.source())
+        .any(|error| error.downcast_ref::<std::io::Error>().is_some_and(|error| error.kind() == std::io::ErrorKind::BrokenPipe))
+}
+
+// 標準出力に書く（println! と違い、パイプの先が閉じられたらパニックせず何も言わずに 0 で終了する）
+pub fn write_stdout(args: fmt::Arguments<'_>) {
+    let mut stdout = std::io::stdout().lock();
+    if let Err(e) = stdout.write_fmt(args).and_then(|()| stdout.flush()) {
+        if is_broken_pipe(&e) {
+            std::process::exit(EXIT_SUCCESS);
+        }
+        exit(&Failure::new(EXIT_FAILURE, format!("Failed to write to standard output: {}", e)));
+    }
+}
+
+// print! と println! の代わり（write_stdout で書く）
+#[macro_export]
+macro_rules! out {
+    ($($arg:tt)*) => {
+        $crate::write_stdout(format_args!($($arg)*))
+    };
+}
+
+#[macro_export]
+macro_rules! outln {
+    () => {
+        $crate::write_stdout(format_args!("\n"))
+    };
+    ($($arg:tt)*) => {
+        $crate::write_stdout(format_args!("{}\n", format_args!($($arg)*)))
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug)]
+    struct Wrapped {
+        message: &'static str,
+        source: Option<Box<dyn Error + 'static>>,
+    }
+
+    impl fmt::Display for Wrapped {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "{}", self.message)
+        }
+    }
+
+    impl Error for Wrapped {
+        fn source(&self) -> Option<&(dyn Error + 'static)> {
+            self.source.as_deref()
+        }
+    }
+
+    fn wrap(message: &'static str, source: impl Error + 'static) -> Wrapped {
+        Wrapped { message, source: Some(Box::new(source)) }
+    }
+
+    #[test]
+    fn test_render() {
+        let plain = |label: &str| label.to_string();
+        assert_eq!(render(&Failure::new(EXIT_FAILURE, "no names to greet"), None, plain), "Error: no names to greet");
+        let error = wrap("Failed to read data.csv", Failure::new(EXIT_FAILURE, "No such file"));
+        assert_eq!(render(&error, None, plain), "Error: Failed to read data.csv\n\nCaused by:\n    No such file");
+        let error = wrap("Failed to run script.calc", wrap("line 3", Failure::new(EXIT_FAILURE, "Division by zero")));
+        assert_eq!(
+            render(&error, Some("check the divisor"), plain),
+            "Error: Failed to run script.calc\n\nCaused by:\n    0: line 3\n    1: Division by zero\n\nhint: check the divisor"
+        );
+        assert_eq!(render(&Failure::new(EXIT_FAILURE, "oops"), None, |label| red(label, true)), "\x1b[1;31mError:\x1b[0m oops");
+    }
+
+    #[test]
+    fn test_exit_code() {
+        let classify = |error: &(dyn Error + 'static)| error.downcast_ref::<Failure>().map(CliError::exit_code);
+        assert_eq!(exit_code(&Failure::new(4, "unsupported language"), classify), 4);
+        assert_eq!(exit_code(&wrap("Failed to run", Failure::new(5, "cannot write")), classify), 5);
+        assert_eq!(exit_code(&Wrapped { message: "unknown", source: None }, classify), EXIT_FAILURE);
+    }
+
+    #[test]
+    fn test_is_broken_pipe() {
+        let pipe = || std::io::Error::from(std::io::ErrorKind::BrokenPipe);
+        assert!(is_broken_pipe(&pipe()));
+        assert!(is_broken_pipe(&wrap("Failed to write the report", pipe())));
+        assert!(!is_broken_pipe(&wrap("Failed to write out.txt", std::io::Error::from(std::io::ErrorKind::PermissionDenied))));
+        assert!(!is_broken_pipe(&Failure::new(EXIT_FAILURE, "broken pipe")));
+    }
+}
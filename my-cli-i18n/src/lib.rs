@@ -0,0 +1,207 @@
+// my-cli のツールに共通の翻訳（Fluent のメッセージカタログ）
+//
+// 各ツールは言語ごとの .ftl を include_str! で埋め込み、Messages::new に渡す
+//   let messages = Messages::new(&[("en", EN), ("ja", JA)], &detect(lang_arg(&args, &["--lang"]).as_deref(), &["en", "ja"]));
+//   println!("{}", messages.format("wrote", &[("path", path.display().to_string().into())]));
+// 訳のないメッセージは英語（最初のカタログ）、それもなければ ID をそのまま表示する
+// ヘルプは localize でコマンドの定義に訳を当てる（ID の決まりは localize を参照）
+// clap が作る文言（Usage:、Options:、引数のエラーなど）は英語のまま
+use std::borrow::Cow;
+use std::ffi::OsString;
+
+use fluent_bundle::concurrent::FluentBundle;
+use fluent_bundle::{FluentArgs, FluentResource};
+use unic_langid::LanguageIdentifier;
+
+pub use fluent_bundle::FluentValue;
+
+// 選んだ言語のカタログと、英語のカタログ
+pub struct Messages {
+    lang: String,
+    bundles: Vec<FluentBundle<FluentResource>>,
+}
+
+impl Messages {
+    // catalogs は (言語, .ftl の中身) で、最初のものを訳がないときに使う
+    // .ftl の誤りは埋め込んだ時点で分かるように panic する
+    pub fn new(catalogs: &[(&str, &str)], lang: &str) -> Messages {
+        let chosen = catalogs.iter().position(|(code, _)| *code == lang).unwrap_or(0);
+        let order = std::iter::once(chosen).chain((chosen != 0).then_some(0));
+        let bundles = order.map(|index| bundle(catalogs[index].0, catalogs[index].1)).collect();
+        Messages { lang: catalogs.get(chosen).map_or("en", |(code, _)| code).to_string(), bundles }
+    }
+
+    // 訳のない英語だけのメッセージ（テストや、カタログを読む前のエラー用）
+    pub fn english() -> Messages {
+        Messages { lang: "en".to_string(), bundles: Vec::new() }
+    }
+
+    pub fn lang(&self) -> &str {
+        &self.lang
+    }
+
+    pub fn get(&self, id: &str) -> String {
+        self.format(id, &[])
+    }
+
+    // 引数付きのメッセージ（数は FluentValue::from(n) で渡すと複数形を選べる）
+    pub fn format(&self, id: &str, args: &[(&str, FluentValue)]) -> String {
+        self.lookup(id, args).map_or_else(|| id.to_string(), Cow::into_owned)
+    }
+
+    // 訳があれば訳（英語のカタログも見る）
+    pub fn lookup(&self, id: &str, args: &[(&str, FluentValue)]) -> Option<Cow<'_, str>> {
+        let mut fluent_args = FluentArgs::new();
+        for (name, value) in args {
+            fluent_args.set(*name, value.clone());
+        }
+        self.bundles.iter().find_map(|bundle| {
+            let pattern = bundle.get_message(id)?.value()?;
+            let mut errors = Vec::new();
+            Some(bundle.format_pattern(pattern, Some(&fluent_args), &mut errors))
+        })
+    }
+}
+
+fn bundle(lang: &str, source: &str) -> FluentBundle<FluentResource> {
+    let langid: LanguageIdentifier = lang.parse().unwrap_or_default();
+    let resource = FluentResource::try_new(source.to_string())
+        .unwrap_or_else(|(_, errors)| panic!("invalid {} message catalog: {:?}", lang, errors));
+    let mut bundle = FluentBundle::new_concurrent(vec![langid]);
+    // 端末に出すので、引数を Unicode の方向制御文字で囲まない
+    bundle.set_use_isolating(false);
+    bundle
+        .add_resource(resource)
+        .unwrap_or_else(|errors| panic!("duplicate messages in the {} catalog: {:?}", lang, errors));
+    bundle
+}
+
+// "ja"、"ja-JP"、"ja_JP.UTF-8" の言語の部分（C と POSIX は None）
+pub fn language(code: &str) -> Option<String> {
+    let language = code.split(['_', '-', '.', '@']).next().unwrap_or(code).to_lowercase();
+    (!language.is_empty() && language != "c" && language != "posix").then_some(language)
+}
+
+// 表示に使う言語を選ぶ
+// explicit（--lang など）、LC_ALL、LC_MESSAGES、LANG の順に見て、supported にある最初のもの（なければ supported の最初）
+pub fn detect(explicit: Option<&str>, supported: &[&str]) -> String {
+    let environment = ["LC_ALL", "LC_MESSAGES", "LANG"].iter().filter_map(|name| std::env::var(name).ok());
+    choose(explicit.map(str::to_string).into_iter().chain(environment), supported)
+}
+
+fn choose(candidates: impl IntoIterator<Item = String>, supported: &[&str]) -> String {
+    candidates
+        .into_iter()
+        .filter_map(|code| language(&code))
+        .find(|language| supported.contains(&language.as_str()))
+        .unwrap_or_else(|| supported.first().unwrap_or(&"en").to_string())
+}
+
+// 引数を読む前に --lang の値を探す（ヘルプは引数を読む途中で表示するため）
+// names は "--lang" や "-l" で、"--lang ja" と "--lang=ja" の形を見る（"--" より後は見ない）
+pub fn lang_arg(args: &[OsString], names: &[&str]) -> Option<String> {
+    let mut args = args.iter().skip(1).map(|arg| arg.to_string_lossy()).take_while(|arg| arg != "--");
+    while let Some(arg) = args.next() {
+        for name in names {
+            if arg == *name {
+                return args.next().map(Cow::into_owned);
+            }
+            if let Some(value) = arg.strip_prefix(name).and_then(|rest| rest.strip_prefix('=')) {
+                return Some(value.to_string());
+            }
+        }
+    }
+    None
+}
+
+// コマンドの説明とオプションのヘルプに訳を当てる（訳がなければ元の英語のまま）
+//   about / after-help                    ルートのコマンド
+//   <サブコマンド>-about / -after-help      サブコマンド（入れ子なら farewell-about、config-get-about のようにつなぐ）
+//   arg-<引数>                             オプション（引数の ID の _ は - にする。例: arg-names-file）
+//   <サブコマンド>-arg-<引数>               そのサブコマンドだけの訳（なければ arg-<引数> を使う）
+pub fn localize(command: clap::Command, messages: &Messages) -> clap::Command {
+    localize_at(command, messages, "")
+}
+
+fn localize_at(mut command: clap::Command, messages: &Messages, prefix: &str) -> clap::Command {
+    let get = |id: &str| messages.lookup(id, &[]).map(Cow::into_owned);
+    if let Some(about) = get(&format!("{}about", prefix)) {
+        command = command.about(about);
+    }
+    if let Some(after_help) = get(&format!("{}after-help", prefix)) {
+        command = command.after_help(after_help);
+    }
+    let ids: Vec<String> = command.get_arguments().map(|arg| arg.get_id().to_string()).collect();
+    for id in ids {
+        let key = id.replace('_', "-");
+        if let Some(help) = get(&format!("{}arg-{}", prefix, key)).or_else(|| get(&format!("arg-{}", key))) {
+            command = command.mut_arg(id, |arg| arg.help(help));
+        }
+    }
+    let names: Vec<String> = command.get_subcommands().map(|subcommand| subcommand.get_name().to_string()).collect();
+    for name in names {
+        let prefix = format!("{}{}-", prefix, name);
+        command = command.mut_subcommand(&name, |subcommand| localize_at(subcommand, messages, &prefix));
+    }
+    command
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EN: &str = "wrote = Wrote { $path }\nlines = { $count ->\n    [one] 1 line\n   *[other] { $count } lines\n}\n";
+    const JA: &str = "wrote = { $path } に書き込みました\nabout = 簡単なツール\narg-dry-run = 書き込まずに表示する\nfix-about = 直す\n";
+
+    #[test]
+    fn test_messages() {
+        let catalogs = [("en", EN), ("ja", JA)];
+        let ja = Messages::new(&catalogs, "ja");
+        assert_eq!(ja.lang(), "ja");
+        assert_eq!(ja.format("wrote", &[("path", "a.txt".into())]), "a.txt に書き込みました");
+        // 訳がなければ英語、英語もなければ ID
+        assert_eq!(ja.format("lines", &[("count", 1.into())]), "1 line");
+        assert_eq!(ja.format("lines", &[("count", 3.into())]), "3 lines");
+        assert_eq!(ja.get("missing"), "missing");
+        // 対応していない言語は英語
+        assert_eq!(Messages::new(&catalogs, "fr").lang(), "en");
+        assert_eq!(Messages::english().get("wrote"), "wrote");
+    }
+
+    #[test]
+    fn test_detect() {
+        let supported = ["en", "ja"];
+        let choose = |candidates: &[&str]| choose(candidates.iter().map(|code| code.to_string()), &supported);
+        assert_eq!(choose(&["ja_JP.UTF-8"]), "ja");
+        assert_eq!(choose(&["fr", "C", "ja-JP"]), "ja");
+        assert_eq!(choose(&["POSIX"]), "en");
+        assert_eq!(choose(&[]), "en");
+        assert_eq!(language("C.UTF-8"), None);
+    }
+
+    #[test]
+    fn test_lang_arg() {
+        let args = |args: &[&str]| args.iter().map(OsString::from).collect::<Vec<_>>();
+        assert_eq!(lang_arg(&args(&["tool", "--lang", "ja", "x"]), &["--lang"]), Some("ja".to_string()));
+        assert_eq!(lang_arg(&args(&["tool", "sub", "--lang=ja"]), &["--lang"]), Some("ja".to_string()));
+        assert_eq!(lang_arg(&args(&["tool", "-l", "ja"]), &["--lang", "-l"]), Some("ja".to_string()));
+        assert_eq!(lang_arg(&args(&["tool", "--", "--lang", "ja"]), &["--lang"]), None);
+        assert_eq!(lang_arg(&args(&["--lang", "x"]), &["--lang"]), None);
+        assert_eq!(lang_arg(&args(&["tool", "--language", "ja"]), &["--lang"]), None);
+    }
+
+    #[test]
+    fn test_localize() {
+        let command = clap::Command::new("tool")
+            .about("A simple tool")
+            .arg(clap::Arg::new("dry_run").long("dry-run").help("Print instead of writing"))
+            .subcommand(clap::Command::new("fix").about("Fix it").arg(clap::Arg::new("dry_run").long("dry-run")));
+        let command = localize(command, &Messages::new(&[("en", EN), ("ja", JA)], "ja"));
+        assert_eq!(command.get_about().unwrap().to_string(), "簡単なツール");
+        let help = |command: &clap::Command| command.get_arguments().find(|arg| arg.get_id() == "dry_run").unwrap().get_help().unwrap().to_string();
+        assert_eq!(help(&command), "書き込まずに表示する");
+        let fix = command.find_subcommand("fix").unwrap();
+        assert_eq!(fix.get_about().unwrap().to_string(), "直す");
+        assert_eq!(help(fix), "書き込まずに表示する");
+    }
+}
@@ -0,0 +1,95 @@
+// my-cli のツールに共通のログ出力（tracing）
+//
+// ログは標準エラー出力に出し、結果を出す標準出力には混ぜない
+//   既定      warn 以上（警告だけ）
+//   -v        info 以上
+//   -vv       debug 以上
+//   -vvv      trace まで
+//   RUST_LOG  あれば -v より優先する（例: RUST_LOG=calc_cli=debug,my_cli_config=trace）
+// --log-format json で1行に1件の JSON にする（jq などで読む用）
+// 各ツールは Cli に LogArgs を flatten し、引数を読んだ直後に init を呼ぶ
+use clap::{ArgAction, Args, ValueEnum};
+use tracing_subscriber::EnvFilter;
+
+// ログの形式
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum LogFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+// 全てのツールで共通のオプション（サブコマンドの後にも書ける）
+#[derive(Args, Clone, Debug, Default)]
+pub struct LogArgs {
+    /// Show more log messages on stderr: -v info, -vv debug, -vvv trace (RUST_LOG overrides this)
+    #[arg(short, long, global = true, action = ArgAction::Count)]
+    pub verbose: u8,
+
+    /// Format of log messages on stderr
+    #[arg(long, global = true, env = "MY_CLI_LOG_FORMAT", value_enum, value_name = "FORMAT", default_value_t = LogFormat::Text)]
+    pub log_format: LogFormat,
+}
+
+// -v の数に対応する tracing の絞り込み
+pub fn level(verbose: u8) -> &'static str {
+    match verbose {
+        0 => "warn",
+        1 => "info",
+        2 => "debug",
+        _ => "trace",
+    }
+}
+
+// ログの出力先を設定する（my-cli から同じプロセスで2回呼ばれたときは、先に設定したほうを使う）
+pub fn init(args: &LogArgs) {
+    // RUST_LOG が正しくないときは -v の指定に戻す
+    let from_env = std::env::var("RUST_LOG").ok().and_then(|filter| EnvFilter::try_new(filter).ok());
+    let filter = from_env.unwrap_or_else(|| EnvFilter::new(level(args.verbose)));
+    let builder = tracing_subscriber::fmt().with_env_filter(filter).with_writer(std::io::stderr);
+    let _ = match args.log_format {
+        // 普段の警告は「WARN メッセージ」だけにし、-v からはどのモジュールのログかも出す
+        LogFormat::Text => builder
+            .without_time()
+            .with_target(args.verbose > 0)
+            .with_ansi(my_cli_errors::stderr_color())
+            .try_init(),
+        LogFormat::Json => builder.json().try_init(),
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    #[derive(Parser)]
+    struct Cli {
+        #[command(flatten)]
+        log: LogArgs,
+
+        #[command(subcommand)]
+        command: Option<Command>,
+    }
+
+    #[derive(clap::Subcommand)]
+    enum Command {
+        Run,
+    }
+
+    #[test]
+    fn test_verbose_levels() {
+        assert_eq!(level(0), "warn");
+        assert_eq!(level(1), "info");
+        assert_eq!(level(2), "debug");
+        assert_eq!(level(3), "trace");
+        assert_eq!(level(9), "trace");
+        // サブコマンドの後にも書け、-v は重ねた数だけ詳しくなる
+        let cli = Cli::parse_from(["tool", "run", "-vv", "--log-format", "json"]);
+        assert_eq!(cli.log.verbose, 2);
+        assert_eq!(cli.log.log_format, LogFormat::Json);
+        assert!(cli.command.is_some());
+        let cli = Cli::parse_from(["tool", "--verbose"]);
+        assert_eq!(cli.log.verbose, 1);
+    }
+}
@@ -0,0 +1,132 @@
+// my-cli のツールに共通の進捗表示（進捗バーとスピナー）
+//
+// 時間のかかる処理（calc-cli batch、gltf-cli convert / optimize / render、hello-cli --interval）で使う
+//   let progress = Progress::bar(lines.len() as u64, "data.txt", my_cli_progress::shown(quiet));
+//   for line in lines {
+//       progress.suspend(|| println!("{}", evaluate(line)));  // 結果の行はバーを消してから出す
+//       progress.inc(1);
+//   }
+//   progress.finish();  // 終わったらバーを消す（drop でも消える）
+// 表示は標準エラー出力だけに出すので、標準出力の結果には混ざらない
+// 出すかどうかはどのツールも shown で決める（--quiet、JSON / CSV 出力、端末でないとき、TERM=dumb では出さない）
+use std::borrow::Cow;
+use std::io::IsTerminal;
+use std::time::Duration;
+
+use indicatif::{ProgressBar, ProgressDrawTarget, ProgressStyle};
+
+// スピナーを回す間隔
+const TICK: Duration = Duration::from_millis(100);
+
+// 進捗の表示（表示しないときは何もしない）
+pub struct Progress {
+    bar: Option<ProgressBar>,
+}
+
+// 進捗を表示するか（quiet は --quiet のほか、JSON や CSV など機械が読む出力のときにも true にする）
+pub fn shown(quiet: bool) -> bool {
+    visible(quiet, std::io::stderr().is_terminal(), std::env::var("TERM").ok().as_deref())
+}
+
+fn visible(quiet: bool, terminal: bool, term: Option<&str>) -> bool {
+    !quiet && terminal && term != Some("dumb")
+}
+
+impl Progress {
+    // 全体の数が分かる処理（例: [00:00:02] ████████░░░░ 40/100 data.txt）
+    pub fn bar(len: u64, message: impl Into<Cow<'static, str>>, shown: bool) -> Progress {
+        let style = ProgressStyle::with_template("[{elapsed_precise}] {bar:30} {pos}/{len} {wide_msg}")
+            .expect("the bar template is valid")
+            .progress_chars("█▉▊▋▌▍▎▏░");
+        Progress::with(shown, || ProgressBar::new(len).with_style(style).with_message(message.into()))
+    }
+
+    // 全体の数が分からない処理（例: ⠹ Optimizing model.glb (3s)）
+    pub fn spinner(message: impl Into<Cow<'static, str>>, shown: bool) -> Progress {
+        let style = ProgressStyle::with_template("{spinner} {wide_msg} ({elapsed})")
+            .expect("the spinner template is valid")
+            .tick_chars("⠋⠙⠹⠸⠼⠴⠦⠧⠇⠏ ");
+        let progress = Progress::with(shown, || ProgressBar::new_spinner().with_style(style).with_message(message.into()));
+        if let Some(bar) = &progress.bar {
+            bar.enable_steady_tick(TICK);
+        }
+        progress
+    }
+
+    // 何も表示しない
+    pub fn hidden() -> Progress {
+        Progress { bar: None }
+    }
+
+    fn with(shown: bool, create: impl FnOnce() -> ProgressBar) -> Progress {
+        let bar = shown.then(|| {
+            let bar = create();
+            bar.set_draw_target(ProgressDrawTarget::stderr());
+            bar
+        });
+        Progress { bar }
+    }
+
+    pub fn is_shown(&self) -> bool {
+        self.bar.is_some()
+    }
+
+    pub fn inc(&self, delta: u64) {
+        if let Some(bar) = &self.bar {
+            bar.inc(delta);
+        }
+    }
+
+    pub fn set_message(&self, message: impl Into<Cow<'static, str>>) {
+        if let Some(bar) = &self.bar {
+            bar.set_message(message);
+        }
+    }
+
+    // バーを消してから f を実行し、その後に描き直す（結果やエラーの行を書くとき用）
+    pub fn suspend<R>(&self, f: impl FnOnce() -> R) -> R {
+        match &self.bar {
+            Some(bar) => bar.suspend(f),
+            None => f(),
+        }
+    }
+
+    // 表示を消す（結果は別に出すので、終わったバーは残さない）
+    pub fn finish(&self) {
+        if let Some(bar) = &self.bar {
+            bar.finish_and_clear();
+        }
+    }
+}
+
+impl Drop for Progress {
+    fn drop(&mut self) {
+        self.finish();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_visible() {
+        assert!(visible(false, true, Some("xterm-256color")));
+        assert!(visible(false, true, None));
+        assert!(!visible(true, true, Some("xterm")));
+        assert!(!visible(false, false, Some("xterm")));
+        assert!(!visible(false, true, Some("dumb")));
+    }
+
+    #[test]
+    fn test_hidden() {
+        // 表示しないときも同じように呼べる
+        for progress in [Progress::bar(3, "data.txt", false), Progress::spinner("Reading", false), Progress::hidden()] {
+            assert!(!progress.is_shown());
+            progress.inc(1);
+            progress.set_message("Writing");
+            assert_eq!(progress.suspend(|| 42), 42);
+            progress.finish();
+        }
+    }
+}
@@ -0,0 +1,188 @@
+// 使ったコマンドの記録（my-cli recent）とブックマーク（my-cli bookmark）
+//
+// 記録は明示的に有効にしたときだけ（--record、MY_CLI_RECORD=1、設定ファイルの [my-cli] record = true）
+// ~/.local/share/my-cli/activity.jsonl に1行に1つの JSON で追記する
+//   {"time":"2026-10-15T09:30:00+09:00","dir":"/home/alice/models","args":["gltf","optimize","in.glb","out.glb"]}
+// ブックマークは同じディレクトリの bookmarks.json に名前順で保存する（記録を有効にしなくても使える）
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Local};
+use my_cli_config::{ConfigError, File, Section, Setting};
+use serde::{Deserialize, Serialize};
+
+// 共通の設定ファイルの [my-cli] の表
+pub const SECTION: Section = Section { tool: "my-cli", keys: &["record"] };
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Entry {
+    pub time: DateTime<Local>,
+    pub dir: PathBuf,
+    // my-cli より後の引数
+    pub args: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Bookmark {
+    pub name: String,
+    pub args: Vec<String>,
+    pub created: DateTime<Local>,
+}
+
+// 記録とブックマークを置くディレクトリ
+fn data_dir() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("my-cli"))
+}
+
+pub fn log_path() -> Option<PathBuf> {
+    data_dir().map(|dir| dir.join("activity.jsonl"))
+}
+
+pub fn bookmarks_path() -> Option<PathBuf> {
+    data_dir().map(|dir| dir.join("bookmarks.json"))
+}
+
+// 読む設定ファイル（my-cli だけのファイルはない）
+pub fn config_files() -> Vec<File> {
+    SECTION.files(None)
+}
+
+// 値は my-cli のオプションと同じ規則で確かめる（サブコマンドなしで読めるようにしてから）
+pub fn load_config(command: &clap::Command) -> Result<Vec<Setting>, ConfigError> {
+    let command = command.clone().subcommand_required(false).arg_required_else_help(false);
+    SECTION.load(&config_files(), &command)
+}
+
+// 読み書きするファイル（説明と場所。my-cli doctor で調べる）
+pub fn state_files() -> Vec<(&'static str, PathBuf)> {
+    [("activity log (--record)", log_path()), ("bookmarks", bookmarks_path())]
+        .into_iter()
+        .filter_map(|(description, path)| path.map(|path| (description, path)))
+        .collect()
+}
+
+pub fn record(path: &Path, entry: &Entry) -> Result<(), String> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| format!("cannot create {}: {}", dir.display(), e))?;
+    }
+    let line = serde_json::to_string(entry).map_err(|e| e.to_string())?;
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .and_then(|mut file| writeln!(file, "{}", line))
+        .map_err(|e| format!("cannot write {}: {}", path.display(), e))
+}
+
+// 記録を全て読む（ファイルがなければ空）
+pub fn read(path: &Path) -> Result<Vec<Entry>, String> {
+    let contents = match std::fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(format!("cannot read {}: {}", path.display(), e)),
+    };
+    contents
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| serde_json::from_str(line).map_err(|e| format!("{} line {}: {}", path.display(), index + 1, e)))
+        .collect()
+}
+
+// 最近の last 件を古い順に、新しいものから 1、2、… と数えた番号付きで
+pub fn recent(entries: &[Entry], last: usize) -> Vec<(usize, &Entry)> {
+    let skip = entries.len().saturating_sub(last);
+    entries.iter().enumerate().skip(skip).map(|(index, entry)| (entries.len() - index, entry)).collect()
+}
+
+pub fn load_bookmarks(path: &Path) -> Result<Vec<Bookmark>, String> {
+    match std::fs::read_to_string(path) {
+        Ok(contents) => serde_json::from_str(&contents).map_err(|e| format!("{}: {}", path.display(), e)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(format!("cannot read {}: {}", path.display(), e)),
+    }
+}
+
+pub fn save_bookmarks(path: &Path, bookmarks: &[Bookmark]) -> Result<(), String> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| format!("cannot create {}: {}", dir.display(), e))?;
+    }
+    let mut bookmarks = bookmarks.to_vec();
+    bookmarks.sort_by(|a, b| a.name.cmp(&b.name));
+    let json = serde_json::to_string_pretty(&bookmarks).map_err(|e| e.to_string())?;
+    std::fs::write(path, json + "\n").map_err(|e| format!("cannot write {}: {}", path.display(), e))
+}
+
+// ブックマークの名前は空白を含まない1語（bookmark run の後に続けて書けるように）
+pub fn check_name(name: &str) -> Result<(), String> {
+    if name.is_empty() || name.starts_with('-') || name.chars().any(char::is_whitespace) {
+        return Err(format!("'{}' is not a bookmark name (use one word that does not start with -)", name));
+    }
+    Ok(())
+}
+
+// シェルに貼り付けられる形のコマンド（my-cli gltf optimize in.glb out.glb --max-triangles '10^4'）
+pub fn command_line(args: &[String]) -> String {
+    std::iter::once("my-cli".to_string()).chain(args.iter().map(|arg| quote(arg))).collect::<Vec<_>>().join(" ")
+}
+
+fn quote(arg: &str) -> String {
+    let plain = |c: char| c.is_ascii_alphanumeric() || "-_./=:,+@%".contains(c);
+    if !arg.is_empty() && arg.chars().all(plain) {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', "'\\''"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn test_command_line() {
+        assert_eq!(command_line(&args(&["gltf", "optimize", "in.glb", "out.glb", "--max-triangles", "10^4"])), "my-cli gltf optimize in.glb out.glb --max-triangles '10^4'");
+        assert_eq!(command_line(&args(&["calc", "eval", "1 + 2"])), "my-cli calc eval '1 + 2'");
+        assert_eq!(command_line(&args(&["hello", "-n", "O'Brien", ""])), "my-cli hello -n 'O'\\''Brien' ''");
+    }
+
+    #[test]
+    fn test_record() {
+        let dir = std::env::temp_dir().join(format!("my-cli-activity-{}", std::process::id()));
+        let path = dir.join("activity.jsonl");
+        assert_eq!(read(&path).unwrap(), Vec::new());
+        let entries: Vec<Entry> = ["info", "validate", "render"]
+            .iter()
+            .map(|command| Entry { time: Local::now(), dir: dir.clone(), args: args(&["gltf", command, "model.glb"]) })
+            .collect();
+        for entry in &entries {
+            record(&path, entry).unwrap();
+        }
+        assert_eq!(read(&path).unwrap(), entries);
+        // 新しいものが 1
+        let numbers: Vec<(usize, &str)> = recent(&entries, 2).into_iter().map(|(number, entry)| (number, entry.args[1].as_str())).collect();
+        assert_eq!(numbers, [(2, "validate"), (1, "render")]);
+
+        let bookmarks_path = dir.join("bookmarks.json");
+        let bookmark = |name: &str| Bookmark { name: name.to_string(), args: args(&["calc", "eval", "1 + 2"]), created: Local::now() };
+        save_bookmarks(&bookmarks_path, &[bookmark("sum"), bookmark("add")]).unwrap();
+        let names: Vec<String> = load_bookmarks(&bookmarks_path).unwrap().into_iter().map(|bookmark| bookmark.name).collect();
+        assert_eq!(names, ["add", "sum"]);
+        std::fs::write(&path, "{\"broken\n").unwrap();
+        assert!(read(&path).unwrap_err().ends_with(&format!("line 1: {}", serde_json::from_str::<Entry>("{\"broken").unwrap_err())));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_check_name() {
+        assert!(check_name("optimize-assets").is_ok());
+        assert!(check_name("").is_err());
+        assert!(check_name("two words").is_err());
+        assert!(check_name("--force").is_err());
+    }
+}
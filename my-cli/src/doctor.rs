@@ -0,0 +1,291 @@
+// 環境の診断（my-cli doctor）
+//
+// 設定ファイル、履歴などのファイル、端末、glTF ビューアーの表示環境を調べ、
+// 問題には直し方（fix:）を添える。fail が1つでもあれば終了コード 1（warn だけなら 0）
+use std::path::{Path, PathBuf};
+
+use clap::CommandFactory;
+
+use crate::activity;
+use crate::gltf::VIEWER_DIR;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Status {
+    Ok,
+    Warn,
+    Fail,
+}
+
+#[derive(Debug)]
+pub struct Check {
+    pub status: Status,
+    pub subject: String,
+    pub detail: String,
+    pub fix: Option<String>,
+}
+
+// 見出しごとの結果
+pub struct Section {
+    pub title: &'static str,
+    pub checks: Vec<Check>,
+}
+
+impl Check {
+    fn ok(subject: impl Into<String>, detail: impl Into<String>) -> Check {
+        Check { status: Status::Ok, subject: subject.into(), detail: detail.into(), fix: None }
+    }
+
+    fn warn(subject: impl Into<String>, detail: impl Into<String>, fix: impl Into<String>) -> Check {
+        Check { status: Status::Warn, subject: subject.into(), detail: detail.into(), fix: Some(fix.into()) }
+    }
+
+    fn fail(subject: impl Into<String>, detail: impl Into<String>, fix: impl Into<String>) -> Check {
+        Check { status: Status::Fail, subject: subject.into(), detail: detail.into(), fix: Some(fix.into()) }
+    }
+}
+
+pub fn run() -> Vec<Section> {
+    let env = |name: &str| std::env::var(name).ok();
+    vec![
+        Section { title: "Config files", checks: config() },
+        Section { title: "History and state files", checks: files() },
+        Section { title: "Terminal", checks: terminal(env, std::io::IsTerminal::is_terminal(&std::io::stdout())) },
+        Section { title: "glTF viewer", checks: gltf(env) },
+    ]
+}
+
+fn config() -> Vec<Check> {
+    let my_cli = activity::load_config(&crate::Cli::command()).map(|_| ());
+    let tools = [
+        ("my-cli", activity::config_files(), my_cli),
+        ("hello", hello_cli::config_files(), hello_cli::check_config()),
+        ("calc", calc_cli::config_files(), calc_cli::check_config()),
+    ];
+    tools
+        .into_iter()
+        .map(|(tool, files, result)| match result {
+            Ok(()) => {
+                let found: Vec<String> = files.iter().filter(|file| file.path.exists()).map(|file| file.path.display().to_string()).collect();
+                let detail = if found.is_empty() { "no config files (built-in defaults)".to_string() } else { found.join(", ") };
+                Check::ok(tool, detail)
+            }
+            Err(e) => Check::fail(
+                tool,
+                e.to_string(),
+                match tool {
+                    "my-cli" => format!("edit {} (the only key under [my-cli] is record = true or false)", e.path.display()),
+                    tool => format!("edit {} (valid keys and values are the options in `my-cli {} --help`)", e.path.display(), tool),
+                },
+            ),
+        })
+        .collect()
+}
+
+fn files() -> Vec<Check> {
+    let hello = hello_cli::state_files().into_iter().map(|(description, path)| (format!("hello {}", description), path));
+    let calc = calc_cli::state_files().into_iter().map(|(description, path)| (format!("calc {}", description), path));
+    let my_cli = activity::state_files().into_iter().map(|(description, path)| (format!("my-cli {}", description), path));
+    hello.chain(calc).chain(my_cli).map(|(subject, path)| check_file(&subject, &path)).collect()
+}
+
+// 読み書きできるか（ないファイルは、作るディレクトリに書けるか）
+// ディレクトリもまだなければ保存するときに作るので、ある中で一番近い親ディレクトリに書けるかを見る
+fn check_file(subject: &str, path: &Path) -> Check {
+    let shown = path.display();
+    if !path.exists() {
+        let dir = path
+            .ancestors()
+            .skip(1)
+            .map(|dir| if dir.as_os_str().is_empty() { Path::new(".") } else { dir })
+            .find(|dir| dir.exists())
+            .unwrap_or(Path::new("."));
+        return match std::fs::metadata(dir) {
+            Ok(metadata) if metadata.is_dir() && !metadata.permissions().readonly() => Check::ok(subject, format!("{} (not created yet)", shown)),
+            Ok(metadata) if !metadata.is_dir() => {
+                Check::fail(subject, format!("cannot create {}: {} is not a directory", shown, dir.display()), format!("move {} out of the way", dir.display()))
+            }
+            Ok(_) => Check::fail(subject, format!("cannot create {}: {} is not writable", shown, dir.display()), format!("chmod u+w {}", dir.display())),
+            Err(e) => Check::fail(subject, format!("cannot create {}: {}", shown, e), format!("mkdir -p {}", dir.display())),
+        };
+    }
+    if !path.is_file() {
+        return Check::fail(subject, format!("{} is not a file", shown), format!("move {} out of the way", shown));
+    }
+    // 追記で開くだけなら中身は変わらない
+    if let Err(e) = std::fs::OpenOptions::new().read(true).append(true).open(path) {
+        return Check::fail(subject, format!("cannot read and write {}: {}", shown, e), format!("chmod u+rw {}", shown));
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(path).map(|metadata| metadata.permissions().mode()).unwrap_or(0);
+        if mode & 0o002 != 0 {
+            return Check::warn(subject, format!("{} is writable by every user", shown), format!("chmod 600 {}", shown));
+        }
+    }
+    Check::ok(subject, shown.to_string())
+}
+
+// 色と文字幅（env は環境変数、terminal は標準出力が端末か）
+fn terminal(env: impl Fn(&str) -> Option<String>, terminal: bool) -> Vec<Check> {
+    let set = |name: &str| env(name).filter(|value| !value.is_empty());
+    let mut checks = Vec::new();
+    checks.push(if terminal {
+        Check::ok("output", "standard output is a terminal")
+    } else {
+        Check::ok("output", "standard output is not a terminal (colors, --frame and --align follow --color and COLUMNS)")
+    });
+
+    let term = set("TERM");
+    checks.push(if set("NO_COLOR").is_some() {
+        Check::warn("color", "NO_COLOR is set, so colors are off", "unset NO_COLOR, or pass --color always for one command")
+    } else if term.as_deref() == Some("dumb") {
+        Check::warn("color", "TERM=dumb, so colors are off", "set TERM to your terminal, e.g. export TERM=xterm-256color")
+    } else if set("COLORTERM").is_some_and(|value| value == "truecolor" || value == "24bit") {
+        Check::ok("color", "24-bit color (COLORTERM)")
+    } else if term.as_deref().is_some_and(|term| term.contains("256color")) {
+        Check::ok("color", "256 colors (TERM)")
+    } else {
+        Check::ok("color", format!("basic colors (TERM={})", term.as_deref().unwrap_or("not set")))
+    });
+
+    // 文字の幅（日本語や絵文字は2列）は UTF-8 のロケールで正しく数えられる
+    let locale = ["LC_ALL", "LC_CTYPE", "LANG"].iter().find_map(|name| set(name).map(|value| format!("{}={}", name, value)));
+    let utf8 = locale.as_deref().is_some_and(|locale| {
+        let locale = locale.to_ascii_lowercase();
+        locale.contains("utf-8") || locale.contains("utf8")
+    });
+    checks.push(if utf8 {
+        Check::ok("unicode", format!("UTF-8 locale ({})", locale.unwrap_or_default()))
+    } else {
+        Check::warn(
+            "unicode",
+            format!("{} is not a UTF-8 locale, so wide characters may break --frame, --align and plots", locale.as_deref().unwrap_or("the locale")),
+            "export LANG=en_US.UTF-8 (or ja_JP.UTF-8)",
+        )
+    });
+    checks
+}
+
+// ビューアーは WebGL2 を使うブラウザーで動く（gltf-cli render は GPU なしで描く）
+fn gltf(env: impl Fn(&str) -> Option<String>) -> Vec<Check> {
+    let mut checks = vec![Check::ok("render", "gltf-cli render uses a software rasterizer and needs no GPU")];
+    let built = std::fs::read_dir(Path::new(VIEWER_DIR).join("pkg"))
+        .map(|entries| entries.flatten().any(|entry| entry.path().extension().is_some_and(|extension| extension == "wasm")))
+        .unwrap_or(false);
+    checks.push(if built {
+        Check::ok("viewer", "built (my-cli gltf serve)")
+    } else if find_program(env("PATH").as_deref().unwrap_or(""), "wasm-pack").is_some() {
+        Check::warn("viewer", "not built yet", "run `make build` in gltf-viewer")
+    } else {
+        Check::warn("viewer", "not built yet, and wasm-pack is not on PATH", "cargo install wasm-pack, then run `make build` in gltf-viewer")
+    });
+    if cfg!(target_os = "linux") {
+        let render_node = std::fs::read_dir("/dev/dri")
+            .ok()
+            .and_then(|entries| entries.flatten().map(|entry| entry.path()).find(|path| path.file_name().is_some_and(|name| name.to_string_lossy().starts_with("renderD"))));
+        checks.push(match render_node {
+            Some(path) => Check::ok("gpu", format!("{} (browsers can use hardware-accelerated WebGL2)", path.display())),
+            None => Check::warn(
+                "gpu",
+                "no GPU render node in /dev/dri; browsers fall back to slow software WebGL",
+                "install the GPU driver, or use `my-cli gltf render` for previews",
+            ),
+        });
+        checks.push(if env("DISPLAY").or_else(|| env("WAYLAND_DISPLAY")).is_some() {
+            Check::ok("display", "graphical session found (DISPLAY or WAYLAND_DISPLAY)")
+        } else {
+            Check::warn(
+                "display",
+                "no graphical session (DISPLAY and WAYLAND_DISPLAY are not set)",
+                "run `my-cli gltf serve --host 0.0.0.0` and open the viewer from another machine",
+            )
+        });
+    }
+    checks
+}
+
+// PATH から実行ファイルを探す
+fn find_program(path: &str, name: &str) -> Option<PathBuf> {
+    std::env::split_paths(path).map(|dir| dir.join(name)).find(|candidate| candidate.is_file())
+}
+
+pub fn render(sections: &[Section]) -> String {
+    let mut text = String::new();
+    for section in sections {
+        text.push_str(&format!("{}\n", section.title));
+        for check in &section.checks {
+            let status = match check.status {
+                Status::Ok => "ok",
+                Status::Warn => "warn",
+                Status::Fail => "fail",
+            };
+            text.push_str(&format!("  {:<5} {}: {}\n", status, check.subject, check.detail));
+            if let Some(fix) = &check.fix {
+                text.push_str(&format!("        fix: {}\n", fix));
+            }
+        }
+    }
+    let count = |status| sections.iter().flat_map(|section| &section.checks).filter(|check| check.status == status).count();
+    text.push_str(&format!("\n{} problem(s), {} warning(s)\n", count(Status::Fail), count(Status::Warn)));
+    text
+}
+
+pub fn failed(sections: &[Section]) -> bool {
+    sections.iter().flat_map(|section| &section.checks).any(|check| check.status == Status::Fail)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_terminal() {
+        let env = |vars: &'static [(&'static str, &'static str)]| {
+            move |name: &str| vars.iter().find(|(key, _)| *key == name).map(|(_, value)| value.to_string())
+        };
+        let checks = terminal(env(&[("TERM", "xterm-256color"), ("LANG", "ja_JP.UTF-8")]), true);
+        assert!(checks.iter().all(|check| check.status == Status::Ok));
+        assert_eq!(checks[1].detail, "256 colors (TERM)");
+
+        // LC_ALL は LANG より強い
+        let checks = terminal(env(&[("NO_COLOR", "1"), ("LC_ALL", "C"), ("LANG", "en_US.UTF-8")]), false);
+        assert_eq!(checks[1].status, Status::Warn);
+        assert_eq!(checks[2].status, Status::Warn);
+        assert!(checks[2].detail.starts_with("LC_ALL=C "));
+        // 空の NO_COLOR は設定していないのと同じ
+        assert_eq!(terminal(env(&[("NO_COLOR", "")]), true)[1].status, Status::Ok);
+    }
+
+    #[test]
+    fn test_check_file() {
+        let dir = std::env::temp_dir().join(format!("my-cli-doctor-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("history");
+        assert!(check_file("history", &path).detail.ends_with("(not created yet)"));
+        std::fs::write(&path, "1 + 2\n").unwrap();
+        assert_eq!(check_file("history", &path).status, Status::Ok);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "1 + 2\n");
+        assert_eq!(check_file("history", &dir).status, Status::Fail);
+        // まだないディレクトリは保存するときに作る
+        let nested = check_file("history", &dir.join("missing").join("my-cli").join("history"));
+        assert_eq!(nested.status, Status::Ok);
+        assert!(nested.detail.ends_with("(not created yet)"));
+        assert_eq!(check_file("history", &path.join("history")).status, Status::Fail);
+        assert!(!dir.join("missing").exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_render() {
+        let sections = [Section {
+            title: "Terminal",
+            checks: vec![Check::ok("output", "a terminal"), Check::fail("color", "off", "unset NO_COLOR")],
+        }];
+        assert_eq!(
+            render(&sections),
+            "Terminal\n  ok    output: a terminal\n  fail  color: off\n        fix: unset NO_COLOR\n\n1 problem(s), 0 warning(s)\n"
+        );
+        assert!(failed(&sections));
+    }
+}
@@ -0,0 +1,92 @@
+// glTF ビューアー（my-cli gltf serve）と gltf-cli のサブコマンド（my-cli gltf info など）
+//
+// ビューアーは WebAssembly なので、wasm-pack の出力（pkg/）と index.html をブラウザーに配信する
+// python3 -m http.server と違い、.wasm を application/wasm として返す
+use std::path::{Component, Path, PathBuf};
+
+use clap::Subcommand;
+use tiny_http::{Header, Response, Server};
+
+// ワークスペースの gltf-viewer
+pub const VIEWER_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../gltf-viewer");
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Serve the viewer over HTTP (build it first with `make build` in gltf-viewer)
+    Serve {
+        /// Address to listen on
+        #[arg(long, value_name = "HOST", default_value = "127.0.0.1")]
+        host: String,
+        
+        /// Port to listen on
+        #[arg(long, value_name = "PORT", default_value_t = 8000)]
+        port: u16,
+        
+        /// Directory with index.html and pkg/ (default: gltf-viewer in this workspace)
+        #[arg(long, value_name = "PATH")]
+        dir: Option<PathBuf>,
+    },
+    
+    #[command(flatten)]
+    Tool(gltf_cli::Command),
+}
+
+pub fn run(command: Command) -> Result<(), String> {
+    match command {
+        Command::Serve { host, port, dir } => serve(&host, port, &dir.unwrap_or_else(|| PathBuf::from(VIEWER_DIR))),
+        // gltf-cli と同じ終了コードで終わる
+        Command::Tool(command) => gltf_cli::run(command).map_err(|e| gltf_cli::exit_with_error(&e)),
+    }
+}
+
+fn serve(host: &str, port: u16, dir: &Path) -> Result<(), String> {
+    if !dir.join("index.html").is_file() {
+        return Err(format!("{} has no index.html (use --dir to point at gltf-viewer)", dir.display()));
+    }
+    if !dir.join("pkg").is_dir() {
+        return Err(format!("{} has no pkg/ directory (run `make build` there first; it needs wasm-pack)", dir.display()));
+    }
+    let server = Server::http((host, port)).map_err(|e| format!("failed to listen on {}:{}: {}", host, port, e))?;
+    eprintln!("Serving {} on http://{}:{} (Ctrl-C to stop)", dir.display(), host, port);
+    for request in server.incoming_requests() {
+        let response = match resolve(dir, request.url()).and_then(|path| std::fs::read(&path).ok().map(|body| (path, body))) {
+            Some((path, body)) => Response::from_data(body).with_header(header("Content-Type", content_type(&path))),
+            None => Response::from_data(b"not found".to_vec()).with_status_code(404).with_header(header("Content-Type", "text/plain")),
+        };
+        tracing::info!(method = %request.method(), url = request.url(), status = response.status_code().0, "request");
+        if let Err(e) = request.respond(response) {
+            tracing::warn!("failed to send a response: {}", e);
+        }
+    }
+    Ok(())
+}
+
+fn header(name: &str, value: &str) -> Header {
+    Header::from_bytes(name, value).expect("header names and values are ASCII")
+}
+
+// URL のパスを dir の中のファイルにする（.. などで dir の外に出るものは見つからない扱い）
+fn resolve(dir: &Path, url: &str) -> Option<PathBuf> {
+    let path = url.split(['?', '#']).next().unwrap_or_default().trim_start_matches('/');
+    let path = if path.is_empty() || path.ends_with('/') { format!("{}index.html", path) } else { path.to_string() };
+    let relative = Path::new(&path);
+    if !relative.components().all(|component| matches!(component, Component::Normal(_))) {
+        return None;
+    }
+    Some(dir.join(relative)).filter(|path| path.is_file())
+}
+
+fn content_type(path: &Path) -> &'static str {
+    match path.extension().and_then(|extension| extension.to_str()) {
+        Some("html") => "text/html; charset=utf-8",
+        Some("js") => "text/javascript",
+        Some("wasm") => "application/wasm",
+        Some("json") => "application/json",
+        Some("css") => "text/css",
+        Some("gltf") => "model/gltf+json",
+        Some("glb") => "model/gltf-binary",
+        Some("png") => "image/png",
+        Some("jpg" | "jpeg") => "image/jpeg",
+        _ => "application/octet-stream",
+    }
+}
@@ -0,0 +1,390 @@
+use std::ffi::OsString;
+use std::path::PathBuf;
+
+use calc_cli::docs;
+use chrono::Local;
+use clap::{CommandFactory, FromArgMatches, Parser, Subcommand};
+use my_cli_errors::{Failure, EXIT_FAILURE, EXIT_USAGE};
+
+mod activity;
+mod doctor;
+mod gltf;
+mod plugin;
+
+// ツールごとのオプションはそのまま渡すので、my-cli では解釈しない
+#[derive(Parser)]
+#[command(name = "my-cli")]
+#[command(version)]
+#[command(about = "All the my-cli tools behind one command")]
+#[command(author = "Otsuka Noboru <mopinfish@gmail.ocm>")]
+#[command(after_help = "External commands:\n  \
+Any other COMMAND runs the executable my-cli-COMMAND found on PATH with the remaining arguments,\n  \
+e.g. `my-cli foo --bar` runs `my-cli-foo --bar`. MY_CLI is set to the path of my-cli.")]
+struct Cli {
+    /// Record hello, calc, gltf and external commands in the local activity log (see `my-cli recent`)
+    #[arg(long, env = "MY_CLI_RECORD", value_parser = clap::builder::BoolishValueParser::new())]
+    record: bool,
+
+    #[command(subcommand)]
+    command: Commands,
+}
+
+#[derive(Subcommand)]
+enum Commands {
+    /// Say hello (hello-cli; see `my-cli hello --help`)
+    #[command(disable_help_flag = true, disable_version_flag = true)]
+    Hello {
+        /// Options and subcommands of hello-cli
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<OsString>,
+    },
+    
+    /// Calculate (calc-cli; see `my-cli calc --help`)
+    #[command(disable_help_flag = true, disable_version_flag = true)]
+    Calc {
+        /// Expression, options and subcommands of calc-cli
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<OsString>,
+    },
+    
+    /// View glTF models in the browser (gltf-viewer) or inspect them (gltf-cli)
+    Gltf {
+        #[command(subcommand)]
+        command: gltf::Command,
+        
+        // -v と --log-format（hello と calc ではそれぞれのツールが読む）
+        #[command(flatten)]
+        log: my_cli_log::LogArgs,
+    },
+    
+    /// Print a shell completion script for my-cli and all its tools
+    #[command(after_help = "Examples:\n  my-cli completions bash > ~/.local/share/bash-completion/completions/my-cli\n  my-cli completions zsh > ~/.zfunc/_my-cli\n  my-cli completions fish > ~/.config/fish/completions/my-cli.fish\n  my-cli completions powershell >> $PROFILE")]
+    Completions {
+        /// Shell to generate completions for
+        #[arg(value_enum)]
+        shell: clap_complete::Shell,
+    },
+    
+    /// Generate documentation for my-cli and all its tools
+    #[command(after_help = "Examples:\n  my-cli docs --markdown > docs/my-cli.md\n  my-cli docs --man > my-cli.1\n  my-cli docs --man --out-dir man/")]
+    #[command(group = clap::ArgGroup::new("kind").args(["man", "markdown"]).required(true))]
+    Docs {
+        /// Print a man page (roff)
+        #[arg(long)]
+        man: bool,
+        
+        /// Print Markdown with a section for every subcommand
+        #[arg(long)]
+        markdown: bool,
+        
+        /// Write one man page per subcommand (my-cli-hello-greet.1, ...) into this directory instead of printing
+        #[arg(long, value_name = "DIR", conflicts_with = "markdown")]
+        out_dir: Option<PathBuf>,
+    },
+    
+    /// Check config files, history files, the terminal and the glTF viewer setup, and suggest fixes
+    #[command(after_help = "Each check prints ok, warn or fail; warnings and failures come with a fix. \
+Exits with 1 if any check fails.")]
+    Doctor,
+    
+    /// Show commands recorded with --record, newest last and numbered from 1 (the most recent)
+    #[command(after_help = "Recording is off until you pass --record, set MY_CLI_RECORD=1 \
+or add `record = true` under [my-cli] in ~/.config/my-cli/config.toml.\n\n\
+Examples:\n  my-cli recent --last 5\n  my-cli bookmark add slim --recent 1\n  my-cli recent --clear")]
+    Recent {
+        /// Show only the most recent N entries
+        #[arg(long, value_name = "N", default_value_t = 20)]
+        last: usize,
+        
+        /// Delete the activity log
+        #[arg(long, conflicts_with = "last")]
+        clear: bool,
+    },
+    
+    /// Save long commands under a name and run them again by name
+    #[command(after_help = "Examples:\n  my-cli bookmark add slim gltf optimize scene.gltf small.glb --max-triangles 5000\n  \
+my-cli bookmark add slim --force --recent 1\n  my-cli bookmark run slim\n  my-cli bookmark run slim --scale 0.01")]
+    Bookmark {
+        #[command(subcommand)]
+        command: BookmarkCommand,
+    },
+    
+    /// Print the versions of my-cli and its tools, git commit, build date, target and features (attach this to bug reports)
+    Version {
+        /// Print JSON
+        #[arg(long)]
+        json: bool,
+    },
+    
+    // それ以外は PATH にある my-cli-<名前> に渡す
+    #[command(external_subcommand)]
+    External(Vec<OsString>),
+}
+
+#[derive(Subcommand)]
+enum BookmarkCommand {
+    /// Save a my-cli command (options of add go before the command)
+    #[command(group = clap::ArgGroup::new("source").args(["command", "recent"]).required(true))]
+    Add {
+        /// Bookmark name (one word)
+        #[arg(value_name = "NAME")]
+        name: String,
+        
+        /// Replace a bookmark with the same name
+        #[arg(long)]
+        force: bool,
+        
+        /// Save entry N of `my-cli recent` (1 is the most recent) instead of a command
+        #[arg(long, value_name = "N")]
+        recent: Option<usize>,
+        
+        /// Command to save, without my-cli (e.g. gltf optimize in.glb out.glb)
+        #[arg(value_name = "COMMAND", trailing_var_arg = true, allow_hyphen_values = true)]
+        command: Vec<String>,
+    },
+    
+    /// List bookmarks
+    List,
+    
+    /// Delete a bookmark
+    Remove {
+        /// Bookmark name
+        #[arg(value_name = "NAME")]
+        name: String,
+    },
+    
+    /// Run a bookmark in the current directory
+    Run {
+        /// Bookmark name
+        #[arg(value_name = "NAME")]
+        name: String,
+        
+        /// Arguments appended to the saved command
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<OsString>,
+    },
+}
+
+fn main() {
+    run(std::env::args_os().collect());
+}
+
+// args の最初は my-cli（bookmark run は保存したコマンドでもう一度呼ぶ）
+fn run(args: Vec<OsString>) {
+    // 設定ファイルの [my-cli] の値をオプションの既定値にする
+    let config = activity::load_config(&Cli::command());
+    let matches = my_cli_config::apply(Cli::command(), config.as_deref().unwrap_or_default()).get_matches_from(&args);
+    let cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    // doctor は設定ファイルの誤りも診断して表示する
+    if let Err(e) = &config
+        && !matches!(cli.command, Commands::Doctor)
+    {
+        my_cli_errors::exit(&Failure::new(EXIT_USAGE, format!("invalid config file: {}", e)));
+    }
+    if cli.record && matches!(cli.command, Commands::Hello { .. } | Commands::Calc { .. } | Commands::Gltf { .. } | Commands::External(_)) {
+        record(&args);
+    }
+    match cli.command {
+        Commands::Hello { args } => hello_cli::main_from(with_name("my-cli hello", args)),
+        Commands::Calc { args } => calc_cli::main_from(with_name("my-cli calc", args)),
+        Commands::Gltf { command, log } => {
+            my_cli_log::init(&log);
+            if let Err(e) = gltf::run(command) {
+                my_cli_errors::exit(&Failure::new(EXIT_FAILURE, e));
+            }
+        }
+        Commands::Completions { shell } => {
+            clap_complete::generate(shell, &mut command(), "my-cli", &mut std::io::stdout());
+        }
+        Commands::Docs { man, out_dir, .. } => {
+            let mut command = command();
+            let written = match (man, out_dir) {
+                (true, Some(dir)) => docs::write_man_pages(&mut command, &dir)
+                    .map(|count| println!("Wrote {} man page(s) to {}", count, dir.display()))
+                    .map_err(|e| format!("failed to write man pages to {}: {}", dir.display(), e)),
+                (true, None) => docs::man_page(command, &mut std::io::stdout()).map_err(|e| e.to_string()),
+                (false, _) => {
+                    print!("{}", docs::markdown(&mut command));
+                    Ok(())
+                }
+            };
+            if let Err(e) = written {
+                my_cli_errors::exit(&Failure::new(EXIT_FAILURE, e));
+            }
+        }
+        Commands::Doctor => {
+            let sections = doctor::run();
+            print!("{}", doctor::render(&sections));
+            if doctor::failed(&sections) {
+                std::process::exit(EXIT_FAILURE);
+            }
+        }
+        Commands::Version { json } => {
+            let info = build_info();
+            if json {
+                println!("{}", info.json());
+            } else {
+                print!("{}", info.text());
+            }
+        }
+        Commands::Recent { last, clear } => {
+            let Some(path) = activity::log_path() else {
+                my_cli_errors::exit(&Failure::new(EXIT_FAILURE, "cannot find the data directory for the activity log"));
+            };
+            if clear {
+                match std::fs::remove_file(&path) {
+                    Ok(()) => println!("Deleted {}", path.display()),
+                    Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                    Err(e) => my_cli_errors::exit(&Failure::new(EXIT_FAILURE, format!("cannot delete {}: {}", path.display(), e))),
+                }
+                return;
+            }
+            let entries = activity::read(&path).unwrap_or_else(|e| my_cli_errors::exit(&Failure::new(EXIT_FAILURE, e)));
+            for (number, entry) in activity::recent(&entries, last) {
+                println!("{:>4}  {}  {}  {}", number, entry.time.format("%Y-%m-%d %H:%M"), entry.dir.display(), activity::command_line(&entry.args));
+            }
+        }
+        Commands::Bookmark { command } => {
+            if let Err(e) = bookmark(command) {
+                my_cli_errors::exit(&e);
+            }
+        }
+        Commands::External(args) => match plugin::run(args) {
+            Ok(code) => std::process::exit(code),
+            Err(e) => my_cli_errors::exit(&e),
+        },
+    }
+}
+
+// 補完とドキュメント用の定義（hello と calc は引数をそのまま渡すだけなので、各ツールの定義に差し替える）
+fn command() -> clap::Command {
+    let command = Cli::command();
+    let names: Vec<String> = command.get_subcommands().map(|sub| sub.get_name().to_string()).collect();
+    // mut_subcommand は書き換えたものを最後に回すので、全てを順に通して並びを保つ
+    names.iter().fold(command, |command, name| {
+        command.mut_subcommand(name, |wrapper| {
+            let tool = match name.as_str() {
+                "hello" => hello_cli::command(),
+                "calc" => calc_cli::command(),
+                _ => return wrapper,
+            };
+            tool.name(name).about(wrapper.get_about().cloned().unwrap_or_default())
+        })
+    })
+}
+
+// my-cli より後の引数を記録する（書けなくてもコマンドは実行する）
+fn record(args: &[OsString]) {
+    // --record などの my-cli のオプションは残さない
+    // UTF-8 でない引数は置き換えると別のコマンドになるので記録しない
+    let Some(args) = args.iter().skip(1).map(|arg| arg.to_str().map(str::to_string)).collect::<Option<Vec<String>>>() else {
+        eprintln!("warning: not recording the command because an argument is not valid UTF-8");
+        return;
+    };
+    let args: Vec<String> = args.into_iter().skip_while(|arg| arg.starts_with('-')).collect();
+    let entry = activity::Entry { time: Local::now(), dir: std::env::current_dir().unwrap_or_default(), args };
+    if let Some(Err(e)) = activity::log_path().map(|path| activity::record(&path, &entry)) {
+        eprintln!("warning: could not record the command: {}", e);
+    }
+}
+
+fn bookmark(command: BookmarkCommand) -> Result<(), Failure> {
+    let failure = |message: String| Failure::new(EXIT_FAILURE, message);
+    let path = activity::bookmarks_path().ok_or_else(|| failure("cannot find the data directory for bookmarks".to_string()))?;
+    let mut bookmarks = activity::load_bookmarks(&path).map_err(failure)?;
+    match command {
+        BookmarkCommand::Add { name, force, recent, command } => {
+            activity::check_name(&name).map_err(|e| Failure::new(EXIT_USAGE, e))?;
+            let args = match recent {
+                Some(number) => {
+                    let entries = activity::log_path().map_or(Ok(Vec::new()), |path| activity::read(&path)).map_err(failure)?;
+                    let index = entries.len().checked_sub(number).filter(|_| number > 0);
+                    let entry = index.map(|index| &entries[index]).ok_or_else(|| {
+                        Failure::new(EXIT_USAGE, format!("there is no entry {} in `my-cli recent` ({} recorded)", number, entries.len()))
+                    })?;
+                    entry.args.clone()
+                }
+                None => command,
+            };
+            // 保存する前に、my-cli のコマンドとして読めるかを確かめる
+            if args.first().is_some_and(|word| word == "bookmark") {
+                return Err(Failure::new(EXIT_USAGE, "a bookmark cannot run another bookmark command"));
+            }
+            Cli::try_parse_from(std::iter::once("my-cli".to_string()).chain(args.iter().cloned()))
+                .map_err(|e| Failure::new(EXIT_USAGE, format!("`{}` is not a my-cli command: {}", activity::command_line(&args), e.kind())))?;
+            if bookmarks.iter().any(|bookmark| bookmark.name == name) {
+                if !force {
+                    return Err(Failure::new(EXIT_USAGE, format!("bookmark '{}' already exists (use --force to replace it)", name)));
+                }
+                bookmarks.retain(|bookmark| bookmark.name != name);
+            }
+            println!("{}: {}", name, activity::command_line(&args));
+            bookmarks.push(activity::Bookmark { name, args, created: Local::now() });
+            activity::save_bookmarks(&path, &bookmarks).map_err(failure)?;
+        }
+        BookmarkCommand::List => {
+            let width = bookmarks.iter().map(|bookmark| bookmark.name.chars().count()).max().unwrap_or(0);
+            for bookmark in &bookmarks {
+                println!("{:<width$}  {}", bookmark.name, activity::command_line(&bookmark.args), width = width);
+            }
+        }
+        BookmarkCommand::Remove { name } => {
+            let count = bookmarks.len();
+            bookmarks.retain(|bookmark| bookmark.name != name);
+            if bookmarks.len() == count {
+                return Err(unknown_bookmark(&name));
+            }
+            activity::save_bookmarks(&path, &bookmarks).map_err(failure)?;
+        }
+        BookmarkCommand::Run { name, args } => {
+            let bookmark = bookmarks.into_iter().find(|bookmark| bookmark.name == name).ok_or_else(|| unknown_bookmark(&name))?;
+            let saved = bookmark.args.into_iter().map(OsString::from);
+            run(std::iter::once(OsString::from("my-cli")).chain(saved).chain(args).collect());
+        }
+    }
+    Ok(())
+}
+
+fn unknown_bookmark(name: &str) -> Failure {
+    Failure::new(EXIT_USAGE, format!("no bookmark named '{}' (see `my-cli bookmark list`)", name))
+}
+
+// my-cli と、一緒にビルドした全てのツール
+fn build_info() -> my_cli_build_info::BuildInfo {
+    my_cli_build_info::build_info!()
+        .with_crates(hello_cli::build_info().as_crates())
+        .with_crates(calc_cli::build_info().as_crates())
+        .with_crates(gltf_cli::build_info().as_crates())
+}
+
+// 使い方の表示に出るコマンド名を先頭に付ける
+fn with_name(name: &str, args: Vec<OsString>) -> impl Iterator<Item = OsString> {
+    std::iter::once(OsString::from(name)).chain(args)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_command() {
+        let mut command = command();
+        command.build();
+        // 各ツールのサブコマンドとオプションまで補完できる
+        let hello = command.find_subcommand("hello").unwrap();
+        assert!(hello.find_subcommand("greet").is_some());
+        assert!(hello.get_arguments().any(|arg| arg.get_long() == Some("lang")));
+        let calc = command.find_subcommand("calc").unwrap();
+        assert!(calc.find_subcommand("eval").is_some());
+        assert_eq!(calc.get_about().map(|about| about.to_string()), Cli::command().find_subcommand("calc").unwrap().get_about().map(|about| about.to_string()));
+        assert_eq!(calc.find_subcommand("eval").unwrap().get_display_name(), Some("my-cli-calc-eval"));
+    }
+
+    #[test]
+    fn test_build_info() {
+        let names: Vec<&str> = build_info().crates.iter().map(|krate| krate.name).collect();
+        assert_eq!(names, ["hello-cli", "calc-cli", "calc-core", "gltf-cli"]);
+        let calc_core = &build_info().crates[2];
+        assert!(calc_core.features.contains(&"errors"));
+    }
+}
@@ -0,0 +1,96 @@
+// 外部コマンド（git と同じく my-cli foo は PATH にある my-cli-foo を実行する）
+//
+// 引数はそのまま渡し、環境変数も引き継ぐ。加えて MY_CLI に my-cli 自身のパスを入れるので、
+// プラグインから my-cli hello などを呼び戻せる
+// 終了コードはプラグインのものをそのまま返す
+use std::ffi::{OsStr, OsString};
+use std::path::{Path, PathBuf};
+use std::process::ExitStatus;
+
+use my_cli_errors::{Failure, EXIT_FAILURE, EXIT_USAGE};
+
+// プラグインの実行ファイル名の接頭辞
+pub const PREFIX: &str = "my-cli-";
+
+// args の最初がサブコマンド名
+pub fn run(args: Vec<OsString>) -> Result<i32, Failure> {
+    let (name, args) = args.split_first().expect("clap passes the external subcommand name first");
+    let program = find(name, std::env::var_os("PATH").as_deref()).ok_or_else(|| {
+        Failure::new(
+            EXIT_USAGE,
+            format!("unrecognized subcommand '{}' (no {}{} on PATH; see `my-cli --help`)", name.to_string_lossy(), PREFIX, name.to_string_lossy()),
+        )
+    })?;
+    let mut command = std::process::Command::new(&program);
+    command.args(args);
+    if let Ok(exe) = std::env::current_exe() {
+        command.env("MY_CLI", exe);
+    }
+    let status = command.status().map_err(|e| Failure::new(EXIT_FAILURE, format!("failed to run {}: {}", program.display(), e)))?;
+    Ok(exit_code(status))
+}
+
+// PATH の前のディレクトリにあるものを優先する
+pub fn find(name: &OsStr, path: Option<&OsStr>) -> Option<PathBuf> {
+    // my-cli ../foo で PATH の外を実行しないようにする
+    let name = name.to_str().filter(|name| !name.is_empty() && !name.contains(['/', '\\']))?;
+    let file = format!("{}{}{}", PREFIX, name, std::env::consts::EXE_SUFFIX);
+    std::env::split_paths(path?).map(|dir| dir.join(&file)).find(|candidate| is_executable(candidate))
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    path.metadata().is_ok_and(|metadata| metadata.is_file() && metadata.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+// シグナルで終わったときはシェルと同じく 128 + シグナル番号
+fn exit_code(status: ExitStatus) -> i32 {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        if let Some(signal) = status.signal() {
+            return 128 + signal;
+        }
+    }
+    status.code().unwrap_or(EXIT_FAILURE)
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+
+    #[test]
+    fn test_find() {
+        let base = std::env::temp_dir().join(format!("my-cli-plugin-test-{}", std::process::id()));
+        let (first, second) = (base.join("first"), base.join("second"));
+        for dir in [&first, &second] {
+            std::fs::create_dir_all(dir).unwrap();
+        }
+        let write = |path: PathBuf, mode: u32| {
+            std::fs::write(&path, "#!/bin/sh\n").unwrap();
+            std::fs::set_permissions(&path, std::fs::Permissions::from_mode(mode)).unwrap();
+        };
+        write(first.join("my-cli-foo"), 0o644);
+        write(second.join("my-cli-foo"), 0o755);
+        write(second.join("my-cli-bar"), 0o755);
+        write(first.join("my-cli-bar"), 0o755);
+        let path = std::env::join_paths([&first, &second]).unwrap();
+
+        // 実行できないものは飛ばし、PATH の前のほうを使う
+        assert_eq!(find(OsStr::new("foo"), Some(&path)), Some(second.join("my-cli-foo")));
+        assert_eq!(find(OsStr::new("bar"), Some(&path)), Some(first.join("my-cli-bar")));
+        assert_eq!(find(OsStr::new("baz"), Some(&path)), None);
+        assert_eq!(find(OsStr::new("../first/my-cli-bar"), Some(&path)), None);
+        assert_eq!(find(OsStr::new(""), Some(&path)), None);
+        assert_eq!(find(OsStr::new("foo"), None), None);
+
+        std::fs::remove_dir_all(&base).unwrap();
+    }
+}
@@ -0,0 +1,4 @@
+// version サブコマンドに出すコミット、ビルド日、ターゲットなどを埋め込む
+fn main() {
+    my_cli_build_info::emit();
+}
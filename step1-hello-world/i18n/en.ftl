@@ -0,0 +1,14 @@
+# hello-cli のメッセージ（英語）
+# ヘルプは lib.rs の doc コメントの英語をそのまま使うので、ヘルプの訳は ja.ftl にだけ書く
+
+invalid-config = invalid config file { $error }
+no-home-config = HOME is not set, so there is no config directory
+no-home-history = HOME is not set, so there is no history file
+wrote = Wrote { $path }
+unsupported-lang = unsupported language '{ $lang }' (see --list-langs)
+read-names-failed = failed to read names from { $path }: { $error }
+read-stdin-failed = failed to read names from standard input: { $error }
+no-names = no names to greet (blank lines and lines starting with # are skipped)
+rejected-name = rejected name { $name }: { $reason }
+qr-file-endless = --qr-file cannot be used with --count 0
+greeting-until-interrupted = Greeting until Ctrl-C
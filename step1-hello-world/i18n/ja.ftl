@@ -0,0 +1,76 @@
+# hello-cli のメッセージ（日本語）
+# ID の決まりは my-cli-i18n の localize を参照
+
+invalid-config = 設定ファイルが正しくありません: { $error }
+no-home-config = HOME が設定されていないため、設定ディレクトリがありません
+no-home-history = HOME が設定されていないため、履歴ファイルがありません
+wrote = { $path } に書き込みました
+unsupported-lang = 対応していない言語です: '{ $lang }'（--list-langs を参照）
+read-names-failed = { $path } から名前を読めませんでした: { $error }
+read-stdin-failed = 標準入力から名前を読めませんでした: { $error }
+no-names = 挨拶する名前がありません（空行と # で始まる行は読み飛ばします）
+rejected-name = 名前 { $name } を拒否しました: { $reason }
+qr-file-endless = --qr-file は --count 0 と一緒に使えません
+greeting-until-interrupted = Ctrl-C で止めるまで挨拶します
+
+## ヘルプ
+
+about = シンプルな Hello World CLI ツール
+greet-about = 挨拶する（サブコマンドを省略したときと同じ）
+farewell-about = 別れの挨拶をする
+custom-about = 名前ごとに自分で決めたメッセージを表示する
+interactive-about = 名前、言語、スタイルを尋ねてから挨拶する
+history-about = --log で保存した最近の挨拶を表示する
+serve-about = HTTP で挨拶を返す（例: GET /greet?name=Alice&lang=ja）
+version-about = バージョン、git のコミット、ビルド日、ターゲット、フィーチャを表示する（不具合の報告に添える）
+version-arg-json = JSON で表示する
+
+arg-list-langs = 対応している言語を一覧表示して終了する
+arg-completions = シェルの補完スクリプト（bash、zsh、fish、powershell、elvish）を出力して終了する
+arg-init-config = コメント付きの設定ファイルのひな形を ~/.config/hello-cli/config.toml に書き込んで終了する
+arg-verbose = 標準エラー出力のログを増やす: -v は info、-vv は debug、-vvv は trace（RUST_LOG があればそちらを優先）
+arg-log-format = 標準エラー出力のログの形式
+
+arg-name = 挨拶する名前（繰り返すと複数の人に挨拶する）
+arg-combine = 全員に1文で挨拶する（例: "Hello, Alice, Bob and Carol!"）
+arg-count = 挨拶する回数（0 なら Ctrl-C まで繰り返す）
+arg-interval = 挨拶の間に待つ時間（例: 500ms、2s、1m）
+arg-case = 挨拶の大文字・小文字を変える
+arg-uppercase = 挨拶を大文字で表示する（--case upper と同じ）
+arg-lang = 挨拶とメッセージの言語（例: ja、en、es。既定は LC_ALL、LC_MESSAGES、LANG から）
+arg-at = 現在の時刻の代わりにこの時刻を使う
+arg-date-format = テンプレートの {"{"}date{"}"} の書式（例: "%Y-%m-%d"。既定は言語ごとの書式）
+arg-typewriter = 挨拶を1文字ずつ表示する（1文字あたり 50 や 50ms。テキスト形式のみ）
+arg-countdown = 最初の挨拶の前に N から1秒ずつ数える（テキスト形式のみ）
+arg-emoji = 挨拶に絵文字を付ける
+arg-frame = 挨拶を枠で囲み、端末の幅で折り返す（テキスト形式のみ）
+arg-qr = 挨拶ごとに文の上に QR コードを表示する（テキスト形式のみ）
+arg-qr-file = 挨拶（1行に1つ）を QR コードの PNG 画像に保存する
+arg-align = 端末の幅の中で挨拶を揃える（テキスト形式のみ。端末か COLUMNS が必要）
+arg-strict = 制御文字や ANSI エスケープシーケンスを含む名前を拒否する（信頼できない入力用）
+arg-max-length = この文字数より長い名前を「…」で終わるように縮める
+arg-names-file = ファイルの名前（1行に1つ）ごとに挨拶する
+arg-stdin = 標準入力から読んだ名前（1行に1つ）ごとに挨拶する
+arg-format = 出力形式（json は1行に1つのオブジェクト、csv は最初に見出しの行）
+arg-output = 挨拶を標準出力の代わりにファイルに書き込む
+arg-append = --output のファイルを置き換えずに追記する
+arg-color = 出力に色を付けるか（auto は NO_COLOR に従い、端末のときだけ色を付ける）
+arg-style = 色を付けるときの挨拶のスタイル（テキスト形式のみ）
+arg-log = 挨拶を時刻と一緒に ~/.hello_history に追記する（history サブコマンドを参照）
+arg-quiet = 番号、絵文字、枠、色を付けずにメッセージだけを表示する
+arg-template = {"{"}name{"}"}、{"{"}index{"}"}、{"{"}time_of_day{"}"} などのプレースホルダー（下の一覧）を使った挨拶
+arg-greeting-word = 言語ごとの言葉の代わりに使う挨拶の言葉（例: "Howdy"）
+arg-punctuation = 挨拶の最後に付ける句読点（例: "!!!"）
+arg-smart-greeting = 時刻に合わせて朝・昼・夜の挨拶をする
+arg-random = ランダムに選んだ言語で挨拶する
+arg-seed = --random のシード（毎回同じ挨拶を選ぶ）
+arg-all = 組み込みの全ての言語で1回ずつ挨拶する
+arg-message = {"{"}name{"}"}、{"{"}index{"}"}、{"{"}time_of_day{"}"} などのプレースホルダー（下の一覧）を使ったメッセージ
+
+history-arg-last = 最近の N 件だけを表示する
+history-arg-name = この名前への挨拶だけ
+history-arg-command = このサブコマンド（greet、farewell、custom）の挨拶だけ
+history-arg-contains = この文字列を含む挨拶だけ
+serve-arg-host = 待ち受けるアドレス
+serve-arg-port = 待ち受けるポート
+serve-arg-lang = リクエストに lang パラメーターがないときの言語
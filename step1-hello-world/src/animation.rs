@@ -0,0 +1,33 @@
+// 端末でのデモ向けの表示（--typewriter、--countdown）
+//
+// タイプライターは見える文字ごとに区切り、色のエスケープシーケンスは次の文字と一緒に書く
+// 秒読みは「3...」「2...」「1...」と1秒ごとに数える
+
+// 行を1文字ずつ書き出す単位に分ける（エスケープシーケンスだけでは区切らない）
+pub fn keystrokes(line: &str) -> Vec<&str> {
+    let mut keystrokes = Vec::new();
+    let mut start = 0;
+    let mut chars = line.char_indices().peekable();
+    while let Some((_, c)) = chars.next() {
+        if c == '\x1b' {
+            chars.by_ref().find(|&(_, c)| c == 'm');
+            continue;
+        }
+        let end = chars.peek().map_or(line.len(), |&(i, _)| i);
+        keystrokes.push(&line[start..end]);
+        start = end;
+    }
+    // 最後の文字の後ろのエスケープシーケンス（色を戻すもの）
+    if start < line.len() {
+        match keystrokes.last_mut() {
+            Some(last) => *last = &line[start - last.len()..],
+            None => keystrokes.push(&line[start..]),
+        }
+    }
+    keystrokes
+}
+
+// N から 1 まで数える行
+pub fn countdown(from: u32) -> impl Iterator<Item = String> {
+    (1..=from).rev().map(|n| format!("{}...", n))
+}
@@ -0,0 +1,81 @@
+// 設定ファイル（~/.config/hello-cli/config.toml）
+//
+// 優先順位: コマンドラインのフラグ > 環境変数（HELLO_NAME など） > 設定ファイル > 組み込みの既定値
+// 設定ファイルは my-cli の他のツールと同じく重ねて読む（my-cli-config）
+//   プロジェクトの .my-cli.toml の [hello] > このファイル > ~/.config/my-cli/config.toml の [hello] > /etc/my-cli/config.toml の [hello]
+// 設定ファイルの値は clap の既定値として渡すので、値の検証もフラグと同じ規則で行われる
+use std::path::{Path, PathBuf};
+
+use clap::Command;
+use my_cli_config::{Section, Setting};
+
+pub use my_cli_config::ConfigError;
+
+// 設定できるキー（オプションの --long 名と同じ。そのオプションがあるサブコマンドにだけ効く）
+pub const KEYS: &[&str] = &["name", "lang", "template", "style", "color"];
+
+// 共通の設定ファイルでは [hello] の表に書く
+pub const SECTION: Section = Section { tool: "hello", keys: KEYS };
+
+// --init-config で書き出す雛形
+pub const STARTER: &str = r#"# hello-cli configuration
+#
+# Each key sets the default of the option with the same name.
+# Options given on the command line and the HELLO_NAME, HELLO_LANG and
+# HELLO_STYLE environment variables win over this file, and so does the
+# [hello] table of a .my-cli.toml in the current directory or its parents.
+
+# Name to greet when no --name is given
+# name = "World"
+
+# Language of the greeting (see hello-cli --list-langs)
+# lang = "en"
+
+# Custom greeting with {name}, {index}, {count}, {date}, {time} and {time_of_day}
+# template = "Good {time_of_day}, {name}!"
+
+# Style of the greeting: "bold" or "rainbow"
+# style = "bold"
+
+# When to color the output: "auto", "always" or "never"
+# color = "auto"
+"#;
+
+#[derive(Debug, Default)]
+pub struct Config {
+    settings: Vec<Setting>,
+}
+
+impl Config {
+    pub fn default_path() -> Option<PathBuf> {
+        std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config").join("hello-cli").join("config.toml"))
+    }
+
+    // 全ての層の設定ファイルを重ねる（ないファイルは飛ばす）
+    pub fn load(command: &Command) -> Result<Config, ConfigError> {
+        let settings = SECTION.load(&SECTION.files(Config::default_path()), command)?;
+        Ok(Config { settings })
+    }
+
+    // 設定ファイルの値を各オプションの既定値にする
+    pub fn apply(&self, command: Command) -> Command {
+        my_cli_config::apply(command, &self.settings)
+    }
+
+    // -vv のときに読んだ値を出す
+    pub fn trace(&self) {
+        my_cli_config::trace(&self.settings);
+    }
+}
+
+// 雛形を書き出す（既にあれば上書きしない）
+pub fn init(path: &Path) -> Result<(), ConfigError> {
+    let error = |message: String| ConfigError { path: path.to_path_buf(), message };
+    if path.exists() {
+        return Err(error("already exists (remove it first to start over)".to_string()));
+    }
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| error(e.to_string()))?;
+    }
+    std::fs::write(path, STARTER).map_err(|e| error(e.to_string()))
+}
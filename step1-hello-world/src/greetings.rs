@@ -0,0 +1,61 @@
+// いろいろな言語の挨拶（--random で1つ選び、--all で全て表示する）
+//
+// 挨拶は {name} を名前に置き換えるテンプレート
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+
+// 言語コードと挨拶
+pub const POOL: &[(&str, &str)] = &[
+    ("en", "Hello, {name}!"),
+    ("ja", "こんにちは、{name}！"),
+    ("es", "¡Hola, {name}!"),
+    ("fr", "Bonjour, {name} !"),
+    ("de", "Hallo, {name}!"),
+    ("it", "Ciao, {name}!"),
+    ("pt", "Olá, {name}!"),
+    ("zh", "你好，{name}！"),
+    ("ko", "안녕하세요, {name}!"),
+    ("ru", "Привет, {name}!"),
+    ("nl", "Hallo, {name}!"),
+    ("sv", "Hej, {name}!"),
+    ("fi", "Hei, {name}!"),
+    ("pl", "Cześć, {name}!"),
+    ("tr", "Merhaba, {name}!"),
+    ("el", "Γεια σου, {name}!"),
+    ("ar", "مرحبا، {name}!"),
+    ("hi", "नमस्ते, {name}!"),
+    ("sw", "Jambo, {name}!"),
+    ("haw", "Aloha, {name}!"),
+];
+
+pub fn greet(greeting: &str, name: &str) -> String {
+    greeting.replace("{name}", name)
+}
+
+// 挨拶を選ぶ乱数（SplitMix64。同じシードからは常に同じ順に選ぶ）
+pub struct Picker {
+    state: u64,
+}
+
+impl Picker {
+    pub fn seeded(seed: u64) -> Picker {
+        Picker { state: seed }
+    }
+
+    // シードを指定しない場合は実行ごとに異なる
+    pub fn from_entropy() -> Picker {
+        Picker::seeded(RandomState::new().build_hasher().finish())
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    pub fn pick(&mut self) -> &'static str {
+        POOL[(self.next_u64() % POOL.len() as u64) as usize].1
+    }
+}
@@ -0,0 +1,75 @@
+// 挨拶の履歴（--log で ~/.hello_history に追記し、history サブコマンドで表示する）
+//
+// 1行に1つの JSON（{"time":"2026-01-02T09:30:00+09:00","command":"greet","name":"Alice","message":"Hello, Alice!"}）
+use std::fs::OpenOptions;
+use std::io::{LineWriter, Write};
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+use crate::output;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Entry {
+    pub time: DateTime<Local>,
+    pub command: String,
+    pub name: String,
+    pub message: String,
+}
+
+// 表示する履歴の条件（名前とコマンドは大文字・小文字を区別しない）
+#[derive(Debug, Default)]
+pub struct Filter {
+    pub name: Option<String>,
+    pub command: Option<String>,
+    pub contains: Option<String>,
+}
+
+pub struct Log {
+    path: PathBuf,
+    writer: LineWriter<std::fs::File>,
+}
+
+pub fn default_path() -> Option<PathBuf> {
+    std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".hello_history"))
+}
+
+impl Log {
+    pub fn open(path: &Path) -> Result<Log, String> {
+        let file = OpenOptions::new().create(true).append(true).open(path);
+        let file = file.map_err(|e| output::describe(&e, &path.display().to_string()))?;
+        tracing::debug!("appending greetings to {}", path.display());
+        Ok(Log { path: path.to_path_buf(), writer: LineWriter::new(file) })
+    }
+
+    pub fn append(&mut self, entry: &Entry) -> Result<(), String> {
+        let json = serde_json::to_string(entry).expect("entries always serialize");
+        writeln!(self.writer, "{}", json).map_err(|e| output::describe(&e, &self.path.display().to_string()))
+    }
+}
+
+// 履歴を古い順に読む（ファイルがなければ空、読めない行は飛ばす）
+pub fn read(path: &Path) -> Result<Vec<Entry>, String> {
+    let contents = match std::fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(format!("failed to read {}: {}", path.display(), e)),
+    };
+    Ok(contents.lines().filter_map(|line| serde_json::from_str(line).ok()).collect())
+}
+
+impl Filter {
+    pub fn matches(&self, entry: &Entry) -> bool {
+        let same = |expected: &Option<String>, actual: &str| expected.as_ref().is_none_or(|expected| expected.eq_ignore_ascii_case(actual));
+        same(&self.name, &entry.name)
+            && same(&self.command, &entry.command)
+            && self.contains.as_ref().is_none_or(|text| entry.message.to_lowercase().contains(&text.to_lowercase()))
+    }
+}
+
+// 条件に合う最近の last 件（古い順）
+pub fn recent<'a>(entries: &'a [Entry], filter: &Filter, last: usize) -> Vec<&'a Entry> {
+    let matched: Vec<&Entry> = entries.iter().filter(|entry| filter.matches(entry)).collect();
+    matched[matched.len().saturating_sub(last)..].to_vec()
+}
@@ -0,0 +1,46 @@
+// hello-cli のメッセージ（i18n/*.ftl。挨拶そのものは locale.rs の表）
+//
+// 表示の言語は --lang（なければ HELLO_LANG、LC_ALL、LC_MESSAGES、LANG）で選び、
+// メッセージのない言語（es など）では英語にする
+use std::sync::OnceLock;
+
+use my_cli_i18n::{FluentValue, Messages};
+
+const CATALOGS: &[(&str, &str)] = &[("en", include_str!("../i18n/en.ftl")), ("ja", include_str!("../i18n/ja.ftl"))];
+const LANGUAGES: &[&str] = &["en", "ja"];
+
+static MESSAGES: OnceLock<Messages> = OnceLock::new();
+
+// 引数を読む前に1回だけ呼ぶ（lang は --lang の値）
+pub fn init(lang: Option<&str>) -> &'static Messages {
+    let lang = lang.map(str::to_string).or_else(|| std::env::var("HELLO_LANG").ok());
+    MESSAGES.get_or_init(|| Messages::new(CATALOGS, &my_cli_i18n::detect(lang.as_deref(), LANGUAGES)))
+}
+
+// init の前（テストなど）は英語
+pub fn messages() -> &'static Messages {
+    MESSAGES.get_or_init(|| Messages::new(CATALOGS, "en"))
+}
+
+pub fn tr(id: &str, args: &[(&str, FluentValue)]) -> String {
+    messages().format(id, args)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // どの ID も英語のカタログにある
+    #[test]
+    fn test_catalogs() {
+        let ja = Messages::new(CATALOGS, "ja");
+        let en = Messages::new(CATALOGS, "en");
+        for id in ["wrote", "unsupported-lang", "no-names", "rejected-name", "qr-file-endless", "greeting-until-interrupted"] {
+            assert_ne!(en.get(id), id);
+            assert_ne!(ja.get(id), en.get(id));
+        }
+        assert_eq!(ja.format("unsupported-lang", &[("lang", "xx".into())]), "対応していない言語です: 'xx'（--list-langs を参照）");
+        let command = my_cli_i18n::localize(crate::command(), &ja);
+        assert_eq!(command.get_about().unwrap().to_string(), "シンプルな Hello World CLI ツール");
+    }
+}
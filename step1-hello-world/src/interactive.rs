@@ -0,0 +1,58 @@
+// 対話モード（hello-cli interactive）
+//
+// 名前・言語・スタイルを順に尋ねてから挨拶する
+use dialoguer::theme::ColorfulTheme;
+use dialoguer::{Input, Select};
+
+use crate::locale::{self, Locale};
+use crate::style::Style;
+
+// 名前の長さの上限（文字数）
+pub const MAX_NAME_LENGTH: usize = 64;
+
+pub struct Answers {
+    pub name: String,
+    pub locale: &'static Locale,
+    pub style: Option<Style>,
+}
+
+pub fn ask(default_locale: &Locale) -> dialoguer::Result<Answers> {
+    let theme = ColorfulTheme::default();
+    let name: String = Input::with_theme(&theme)
+        .with_prompt("Your name")
+        .validate_with(|input: &String| validate_name(input))
+        .interact_text()?;
+
+    let languages: Vec<String> = locale::LOCALES.iter().map(|locale| format!("{} ({})", locale.name, locale.code)).collect();
+    let language = Select::with_theme(&theme)
+        .with_prompt("Language")
+        .items(&languages)
+        .default(locale::LOCALES.iter().position(|locale| locale.code == default_locale.code).unwrap_or(0))
+        .interact()?;
+
+    let styles = ["plain", "bold", "rainbow"];
+    let style = Select::with_theme(&theme).with_prompt("Style").items(&styles).default(0).interact()?;
+
+    Ok(Answers {
+        name: name.trim().to_string(),
+        locale: &locale::LOCALES[language],
+        style: match style {
+            1 => Some(Style::Bold),
+            2 => Some(Style::Rainbow),
+            _ => None,
+        },
+    })
+}
+
+fn validate_name(input: &str) -> Result<(), String> {
+    let name = input.trim();
+    if name.is_empty() {
+        Err("Please enter a name".to_string())
+    } else if name.chars().count() > MAX_NAME_LENGTH {
+        Err(format!("Names can be at most {} characters", MAX_NAME_LENGTH))
+    } else if name.chars().any(char::is_control) {
+        Err("Names cannot contain control characters".to_string())
+    } else {
+        Ok(())
+    }
+}
@@ -0,0 +1,109 @@
+// 挨拶の飾りと配置（--emoji wave|party、--frame、--align left|center|right）
+//
+// 幅は表示幅で数える（全角文字や絵文字は2、色のエスケープシーケンスは0）
+// 枠は挨拶に合わせた大きさにし、端末の幅に収まらなければ挨拶を折り返す
+use clap::ValueEnum;
+use terminal_size::{terminal_size, Width};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Emoji {
+    Wave,
+    Party,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum Align {
+    #[default]
+    Left,
+    Center,
+    Right,
+}
+
+// 端末の幅が分からないとき
+const DEFAULT_WIDTH: usize = 80;
+
+pub fn decorate(message: &str, emoji: Emoji) -> String {
+    match emoji {
+        Emoji::Wave => format!("👋 {}", message),
+        Emoji::Party => format!("🎉 {} 🎉", message),
+    }
+}
+
+// COLUMNS があればその値、なければ端末に問い合わせる
+pub fn terminal_width() -> usize {
+    columns().or_else(|| terminal_size().map(|(Width(width), _)| width as usize)).unwrap_or(DEFAULT_WIDTH)
+}
+
+// 配置に使う幅（端末でなければ COLUMNS があるときだけ。なければ配置しない）
+pub fn align_width(terminal: bool) -> Option<usize> {
+    if terminal { Some(terminal_width()) } else { columns() }
+}
+
+fn columns() -> Option<usize> {
+    std::env::var("COLUMNS").ok().and_then(|columns| columns.parse().ok()).filter(|&columns: &usize| columns > 0)
+}
+
+// 前に空白を入れて width の中で寄せる（収まらない行はそのまま）
+pub fn align(line: &str, align: Align, width: usize) -> String {
+    let room = width.saturating_sub(visible_width(line));
+    let padding = match align {
+        Align::Left => 0,
+        Align::Center => room / 2,
+        Align::Right => room,
+    };
+    format!("{}{}", " ".repeat(padding), line)
+}
+
+// 色のエスケープシーケンス（ESC [ ... m）を除いた表示幅
+fn visible_width(line: &str) -> usize {
+    let mut width = 0;
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            chars.by_ref().find(|&c| c == 'm');
+        } else {
+            width += c.width().unwrap_or(0);
+        }
+    }
+    width
+}
+
+// 枠で囲んだ行（paint は枠の中の文字に色を付ける）
+pub fn frame(message: &str, max_width: usize, paint: impl Fn(&str) -> String) -> Vec<String> {
+    // 左右の「│ 」「 │」の分を引く
+    let lines = wrap(message, max_width.saturating_sub(4).max(1));
+    let width = lines.iter().map(|line| line.width()).max().unwrap_or(0);
+    let mut framed = vec![format!("╭{}╮", "─".repeat(width + 2))];
+    for line in &lines {
+        framed.push(format!("│ {}{} │", paint(line), " ".repeat(width - line.width())));
+    }
+    framed.push(format!("╰{}╯", "─".repeat(width + 2)));
+    framed
+}
+
+// 単語の区切りで width 以内に折り返す（長すぎる単語は途中で切る）
+pub fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    for word in text.split_whitespace() {
+        if !line.is_empty() && line.width() + 1 + word.width() <= width {
+            line.push(' ');
+            line.push_str(word);
+            continue;
+        }
+        if !line.is_empty() {
+            lines.push(std::mem::take(&mut line));
+        }
+        for c in word.chars() {
+            if !line.is_empty() && line.width() + c.width().unwrap_or(0) > width {
+                lines.push(std::mem::take(&mut line));
+            }
+            line.push(c);
+        }
+    }
+    if !line.is_empty() || lines.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
@@ -0,0 +1,856 @@
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver};
+use std::time::Duration;
+
+use chrono::{Local, NaiveTime, Timelike};
+use clap::parser::ValueSource;
+use clap::{ArgMatches, Args, CommandFactory, FromArgMatches, Parser, Subcommand};
+use my_cli_errors::{CliError, Failure, EXIT_FAILURE, EXIT_INTERRUPTED, EXIT_USAGE};
+use my_cli_progress::Progress;
+
+mod animation;
+mod config;
+mod greetings;
+mod history;
+mod i18n;
+mod interactive;
+mod layout;
+mod locale;
+mod names;
+mod output;
+mod qr;
+mod sanitize;
+mod serve;
+mod style;
+mod template;
+mod transform;
+
+use config::Config;
+use layout::{Align, Emoji};
+use locale::Locale;
+use output::{Destination, Format, Record};
+use style::{ColorChoice, Style};
+use template::{Template, TemplateError};
+use transform::Case;
+
+// hello-cli の終了コード（0、1、2、130 は my-cli の全てのツールで同じ）
+const EXIT_TEMPLATE: i32 = 3;
+const EXIT_LOCALE: i32 = 4;
+const EXIT_IO: i32 = 5;
+const EXIT_INVALID_NAME: i32 = 6;
+
+// CLIコマンド構造体
+#[derive(Parser)]
+#[command(name = "hello-cli")]
+#[command(version = "0.1.0")]
+#[command(about = "A simple Hello World CLI tool")]
+#[command(author = "Otsuka Noboru <mopinfish@gmail.ocm>")]
+#[command(after_help = after_help())]
+#[command(args_conflicts_with_subcommands = true)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Commands>,
+    
+    // サブコマンドを省略したときは greet と同じ
+    #[command(flatten)]
+    greet: GreetArgs,
+    
+    /// List the supported languages and exit
+    #[arg(long)]
+    list_langs: bool,
+    
+    /// Print a shell completion script (bash, zsh, fish, powershell or elvish) and exit
+    #[arg(long, value_name = "SHELL")]
+    completions: Option<clap_complete::Shell>,
+    
+    /// Write a commented starter config file to ~/.config/hello-cli/config.toml and exit
+    #[arg(long)]
+    init_config: bool,
+    
+    // -v と --log-format（my-cli 共通）
+    #[command(flatten)]
+    log: my_cli_log::LogArgs,
+}
+
+#[derive(Subcommand)]
+enum Commands {
+    /// Say hello (the default when no subcommand is given)
+    #[command(after_help = after_help())]
+    Greet(GreetArgs),
+    
+    /// Say goodbye
+    Farewell(FarewellArgs),
+    
+    /// Print your own message for each name
+    #[command(after_help = after_help())]
+    Custom(CustomArgs),
+    
+    /// Ask for a name, language and style, then greet
+    Interactive,
+    
+    /// Show recent greetings saved with --log
+    History {
+        /// Show only the most recent N entries
+        #[arg(long, value_name = "N", default_value_t = 20)]
+        last: usize,
+        
+        /// Only greetings to this name
+        #[arg(long, value_name = "NAME")]
+        name: Option<String>,
+        
+        /// Only greetings from this subcommand (greet, farewell or custom)
+        #[arg(long, value_name = "COMMAND")]
+        command: Option<String>,
+        
+        /// Only greetings whose message contains this text
+        #[arg(long, value_name = "TEXT")]
+        contains: Option<String>,
+    },
+    
+    /// Serve greetings over HTTP, e.g. GET /greet?name=Alice&lang=ja
+    Serve {
+        /// Address to listen on
+        #[arg(long, value_name = "HOST", default_value = "127.0.0.1")]
+        host: String,
+        
+        /// Port to listen on
+        #[arg(long, value_name = "PORT", default_value_t = 3000)]
+        port: u16,
+        
+        /// Language used when the request has no lang parameter
+        #[arg(short, long, value_name = "LANG", env = "HELLO_LANG")]
+        lang: Option<String>,
+    },
+    
+    /// Print the version, git commit, build date, target and features (attach this to bug reports)
+    Version {
+        /// Print JSON
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+// 全てのサブコマンドに共通のオプション
+#[derive(Args)]
+struct CommonArgs {
+    /// Name to greet (repeat to greet several people)
+    #[arg(short, long, value_name = "NAME", env = "HELLO_NAME")]
+    name: Vec<String>,
+    
+    /// Greet everyone in one sentence, e.g. "Hello, Alice, Bob and Carol!"
+    #[arg(long)]
+    combine: bool,
+    
+    /// Number of times to greet (0 repeats until Ctrl-C)
+    #[arg(short, long, value_name = "NUMBER", default_value_t = 1)]
+    count: u32,
+    
+    /// Wait between greetings, e.g. 500ms, 2s or 1m
+    #[arg(short, long, value_name = "DURATION", value_parser = parse_interval)]
+    interval: Option<Duration>,
+    
+    /// Change the case of the greeting
+    #[arg(long, value_enum, default_value_t = Case::None)]
+    case: Case,
+    
+    /// Display greeting in uppercase (same as --case upper)
+    #[arg(short, long, conflicts_with = "case")]
+    uppercase: bool,
+    
+    /// Language of the greeting and of messages, e.g. ja, en, es (default: from LC_ALL, LC_MESSAGES or LANG)
+    #[arg(short, long, value_name = "LANG", env = "HELLO_LANG")]
+    lang: Option<String>,
+    
+    /// Use this time of day instead of the current time
+    #[arg(long, value_name = "HH:MM", value_parser = parse_time)]
+    at: Option<NaiveTime>,
+    
+    /// Format of {date} in templates, e.g. "%Y-%m-%d" (default: the language's own format)
+    #[arg(long, value_name = "FORMAT", value_parser = parse_date_format)]
+    date_format: Option<String>,
+    
+    /// Type each greeting out one character at a time, e.g. 50 or 50ms per character (text format only)
+    #[arg(long, value_name = "DELAY", value_parser = parse_interval)]
+    typewriter: Option<Duration>,
+    
+    /// Count down from N, one second per number, before the first greeting (text format only)
+    #[arg(long, value_name = "N")]
+    countdown: Option<u32>,
+    
+    /// Add an emoji to the greeting
+    #[arg(long, value_enum, value_name = "EMOJI", num_args = 0..=1, default_missing_value = "wave")]
+    emoji: Option<Emoji>,
+    
+    /// Draw a box around the greeting, wrapped to the terminal width (text format only)
+    #[arg(long)]
+    frame: bool,
+    
+    /// Show each greeting as a QR code above the text (text format only)
+    #[arg(long)]
+    qr: bool,
+    
+    /// Save the greetings (one per line) as a QR code in a PNG image
+    #[arg(long, value_name = "PATH")]
+    qr_file: Option<PathBuf>,
+    
+    /// Align the greeting within the terminal width (text format only; needs a terminal or COLUMNS)
+    #[arg(long, value_enum, default_value_t = Align::Left)]
+    align: Align,
+    
+    /// Reject names containing control characters or ANSI escape sequences (for untrusted input)
+    #[arg(long)]
+    strict: bool,
+    
+    /// Shorten names longer than this many characters, ending them with "…"
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+    max_length: Option<u32>,
+    
+    /// Greet each name in the file (one per line)
+    #[arg(long, value_name = "PATH")]
+    names_file: Option<PathBuf>,
+    
+    /// Greet each name read from standard input (one per line)
+    #[arg(long)]
+    stdin: bool,
+    
+    /// Output format (json prints one object per line, csv starts with a header row)
+    #[arg(short, long, value_enum, default_value_t = Format::Text)]
+    format: Format,
+    
+    /// Write the greetings to a file instead of standard output
+    #[arg(short, long, value_name = "PATH")]
+    output: Option<PathBuf>,
+    
+    /// Append to the --output file instead of replacing it
+    #[arg(long, requires = "output")]
+    append: bool,
+    
+    /// When to color the output (auto respects NO_COLOR and only colors a terminal)
+    #[arg(long, value_enum, value_name = "WHEN", default_value_t = ColorChoice::Auto)]
+    color: ColorChoice,
+    
+    /// Style of the greeting when colors are enabled (text format only)
+    #[arg(short, long, value_enum, env = "HELLO_STYLE")]
+    style: Option<Style>,
+    
+    /// Append each greeting with a timestamp to ~/.hello_history (see the history subcommand)
+    #[arg(long)]
+    log: bool,
+    
+    /// Print only the message, without numbering, emoji, frame or colors
+    #[arg(short, long)]
+    quiet: bool,
+}
+
+#[derive(Args)]
+struct GreetArgs {
+    #[command(flatten)]
+    common: CommonArgs,
+    
+    /// Custom greeting with placeholders such as {name}, {index} and {time_of_day} (listed below)
+    #[arg(short, long, value_name = "TEMPLATE")]
+    template: Option<String>,
+    
+    /// Word to greet with instead of the language's, e.g. "Howdy"
+    #[arg(long, value_name = "WORD", conflicts_with_all = ["smart_greeting", "random", "all"])]
+    greeting_word: Option<String>,
+    
+    /// Punctuation to end the greeting with, e.g. "!!!"
+    #[arg(long, value_name = "TEXT", conflicts_with_all = ["smart_greeting", "random", "all"])]
+    punctuation: Option<String>,
+    
+    /// Say good morning, afternoon or evening depending on the time
+    #[arg(long, conflicts_with = "template")]
+    smart_greeting: bool,
+    
+    /// Greet in a randomly chosen language
+    #[arg(long, conflicts_with_all = ["template", "smart_greeting"])]
+    random: bool,
+    
+    /// Seed for --random, to pick the same greetings every time
+    #[arg(long, value_name = "SEED", requires = "random")]
+    seed: Option<u64>,
+    
+    /// Greet once in every built-in language
+    #[arg(long, conflicts_with_all = ["template", "smart_greeting", "random"])]
+    all: bool,
+}
+
+#[derive(Args)]
+struct FarewellArgs {
+    #[command(flatten)]
+    common: CommonArgs,
+}
+
+#[derive(Args)]
+struct CustomArgs {
+    /// Message with placeholders such as {name}, {index} and {time_of_day} (listed below)
+    #[arg(short, long, value_name = "MESSAGE")]
+    message: String,
+    
+    #[command(flatten)]
+    common: CommonArgs,
+}
+
+// --greeting-word と --punctuation（なければ言語ごとの言葉）
+#[derive(Default)]
+struct Words {
+    word: Option<String>,
+    punctuation: Option<String>,
+}
+
+// 挨拶の種類
+enum Greeting {
+    Hello,
+    // --smart-greeting
+    TimeOfDay,
+    Farewell,
+    Template(Template),
+    Random(greetings::Picker),
+    // 組み込みの挨拶を1つずつ
+    All,
+}
+
+impl Greeting {
+    // 番号を末尾に付けるか（テンプレートでは {index} で番号を入れる）
+    fn numbered(&self) -> bool {
+        !matches!(self, Greeting::Template(_) | Greeting::All)
+    }
+
+    fn message(&mut self, locale: &Locale, words: &Words, context: &template::Context) -> String {
+        match self {
+            Greeting::Hello => locale.greet_with(context.name, words.word.as_deref(), words.punctuation.as_deref()),
+            Greeting::TimeOfDay => locale.greet_at(context.name, context.now.hour()),
+            Greeting::Farewell => locale.farewell(context.name),
+            Greeting::Template(template) => template.render(context),
+            Greeting::Random(picker) => greetings::greet(picker.pick(), context.name),
+            Greeting::All => greetings::greet(greetings::POOL[context.index as usize - 1].1, context.name),
+        }
+    }
+}
+
+fn after_help() -> String {
+    let placeholders: Vec<String> = template::PLACEHOLDERS
+        .iter()
+        .map(|(name, description)| format!("  {:<15}{}", format!("{{{}}}", name), description))
+        .collect();
+    format!("Template placeholders:\n{}\n\n\
+Environment:\n  \
+HELLO_NAME   default for --name\n  \
+HELLO_LANG   default for --lang\n  \
+HELLO_STYLE  default for --style\n  \
+RUST_LOG     log filter for stderr, e.g. hello_cli=debug (overrides -v)\n\
+Flags win over HELLO_*, and HELLO_* wins over ~/.config/hello-cli/config.toml.\n\n\
+Exit status:\n  \
+0    success\n  \
+1    other errors (no names to greet, HOME not set, existing config file)\n  \
+2    invalid command-line usage or config file\n  \
+3    invalid template\n  \
+4    unsupported language\n  \
+5    failed to read or write a file (names, --output, --log, --qr-file, history)\n  \
+6    name rejected by --strict\n  \
+130  interrupted with Ctrl-C", placeholders.join("\n"))
+}
+
+// エラーを表示して終了する
+fn fail(code: i32, message: impl std::fmt::Display) -> ! {
+    my_cli_errors::exit(&Failure::new(code, message))
+}
+
+fn unsupported_lang(code: &str) -> String {
+    i18n::tr("unsupported-lang", &[("lang", code.to_string().into())])
+}
+
+impl CliError for TemplateError {
+    fn exit_code(&self) -> i32 {
+        EXIT_TEMPLATE
+    }
+}
+
+// hello-cli のコマンドの定義（my-cli completions と my-cli docs で使う）
+pub fn command() -> clap::Command {
+    Cli::command()
+}
+
+// my-cli doctor で調べる設定ファイル（弱い順）
+pub fn config_files() -> Vec<my_cli_config::File> {
+    config::SECTION.files(Config::default_path())
+}
+
+// 設定ファイルを全て読んで、キーと値を確かめる
+pub fn check_config() -> Result<(), config::ConfigError> {
+    Config::load(&Cli::command()).map(|_| ())
+}
+
+// 読み書きするファイル（説明と場所）
+pub fn state_files() -> Vec<(&'static str, PathBuf)> {
+    history::default_path().map(|path| ("greeting history (--log)", path)).into_iter().collect()
+}
+
+// version サブコマンドの内容（my-cli version にもまとめて出す）
+pub fn build_info() -> my_cli_build_info::BuildInfo {
+    my_cli_build_info::build_info!()
+}
+
+// hello-cli の入口（args の最初は使い方に表示するコマンド名。my-cli hello からも呼び出す）
+pub fn main_from<I, T>(args: I)
+where
+    I: IntoIterator<Item = T>,
+    T: Into<std::ffi::OsString> + Clone,
+{
+    // ヘルプとメッセージの言語（ヘルプは引数を読む途中で表示するので、先に --lang を探す）
+    let args: Vec<std::ffi::OsString> = args.into_iter().map(Into::into).collect();
+    let messages = i18n::init(my_cli_i18n::lang_arg(&args, &["--lang", "-l"]).as_deref());
+    // 設定ファイルの値をオプションの既定値にする
+    let config_path = Config::default_path();
+    let config = Config::load(&Cli::command())
+        .unwrap_or_else(|e| fail(EXIT_USAGE, i18n::tr("invalid-config", &[("error", e.to_string().into())])));
+    let matches = config.apply(my_cli_i18n::localize(Cli::command(), messages)).get_matches_from(args);
+    let cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    my_cli_log::init(&cli.log);
+    config.trace();
+
+    if let Some(shell) = cli.completions {
+        clap_complete::generate(shell, &mut Cli::command(), "hello-cli", &mut std::io::stdout());
+        return;
+    }
+
+    if cli.init_config {
+        let Some(path) = config_path else {
+            fail(EXIT_FAILURE, i18n::tr("no-home-config", &[]));
+        };
+        match config::init(&path) {
+            Ok(()) => println!("{}", i18n::tr("wrote", &[("path", path.display().to_string().into())])),
+            Err(e) => fail(EXIT_FAILURE, e),
+        }
+        return;
+    }
+
+    if cli.list_langs {
+        for locale in locale::LOCALES {
+            println!("{:<4}{}: {}", locale.code, locale.name, locale.greet(locale.world));
+        }
+        return;
+    }
+
+    // 履歴に残すコマンド名（サブコマンドを省略したときは greet）
+    let command = matches.subcommand_name().unwrap_or("greet").to_string();
+    // 設定ファイルの値と区別するため、サブコマンドの引数も見る
+    let (common, greeting, words, matches) = match cli.command {
+        None => {
+            let greeting = choose_greeting(&cli.greet, &matches);
+            let words = Words { word: cli.greet.greeting_word, punctuation: cli.greet.punctuation };
+            (cli.greet.common, greeting, words, &matches)
+        }
+        Some(Commands::Greet(args)) => {
+            let matches = matches.subcommand_matches("greet").unwrap();
+            let greeting = choose_greeting(&args, matches);
+            (args.common, greeting, Words { word: args.greeting_word, punctuation: args.punctuation }, matches)
+        }
+        Some(Commands::Farewell(args)) => {
+            (args.common, Greeting::Farewell, Words::default(), matches.subcommand_matches("farewell").unwrap())
+        }
+        Some(Commands::Custom(args)) => {
+            let greeting = Greeting::Template(parse_template(&args.message));
+            (args.common, greeting, Words::default(), matches.subcommand_matches("custom").unwrap())
+        }
+        Some(Commands::Interactive) => {
+            run_interactive();
+            return;
+        }
+        Some(Commands::History { last, name, command, contains }) => {
+            run_history(last, &history::Filter { name, command, contains });
+            return;
+        }
+        Some(Commands::Serve { host, port, lang }) => {
+            run_serve(&host, port, lang.as_deref());
+            return;
+        }
+        Some(Commands::Version { json }) => {
+            let info = build_info();
+            print!("{}", if json { info.json() + "\n" } else { info.text() });
+            return;
+        }
+    };
+    run(common, greeting, &words, matches, &command);
+}
+
+// 設定ファイルのテンプレートは、フラグで別の挨拶を選んだときには使わない
+fn choose_greeting(args: &GreetArgs, matches: &ArgMatches) -> Greeting {
+    let other_greeting = args.smart_greeting || args.random || args.all;
+    match &args.template {
+        Some(text) if from_command_line(matches, "template") || !other_greeting => Greeting::Template(parse_template(text)),
+        _ if args.all => Greeting::All,
+        _ if args.random => Greeting::Random(match args.seed {
+            Some(seed) => greetings::Picker::seeded(seed),
+            None => greetings::Picker::from_entropy(),
+        }),
+        _ if args.smart_greeting => Greeting::TimeOfDay,
+        _ => Greeting::Hello,
+    }
+}
+
+// テンプレートは最初にまとめて検査する
+fn parse_template(text: &str) -> Template {
+    Template::parse(text).unwrap_or_else(|e| my_cli_errors::exit(&e))
+}
+
+fn from_command_line(matches: &ArgMatches, id: &str) -> bool {
+    matches.value_source(id) == Some(ValueSource::CommandLine)
+}
+
+fn run_interactive() {
+    let answers = interactive::ask(locale::detect().unwrap_or(locale::default_locale())).unwrap_or_else(|e| fail(EXIT_FAILURE, e));
+    let color = ColorChoice::Auto.enabled(std::io::IsTerminal::is_terminal(&std::io::stdout()));
+    println!("{}", style::apply(&answers.locale.greet(&answers.name), answers.style, color));
+}
+
+fn run_history(last: usize, filter: &history::Filter) {
+    let Some(path) = history::default_path() else {
+        fail(EXIT_FAILURE, i18n::tr("no-home-history", &[]));
+    };
+    let entries = history::read(&path).unwrap_or_else(|e| fail(EXIT_IO, e));
+    for entry in history::recent(&entries, filter, last) {
+        println!("{}  {:<8}  {}", entry.time.format("%Y-%m-%d %H:%M:%S"), entry.command, entry.message);
+    }
+}
+
+fn run_serve(host: &str, port: u16, lang: Option<&str>) {
+    let locale = match lang {
+        Some(code) => locale::find(code).unwrap_or_else(|| fail(EXIT_LOCALE, unsupported_lang(code))),
+        None => locale::detect().unwrap_or(locale::default_locale()),
+    };
+    serve::run(host, port, locale).unwrap_or_else(|e| fail(EXIT_IO, e));
+}
+
+fn run(common: CommonArgs, mut greeting: Greeting, words: &Words, matches: &ArgMatches, command: &str) {
+    // 言語の選択（指定がなければ環境変数から、それもなければ英語）
+    let locale = match &common.lang {
+        Some(code) => match locale::find(code) {
+            Some(locale) => locale,
+            None => fail(EXIT_LOCALE, unsupported_lang(code)),
+        },
+        None => locale::detect().unwrap_or(locale::default_locale()),
+    };
+    tracing::debug!(lang = locale.code, "chose the language");
+
+    // 引数の取得（--name、--names-file、--stdin の順に名前を並べる）
+    // 設定ファイルの name は他に名前がないときだけ使う
+    let mut names: Vec<String> = if from_command_line(matches, "name") { common.name.clone() } else { Vec::new() };
+    if let Some(path) = &common.names_file {
+        match names::read_file(path) {
+            Ok(file_names) => names.extend(file_names),
+            Err(e) => fail(EXIT_IO, i18n::tr("read-names-failed", &[("path", path.display().to_string().into()), ("error", e.to_string().into())])),
+        }
+    }
+    if common.stdin {
+        match names::read_stdin() {
+            Ok(stdin_names) => names.extend(stdin_names),
+            Err(e) => fail(EXIT_IO, i18n::tr("read-stdin-failed", &[("error", e.to_string().into())])),
+        }
+    }
+    if names.is_empty() {
+        if common.names_file.is_some() || common.stdin {
+            fail(EXIT_FAILURE, i18n::tr("no-names", &[]));
+        }
+        let name = common.name
+            .first()
+            .cloned()
+            .unwrap_or_else(|| locale.world.to_string());  // デフォルトは言語ごとの「World」
+        names.push(name);
+    }
+    // --strict は全ての名前を確かめてから挨拶を始める
+    if common.strict {
+        for name in &names {
+            if let Err(rejection) = sanitize::check(name) {
+                let args = [("name", format!("{:?}", name).into()), ("reason", rejection.to_string().into())];
+                fail(EXIT_INVALID_NAME, i18n::tr("rejected-name", &args));
+            }
+        }
+    }
+    if let Some(max_length) = common.max_length {
+        names = names.iter().map(|name| sanitize::truncate(name, max_length as usize)).collect();
+    }
+    if common.combine {
+        names = vec![locale.join(&names)];
+    }
+    // -u は以前からある --case upper の別名
+    let case = if common.uppercase { Case::Upper } else { common.case };
+    // --quiet では飾りを全て省く
+    let frame = (common.frame && !common.quiet).then(layout::terminal_width);
+    let emoji = common.emoji.filter(|_| !common.quiet);
+    let format = common.format;
+    let mut destination = match &common.output {
+        Some(path) => Destination::file(path, common.append).unwrap_or_else(|e| fail(EXIT_IO, e)),
+        None => Destination::stdout(),
+    };
+    let color = format == Format::Text && !common.quiet && common.color.enabled(destination.terminal);
+    let align_width = if format == Format::Text && common.align != Align::Left { layout::align_width(destination.terminal) } else { None };
+    let align = |line: &str| match align_width {
+        Some(width) => layout::align(line, common.align, width),
+        None => line.to_string(),
+    };
+    let mut log = common.log.then(|| {
+        let path = history::default_path().unwrap_or_else(|| fail(EXIT_FAILURE, i18n::tr("no-home-history", &[])));
+        history::Log::open(&path).unwrap_or_else(|e| fail(EXIT_IO, e))
+    });
+    // --at があれば今日のその時刻として扱う
+    let now = match common.at {
+        Some(time) => Local::now().date_naive().and_time(time).and_local_timezone(Local).earliest().unwrap_or_else(Local::now),
+        None => Local::now(),
+    };
+
+    // --all では挨拶ごとに1回ずつ表示する
+    let count = if matches!(greeting, Greeting::All) { greetings::POOL.len() as u32 } else { common.count };
+    // --qr-file は全ての挨拶が終わってから書く
+    if common.qr_file.is_some() && count == 0 {
+        fail(EXIT_USAGE, i18n::tr("qr-file-endless", &[]));
+    }
+    let qr = common.qr && format == Format::Text && !common.quiet;
+    let mut qr_messages = Vec::new();
+
+    if let Some(header) = output::header(format).filter(|_| destination.needs_header) {
+        write_line(&mut destination, header);
+    }
+
+    // 名前ごとに、指定された回数だけメッセージを表示
+    // --count 0 では全ての名前への挨拶を Ctrl-C まで繰り返す
+    let schedule: Box<dyn Iterator<Item = (&String, u32)>> = if count == 0 {
+        Box::new((1..).flat_map(|i| names.iter().map(move |name| (name, i))))
+    } else {
+        Box::new(names.iter().flat_map(|name| (1..=count).map(move |i| (name, i))))
+    };
+    // タイプライターと秒読みは文字の表示だけに使う
+    let typewriter = common.typewriter.filter(|_| format == Format::Text);
+    let countdown = common.countdown.filter(|_| format == Format::Text && !common.quiet).unwrap_or(0);
+    let interrupted = (common.interval.is_some() || count == 0 || typewriter.is_some() || countdown > 0).then(on_interrupt);
+    for line in animation::countdown(countdown) {
+        write_line(&mut destination, &align(&line));
+        pause(interrupted.as_ref(), Duration::from_secs(1));
+    }
+    // --interval で挨拶を流すときは、標準エラー出力に進捗を出す（--quiet と JSON / CSV では出さない）
+    let shown = common.interval.is_some() && my_cli_progress::shown(common.quiet || format != Format::Text);
+    let progress = match count {
+        0 => Progress::spinner(i18n::tr("greeting-until-interrupted", &[]), shown),
+        count => Progress::bar(names.len() as u64 * u64::from(count), "", shown),
+    };
+    for (n, (name, i)) in schedule.enumerate() {
+        // 2つ目からは間隔を空ける（待っている間に Ctrl-C が押されたら終わる）
+        let stop = match (&interrupted, common.interval) {
+            (Some(interrupted), Some(interval)) if n > 0 => interrupted.recv_timeout(interval).is_ok(),
+            (Some(interrupted), _) => interrupted.try_recv().is_ok(),
+            (None, _) => false,
+        };
+        if stop {
+            progress.finish();
+            std::process::exit(EXIT_INTERRUPTED);
+        }
+        // グリーティングメッセージの作成
+        let context = template::Context {
+            name,
+            index: i,
+            count,
+            now,
+            greeting: words.word.as_deref().unwrap_or(locale.word),
+            punctuation: words.punctuation.as_deref().unwrap_or(locale.punctuation),
+            date_format: common.date_format.as_deref().unwrap_or(locale.date_format),
+            time_format: locale.time_format,
+        };
+        let mut message = greeting.message(locale, words, &context);
+        if greeting.numbered() && count != 1 && format == Format::Text && !common.quiet {
+            message = format!("{} ({})", message, i);
+        }
+        message = transform::apply(&message, case);
+        if let Some(emoji) = emoji {
+            message = layout::decorate(&message, emoji);
+        }
+        if let Some(log) = &mut log {
+            let entry = history::Entry { time: Local::now(), command: command.to_string(), name: name.clone(), message: message.clone() };
+            if let Err(e) = log.append(&entry) {
+                fail(EXIT_IO, e);
+            }
+        }
+        if common.qr_file.is_some() {
+            qr_messages.push(message.clone());
+        }
+        // 挨拶を書く間は進捗を消す
+        progress.suspend(|| {
+            if qr {
+                for line in qr::render(&message).unwrap_or_else(|e| fail(EXIT_FAILURE, e)) {
+                    write_line(&mut destination, &align(&line));
+                }
+            }
+            match frame {
+                Some(width) if format == Format::Text => {
+                    for line in layout::frame(&message, width, |text| style::apply(text, common.style, color)) {
+                        type_line(&mut destination, &align(&line), typewriter, interrupted.as_ref());
+                    }
+                }
+                _ => {
+                    let line = output::format_record(format, &Record { name, message: &message, index: i });
+                    type_line(&mut destination, &align(&style::apply(&line, common.style, color)), typewriter, interrupted.as_ref());
+                }
+            }
+        });
+        progress.inc(1);
+    }
+    progress.finish();
+    if let Some(path) = &common.qr_file {
+        qr::save(&qr_messages.join("\n"), path).unwrap_or_else(|e| fail(EXIT_IO, e));
+    }
+}
+
+// 書けなければ終了する（パイプの先が閉じられたときは何も言わない）
+fn write_line(destination: &mut Destination, line: &str) {
+    if let Err(e) = destination.write_line(line) {
+        write_error(destination, e);
+    }
+}
+
+fn write_error(destination: &Destination, error: std::io::Error) -> ! {
+    if my_cli_errors::is_broken_pipe(&error) {
+        std::process::exit(my_cli_errors::EXIT_SUCCESS);
+    }
+    fail(EXIT_IO, output::describe(&error, &destination.name))
+}
+
+// delay があれば1文字ずつ間を空けて書く
+fn type_line(destination: &mut Destination, line: &str, delay: Option<Duration>, interrupted: Option<&Receiver<()>>) {
+    let Some(delay) = delay else {
+        return write_line(destination, line);
+    };
+    for keystroke in animation::keystrokes(line) {
+        if let Err(e) = destination.write_now(keystroke) {
+            write_error(destination, e);
+        }
+        pause(interrupted, delay);
+    }
+    write_line(destination, "");
+}
+
+// 待っている間に Ctrl-C が押されたら終わる
+fn pause(interrupted: Option<&Receiver<()>>, duration: Duration) {
+    match interrupted {
+        Some(interrupted) if interrupted.recv_timeout(duration).is_ok() => std::process::exit(EXIT_INTERRUPTED),
+        Some(_) => {}
+        None => std::thread::sleep(duration),
+    }
+}
+
+// Ctrl-C が押されたら受け取れるチャンネル
+fn on_interrupt() -> Receiver<()> {
+    let (sender, receiver) = mpsc::channel();
+    if let Err(e) = ctrlc::set_handler(move || {
+        let _ = sender.send(());
+    }) {
+        tracing::warn!("cannot handle Ctrl-C: {}", e);
+    }
+    receiver
+}
+
+// "500ms"、"2s"、"1.5s"、"1m"（単位がなければミリ秒）を間隔にする
+fn parse_interval(s: &str) -> Result<Duration, String> {
+    let s = s.trim();
+    let split = s.find(|c: char| !(c.is_ascii_digit() || c == '.')).unwrap_or(s.len());
+    let (number, unit) = s.split_at(split);
+    let seconds_per_unit = match unit.trim() {
+        "ms" | "" => 0.001,
+        "s" => 1.0,
+        "m" => 60.0,
+        _ => return Err(format!("unknown unit in '{}' (use ms, s or m)", s)),
+    };
+    match number.parse::<f64>() {
+        Ok(number) => Duration::try_from_secs_f64(number * seconds_per_unit).map_err(|e| e.to_string()),
+        Err(_) => Err(format!("'{}' is not an interval like 500ms or 2s", s)),
+    }
+}
+
+fn parse_date_format(s: &str) -> Result<String, String> {
+    template::check_format(s).map(|()| s.to_string())
+}
+
+// "7:30" や "19:05" を時刻にする
+fn parse_time(s: &str) -> Result<NaiveTime, String> {
+    NaiveTime::parse_from_str(s, "%H:%M").map_err(|_| format!("'{}' is not a time in HH:MM format", s))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 設定ファイルを読み込んだときと同じように引数を解析する
+    fn parse(args: &[&str], config: &str) -> Result<Cli, clap::Error> {
+        let settings = parse_config(config).unwrap();
+        let command = my_cli_config::apply(Cli::command(), &settings);
+        let matches = command.try_get_matches_from(std::iter::once("hello-cli").chain(args.iter().copied()))?;
+        Cli::from_arg_matches(&matches)
+    }
+
+    fn parse_config(config: &str) -> Result<Vec<my_cli_config::Setting>, String> {
+        let file = my_cli_config::File { layer: my_cli_config::Layer::Tool, path: "config.toml".into() };
+        config::SECTION.parse(config, &file, &Cli::command())
+    }
+
+    fn set_env(name: &str, value: Option<&str>) {
+        // HELLO_* を読み書きするのは test_environment_precedence だけなので、並列に実行しても競合しない
+        unsafe {
+            match value {
+                Some(value) => std::env::set_var(name, value),
+                None => std::env::remove_var(name),
+            }
+        }
+    }
+
+    #[test]
+    fn test_cli_definition() {
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn test_environment_precedence() {
+        let config = "name = \"Config\"\nlang = \"ja\"\nstyle = \"bold\"\n";
+        let common = |args: &[&str], config: &str| parse(args, config).unwrap().greet.common;
+        let set_all = |name, lang, style| {
+            set_env("HELLO_NAME", name);
+            set_env("HELLO_LANG", lang);
+            set_env("HELLO_STYLE", style);
+        };
+
+        // 組み込みの既定値
+        set_all(None, None, None);
+        let args = common(&[], "");
+        assert_eq!((args.name, args.lang, args.style), (vec![], None, None));
+
+        // 設定ファイル > 組み込みの既定値
+        let args = common(&[], config);
+        assert_eq!((args.name, args.lang, args.style), (vec!["Config".to_string()], Some("ja".to_string()), Some(Style::Bold)));
+
+        // 環境変数 > 設定ファイル
+        set_all(Some("Env"), Some("es"), Some("rainbow"));
+        let args = common(&[], config);
+        assert_eq!((args.name, args.lang, args.style), (vec!["Env".to_string()], Some("es".to_string()), Some(Style::Rainbow)));
+        assert_eq!(common(&[], "").name, vec!["Env".to_string()]);
+
+        // フラグ > 環境変数
+        let args = common(&["--name", "Flag", "--lang", "en", "--style", "bold"], config);
+        assert_eq!((args.name, args.lang, args.style), (vec!["Flag".to_string()], Some("en".to_string()), Some(Style::Bold)));
+
+        // サブコマンドでも同じ
+        let Some(Commands::Farewell(args)) = parse(&["farewell"], config).unwrap().command else {
+            panic!("expected the farewell subcommand");
+        };
+        assert_eq!((args.common.name, args.common.style), (vec!["Env".to_string()], Some(Style::Rainbow)));
+        let Some(Commands::Serve { lang, .. }) = parse(&["serve"], config).unwrap().command else {
+            panic!("expected the serve subcommand");
+        };
+        assert_eq!(lang, Some("es".to_string()));
+
+        // 環境変数の値も検証するが、設定ファイルの誤りとしては扱わない
+        set_all(None, None, Some("sparkly"));
+        assert!(parse_config(config).is_ok());
+        let error = parse(&[], config).err().unwrap();
+        assert_eq!(error.kind(), clap::error::ErrorKind::InvalidValue);
+
+        set_all(None, None, None);
+    }
+}
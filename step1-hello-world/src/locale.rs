@@ -0,0 +1,143 @@
+// 言語ごとの挨拶の表
+//
+// 挨拶は {name} を名前に置き換えるテンプレート
+// --lang を省略したときは LC_ALL、LC_MESSAGES、LANG の順に見て言語を選ぶ
+
+pub struct Locale {
+    pub code: &'static str,
+    pub name: &'static str,
+    pub greeting: &'static str,
+    // greeting の最初の挨拶の言葉と最後の句読点（--greeting-word と --punctuation で置き換える）
+    pub word: &'static str,
+    pub punctuation: &'static str,
+    // 名前を指定しないときに挨拶する相手
+    pub world: &'static str,
+    // --smart-greeting の朝・昼・夜の挨拶
+    pub times: [&'static str; 3],
+    // farewell サブコマンドの別れの挨拶
+    pub farewell: &'static str,
+    // --combine で名前をつなぐ区切り（最後の2つの間は and）
+    pub comma: &'static str,
+    pub and: &'static str,
+    // テンプレートの {date} と {time} の書式（chrono の strftime 形式）
+    pub date_format: &'static str,
+    pub time_format: &'static str,
+}
+
+pub const LOCALES: &[Locale] = &[
+    Locale { code: "en", name: "English", greeting: "Hello, {name}!", word: "Hello", punctuation: "!",
+        world: "World",
+        times: ["Good morning, {name}!", "Good afternoon, {name}!", "Good evening, {name}!"],
+        farewell: "Goodbye, {name}!",
+        comma: ", ", and: " and ",
+        date_format: "%B %-d, %Y", time_format: "%-I:%M %p" },
+    Locale { code: "ja", name: "日本語", greeting: "こんにちは、{name}！", word: "こんにちは", punctuation: "！",
+        world: "世界",
+        times: ["おはようございます、{name}！", "こんにちは、{name}！", "こんばんは、{name}！"],
+        farewell: "さようなら、{name}！",
+        comma: "、", and: "と",
+        date_format: "%Y年%-m月%-d日", time_format: "%-H時%M分" },
+    Locale { code: "es", name: "Español", greeting: "¡Hola, {name}!", word: "¡Hola", punctuation: "!",
+        world: "Mundo",
+        times: ["¡Buenos días, {name}!", "¡Buenas tardes, {name}!", "¡Buenas noches, {name}!"],
+        farewell: "¡Adiós, {name}!",
+        comma: ", ", and: " y ",
+        date_format: "%-d/%-m/%Y", time_format: "%H:%M" },
+    Locale { code: "fr", name: "Français", greeting: "Bonjour, {name} !", word: "Bonjour", punctuation: " !",
+        world: "le monde",
+        times: ["Bonjour, {name} !", "Bon après-midi, {name} !", "Bonsoir, {name} !"],
+        farewell: "Au revoir, {name} !",
+        comma: ", ", and: " et ",
+        date_format: "%d/%m/%Y", time_format: "%H:%M" },
+    Locale { code: "de", name: "Deutsch", greeting: "Hallo, {name}!", word: "Hallo", punctuation: "!",
+        world: "Welt",
+        times: ["Guten Morgen, {name}!", "Guten Tag, {name}!", "Guten Abend, {name}!"],
+        farewell: "Auf Wiedersehen, {name}!",
+        comma: ", ", and: " und ",
+        date_format: "%d.%m.%Y", time_format: "%H:%M" },
+    Locale { code: "it", name: "Italiano", greeting: "Ciao, {name}!", word: "Ciao", punctuation: "!",
+        world: "Mondo",
+        times: ["Buongiorno, {name}!", "Buon pomeriggio, {name}!", "Buonasera, {name}!"],
+        farewell: "Arrivederci, {name}!",
+        comma: ", ", and: " e ",
+        date_format: "%d/%m/%Y", time_format: "%H:%M" },
+    Locale { code: "pt", name: "Português", greeting: "Olá, {name}!", word: "Olá", punctuation: "!",
+        world: "Mundo",
+        times: ["Bom dia, {name}!", "Boa tarde, {name}!", "Boa noite, {name}!"],
+        farewell: "Adeus, {name}!",
+        comma: ", ", and: " e ",
+        date_format: "%d/%m/%Y", time_format: "%H:%M" },
+    Locale { code: "zh", name: "中文", greeting: "你好，{name}！", word: "你好", punctuation: "！",
+        world: "世界",
+        times: ["早上好，{name}！", "下午好，{name}！", "晚上好，{name}！"],
+        farewell: "再见，{name}！",
+        comma: "、", and: "和",
+        date_format: "%Y年%-m月%-d日", time_format: "%H:%M" },
+    Locale { code: "ko", name: "한국어", greeting: "안녕하세요, {name}!", word: "안녕하세요", punctuation: "!",
+        world: "세계",
+        times: ["좋은 아침입니다, {name}!", "안녕하세요, {name}!", "좋은 저녁입니다, {name}!"],
+        farewell: "안녕히 가세요, {name}!",
+        comma: ", ", and: " 그리고 ",
+        date_format: "%Y년 %-m월 %-d일", time_format: "%H:%M" },
+    Locale { code: "ru", name: "Русский", greeting: "Привет, {name}!", word: "Привет", punctuation: "!",
+        world: "мир",
+        times: ["Доброе утро, {name}!", "Добрый день, {name}!", "Добрый вечер, {name}!"],
+        farewell: "До свидания, {name}!",
+        comma: ", ", and: " и ",
+        date_format: "%d.%m.%Y", time_format: "%H:%M" },
+];
+
+// 既定の言語（英語）
+pub fn default_locale() -> &'static Locale {
+    &LOCALES[0]
+}
+
+// "ja"、"ja-JP"、"ja_JP.UTF-8" のどれでも見つける
+pub fn find(code: &str) -> Option<&'static Locale> {
+    let language = code.split(['_', '-', '.', '@']).next().unwrap_or(code).to_lowercase();
+    LOCALES.iter().find(|locale| locale.code == language)
+}
+
+// 環境変数から言語を選ぶ（C や POSIX、未対応の言語なら None）
+pub fn detect() -> Option<&'static Locale> {
+    ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .find(|value| !value.is_empty())
+        .and_then(|value| find(&value))
+}
+
+impl Locale {
+    pub fn greet(&self, name: &str) -> String {
+        self.greeting.replace("{name}", name)
+    }
+
+    // 挨拶の言葉や句読点を置き換えた挨拶（"Howdy, Alice!!!"）
+    pub fn greet_with(&self, name: &str, word: Option<&str>, punctuation: Option<&str>) -> String {
+        let body = &self.greeting[self.word.len()..self.greeting.len() - self.punctuation.len()];
+        format!("{}{}{}", word.unwrap_or(self.word), body, punctuation.unwrap_or(self.punctuation)).replace("{name}", name)
+    }
+
+    // "Alice, Bob and Carol"
+    pub fn join(&self, names: &[String]) -> String {
+        match names {
+            [] => String::new(),
+            [name] => name.clone(),
+            [rest @ .., last] => format!("{}{}{}", rest.join(self.comma), self.and, last),
+        }
+    }
+
+    pub fn farewell(&self, name: &str) -> String {
+        self.farewell.replace("{name}", name)
+    }
+
+    // 時刻に合わせた挨拶（夜は晩の挨拶と同じ）
+    pub fn greet_at(&self, name: &str, hour: u32) -> String {
+        let greeting = match crate::template::time_of_day(hour) {
+            "morning" => self.times[0],
+            "afternoon" => self.times[1],
+            _ => self.times[2],
+        };
+        greeting.replace("{name}", name)
+    }
+}
@@ -0,0 +1,23 @@
+// 挨拶する名前の一覧（--names-file list.txt、cat names.txt | hello-cli --stdin）
+//
+// 1行に1つの名前を書く。前後の空白は取り除き、空行と # で始まる行は飛ばす
+use std::io::{self, Read};
+use std::path::Path;
+
+pub fn parse(text: &str) -> Vec<String> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_string)
+        .collect()
+}
+
+pub fn read_file(path: &Path) -> io::Result<Vec<String>> {
+    Ok(parse(&std::fs::read_to_string(path)?))
+}
+
+pub fn read_stdin() -> io::Result<Vec<String>> {
+    let mut text = String::new();
+    io::stdin().read_to_string(&mut text)?;
+    Ok(parse(&text))
+}
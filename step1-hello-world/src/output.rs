@@ -0,0 +1,104 @@
+// 出力の形式（--format text|json|csv）
+//
+// json は1行に1つのオブジェクト（{"name":"World","message":"Hello, World!","index":1}）
+// csv は見出し行 name,message,index のあとに1行ずつ出力する
+// --output があれば標準出力の代わりにファイルに書く（--append なら末尾に追記）
+use std::fs::{File, OpenOptions};
+use std::io::{self, ErrorKind, IsTerminal, LineWriter, Write};
+use std::path::Path;
+
+use clap::ValueEnum;
+use serde::Serialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    Text,
+    Json,
+    Csv,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Record<'a> {
+    pub name: &'a str,
+    pub message: &'a str,
+    pub index: u32,
+}
+
+// 最初の出力の前に書く行（csv の見出し）
+pub fn header(format: Format) -> Option<&'static str> {
+    match format {
+        Format::Csv => Some("name,message,index"),
+        Format::Text | Format::Json => None,
+    }
+}
+
+pub fn format_record(format: Format, record: &Record) -> String {
+    match format {
+        Format::Text => record.message.to_string(),
+        Format::Json => serde_json::to_string(record).expect("records always serialize"),
+        Format::Csv => format!("{},{},{}", csv_field(record.name), csv_field(record.message), record.index),
+    }
+}
+
+// カンマ・「"」・改行を含む欄は「"」で囲み、中の「"」は「""」にする
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+// 書き出し先（追記先のファイルが空でなければ csv の見出しは書かない）
+pub struct Destination {
+    // エラーメッセージ用の名前
+    pub name: String,
+    writer: Box<dyn Write>,
+    pub terminal: bool,
+    pub needs_header: bool,
+}
+
+impl Destination {
+    pub fn stdout() -> Destination {
+        Destination {
+            name: "standard output".to_string(),
+            writer: Box::new(io::stdout()),
+            terminal: io::stdout().is_terminal(),
+            needs_header: true,
+        }
+    }
+
+    // 1行ごとに書き出すので、--interval の途中でもファイルを読める
+    pub fn file(path: &Path, append: bool) -> Result<Destination, String> {
+        let file = if append {
+            OpenOptions::new().create(true).append(true).open(path)
+        } else {
+            File::create(path)
+        };
+        let name = path.display().to_string();
+        let file = file.map_err(|e| describe(&e, &name))?;
+        let needs_header = file.metadata().map_or(true, |metadata| metadata.len() == 0);
+        Ok(Destination { name, writer: Box::new(LineWriter::new(file)), terminal: false, needs_header })
+    }
+
+    pub fn write_line(&mut self, line: &str) -> io::Result<()> {
+        writeln!(self.writer, "{}", line)
+    }
+
+    // 改行を待たずにすぐ表示する（--typewriter）
+    pub fn write_now(&mut self, text: &str) -> io::Result<()> {
+        self.writer.write_all(text.as_bytes())?;
+        self.writer.flush()
+    }
+}
+
+// よくある原因は分かりやすく説明する
+pub fn describe(error: &io::Error, name: &str) -> String {
+    match error.kind() {
+        ErrorKind::PermissionDenied => format!("permission denied: cannot write to {}", name),
+        ErrorKind::NotFound => format!("cannot create {}: the directory does not exist", name),
+        ErrorKind::IsADirectory => format!("cannot write to {}: it is a directory", name),
+        ErrorKind::StorageFull => format!("cannot write to {}: no space left on the device", name),
+        _ => format!("cannot write to {}: {}", name, error),
+    }
+}
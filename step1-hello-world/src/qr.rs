@@ -0,0 +1,30 @@
+// 挨拶の QR コード（--qr で端末に表示、--qr-file で PNG に保存）
+//
+// 端末では上下2つのモジュールを1文字（▀ ▄ █）で表す
+// 暗い背景の端末でも読めるように、明るいモジュールを文字で塗る
+use std::path::Path;
+
+use image::{ImageFormat, Luma};
+use qrcode::render::unicode::Dense1x2;
+use qrcode::QrCode;
+
+// PNG の1モジュールの大きさ（ピクセル）
+const MODULE_SIZE: u32 = 8;
+
+fn encode(text: &str) -> Result<QrCode, String> {
+    QrCode::new(text.as_bytes()).map_err(|e| format!("cannot make a QR code of the greeting: {}", e))
+}
+
+// 端末に表示する行
+pub fn render(text: &str) -> Result<Vec<String>, String> {
+    let code = encode(text)?;
+    let image = code.render::<Dense1x2>().dark_color(Dense1x2::Light).light_color(Dense1x2::Dark).build();
+    Ok(image.lines().map(str::to_string).collect())
+}
+
+// 拡張子にかかわらず PNG で保存する
+pub fn save(text: &str, path: &Path) -> Result<(), String> {
+    let code = encode(text)?;
+    let image = code.render::<Luma<u8>>().module_dimensions(MODULE_SIZE, MODULE_SIZE).build();
+    image.save_with_format(path, ImageFormat::Png).map_err(|e| format!("cannot write {}: {}", path.display(), e))
+}
@@ -0,0 +1,114 @@
+// 名前の検査と切り詰め（--strict、--max-length）
+//
+// --stdin や --names-file の名前は信頼できないことがある
+// 制御文字や ANSI エスケープシーケンスがあると、端末の表示を書き換えられてしまう
+use std::fmt;
+
+// 省略したことを示す記号
+pub const ELLIPSIS: char = '…';
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Rejection {
+    // ESC [ ... で始まる端末の制御
+    EscapeSequence,
+    // タブや改行などの制御文字
+    ControlCharacter(char),
+    // 文字の向きを変える書式文字（U+202E など。表示と中身が食い違う）
+    BidiControl(char),
+}
+
+impl fmt::Display for Rejection {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Rejection::EscapeSequence => write!(f, "contains an ANSI escape sequence"),
+            Rejection::ControlCharacter(c) => write!(f, "contains the control character U+{:04X}", *c as u32),
+            Rejection::BidiControl(c) => write!(f, "contains the bidirectional control character U+{:04X}", *c as u32),
+        }
+    }
+}
+
+// --strict で受け付けない名前なら、その理由を返す
+pub fn check(name: &str) -> Result<(), Rejection> {
+    for c in name.chars() {
+        if c == '\x1b' || c == '\u{9b}' {
+            return Err(Rejection::EscapeSequence);
+        }
+        if c.is_control() {
+            return Err(Rejection::ControlCharacter(c));
+        }
+        if matches!(c, '\u{200e}' | '\u{200f}' | '\u{202a}'..='\u{202e}' | '\u{2066}'..='\u{2069}') {
+            return Err(Rejection::BidiControl(c));
+        }
+    }
+    Ok(())
+}
+
+// max_length 文字（省略記号を含む）に収まるように切り詰める
+pub fn truncate(name: &str, max_length: usize) -> String {
+    if name.chars().count() <= max_length {
+        return name.to_string();
+    }
+    let mut truncated: String = name.chars().take(max_length.saturating_sub(1)).collect();
+    truncated.truncate(truncated.trim_end().len());
+    truncated.push(ELLIPSIS);
+    truncated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_accepts_ordinary_names() {
+        assert_eq!(check("Alice"), Ok(()));
+        assert_eq!(check("Mary Jane"), Ok(()));
+        assert_eq!(check("太郎"), Ok(()));
+        assert_eq!(check("José 👋"), Ok(()));
+        assert_eq!(check(""), Ok(()));
+    }
+
+    #[test]
+    fn test_check_rejects_escape_sequences() {
+        assert_eq!(check("\x1b[31mAlice\x1b[0m"), Err(Rejection::EscapeSequence));
+        assert_eq!(check("Alice\x1b]0;title\x07"), Err(Rejection::EscapeSequence));
+        assert_eq!(check("\u{9b}2J"), Err(Rejection::EscapeSequence));
+    }
+
+    #[test]
+    fn test_check_rejects_control_characters() {
+        assert_eq!(check("Alice\nBob"), Err(Rejection::ControlCharacter('\n')));
+        assert_eq!(check("Al\tice"), Err(Rejection::ControlCharacter('\t')));
+        assert_eq!(check("Alice\r"), Err(Rejection::ControlCharacter('\r')));
+        assert_eq!(check("\u{7f}"), Err(Rejection::ControlCharacter('\u{7f}')));
+        assert_eq!(check("\0"), Err(Rejection::ControlCharacter('\0')));
+    }
+
+    #[test]
+    fn test_check_rejects_bidi_controls() {
+        assert_eq!(check("Alice\u{202e}gnp.exe"), Err(Rejection::BidiControl('\u{202e}')));
+        assert_eq!(check("\u{2066}Bob\u{2069}"), Err(Rejection::BidiControl('\u{2066}')));
+    }
+
+    #[test]
+    fn test_rejection_messages() {
+        assert_eq!(Rejection::EscapeSequence.to_string(), "contains an ANSI escape sequence");
+        assert_eq!(Rejection::ControlCharacter('\n').to_string(), "contains the control character U+000A");
+        assert_eq!(Rejection::BidiControl('\u{202e}').to_string(), "contains the bidirectional control character U+202E");
+    }
+
+    #[test]
+    fn test_truncate() {
+        assert_eq!(truncate("Alice", 10), "Alice");
+        assert_eq!(truncate("Alice", 5), "Alice");
+        assert_eq!(truncate("Alexander", 5), "Alex…");
+        assert_eq!(truncate("Mary Jane", 6), "Mary…");
+        assert_eq!(truncate("Alice", 1), "…");
+    }
+
+    #[test]
+    fn test_truncate_counts_characters() {
+        assert_eq!(truncate("山田太郎", 4), "山田太郎");
+        assert_eq!(truncate("山田太郎", 3), "山田…");
+        assert_eq!(truncate("👋👋👋", 2), "👋…");
+    }
+}
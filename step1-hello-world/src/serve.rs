@@ -0,0 +1,218 @@
+// 挨拶を返す HTTP サーバー（hello-cli serve --port 3000）
+//
+//   GET /greet?name=Alice&lang=ja&template=...&format=json
+//     200 Hello, Alice!（format=json か Accept: application/json なら {"name": ..., "lang": ..., "message": ...}）
+//     400 言語やテンプレートが正しくないとき
+//   GET /health ok
+// 挨拶は CLI と同じ言語の表とテンプレートで作る
+use chrono::Local;
+use serde_json::json;
+use tiny_http::{Header, Method, Request, Response, Server};
+
+use crate::locale::{self, Locale};
+use crate::template::{self, Template};
+
+// (ステータスコード, 本文, JSON か)
+pub struct Reply {
+    pub status: u16,
+    pub body: String,
+    pub json: bool,
+}
+
+// Ctrl-C で止めるまで1件ずつ処理する
+pub fn run(host: &str, port: u16, default_locale: &'static Locale) -> Result<(), String> {
+    let server = Server::http((host, port)).map_err(|e| format!("failed to listen on {}:{}: {}", host, port, e))?;
+    eprintln!("Listening on http://{}:{} (GET /greet?name=..., Ctrl-C to stop)", host, port);
+    for request in server.incoming_requests() {
+        let reply = handle(request.method(), request.url(), accepts_json(&request), default_locale);
+        tracing::info!(method = %request.method(), url = request.url(), status = reply.status, "request");
+        let content_type = if reply.json { "application/json" } else { "text/plain; charset=utf-8" };
+        let response = Response::from_string(reply.body)
+            .with_status_code(reply.status)
+            .with_header(header("Content-Type", content_type))
+            .with_header(header("Access-Control-Allow-Origin", "*"));
+        if let Err(e) = request.respond(response) {
+            tracing::warn!("failed to send a response: {}", e);
+        }
+    }
+    Ok(())
+}
+
+fn header(name: &str, value: &str) -> Header {
+    Header::from_bytes(name, value).expect("header names and values are ASCII")
+}
+
+fn accepts_json(request: &Request) -> bool {
+    request
+        .headers()
+        .iter()
+        .any(|header| header.field.equiv("Accept") && header.value.as_str().contains("application/json"))
+}
+
+pub fn handle(method: &Method, url: &str, accepts_json: bool, default_locale: &'static Locale) -> Reply {
+    let (path, query) = url.split_once('?').unwrap_or((url, ""));
+    let params = parse_query(query);
+    let param = |key: &str| params.iter().rev().find(|(name, _)| name == key).map(|(_, value)| value.as_str());
+    let json = match param("format") {
+        Some(format) => format == "json",
+        None => accepts_json,
+    };
+    let reply = |status: u16, text: String, value: serde_json::Value| Reply { status, body: if json { value.to_string() } else { text }, json };
+    let error = |status: u16, message: String| reply(status, message.clone(), json!({ "error": message }));
+    match (method, path) {
+        (Method::Get, "/greet") => {}
+        (Method::Get, "/health") => return reply(200, "ok".to_string(), json!({ "status": "ok" })),
+        (_, "/greet" | "/health") => return error(405, format!("method {} not allowed for {}", method, path)),
+        _ => return error(404, format!("not found: {}", path)),
+    }
+
+    let locale = match param("lang") {
+        Some(code) => match locale::find(code) {
+            Some(locale) => locale,
+            None => return error(400, format!("unsupported language '{}'", code)),
+        },
+        None => default_locale,
+    };
+    let name = param("name").filter(|name| !name.trim().is_empty()).unwrap_or(locale.world);
+    let message = match param("template") {
+        Some(text) => match Template::parse(text) {
+            Ok(template) => template.render(&template::Context {
+                name,
+                index: 1,
+                count: 1,
+                now: Local::now(),
+                greeting: locale.word,
+                punctuation: locale.punctuation,
+                date_format: locale.date_format,
+                time_format: locale.time_format,
+            }),
+            Err(e) => return error(400, e.to_string()),
+        },
+        None => locale.greet(name),
+    };
+    reply(200, message.clone(), json!({ "name": name, "lang": locale.code, "message": message }))
+}
+
+// "name=Alice+Smith&lang=ja" を (キー, 値) の列にする（%XX と + を戻す）
+fn parse_query(query: &str) -> Vec<(String, String)> {
+    query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (decode(key), decode(value))
+        })
+        .collect()
+}
+
+fn decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => decoded.push(b' '),
+            b'%' => match (bytes.get(i + 1).and_then(hex), bytes.get(i + 2).and_then(hex)) {
+                (Some(high), Some(low)) => {
+                    decoded.push(high * 16 + low);
+                    i += 2;
+                }
+                _ => decoded.push(b'%'),
+            },
+            byte => decoded.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+fn hex(&byte: &u8) -> Option<u8> {
+    (byte as char).to_digit(16).map(|digit| digit as u8)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::locale::LOCALES;
+
+    fn get(url: &str) -> Reply {
+        handle(&Method::Get, url, false, &LOCALES[0])
+    }
+
+    fn body_json(reply: &Reply) -> serde_json::Value {
+        assert!(reply.json, "{}", reply.body);
+        serde_json::from_str(&reply.body).unwrap()
+    }
+
+    #[test]
+    fn test_greet() {
+        let reply = get("/greet?name=Alice");
+        assert_eq!((reply.status, reply.body.as_str(), reply.json), (200, "Hello, Alice!", false));
+        assert_eq!(get("/greet").body, "Hello, World!");
+        assert_eq!(get("/greet?name=+").body, "Hello, World!");
+        assert_eq!(get("/greet?name=Alice&lang=ja").body, "こんにちは、Alice！");
+        assert_eq!(get("/greet?lang=de_DE").body, "Hallo, Welt!");
+        // + と %XX を戻し、同じキーは最後のものを使う
+        assert_eq!(get("/greet?name=Mary+Jane").body, "Hello, Mary Jane!");
+        assert_eq!(get("/greet?name=%E5%A4%AA%E9%83%8E&lang=ja").body, "こんにちは、太郎！");
+        assert_eq!(get("/greet?name=Alice&name=Bob").body, "Hello, Bob!");
+        // lang がなければ起動時の言語
+        let locale = locale::find("fr").unwrap();
+        assert_eq!(handle(&Method::Get, "/greet?name=Alice", false, locale).body, "Bonjour, Alice !");
+    }
+
+    #[test]
+    fn test_template() {
+        assert_eq!(get("/greet?name=Alice&template=%7Bgreeting%7D+%7Bname%7D%7Bpunctuation%7D").body, "Hello Alice!");
+        assert_eq!(get("/greet?name=Alice&lang=es&template={greeting}+{name}{punctuation}").body, "¡Hola Alice!");
+        assert_eq!(get("/greet?template=#{index}+of+{count}+{{x}}").body, "#1 of 1 {x}");
+    }
+
+    #[test]
+    fn test_json() {
+        let json = body_json(&get("/greet?name=Alice&lang=ja&format=json"));
+        assert_eq!(json, json!({ "name": "Alice", "lang": "ja", "message": "こんにちは、Alice！" }));
+        // Accept: application/json でも JSON にし、format=text はそれより優先する
+        let reply = handle(&Method::Get, "/greet?name=Alice", true, &LOCALES[0]);
+        assert_eq!(body_json(&reply)["message"], "Hello, Alice!");
+        let reply = handle(&Method::Get, "/greet?name=Alice&format=text", true, &LOCALES[0]);
+        assert_eq!((reply.body.as_str(), reply.json), ("Hello, Alice!", false));
+        assert_eq!(body_json(&get("/health?format=json")), json!({ "status": "ok" }));
+    }
+
+    #[test]
+    fn test_bad_parameters() {
+        let reply = get("/greet?lang=xx");
+        assert_eq!((reply.status, reply.body.as_str()), (400, "unsupported language 'xx'"));
+        let reply = get("/greet?lang=xx&format=json");
+        assert_eq!((reply.status, body_json(&reply)), (400, json!({ "error": "unsupported language 'xx'" })));
+        let reply = get("/greet?template={nme}");
+        assert_eq!(reply.status, 400);
+        assert!(reply.body.contains("unknown placeholder {nme} (did you mean {name}?)"), "{}", reply.body);
+        assert_eq!(get("/greet?template={name").status, 400);
+    }
+
+    #[test]
+    fn test_paths_and_methods() {
+        let reply = get("/health");
+        assert_eq!((reply.status, reply.body.as_str()), (200, "ok"));
+        let reply = get("/other?name=Alice");
+        assert_eq!((reply.status, reply.body.as_str()), (404, "not found: /other"));
+        assert_eq!(body_json(&get("/other?format=json")), json!({ "error": "not found: /other" }));
+        let reply = handle(&Method::Post, "/greet?name=Alice", false, &LOCALES[0]);
+        assert_eq!((reply.status, reply.body.as_str()), (405, "method POST not allowed for /greet"));
+        assert_eq!(handle(&Method::Delete, "/health", false, &LOCALES[0]).status, 405);
+    }
+
+    #[test]
+    fn test_decode() {
+        assert_eq!(parse_query("a=1&b=x+y&&c"), vec![
+            ("a".to_string(), "1".to_string()),
+            ("b".to_string(), "x y".to_string()),
+            ("c".to_string(), String::new()),
+        ]);
+        assert_eq!(decode("100%25"), "100%");
+        assert_eq!(decode("50%"), "50%");
+        assert_eq!(decode("%zz"), "%zz");
+    }
+}
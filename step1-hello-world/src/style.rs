@@ -0,0 +1,62 @@
+// 挨拶の装飾（--color auto|always|never、--style bold|rainbow）
+//
+// auto では出力先が端末で、NO_COLOR が設定されていないときだけ色を付ける
+use clap::ValueEnum;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ColorChoice {
+    Auto,
+    Always,
+    Never,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Style {
+    Bold,
+    Rainbow,
+}
+
+const RESET: &str = "\x1b[0m";
+const BOLD: &str = "\x1b[1m";
+// 赤、黄、緑、シアン、青、マゼンタ
+const RAINBOW: &[u8] = &[31, 33, 32, 36, 34, 35];
+
+impl ColorChoice {
+    pub fn enabled(self, terminal: bool) -> bool {
+        match self {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto => {
+                std::env::var_os("NO_COLOR").is_none_or(|value| value.is_empty()) && terminal
+            }
+        }
+    }
+}
+
+// 色を付けないときや style がないときはそのまま返す
+pub fn apply(message: &str, style: Option<Style>, color: bool) -> String {
+    match style {
+        Some(style) if color => paint(message, style),
+        _ => message.to_string(),
+    }
+}
+
+fn paint(message: &str, style: Style) -> String {
+    match style {
+        Style::Bold => format!("{}{}{}", BOLD, message, RESET),
+        Style::Rainbow => {
+            // 空白は色を進めない
+            let mut painted = String::new();
+            let mut colors = RAINBOW.iter().cycle();
+            for c in message.chars() {
+                if c.is_whitespace() {
+                    painted.push(c);
+                } else {
+                    painted.push_str(&format!("\x1b[{}m{}", colors.next().unwrap(), c));
+                }
+            }
+            painted.push_str(RESET);
+            painted
+        }
+    }
+}
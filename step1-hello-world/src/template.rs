@@ -0,0 +1,248 @@
+// 挨拶のテンプレート（--template "Good {time_of_day}, {name}! You are visitor #{index}"）
+//
+// {name} などのプレースホルダーを置き換える。「{」「}」そのものは {{ と }} で書く
+use std::fmt;
+
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, Local, Timelike};
+
+pub const PLACEHOLDERS: &[(&str, &str)] = &[
+    ("name", "name to greet"),
+    ("index", "number of this greeting, from 1"),
+    ("count", "total number of greetings"),
+    ("date", "today's date in the language's format (--date-format)"),
+    ("time", "current time in the language's format"),
+    ("time_of_day", "morning, afternoon, evening or night"),
+    ("greeting", "greeting word, e.g. Hello (--greeting-word)"),
+    ("punctuation", "closing punctuation, e.g. ! (--punctuation)"),
+];
+
+#[derive(Debug, Clone, PartialEq)]
+enum Part {
+    Text(String),
+    Placeholder(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Template {
+    parts: Vec<Part>,
+}
+
+// テンプレートの誤り（position は文字単位の位置）
+#[derive(Debug, Clone, PartialEq)]
+pub struct TemplateError {
+    pub template: String,
+    pub position: usize,
+    pub len: usize,
+    pub message: String,
+}
+
+// 置き換える値
+pub struct Context<'a> {
+    pub name: &'a str,
+    pub index: u32,
+    pub count: u32,
+    pub now: DateTime<Local>,
+    pub greeting: &'a str,
+    pub punctuation: &'a str,
+    // {date} と {time} の strftime 形式の書式
+    pub date_format: &'a str,
+    pub time_format: &'a str,
+}
+
+impl Template {
+    pub fn parse(template: &str) -> Result<Template, TemplateError> {
+        let chars: Vec<char> = template.chars().collect();
+        let error = |position: usize, len: usize, message: String| TemplateError { template: template.to_string(), position, len, message };
+        let mut parts = Vec::new();
+        let mut text = String::new();
+        let mut i = 0;
+        while i < chars.len() {
+            match chars[i] {
+                '{' if chars.get(i + 1) == Some(&'{') => {
+                    text.push('{');
+                    i += 2;
+                }
+                '}' if chars.get(i + 1) == Some(&'}') => {
+                    text.push('}');
+                    i += 2;
+                }
+                '{' => {
+                    let Some(length) = chars[i + 1..].iter().position(|&c| c == '}') else {
+                        return Err(error(i, 1, "unclosed '{' (write {{ for a literal brace)".to_string()));
+                    };
+                    let name: String = chars[i + 1..i + 1 + length].iter().collect();
+                    let name = name.trim();
+                    if !PLACEHOLDERS.iter().any(|(placeholder, _)| *placeholder == name) {
+                        let message = match suggest(name) {
+                            Some(suggestion) => format!("unknown placeholder {{{}}} (did you mean {{{}}}?)", name, suggestion),
+                            None => format!("unknown placeholder {{{}}}", name),
+                        };
+                        return Err(error(i, length + 2, message));
+                    }
+                    if !text.is_empty() {
+                        parts.push(Part::Text(std::mem::take(&mut text)));
+                    }
+                    parts.push(Part::Placeholder(name.to_string()));
+                    i += length + 2;
+                }
+                '}' => return Err(error(i, 1, "unmatched '}' (write }} for a literal brace)".to_string())),
+                c => {
+                    text.push(c);
+                    i += 1;
+                }
+            }
+        }
+        if !text.is_empty() {
+            parts.push(Part::Text(text));
+        }
+        Ok(Template { parts })
+    }
+
+    pub fn render(&self, context: &Context) -> String {
+        self.parts
+            .iter()
+            .map(|part| match part {
+                Part::Text(text) => text.clone(),
+                Part::Placeholder(name) => match name.as_str() {
+                    "name" => context.name.to_string(),
+                    "index" => context.index.to_string(),
+                    "count" => context.count.to_string(),
+                    "date" => context.now.format(context.date_format).to_string(),
+                    "time" => context.now.format(context.time_format).to_string(),
+                    "time_of_day" => time_of_day(context.now.hour()).to_string(),
+                    "greeting" => context.greeting.to_string(),
+                    "punctuation" => context.punctuation.to_string(),
+                    _ => unreachable!("placeholders are checked when parsing"),
+                },
+            })
+            .collect()
+    }
+}
+
+// strftime 形式の書式を確かめる（chrono は誤った書式で表示するときに panic する）
+pub fn check_format(format: &str) -> Result<(), String> {
+    if StrftimeItems::new(format).any(|item| matches!(item, Item::Error)) {
+        return Err(format!("'{}' is not a valid date format (use strftime codes like %Y-%m-%d)", format));
+    }
+    Ok(())
+}
+
+// 5時から正午までが morning、17時までが afternoon、21時までが evening
+pub fn time_of_day(hour: u32) -> &'static str {
+    match hour {
+        5..=11 => "morning",
+        12..=16 => "afternoon",
+        17..=20 => "evening",
+        _ => "night",
+    }
+}
+
+// 綴りの近いプレースホルダー（編集距離が2以下）
+fn suggest(name: &str) -> Option<&'static str> {
+    PLACEHOLDERS
+        .iter()
+        .map(|(placeholder, _)| (*placeholder, edit_distance(name, placeholder)))
+        .filter(|&(_, distance)| distance <= 2)
+        .min_by_key(|&(_, distance)| distance)
+        .map(|(placeholder, _)| placeholder)
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let cost = if ca == *cb { 0 } else { 1 };
+            current.push((previous[j] + cost).min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+// 誤りの位置に ^ を付けて、使えるプレースホルダーを示す
+impl fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "invalid template: {}", self.message)?;
+        writeln!(f, "  {}", self.template)?;
+        writeln!(f, "  {}{}", " ".repeat(self.position), "^".repeat(self.len.max(1)))?;
+        let available: Vec<String> = PLACEHOLDERS.iter().map(|(name, _)| format!("{{{}}}", name)).collect();
+        write!(f, "  available: {}", available.join(", "))
+    }
+}
+
+impl std::error::Error for TemplateError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn context(name: &str) -> Context<'_> {
+        Context {
+            name,
+            index: 2,
+            count: 3,
+            now: Local.with_ymd_and_hms(2024, 3, 9, 18, 5, 0).unwrap(),
+            greeting: "Hello",
+            punctuation: "!",
+            date_format: "%Y-%m-%d",
+            time_format: "%H:%M",
+        }
+    }
+
+    fn render(template: &str) -> String {
+        Template::parse(template).unwrap().render(&context("Alice"))
+    }
+
+    #[test]
+    fn test_placeholders() {
+        assert_eq!(render("{greeting}, {name}{punctuation}"), "Hello, Alice!");
+        assert_eq!(render("Good {time_of_day}, {name}! You are visitor #{index} of {count}"), "Good evening, Alice! You are visitor #2 of 3");
+        assert_eq!(render("{date} {time}"), "2024-03-09 18:05");
+        // 名前の前後の空白は無視し、同じプレースホルダーは何度でも使える
+        assert_eq!(render("{ name } and {name}"), "Alice and Alice");
+        assert_eq!(render("no placeholders"), "no placeholders");
+        assert_eq!(render(""), "");
+    }
+
+    #[test]
+    fn test_escaped_braces() {
+        assert_eq!(render("{{name}} is {name}"), "{name} is Alice");
+        assert_eq!(render("}}{{"), "}{");
+        // 値の中の括弧はそのまま出す
+        assert_eq!(Template::parse("{name}").unwrap().render(&context("{index}")), "{index}");
+    }
+
+    #[test]
+    fn test_errors() {
+        let error = Template::parse("Hi {nme}!").unwrap_err();
+        assert_eq!((error.position, error.len), (3, 5));
+        assert_eq!(error.message, "unknown placeholder {nme} (did you mean {name}?)");
+        assert_eq!(Template::parse("{xyzzy}").unwrap_err().message, "unknown placeholder {xyzzy}");
+
+        let error = Template::parse("Hi {name").unwrap_err();
+        assert_eq!((error.position, error.len), (3, 1));
+        assert!(error.message.starts_with("unclosed '{'"));
+        let error = Template::parse("Hi }").unwrap_err();
+        assert_eq!((error.position, error.message.as_str()), (3, "unmatched '}' (write }} for a literal brace)"));
+
+        // 位置は文字単位で数え、^ で示す
+        let error = Template::parse("こんにちは {nam}").unwrap_err();
+        assert_eq!(error.position, 6);
+        let lines: Vec<String> = error.to_string().lines().map(str::to_string).collect();
+        assert_eq!(lines[0], "invalid template: unknown placeholder {nam} (did you mean {name}?)");
+        assert_eq!(lines[1], "  こんにちは {nam}");
+        assert_eq!(lines[2], "        ^^^^^");
+        assert!(lines[3].starts_with("  available: {name}, {index}"));
+    }
+
+    #[test]
+    fn test_check_format_and_time_of_day() {
+        assert!(check_format("%Y-%m-%d").is_ok());
+        assert!(check_format("%Q").is_err());
+        assert_eq!([4, 5, 12, 17, 21].map(time_of_day), ["night", "morning", "afternoon", "evening", "night"]);
+    }
+}
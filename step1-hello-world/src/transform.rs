@@ -0,0 +1,61 @@
+// 大文字・小文字の変換（--case upper|lower|title|alternating|none）
+use clap::ValueEnum;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum Case {
+    Upper,
+    Lower,
+    // 単語の最初の文字だけ大文字
+    Title,
+    // 文字ごとに大文字と小文字を交互にする（hElLo）
+    Alternating,
+    #[default]
+    None,
+}
+
+pub fn apply(text: &str, case: Case) -> String {
+    match case {
+        Case::Upper => text.to_uppercase(),
+        Case::Lower => text.to_lowercase(),
+        Case::Title => title(text),
+        Case::Alternating => alternating(text),
+        Case::None => text.to_string(),
+    }
+}
+
+// 単語の最初の文字（「¡Hola」の H など記号は飛ばす）を大文字、それ以外を小文字にする
+fn title(text: &str) -> String {
+    let mut result = String::new();
+    let mut start = true;
+    for c in text.chars() {
+        if c.is_whitespace() {
+            start = true;
+            result.push(c);
+        } else if start && c.is_alphabetic() {
+            start = false;
+            result.extend(c.to_uppercase());
+        } else {
+            result.extend(c.to_lowercase());
+        }
+    }
+    result
+}
+
+// 文字（記号や空白は数えない）ごとに大文字から交互にする
+fn alternating(text: &str) -> String {
+    let mut result = String::new();
+    let mut upper = true;
+    for c in text.chars() {
+        if !c.is_alphabetic() {
+            result.push(c);
+            continue;
+        }
+        if upper {
+            result.extend(c.to_uppercase());
+        } else {
+            result.extend(c.to_lowercase());
+        }
+        upper = !upper;
+    }
+    result
+}
@@ -0,0 +1,128 @@
+// 基本的な算術関数（f64 で計算し、オーバーフローや定義されない演算はエラーにする）
+use crate::CalcError;
+
+pub fn add(a: f64, b: f64) -> Result<f64, CalcError> {
+    let result = a + b;
+    if result.is_infinite() || result.is_nan() {
+        return Err(CalcError::InvalidExpression("Result overflow".to_string()));
+    }
+    Ok(result)
+}
+
+pub fn subtract(a: f64, b: f64) -> Result<f64, CalcError> {
+    let result = a - b;
+    if result.is_infinite() || result.is_nan() {
+        return Err(CalcError::InvalidExpression("Result overflow".to_string()));
+    }
+    Ok(result)
+}
+
+pub fn multiply(a: f64, b: f64) -> Result<f64, CalcError> {
+    let result = a * b;
+    if result.is_infinite() || result.is_nan() {
+        return Err(CalcError::InvalidExpression("Result overflow".to_string()));
+    }
+    Ok(result)
+}
+
+pub fn divide(a: f64, b: f64) -> Result<f64, CalcError> {
+    if b == 0.0 {
+        return Err(CalcError::DivisionByZero);
+    }
+    
+    let result = a / b;
+    if result.is_infinite() || result.is_nan() {
+        return Err(CalcError::InvalidExpression("Result overflow".to_string()));
+    }
+    Ok(result)
+}
+
+// 余り（割る数と同じ符号。-7 % 3 = 2）
+pub fn modulo(a: f64, b: f64) -> Result<f64, CalcError> {
+    if b == 0.0 {
+        return Err(CalcError::DivisionByZero);
+    }
+    
+    let result = a % b;
+    if result.is_nan() {
+        return Err(CalcError::InvalidExpression("Result overflow or invalid".to_string()));
+    }
+    if result != 0.0 && (result < 0.0) != (b < 0.0) {
+        return Ok(result + b);
+    }
+    Ok(result)
+}
+
+// 切り捨て除算（-7 // 2 = -4）
+pub fn floor_divide(a: f64, b: f64) -> Result<f64, CalcError> {
+    let remainder = modulo(a, b)?;
+    divide(a - remainder, b).map(f64::round)
+}
+
+pub fn power(base: f64, exp: f64) -> Result<f64, CalcError> {
+    if base < 0.0 && exp.fract() != 0.0 {
+        return Err(CalcError::InvalidExpression(
+            "Cannot calculate non-integer power of negative number".to_string()
+        ));
+    }
+    
+    let result = base.powf(exp);
+    if result.is_infinite() || result.is_nan() {
+        return Err(CalcError::InvalidExpression("Result overflow or invalid".to_string()));
+    }
+    Ok(result)
+}
+
+pub fn square_root(number: f64) -> Result<f64, CalcError> {
+    if number < 0.0 {
+        return Err(CalcError::InvalidExpression(
+            "Cannot calculate square root of negative number".to_string()
+        ));
+    }
+    
+    Ok(number.sqrt())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_basic_operations() {
+        assert_eq!(add(2.0, 3.0).unwrap(), 5.0);
+        assert_eq!(subtract(5.0, 3.0).unwrap(), 2.0);
+        assert_eq!(multiply(4.0, 3.0).unwrap(), 12.0);
+        assert_eq!(divide(10.0, 2.0).unwrap(), 5.0);
+    }
+
+    #[test]
+    fn test_division_by_zero() {
+        assert!(matches!(divide(5.0, 0.0), Err(CalcError::DivisionByZero)));
+    }
+
+    #[test]
+    fn test_modulo_and_floor_division() {
+        assert_eq!(modulo(7.0, 3.0).unwrap(), 1.0);
+        assert_eq!(modulo(-7.0, 3.0).unwrap(), 2.0);
+        assert_eq!(modulo(7.0, -3.0).unwrap(), -2.0);
+        assert_eq!(modulo(5.5, 2.0).unwrap(), 1.5);
+        assert_eq!(floor_divide(7.0, 2.0).unwrap(), 3.0);
+        assert_eq!(floor_divide(-7.0, 2.0).unwrap(), -4.0);
+        assert!(matches!(modulo(5.0, 0.0), Err(CalcError::DivisionByZero)));
+        assert!(matches!(floor_divide(5.0, 0.0), Err(CalcError::DivisionByZero)));
+    }
+
+    #[test]
+    fn test_square_root() {
+        assert_eq!(square_root(16.0).unwrap(), 4.0);
+        assert_eq!(square_root(9.0).unwrap(), 3.0);
+        assert!(square_root(-1.0).is_err());
+    }
+
+    #[test]
+    fn test_power() {
+        assert_eq!(power(2.0, 3.0).unwrap(), 8.0);
+        assert_eq!(power(5.0, 2.0).unwrap(), 25.0);
+        assert!(power(-2.0, 0.5).is_err()); // 負数の非整数乗
+    }
+}
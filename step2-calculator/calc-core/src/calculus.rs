@@ -0,0 +1,150 @@
+// 数値積分と数値微分（calc-cli integrate / diff）
+//
+//   積分: 適応型シンプソン法。区間を半分にした2つの値との差が許容誤差に収まるまで分割する
+//   微分: 中心差分 (f(x+h) - f(x-h)) / 2h を h と h/2 で求めてリチャードソン補外する
+use crate::evaluator::Evaluator;
+use crate::parser;
+use crate::solver;
+use crate::CalcError;
+
+// 積分の許容誤差と分割の深さの既定値
+pub const DEFAULT_TOLERANCE: f64 = 1e-10;
+pub const DEFAULT_MAX_DEPTH: usize = 50;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Estimate {
+    pub value: f64,
+    // 誤差の見積もり
+    pub error: f64,
+    // 関数を評価した回数
+    pub evaluations: usize,
+}
+
+// from から to までの定積分
+pub fn integrate(
+    evaluator: &Evaluator,
+    expression: &str,
+    variable: Option<&str>,
+    from: f64,
+    to: f64,
+    tolerance: f64,
+    max_depth: usize,
+) -> Result<(String, Estimate), CalcError> {
+    let expr = parser::parse(expression, evaluator.syntax)?;
+    let variable = match variable {
+        Some(variable) => variable.to_string(),
+        None => solver::find_unknown(evaluator, &expr)?,
+    };
+    let estimate = {
+        let f = solver::function(evaluator, &expr, &variable);
+        let middle = from + (to - from) / 2.0;
+        let (fa, fm, fb) = (f(from)?, f(middle)?, f(to)?);
+        let whole = simpson(from, to, fa, fm, fb);
+        let mut estimate = Estimate { value: 0.0, error: 0.0, evaluations: 3 };
+        adaptive_simpson(&f, [(from, fa), (middle, fm), (to, fb)], whole, tolerance, max_depth, &mut estimate)?;
+        estimate
+    };
+    Ok((variable, estimate))
+}
+
+// at での微分係数（step を省くと at の大きさに合わせて決める）
+pub fn differentiate(
+    evaluator: &Evaluator,
+    expression: &str,
+    variable: Option<&str>,
+    at: f64,
+    step: Option<f64>,
+) -> Result<(String, Estimate), CalcError> {
+    let expr = parser::parse(expression, evaluator.syntax)?;
+    let variable = match variable {
+        Some(variable) => variable.to_string(),
+        None => solver::find_unknown(evaluator, &expr)?,
+    };
+    let step = step.unwrap_or(1e-3 * at.abs().max(1.0));
+    if !(step > 0.0 && step.is_finite()) {
+        return Err(CalcError::InvalidExpression(format!("step must be a positive number, got {}", step)));
+    }
+    let estimate = {
+        let f = solver::function(evaluator, &expr, &variable);
+        let central = |h: f64| -> Result<f64, CalcError> {
+            // at + h を正確に表せる h にして丸め誤差を減らす
+            let h = (at + h) - at;
+            Ok((f(at + h)? - f(at - h)?) / (2.0 * h))
+        };
+        let (coarse, fine) = (central(step)?, central(step / 2.0)?);
+        // 誤差が h^2 に比例することを使って打ち消す
+        Estimate { value: (4.0 * fine - coarse) / 3.0, error: (fine - coarse).abs() / 3.0, evaluations: 4 }
+    };
+    Ok((variable, estimate))
+}
+
+fn simpson(a: f64, b: f64, fa: f64, fm: f64, fb: f64) -> f64 {
+    (b - a) / 6.0 * (fa + 4.0 * fm + fb)
+}
+
+// [a, b] を半分に分けたシンプソン則の和と whole の差が許容誤差の 15 倍以下なら受け入れる
+// points は区間の左端・中点・右端とそこでの関数の値
+fn adaptive_simpson(
+    f: &impl Fn(f64) -> Result<f64, CalcError>,
+    points: [(f64, f64); 3],
+    whole: f64,
+    tolerance: f64,
+    depth: usize,
+    estimate: &mut Estimate,
+) -> Result<(), CalcError> {
+    let [(a, fa), (m, fm), (b, fb)] = points;
+    let (left_middle, right_middle) = ((a + m) / 2.0, (m + b) / 2.0);
+    let (flm, frm) = (f(left_middle)?, f(right_middle)?);
+    estimate.evaluations += 2;
+    let left = simpson(a, m, fa, flm, fm);
+    let right = simpson(m, b, fm, frm, fb);
+    let difference = left + right - whole;
+    // 深さの上限に達したら、その時点の値と誤差の見積もりを使う
+    if depth == 0 || difference.abs() <= 15.0 * tolerance {
+        estimate.value += left + right + difference / 15.0;
+        estimate.error += difference.abs() / 15.0;
+        return Ok(());
+    }
+    adaptive_simpson(f, [(a, fa), (left_middle, flm), (m, fm)], left, tolerance / 2.0, depth - 1, estimate)?;
+    adaptive_simpson(f, [(m, fm), (right_middle, frm), (b, fb)], right, tolerance / 2.0, depth - 1, estimate)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn integral(expression: &str, from: f64, to: f64) -> f64 {
+        integrate(&Evaluator::default(), expression, None, from, to, DEFAULT_TOLERANCE, DEFAULT_MAX_DEPTH).unwrap().1.value
+    }
+
+    fn derivative(expression: &str, at: f64) -> f64 {
+        differentiate(&Evaluator::default(), expression, None, at, None).unwrap().1.value
+    }
+
+    #[test]
+    fn test_integrate() {
+        assert!((integral("sin(x)", 0.0, std::f64::consts::PI) - 2.0).abs() < 1e-10);
+        assert!((integral("x^2", 0.0, 3.0) - 9.0).abs() < 1e-12);
+        assert!((integral("exp(-t^2)", -5.0, 5.0) - std::f64::consts::PI.sqrt()).abs() < 1e-9);
+        // 向きを逆にすると符号が変わる
+        assert!((integral("x^2", 3.0, 0.0) + 9.0).abs() < 1e-12);
+
+        let (variable, estimate) = integrate(&Evaluator::default(), "sqrt(x)", None, 0.0, 1.0, 1e-6, 50).unwrap();
+        assert_eq!(variable, "x");
+        assert!((estimate.value - 2.0 / 3.0).abs() < 1e-6);
+        assert!(estimate.evaluations > 3);
+    }
+
+    #[test]
+    fn test_differentiate() {
+        assert!((derivative("x^2", 3.0) - 6.0).abs() < 1e-9);
+        assert!((derivative("sin(x)", 0.0) - 1.0).abs() < 1e-10);
+        assert!((derivative("exp(x)", 10.0) - 10f64.exp()).abs() / 10f64.exp() < 1e-9);
+
+        let evaluator = Evaluator::default();
+        let (_, estimate) = differentiate(&evaluator, "a^3", Some("a"), 2.0, Some(0.1)).unwrap();
+        assert!((estimate.value - 12.0).abs() < 1e-9);
+        assert!(differentiate(&evaluator, "x^2", None, 1.0, Some(0.0)).is_err());
+        assert!(matches!(differentiate(&evaluator, "ln(x)", None, 0.0, None), Err(CalcError::InvalidExpression(_))));
+    }
+}
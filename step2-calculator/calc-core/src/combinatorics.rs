@@ -0,0 +1,215 @@
+// 階乗・ガンマ関数・順列と組み合わせ
+use std::f64::consts::PI;
+
+use bigdecimal::{BigDecimal, ToPrimitive};
+use num_bigint::{BigInt, Sign};
+use num_rational::BigRational;
+
+use crate::number::Number;
+use crate::CalcError;
+
+// 正確に計算する階乗などの引数の上限（結果は数十万桁になる）
+pub const MAX_EXACT_ARGUMENT: u64 = 20_000;
+
+// f64 で誤差なく表せる整数のビット数
+const F64_EXACT_BITS: u64 = 53;
+
+// Lanczos 近似の係数（g = 7）
+const LANCZOS_G: f64 = 7.0;
+const LANCZOS: [f64; 9] = [
+    0.999_999_999_999_809_9,
+    676.520_368_121_885_1,
+    -1_259.139_216_722_402_8,
+    771.323_428_777_653_1,
+    -176.615_029_162_140_6,
+    12.507_343_278_686_905,
+    -0.138_571_095_265_720_12,
+    9.984_369_578_019_572e-6,
+    1.505_632_735_149_311_6e-7,
+];
+
+pub fn factorial(n: u64) -> BigInt {
+    (2..=n).fold(BigInt::from(1), |acc, k| acc * k)
+}
+
+// nPr = n! / (n - r)!
+pub fn permutations(n: u64, r: u64) -> BigInt {
+    if r > n {
+        return BigInt::ZERO;
+    }
+    (n - r + 1..=n).fold(BigInt::from(1), |acc, k| acc * k)
+}
+
+// nCr（途中の積は常に割り切れるので整数のまま計算できる）
+pub fn combinations(n: u64, r: u64) -> BigInt {
+    if r > n {
+        return BigInt::ZERO;
+    }
+    let r = r.min(n - r);
+    (0..r).fold(BigInt::from(1), |acc, i| acc * (n - i) / (i + 1))
+}
+
+// ガンマ関数（正の整数では (x-1)! と一致する）
+pub fn gamma(x: f64) -> f64 {
+    if x > 0.0 && x.fract() == 0.0 && x <= 171.0 {
+        return (2..x as u64).fold(1.0, |acc, k| acc * k as f64);
+    }
+    if x < 0.5 {
+        // 反射公式 Γ(x)Γ(1-x) = π / sin(πx)
+        return PI / ((PI * x).sin() * gamma(1.0 - x));
+    }
+
+    let x = x - 1.0;
+    let t = x + LANCZOS_G + 0.5;
+    let series = LANCZOS
+        .iter()
+        .enumerate()
+        .skip(1)
+        .fold(LANCZOS[0], |sum, (i, c)| sum + c / (x + i as f64));
+    // t^(x+0.5) が先にあふれないよう2つに分けて掛ける
+    let half = t.powf((x + 0.5) / 2.0);
+    (2.0 * PI).sqrt() * half * (half * (-t).exp()) * series
+}
+
+// ガンマ関数の絶対値の自然対数 ln|Γ(x)|（Γ(x) があふれる大きな x でも求められる）
+pub fn ln_gamma(x: f64) -> f64 {
+    if x < 0.5 {
+        // 反射公式の対数
+        return (PI / (PI * x).sin().abs()).ln() - ln_gamma(1.0 - x);
+    }
+    let x = x - 1.0;
+    let t = x + LANCZOS_G + 0.5;
+    let series = LANCZOS
+        .iter()
+        .enumerate()
+        .skip(1)
+        .fold(LANCZOS[0], |sum, (i, c)| sum + c / (x + i as f64));
+    0.5 * (2.0 * PI).ln() + (x + 0.5) * t.ln() - t + series.ln()
+}
+
+// x!（整数なら正確に、それ以外は Γ(x+1) で近似する）
+pub fn factorial_of(x: &Number) -> Result<Number, CalcError> {
+    match x.to_bigint() {
+        Some(n) => {
+            let n = exact_argument("factorial", &n)?;
+            Ok(exact_result(factorial(n), std::slice::from_ref(x)))
+        }
+        None => {
+            let result = gamma(x.to_f64() + 1.0);
+            if !result.is_finite() {
+                return Err(CalcError::InvalidExpression("Result overflow or invalid".to_string()));
+            }
+            Ok(Number::Float(result))
+        }
+    }
+}
+
+// 正確に計算する組み込み関数（それ以外の名前は None）
+pub fn call(name: &str, args: &[Number]) -> Option<Result<Number, CalcError>> {
+    let result = match name {
+        "factorial" => match args {
+            [x] => factorial_of(x),
+            _ => Err(argument_count(name, "1", args.len())),
+        },
+        "ncr" | "npr" => match args {
+            [n, r] => integer_pair(name, n, r).map(|(n, r)| {
+                let value = if name == "ncr" { combinations(n, r) } else { permutations(n, r) };
+                exact_result(value, args)
+            }),
+            _ => Err(argument_count(name, "2", args.len())),
+        },
+        _ => return None,
+    };
+    Some(result)
+}
+
+fn argument_count(name: &str, expected: &str, found: usize) -> CalcError {
+    CalcError::ArgumentCount { name: name.to_string(), expected: expected.to_string(), found }
+}
+
+fn integer_pair(name: &str, n: &Number, r: &Number) -> Result<(u64, u64), CalcError> {
+    match (n.to_bigint(), r.to_bigint()) {
+        (Some(n), Some(r)) => Ok((exact_argument(name, &n)?, exact_argument(name, &r)?)),
+        _ => Err(CalcError::InvalidExpression(format!("{} expects non-negative integers", name))),
+    }
+}
+
+fn exact_argument(name: &str, n: &BigInt) -> Result<u64, CalcError> {
+    if n.sign() == Sign::Minus {
+        return Err(CalcError::InvalidExpression(format!("{} is not defined for negative integers", name)));
+    }
+    n.to_u64()
+        .filter(|&n| n <= MAX_EXACT_ARGUMENT)
+        .ok_or_else(|| CalcError::InvalidExpression(format!(
+            "{} argument is too large (maximum {})", name, MAX_EXACT_ARGUMENT
+        )))
+}
+
+// 引数と同じ種類の数値にする
+// f64 では誤差が出る大きさになったら多倍長整数に切り替える
+fn exact_result(value: BigInt, args: &[Number]) -> Number {
+    if args.iter().any(|arg| matches!(arg, Number::Decimal(_))) {
+        Number::Decimal(BigDecimal::from(value))
+    } else if args.iter().any(|arg| matches!(arg, Number::Rational(_))) {
+        Number::Rational(BigRational::from_integer(value))
+    } else if args.iter().all(|arg| matches!(arg, Number::Float(_))) && value.bits() <= F64_EXACT_BITS {
+        Number::Float(value.to_f64().unwrap_or(f64::INFINITY))
+    } else {
+        Number::Integer(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_factorial() {
+        assert_eq!(factorial(0), BigInt::from(1));
+        assert_eq!(factorial(10), BigInt::from(3_628_800));
+        assert_eq!(
+            factorial(50).to_string(),
+            "30414093201713378043612608166064768844377641568960512000000000000"
+        );
+    }
+
+    #[test]
+    fn test_combinatorics() {
+        assert_eq!(combinations(5, 2), BigInt::from(10));
+        assert_eq!(combinations(52, 5), BigInt::from(2_598_960));
+        assert_eq!(combinations(3, 5), BigInt::ZERO);
+        assert_eq!(permutations(5, 2), BigInt::from(20));
+        assert_eq!(permutations(5, 0), BigInt::from(1));
+        assert_eq!(combinations(100, 50).to_string(), "100891344545564193334812497256");
+    }
+
+    #[test]
+    fn test_gamma() {
+        assert_eq!(gamma(5.0), 24.0);
+        assert!((gamma(0.5) - PI.sqrt()).abs() < 1e-12);
+        assert!((gamma(-0.5) + 2.0 * PI.sqrt()).abs() < 1e-12);
+        assert!((gamma(150.5) / 4.661_072_627_097_377e261 - 1.0).abs() < 1e-10);
+        assert!(gamma(200.0).is_infinite());
+    }
+
+    #[test]
+    fn test_ln_gamma() {
+        assert!(ln_gamma(1.0).abs() < 1e-14);
+        assert!((ln_gamma(10.0) - 362_880f64.ln()).abs() < 1e-12);
+        assert!((ln_gamma(0.5) - PI.sqrt().ln()).abs() < 1e-12);
+        assert!((ln_gamma(-0.5) - (2.0 * PI.sqrt()).ln()).abs() < 1e-12);
+        assert!((ln_gamma(1000.0) - 5_905.220_423_209_181).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_promotion() {
+        assert_eq!(factorial_of(&Number::Float(5.0)).unwrap(), Number::Float(120.0));
+        assert!(matches!(factorial_of(&Number::Float(30.0)).unwrap(), Number::Integer(_)));
+        assert!((factorial_of(&Number::Float(0.5)).unwrap().to_f64() - PI.sqrt() / 2.0).abs() < 1e-12);
+        assert!(factorial_of(&Number::Float(-1.0)).is_err());
+        assert!(factorial_of(&Number::Float(1e6)).is_err());
+        assert!(call("ncr", &[Number::Float(5.0), Number::Float(1.5)]).unwrap().is_err());
+        assert!(call("ncr", &[Number::Float(5.0)]).unwrap().is_err());
+        assert!(call("sin", &[Number::Float(5.0)]).is_none());
+    }
+}
@@ -0,0 +1,43 @@
+// 数学定数
+pub struct Constant {
+    pub name: &'static str,
+    pub value: f64,
+    // 10進数モードで使う50桁の値
+    pub decimal: &'static str,
+    pub description: &'static str,
+}
+
+pub const CONSTANTS: &[Constant] = &[
+    Constant { name: "pi", value: std::f64::consts::PI, decimal: "3.1415926535897932384626433832795028841971693993751", description: "Ratio of a circle's circumference to its diameter" },
+    Constant { name: "e", value: std::f64::consts::E, decimal: "2.7182818284590452353602874713526624977572470937000", description: "Euler's number, base of the natural logarithm" },
+    Constant { name: "tau", value: std::f64::consts::TAU, decimal: "6.2831853071795864769252867665590057683943387987502", description: "Full turn in radians (2 * pi)" },
+    Constant { name: "phi", value: 1.618_033_988_749_895, decimal: "1.6180339887498948482045868343656381177203091798058", description: "Golden ratio ((1 + sqrt(5)) / 2)" },
+];
+
+pub fn find(name: &str) -> Option<&'static Constant> {
+    CONSTANTS.iter().find(|c| c.name == name)
+}
+
+pub fn lookup(name: &str) -> Option<f64> {
+    find(name).map(|c| c.value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lookup() {
+        assert_eq!(lookup("pi"), Some(std::f64::consts::PI));
+        assert_eq!(lookup("tau"), Some(2.0 * std::f64::consts::PI));
+        assert!((lookup("phi").unwrap() - (1.0 + 5.0_f64.sqrt()) / 2.0).abs() < 1e-15);
+        assert_eq!(lookup("PI"), None);
+    }
+
+    #[test]
+    fn test_decimal_values_match_f64() {
+        for constant in CONSTANTS {
+            assert_eq!(constant.decimal.parse::<f64>().unwrap(), constant.value, "{}", constant.name);
+        }
+    }
+}
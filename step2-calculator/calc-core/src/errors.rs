@@ -0,0 +1,31 @@
+// 計算エラーの終了コード（my-cli-errors の CliError）
+//
+// 2 はコマンドラインの使い方の誤りと同じ（clap も使う）
+use my_cli_errors::CliError;
+
+use crate::CalcError;
+
+pub const EXIT_PARSE_ERROR: i32 = 2;
+pub const EXIT_MATH_ERROR: i32 = 3;
+pub const EXIT_DIVISION_BY_ZERO: i32 = 4;
+pub const EXIT_UNKNOWN_NAME: i32 = 5;
+pub const EXIT_ARGUMENT_COUNT: i32 = 6;
+
+impl CliError for CalcError {
+    fn exit_code(&self) -> i32 {
+        match self {
+            CalcError::SyntaxError { .. } | CalcError::ParseError(_) => EXIT_PARSE_ERROR,
+            CalcError::DivisionByZero => EXIT_DIVISION_BY_ZERO,
+            CalcError::UnknownVariable(_) | CalcError::UnknownFunction(_) | CalcError::UnknownOperation(_) => EXIT_UNKNOWN_NAME,
+            CalcError::ArgumentCount { .. } => EXIT_ARGUMENT_COUNT,
+            CalcError::InvalidExpression(_) | CalcError::RecursionLimit(_) | CalcError::StepLimit(_) | CalcError::DimensionMismatch(_) => EXIT_MATH_ERROR,
+        }
+    }
+
+    fn hint(&self) -> Option<String> {
+        match self {
+            CalcError::SyntaxError { hint, .. } => hint.clone(),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,881 @@
+// 構文木の評価
+use std::cell::Cell;
+use std::collections::HashMap;
+
+use crate::combinatorics;
+use crate::constants;
+use crate::format::NumberFormat;
+use crate::functions::{self, AngleMode};
+use crate::number::{self, Number, NumberMode, DEFAULT_DIGITS};
+use crate::parser::{self, BinaryOp, Expr, Statement, Syntax, UnaryOp};
+use crate::radix::Radix;
+use crate::random::Random;
+use crate::units::{self, Quantity, Unit};
+use crate::uncertainty::Uncertain;
+use crate::value::{self, Value};
+use crate::CalcError;
+
+// ユーザー定義関数の呼び出しの深さの上限（無限再帰の防止）
+pub const MAX_CALL_DEPTH: usize = 256;
+
+// sum と prod で繰り返す回数の上限
+pub const MAX_SERIES_TERMS: i64 = 10_000_000;
+
+// round(x, places) で指定できる桁数の上限
+pub const MAX_ROUND_PLACES: i64 = 1000;
+
+// eval_str で使う乱数のシード
+const EVAL_STR_SEED: u64 = 0;
+
+// ユーザー定義関数
+#[derive(Debug, Clone)]
+pub struct UserFunction {
+    pub params: Vec<String>,
+    pub body: Expr,
+}
+
+// 文を実行した結果
+#[derive(Debug, Clone, PartialEq)]
+pub enum Outcome {
+    Value(Value),
+    Assigned { name: String, value: Value },
+    Defined { name: String, params: Vec<String> },
+}
+
+#[derive(Debug, Clone)]
+pub struct Evaluator {
+    pub angle_mode: AngleMode,
+    pub number_mode: NumberMode,
+    // 10進数モードで丸める有効桁数
+    pub digits: u64,
+    // 変数・定数にない名前を単位として扱うか（--units）
+    pub units: bool,
+    // 整数の結果を表示する基数（--output-base）
+    pub output_radix: Radix,
+    // 桁数・表記・桁区切り（--places など）
+    pub number_format: NumberFormat,
+    // ^ を xor とするかどうか（--bitwise）
+    pub syntax: Syntax,
+    // rand() と randint() の乱数の状態（評価は &self で行うので Cell に入れる）
+    random: Cell<Random>,
+    // 評価できる構文木のノードの数の上限と、これまでに評価した数（None なら無制限）
+    step_limit: Option<u64>,
+    steps: Cell<u64>,
+    variables: HashMap<String, Value>,
+    functions: HashMap<String, UserFunction>,
+}
+
+impl Default for Evaluator {
+    fn default() -> Self {
+        Evaluator::new(AngleMode::default())
+    }
+}
+
+// 関数呼び出し中の引数の束縛
+struct Scope<'a> {
+    locals: Option<&'a HashMap<String, Value>>,
+    depth: usize,
+}
+
+impl Evaluator {
+    pub fn new(angle_mode: AngleMode) -> Self {
+        Evaluator {
+            angle_mode,
+            number_mode: NumberMode::default(),
+            digits: DEFAULT_DIGITS,
+            units: false,
+            output_radix: Radix::Dec,
+            number_format: NumberFormat::default(),
+            syntax: Syntax::default(),
+            random: Cell::new(Random::from_entropy()),
+            step_limit: None,
+            steps: Cell::new(0),
+            variables: HashMap::new(),
+            functions: HashMap::new(),
+        }
+    }
+
+    // 数値の種類と有効桁数を指定して作成
+    pub fn with_number_mode(angle_mode: AngleMode, number_mode: NumberMode, digits: u64) -> Self {
+        Evaluator { number_mode, digits, ..Evaluator::new(angle_mode) }
+    }
+
+    // 乱数のシードを固定する（--seed）
+    pub fn seed(&mut self, seed: u64) {
+        self.random.set(Random::seeded(seed));
+    }
+
+    // 数値のモードを切り替え、変数（ans を含む）を新しいモードの値にする
+    pub fn set_number_mode(&mut self, mode: NumberMode) {
+        self.number_mode = mode;
+        for value in self.variables.values_mut() {
+            *value = value.convert(mode, self.digits);
+        }
+    }
+
+    pub fn set_variable(&mut self, name: &str, value: Value) {
+        self.variables.insert(name.to_string(), value);
+    }
+
+    // 外から渡された名前と値で変数を定義する（名前を式として解釈しないので、文を紛れ込ませられない）
+    pub fn define_variable(&mut self, name: &str, value: Value) -> Result<(), CalcError> {
+        if !parser::is_identifier(name) {
+            return Err(CalcError::InvalidExpression(format!("'{}' is not a variable name", name)));
+        }
+        check_definable(name)?;
+        self.set_variable(name, value);
+        Ok(())
+    }
+
+    // 直前の結果を ans と _ に束縛
+    pub fn set_last_result(&mut self, value: Value) {
+        self.set_variable("ans", value.clone());
+        self.set_variable("_", value);
+    }
+
+    // コマンドライン引数の f64 を現在のモードの数値リテラルとして扱う
+    pub fn number(&self, value: f64) -> Number {
+        Number::parse(&value.to_string(), self.number_mode).unwrap_or(Number::Float(value))
+    }
+
+    // 結果を --output-base と --places などの表示形式に従って文字列にする
+    pub fn format(&self, value: &Value) -> String {
+        match value {
+            Value::Number(number) => self.format_number(number),
+            Value::Vector(values) => self.format_list(values),
+            Value::Matrix(matrix) => {
+                let rows: Vec<String> = matrix.rows().map(|row| self.format_list(row)).collect();
+                format!("[{}]", rows.join(", "))
+            }
+            Value::Quantity(quantity) => {
+                format!("{} {}", self.number_format.format_f64(quantity.value), quantity.unit)
+            }
+            // 桁数の指定がなければ誤差の有効数字2桁に合わせて丸める
+            Value::Uncertain(uncertain) => {
+                let format = match (self.number_format.places, self.number_format.significant, uncertain.places()) {
+                    (None, None, Some(places)) => NumberFormat { places: Some(places), ..self.number_format },
+                    _ => self.number_format,
+                };
+                format!("{} ± {}", format.format_f64(uncertain.value), format.format_f64(uncertain.error))
+            }
+        }
+    }
+
+    pub fn format_number(&self, number: &Number) -> String {
+        if self.output_radix != Radix::Dec && number.to_bigint().is_some() {
+            self.output_radix.format_number(number)
+        } else {
+            self.number_format.format(number)
+        }
+    }
+
+    fn format_list(&self, values: &[Number]) -> String {
+        let values: Vec<String> = values.iter().map(|value| self.format_number(value)).collect();
+        format!("[{}]", values.join(", "))
+    }
+
+    // 名前順の変数一覧
+    pub fn variables(&self) -> Vec<(&str, &Value)> {
+        let mut variables: Vec<(&str, &Value)> =
+            self.variables.iter().map(|(name, value)| (name.as_str(), value)).collect();
+        variables.sort_by(|a, b| a.0.cmp(b.0));
+        variables
+    }
+
+    // 名前順のユーザー定義関数一覧
+    pub fn functions(&self) -> Vec<(&str, &UserFunction)> {
+        let mut functions: Vec<(&str, &UserFunction)> =
+            self.functions.iter().map(|(name, function)| (name.as_str(), function)).collect();
+        functions.sort_by(|a, b| a.0.cmp(b.0));
+        functions
+    }
+
+    // 文字列の式を構文解析して評価
+    pub fn evaluate_str(&self, input: &str) -> Result<Value, CalcError> {
+        let ast = parser::parse(input, self.syntax)?;
+        self.evaluate(&ast)
+    }
+
+    // 同じ状態なら何度呼んでも同じ結果になる評価（差分テストなどで使う）
+    // rand() などは固定のシードから引き、この評価器の乱数の状態は進めない
+    pub fn eval_str(&self, input: &str) -> Result<Value, CalcError> {
+        let saved = self.random.replace(Random::seeded(EVAL_STR_SEED));
+        let result = self.evaluate_str(input);
+        self.random.set(saved);
+        result
+    }
+
+    // 代入や関数定義を含む文を実行
+    pub fn execute(&mut self, input: &str) -> Result<Outcome, CalcError> {
+        let statement = parser::parse_statement(input, self.syntax)?;
+        self.execute_statement(statement)
+    }
+
+    // 解析済みの文を実行（解析と実行を別々に計測するときに使う）
+    pub fn execute_statement(&mut self, statement: Statement) -> Result<Outcome, CalcError> {
+        match statement {
+            Statement::Expr(expr) => self.evaluate(&expr).map(Outcome::Value),
+            Statement::Assign { name, value } => {
+                check_definable(&name)?;
+                let value = self.evaluate(&value)?;
+                self.set_variable(&name, value.clone());
+                Ok(Outcome::Assigned { name, value })
+            }
+            Statement::FunctionDef { name, params, body } => {
+                check_definable(&name)?;
+                for (i, param) in params.iter().enumerate() {
+                    if params[..i].contains(param) {
+                        return Err(CalcError::InvalidExpression(format!(
+                            "Duplicate parameter '{}' in definition of '{}'", param, name
+                        )));
+                    }
+                }
+                self.functions.insert(name.clone(), UserFunction { params: params.clone(), body });
+                Ok(Outcome::Defined { name, params })
+            }
+        }
+    }
+
+    // これから評価できるノードの数を limit までにする（calc-cli serve で1件のリクエストの計算量を抑える）
+    // 短くて浅くても f(n) = f(n-1) + f(n-1) のように指数的に増える計算は上限で打ち切る
+    pub fn limit_steps(&mut self, limit: u64) {
+        self.step_limit = Some(limit);
+        self.steps.set(0);
+    }
+
+    fn step(&self) -> Result<(), CalcError> {
+        if let Some(limit) = self.step_limit {
+            let steps = self.steps.get() + 1;
+            if steps > limit {
+                return Err(CalcError::StepLimit(limit));
+            }
+            self.steps.set(steps);
+        }
+        Ok(())
+    }
+
+    // 構文木を評価
+    pub fn evaluate(&self, expr: &Expr) -> Result<Value, CalcError> {
+        self.eval_in(expr, &Scope { locals: None, depth: 0 })
+    }
+
+    // 変数を1つ束縛して評価する（solve などで未知数に値を入れて計算する）
+    pub fn evaluate_with(&self, expr: &Expr, name: &str, value: Value) -> Result<Value, CalcError> {
+        let locals = HashMap::from([(name.to_string(), value)]);
+        self.eval_in(expr, &Scope { locals: Some(&locals), depth: 0 })
+    }
+
+    fn eval_in(&self, expr: &Expr, scope: &Scope) -> Result<Value, CalcError> {
+        self.step()?;
+        match expr {
+            Expr::Number(text) => Number::parse(text, self.number_mode).map(Value::Number),
+            Expr::Variable(name) => scope
+                .locals
+                .and_then(|locals| locals.get(name))
+                .or_else(|| self.variables.get(name))
+                .cloned()
+                .or_else(|| self.constant(name).map(Value::Number))
+                .or_else(|| self.angle_unit(name))
+                .or_else(|| self.unit(name))
+                .ok_or_else(|| CalcError::UnknownVariable(name.clone())),
+            Expr::List(items) => {
+                let items = items
+                    .iter()
+                    .map(|item| self.eval_in(item, scope))
+                    .collect::<Result<Vec<Value>, CalcError>>()?;
+                Value::from_list(items)
+            }
+            Expr::Call { name, args } if name == "if" => self.conditional(args, scope),
+            Expr::Call { name, args } if name == "sum" || name == "prod" => self.series(name, args, scope),
+            Expr::Call { name, args } => {
+                let args = args
+                    .iter()
+                    .map(|arg| self.eval_in(arg, scope))
+                    .collect::<Result<Vec<Value>, CalcError>>()?;
+                self.call_in(name, &args, scope.depth)
+            }
+            Expr::Unary { op, operand } => {
+                let value = self.eval_in(operand, scope)?;
+                self.unary(*op, value)
+            }
+            Expr::Binary { op, left, right } => {
+                let left = self.eval_in(left, scope)?;
+                let right = self.eval_in(right, scope)?;
+                Value::binary(*op, &left, &right, self.digits)
+            }
+        }
+    }
+
+    // if(c, a, b) と c ? a : b
+    // c が 0 でなければ a、0 なら b を評価する（選ばれなかった側は評価しないので再帰の終了条件に使える）
+    fn conditional(&self, args: &[Expr], scope: &Scope) -> Result<Value, CalcError> {
+        let [condition, then, otherwise] = args else {
+            return Err(CalcError::ArgumentCount { name: "if".to_string(), expected: "3".to_string(), found: args.len() });
+        };
+        let condition = self.eval_in(condition, scope)?.into_number()?;
+        self.eval_in(if condition.is_zero() { otherwise } else { then }, scope)
+    }
+
+    // sum(i, from, to, expr) と prod(i, from, to, expr)
+    // i を from から to までの整数に束縛して expr を評価し、和または積をとる
+    fn series(&self, name: &str, args: &[Expr], scope: &Scope) -> Result<Value, CalcError> {
+        let (variable, from, to, body) = match args {
+            [Expr::Variable(variable), from, to, body] => (variable, from, to, body),
+            [_, _, _, _] => return Err(CalcError::InvalidExpression(format!("the first argument of {} must be a variable name", name))),
+            _ => return Err(CalcError::ArgumentCount { name: name.to_string(), expected: "4".to_string(), found: args.len() }),
+        };
+        let bound = |expr: &Expr| -> Result<i64, CalcError> {
+            let value = self.eval_in(expr, scope)?.into_number()?;
+            integer_argument(&value)
+                .ok_or_else(|| CalcError::InvalidExpression(format!("{} bounds must be integers, got {}", name, self.format_number(&value))))
+        };
+        let (from, to) = (bound(from)?, bound(to)?);
+        if to.saturating_sub(from) >= MAX_SERIES_TERMS {
+            return Err(CalcError::InvalidExpression(format!("{} has too many terms (the limit is {})", name, MAX_SERIES_TERMS)));
+        }
+        let (op, identity) = if name == "sum" { (BinaryOp::Add, "0") } else { (BinaryOp::Mul, "1") };
+        // 外側の引数を引き継ぎ、同じ名前があればループ変数で隠す
+        let mut locals = scope.locals.cloned().unwrap_or_default();
+        let mut total = Value::Number(Number::parse(identity, self.number_mode)?);
+        for i in from..=to {
+            locals.insert(variable.clone(), Value::Number(Number::parse(&i.to_string(), self.number_mode)?));
+            let term = self.eval_in(body, &Scope { locals: Some(&locals), depth: scope.depth })?;
+            total = Value::binary(op, &total, &term, self.digits)?;
+        }
+        Ok(total)
+    }
+
+    // 評価済みの引数で関数を呼ぶ（ユーザー定義関数を優先）
+    pub fn call(&self, name: &str, args: &[Value]) -> Result<Value, CalcError> {
+        self.call_in(name, args, 0)
+    }
+
+    fn call_in(&self, name: &str, args: &[Value], depth: usize) -> Result<Value, CalcError> {
+        match self.functions.get(name) {
+            Some(function) => self.call_user_function(name, function, args, depth),
+            None => self.call_builtin(name, args),
+        }
+    }
+
+    pub fn unary(&self, op: UnaryOp, value: Value) -> Result<Value, CalcError> {
+        match op {
+            UnaryOp::Neg => Ok(value.neg()),
+            UnaryOp::Plus => Ok(value),
+            UnaryOp::Not => value.not(),
+            UnaryOp::Factorial => combinatorics::factorial_of(&value.into_number()?).map(Value::Number),
+            UnaryOp::Percent => {
+                let hundred = Value::Number(Number::parse("100", self.number_mode)?);
+                Value::binary(BinaryOp::Div, &value, &hundred, self.digits)
+            }
+        }
+    }
+
+    // 10進数モードでは定数も高精度の値を使う（分数・整数では表せないのでそれらのモードでは f64）
+    fn constant(&self, name: &str) -> Option<Number> {
+        let constant = constants::find(name)?;
+        match self.number_mode {
+            NumberMode::Float | NumberMode::Rational | NumberMode::Integer => Some(Number::Float(constant.value)),
+            NumberMode::Decimal => Number::parse(constant.decimal, self.number_mode)
+                .ok()
+                .map(|value| value.rounded(self.digits)),
+        }
+    }
+
+    // 角度の単位は今の角度モードでの大きさになる（ラジアンモードで deg は pi / 180、sin(0.25 turn) は sin(pi / 2)）
+    fn angle_unit(&self, name: &str) -> Option<Value> {
+        let unit = AngleMode::from_unit_name(name)?;
+        if self.number_mode != NumberMode::Decimal {
+            return Some(Value::Number(Number::Float(self.angle_mode.full_turn() / unit.full_turn())));
+        }
+        // 10進数モードでは tau を桁数どおりに使う
+        let full_turn = |mode: AngleMode| match mode {
+            AngleMode::Radians => self.constant("tau").map(Value::Number),
+            other => Number::parse(&other.full_turn().to_string(), self.number_mode).ok().map(Value::Number),
+        };
+        Value::binary(BinaryOp::Div, &full_turn(self.angle_mode)?, &full_turn(unit)?, self.digits).ok()
+    }
+
+    // 単位名は大きさ 1 の量になる（60 km は 60 * km）
+    fn unit(&self, name: &str) -> Option<Value> {
+        if !self.units {
+            return None;
+        }
+        units::lookup(name).map(|def| Value::Quantity(Quantity { value: 1.0, unit: Unit::single(def) }))
+    }
+
+    // 組み込み関数は線形代数と、正確に計算できるものを除き f64 で計算する
+    fn call_builtin(&self, name: &str, args: &[Value]) -> Result<Value, CalcError> {
+        if let Some(result) = value::call_linear(name, args, self.digits) {
+            return result;
+        }
+        if args.iter().any(|arg| matches!(arg, Value::Uncertain(_))) {
+            let args = args
+                .iter()
+                .map(|arg| match arg {
+                    Value::Uncertain(uncertain) => Ok(*uncertain),
+                    other => other.clone().into_number().map(|number| Uncertain::exact(number.to_f64())),
+                })
+                .collect::<Result<Vec<Uncertain>, CalcError>>()?;
+            return Uncertain::call(name, &args, self.angle_mode).map(Value::Uncertain);
+        }
+        let args = args
+            .iter()
+            .cloned()
+            .map(Value::into_number)
+            .collect::<Result<Vec<Number>, CalcError>>()?;
+        if let Some(result) = self.call_random(name, &args) {
+            return result.map(Value::Number);
+        }
+        if let Some(result) = combinatorics::call(name, &args) {
+            return result.map(Value::Number);
+        }
+        if name == "round" {
+            return self.round(&args).map(Value::Number);
+        }
+        if let Some(result) = number::call_exact(name, &args, self.digits) {
+            return result.map(Value::Number);
+        }
+        let args: Vec<f64> = args.iter().map(Number::to_f64).collect();
+        functions::call(name, &args, self.angle_mode)
+            .map(|value| Value::Number(Number::from_f64(value, self.number_mode)))
+    }
+
+    // round(x) と round(x, places) は表示と同じ丸め方を使う
+    fn round(&self, args: &[Number]) -> Result<Number, CalcError> {
+        let (x, places) = match args {
+            [x] => (x, 0),
+            [x, places] => match integer_argument(places) {
+                Some(places) if places.abs() <= MAX_ROUND_PLACES => (x, places),
+                _ => {
+                    return Err(CalcError::InvalidExpression(format!(
+                        "round expects an integer number of places from -{0} to {0}", MAX_ROUND_PLACES
+                    )));
+                }
+            },
+            _ => return Err(CalcError::ArgumentCount { name: "round".to_string(), expected: "1 or 2".to_string(), found: args.len() }),
+        };
+        Ok(x.round_places(places, self.number_format.rounding.mode()))
+    }
+
+    // rand() は [0, 1) の実数、randint(a, b) は a 以上 b 以下の整数
+    fn call_random(&self, name: &str, args: &[Number]) -> Option<Result<Number, CalcError>> {
+        let mut random = self.random.get();
+        let result = match (name, args) {
+            ("rand", []) => Ok(Number::from_f64(random.uniform(), self.number_mode)),
+            ("randint", [a, b]) => match (integer_argument(a), integer_argument(b)) {
+                (Some(a), Some(b)) => random
+                    .int(a, b)
+                    .and_then(|value| Number::parse(&value.to_string(), self.number_mode)),
+                _ => Err(CalcError::InvalidExpression("randint expects two integers".to_string())),
+            },
+            ("rand", _) => Err(CalcError::ArgumentCount { name: name.to_string(), expected: "0".to_string(), found: args.len() }),
+            ("randint", _) => Err(CalcError::ArgumentCount { name: name.to_string(), expected: "2".to_string(), found: args.len() }),
+            _ => return None,
+        };
+        self.random.set(random);
+        Some(result)
+    }
+
+    fn call_user_function(
+        &self,
+        name: &str,
+        function: &UserFunction,
+        args: &[Value],
+        depth: usize,
+    ) -> Result<Value, CalcError> {
+        if args.len() != function.params.len() {
+            return Err(CalcError::ArgumentCount {
+                name: name.to_string(),
+                expected: function.params.len().to_string(),
+                found: args.len(),
+            });
+        }
+        if depth >= MAX_CALL_DEPTH {
+            return Err(CalcError::RecursionLimit(MAX_CALL_DEPTH));
+        }
+
+        let locals: HashMap<String, Value> =
+            function.params.iter().cloned().zip(args.iter().cloned()).collect();
+        self.eval_in(&function.body, &Scope { locals: Some(&locals), depth: depth + 1 })
+    }
+}
+
+// sum(i, 1, 10, i^2) と prod の形なら束縛する変数の名前
+pub fn loop_variable<'a>(name: &str, args: &'a [Expr]) -> Option<&'a str> {
+    match (name, args) {
+        ("sum" | "prod", [Expr::Variable(variable), _, _, _]) => Some(variable),
+        _ => None,
+    }
+}
+
+fn integer_argument(number: &Number) -> Option<i64> {
+    number.to_bigint().and_then(|value| i64::try_from(&value).ok())
+}
+
+// 定数と組み込み関数の名前は上書きできない
+fn check_definable(name: &str) -> Result<(), CalcError> {
+    if constants::lookup(name).is_some() {
+        return Err(CalcError::InvalidExpression(format!("Cannot redefine constant '{}'", name)));
+    }
+    if functions::is_builtin(name) {
+        return Err(CalcError::InvalidExpression(format!("Cannot redefine built-in function '{}'", name)));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format::Rounding;
+
+    fn number(evaluator: &Evaluator, input: &str) -> f64 {
+        evaluator.evaluate_str(input).unwrap().into_number().unwrap().to_f64()
+    }
+
+    #[test]
+    fn test_assignment() {
+        let mut evaluator = Evaluator::default();
+        assert_eq!(
+            evaluator.execute("r = 3").unwrap(),
+            Outcome::Assigned { name: "r".to_string(), value: Value::Number(Number::Float(3.0)) }
+        );
+        assert_eq!(evaluator.execute("r * 2").unwrap(), Outcome::Value(Value::Number(Number::Float(6.0))));
+        assert!(evaluator.execute("pi = 3").is_err());
+    }
+
+    #[test]
+    fn test_define_variable() {
+        let mut evaluator = Evaluator::default();
+        let three = Value::Number(Number::Float(3.0));
+        evaluator.define_variable("r_2", three.clone()).unwrap();
+        assert_eq!(number(&evaluator, "r_2 * 2"), 6.0);
+        // 名前に式や文を書いても実行されない
+        for name in ["", "a+b", "x; f(y) = y", "2x", "pi", "sin"] {
+            assert!(evaluator.define_variable(name, three.clone()).is_err(), "{}", name);
+        }
+        assert!(evaluator.functions().is_empty());
+    }
+
+    #[test]
+    fn test_user_functions() {
+        let mut evaluator = Evaluator::default();
+        evaluator.execute("f(x) = x^2 + 1").unwrap();
+        evaluator.execute("hyp(a, b) = sqrt(a^2 + b^2)").unwrap();
+        evaluator.execute("g(x) = f(x) * 2").unwrap();
+
+        assert_eq!(number(&evaluator, "f(3)"), 10.0);
+        assert_eq!(number(&evaluator, "hyp(3, 4)"), 5.0);
+        assert_eq!(number(&evaluator, "g(2)"), 10.0);
+        assert!(matches!(evaluator.evaluate_str("f(1, 2)"), Err(CalcError::ArgumentCount { .. })));
+    }
+
+    #[test]
+    fn test_parameters_shadow_globals() {
+        let mut evaluator = Evaluator::default();
+        evaluator.execute("x = 100").unwrap();
+        evaluator.execute("f(x) = x + 1").unwrap();
+        assert_eq!(number(&evaluator, "f(1)"), 2.0);
+        assert_eq!(number(&evaluator, "x"), 100.0);
+    }
+
+    #[test]
+    fn test_invalid_definitions() {
+        let mut evaluator = Evaluator::default();
+        assert!(evaluator.execute("sin(x) = x").is_err());
+        assert!(evaluator.execute("f(x, x) = x").is_err());
+    }
+
+    #[test]
+    fn test_decimal_mode() {
+        let mut evaluator = Evaluator::with_number_mode(AngleMode::Radians, NumberMode::Decimal, 30);
+        assert_eq!(evaluator.evaluate_str("0.1 + 0.2").unwrap().to_string(), "0.3");
+        assert_eq!(evaluator.evaluate_str("1 / 3").unwrap().to_string(), "0.333333333333333333333333333333");
+        assert_eq!(evaluator.evaluate_str("round(2.675 * 100) / 100").unwrap().to_string(), "2.68");
+        assert_eq!(evaluator.evaluate_str("pi").unwrap().to_string(), "3.14159265358979323846264338328");
+
+        evaluator.execute("price = 19.99").unwrap();
+        evaluator.execute("total(n) = price * n").unwrap();
+        assert_eq!(evaluator.evaluate_str("total(3)").unwrap().to_string(), "59.97");
+    }
+
+    #[test]
+    fn test_rational_mode() {
+        let mut evaluator = Evaluator::with_number_mode(AngleMode::Radians, NumberMode::Rational, 10);
+        assert_eq!(evaluator.evaluate_str("1/3 + 1/6").unwrap().to_string(), "1/2");
+        assert_eq!(evaluator.evaluate_str("(2/3)^2 * 9").unwrap().to_string(), "4");
+        assert_eq!(evaluator.evaluate_str("to_decimal(2/3)").unwrap().to_string(), "0.6666666667");
+        assert!(matches!(evaluator.evaluate_str("sqrt(2) * 1/2").unwrap(), Value::Number(Number::Float(_))));
+
+        evaluator.execute("half(x) = x / 2").unwrap();
+        assert_eq!(evaluator.evaluate_str("half(1/3)").unwrap().to_string(), "1/6");
+    }
+
+    #[test]
+    fn test_set_number_mode() {
+        let mut evaluator = Evaluator::default();
+        evaluator.execute("x = 0.25").unwrap();
+        evaluator.set_last_result(evaluator.evaluate_str("[0.5, 2]").unwrap());
+        evaluator.set_number_mode(NumberMode::Rational);
+        assert_eq!(evaluator.evaluate_str("x + 1/3").unwrap().to_string(), "7/12");
+        assert_eq!(evaluator.evaluate_str("ans").unwrap().to_string(), "[1/2, 2]");
+        evaluator.set_number_mode(NumberMode::Integer);
+        assert_eq!(evaluator.evaluate_str("ans").unwrap().to_string(), "[0.5, 2]");
+        evaluator.set_number_mode(NumberMode::Decimal);
+        assert_eq!(evaluator.evaluate_str("x + 0.05").unwrap().to_string(), "0.3");
+    }
+
+    #[test]
+    fn test_integer_mode() {
+        let evaluator = Evaluator::with_number_mode(AngleMode::Radians, NumberMode::Integer, DEFAULT_DIGITS);
+        assert_eq!(
+            evaluator.evaluate_str("2^100 + 1").unwrap().to_string(),
+            "1267650600228229401496703205377"
+        );
+        assert_eq!(evaluator.evaluate_str("(10^30 - 1) / 9").unwrap().to_string(), "111111111111111111111111111111");
+        assert_eq!(evaluator.evaluate_str("min(2^70, 3^40)").unwrap().to_string(), "12157665459056928801");
+        assert!(matches!(evaluator.evaluate_str("2^70 * 0.5").unwrap(), Value::Number(Number::Float(_))));
+    }
+
+    #[test]
+    fn test_matrices() {
+        let mut evaluator = Evaluator::with_number_mode(AngleMode::Radians, NumberMode::Rational, DEFAULT_DIGITS);
+        evaluator.execute("a = [[2, 1], [1, 3]]").unwrap();
+        evaluator.execute("f(m) = inverse(m) * m").unwrap();
+        assert_eq!(evaluator.evaluate_str("a * [1, 2]").unwrap().to_string(), "[4, 7]");
+        assert_eq!(evaluator.evaluate_str("inverse(a)").unwrap().to_string(), "[[3/5, -1/5], [-1/5, 2/5]]");
+        assert_eq!(evaluator.evaluate_str("f(a)").unwrap().to_string(), "[[1, 0], [0, 1]]");
+        assert_eq!(evaluator.evaluate_str("det(a^2) - det(a)^2").unwrap().to_string(), "0");
+        assert_eq!(evaluator.evaluate_str("cross([1, 0, 0], [0, 1, 0])").unwrap().to_string(), "[0, 0, 1]");
+        assert!(matches!(evaluator.evaluate_str("a + [1, 2]"), Err(CalcError::InvalidExpression(_))));
+        assert!(matches!(evaluator.evaluate_str("[[1, 2], [3]]"), Err(CalcError::DimensionMismatch(_))));
+        assert!(evaluator.evaluate_str("sin(a)").is_err());
+    }
+
+    #[test]
+    fn test_units() {
+        let mut evaluator = Evaluator { units: true, ..Evaluator::default() };
+        assert_eq!(evaluator.evaluate_str("60 km / 1.5 h").unwrap().to_string(), "40 km/h");
+        assert_eq!(evaluator.evaluate_str("2 m * 3 m").unwrap().to_string(), "6 m^2");
+        assert_eq!(evaluator.evaluate_str("1 km + 500 m").unwrap().to_string(), "1.5 km");
+        assert_eq!(evaluator.evaluate_str("2 km / 500 m").unwrap().to_string(), "4");
+        assert!(matches!(evaluator.evaluate_str("1 km + 1 kg"), Err(CalcError::DimensionMismatch(_))));
+
+        // 変数は単位より優先する
+        evaluator.execute("m = 3").unwrap();
+        assert_eq!(evaluator.evaluate_str("2 m").unwrap().to_string(), "6");
+        assert!(matches!(Evaluator::default().evaluate_str("5 km"), Err(CalcError::UnknownVariable(_))));
+    }
+
+    #[test]
+    fn test_angle_units() {
+        let close = |evaluator: &Evaluator, input: &str, expected: f64| {
+            let value = number(evaluator, input);
+            assert!((value - expected).abs() < 1e-12, "{} = {}", input, value);
+        };
+        let mut evaluator = Evaluator::default();
+        close(&evaluator, "sin(0.25 turn)", 1.0);
+        close(&evaluator, "cos(200 grad)", -1.0);
+        close(&evaluator, "tan(45deg)", 1.0);
+        close(&evaluator, "asin(1) / deg", 90.0);
+        evaluator.angle_mode = AngleMode::Turns;
+        close(&evaluator, "sin(0.25)", 1.0);
+        close(&evaluator, "sin(90 deg)", 1.0);
+        close(&evaluator, "acos(0)", 0.25);
+        evaluator.angle_mode = AngleMode::Gradians;
+        close(&evaluator, "sin((pi / 2) rad)", 1.0);
+        close(&evaluator, "atan2(1, 1)", 50.0);
+        // 変数が優先される
+        evaluator.execute("deg = 3").unwrap();
+        assert_eq!(number(&evaluator, "deg"), 3.0);
+
+        let decimal = Evaluator::with_number_mode(AngleMode::Radians, NumberMode::Decimal, 30);
+        assert_eq!(decimal.evaluate_str("180 deg").unwrap().to_string(), "3.14159265358979323846264338328");
+        assert_eq!(decimal.evaluate_str("turn / grad").unwrap().to_string(), "400");
+    }
+
+    #[test]
+    fn test_base_literals() {
+        let mut evaluator = Evaluator::default();
+        assert_eq!(number(&evaluator, "0xFF + 0b1010 - 0o10"), 257.0);
+        evaluator.output_radix = Radix::Hex;
+        assert_eq!(evaluator.format(&evaluator.evaluate_str("0xF0 + 15").unwrap()), "0xff");
+        assert_eq!(evaluator.format(&evaluator.evaluate_str("1 / 2").unwrap()), "0.5");
+
+        let integer = Evaluator::with_number_mode(AngleMode::Radians, NumberMode::Integer, DEFAULT_DIGITS);
+        assert_eq!(integer.evaluate_str("0xFFFF_FFFF_FFFF_FFFF_FF + 1").unwrap().to_string(), "4722366482869645213696");
+    }
+
+    #[test]
+    fn test_number_format() {
+        let mut evaluator = Evaluator { units: true, ..Evaluator::default() };
+        evaluator.number_format = NumberFormat { places: Some(2), separators: true, ..NumberFormat::default() };
+        assert_eq!(evaluator.format(&evaluator.evaluate_str("1000 / 3").unwrap()), "333.33");
+        assert_eq!(evaluator.format(&evaluator.evaluate_str("[1, 2500] * 2").unwrap()), "[2.00, 5,000.00]");
+        assert_eq!(evaluator.format(&evaluator.evaluate_str("[[1, 2], [3, 4]]").unwrap()), "[[1.00, 2.00], [3.00, 4.00]]");
+        assert_eq!(evaluator.format(&evaluator.evaluate_str("1234.5 m").unwrap()), "1,234.50 m");
+        evaluator.output_radix = Radix::Hex;
+        assert_eq!(evaluator.format(&evaluator.evaluate_str("255").unwrap()), "0xff");
+    }
+
+    #[test]
+    fn test_round() {
+        let mut evaluator = Evaluator::default();
+        assert_eq!(evaluator.evaluate_str("round(2.675, 2)").unwrap().to_string(), "2.68");
+        assert_eq!(evaluator.evaluate_str("round(1250, -2)").unwrap().to_string(), "1300");
+        evaluator.number_format.rounding = Rounding::HalfEven;
+        assert_eq!(evaluator.evaluate_str("[round(2.5), round(3.5), round(0.125, 2)]").unwrap().to_string(), "[2, 4, 0.12]");
+        evaluator.number_format.rounding = Rounding::Truncate;
+        assert_eq!(evaluator.evaluate_str("round(-2.99, 1)").unwrap().to_string(), "-2.9");
+        assert!(evaluator.evaluate_str("round(1, 0.5)").is_err());
+        assert!(evaluator.evaluate_str("round(1, 2, 3)").is_err());
+    }
+
+    #[test]
+    fn test_bitwise() {
+        let mut evaluator = Evaluator::with_number_mode(AngleMode::Radians, NumberMode::Integer, DEFAULT_DIGITS);
+        assert_eq!(evaluator.evaluate_str("0xF0 | 0b1010 & ~0").unwrap().to_string(), "250");
+        assert_eq!(evaluator.evaluate_str("1 << 4 + 1").unwrap().to_string(), "32");
+        assert_eq!(evaluator.evaluate_str("2 ^ 10").unwrap().to_string(), "1024");
+        assert!(evaluator.evaluate_str("1.5 & 1").is_err());
+
+        evaluator.syntax = Syntax { caret_xor: true, ..Syntax::default() };
+        assert_eq!(evaluator.evaluate_str("0xFF ^ 0x0F").unwrap().to_string(), "240");
+        assert_eq!(evaluator.evaluate_str("2 ** 10").unwrap().to_string(), "1024");
+    }
+
+    #[test]
+    fn test_comparison() {
+        let evaluator = Evaluator::default();
+        assert_eq!(number(&evaluator, "2 > 1"), 1.0);
+        assert_eq!(number(&evaluator, "2 >= 3"), 0.0);
+        assert_eq!(number(&evaluator, "(1 < 2) + (2 != 2)"), 1.0);
+        assert_eq!(number(&evaluator, "0.1 + 0.2 == 0.3"), 0.0);
+        let decimal = Evaluator::with_number_mode(AngleMode::Radians, NumberMode::Decimal, DEFAULT_DIGITS);
+        assert_eq!(number(&decimal, "0.1 + 0.2 == 0.3"), 1.0);
+        assert!(evaluator.evaluate_str("[1, 2] == [1, 2]").is_err());
+    }
+
+    #[test]
+    fn test_eval_str_is_deterministic() {
+        let evaluator = Evaluator::default();
+        let first = number(&evaluator, "rand() + randint(1, 100)");
+        let value = |input: &str| evaluator.eval_str(input).unwrap().into_number().unwrap().to_f64();
+        assert_eq!(value("rand() + randint(1, 100)"), value("rand() + randint(1, 100)"));
+        // 評価器の乱数は eval_str の影響を受けない
+        assert_ne!(number(&evaluator, "rand() + randint(1, 100)"), first);
+        assert!(evaluator.eval_str("1 +").is_err());
+    }
+
+    #[test]
+    fn test_random_functions() {
+        let mut evaluator = Evaluator::default();
+        evaluator.seed(1);
+        let first = number(&evaluator, "rand() + randint(1, 6)");
+        evaluator.seed(1);
+        assert_eq!(number(&evaluator, "rand() + randint(1, 6)"), first);
+        assert_ne!(number(&evaluator, "rand()"), number(&evaluator, "rand()"));
+        for _ in 0..100 {
+            let x = number(&evaluator, "randint(-3, 3)");
+            assert!((-3.0..=3.0).contains(&x) && x.fract() == 0.0);
+        }
+        assert!(evaluator.evaluate_str("randint(1.5, 3)").is_err());
+        assert!(evaluator.evaluate_str("rand(1)").is_err());
+    }
+
+    #[test]
+    fn test_percent() {
+        let evaluator = Evaluator::default();
+        assert_eq!(number(&evaluator, "200 * 15%"), 30.0);
+        assert_eq!(number(&evaluator, "17 % 5"), 2.0);
+        let exact = Evaluator::with_number_mode(AngleMode::Radians, NumberMode::Rational, DEFAULT_DIGITS);
+        assert_eq!(exact.evaluate_str("1 / 3 * 50%").unwrap().to_string(), "1/6");
+    }
+
+    #[test]
+    fn test_factorial_and_combinatorics() {
+        let evaluator = Evaluator::default();
+        assert_eq!(number(&evaluator, "5! + 3!"), 126.0);
+        assert_eq!(number(&evaluator, "-3!"), -6.0);
+        assert_eq!(number(&evaluator, "2^3!"), 64.0);
+        assert_eq!(number(&evaluator, "ncr(5, 2) * npr(4, 2)"), 120.0);
+        assert_eq!(
+            evaluator.evaluate_str("50!").unwrap().to_string(),
+            "30414093201713378043612608166064768844377641568960512000000000000"
+        );
+        assert!((number(&evaluator, "gamma(0.5)^2") - std::f64::consts::PI).abs() < 1e-12);
+        assert!(evaluator.evaluate_str("(-1)!").is_err());
+        assert!(evaluator.evaluate_str("gamma(0)").is_err());
+    }
+
+    #[test]
+    fn test_recursion_limit() {
+        let mut evaluator = Evaluator::default();
+        evaluator.execute("loop(n) = loop(n + 1)").unwrap();
+        assert!(matches!(evaluator.evaluate_str("loop(0)"), Err(CalcError::RecursionLimit(_))));
+    }
+
+    #[test]
+    fn test_step_limit() {
+        let mut evaluator = Evaluator::default();
+        evaluator.execute("f(n) = if(n < 1, 0, f(n - 1) + f(n - 1))").unwrap();
+        evaluator.limit_steps(1000);
+        assert_eq!(number(&evaluator, "f(3)"), 0.0);
+        // 深さは 40 段でも呼び出しは 2^40 回になる
+        assert!(matches!(evaluator.evaluate_str("f(40)"), Err(CalcError::StepLimit(1000))));
+        assert!(matches!(evaluator.evaluate_str("sum(i, 1, 1e6, i)"), Err(CalcError::StepLimit(_))));
+        // 上限を設け直すと数え直す
+        evaluator.limit_steps(1000);
+        assert_eq!(number(&evaluator, "f(3)"), 0.0);
+        assert!(Evaluator::default().evaluate_str("sum(i, 1, 1e4, i)").is_ok());
+    }
+
+    #[test]
+    fn test_uncertainty() {
+        let evaluator = Evaluator::default();
+        let format = |input: &str| evaluator.format(&evaluator.evaluate_str(input).unwrap());
+        assert_eq!(format("5.0±0.1 * 3.2±0.05"), "16.00 ± 0.41");
+        assert_eq!(format("(2 +/- 0.1)^2 / 2"), "2.00 ± 0.20");
+        assert_eq!(format("sqrt(16 ± 0.8)"), "4.00 ± 0.10");
+        assert_eq!(format("-(1 ± 0.5)"), "-1.00 ± 0.50");
+        assert!(evaluator.evaluate_str("(1 ± 0.1) ± 0.1").is_err());
+        assert!(evaluator.evaluate_str("(1 ± 0.1) < 2").is_err());
+    }
+
+    #[test]
+    fn test_conditional() {
+        let mut evaluator = Evaluator::default();
+        evaluator.execute("f(n) = n <= 1 ? 1 : n * f(n - 1)").unwrap();
+        assert_eq!(number(&evaluator, "f(10)"), 3628800.0);
+        evaluator.execute("sign(x) = if(x > 0, 1, if(x < 0, -1, 0))").unwrap();
+        assert_eq!(number(&evaluator, "sign(-3) + sign(0) * 10 + sign(2) * 100"), 99.0);
+        // 選ばれなかった側のエラーは起きない
+        assert_eq!(number(&evaluator, "1 ? 2 : 1 / 0"), 2.0);
+        assert_eq!(number(&evaluator, "if(0, unknown, 5)"), 5.0);
+        assert!(matches!(evaluator.evaluate_str("if(1, 2)"), Err(CalcError::ArgumentCount { .. })));
+        assert!(matches!(evaluator.evaluate_str("[1, 2] ? 1 : 0"), Err(CalcError::DimensionMismatch(_) | CalcError::InvalidExpression(_))));
+    }
+
+    #[test]
+    fn test_sum_and_prod() {
+        let mut evaluator = Evaluator::default();
+        assert_eq!(number(&evaluator, "sum(i, 1, 100, i^2)"), 338350.0);
+        assert_eq!(number(&evaluator, "prod(k, 1, 10, k)"), 3628800.0);
+        assert_eq!(number(&evaluator, "sum(i, 1, 3, sum(j, 1, i, j))"), 10.0);
+        // 範囲が空なら 0 と 1
+        assert_eq!(number(&evaluator, "sum(i, 5, 1, i) + prod(i, 5, 1, i)"), 1.0);
+
+        // ループ変数は外側の変数や関数の引数を隠すが、外には残らない
+        evaluator.execute("i = 100").unwrap();
+        evaluator.execute("f(n) = sum(i, 1, n, i * n)").unwrap();
+        assert_eq!(number(&evaluator, "f(3) + i"), 118.0);
+
+        let rational = Evaluator::with_number_mode(AngleMode::Radians, NumberMode::Rational, DEFAULT_DIGITS);
+        let value = rational.evaluate_str("sum(k, 1, 4, 1/k)").unwrap();
+        assert_eq!(rational.format(&value), "25/12");
+
+        assert!(matches!(evaluator.evaluate_str("sum(i, 1, 2)"), Err(CalcError::ArgumentCount { .. })));
+        assert!(matches!(evaluator.evaluate_str("sum(2, 1, 2, i)"), Err(CalcError::InvalidExpression(_))));
+        assert!(matches!(evaluator.evaluate_str("sum(i, 1, 2.5, i)"), Err(CalcError::InvalidExpression(_))));
+        assert!(matches!(evaluator.evaluate_str("sum(i, 0, 1e12, i)"), Err(CalcError::InvalidExpression(_))));
+    }
+}
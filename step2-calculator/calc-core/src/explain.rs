@@ -0,0 +1,236 @@
+// 評価の過程の表示（--explain）
+//
+// 構文木を表示した後、値が揃った一番左の内側の演算を1つずつ計算して式を書き直す
+//   2 + 3 * 4
+//   = 2 + 12
+//   = 14
+use crate::evaluator::Evaluator;
+use crate::parser::{BinaryOp, Expr, UnaryOp};
+use crate::value::Value;
+use crate::CalcError;
+
+// 計算途中の式（計算済みの部分は値になっている）
+#[derive(Debug, Clone)]
+enum Node {
+    Value(Value),
+    Variable(String),
+    List(Vec<Node>),
+    Call { name: String, args: Vec<Node> },
+    // sum と prod はループ変数を含むので式のまま1段階で計算する
+    Series(Expr),
+    Unary { op: UnaryOp, operand: Box<Node> },
+    Binary { op: BinaryOp, left: Box<Node>, right: Box<Node> },
+}
+
+// 構文木を罫線付きの木で表す
+pub fn tree(expr: &Expr) -> String {
+    let mut lines = vec![label(expr)];
+    write_children(&mut lines, expr, "");
+    lines.join("\n")
+}
+
+// 最初の式から結果までの各段階の式
+pub fn steps(evaluator: &Evaluator, expr: &Expr) -> Result<Vec<String>, CalcError> {
+    let mut node = to_node(evaluator, expr)?;
+    let mut steps = vec![expr.to_string()];
+    while let Some(next) = reduce(evaluator, &node)? {
+        node = next;
+        let text = to_expr(evaluator, &node).to_string();
+        // 単項の + のように見た目が変わらない段階は省く
+        if steps.last() != Some(&text) {
+            steps.push(text);
+        }
+    }
+    Ok(steps)
+}
+
+fn label(expr: &Expr) -> String {
+    match expr {
+        Expr::Number(text) => text.clone(),
+        Expr::Variable(name) => name.clone(),
+        Expr::List(_) => "[ ]".to_string(),
+        Expr::Call { name, .. } => format!("{}()", name),
+        Expr::Unary { op, .. } => match op {
+            UnaryOp::Neg => "- (negate)".to_string(),
+            UnaryOp::Plus => "+ (unary plus)".to_string(),
+            UnaryOp::Not => "~".to_string(),
+            UnaryOp::Factorial => "! (factorial)".to_string(),
+            UnaryOp::Percent => "% (percent)".to_string(),
+        },
+        Expr::Binary { op, .. } => op.symbol().to_string(),
+    }
+}
+
+fn write_children(lines: &mut Vec<String>, expr: &Expr, indent: &str) {
+    let children: Vec<&Expr> = match expr {
+        Expr::Number(_) | Expr::Variable(_) => Vec::new(),
+        Expr::List(items) | Expr::Call { args: items, .. } => items.iter().collect(),
+        Expr::Unary { operand, .. } => vec![operand],
+        Expr::Binary { left, right, .. } => vec![left, right],
+    };
+    for (i, child) in children.iter().enumerate() {
+        let last = i + 1 == children.len();
+        let (branch, next_indent) = if last { ("└── ", "    ") } else { ("├── ", "│   ") };
+        lines.push(format!("{}{}{}", indent, branch, label(child)));
+        write_children(lines, child, &format!("{}{}", indent, next_indent));
+    }
+}
+
+// 数値リテラルは最初から値にしておく
+fn to_node(evaluator: &Evaluator, expr: &Expr) -> Result<Node, CalcError> {
+    let children = |items: &[Expr]| items.iter().map(|item| to_node(evaluator, item)).collect::<Result<Vec<Node>, CalcError>>();
+    Ok(match expr {
+        Expr::Number(_) => Node::Value(evaluator.evaluate(expr)?),
+        Expr::Variable(name) => Node::Variable(name.clone()),
+        Expr::List(items) => Node::List(children(items)?),
+        Expr::Call { name, .. } if name == "sum" || name == "prod" => Node::Series(expr.clone()),
+        Expr::Call { name, args } => Node::Call { name: name.clone(), args: children(args)? },
+        Expr::Unary { op, operand } => Node::Unary { op: *op, operand: Box::new(to_node(evaluator, operand)?) },
+        Expr::Binary { op, left, right } => Node::Binary {
+            op: *op,
+            left: Box::new(to_node(evaluator, left)?),
+            right: Box::new(to_node(evaluator, right)?),
+        },
+    })
+}
+
+// 1段階だけ計算する（全て値になっていれば None）
+fn reduce(evaluator: &Evaluator, node: &Node) -> Result<Option<Node>, CalcError> {
+    // 子のうち最初に計算できるものを計算する
+    let reduce_first = |nodes: &[Node]| -> Result<Option<Vec<Node>>, CalcError> {
+        for (i, child) in nodes.iter().enumerate() {
+            if let Some(reduced) = reduce(evaluator, child)? {
+                let mut nodes = nodes.to_vec();
+                nodes[i] = reduced;
+                return Ok(Some(nodes));
+            }
+        }
+        Ok(None)
+    };
+    let value = match node {
+        Node::Value(_) => return Ok(None),
+        Node::Variable(name) => evaluator.evaluate(&Expr::Variable(name.clone()))?,
+        Node::Series(expr) => evaluator.evaluate(expr)?,
+        Node::List(items) => match reduce_first(items)? {
+            Some(items) => return Ok(Some(Node::List(items))),
+            None => Value::from_list(values(items))?,
+        },
+        // 条件を計算したら選ばれた側だけを残す
+        Node::Call { name, args } if name == "if" && args.len() == 3 => {
+            if let Some(condition) = reduce(evaluator, &args[0])? {
+                let mut args = args.clone();
+                args[0] = condition;
+                return Ok(Some(Node::Call { name: name.clone(), args }));
+            }
+            let condition = value_of(&args[0]).clone().into_number()?;
+            return Ok(Some(args[if condition.is_zero() { 2 } else { 1 }].clone()));
+        }
+        Node::Call { name, args } => match reduce_first(args)? {
+            Some(args) => return Ok(Some(Node::Call { name: name.clone(), args })),
+            None => evaluator.call(name, &values(args))?,
+        },
+        Node::Unary { op, operand } => match reduce(evaluator, operand)? {
+            Some(operand) => return Ok(Some(Node::Unary { op: *op, operand: Box::new(operand) })),
+            None => evaluator.unary(*op, value_of(operand).clone())?,
+        },
+        Node::Binary { op, left, right } => {
+            if let Some(left) = reduce(evaluator, left)? {
+                return Ok(Some(Node::Binary { op: *op, left: Box::new(left), right: right.clone() }));
+            }
+            if let Some(right) = reduce(evaluator, right)? {
+                return Ok(Some(Node::Binary { op: *op, left: left.clone(), right: Box::new(right) }));
+            }
+            Value::binary(*op, value_of(left), value_of(right), evaluator.digits)?
+        }
+    };
+    Ok(Some(Node::Value(value)))
+}
+
+// 全て計算済みの子の値
+fn values(nodes: &[Node]) -> Vec<Value> {
+    nodes.iter().map(|node| value_of(node).clone()).collect()
+}
+
+// reduce が None を返したノードは値になっている
+fn value_of(node: &Node) -> &Value {
+    match node {
+        Node::Value(value) => value,
+        other => unreachable!("{:?} has not been reduced", other),
+    }
+}
+
+// 表示用に式へ戻す（負の数と分数は括弧の判断のため演算として表す）
+fn to_expr(evaluator: &Evaluator, node: &Node) -> Expr {
+    let nodes = |items: &[Node]| items.iter().map(|item| to_expr(evaluator, item)).collect();
+    match node {
+        Node::Value(value) => {
+            let text = evaluator.format(value);
+            match text.strip_prefix('-') {
+                Some(positive) => Expr::Unary { op: UnaryOp::Neg, operand: Box::new(number_expr(positive)) },
+                None => number_expr(&text),
+            }
+        }
+        Node::Variable(name) => Expr::Variable(name.clone()),
+        Node::List(items) => Expr::List(nodes(items)),
+        Node::Call { name, args } => Expr::Call { name: name.clone(), args: nodes(args) },
+        Node::Series(expr) => expr.clone(),
+        Node::Unary { op, operand } => Expr::Unary { op: *op, operand: Box::new(to_expr(evaluator, operand)) },
+        Node::Binary { op, left, right } => Expr::Binary {
+            op: *op,
+            left: Box::new(to_expr(evaluator, left)),
+            right: Box::new(to_expr(evaluator, right)),
+        },
+    }
+}
+
+// 分数 a/b は割り算として表す
+fn number_expr(text: &str) -> Expr {
+    let is_integer = |part: &str| !part.is_empty() && part.chars().all(|c| c.is_ascii_digit());
+    match text.split_once('/') {
+        Some((numerator, denominator)) if is_integer(numerator) && is_integer(denominator) => Expr::Binary {
+            op: BinaryOp::Div,
+            left: Box::new(Expr::Number(numerator.to_string())),
+            right: Box::new(Expr::Number(denominator.to_string())),
+        },
+        _ => Expr::Number(text.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::number::NumberMode;
+    use crate::parser::{self, Syntax};
+
+    fn explain(evaluator: &Evaluator, input: &str) -> Vec<String> {
+        steps(evaluator, &parser::parse(input, Syntax::default()).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_steps() {
+        let evaluator = Evaluator::default();
+        assert_eq!(explain(&evaluator, "2 + 3 * 4"), vec!["2 + 3 * 4", "2 + 12", "14"]);
+        assert_eq!(explain(&evaluator, "(1 + 2) * (3 - 5)"), vec!["(1 + 2) * (3 - 5)", "3 * (3 - 5)", "3 * -2", "-6"]);
+        assert_eq!(explain(&evaluator, "-2^2"), vec!["-2 ^ 2", "-4"]);
+        assert_eq!(explain(&evaluator, "max(1, 2 * 3) + pi"), vec!["max(1, 2 * 3) + pi", "max(1, 6) + pi", "6 + pi", "6 + 3.141592653589793", "9.141592653589793"]);
+        assert_eq!(explain(&evaluator, "5"), vec!["5"]);
+        assert_eq!(explain(&evaluator, "2 > 1 ? 3 * 4 : 1 / 0"), vec!["if(2 > 1, 3 * 4, 1 / 0)", "if(1, 3 * 4, 1 / 0)", "3 * 4", "12"]);
+        assert_eq!(explain(&evaluator, "1 + prod(k, 1, 5, k)"), vec!["1 + prod(k, 1, 5, k)", "1 + 120", "121"]);
+
+        let exact = Evaluator::with_number_mode(Default::default(), NumberMode::Rational, 50);
+        assert_eq!(explain(&exact, "(1/3)^2 * 3"), vec!["(1 / 3) ^ 2 * 3", "1 / 9 * 3", "1 / 3"]);
+    }
+
+    #[test]
+    fn test_errors() {
+        let evaluator = Evaluator::default();
+        let expr = parser::parse("1 + 2 / 0", Syntax::default()).unwrap();
+        assert!(matches!(steps(&evaluator, &expr), Err(CalcError::DivisionByZero)));
+    }
+
+    #[test]
+    fn test_tree() {
+        let expr = parser::parse("2 + 3 * sqrt(4)", Syntax::default()).unwrap();
+        assert_eq!(tree(&expr), "+\n├── 2\n└── *\n    ├── 3\n    └── sqrt()\n        └── 4");
+    }
+}
@@ -0,0 +1,366 @@
+// 結果の表示形式（小数点以下の桁数・有効数字・指数表記・桁区切り・小数点の記号）
+use bigdecimal::{BigDecimal, RoundingMode, Zero};
+
+use crate::number::{Number, DEFAULT_DIGITS};
+
+// 数値の表記
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+pub enum Notation {
+    /// Plain digits (1234.5)
+    #[default]
+    Fixed,
+    /// One digit before the point (1.2345e3)
+    Scientific,
+    /// Exponent is a multiple of 3 (12.345e3, 4.7e-6)
+    Engineering,
+    /// Like engineering with SI prefixes (12.345k, 4.7µ); without --units, expressions may use them too (4.7k, 100n)
+    Si,
+}
+
+// SI 接頭辞と 10 の指数（入力では µ の代わりに u と μ も使える）
+const SI_PREFIXES: [(char, i64); 11] = [
+    ('f', -15),
+    ('p', -12),
+    ('n', -9),
+    ('µ', -6),
+    ('m', -3),
+    ('k', 3),
+    ('M', 6),
+    ('G', 9),
+    ('T', 12),
+    ('P', 15),
+    ('u', -6),
+];
+
+// 数値の後ろの接頭辞の指数（4.7k の k なら 3）
+pub fn si_prefix_exponent(prefix: char) -> Option<i64> {
+    let prefix = if prefix == 'μ' { 'µ' } else { prefix };
+    SI_PREFIXES.iter().find(|(c, _)| *c == prefix).map(|(_, exponent)| *exponent)
+}
+
+fn si_prefix(exponent: i64) -> Option<char> {
+    SI_PREFIXES.iter().find(|(_, e)| *e == exponent).map(|(c, _)| *c)
+}
+
+// 端数の丸め方（表示と round(x, places) で使う）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+pub enum Rounding {
+    /// Halves away from zero (2.5 → 3, -2.5 → -3)
+    #[default]
+    HalfUp,
+    /// Halves to the even neighbour, as in banking (2.5 → 2, 3.5 → 4)
+    HalfEven,
+    /// Toward negative infinity
+    Floor,
+    /// Toward positive infinity
+    Ceil,
+    /// Toward zero (drop the extra digits)
+    Truncate,
+}
+
+impl Rounding {
+    pub fn mode(self) -> RoundingMode {
+        match self {
+            Rounding::HalfUp => RoundingMode::HalfUp,
+            Rounding::HalfEven => RoundingMode::HalfEven,
+            Rounding::Floor => RoundingMode::Floor,
+            Rounding::Ceil => RoundingMode::Ceiling,
+            Rounding::Truncate => RoundingMode::Down,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct NumberFormat {
+    // 小数点以下の桁数（指数表記では仮数部の桁数）
+    pub places: Option<u64>,
+    // 有効数字の桁数
+    pub significant: Option<u64>,
+    pub notation: Notation,
+    // 整数部を3桁ごとにカンマで区切る
+    pub separators: bool,
+    // 小数点をカンマ、桁区切りをピリオドにする（1.234.567,5）
+    pub decimal_comma: bool,
+    // 桁数を指定したときの丸め方
+    pub rounding: Rounding,
+}
+
+// 小数点にカンマを使う言語（ロケール名の先頭の言語コードで判定する）
+const DECIMAL_COMMA_LANGUAGES: &[&str] = &[
+    "bg", "ca", "cs", "da", "de", "el", "es", "et", "fi", "fr", "hr", "hu", "id", "is", "it", "lt", "lv", "nb", "nl",
+    "nn", "no", "pl", "pt", "ro", "ru", "sk", "sl", "sr", "sv", "tr", "uk", "vi",
+];
+
+// de_DE.UTF-8 や fr-FR などのロケールで小数点がカンマか（スイスは de_CH などでもピリオド）
+pub fn uses_decimal_comma(locale: &str) -> bool {
+    let locale = locale.split(['.', '@']).next().unwrap_or_default();
+    let mut parts = locale.split(['_', '-']);
+    let language = parts.next().unwrap_or_default().to_ascii_lowercase();
+    let region = parts.next().unwrap_or_default().to_ascii_uppercase();
+    DECIMAL_COMMA_LANGUAGES.contains(&language.as_str()) && region != "CH" && region != "LI"
+}
+
+impl NumberFormat {
+    pub fn format(&self, number: &Number) -> String {
+        let text = match self.decimal_value(number) {
+            Some(value) => self.render(value),
+            None => number.to_string(),
+        };
+        let text = if self.separators { group_digits(&text) } else { text };
+        if self.decimal_comma { swap_separators(&text) } else { text }
+    }
+
+    pub fn format_f64(&self, value: f64) -> String {
+        self.format(&Number::Float(value))
+    }
+
+    // 桁数も表記も指定されていなければ通常の表示のまま（分数は a/b のまま）
+    fn decimal_value(&self, number: &Number) -> Option<BigDecimal> {
+        if self.places.is_none() && self.significant.is_none() && self.notation == Notation::Fixed {
+            return None;
+        }
+        match number {
+            Number::Float(value) if !value.is_finite() => None,
+            _ => Some(number.to_bigdecimal(DEFAULT_DIGITS)),
+        }
+    }
+
+    fn render(&self, value: BigDecimal) -> String {
+        let value = match self.significant {
+            Some(significant) => value.with_scale_round(significant as i64 - 1 - leading_exponent(&value), self.rounding.mode()),
+            None => value,
+        };
+        let notation = self.notation_for(&value);
+        let mut shift = exponent_shift(notation, &value);
+        let value = match (self.places, self.significant) {
+            (Some(places), None) => {
+                // 丸めで 9.99 が 10.0 になったら指数を取り直す
+                let rounded = value.with_scale_round(places as i64 - shift, self.rounding.mode());
+                shift = exponent_shift(notation, &rounded);
+                rounded
+            }
+            _ => value,
+        };
+
+        let mantissa = scale_by_power_of_ten(&value, -shift);
+        let mantissa = match (self.places, self.significant) {
+            (Some(places), None) => mantissa.with_scale(places as i64),
+            (_, Some(significant)) => {
+                let scale = significant as i64 - 1 - leading_exponent(&mantissa);
+                mantissa.with_scale(scale.max(0))
+            }
+            (None, None) => mantissa,
+        };
+        let text = match (self.places, self.significant) {
+            (None, None) => Number::Decimal(mantissa).to_string(),
+            _ => mantissa.to_plain_string(),
+        };
+        match notation {
+            Notation::Fixed => text,
+            Notation::Si if shift == 0 => text,
+            // 接頭辞のない桁は指数で書く
+            Notation::Si => match si_prefix(shift) {
+                Some(prefix) => format!("{}{}", text, prefix),
+                None => format!("{}e{}", text, shift),
+            },
+            Notation::Scientific | Notation::Engineering => format!("{}e{}", text, shift),
+        }
+    }
+
+    // 有効数字を指定したときは、普通の表記で桁数が分からなくなる値を指数表記にする
+    // 12000 の末尾の0が有効か分からない（1.2e4）、0.0000123 は0が多すぎる（1.23e-5）
+    fn notation_for(&self, rounded: &BigDecimal) -> Notation {
+        match (self.notation, self.significant) {
+            (Notation::Fixed, Some(significant)) => {
+                let exponent = leading_exponent(rounded);
+                if exponent >= significant as i64 || exponent < -4 { Notation::Scientific } else { Notation::Fixed }
+            }
+            (notation, _) => notation,
+        }
+    }
+}
+
+// 仮数部にするために動かす桁数
+fn exponent_shift(notation: Notation, value: &BigDecimal) -> i64 {
+    match notation {
+        Notation::Fixed => 0,
+        Notation::Scientific => leading_exponent(value),
+        Notation::Engineering | Notation::Si => leading_exponent(value).div_euclid(3) * 3,
+    }
+}
+
+// 最上位の桁の位（1234.5 → 3、0.01 → -2、0 → 0）
+fn leading_exponent(value: &BigDecimal) -> i64 {
+    if value.is_zero() {
+        return 0;
+    }
+    let (_, scale) = value.as_bigint_and_exponent();
+    value.digits() as i64 - 1 - scale
+}
+
+// value × 10^power
+fn scale_by_power_of_ten(value: &BigDecimal, power: i64) -> BigDecimal {
+    let (digits, scale) = value.as_bigint_and_exponent();
+    BigDecimal::new(digits, scale - power)
+}
+
+// 整数部の数字を3桁ごとに区切る（小数部と指数はそのまま）
+fn group_digits(text: &str) -> String {
+    let mut result = String::with_capacity(text.len() + text.len() / 3);
+    let mut in_fraction = false;
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        if c.is_ascii_digit() {
+            let end = rest.find(|c: char| !c.is_ascii_digit()).unwrap_or(rest.len());
+            let (digits, tail) = rest.split_at(end);
+            if in_fraction {
+                result.push_str(digits);
+            } else {
+                for (i, digit) in digits.chars().enumerate() {
+                    if i > 0 && (digits.len() - i) % 3 == 0 {
+                        result.push(',');
+                    }
+                    result.push(digit);
+                }
+            }
+            rest = tail;
+            continue;
+        }
+        match c {
+            '.' | 'e' => in_fraction = true,
+            '-' => {}
+            _ => in_fraction = false,
+        }
+        result.push(c);
+        rest = &rest[c.len_utf8()..];
+    }
+    result
+}
+
+// 1,234.5 を 1.234,5 にする
+fn swap_separators(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            '.' => ',',
+            ',' => '.',
+            c => c,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::number::NumberMode;
+
+    fn format(format: NumberFormat, text: &str) -> String {
+        format.format(&Number::parse(text, NumberMode::Float).unwrap())
+    }
+
+    fn notation(notation: Notation) -> NumberFormat {
+        NumberFormat { notation, ..NumberFormat::default() }
+    }
+
+    #[test]
+    fn test_default_is_unchanged() {
+        let plain = NumberFormat::default();
+        assert_eq!(format(plain, "0.1"), "0.1");
+        assert_eq!(format(plain, "1234567.5"), "1234567.5");
+        let third = Number::parse("1", NumberMode::Rational).unwrap().div(&Number::parse("3", NumberMode::Rational).unwrap(), 10).unwrap();
+        assert_eq!(plain.format(&third), "1/3");
+        assert_eq!(plain.format(&Number::Float(f64::INFINITY)), "inf");
+    }
+
+    #[test]
+    fn test_places_and_significant() {
+        let places = |n| NumberFormat { places: Some(n), ..NumberFormat::default() };
+        let significant = |n| NumberFormat { significant: Some(n), ..NumberFormat::default() };
+        assert_eq!(format(places(2), "3.14159"), "3.14");
+        assert_eq!(format(places(2), "2.005"), "2.01");
+        assert_eq!(format(places(3), "42"), "42.000");
+        assert_eq!(format(places(0), "-2.5"), "-3");
+        assert_eq!(format(significant(3), "3.14159"), "3.14");
+        assert_eq!(format(significant(3), "0.00012345"), "0.000123");
+        assert_eq!(format(significant(3), "9.999"), "10.0");
+        assert_eq!(format(significant(5), "12345"), "12345");
+        // 末尾の0が有効数字か分からない値と、小さすぎる値は指数表記にする
+        assert_eq!(format(significant(2), "12345"), "1.2e4");
+        assert_eq!(format(significant(3), "999.7"), "1.00e3");
+        assert_eq!(format(significant(3), "0.000012345"), "1.23e-5");
+        assert_eq!(format(significant(2), "-0.000099"), "-9.9e-5");
+    }
+
+    #[test]
+    fn test_rounding() {
+        let places = |n, rounding| NumberFormat { places: Some(n), rounding, ..NumberFormat::default() };
+        assert_eq!(format(places(2, Rounding::HalfEven), "2.125"), "2.12");
+        assert_eq!(format(places(2, Rounding::HalfEven), "2.135"), "2.14");
+        assert_eq!(format(places(0, Rounding::Floor), "-2.1"), "-3");
+        assert_eq!(format(places(0, Rounding::Ceil), "2.1"), "3");
+        assert_eq!(format(places(1, Rounding::Truncate), "-2.99"), "-2.9");
+        let significant = NumberFormat { significant: Some(2), rounding: Rounding::Truncate, ..NumberFormat::default() };
+        assert_eq!(format(significant, "9.99"), "9.9");
+    }
+
+    #[test]
+    fn test_notation() {
+        assert_eq!(format(notation(Notation::Scientific), "1234.5"), "1.2345e3");
+        assert_eq!(format(notation(Notation::Scientific), "-0.00042"), "-4.2e-4");
+        assert_eq!(format(notation(Notation::Engineering), "12345"), "12.345e3");
+        assert_eq!(format(notation(Notation::Engineering), "0.0000047"), "4.7e-6");
+        assert_eq!(format(notation(Notation::Scientific), "0"), "0e0");
+
+        let scientific = NumberFormat { places: Some(2), notation: Notation::Scientific, ..NumberFormat::default() };
+        assert_eq!(format(scientific, "123456"), "1.23e5");
+        assert_eq!(format(scientific, "9.999"), "1.00e1");
+        let engineering = NumberFormat { significant: Some(3), notation: Notation::Engineering, ..NumberFormat::default() };
+        assert_eq!(format(engineering, "1234567"), "1.23e6");
+        assert_eq!(format(engineering, "999999"), "1.00e6");
+    }
+
+    #[test]
+    fn test_si_prefixes() {
+        assert_eq!(format(notation(Notation::Si), "12400"), "12.4k");
+        assert_eq!(format(notation(Notation::Si), "0.0000033"), "3.3µ");
+        assert_eq!(format(notation(Notation::Si), "-470"), "-470");
+        assert_eq!(format(notation(Notation::Si), "0"), "0");
+        assert_eq!(format(notation(Notation::Si), "2.2e-10"), "220p");
+        assert_eq!(format(notation(Notation::Si), "1e21"), "1e21");
+        let places = NumberFormat { places: Some(1), notation: Notation::Si, ..NumberFormat::default() };
+        assert_eq!(format(places, "999999"), "1.0M");
+
+        assert_eq!(si_prefix_exponent('k'), Some(3));
+        assert_eq!(si_prefix_exponent('u'), Some(-6));
+        assert_eq!(si_prefix_exponent('μ'), Some(-6));
+        assert_eq!(si_prefix_exponent('x'), None);
+    }
+
+    #[test]
+    fn test_separators() {
+        let separators = NumberFormat { separators: true, ..NumberFormat::default() };
+        assert_eq!(format(separators, "1234567.891"), "1,234,567.891");
+        assert_eq!(format(separators, "-1000"), "-1,000");
+        assert_eq!(format(separators, "999"), "999");
+        assert_eq!(group_digits("1.23456e1000"), "1.23456e1000");
+        assert_eq!(group_digits("[12345, 0.12345]"), "[12,345, 0.12345]");
+        assert_eq!(group_digits("12000/7"), "12,000/7");
+    }
+
+    #[test]
+    fn test_decimal_comma() {
+        let comma = NumberFormat { decimal_comma: true, ..NumberFormat::default() };
+        assert_eq!(format(comma, "3.25"), "3,25");
+        assert_eq!(format(NumberFormat { separators: true, ..comma }, "-1234567.5"), "-1.234.567,5");
+        assert_eq!(format(NumberFormat { notation: Notation::Scientific, ..comma }, "1500"), "1,5e3");
+
+        assert!(uses_decimal_comma("de_DE.UTF-8"));
+        assert!(uses_decimal_comma("fr-FR"));
+        assert!(uses_decimal_comma("pt_BR"));
+        assert!(!uses_decimal_comma("de_CH.UTF-8"));
+        assert!(!uses_decimal_comma("en_US.UTF-8"));
+        assert!(!uses_decimal_comma("C"));
+        assert!(!uses_decimal_comma(""));
+    }
+}
@@ -0,0 +1,381 @@
+// 組み込み関数
+use crate::arithmetic::square_root;
+use crate::combinatorics;
+use crate::CalcError;
+
+// 三角関数で使う角度の単位
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+pub enum AngleMode {
+    /// Radians
+    #[default]
+    #[cfg_attr(feature = "clap", value(name = "rad"))]
+    Radians,
+    /// Degrees
+    #[cfg_attr(feature = "clap", value(name = "deg"))]
+    Degrees,
+    /// Gradians (400 per turn)
+    #[cfg_attr(feature = "clap", value(name = "grad"))]
+    Gradians,
+    /// Full turns
+    #[cfg_attr(feature = "clap", value(name = "turn"))]
+    Turns,
+}
+
+// 式の中で使える角度の単位の名前（sin(0.25 turn)）
+pub const ANGLE_UNITS: &[(&str, AngleMode)] =
+    &[("rad", AngleMode::Radians), ("deg", AngleMode::Degrees), ("grad", AngleMode::Gradians), ("turn", AngleMode::Turns)];
+
+impl AngleMode {
+    pub fn from_unit_name(name: &str) -> Option<AngleMode> {
+        ANGLE_UNITS.iter().find(|(unit, _)| *unit == name).map(|&(_, mode)| mode)
+    }
+
+    // 1回転の大きさ
+    pub fn full_turn(self) -> f64 {
+        match self {
+            AngleMode::Radians => std::f64::consts::TAU,
+            AngleMode::Degrees => 360.0,
+            AngleMode::Gradians => 400.0,
+            AngleMode::Turns => 1.0,
+        }
+    }
+
+    fn angle_to_radians(self, angle: f64) -> f64 {
+        match self {
+            AngleMode::Radians => angle,
+            AngleMode::Degrees => angle.to_radians(),
+            other => angle / other.full_turn() * std::f64::consts::TAU,
+        }
+    }
+
+    fn radians_to_angle(self, angle: f64) -> f64 {
+        match self {
+            AngleMode::Radians => angle,
+            AngleMode::Degrees => angle.to_degrees(),
+            other => angle / std::f64::consts::TAU * other.full_turn(),
+        }
+    }
+}
+
+// ヘルプや補完で使う関数の一覧
+pub struct FunctionInfo {
+    pub name: &'static str,
+    pub signature: &'static str,
+    pub description: &'static str,
+}
+
+pub const FUNCTIONS: &[FunctionInfo] = &[
+    FunctionInfo { name: "sin", signature: "sin(x)", description: "Sine" },
+    FunctionInfo { name: "cos", signature: "cos(x)", description: "Cosine" },
+    FunctionInfo { name: "tan", signature: "tan(x)", description: "Tangent" },
+    FunctionInfo { name: "asin", signature: "asin(x)", description: "Inverse sine" },
+    FunctionInfo { name: "acos", signature: "acos(x)", description: "Inverse cosine" },
+    FunctionInfo { name: "atan", signature: "atan(x)", description: "Inverse tangent" },
+    FunctionInfo { name: "atan2", signature: "atan2(y, x)", description: "Angle of the point (x, y)" },
+    FunctionInfo { name: "sinh", signature: "sinh(x)", description: "Hyperbolic sine" },
+    FunctionInfo { name: "cosh", signature: "cosh(x)", description: "Hyperbolic cosine" },
+    FunctionInfo { name: "tanh", signature: "tanh(x)", description: "Hyperbolic tangent" },
+    FunctionInfo { name: "asinh", signature: "asinh(x)", description: "Inverse hyperbolic sine" },
+    FunctionInfo { name: "acosh", signature: "acosh(x)", description: "Inverse hyperbolic cosine (x >= 1)" },
+    FunctionInfo { name: "atanh", signature: "atanh(x)", description: "Inverse hyperbolic tangent (-1 < x < 1)" },
+    FunctionInfo { name: "sqrt", signature: "sqrt(x)", description: "Square root" },
+    FunctionInfo { name: "log", signature: "log(x[, base])", description: "Logarithm (base 10 by default)" },
+    FunctionInfo { name: "ln", signature: "ln(x)", description: "Natural logarithm" },
+    FunctionInfo { name: "log2", signature: "log2(x)", description: "Base-2 logarithm" },
+    FunctionInfo { name: "exp", signature: "exp(x)", description: "e raised to the power x" },
+    FunctionInfo { name: "abs", signature: "abs(x)", description: "Absolute value" },
+    FunctionInfo { name: "floor", signature: "floor(x)", description: "Round down" },
+    FunctionInfo { name: "ceil", signature: "ceil(x)", description: "Round up" },
+    FunctionInfo { name: "round", signature: "round(x[, places])", description: "Round to an integer or to decimal places (uses --rounding)" },
+    FunctionInfo { name: "min", signature: "min(x, ...)", description: "Smallest argument" },
+    FunctionInfo { name: "max", signature: "max(x, ...)", description: "Largest argument" },
+    FunctionInfo { name: "factorial", signature: "factorial(n)", description: "n! (also written n!)" },
+    FunctionInfo { name: "gamma", signature: "gamma(x)", description: "Gamma function, gamma(n) = (n-1)!" },
+    FunctionInfo { name: "lgamma", signature: "lgamma(x)", description: "Natural logarithm of |gamma(x)|" },
+    FunctionInfo { name: "erf", signature: "erf(x)", description: "Error function" },
+    FunctionInfo { name: "erfc", signature: "erfc(x)", description: "Complementary error function, 1 - erf(x)" },
+    FunctionInfo { name: "ncr", signature: "ncr(n, r)", description: "Number of combinations" },
+    FunctionInfo { name: "npr", signature: "npr(n, r)", description: "Number of permutations" },
+    FunctionInfo { name: "if", signature: "if(cond, a, b)", description: "a if cond is nonzero, otherwise b (also cond ? a : b)" },
+    FunctionInfo { name: "sum", signature: "sum(i, from, to, expr)", description: "Sum of expr for i = from to to" },
+    FunctionInfo { name: "prod", signature: "prod(i, from, to, expr)", description: "Product of expr for i = from to to" },
+    FunctionInfo { name: "rand", signature: "rand()", description: "Random number in [0, 1)" },
+    FunctionInfo { name: "randint", signature: "randint(a, b)", description: "Random integer from a to b inclusive" },
+    FunctionInfo { name: "to_decimal", signature: "to_decimal(x)", description: "Convert an exact fraction to a decimal" },
+    FunctionInfo { name: "transpose", signature: "transpose(M)", description: "Matrix transpose" },
+    FunctionInfo { name: "det", signature: "det(M)", description: "Determinant of a square matrix" },
+    FunctionInfo { name: "inverse", signature: "inverse(M)", description: "Inverse of a square matrix" },
+    FunctionInfo { name: "dot", signature: "dot(u, v)", description: "Dot product of two vectors" },
+    FunctionInfo { name: "cross", signature: "cross(u, v)", description: "Cross product of two 3-element vectors" },
+];
+
+pub fn is_builtin(name: &str) -> bool {
+    FUNCTIONS.iter().any(|f| f.name == name)
+}
+
+// 組み込み関数を呼び出す
+pub fn call(name: &str, args: &[f64], angle_mode: AngleMode) -> Result<f64, CalcError> {
+    let result = match name {
+        "sin" => angle_mode.angle_to_radians(unary(name, args)?).sin(),
+        "cos" => angle_mode.angle_to_radians(unary(name, args)?).cos(),
+        "tan" => angle_mode.angle_to_radians(unary(name, args)?).tan(),
+        "asin" => angle_mode.radians_to_angle(unit_interval(name, unary(name, args)?)?.asin()),
+        "acos" => angle_mode.radians_to_angle(unit_interval(name, unary(name, args)?)?.acos()),
+        "atan" => angle_mode.radians_to_angle(unary(name, args)?.atan()),
+        "atan2" => {
+            let [y, x] = binary(name, args)?;
+            angle_mode.radians_to_angle(y.atan2(x))
+        }
+        // 双曲線関数は角度を使わない
+        "sinh" => unary(name, args)?.sinh(),
+        "cosh" => unary(name, args)?.cosh(),
+        "tanh" => unary(name, args)?.tanh(),
+        "asinh" => unary(name, args)?.asinh(),
+        "acosh" => {
+            let x = unary(name, args)?;
+            if x < 1.0 {
+                return Err(CalcError::InvalidExpression("acosh argument must be at least 1".to_string()));
+            }
+            x.acosh()
+        }
+        "atanh" => {
+            let x = unary(name, args)?;
+            if x.abs() >= 1.0 {
+                return Err(CalcError::InvalidExpression("atanh argument must be between -1 and 1 (exclusive)".to_string()));
+            }
+            x.atanh()
+        }
+        "sqrt" => square_root(unary(name, args)?)?,
+        "log" => match args {
+            [x] => positive(name, *x)?.log10(),
+            [x, base] => {
+                if *base <= 0.0 || *base == 1.0 {
+                    return Err(CalcError::InvalidExpression(
+                        "Logarithm base must be positive and not equal to 1".to_string()
+                    ));
+                }
+                positive(name, *x)?.log(*base)
+            }
+            _ => return Err(argument_count(name, "1 or 2", args.len())),
+        },
+        "ln" => positive(name, unary(name, args)?)?.ln(),
+        "log2" => positive(name, unary(name, args)?)?.log2(),
+        "exp" => unary(name, args)?.exp(),
+        "abs" => unary(name, args)?.abs(),
+        "floor" => unary(name, args)?.floor(),
+        "ceil" => unary(name, args)?.ceil(),
+        // 誤差付きの値で使う近似（通常は Evaluator が丸め方に従って正確に丸める）
+        "round" => match args {
+            [x] => x.round(),
+            [x, places] => {
+                let scale = 10f64.powf(places.trunc());
+                (x * scale).round() / scale
+            }
+            _ => return Err(argument_count(name, "1 or 2", args.len())),
+        },
+        "gamma" => {
+            let x = unary(name, args)?;
+            if x <= 0.0 && x.fract() == 0.0 {
+                return Err(CalcError::InvalidExpression(
+                    "Gamma function is undefined for zero and negative integers".to_string()
+                ));
+            }
+            combinatorics::gamma(x)
+        }
+        "lgamma" => {
+            let x = unary(name, args)?;
+            if x <= 0.0 && x.fract() == 0.0 {
+                return Err(CalcError::InvalidExpression(
+                    "Gamma function is undefined for zero and negative integers".to_string()
+                ));
+            }
+            combinatorics::ln_gamma(x)
+        }
+        "erf" => erf(unary(name, args)?),
+        "erfc" => erfc(unary(name, args)?),
+        "min" => variadic(name, args)?.iter().copied().fold(f64::INFINITY, f64::min),
+        "max" => variadic(name, args)?.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+        // f64 はすでに小数なのでそのまま（分数の変換は number::call_exact で行う）
+        "to_decimal" => unary(name, args)?,
+        // 式としては Evaluator が選ばれた側だけを評価する。ここは評価済みの引数で呼ばれたとき
+        "if" => match args {
+            [condition, then, otherwise] => {
+                if *condition != 0.0 {
+                    *then
+                } else {
+                    *otherwise
+                }
+            }
+            _ => return Err(argument_count(name, "3", args.len())),
+        },
+        // 変数名と式を受け取るので評価済みの引数では呼べない（Evaluator が扱う）
+        "sum" | "prod" => {
+            return Err(CalcError::InvalidExpression(format!("{}() expects a variable, two bounds and an expression, such as {}(i, 1, 10, i^2)", name, name)))
+        }
+        _ => return Err(CalcError::UnknownFunction(name.to_string())),
+    };
+
+    if result.is_infinite() || result.is_nan() {
+        return Err(CalcError::InvalidExpression("Result overflow or invalid".to_string()));
+    }
+    Ok(result)
+}
+
+// これより小さい |x| では erf を級数で、大きい x では erfc を連分数で求める
+const ERF_SERIES_LIMIT: f64 = 2.0;
+
+// 誤差関数 erf(x) = 2/√π ∫[0, x] e^(-t²) dt
+fn erf(x: f64) -> f64 {
+    if x.abs() >= ERF_SERIES_LIMIT {
+        return x.signum() * (1.0 - erfc(x.abs()));
+    }
+    // erf(x) = 2/√π e^(-x²) Σ 2^n x^(2n+1) / (1·3·…·(2n+1))（項がすべて正なので桁落ちしない）
+    let mut term = x;
+    let mut sum = x;
+    for n in 1..200 {
+        term *= 2.0 * x * x / (2 * n + 1) as f64;
+        sum += term;
+        if term.abs() < sum.abs() * f64::EPSILON {
+            break;
+        }
+    }
+    2.0 / std::f64::consts::PI.sqrt() * (-x * x).exp() * sum
+}
+
+// 相補誤差関数 erfc(x) = 1 - erf(x)（大きな x でも 1 - erf の桁落ちなしに求める）
+fn erfc(x: f64) -> f64 {
+    if x < ERF_SERIES_LIMIT {
+        return 1.0 - erf(x);
+    }
+    // erfc(x) = e^(-x²)/√π · 1/(x + (1/2)/(x + 1/(x + (3/2)/(x + …))))
+    let fraction = (1..=60).rev().fold(x, |tail, n| x + n as f64 / 2.0 / tail);
+    (-x * x).exp() / std::f64::consts::PI.sqrt() / fraction
+}
+
+fn argument_count(name: &str, expected: &str, found: usize) -> CalcError {
+    CalcError::ArgumentCount {
+        name: name.to_string(),
+        expected: expected.to_string(),
+        found,
+    }
+}
+
+fn unary(name: &str, args: &[f64]) -> Result<f64, CalcError> {
+    match args {
+        [x] => Ok(*x),
+        _ => Err(argument_count(name, "1", args.len())),
+    }
+}
+
+fn binary(name: &str, args: &[f64]) -> Result<[f64; 2], CalcError> {
+    match args {
+        [a, b] => Ok([*a, *b]),
+        _ => Err(argument_count(name, "2", args.len())),
+    }
+}
+
+fn variadic<'a>(name: &str, args: &'a [f64]) -> Result<&'a [f64], CalcError> {
+    if args.is_empty() {
+        return Err(argument_count(name, "at least 1", 0));
+    }
+    Ok(args)
+}
+
+// 対数の引数チェック
+fn positive(name: &str, x: f64) -> Result<f64, CalcError> {
+    if x <= 0.0 {
+        return Err(CalcError::InvalidExpression(format!(
+            "Cannot calculate {} of non-positive number", name
+        )));
+    }
+    Ok(x)
+}
+
+// 逆三角関数の引数チェック
+fn unit_interval(name: &str, x: f64) -> Result<f64, CalcError> {
+    if !(-1.0..=1.0).contains(&x) {
+        return Err(CalcError::InvalidExpression(format!(
+            "{} argument must be between -1 and 1", name
+        )));
+    }
+    Ok(x)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn approx(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9
+    }
+
+    #[test]
+    fn test_trigonometry_modes() {
+        assert!(approx(call("sin", &[std::f64::consts::FRAC_PI_2], AngleMode::Radians).unwrap(), 1.0));
+        assert!(approx(call("sin", &[90.0], AngleMode::Degrees).unwrap(), 1.0));
+        assert!(approx(call("acos", &[0.0], AngleMode::Degrees).unwrap(), 90.0));
+        assert!(approx(call("atan2", &[1.0, 1.0], AngleMode::Degrees).unwrap(), 45.0));
+        assert!(approx(call("sin", &[100.0], AngleMode::Gradians).unwrap(), 1.0));
+        assert!(approx(call("cos", &[0.5], AngleMode::Turns).unwrap(), -1.0));
+        assert!(approx(call("asin", &[1.0], AngleMode::Turns).unwrap(), 0.25));
+        assert!(approx(call("atan", &[1.0], AngleMode::Gradians).unwrap(), 50.0));
+    }
+
+    #[test]
+    fn test_logarithms() {
+        assert!(approx(call("log", &[1000.0], AngleMode::Radians).unwrap(), 3.0));
+        assert!(approx(call("log", &[8.0, 2.0], AngleMode::Radians).unwrap(), 3.0));
+        assert!(approx(call("ln", &[std::f64::consts::E], AngleMode::Radians).unwrap(), 1.0));
+        assert!(approx(call("log2", &[1024.0], AngleMode::Radians).unwrap(), 10.0));
+        assert!(call("ln", &[0.0], AngleMode::Radians).is_err());
+        assert!(call("log", &[10.0, 1.0], AngleMode::Radians).is_err());
+    }
+
+    #[test]
+    fn test_rounding_and_extrema() {
+        assert_eq!(call("floor", &[2.7], AngleMode::Radians).unwrap(), 2.0);
+        assert_eq!(call("ceil", &[2.1], AngleMode::Radians).unwrap(), 3.0);
+        assert_eq!(call("round", &[-2.5], AngleMode::Radians).unwrap(), -3.0);
+        assert_eq!(call("min", &[3.0, -1.0, 2.0], AngleMode::Radians).unwrap(), -1.0);
+        assert_eq!(call("max", &[3.0, -1.0, 2.0], AngleMode::Radians).unwrap(), 3.0);
+    }
+
+    #[test]
+    fn test_hyperbolic() {
+        assert!(approx(call("sinh", &[1.0], AngleMode::Degrees).unwrap(), 1.175_201_193_643_801_4));
+        assert!(approx(call("cosh", &[0.0], AngleMode::Radians).unwrap(), 1.0));
+        assert!(approx(call("tanh", &[0.5], AngleMode::Radians).unwrap(), 0.462_117_157_260_009_8));
+        assert!(approx(call("asinh", &[call("sinh", &[2.0], AngleMode::Radians).unwrap()], AngleMode::Radians).unwrap(), 2.0));
+        assert!(approx(call("acosh", &[1.0], AngleMode::Radians).unwrap(), 0.0));
+        assert!(approx(call("atanh", &[0.5], AngleMode::Radians).unwrap(), 0.549_306_144_334_054_9));
+        assert!(call("acosh", &[0.5], AngleMode::Radians).is_err());
+        assert!(call("atanh", &[1.0], AngleMode::Radians).is_err());
+    }
+
+    #[test]
+    fn test_error_function_and_lgamma() {
+        let close = |a: f64, b: f64| ((a - b) / b).abs() < 1e-13;
+        assert!(close(erf(0.5), 0.520_499_877_813_046_5));
+        assert!(close(erf(1.0), 0.842_700_792_949_714_9));
+        assert!(close(erf(-2.0), -0.995_322_265_018_952_7));
+        assert!(close(erf(1.999), 0.995_301_556_651_370_5));
+        assert!(close(erfc(0.1), 0.887_537_083_981_715_2));
+        assert!(close(erfc(1.99), 4.888_586_800_383_003e-3));
+        assert!(close(erfc(2.0), 4.677_734_981_047_265e-3));
+        assert!(close(erfc(3.0), 2.209_049_699_858_544e-5));
+        assert!(close(erfc(5.0), 1.537_459_794_428_035e-12));
+        assert!(close(erfc(-1.0), 1.842_700_792_949_715));
+        assert_eq!(erf(0.0), 0.0);
+        assert!(approx(call("lgamma", &[171.5], AngleMode::Radians).unwrap(), 709.143_163_030_928_2));
+        assert!(call("lgamma", &[-2.0], AngleMode::Radians).is_err());
+    }
+
+    #[test]
+    fn test_errors() {
+        assert!(matches!(call("asin", &[2.0], AngleMode::Radians), Err(CalcError::InvalidExpression(_))));
+        assert!(matches!(call("sin", &[1.0, 2.0], AngleMode::Radians), Err(CalcError::ArgumentCount { .. })));
+        assert!(matches!(call("max", &[], AngleMode::Radians), Err(CalcError::ArgumentCount { .. })));
+        assert!(matches!(call("nope", &[1.0], AngleMode::Radians), Err(CalcError::UnknownFunction(_))));
+    }
+}
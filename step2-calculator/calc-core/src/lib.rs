@@ -0,0 +1,215 @@
+// 計算エンジン（式の解析・評価・数値の表現）
+//
+// calc-cli のほか、他のクレートからも式の評価に使える
+//   let evaluator = calc_core::Evaluator::default();
+//   let value = evaluator.evaluate_str("2 + 3 * 4")?;
+//   println!("{}", evaluator.format(&value));  // 14
+//   let scale = calc_core::evaluate_number("1/25.4")?;  // 数値だけが欲しいとき
+//
+// 列挙型を clap の値として使うには clap フィーチャ、統計量の JSON 出力には serde フィーチャを有効にする
+// errors フィーチャは CalcError に my-cli-errors の終了コード（calc_core::errors）を付ける
+// C などから共有ライブラリとして使うには calc-ffi クレートをビルドする（calc-ffi/include/calc.h）
+// reference フィーチャは差分テスト用の参照評価器（calc_core::reference::evaluate_str）を公開する
+pub mod arithmetic;
+pub mod calculus;
+pub mod combinatorics;
+pub mod constants;
+#[cfg(feature = "errors")]
+pub mod errors;
+pub mod evaluator;
+pub mod explain;
+pub mod format;
+pub mod functions;
+pub mod matrix;
+pub mod number;
+pub mod numtheory;
+pub mod parser;
+pub mod radix;
+pub mod random;
+#[cfg(any(test, feature = "reference"))]
+pub mod reference;
+pub mod solver;
+pub mod stats;
+pub mod symbolic;
+pub mod uncertainty;
+pub mod units;
+pub mod value;
+
+pub use evaluator::{Evaluator, Outcome};
+pub use functions::AngleMode;
+pub use number::{Number, NumberMode};
+pub use value::Value;
+
+// カスタムエラー型の定義
+#[derive(thiserror::Error, Debug)]
+pub enum CalcError {
+    #[error("Division by zero")]
+    DivisionByZero,
+    
+    #[error("Invalid expression: {0}")]
+    InvalidExpression(String),
+    
+    #[error("Syntax error at position {position}: {message}")]
+    SyntaxError { position: usize, len: usize, message: String, hint: Option<String> },
+    
+    #[error("Number parsing error: {0}")]
+    ParseError(#[from] std::num::ParseFloatError),
+    
+    #[error("Unknown operation: {0}")]
+    UnknownOperation(String),
+    
+    #[error("Unknown variable or constant: {0}")]
+    UnknownVariable(String),
+    
+    #[error("Unknown function: {0}")]
+    UnknownFunction(String),
+    
+    #[error("Function '{name}' expects {expected} argument(s), got {found}")]
+    ArgumentCount { name: String, expected: String, found: usize },
+    
+    #[error("Recursion limit of {0} nested calls exceeded")]
+    RecursionLimit(usize),
+    
+    #[error("Evaluation step limit of {0} exceeded")]
+    StepLimit(u64),
+    
+    #[error("Dimension mismatch: {0}")]
+    DimensionMismatch(String),
+}
+
+// 他のツールの数値の引数に書かれた式を評価する（gltf-cli の --scale "1/25.4" など）
+// 既定の評価器で評価し、数値でない結果と有限でない結果はエラーにする
+pub fn evaluate_number(input: &str) -> Result<f64, CalcError> {
+    let value = Evaluator::default().evaluate_str(input)?.into_number()?.to_f64();
+    if !value.is_finite() {
+        return Err(CalcError::InvalidExpression(format!("{} is not a finite number", input.trim())));
+    }
+    Ok(value)
+}
+
+// calc-cli version に表示するバージョンと、有効にしたフィーチャ
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+pub fn features() -> Vec<&'static str> {
+    let features = [
+        ("clap", cfg!(feature = "clap")),
+        ("serde", cfg!(feature = "serde")),
+        ("errors", cfg!(feature = "errors")),
+        ("reference", cfg!(feature = "reference")),
+    ];
+    features.into_iter().filter(|(_, enabled)| *enabled).map(|(name, _)| name).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn evaluate_expression(expr: &str) -> Result<f64, CalcError> {
+        Evaluator::default().evaluate_str(expr).and_then(Value::into_number).map(|result| result.to_f64())
+    }
+
+    #[test]
+    fn test_expression_evaluation() {
+        assert_eq!(evaluate_expression("2 + 3").unwrap(), 5.0);
+        assert_eq!(evaluate_expression("10 - 4").unwrap(), 6.0);
+        assert_eq!(evaluate_expression("3 * 4").unwrap(), 12.0);
+        assert_eq!(evaluate_expression("15 / 3").unwrap(), 5.0);
+        assert_eq!(evaluate_expression("2 + 3 * 4").unwrap(), 14.0); // 演算子優先順位
+        assert_eq!(evaluate_expression("17 % 5 + 17 // 5 * 5").unwrap(), 17.0);
+    }
+
+    #[test]
+    fn test_evaluate_number() {
+        assert_eq!(evaluate_number("1/25.4").unwrap(), 1.0 / 25.4);
+        assert_eq!(evaluate_number(" 2^10 ").unwrap(), 1024.0);
+        assert!(matches!(evaluate_number("1 +"), Err(CalcError::SyntaxError { .. })));
+        assert!(matches!(evaluate_number("inches"), Err(CalcError::UnknownVariable(_))));
+        assert!(matches!(evaluate_number("1/0"), Err(CalcError::DivisionByZero)));
+    }
+
+    #[test]
+    fn test_negative_numbers() {
+        assert_eq!(evaluate_expression("-5").unwrap(), -5.0);
+        assert_eq!(evaluate_expression("-5 + 3").unwrap(), -2.0);
+    }
+
+    #[test]
+    fn test_parentheses() {
+        assert_eq!(evaluate_expression("(2 + 3) * 4").unwrap(), 20.0);
+        assert_eq!(evaluate_expression("2 * (3 + 4) - 1").unwrap(), 13.0);
+        assert_eq!(evaluate_expression("((1 + 1))").unwrap(), 2.0);
+        assert_eq!(evaluate_expression("-(2 + 3)").unwrap(), -5.0);
+    }
+
+    #[test]
+    fn test_unary_minus() {
+        assert_eq!(evaluate_expression("2 * -3").unwrap(), -6.0);
+        assert_eq!(evaluate_expression("2 - -3").unwrap(), 5.0);
+        assert_eq!(evaluate_expression("--4").unwrap(), 4.0);
+        assert_eq!(evaluate_expression("-2 ^ 2").unwrap(), -4.0);
+        assert_eq!(evaluate_expression("2 ^ -1").unwrap(), 0.5);
+    }
+
+    #[test]
+    fn test_associativity() {
+        assert_eq!(evaluate_expression("10 - 4 - 3").unwrap(), 3.0);
+        assert_eq!(evaluate_expression("64 / 4 / 2").unwrap(), 8.0);
+        assert_eq!(evaluate_expression("2 ^ 3 ^ 2").unwrap(), 512.0);
+        assert_eq!(evaluate_expression("1 - 2 + 3").unwrap(), 2.0);
+    }
+
+    #[test]
+    fn test_decimal_and_exponent_literals() {
+        assert_eq!(evaluate_expression("1.5 * 2").unwrap(), 3.0);
+        assert_eq!(evaluate_expression(".5 + .25").unwrap(), 0.75);
+        assert_eq!(evaluate_expression("1e3 + 2.5e-1").unwrap(), 1000.25);
+    }
+
+    #[test]
+    fn test_function_calls() {
+        assert_eq!(evaluate_expression("sqrt(16) + 1").unwrap(), 5.0);
+        assert_eq!(evaluate_expression("max(1, 2 * 3, 4)").unwrap(), 6.0);
+        assert_eq!(evaluate_expression("abs(-3) * floor(2.9)").unwrap(), 6.0);
+        assert_eq!(evaluate_expression("-min(2, 5)").unwrap(), -2.0);
+        assert_eq!(evaluate_expression("2 ^ log2(8)").unwrap(), 8.0);
+        assert!(evaluate_expression("sqrt(-1)").is_err());
+        assert!(evaluate_expression("unknown(1)").is_err());
+    }
+
+    #[test]
+    fn test_constants() {
+        assert_eq!(evaluate_expression("2 * pi * 3").unwrap(), 6.0 * std::f64::consts::PI);
+        assert_eq!(evaluate_expression("tau / 2").unwrap(), std::f64::consts::PI);
+        assert!((evaluate_expression("ln(e)").unwrap() - 1.0).abs() < 1e-12);
+        assert!((evaluate_expression("phi ^ 2 - phi").unwrap() - 1.0).abs() < 1e-12);
+        assert!(matches!(evaluate_expression("2 * r"), Err(CalcError::UnknownVariable(_))));
+    }
+
+    #[test]
+    fn test_previous_result() {
+        let mut evaluator = Evaluator::default();
+        assert!(matches!(evaluator.evaluate_str("ans"), Err(CalcError::UnknownVariable(_))));
+
+        let result = evaluator.evaluate_str("100").unwrap();
+        evaluator.set_last_result(result);
+        assert_eq!(evaluator.evaluate_str("ans * 1.5").and_then(Value::into_number).unwrap().to_f64(), 150.0);
+        assert_eq!(evaluator.evaluate_str("_ + ans").and_then(Value::into_number).unwrap().to_f64(), 200.0);
+    }
+
+    #[test]
+    fn test_degree_mode() {
+        let evaluator = Evaluator::new(AngleMode::Degrees);
+        assert!((evaluator.evaluate_str("sin(30)").and_then(Value::into_number).unwrap().to_f64() - 0.5).abs() < 1e-12);
+        assert!((evaluator.evaluate_str("atan(1)").and_then(Value::into_number).unwrap().to_f64() - 45.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_error_cases() {
+        assert!(evaluate_expression("5 / 0").is_err());
+        assert!(evaluate_expression("abc").is_err());
+        assert!(evaluate_expression("").is_err());
+        assert!(evaluate_expression("(2 + 3").is_err());
+        assert!(evaluate_expression("2 +").is_err());
+        assert!(evaluate_expression("2 3").is_err());
+    }
+}
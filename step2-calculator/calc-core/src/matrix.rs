@@ -0,0 +1,354 @@
+// 行列とベクトルの演算
+use std::fmt;
+
+use crate::number::Number;
+use crate::CalcError;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Matrix {
+    rows: usize,
+    cols: usize,
+    // 行優先で並べた要素
+    data: Vec<Number>,
+}
+
+impl Matrix {
+    pub fn from_rows(rows: Vec<Vec<Number>>) -> Result<Matrix, CalcError> {
+        let cols = rows.first().map_or(0, Vec::len);
+        if cols == 0 {
+            return Err(CalcError::DimensionMismatch("a matrix needs at least one element".to_string()));
+        }
+        if rows.iter().any(|row| row.len() != cols) {
+            return Err(CalcError::DimensionMismatch("all matrix rows must have the same length".to_string()));
+        }
+        Ok(Matrix {
+            rows: rows.len(),
+            cols,
+            data: rows.into_iter().flatten().collect(),
+        })
+    }
+
+    // ベクトルを n×1 の列ベクトルとして扱う
+    pub fn column(values: &[Number]) -> Matrix {
+        Matrix { rows: values.len(), cols: 1, data: values.to_vec() }
+    }
+
+    // ベクトルを 1×n の行ベクトルとして扱う
+    pub fn row(values: &[Number]) -> Matrix {
+        Matrix { rows: 1, cols: values.len(), data: values.to_vec() }
+    }
+
+    // 単位行列（整数の0と1は演算時に他の数値の種類へそろえられる）
+    pub fn identity(n: usize) -> Matrix {
+        let data = (0..n * n)
+            .map(|i| if i % (n + 1) == 0 { Number::one() } else { Number::zero() })
+            .collect();
+        Matrix { rows: n, cols: n, data }
+    }
+
+    // 要素を行優先の順で取り出す
+    pub fn into_elements(self) -> Vec<Number> {
+        self.data
+    }
+
+    fn get(&self, row: usize, col: usize) -> &Number {
+        &self.data[row * self.cols + col]
+    }
+
+    fn dimensions(&self) -> String {
+        format!("{}x{}", self.rows, self.cols)
+    }
+
+    pub fn transpose(&self) -> Matrix {
+        let data = (0..self.cols)
+            .flat_map(|col| (0..self.rows).map(move |row| self.get(row, col).clone()))
+            .collect();
+        Matrix { rows: self.cols, cols: self.rows, data }
+    }
+
+    pub fn map(&self, f: impl Fn(&Number) -> Result<Number, CalcError>) -> Result<Matrix, CalcError> {
+        let data = self.data.iter().map(f).collect::<Result<Vec<Number>, CalcError>>()?;
+        Ok(Matrix { rows: self.rows, cols: self.cols, data })
+    }
+
+    // 同じ大きさの行列どうしの要素ごとの演算
+    pub fn zip_with(
+        &self,
+        other: &Matrix,
+        op: &str,
+        f: impl Fn(&Number, &Number) -> Result<Number, CalcError>,
+    ) -> Result<Matrix, CalcError> {
+        if self.rows != other.rows || self.cols != other.cols {
+            return Err(CalcError::DimensionMismatch(format!(
+                "cannot {} a {} matrix and a {} matrix", op, self.dimensions(), other.dimensions()
+            )));
+        }
+        let data = self
+            .data
+            .iter()
+            .zip(&other.data)
+            .map(|(a, b)| f(a, b))
+            .collect::<Result<Vec<Number>, CalcError>>()?;
+        Ok(Matrix { rows: self.rows, cols: self.cols, data })
+    }
+
+    pub fn mul(&self, other: &Matrix, digits: u64) -> Result<Matrix, CalcError> {
+        if self.cols != other.rows {
+            return Err(CalcError::DimensionMismatch(format!(
+                "cannot multiply a {} matrix by a {} matrix", self.dimensions(), other.dimensions()
+            )));
+        }
+        let mut data = Vec::with_capacity(self.rows * other.cols);
+        for row in 0..self.rows {
+            for col in 0..other.cols {
+                let mut sum = Number::zero();
+                for k in 0..self.cols {
+                    sum = sum.add(&self.get(row, k).mul(other.get(k, col), digits)?, digits)?;
+                }
+                data.push(sum);
+            }
+        }
+        Ok(Matrix { rows: self.rows, cols: other.cols, data })
+    }
+
+    fn require_square(&self, operation: &str) -> Result<usize, CalcError> {
+        if self.rows != self.cols {
+            return Err(CalcError::DimensionMismatch(format!(
+                "{} requires a square matrix, got {}", operation, self.dimensions()
+            )));
+        }
+        Ok(self.rows)
+    }
+
+    // 行列式（部分ピボット選択付きのガウスの消去法）
+    pub fn determinant(&self, digits: u64) -> Result<Number, CalcError> {
+        let n = self.require_square("det")?;
+        let mut rows = self.to_rows();
+        let mut det = Number::one();
+
+        for k in 0..n {
+            let pivot = pivot_row(&rows, k);
+            if is_negligible(&rows[pivot][k]) {
+                return Ok(Number::zero());
+            }
+            if pivot != k {
+                rows.swap(pivot, k);
+                det = det.neg();
+            }
+            let (upper, lower) = rows.split_at_mut(k + 1);
+            let pivot = &upper[k];
+            for row in lower {
+                let factor = row[k].div(&pivot[k], digits)?;
+                for j in k..n {
+                    row[j] = row[j].sub(&factor.mul(&pivot[j], digits)?, digits)?;
+                }
+            }
+            det = det.mul(&rows[k][k], digits)?;
+        }
+        Ok(det)
+    }
+
+    // 逆行列（ガウス・ジョルダンの消去法）
+    pub fn inverse(&self, digits: u64) -> Result<Matrix, CalcError> {
+        let n = self.require_square("inverse")?;
+        let mut rows = self.to_rows();
+        let mut inverse = Matrix::identity(n).to_rows();
+
+        for k in 0..n {
+            let pivot = pivot_row(&rows, k);
+            if is_negligible(&rows[pivot][k]) {
+                return Err(CalcError::InvalidExpression("Matrix is singular and has no inverse".to_string()));
+            }
+            rows.swap(pivot, k);
+            inverse.swap(pivot, k);
+
+            let scale = rows[k][k].clone();
+            for j in 0..n {
+                rows[k][j] = rows[k][j].div(&scale, digits)?;
+                inverse[k][j] = inverse[k][j].div(&scale, digits)?;
+            }
+            for i in (0..n).filter(|&i| i != k) {
+                let factor = rows[i][k].clone();
+                if factor.is_zero() {
+                    continue;
+                }
+                for j in 0..n {
+                    rows[i][j] = rows[i][j].sub(&factor.mul(&rows[k][j], digits)?, digits)?;
+                    inverse[i][j] = inverse[i][j].sub(&factor.mul(&inverse[k][j], digits)?, digits)?;
+                }
+            }
+        }
+        Matrix::from_rows(inverse)
+    }
+
+    // 整数乗（負の指数は逆行列の累乗）
+    pub fn pow(&self, exp: i64, digits: u64) -> Result<Matrix, CalcError> {
+        let n = self.require_square("matrix power")?;
+        let mut base = if exp < 0 { self.inverse(digits)? } else { self.clone() };
+        let mut exp = exp.unsigned_abs();
+        let mut result = Matrix::identity(n);
+        while exp > 0 {
+            if exp & 1 == 1 {
+                result = result.mul(&base, digits)?;
+            }
+            exp >>= 1;
+            if exp > 0 {
+                base = base.mul(&base, digits)?;
+            }
+        }
+        Ok(result)
+    }
+
+    fn to_rows(&self) -> Vec<Vec<Number>> {
+        self.rows().map(<[Number]>::to_vec).collect()
+    }
+
+    // 行ごとの要素
+    pub fn rows(&self) -> impl Iterator<Item = &[Number]> {
+        self.data.chunks(self.cols)
+    }
+
+    // 列をそろえた複数行の表示（要素は format で文字列にする）
+    pub fn to_grid(&self, format: impl Fn(&Number) -> String) -> String {
+        let cells: Vec<String> = self.data.iter().map(format).collect();
+        let widths: Vec<usize> = (0..self.cols)
+            .map(|col| (0..self.rows).map(|row| cells[row * self.cols + col].len()).max().unwrap_or(0))
+            .collect();
+        cells
+            .chunks(self.cols)
+            .map(|row| {
+                let row: Vec<String> = row.iter().zip(&widths).map(|(cell, width)| format!("{:>width$}", cell, width = width)).collect();
+                format!("[ {} ]", row.join("  "))
+            })
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+}
+
+impl fmt::Display for Matrix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[")?;
+        for (i, row) in self.data.chunks(self.cols).enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write_vector(f, row)?;
+        }
+        write!(f, "]")
+    }
+}
+
+// [1, 2, 3] の形式で表示
+pub fn write_vector(f: &mut fmt::Formatter<'_>, values: &[Number]) -> fmt::Result {
+    write!(f, "[")?;
+    for (i, value) in values.iter().enumerate() {
+        if i > 0 {
+            write!(f, ", ")?;
+        }
+        write!(f, "{}", value)?;
+    }
+    write!(f, "]")
+}
+
+pub fn dot(a: &[Number], b: &[Number], digits: u64) -> Result<Number, CalcError> {
+    if a.len() != b.len() {
+        return Err(CalcError::DimensionMismatch(format!(
+            "dot product of vectors with {} and {} elements", a.len(), b.len()
+        )));
+    }
+    a.iter().zip(b).try_fold(Number::zero(), |sum, (x, y)| sum.add(&x.mul(y, digits)?, digits))
+}
+
+pub fn cross(a: &[Number], b: &[Number], digits: u64) -> Result<Vec<Number>, CalcError> {
+    let (Ok([a1, a2, a3]), Ok([b1, b2, b3])) = (<&[Number; 3]>::try_from(a), <&[Number; 3]>::try_from(b)) else {
+        return Err(CalcError::DimensionMismatch("cross product requires two 3-element vectors".to_string()));
+    };
+    let term = |p: &Number, q: &Number, r: &Number, s: &Number| p.mul(q, digits)?.sub(&r.mul(s, digits)?, digits);
+    Ok(vec![term(a2, b3, a3, b2)?, term(a3, b1, a1, b3)?, term(a1, b2, a2, b1)?])
+}
+
+// k 列目で絶対値が最大の要素を持つ行（k 行目以降）
+fn pivot_row(rows: &[Vec<Number>], k: usize) -> usize {
+    (k..rows.len())
+        .max_by(|&a, &b| rows[a][k].to_f64().abs().total_cmp(&rows[b][k].to_f64().abs()).then(b.cmp(&a)))
+        .unwrap_or(k)
+}
+
+// 浮動小数点の丸め誤差で0にならない場合も0とみなす
+fn is_negligible(value: &Number) -> bool {
+    match value {
+        Number::Float(value) => value.abs() < 1e-12,
+        _ => value.is_zero(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::number::{NumberMode, DEFAULT_DIGITS};
+
+    fn matrix(rows: &[&[&str]], mode: NumberMode) -> Matrix {
+        let rows = rows
+            .iter()
+            .map(|row| row.iter().map(|text| Number::parse(text, mode).unwrap()).collect())
+            .collect();
+        Matrix::from_rows(rows).unwrap()
+    }
+
+    #[test]
+    fn test_multiply_and_transpose() {
+        let a = matrix(&[&["1", "2", "3"], &["4", "5", "6"]], NumberMode::Float);
+        let product = a.mul(&a.transpose(), DEFAULT_DIGITS).unwrap();
+        assert_eq!(product.to_string(), "[[14, 32], [32, 77]]");
+        assert!(matches!(a.mul(&a, DEFAULT_DIGITS), Err(CalcError::DimensionMismatch(_))));
+    }
+
+    #[test]
+    fn test_determinant() {
+        let a = matrix(&[&["1", "2"], &["3", "4"]], NumberMode::Float);
+        assert_eq!(a.determinant(DEFAULT_DIGITS).unwrap().to_f64(), -2.0);
+
+        let b = matrix(&[&["0", "1", "2"], &["1", "0", "3"], &["4", "-3", "8"]], NumberMode::Rational);
+        assert_eq!(b.determinant(DEFAULT_DIGITS).unwrap().to_string(), "-2");
+
+        let singular = matrix(&[&["1", "2"], &["2", "4"]], NumberMode::Float);
+        assert_eq!(singular.determinant(DEFAULT_DIGITS).unwrap().to_f64(), 0.0);
+    }
+
+    #[test]
+    fn test_inverse() {
+        let a = matrix(&[&["4", "7"], &["2", "6"]], NumberMode::Rational);
+        let inverse = a.inverse(DEFAULT_DIGITS).unwrap();
+        assert_eq!(inverse.to_string(), "[[3/5, -7/10], [-1/5, 2/5]]");
+        assert_eq!(a.mul(&inverse, DEFAULT_DIGITS).unwrap().to_string(), "[[1, 0], [0, 1]]");
+
+        let singular = matrix(&[&["1", "2"], &["2", "4"]], NumberMode::Float);
+        assert!(singular.inverse(DEFAULT_DIGITS).is_err());
+    }
+
+    #[test]
+    fn test_power() {
+        let fib = matrix(&[&["1", "1"], &["1", "0"]], NumberMode::Integer);
+        assert_eq!(fib.pow(10, DEFAULT_DIGITS).unwrap().to_string(), "[[89, 55], [55, 34]]");
+        assert_eq!(fib.pow(0, DEFAULT_DIGITS).unwrap().to_string(), "[[1, 0], [0, 1]]");
+    }
+
+    #[test]
+    fn test_vector_products() {
+        let parse = |values: &[&str]| -> Vec<Number> {
+            values.iter().map(|v| Number::parse(v, NumberMode::Float).unwrap()).collect()
+        };
+        let x = parse(&["1", "0", "0"]);
+        let y = parse(&["0", "1", "0"]);
+        assert_eq!(dot(&x, &y, DEFAULT_DIGITS).unwrap().to_f64(), 0.0);
+        assert_eq!(dot(&parse(&["1", "2", "3"]), &parse(&["4", "5", "6"]), DEFAULT_DIGITS).unwrap().to_f64(), 32.0);
+        assert_eq!(cross(&x, &y, DEFAULT_DIGITS).unwrap(), parse(&["0", "0", "1"]));
+        assert!(cross(&x[..2], &y[..2], DEFAULT_DIGITS).is_err());
+    }
+
+    #[test]
+    fn test_grid() {
+        let a = matrix(&[&["1", "-20"], &["300", "4"]], NumberMode::Float);
+        assert_eq!(a.to_grid(Number::to_string), "[   1  -20 ]\n[ 300    4 ]");
+    }
+}
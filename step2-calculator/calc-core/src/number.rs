@@ -0,0 +1,880 @@
+// 数値の表現と演算（f64・任意精度の10進数・分数・多倍長整数を切り替える）
+use std::cmp::Ordering;
+use std::fmt;
+use std::num::NonZeroU64;
+use std::str::FromStr;
+
+use bigdecimal::{BigDecimal, Context, FromPrimitive, One, RoundingMode, Signed, ToPrimitive, Zero};
+use num_bigint::BigInt;
+use num_rational::BigRational;
+
+use crate::arithmetic::{add, divide, floor_divide, modulo, multiply, power, square_root, subtract};
+use crate::radix;
+use crate::CalcError;
+
+// 計算に使う数値の種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+pub enum NumberMode {
+    /// 64-bit floating point (fast, about 15-17 significant digits)
+    #[default]
+    Float,
+    /// Arbitrary-precision decimal (exact for decimal fractions like 0.1)
+    Decimal,
+    /// Exact fractions; irrational results fall back to floating point
+    Rational,
+    /// Arbitrary-size integers; non-integer results fall back to floating point
+    Integer,
+}
+
+// 10進数モードで割り算などを丸める有効桁数の既定値
+pub const DEFAULT_DIGITS: u64 = 50;
+
+// 分数のまま計算する累乗の指数の上限（これを超えると f64 で近似する）
+const MAX_EXACT_EXPONENT: i32 = 10_000;
+
+// 整数のまま計算する累乗の結果のビット数の上限（約30万桁）
+const MAX_EXACT_BITS: u64 = 1 << 20;
+
+// 10進数の整数でない乗で許す結果の大きさ（10 の何乗まで）
+const MAX_DECIMAL_EXPONENT: u64 = 1_000_000;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Number {
+    Float(f64),
+    Decimal(BigDecimal),
+    Rational(BigRational),
+    Integer(BigInt),
+}
+
+impl Number {
+    // 数値リテラルを指定のモードで解釈
+    pub fn parse(text: &str, mode: NumberMode) -> Result<Number, CalcError> {
+        // 0xFF などは整数としてモードの数値にする
+        if let Some(value) = radix::parse_prefixed(text) {
+            let value = value?;
+            return Ok(match mode {
+                NumberMode::Float => Number::Float(value.to_f64().unwrap_or(f64::INFINITY)),
+                NumberMode::Decimal => Number::Decimal(BigDecimal::from(value)),
+                NumberMode::Rational => Number::Rational(BigRational::from_integer(value)),
+                NumberMode::Integer => Number::Integer(value),
+            });
+        }
+        match mode {
+            NumberMode::Float => Ok(Number::Float(text.parse()?)),
+            NumberMode::Decimal => parse_decimal(text).map(Number::Decimal),
+            NumberMode::Rational => parse_decimal(text).map(|value| Number::Rational(decimal_to_rational(&value))),
+            // 整数でないリテラルは f64 として扱う
+            NumberMode::Integer => {
+                let value = decimal_to_rational(&parse_decimal(text)?);
+                if value.is_integer() {
+                    Ok(Number::Integer(value.to_integer()))
+                } else {
+                    Ok(Number::Float(text.parse()?))
+                }
+            }
+        }
+    }
+
+    // f64 の計算結果を指定のモードの数値にする
+    // 分数・整数モードでは無理数などの近似値を変換せず f64 のまま扱う
+    pub fn from_f64(value: f64, mode: NumberMode) -> Number {
+        match mode {
+            NumberMode::Float | NumberMode::Rational | NumberMode::Integer => Number::Float(value),
+            NumberMode::Decimal => Number::Decimal(f64_to_decimal(value)),
+        }
+    }
+
+    // 別のモードの数値にする（対話モードの mode コマンド）
+    // 正確に表せないもの（無限大、整数モードでの小数）は f64 にする
+    pub fn convert(&self, mode: NumberMode, digits: u64) -> Number {
+        if let Number::Float(value) = self
+            && !value.is_finite()
+        {
+            return self.clone();
+        }
+        match mode {
+            NumberMode::Float => Number::Float(self.to_f64()),
+            NumberMode::Decimal => Number::Decimal(to_decimal(self, digits)),
+            NumberMode::Rational => match self {
+                Number::Rational(value) => Number::Rational(value.clone()),
+                Number::Integer(value) => Number::Rational(BigRational::from_integer(value.clone())),
+                other => Number::Rational(decimal_to_rational(&to_decimal(other, digits))),
+            },
+            NumberMode::Integer => match self.to_bigint() {
+                Some(value) => Number::Integer(value),
+                None => Number::Float(self.to_f64()),
+            },
+        }
+    }
+
+    // 他の数値の種類と演算すると相手の種類にそろう0と1
+    pub fn zero() -> Number {
+        Number::Integer(BigInt::ZERO)
+    }
+
+    pub fn one() -> Number {
+        Number::Integer(BigInt::from(1))
+    }
+
+    // 整数値なら多倍長整数として返す
+    pub fn to_bigint(&self) -> Option<BigInt> {
+        match self {
+            Number::Float(value) if value.fract() == 0.0 => BigInt::from_f64(*value),
+            Number::Float(_) => None,
+            Number::Decimal(value) => value.is_integer().then(|| value.with_scale(0).into_bigint_and_exponent().0),
+            Number::Rational(value) => value.is_integer().then(|| value.to_integer()),
+            Number::Integer(value) => Some(value.clone()),
+        }
+    }
+
+    // 10進数にする（分数は digits 桁で丸める）
+    pub fn to_bigdecimal(&self, digits: u64) -> BigDecimal {
+        to_decimal(self, digits)
+    }
+
+    pub fn is_zero(&self) -> bool {
+        match self {
+            Number::Float(value) => *value == 0.0,
+            Number::Decimal(value) => value.is_zero(),
+            Number::Rational(value) => value.is_zero(),
+            Number::Integer(value) => value.is_zero(),
+        }
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        match self {
+            Number::Float(value) => *value,
+            Number::Decimal(value) => value.to_f64().unwrap_or(f64::NAN),
+            Number::Rational(value) => value.to_f64().unwrap_or(f64::NAN),
+            Number::Integer(value) => value.to_f64().unwrap_or(f64::NAN),
+        }
+    }
+
+    // 有効桁数に丸める（10進数以外はそのまま）
+    pub fn rounded(self, digits: u64) -> Number {
+        match self {
+            Number::Decimal(value) => Number::Decimal(round(value, digits)),
+            _ => self,
+        }
+    }
+
+    // 小数点以下 places 桁に丸める（負なら 10^-places の位。分数と整数は正確に丸める）
+    pub fn round_places(&self, places: i64, mode: RoundingMode) -> Number {
+        match self {
+            Number::Float(value) if !value.is_finite() => self.clone(),
+            Number::Float(value) => {
+                // 2.675 が 2.67499... として丸められないよう、表示どおりの10進数で丸める
+                let rounded = f64_to_decimal(*value).with_scale_round(places, mode);
+                Number::Float(rounded.to_f64().unwrap_or(f64::NAN))
+            }
+            Number::Decimal(value) => Number::Decimal(value.with_scale_round(places, mode)),
+            Number::Rational(value) => Number::Rational(round_rational(value, places, mode)),
+            Number::Integer(value) => {
+                let rounded = round_rational(&BigRational::from_integer(value.clone()), places, mode);
+                Number::Integer(rounded.to_integer())
+            }
+        }
+    }
+
+    pub fn neg(&self) -> Number {
+        match self {
+            Number::Float(value) => Number::Float(-value),
+            Number::Decimal(value) => Number::Decimal(-value),
+            Number::Rational(value) => Number::Rational(-value),
+            Number::Integer(value) => Number::Integer(-value),
+        }
+    }
+
+    pub fn add(&self, other: &Number, digits: u64) -> Result<Number, CalcError> {
+        match promote(self, other, digits) {
+            Operands::Float(a, b) => add(a, b).map(Number::Float),
+            Operands::Decimal(a, b) => Ok(Number::Decimal(round(a + b, digits))),
+            Operands::Rational(a, b) => Ok(Number::Rational(a + b)),
+            Operands::Integer(a, b) => Ok(Number::Integer(a + b)),
+        }
+    }
+
+    pub fn sub(&self, other: &Number, digits: u64) -> Result<Number, CalcError> {
+        match promote(self, other, digits) {
+            Operands::Float(a, b) => subtract(a, b).map(Number::Float),
+            Operands::Decimal(a, b) => Ok(Number::Decimal(round(a - b, digits))),
+            Operands::Rational(a, b) => Ok(Number::Rational(a - b)),
+            Operands::Integer(a, b) => Ok(Number::Integer(a - b)),
+        }
+    }
+
+    pub fn mul(&self, other: &Number, digits: u64) -> Result<Number, CalcError> {
+        match promote(self, other, digits) {
+            Operands::Float(a, b) => multiply(a, b).map(Number::Float),
+            Operands::Decimal(a, b) => Ok(Number::Decimal(round(a * b, digits))),
+            Operands::Rational(a, b) => Ok(Number::Rational(a * b)),
+            Operands::Integer(a, b) => Ok(Number::Integer(a * b)),
+        }
+    }
+
+    pub fn div(&self, other: &Number, digits: u64) -> Result<Number, CalcError> {
+        match promote(self, other, digits) {
+            Operands::Float(a, b) => divide(a, b).map(Number::Float),
+            Operands::Decimal(a, b) => {
+                if b.is_zero() {
+                    return Err(CalcError::DivisionByZero);
+                }
+                Ok(Number::Decimal(divide_decimal(&a, &b, digits)))
+            }
+            Operands::Rational(a, b) => {
+                if b.is_zero() {
+                    return Err(CalcError::DivisionByZero);
+                }
+                Ok(Number::Rational(a / b))
+            }
+            Operands::Integer(a, b) => {
+                if b.is_zero() {
+                    return Err(CalcError::DivisionByZero);
+                }
+                // 割り切れなければ f64 で近似する
+                if (&a % &b).is_zero() {
+                    Ok(Number::Integer(a / b))
+                } else {
+                    divide(self.to_f64(), other.to_f64()).map(Number::Float)
+                }
+            }
+        }
+    }
+
+    // 切り捨て除算（-7 // 2 = -4）
+    pub fn floor_div(&self, other: &Number, digits: u64) -> Result<Number, CalcError> {
+        match promote(self, other, digits) {
+            Operands::Float(a, b) => floor_divide(a, b).map(Number::Float),
+            Operands::Decimal(a, b) => {
+                let quotient = floor_quotient(&decimal_to_rational(&a), &decimal_to_rational(&b))?;
+                Ok(Number::Decimal(BigDecimal::from(quotient)))
+            }
+            Operands::Rational(a, b) => floor_quotient(&a, &b).map(|q| Number::Rational(BigRational::from_integer(q))),
+            Operands::Integer(a, b) => {
+                floor_quotient(&BigRational::from_integer(a), &BigRational::from_integer(b)).map(Number::Integer)
+            }
+        }
+    }
+
+    // 余り（割る数と同じ符号で a = (a // b) * b + a % b となる）
+    pub fn rem(&self, other: &Number, digits: u64) -> Result<Number, CalcError> {
+        match promote(self, other, digits) {
+            Operands::Float(a, b) => modulo(a, b).map(Number::Float),
+            Operands::Decimal(a, b) => {
+                let quotient = floor_quotient(&decimal_to_rational(&a), &decimal_to_rational(&b))?;
+                Ok(Number::Decimal(&a - &b * BigDecimal::from(quotient)))
+            }
+            Operands::Rational(a, b) => {
+                let quotient = floor_quotient(&a, &b)?;
+                Ok(Number::Rational(&a - &b * BigRational::from_integer(quotient)))
+            }
+            Operands::Integer(a, b) => {
+                let quotient = floor_quotient(&BigRational::from_integer(a.clone()), &BigRational::from_integer(b.clone()))?;
+                Ok(Number::Integer(&a - &b * quotient))
+            }
+        }
+    }
+
+    // 大小の比較（NaN を含むときは None）
+    pub fn compare(&self, other: &Number, digits: u64) -> Option<Ordering> {
+        match promote(self, other, digits) {
+            Operands::Float(a, b) => a.partial_cmp(&b),
+            Operands::Decimal(a, b) => Some(a.cmp(&b)),
+            Operands::Rational(a, b) => Some(a.cmp(&b)),
+            Operands::Integer(a, b) => Some(a.cmp(&b)),
+        }
+    }
+
+    pub fn pow(&self, other: &Number, digits: u64) -> Result<Number, CalcError> {
+        match promote(self, other, digits) {
+            Operands::Float(a, b) => power(a, b).map(Number::Float),
+            Operands::Decimal(a, b) => {
+                // 整数乗はそのまま、正の数の整数でない乗は exp(b·ln a) として10進数で計算する
+                match b.is_integer().then(|| b.to_i64()).flatten() {
+                    Some(exp) => {
+                        if a.is_zero() && exp < 0 {
+                            return Err(CalcError::DivisionByZero);
+                        }
+                        Ok(Number::Decimal(a.powi_with_context(exp, &context(digits))))
+                    }
+                    None if a.is_positive() => decimal_pow(&a, &b, digits).map(Number::Decimal),
+                    // 0 と負の数の底は f64 で扱う（負の数の整数でない乗はエラー）
+                    None => {
+                        let result = power(a.to_f64().unwrap_or(f64::NAN), b.to_f64().unwrap_or(f64::NAN))?;
+                        Ok(Number::Decimal(round(f64_to_decimal(result), digits)))
+                    }
+                }
+            }
+            Operands::Rational(a, b) => {
+                // 指数が小さい整数なら分数のまま、それ以外は f64 で近似する
+                let exp = b.is_integer().then(|| b.to_integer().to_i32()).flatten()
+                    .filter(|exp| exp.abs() <= MAX_EXACT_EXPONENT);
+                match exp {
+                    Some(exp) => {
+                        if a.is_zero() && exp < 0 {
+                            return Err(CalcError::DivisionByZero);
+                        }
+                        Ok(Number::Rational(a.pow(exp)))
+                    }
+                    None => power(a.to_f64().unwrap_or(f64::NAN), b.to_f64().unwrap_or(f64::NAN)).map(Number::Float),
+                }
+            }
+            Operands::Integer(a, b) => {
+                // 0以上の整数乗で結果が大きすぎなければ整数のまま計算する
+                let exp = b.to_u32().filter(|&exp| a.bits().saturating_mul(u64::from(exp)) <= MAX_EXACT_BITS);
+                match exp {
+                    Some(exp) => Ok(Number::Integer(a.pow(exp))),
+                    None => power(self.to_f64(), other.to_f64()).map(Number::Float),
+                }
+            }
+        }
+    }
+
+    // ビット演算（整数値どうしのみ。負の数は無限桁の2の補数として扱う）
+    pub fn bitwise(&self, other: &Number, op: BitOp) -> Result<Number, CalcError> {
+        let a = self.bitwise_operand()?;
+        let b = other.bitwise_operand()?;
+        let value = match op {
+            BitOp::And => a & b,
+            BitOp::Or => a | b,
+            BitOp::Xor => a ^ b,
+            BitOp::Shl | BitOp::Shr => {
+                if b.is_negative() {
+                    return Err(CalcError::InvalidExpression("Shift amount must not be negative".to_string()));
+                }
+                let shift = b.to_u64().unwrap_or(u64::MAX);
+                if op == BitOp::Shr {
+                    // ビット数より大きいシフトは 0 か -1 になる
+                    let shift = shift.min(a.bits() + 1);
+                    a >> shift
+                } else if a.is_zero() {
+                    a
+                } else if a.bits().saturating_add(shift) <= MAX_EXACT_BITS {
+                    a << shift
+                } else {
+                    return Err(CalcError::InvalidExpression("Result overflow".to_string()));
+                }
+            }
+        };
+        Ok(integer_like(value, self, other))
+    }
+
+    // ビット反転（~x = -x - 1）
+    pub fn not(&self) -> Result<Number, CalcError> {
+        Ok(integer_like(!self.bitwise_operand()?, self, self))
+    }
+
+    fn bitwise_operand(&self) -> Result<BigInt, CalcError> {
+        self.to_bigint().ok_or_else(|| {
+            CalcError::InvalidExpression(format!("Bitwise operations need integers, got {}", self))
+        })
+    }
+
+    pub fn sqrt(&self, digits: u64) -> Result<Number, CalcError> {
+        match self {
+            Number::Float(value) => square_root(*value).map(Number::Float),
+            Number::Decimal(value) => value
+                .sqrt_with_context(&context(digits))
+                .map(Number::Decimal)
+                .ok_or_else(|| CalcError::InvalidExpression(
+                    "Cannot calculate square root of negative number".to_string()
+                )),
+            Number::Rational(value) => {
+                // 分子と分母がともに平方数なら分数のまま
+                if !value.is_negative()
+                    && let (Some(numer), Some(denom)) = (exact_sqrt(value.numer()), exact_sqrt(value.denom()))
+                {
+                    return Ok(Number::Rational(BigRational::new(numer, denom)));
+                }
+                square_root(self.to_f64()).map(Number::Float)
+            }
+            Number::Integer(value) => {
+                if !value.is_negative()
+                    && let Some(root) = exact_sqrt(value)
+                {
+                    return Ok(Number::Integer(root));
+                }
+                square_root(self.to_f64()).map(Number::Float)
+            }
+        }
+    }
+}
+
+// 10進数・分数・整数で正確に計算できる組み込み関数（それ以外は None を返し f64 で計算する）
+pub fn call_exact(name: &str, args: &[Number], digits: u64) -> Option<Result<Number, CalcError>> {
+    let unary = args.len() == 1;
+    if name == "to_decimal" && unary {
+        return Some(Ok(Number::Decimal(round(to_decimal(&args[0], digits), digits))));
+    }
+    if name == "sqrt" && unary && !matches!(args[0], Number::Float(_)) {
+        return Some(args[0].sqrt(digits));
+    }
+
+    match args.first()? {
+        Number::Float(_) => None,
+        Number::Decimal(x) => {
+            let result = match name {
+                "abs" if unary => x.abs(),
+                "floor" if unary => x.with_scale_round(0, RoundingMode::Floor),
+                "ceil" if unary => x.with_scale_round(0, RoundingMode::Ceiling),
+                "round" if unary => x.with_scale_round(0, RoundingMode::HalfUp),
+                "min" | "max" => {
+                    let values = args.iter().map(|arg| to_decimal(arg, digits));
+                    if name == "min" { values.min()? } else { values.max()? }
+                }
+                _ => return None,
+            };
+            Some(Ok(Number::Decimal(result)))
+        }
+        Number::Rational(x) => {
+            let result = match name {
+                "abs" if unary => x.abs(),
+                "floor" if unary => x.floor(),
+                "ceil" if unary => x.ceil(),
+                "round" if unary => x.round(),
+                "min" | "max" => {
+                    // f64 が混ざっている場合は f64 で比較する
+                    let values = args
+                        .iter()
+                        .map(|arg| match arg {
+                            Number::Rational(value) => Some(value.clone()),
+                            _ => None,
+                        })
+                        .collect::<Option<Vec<BigRational>>>()?
+                        .into_iter();
+                    if name == "min" { values.min()? } else { values.max()? }
+                }
+                _ => return None,
+            };
+            Some(Ok(Number::Rational(result)))
+        }
+        Number::Integer(x) => {
+            let result = match name {
+                "abs" if unary => x.abs(),
+                "floor" | "ceil" | "round" if unary => x.clone(),
+                "min" | "max" => {
+                    let values = args
+                        .iter()
+                        .map(|arg| match arg {
+                            Number::Integer(value) => Some(value.clone()),
+                            _ => None,
+                        })
+                        .collect::<Option<Vec<BigInt>>>()?
+                        .into_iter();
+                    if name == "min" { values.min()? } else { values.max()? }
+                }
+                _ => return None,
+            };
+            Some(Ok(Number::Integer(result)))
+        }
+    }
+}
+
+impl fmt::Display for Number {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Number::Float(value) => write!(f, "{}", value),
+            Number::Decimal(value) => {
+                let value = value.normalized();
+                if value.is_integer() {
+                    // normalized() は 100 を 1E+2 と表すので整数は桁を戻して表示する
+                    write!(f, "{}", value.with_scale(0).to_plain_string())
+                } else {
+                    write!(f, "{}", value.to_plain_string())
+                }
+            }
+            // 整数なら分母を省略する
+            Number::Rational(value) => write!(f, "{}", value),
+            Number::Integer(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+// ビット演算の種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BitOp {
+    And,
+    Or,
+    Xor,
+    Shl,
+    Shr,
+}
+
+// ビット演算の結果をオペランドと同じ種類の数値にする
+fn integer_like(value: BigInt, a: &Number, b: &Number) -> Number {
+    match (a, b) {
+        (Number::Decimal(_), _) | (_, Number::Decimal(_)) => Number::Decimal(BigDecimal::from(value)),
+        (Number::Float(_), _) | (_, Number::Float(_)) => Number::Float(value.to_f64().unwrap_or(f64::INFINITY)),
+        (Number::Rational(_), _) | (_, Number::Rational(_)) => Number::Rational(BigRational::from_integer(value)),
+        _ => Number::Integer(value),
+    }
+}
+
+// 二項演算のオペランド
+// 整数と分数なら分数、10進数が混ざれば10進数、それ以外で f64 が混ざれば f64 にそろえる
+enum Operands {
+    Float(f64, f64),
+    Decimal(BigDecimal, BigDecimal),
+    Rational(BigRational, BigRational),
+    Integer(BigInt, BigInt),
+}
+
+fn promote(a: &Number, b: &Number, digits: u64) -> Operands {
+    match (a, b) {
+        (Number::Integer(a), Number::Integer(b)) => Operands::Integer(a.clone(), b.clone()),
+        (Number::Rational(_) | Number::Integer(_), Number::Rational(_) | Number::Integer(_)) => {
+            Operands::Rational(to_rational(a), to_rational(b))
+        }
+        (Number::Decimal(_), _) | (_, Number::Decimal(_)) => {
+            Operands::Decimal(to_decimal(a, digits), to_decimal(b, digits))
+        }
+        _ => Operands::Float(a.to_f64(), b.to_f64()),
+    }
+}
+
+fn parse_decimal(text: &str) -> Result<BigDecimal, CalcError> {
+    BigDecimal::from_str(text).map_err(|_| CalcError::InvalidExpression(format!("Invalid number '{}'", text)))
+}
+
+fn to_decimal(number: &Number, digits: u64) -> BigDecimal {
+    match number {
+        Number::Float(value) => f64_to_decimal(*value),
+        Number::Decimal(value) => value.clone(),
+        Number::Rational(value) => divide_decimal(
+            &BigDecimal::from(value.numer().clone()),
+            &BigDecimal::from(value.denom().clone()),
+            digits,
+        ),
+        Number::Integer(value) => BigDecimal::from(value.clone()),
+    }
+}
+
+// 正確な値どうしの切り捨て除算の商
+fn floor_quotient(a: &BigRational, b: &BigRational) -> Result<BigInt, CalcError> {
+    if b.is_zero() {
+        return Err(CalcError::DivisionByZero);
+    }
+    Ok((a / b).floor().to_integer())
+}
+
+// 整数と分数を分数にそろえる
+fn to_rational(number: &Number) -> BigRational {
+    match number {
+        Number::Integer(value) => BigRational::from_integer(value.clone()),
+        Number::Rational(value) => value.clone(),
+        _ => unreachable!("only integers and fractions are converted to fractions"),
+    }
+}
+
+// 10進数の値を分数にする（1.25 → 5/4）
+fn decimal_to_rational(value: &BigDecimal) -> BigRational {
+    let (digits, scale) = value.as_bigint_and_exponent();
+    let ten = BigInt::from(10);
+    if scale >= 0 {
+        BigRational::new(digits, ten.pow(scale as u32))
+    } else {
+        BigRational::from_integer(digits * ten.pow(scale.unsigned_abs() as u32))
+    }
+}
+
+// f64 の最短表記を経由して変換する（0.1 が 0.1000000000000000055... にならないように）
+// value × 10^places を整数に丸めてから戻す
+fn round_rational(value: &BigRational, places: i64, mode: RoundingMode) -> BigRational {
+    let power = BigRational::from_integer(BigInt::from(10).pow(places.unsigned_abs() as u32));
+    let scaled = if places >= 0 { value * &power } else { value / &power };
+    let truncated = scaled.trunc();
+    let fraction = (&scaled - &truncated).abs();
+    let away = if scaled.is_negative() { &truncated - BigRational::one() } else { &truncated + BigRational::one() };
+    // 端数がちょうど半分か、それより大きいか
+    let half = fraction.cmp(&BigRational::new(BigInt::from(1), BigInt::from(2)));
+    let rounded = if fraction.is_zero() {
+        truncated
+    } else {
+        match mode {
+            RoundingMode::Down => truncated,
+            RoundingMode::Up => away,
+            RoundingMode::Floor => scaled.floor(),
+            RoundingMode::Ceiling => scaled.ceil(),
+            RoundingMode::HalfUp => if half == Ordering::Less { truncated } else { away },
+            RoundingMode::HalfDown => if half == Ordering::Greater { away } else { truncated },
+            RoundingMode::HalfEven => match half {
+                Ordering::Less => truncated,
+                Ordering::Greater => away,
+                Ordering::Equal if truncated.to_integer() % 2 == BigInt::ZERO => truncated,
+                Ordering::Equal => away,
+            },
+        }
+    };
+    if places >= 0 { rounded / power } else { rounded * power }
+}
+
+fn f64_to_decimal(value: f64) -> BigDecimal {
+    BigDecimal::from_str(&value.to_string()).unwrap_or_default()
+}
+
+fn divide_decimal(a: &BigDecimal, b: &BigDecimal, digits: u64) -> BigDecimal {
+    // 逆数は少し多めの桁で求めてから丸める
+    let inverse = context(digits + 10).invert(b);
+    round(a * inverse, digits)
+}
+
+// a^b = exp(b·ln a)（a は正）
+// b·ln a の誤差がそのまま結果の相対誤差になるので、b と ln a の大きさの分だけ桁を多めにとる
+fn decimal_pow(a: &BigDecimal, b: &BigDecimal, digits: u64) -> Result<BigDecimal, CalcError> {
+    let (exp, ln) = (b.to_f64().unwrap_or(f64::INFINITY), ln_estimate(a));
+    let scale = (exp * ln).abs();
+    if !scale.is_finite() || scale > MAX_DECIMAL_EXPONENT as f64 * std::f64::consts::LN_10 {
+        return Err(CalcError::InvalidExpression("Result overflow or invalid".to_string()));
+    }
+    let magnitude = |value: f64| value.abs().max(1.0).log10().ceil() as u64;
+    let working = digits + 10 + magnitude(exp) + magnitude(ln);
+    let exponent = round(b * decimal_ln(a, working), working);
+    Ok(round(exponent.exp_with_context(&context(working)), digits))
+}
+
+// 正の数の自然対数を有効桁数 digits で求める
+// e^y = x を Halley 法で解く（1回ごとに正しい桁がおよそ3倍になる）
+fn decimal_ln(x: &BigDecimal, digits: u64) -> BigDecimal {
+    let estimate = ln_estimate(x);
+    let integer_digits = estimate.abs().max(1.0).log10().ceil() as i64;
+    let tolerance = BigDecimal::new(BigInt::one(), digits as i64 - integer_digits);
+    let mut y = f64_to_decimal(estimate);
+    for _ in 0..100 {
+        let e = y.exp_with_context(&context(digits));
+        let delta = divide_decimal(&((x - &e) * BigDecimal::from(2)), &(x + &e), digits);
+        y = round(y + &delta, digits);
+        if delta.abs() < tolerance {
+            break;
+        }
+    }
+    y
+}
+
+// ln の初期値（f64 に収まらない大きさなら桁数から見積もる）
+fn ln_estimate(x: &BigDecimal) -> f64 {
+    match x.to_f64() {
+        Some(value) if value.is_normal() => value.ln(),
+        _ => (x.digits() as i64 - x.fractional_digit_count()) as f64 * std::f64::consts::LN_10,
+    }
+}
+
+// 平方数なら平方根を返す
+fn exact_sqrt(value: &BigInt) -> Option<BigInt> {
+    let root = value.sqrt();
+    (&root * &root == *value).then_some(root)
+}
+
+fn context(digits: u64) -> Context {
+    let precision = NonZeroU64::new(digits).unwrap_or(NonZeroU64::MIN);
+    Context::new(precision, RoundingMode::HalfEven)
+}
+
+// 有効桁数を超えた場合だけ丸める
+fn round(value: BigDecimal, digits: u64) -> BigDecimal {
+    if value.digits() > digits {
+        context(digits).round_decimal(value)
+    } else {
+        value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decimal(text: &str) -> Number {
+        Number::parse(text, NumberMode::Decimal).unwrap()
+    }
+
+    fn rational(text: &str) -> Number {
+        Number::parse(text, NumberMode::Rational).unwrap()
+    }
+
+    #[test]
+    fn test_decimal_addition_is_exact() {
+        let sum = decimal("0.1").add(&decimal("0.2"), DEFAULT_DIGITS).unwrap();
+        assert_eq!(sum.to_string(), "0.3");
+
+        let float = Number::Float(0.1).add(&Number::Float(0.2), DEFAULT_DIGITS).unwrap();
+        assert_ne!(float.to_string(), "0.3");
+    }
+
+    #[test]
+    fn test_decimal_division_uses_digits() {
+        let third = decimal("1").div(&decimal("3"), 20).unwrap();
+        assert_eq!(third.to_string(), "0.33333333333333333333");
+        assert!(matches!(decimal("1").div(&decimal("0"), 20), Err(CalcError::DivisionByZero)));
+    }
+
+    #[test]
+    fn test_decimal_power_and_sqrt() {
+        assert_eq!(decimal("1.1").pow(&decimal("2"), DEFAULT_DIGITS).unwrap().to_string(), "1.21");
+        assert_eq!(decimal("2").pow(&decimal("-2"), DEFAULT_DIGITS).unwrap().to_string(), "0.25");
+        assert_eq!(decimal("100").pow(&decimal("0.5"), DEFAULT_DIGITS).unwrap().to_string(), "10");
+        assert!(decimal("-8").pow(&decimal("0.5"), DEFAULT_DIGITS).is_err());
+        assert_eq!(decimal("2").sqrt(10).unwrap().to_string(), "1.414213562");
+        assert!(decimal("-4").sqrt(10).is_err());
+    }
+
+    #[test]
+    fn test_decimal_power_with_fractional_exponent() {
+        // 整数でない指数も f64 に落とさず、指定した桁数まで正しい
+        let digits = 60;
+        let power = |a: &str, b: &str| decimal(a).pow(&decimal(b), digits).unwrap();
+        assert_eq!(power("2", "0.5"), decimal("2").sqrt(digits).unwrap());
+        assert_eq!(
+            power("10", "1.5").to_string(),
+            "31.622776601683793319988935444327185337195551393252168268575"
+        );
+        assert_eq!(
+            power("2", "-0.25").to_string(),
+            "0.840896415253714543031125476233214895040034262356784510813226"
+        );
+        assert_eq!(
+            power("1.0001", "12345.6789").to_string(),
+            "3.43668095071904560952524686970924253736750311543702827171727"
+        );
+        // f64 に収まらない大きさでも10進数なら計算できる
+        assert_eq!(power("1e-400", "0.5"), decimal("1e-200"));
+        assert_eq!(power("10", "1000.5"), decimal("3.16227766016837933199889354443271853371955513932521682685750e1000"));
+        assert!(decimal("10").pow(&decimal("1e7").add(&decimal("0.5"), digits).unwrap(), digits).is_err());
+    }
+
+    #[test]
+    fn test_mixed_operands_promote_to_decimal() {
+        let result = Number::Float(0.1).mul(&decimal("3"), DEFAULT_DIGITS).unwrap();
+        assert_eq!(result, decimal("0.3"));
+    }
+
+    #[test]
+    fn test_exact_functions() {
+        let call = |name: &str, args: &[&str]| {
+            let args: Vec<Number> = args.iter().map(|a| decimal(a)).collect();
+            call_exact(name, &args, DEFAULT_DIGITS).map(|r| r.unwrap().to_string())
+        };
+        assert_eq!(call("round", &["2.5"]), Some("3".to_string()));
+        assert_eq!(call("floor", &["-2.5"]), Some("-3".to_string()));
+        assert_eq!(call("max", &["0.1", "0.3", "0.2"]), Some("0.3".to_string()));
+        assert_eq!(call("sin", &["1"]), None);
+    }
+
+    #[test]
+    fn test_round_places() {
+        let round = |number: Number, places, mode| number.round_places(places, mode).to_string();
+        assert_eq!(round(Number::Float(2.675), 2, RoundingMode::HalfUp), "2.68");
+        assert_eq!(round(Number::Float(-2.5), 0, RoundingMode::HalfEven), "-2");
+        assert_eq!(round(decimal("1.005"), 2, RoundingMode::HalfEven), "1");
+        assert_eq!(round(decimal("1.015"), 2, RoundingMode::HalfEven), "1.02");
+        assert_eq!(round(decimal("-1.234"), 1, RoundingMode::Floor), "-1.3");
+        assert_eq!(round(rational("2.5"), 0, RoundingMode::HalfEven), "2");
+        assert_eq!(round(rational("-3.5"), 0, RoundingMode::HalfEven), "-4");
+        assert_eq!(round(rational("-2.5"), 0, RoundingMode::HalfUp), "-3");
+        assert_eq!(round(rational("0.125"), 2, RoundingMode::HalfDown), "3/25");
+        assert_eq!(round(rational("1.21"), 1, RoundingMode::Ceiling), "13/10");
+        assert_eq!(round(rational("-1.29"), 1, RoundingMode::Down), "-6/5");
+        let integer = |text: &str| Number::parse(text, NumberMode::Integer).unwrap();
+        assert_eq!(round(integer("1250"), -2, RoundingMode::HalfEven), "1200");
+        assert_eq!(round(integer("-1251"), -2, RoundingMode::Up), "-1300");
+        assert_eq!(round(integer("7"), 2, RoundingMode::Floor), "7");
+    }
+
+    #[test]
+    fn test_rational_arithmetic() {
+        let third = rational("1").div(&rational("3"), DEFAULT_DIGITS).unwrap();
+        let sixth = rational("1").div(&rational("6"), DEFAULT_DIGITS).unwrap();
+        assert_eq!(third.add(&sixth, DEFAULT_DIGITS).unwrap().to_string(), "1/2");
+        assert_eq!(rational("0.1").add(&rational("0.2"), DEFAULT_DIGITS).unwrap().to_string(), "3/10");
+        assert_eq!(rational("1.5e2").to_string(), "150");
+        assert!(matches!(rational("1").div(&rational("0"), DEFAULT_DIGITS), Err(CalcError::DivisionByZero)));
+    }
+
+    #[test]
+    fn test_rational_power_and_sqrt() {
+        assert_eq!(rational("2").pow(&rational("-2"), DEFAULT_DIGITS).unwrap().to_string(), "1/4");
+        assert_eq!(rational("0.5").pow(&rational("3"), DEFAULT_DIGITS).unwrap().to_string(), "1/8");
+        assert!(matches!(rational("2").pow(&rational("0.5"), DEFAULT_DIGITS).unwrap(), Number::Float(_)));
+        assert_eq!(rational("2.25").sqrt(DEFAULT_DIGITS).unwrap().to_string(), "3/2");
+        assert!(matches!(rational("2").sqrt(DEFAULT_DIGITS).unwrap(), Number::Float(_)));
+    }
+
+    #[test]
+    fn test_rational_falls_back_to_float() {
+        let result = rational("1").div(&rational("3"), DEFAULT_DIGITS).unwrap().mul(&Number::Float(0.5), DEFAULT_DIGITS);
+        assert!(matches!(result.unwrap(), Number::Float(_)));
+    }
+
+    #[test]
+    fn test_to_decimal() {
+        let third = rational("1").div(&rational("3"), DEFAULT_DIGITS).unwrap();
+        let result = call_exact("to_decimal", &[third], 10).unwrap().unwrap();
+        assert_eq!(result.to_string(), "0.3333333333");
+        assert_eq!(call_exact("round", &[rational("2.5")], 10).unwrap().unwrap().to_string(), "3");
+        assert_eq!(call_exact("min", &[rational("1"), rational("0.5")], 10).unwrap().unwrap().to_string(), "1/2");
+    }
+
+    #[test]
+    fn test_convert() {
+        let float = Number::Float(0.1);
+        assert_eq!(float.convert(NumberMode::Rational, DEFAULT_DIGITS).to_string(), "1/10");
+        assert_eq!(float.convert(NumberMode::Decimal, DEFAULT_DIGITS).to_string(), "0.1");
+        assert_eq!(float.convert(NumberMode::Integer, DEFAULT_DIGITS), float);
+        let third = rational("1").div(&rational("3"), DEFAULT_DIGITS).unwrap();
+        assert_eq!(third.convert(NumberMode::Decimal, 5).to_string(), "0.33333");
+        assert!(matches!(third.convert(NumberMode::Float, DEFAULT_DIGITS), Number::Float(_)));
+        assert_eq!(rational("6").convert(NumberMode::Integer, DEFAULT_DIGITS), Number::parse("6", NumberMode::Integer).unwrap());
+        assert_eq!(Number::Float(f64::INFINITY).convert(NumberMode::Rational, DEFAULT_DIGITS), Number::Float(f64::INFINITY));
+    }
+
+    #[test]
+    fn test_integer_arithmetic() {
+        let integer = |text: &str| Number::parse(text, NumberMode::Integer).unwrap();
+        let big = integer("2").pow(&integer("200"), DEFAULT_DIGITS).unwrap();
+        assert_eq!(big.to_string(), "1606938044258990275541962092341162602522202993782792835301376");
+        assert_eq!(big.sub(&big, DEFAULT_DIGITS).unwrap(), integer("0"));
+        assert_eq!(integer("1e3").to_string(), "1000");
+        assert_eq!(integer("12").div(&integer("4"), DEFAULT_DIGITS).unwrap(), integer("3"));
+        assert_eq!(integer("7").div(&integer("2"), DEFAULT_DIGITS).unwrap(), Number::Float(3.5));
+        assert_eq!(integer("2").pow(&integer("-1"), DEFAULT_DIGITS).unwrap(), Number::Float(0.5));
+        assert_eq!(integer("144").sqrt(DEFAULT_DIGITS).unwrap(), integer("12"));
+        assert!(integer("-4").sqrt(DEFAULT_DIGITS).is_err());
+        assert!(matches!(integer("1.5"), Number::Float(_)));
+    }
+
+    #[test]
+    fn test_modulo_and_floor_division() {
+        let integer = |text: &str| Number::parse(text, NumberMode::Integer).unwrap();
+        assert_eq!(integer("-7").rem(&integer("3"), DEFAULT_DIGITS).unwrap(), integer("2"));
+        assert_eq!(integer("-7").floor_div(&integer("2"), DEFAULT_DIGITS).unwrap(), integer("-4"));
+        assert_eq!(integer("10").rem(&integer("1180591620717411303424"), DEFAULT_DIGITS).unwrap(), integer("10"));
+        assert_eq!(decimal("5.5").rem(&decimal("0.2"), DEFAULT_DIGITS).unwrap().to_string(), "0.1");
+        assert_eq!(decimal("5.5").floor_div(&decimal("0.2"), DEFAULT_DIGITS).unwrap().to_string(), "27");
+        assert_eq!(rational("3.5").rem(&rational("1"), DEFAULT_DIGITS).unwrap().to_string(), "1/2");
+        assert!(matches!(integer("1").rem(&integer("0"), DEFAULT_DIGITS), Err(CalcError::DivisionByZero)));
+        assert!(matches!(decimal("1").floor_div(&decimal("0"), DEFAULT_DIGITS), Err(CalcError::DivisionByZero)));
+    }
+
+    #[test]
+    fn test_bitwise() {
+        let integer = |text: &str| Number::parse(text, NumberMode::Integer).unwrap();
+        assert_eq!(integer("0xF0").bitwise(&integer("0x3C"), BitOp::And).unwrap(), integer("0x30"));
+        assert_eq!(integer("0xF0").bitwise(&integer("0x0F"), BitOp::Or).unwrap(), integer("255"));
+        assert_eq!(integer("6").bitwise(&integer("3"), BitOp::Xor).unwrap(), integer("5"));
+        assert_eq!(integer("1").bitwise(&integer("100"), BitOp::Shl).unwrap().to_string(), "1267650600228229401496703205376");
+        assert_eq!(integer("-16").bitwise(&integer("2"), BitOp::Shr).unwrap(), integer("-4"));
+        assert_eq!(integer("5").bitwise(&integer("1000"), BitOp::Shr).unwrap(), integer("0"));
+        assert_eq!(integer("5").not().unwrap(), integer("-6"));
+        assert_eq!(Number::Float(12.0).bitwise(&Number::Float(10.0), BitOp::And).unwrap(), Number::Float(8.0));
+        assert!(Number::Float(1.5).bitwise(&integer("1"), BitOp::Or).is_err());
+        assert!(integer("1").bitwise(&integer("-1"), BitOp::Shl).is_err());
+        assert!(integer("1").bitwise(&integer("99999999"), BitOp::Shl).is_err());
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(decimal("1200").to_string(), "1200");
+        assert_eq!(decimal("1.500").to_string(), "1.5");
+        assert_eq!(decimal("-0.000012").to_string(), "-0.000012");
+        assert_eq!(Number::Float(2.5).to_string(), "2.5");
+    }
+}
@@ -0,0 +1,171 @@
+// 最大公約数・最小公倍数・素数判定・素因数分解
+use num_bigint::{BigInt, BigUint};
+
+// Miller-Rabin 法の底（3.3 × 10^24 未満では判定が確定する）
+const WITNESSES: [u64; 12] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37];
+
+pub fn gcd(a: &BigInt, b: &BigInt) -> BigInt {
+    let (mut a, mut b) = (a.magnitude().clone(), b.magnitude().clone());
+    while b != BigUint::ZERO {
+        let remainder = &a % &b;
+        a = b;
+        b = remainder;
+    }
+    BigInt::from(a)
+}
+
+// 0 を含む場合は 0
+pub fn lcm(a: &BigInt, b: &BigInt) -> BigInt {
+    if a.magnitude() == &BigUint::ZERO || b.magnitude() == &BigUint::ZERO {
+        return BigInt::ZERO;
+    }
+    BigInt::from(a.magnitude() / gcd(a, b).magnitude() * b.magnitude())
+}
+
+// 素数判定（非常に大きな数では確率的な判定になる）
+pub fn is_prime(n: &BigInt) -> bool {
+    let Some(n) = n.to_biguint() else {
+        return false;
+    };
+    if n < BigUint::from(2u32) {
+        return false;
+    }
+    for &p in &WITNESSES {
+        if n == BigUint::from(p) {
+            return true;
+        }
+        if (&n % p) == BigUint::ZERO {
+            return false;
+        }
+    }
+
+    // n - 1 = d * 2^s
+    let one = BigUint::from(1u32);
+    let n_minus_one = &n - &one;
+    let s = n_minus_one.trailing_zeros().unwrap_or(0);
+    let d = &n_minus_one >> s;
+
+    WITNESSES.iter().all(|&a| {
+        let mut x = BigUint::from(a).modpow(&d, &n);
+        if x == one || x == n_minus_one {
+            return true;
+        }
+        for _ in 1..s {
+            x = x.modpow(&BigUint::from(2u32), &n);
+            if x == n_minus_one {
+                return true;
+            }
+        }
+        false
+    })
+}
+
+// 素因数分解（素因数と指数の組を昇順で返す。1 は空）
+pub fn factor(n: u64) -> Vec<(u64, u32)> {
+    let mut primes = Vec::new();
+    collect_factors(n, &mut primes);
+    primes.sort_unstable();
+
+    let mut factors: Vec<(u64, u32)> = Vec::new();
+    for p in primes {
+        match factors.last_mut() {
+            Some((last, count)) if *last == p => *count += 1,
+            _ => factors.push((p, 1)),
+        }
+    }
+    factors
+}
+
+// 2^3 * 3^2 * 5 の形式
+pub fn format_factors(factors: &[(u64, u32)]) -> String {
+    factors
+        .iter()
+        .map(|&(p, k)| if k == 1 { p.to_string() } else { format!("{}^{}", p, k) })
+        .collect::<Vec<_>>()
+        .join(" * ")
+}
+
+fn collect_factors(mut n: u64, primes: &mut Vec<u64>) {
+    // 小さな素因数は割り算で取り除く
+    for p in WITNESSES {
+        while n.is_multiple_of(p) {
+            primes.push(p);
+            n /= p;
+        }
+    }
+    if n == 1 {
+        return;
+    }
+    if is_prime(&BigInt::from(n)) {
+        primes.push(n);
+        return;
+    }
+    let divisor = pollard_rho(n);
+    collect_factors(divisor, primes);
+    collect_factors(n / divisor, primes);
+}
+
+// Pollard のロー法で n の非自明な約数を1つ見つける（n は合成数で小さな素因数を持たない）
+fn pollard_rho(n: u64) -> u64 {
+    for c in 1u128.. {
+        // x^2 + c (mod n) は u128 で計算してあふれを防ぐ
+        let step = |x: u64| ((u128::from(x) * u128::from(x) + c) % u128::from(n)) as u64;
+        let (mut x, mut y, mut d) = (2, 2, 1);
+        while d == 1 {
+            x = step(x);
+            y = step(step(y));
+            d = gcd_u64(x.abs_diff(y), n);
+        }
+        if d != n {
+            return d;
+        }
+    }
+    unreachable!("a composite number always has a divisor")
+}
+
+fn gcd_u64(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn big(n: i64) -> BigInt {
+        BigInt::from(n)
+    }
+
+    #[test]
+    fn test_gcd_lcm() {
+        assert_eq!(gcd(&big(12), &big(18)), big(6));
+        assert_eq!(gcd(&big(-12), &big(18)), big(6));
+        assert_eq!(gcd(&big(0), &big(5)), big(5));
+        assert_eq!(lcm(&big(4), &big(6)), big(12));
+        assert_eq!(lcm(&big(0), &big(6)), big(0));
+        assert_eq!(lcm(&big(-3), &big(5)), big(15));
+    }
+
+    #[test]
+    fn test_is_prime() {
+        let primes: Vec<i64> = (0..50).filter(|&n| is_prime(&big(n))).collect();
+        assert_eq!(primes, vec![2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47]);
+        assert!(is_prime(&big(1_000_000_007)));
+        assert!(!is_prime(&big(3_215_031_751))); // 2, 3, 5, 7 に対する強擬素数
+        assert!(!is_prime(&big(-7)));
+        assert!(is_prime(&"170141183460469231731687303715884105727".parse().unwrap())); // 2^127 - 1
+    }
+
+    #[test]
+    fn test_factor() {
+        assert_eq!(factor(360), vec![(2, 3), (3, 2), (5, 1)]);
+        assert_eq!(factor(1), vec![]);
+        assert_eq!(factor(97), vec![(97, 1)]);
+        assert_eq!(factor(600_851_475_143), vec![(71, 1), (839, 1), (1471, 1), (6857, 1)]);
+        assert_eq!(factor(18_446_744_073_709_551_557), vec![(18_446_744_073_709_551_557, 1)]);
+        assert_eq!(factor(1_000_000_016_000_000_063), vec![(1_000_000_007, 1), (1_000_000_009, 1)]);
+        assert_eq!(format_factors(&factor(360)), "2^3 * 3^2 * 5");
+    }
+}
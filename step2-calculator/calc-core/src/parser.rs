@@ -0,0 +1,1288 @@
+// 字句解析と構文解析（Prattパーサー）
+use std::fmt;
+
+use crate::format;
+use crate::radix;
+use crate::CalcError;
+
+// トークンの種類
+#[derive(Debug, Clone, PartialEq)]
+pub enum TokenKind {
+    // 数値リテラルは精度を落とさないよう元の文字列のまま保持する
+    Number(String),
+    Ident(String),
+    Plus,
+    Minus,
+    Star,
+    Slash,
+    SlashSlash,
+    Percent,
+    Bang,
+    Caret,
+    StarStar,
+    Amp,
+    Pipe,
+    Tilde,
+    Shl,
+    Shr,
+    LParen,
+    RParen,
+    LBracket,
+    RBracket,
+    Comma,
+    Equals,
+    EqEq,
+    NotEq,
+    Less,
+    LessEq,
+    Greater,
+    GreaterEq,
+    // 条件式 c ? a : b
+    Question,
+    Colon,
+    // 誤差 5.0 ± 0.1（+/- とも書ける）
+    PlusMinus,
+}
+
+// 入力中の位置（バイトオフセット）と長さ付きのトークン
+#[derive(Debug, Clone, PartialEq)]
+pub struct Token {
+    pub kind: TokenKind,
+    pub position: usize,
+    pub len: usize,
+}
+
+// 構文木
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Number(String),
+    Variable(String),
+    // ベクトル・行列のリテラル [1, 2] / [[1, 2], [3, 4]]
+    List(Vec<Expr>),
+    Call {
+        name: String,
+        args: Vec<Expr>,
+    },
+    Unary {
+        op: UnaryOp,
+        operand: Box<Expr>,
+    },
+    Binary {
+        op: BinaryOp,
+        left: Box<Expr>,
+        right: Box<Expr>,
+    },
+}
+
+// 文（式、変数への代入、関数定義）
+#[derive(Debug, Clone, PartialEq)]
+pub enum Statement {
+    Expr(Expr),
+    Assign {
+        name: String,
+        value: Expr,
+    },
+    FunctionDef {
+        name: String,
+        params: Vec<String>,
+        body: Expr,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UnaryOp {
+    Neg,
+    Plus,
+    // ビット反転 ~
+    Not,
+    // 階乗 n!（後置）
+    Factorial,
+    // パーセント 15%（後置。15% = 0.15）
+    Percent,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    // 余り %
+    Mod,
+    // 切り捨て除算 //
+    FloorDiv,
+    Pow,
+    And,
+    Or,
+    Xor,
+    Shl,
+    Shr,
+    // 誤差付きの値 5.0 ± 0.1
+    PlusMinus,
+    // 比較（真なら 1、偽なら 0）
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+// 構文の切り替え
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Syntax {
+    // ^ を xor として読む（累乗は **）
+    pub caret_xor: bool,
+    // 暗黙の掛け算（2(3 + 4)、2pi、60 km）を認めない
+    pub strict: bool,
+    // 数字の間のカンマを小数点、ピリオドを3桁ごとの区切りとして読む（1.234,5）
+    // 引数の区切りはカンマの後に空白を入れる（max(1,5, 2)）
+    pub decimal_comma: bool,
+    // 数値の直後の SI 接頭辞を桁として読む（4.7k = 4700、100n = 1e-7）
+    pub si_prefixes: bool,
+}
+
+// 演算子の結合力（大きいほど強く結合する）
+const PREFIX_BP: u8 = 30;
+// 後置の ! と % は累乗より強く結合する（2^3! = 2^6）
+const POSTFIX_BP: u8 = 50;
+
+// 入れ子の深さの上限（括弧、単項演算子、右結合の累乗などの再帰でスタックを使い切らないように）
+// デバッグビルドでは1段に数 KB を使うので、2 MB のスタックのスレッドでも解析と評価ができる深さにする
+pub const MAX_DEPTH: usize = 128;
+
+impl BinaryOp {
+    // (左の結合力, 右の結合力)
+    // 右の方が小さい演算子は右結合になる
+    fn binding_power(self) -> (u8, u8) {
+        match self {
+            // 比較は最も弱い（a & 1 == 0 は (a & 1) == 0）
+            BinaryOp::Eq | BinaryOp::Ne | BinaryOp::Lt | BinaryOp::Le | BinaryOp::Gt | BinaryOp::Ge => (1, 2),
+            // ビット演算は算術演算より弱く | < xor < & < シフトの順
+            BinaryOp::Or => (2, 3),
+            BinaryOp::Xor => (4, 5),
+            BinaryOp::And => (6, 7),
+            BinaryOp::Shl | BinaryOp::Shr => (8, 9),
+            BinaryOp::Add | BinaryOp::Sub => (10, 11),
+            BinaryOp::Mul | BinaryOp::Div | BinaryOp::Mod | BinaryOp::FloorDiv => (20, 21),
+            // -2^2 = -(2^2) となるよう単項演算子より強く、右結合
+            BinaryOp::Pow => (41, 40),
+            // 5 ± 0.1 * 2 と (5 ± 0.1)^2 になるよう累乗より強い
+            BinaryOp::PlusMinus => (43, 44),
+        }
+    }
+}
+
+// 文字列をトークン列に分解
+pub fn tokenize(input: &str, syntax: Syntax) -> Result<Vec<Token>, CalcError> {
+    let mut tokens = Vec::new();
+    let mut chars = input.char_indices().peekable();
+
+    while let Some(&(position, c)) = chars.peek() {
+        let kind = match c {
+            c if c.is_whitespace() => {
+                chars.next();
+                continue;
+            }
+            '.' if syntax.decimal_comma => {
+                let error = syntax_error(position, 1, "unexpected '.'");
+                return Err(with_hint(error, "with a decimal comma, write decimals as 3,14 and group digits as 1.234.567"));
+            }
+            '0'..='9' | '.' => {
+                // 0xFF・0b1010・0o755 は整数、それ以外は10進数のリテラル
+                // text は f64 として読める形（小数点をカンマで書いたときは置き換える）
+                let prefixed_len = radix::prefixed_len(&input[position..]);
+                let (number, text, valid) = match prefixed_len {
+                    Some(len) => {
+                        let number = &input[position..position + len];
+                        (number, number.to_string(), matches!(radix::parse_prefixed(number), Some(Ok(_))))
+                    }
+                    None if syntax.decimal_comma => {
+                        let (number, text) = lex_decimal_comma_number(input, position);
+                        let valid = text.parse::<f64>().is_ok();
+                        (number, text, valid)
+                    }
+                    None => {
+                        let number = lex_number(input, position);
+                        (number, number.to_string(), number.parse::<f64>().is_ok())
+                    }
+                };
+                if !valid {
+                    return Err(syntax_error(position, number.len(), format!("invalid number '{}'", number)));
+                }
+                for _ in 0..number.len() {
+                    chars.next();
+                }
+                let mut text = text;
+                let mut len = number.len();
+                if syntax.si_prefixes
+                    && let Some((prefix, exponent)) = si_suffix(&input[position + len..])
+                    && prefixed_len.is_none()
+                    && !text.contains(['e', 'E'])
+                {
+                    chars.next();
+                    text = format!("{}e{}", text, exponent);
+                    len += prefix.len_utf8();
+                }
+                tokens.push(Token { kind: TokenKind::Number(text), position, len });
+                continue;
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let ident = lex_ident(input, position);
+                for _ in ident.chars() {
+                    chars.next();
+                }
+                tokens.push(Token { kind: TokenKind::Ident(ident.to_string()), position, len: ident.len() });
+                continue;
+            }
+            '+' if input[position..].starts_with("+/-") => {
+                chars.next();
+                chars.next();
+                TokenKind::PlusMinus
+            }
+            '+' => TokenKind::Plus,
+            '-' => TokenKind::Minus,
+            '*' if input[position..].starts_with("**") => {
+                chars.next();
+                TokenKind::StarStar
+            }
+            '<' if input[position..].starts_with("<<") => {
+                chars.next();
+                TokenKind::Shl
+            }
+            '>' if input[position..].starts_with(">>") => {
+                chars.next();
+                TokenKind::Shr
+            }
+            '<' if input[position..].starts_with("<=") => {
+                chars.next();
+                TokenKind::LessEq
+            }
+            '>' if input[position..].starts_with(">=") => {
+                chars.next();
+                TokenKind::GreaterEq
+            }
+            '=' if input[position..].starts_with("==") => {
+                chars.next();
+                TokenKind::EqEq
+            }
+            '!' if input[position..].starts_with("!=") => {
+                chars.next();
+                TokenKind::NotEq
+            }
+            '/' if input[position..].starts_with("//") => {
+                chars.next();
+                TokenKind::SlashSlash
+            }
+            '*' => TokenKind::Star,
+            '/' => TokenKind::Slash,
+            '%' => TokenKind::Percent,
+            '!' => TokenKind::Bang,
+            '^' => TokenKind::Caret,
+            '&' => TokenKind::Amp,
+            '|' => TokenKind::Pipe,
+            '~' => TokenKind::Tilde,
+            '(' => TokenKind::LParen,
+            ')' => TokenKind::RParen,
+            '[' => TokenKind::LBracket,
+            ']' => TokenKind::RBracket,
+            ',' => TokenKind::Comma,
+            '=' => TokenKind::Equals,
+            '<' => TokenKind::Less,
+            '>' => TokenKind::Greater,
+            '?' => TokenKind::Question,
+            ':' => TokenKind::Colon,
+            '±' => TokenKind::PlusMinus,
+            other => {
+                let error = syntax_error(position, other.len_utf8(), format!("unexpected character '{}'", other));
+                return Err(match other {
+                    '×' | '·' => with_hint(error, "use '*' for multiplication"),
+                    '÷' => with_hint(error, "use '/' for division"),
+                    '−' => with_hint(error, "use '-' (ASCII hyphen) for subtraction"),
+                    _ => error,
+                });
+            }
+        };
+        chars.next();
+        let end = chars.peek().map_or(input.len(), |&(end, _)| end);
+        tokens.push(Token { kind, position, len: end - position });
+    }
+
+    Ok(tokens)
+}
+
+// 数値リテラル（例: 12, 3.5, .5, 1e-3）の範囲を切り出す
+fn lex_number(input: &str, start: usize) -> &str {
+    let bytes = input.as_bytes();
+    let mut end = start;
+
+    while end < bytes.len() && (bytes[end].is_ascii_digit() || bytes[end] == b'.') {
+        end += 1;
+    }
+
+    &input[start..exponent_end(bytes, end)]
+}
+
+// 数値の直後の SI 接頭辞（後ろに名前の文字が続く 2km や 3ms は接頭辞として読まない）
+fn si_suffix(rest: &str) -> Option<(char, i64)> {
+    let mut chars = rest.chars();
+    let prefix = chars.next()?;
+    if chars.next().is_some_and(|c| c.is_alphanumeric() || c == '_') {
+        return None;
+    }
+    format::si_prefix_exponent(prefix).map(|exponent| (prefix, exponent))
+}
+
+// 指数部は後ろに数字が続く場合だけ取り込む
+fn exponent_end(bytes: &[u8], end: usize) -> usize {
+    if end < bytes.len() && (bytes[end] == b'e' || bytes[end] == b'E') {
+        let mut exp_end = end + 1;
+        if exp_end < bytes.len() && (bytes[exp_end] == b'+' || bytes[exp_end] == b'-') {
+            exp_end += 1;
+        }
+        if exp_end < bytes.len() && bytes[exp_end].is_ascii_digit() {
+            while exp_end < bytes.len() && bytes[exp_end].is_ascii_digit() {
+                exp_end += 1;
+            }
+            return exp_end;
+        }
+    }
+    end
+}
+
+// 小数点をカンマで書いた数（1.234.567,89）の範囲と、ピリオドで書き直した数
+// ピリオドは後ろにちょうど3桁が続くときだけ区切りとして読み、カンマは後ろに数字が続くときだけ小数点にする
+fn lex_decimal_comma_number(input: &str, start: usize) -> (&str, String) {
+    let bytes = input.as_bytes();
+    let digits = |from: usize| bytes[from..].iter().take_while(|b| b.is_ascii_digit()).count();
+    let mut end = start + digits(start);
+    let mut text = input[start..end].to_string();
+    while end < bytes.len() && bytes[end] == b'.' && digits(end + 1) == 3 {
+        text.push_str(&input[end + 1..end + 4]);
+        end += 4;
+    }
+    if end < bytes.len() && bytes[end] == b',' && digits(end + 1) > 0 {
+        let fraction = &input[end + 1..end + 1 + digits(end + 1)];
+        text.push('.');
+        text.push_str(fraction);
+        end += 1 + fraction.len();
+    }
+    let exponent = exponent_end(bytes, end);
+    text.push_str(&input[end..exponent]);
+    (&input[start..exponent], text)
+}
+
+// 識別子（英字または_で始まり、英数字と_が続く）の範囲を切り出す
+fn lex_ident(input: &str, start: usize) -> &str {
+    let end = input[start..]
+        .char_indices()
+        .find(|&(_, c)| !(c.is_alphanumeric() || c == '_'))
+        .map(|(i, _)| start + i)
+        .unwrap_or(input.len());
+    &input[start..end]
+}
+
+// 変数や関数の名前として書けるか（入力全体が1つの識別子か）
+pub fn is_identifier(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_alphabetic() || c == '_') && lex_ident(name, 0).len() == name.len()
+}
+
+// 「r = 3; pi * r^2」を文ごとに分ける（空の文は除く）
+// 全て空なら input をそのまま返す（解析すると空の入力のエラーになる）
+pub fn split_statements(input: &str) -> Vec<&str> {
+    let statements: Vec<&str> = input.split(';').map(str::trim).filter(|statement| !statement.is_empty()).collect();
+    if statements.is_empty() {
+        return vec![input];
+    }
+    statements
+}
+
+// 続きの行が必要な入力か（括弧が閉じていない、または演算子やカンマで終わっている）
+// 対話モードで長い式を複数行に分けて入力するのに使う
+pub fn is_incomplete(input: &str) -> bool {
+    let Ok(tokens) = tokenize(input, Syntax::default()) else {
+        return false;
+    };
+    let depth = tokens.iter().fold(0i32, |depth, token| match token.kind {
+        TokenKind::LParen | TokenKind::LBracket => depth + 1,
+        TokenKind::RParen | TokenKind::RBracket => depth - 1,
+        _ => depth,
+    });
+    // 後置の ! と % の後は続きがなくてもよい
+    let dangling = tokens.last().is_some_and(|token| {
+        !matches!(
+            token.kind,
+            TokenKind::Number(_) | TokenKind::Ident(_) | TokenKind::RParen | TokenKind::RBracket | TokenKind::Bang | TokenKind::Percent
+        )
+    });
+    depth > 0 || dangling
+}
+
+// 式全体を構文解析
+pub fn parse(input: &str, syntax: Syntax) -> Result<Expr, CalcError> {
+    let mut parser = Parser::new(input, syntax)?;
+    let expr = parser.parse_expr(0)?;
+    parser.expect_end()?;
+    Ok(expr)
+}
+
+// 代入・関数定義を含む文を構文解析
+//
+// `x = 式` は代入、`f(x, y) = 式` は関数定義、それ以外は式として扱う
+pub fn parse_statement(input: &str, syntax: Syntax) -> Result<Statement, CalcError> {
+    let mut parser = Parser::new(input, syntax)?;
+    let target = parser.parse_expr(0)?;
+
+    let equals = match parser.peek() {
+        Some(Token { kind: TokenKind::Equals, position, .. }) => *position,
+        _ => {
+            parser.expect_end()?;
+            return Ok(Statement::Expr(target));
+        }
+    };
+    parser.next();
+    let value = parser.parse_expr(0)?;
+    parser.expect_end()?;
+
+    match target {
+        Expr::Variable(name) => Ok(Statement::Assign { name, value }),
+        Expr::Call { name, args } => {
+            let params = args
+                .into_iter()
+                .map(|arg| match arg {
+                    Expr::Variable(param) => Ok(param),
+                    _ => Err(syntax_error(equals, 1, format!("parameters of '{}' must be plain names", name))),
+                })
+                .collect::<Result<Vec<String>, CalcError>>()?;
+            Ok(Statement::FunctionDef { name, params, body: value })
+        }
+        _ => Err(with_hint(
+            syntax_error(equals, 1, "left side of '=' must be a variable or a function signature"),
+            "use '==' to compare values",
+        )),
+    }
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    input_len: usize,
+    syntax: Syntax,
+    // 今の parse_expr の入れ子の深さ
+    depth: usize,
+}
+
+impl Parser {
+    fn new(input: &str, syntax: Syntax) -> Result<Self, CalcError> {
+        Ok(Parser {
+            tokens: tokenize(input, syntax)?,
+            pos: 0,
+            input_len: input.len(),
+            syntax,
+            depth: 0,
+        })
+    }
+
+    // 全てのトークンを消費したことを確認
+    fn expect_end(&self) -> Result<(), CalcError> {
+        let Some(token) = self.peek() else {
+            return Ok(());
+        };
+        let error = error_at(token, format!("unexpected {}", describe(&token.kind)));
+        Err(match token.kind {
+            TokenKind::RParen => with_hint(error, "this ')' has no matching '('"),
+            TokenKind::RBracket => with_hint(error, "this ']' has no matching '['"),
+            _ => self.missing_operator(error, self.pos),
+        })
+    }
+
+    // 値の直後に値が続いたら演算子の書き忘れとみなす（2 (3 + 4)、max(1 2)）
+    fn missing_operator(&self, error: CalcError, index: usize) -> CalcError {
+        let follows_value = matches!(
+            self.tokens.get(index.wrapping_sub(1)).map(|t| &t.kind),
+            Some(TokenKind::Number(_) | TokenKind::Ident(_) | TokenKind::RParen | TokenKind::RBracket | TokenKind::Bang)
+        );
+        if follows_value && self.operand_at(index) {
+            with_hint(error, "insert an operator such as '*' between the two values")
+        } else {
+            error
+        }
+    }
+
+    // 値が来るべき位置のエラー（2 + * 3 → expected a number after '+' but found '*'）
+    fn expected_operand(&self, found: Option<&Token>) -> CalcError {
+        let index = if found.is_some() { self.pos - 1 } else { self.pos };
+        let after = index
+            .checked_sub(1)
+            .and_then(|i| self.tokens.get(i))
+            .map(|previous| format!(" after {}", describe(&previous.kind)))
+            .unwrap_or_default();
+        match found {
+            Some(token) => error_at(token, format!("expected a number{} but found {}", after, describe(&token.kind))),
+            None => self.unexpected_end(&format!("a number{}", after)),
+        }
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        if token.is_some() {
+            self.pos += 1;
+        }
+        token
+    }
+
+    // index のトークンが値の始まりになれるか
+    fn operand_at(&self, index: usize) -> bool {
+        match self.tokens.get(index).map(|t| &t.kind) {
+            Some(TokenKind::Ident(name)) => name != "xor",
+            Some(
+                TokenKind::Number(_)
+                | TokenKind::LParen
+                | TokenKind::LBracket
+                | TokenKind::Plus
+                | TokenKind::Minus
+                | TokenKind::Tilde,
+            ) => true,
+            _ => false,
+        }
+    }
+
+    // 暗黙の掛け算の右側が続くか（strict でなく、直前が値の終わりで次が名前か '('）
+    fn implicit_operand_follows(&self) -> bool {
+        let after_value = matches!(
+            self.tokens.get(self.pos.wrapping_sub(1)).map(|t| &t.kind),
+            Some(TokenKind::Number(_) | TokenKind::RParen | TokenKind::Bang | TokenKind::Percent)
+        );
+        let operand = match self.peek().map(|t| &t.kind) {
+            Some(TokenKind::Ident(name)) => name != "xor",
+            Some(TokenKind::LParen) => true,
+            _ => false,
+        };
+        !self.syntax.strict && after_value && operand
+    }
+
+    // 入力の終端に達したときのエラー
+    fn unexpected_end(&self, expected: &str) -> CalcError {
+        syntax_error(self.input_len, 0, format!("expected {} but reached end of input", expected))
+    }
+
+    // 再帰は全てここを通るので、入れ子の深さはここで数える
+    fn parse_expr(&mut self, min_bp: u8) -> Result<Expr, CalcError> {
+        if self.depth >= MAX_DEPTH {
+            let error = match self.peek() {
+                Some(token) => error_at(token, "expression is nested too deeply"),
+                None => syntax_error(self.input_len, 0, "expression is nested too deeply"),
+            };
+            return Err(with_hint(error, &format!("use at most {} levels of nested parentheses, function calls and unary operators", MAX_DEPTH)));
+        }
+        self.depth += 1;
+        let result = self.parse_binary(min_bp);
+        self.depth -= 1;
+        result
+    }
+
+    // min_bp より強く結合する演算子だけを取り込みながら式を解析
+    fn parse_binary(&mut self, min_bp: u8) -> Result<Expr, CalcError> {
+        let mut left = self.parse_prefix()?;
+
+        loop {
+            // % は後ろに値が続けば余り、続かなければパーセント
+            let postfix = match self.peek().map(|t| &t.kind) {
+                Some(TokenKind::Bang) => Some(UnaryOp::Factorial),
+                Some(TokenKind::Percent) if !self.operand_at(self.pos + 1) => Some(UnaryOp::Percent),
+                _ => None,
+            };
+            if let Some(op) = postfix {
+                if POSTFIX_BP < min_bp {
+                    break;
+                }
+                self.next();
+                left = Expr::Unary { op, operand: Box::new(left) };
+                continue;
+            }
+
+            // 条件式は最も弱く右結合（a ? b : c ? d : e は a ? b : (c ? d : e)）
+            // if(c, a, b) と同じ呼び出しにして、選ばれた側だけを評価する
+            if let Some(Token { kind: TokenKind::Question, .. }) = self.peek() {
+                if min_bp > 0 {
+                    break;
+                }
+                let question = self.next().expect("peeked");
+                let then = self.parse_expr(0)?;
+                match self.next() {
+                    Some(Token { kind: TokenKind::Colon, .. }) => {}
+                    Some(other) => return Err(error_at(&other, format!("expected ':' but found {}", describe(&other.kind)))),
+                    None => {
+                        let hint = format!("the '?' at position {} needs ': value' for when the condition is false", question.position);
+                        return Err(with_hint(self.unexpected_end("':'"), &hint));
+                    }
+                }
+                let otherwise = self.parse_expr(0)?;
+                left = Expr::Call { name: "if".to_string(), args: vec![left, then, otherwise] };
+                continue;
+            }
+
+            // 閉じ括弧や後置演算子の直後の '(' と名前は掛け算（(a + b)(a - b)、(1 + 2)x、3!x）
+            if self.implicit_operand_follows() {
+                let (left_bp, right_bp) = BinaryOp::Mul.binding_power();
+                if left_bp < min_bp {
+                    break;
+                }
+                let right = self.parse_expr(right_bp)?;
+                left = Expr::Binary { op: BinaryOp::Mul, left: Box::new(left), right: Box::new(right) };
+                continue;
+            }
+
+            let op = match self.peek().map(|t| &t.kind) {
+                Some(TokenKind::Plus) => BinaryOp::Add,
+                Some(TokenKind::Minus) => BinaryOp::Sub,
+                Some(TokenKind::Star) => BinaryOp::Mul,
+                Some(TokenKind::Slash) => BinaryOp::Div,
+                Some(TokenKind::SlashSlash) => BinaryOp::FloorDiv,
+                Some(TokenKind::Percent) => BinaryOp::Mod,
+                Some(TokenKind::Caret) if self.syntax.caret_xor => BinaryOp::Xor,
+                Some(TokenKind::Caret | TokenKind::StarStar) => BinaryOp::Pow,
+                Some(TokenKind::Amp) => BinaryOp::And,
+                Some(TokenKind::Pipe) => BinaryOp::Or,
+                Some(TokenKind::Shl) => BinaryOp::Shl,
+                Some(TokenKind::Shr) => BinaryOp::Shr,
+                Some(TokenKind::PlusMinus) => BinaryOp::PlusMinus,
+                Some(TokenKind::EqEq) => BinaryOp::Eq,
+                Some(TokenKind::NotEq) => BinaryOp::Ne,
+                Some(TokenKind::Less) => BinaryOp::Lt,
+                Some(TokenKind::LessEq) => BinaryOp::Le,
+                Some(TokenKind::Greater) => BinaryOp::Gt,
+                Some(TokenKind::GreaterEq) => BinaryOp::Ge,
+                Some(TokenKind::Ident(name)) if name == "xor" => BinaryOp::Xor,
+                _ => break,
+            };
+
+            let (left_bp, right_bp) = op.binding_power();
+            if left_bp < min_bp {
+                break;
+            }
+            self.next();
+
+            let right = self.parse_expr(right_bp)?;
+            left = Expr::Binary {
+                op,
+                left: Box::new(left),
+                right: Box::new(right),
+            };
+        }
+
+        Ok(left)
+    }
+
+    // 数値・括弧・単項演算子
+    fn parse_prefix(&mut self) -> Result<Expr, CalcError> {
+        let token = self.next().ok_or_else(|| self.expected_operand(None))?;
+
+        match token.kind {
+            TokenKind::Number(text) => {
+                // 数値の直後の名前と '(' は掛け算とみなす（60 km は 60 * km、2(3 + 4) は 2 * (3 + 4)）
+                // 累乗だけは先に結び付けるので 2 x^2 は 2 * x^2
+                let number = Expr::Number(text);
+                if !self.implicit_operand_follows() {
+                    return Ok(number);
+                }
+                let factor = self.parse_expr(PREFIX_BP)?;
+                Ok(Expr::Binary {
+                    op: BinaryOp::Mul,
+                    left: Box::new(number),
+                    right: Box::new(factor),
+                })
+            }
+            TokenKind::Ident(name) => {
+                // 直後に '(' があれば関数呼び出し、なければ変数・定数
+                if let Some(Token { kind: TokenKind::LParen, .. }) = self.peek() {
+                    self.next();
+                    let args = self.parse_list(TokenKind::RParen)?;
+                    Ok(Expr::Call { name, args })
+                } else {
+                    Ok(Expr::Variable(name))
+                }
+            }
+            TokenKind::Minus | TokenKind::Plus | TokenKind::Tilde => {
+                let op = match token.kind {
+                    TokenKind::Minus => UnaryOp::Neg,
+                    TokenKind::Plus => UnaryOp::Plus,
+                    _ => UnaryOp::Not,
+                };
+                let operand = self.parse_expr(PREFIX_BP)?;
+                Ok(Expr::Unary {
+                    op,
+                    operand: Box::new(operand),
+                })
+            }
+            TokenKind::LParen => {
+                let expr = self.parse_expr(0)?;
+                match self.next() {
+                    Some(Token { kind: TokenKind::RParen, .. }) => Ok(expr),
+                    Some(other) => {
+                        let error = error_at(&other, format!("expected ')' but found {}", describe(&other.kind)));
+                        Err(self.missing_operator(error, self.pos - 1))
+                    }
+                    None => Err(with_hint(
+                        self.unexpected_end("')'"),
+                        &format!("the '(' at position {} is never closed", token.position),
+                    )),
+                }
+            }
+            TokenKind::LBracket => Ok(Expr::List(self.parse_list(TokenKind::RBracket)?)),
+            _ => Err(self.expected_operand(Some(&token))),
+        }
+    }
+
+    // カンマ区切りの式の並び（関数の引数リストやベクトルの要素）
+    // 開き括弧の直後から閉じ括弧 close までを読む
+    fn parse_list(&mut self, close: TokenKind) -> Result<Vec<Expr>, CalcError> {
+        let open = self.tokens[self.pos - 1].clone();
+        let mut items = Vec::new();
+        if self.peek().is_some_and(|t| t.kind == close) {
+            self.next();
+            return Ok(items);
+        }
+
+        loop {
+            items.push(self.parse_expr(0)?);
+            match self.next() {
+                Some(Token { kind: TokenKind::Comma, .. }) => continue,
+                Some(token) if token.kind == close => return Ok(items),
+                Some(other) => {
+                    let error = error_at(&other, format!("expected ',' or {} but found {}", describe(&close), describe(&other.kind)));
+                    return Err(self.missing_operator(error, self.pos - 1));
+                }
+                None => {
+                    let hint = format!("the {} at position {} is never closed", describe(&open.kind), open.position);
+                    return Err(with_hint(self.unexpected_end(&describe(&close)), &hint));
+                }
+            }
+        }
+    }
+}
+
+fn syntax_error(position: usize, len: usize, message: impl Into<String>) -> CalcError {
+    CalcError::SyntaxError { position, len, message: message.into(), hint: None }
+}
+
+// トークン全体を指す構文エラー
+fn error_at(token: &Token, message: impl Into<String>) -> CalcError {
+    syntax_error(token.position, token.len, message)
+}
+
+fn with_hint(error: CalcError, text: &str) -> CalcError {
+    match error {
+        CalcError::SyntaxError { position, len, message, .. } => {
+            CalcError::SyntaxError { position, len, message, hint: Some(text.to_string()) }
+        }
+        other => other,
+    }
+}
+
+// エラーメッセージ用のトークン表記
+fn describe(kind: &TokenKind) -> String {
+    match kind {
+        TokenKind::Number(text) => format!("number {}", text),
+        TokenKind::Ident(name) => format!("'{}'", name),
+        TokenKind::Plus => "'+'".to_string(),
+        TokenKind::Minus => "'-'".to_string(),
+        TokenKind::Star => "'*'".to_string(),
+        TokenKind::Slash => "'/'".to_string(),
+        TokenKind::Caret => "'^'".to_string(),
+        TokenKind::SlashSlash => "'//'".to_string(),
+        TokenKind::Percent => "'%'".to_string(),
+        TokenKind::Bang => "'!'".to_string(),
+        TokenKind::StarStar => "'**'".to_string(),
+        TokenKind::Amp => "'&'".to_string(),
+        TokenKind::Pipe => "'|'".to_string(),
+        TokenKind::Tilde => "'~'".to_string(),
+        TokenKind::Shl => "'<<'".to_string(),
+        TokenKind::Shr => "'>>'".to_string(),
+        TokenKind::LParen => "'('".to_string(),
+        TokenKind::RParen => "')'".to_string(),
+        TokenKind::LBracket => "'['".to_string(),
+        TokenKind::RBracket => "']'".to_string(),
+        TokenKind::Comma => "','".to_string(),
+        TokenKind::Equals => "'='".to_string(),
+        TokenKind::EqEq => "'=='".to_string(),
+        TokenKind::NotEq => "'!='".to_string(),
+        TokenKind::Less => "'<'".to_string(),
+        TokenKind::LessEq => "'<='".to_string(),
+        TokenKind::Greater => "'>'".to_string(),
+        TokenKind::GreaterEq => "'>='".to_string(),
+        TokenKind::Question => "'?'".to_string(),
+        TokenKind::PlusMinus => "'±'".to_string(),
+        TokenKind::Colon => "':'".to_string(),
+    }
+}
+
+impl BinaryOp {
+    pub fn symbol(self) -> &'static str {
+        match self {
+            BinaryOp::Add => "+",
+            BinaryOp::Sub => "-",
+            BinaryOp::Mul => "*",
+            BinaryOp::Div => "/",
+            BinaryOp::Mod => "%",
+            BinaryOp::FloorDiv => "//",
+            BinaryOp::Pow => "^",
+            BinaryOp::And => "&",
+            BinaryOp::Or => "|",
+            // ^ は構文の設定で意味が変わるので表示にはキーワードを使う
+            BinaryOp::Xor => "xor",
+            BinaryOp::Shl => "<<",
+            BinaryOp::Shr => ">>",
+            BinaryOp::PlusMinus => "±",
+            BinaryOp::Eq => "==",
+            BinaryOp::Ne => "!=",
+            BinaryOp::Lt => "<",
+            BinaryOp::Le => "<=",
+            BinaryOp::Gt => ">",
+            BinaryOp::Ge => ">=",
+        }
+    }
+
+    // 表示用の優先順位
+    fn precedence(self) -> u8 {
+        self.binding_power().0.min(self.binding_power().1)
+    }
+
+    fn is_right_associative(self) -> bool {
+        let (left, right) = self.binding_power();
+        right < left
+    }
+}
+
+impl Expr {
+    fn precedence(&self) -> u8 {
+        match self {
+            Expr::Binary { op, .. } => op.precedence(),
+            Expr::Unary { op: UnaryOp::Factorial | UnaryOp::Percent, .. } => POSTFIX_BP,
+            Expr::Unary { .. } => PREFIX_BP,
+            _ => u8::MAX,
+        }
+    }
+}
+
+// 構文木を必要最小限の括弧付きで式に戻す
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Expr::Number(text) => write!(f, "{}", text),
+            Expr::Variable(name) => write!(f, "{}", name),
+            Expr::List(items) => {
+                write!(f, "[")?;
+                write_list(f, items)?;
+                write!(f, "]")
+            }
+            Expr::Call { name, args } => {
+                write!(f, "{}(", name)?;
+                write_list(f, args)?;
+                write!(f, ")")
+            }
+            Expr::Unary { op: op @ (UnaryOp::Factorial | UnaryOp::Percent), operand } => {
+                let symbol = if *op == UnaryOp::Factorial { "!" } else { "%" };
+                if operand.precedence() < POSTFIX_BP {
+                    write!(f, "({}){}", operand, symbol)
+                } else {
+                    write!(f, "{}{}", operand, symbol)
+                }
+            }
+            Expr::Unary { op, operand } => {
+                let symbol = match op {
+                    UnaryOp::Neg => "-",
+                    UnaryOp::Plus => "+",
+                    UnaryOp::Not => "~",
+                    UnaryOp::Factorial | UnaryOp::Percent => unreachable!("postfix operators are written above"),
+                };
+                if operand.precedence() < PREFIX_BP {
+                    write!(f, "{}({})", symbol, operand)
+                } else {
+                    write!(f, "{}{}", symbol, operand)
+                }
+            }
+            Expr::Binary { op, left, right } => {
+                let precedence = op.precedence();
+                let left_parens = left.precedence() < precedence
+                    || (left.precedence() == precedence && op.is_right_associative());
+                let right_parens = right.precedence() < precedence
+                    || (right.precedence() == precedence && !op.is_right_associative());
+
+                if left_parens {
+                    write!(f, "({})", left)?;
+                } else {
+                    write!(f, "{}", left)?;
+                }
+                write!(f, " {} ", op.symbol())?;
+                if right_parens {
+                    write!(f, "({})", right)
+                } else {
+                    write!(f, "{}", right)
+                }
+            }
+        }
+    }
+}
+
+fn write_list(f: &mut fmt::Formatter<'_>, items: &[Expr]) -> fmt::Result {
+    for (i, item) in items.iter().enumerate() {
+        if i > 0 {
+            write!(f, ", ")?;
+        }
+        write!(f, "{}", item)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn num(text: &str) -> Box<Expr> {
+        Box::new(Expr::Number(text.to_string()))
+    }
+
+    fn parse(input: &str) -> Result<Expr, CalcError> {
+        super::parse(input, Syntax::default())
+    }
+
+    fn parse_statement(input: &str) -> Result<Statement, CalcError> {
+        super::parse_statement(input, Syntax::default())
+    }
+
+    #[test]
+    fn test_tokenize() {
+        let kinds: Vec<TokenKind> = tokenize("(1.5+2)*3e2", Syntax::default()).unwrap().into_iter().map(|t| t.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                TokenKind::LParen,
+                TokenKind::Number("1.5".to_string()),
+                TokenKind::Plus,
+                TokenKind::Number("2".to_string()),
+                TokenKind::RParen,
+                TokenKind::Star,
+                TokenKind::Number("3e2".to_string()),
+            ]
+        );
+
+        let kinds: Vec<TokenKind> = tokenize("0xFF+0b1_0", Syntax::default()).unwrap().into_iter().map(|t| t.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                TokenKind::Number("0xFF".to_string()),
+                TokenKind::Plus,
+                TokenKind::Number("0b1_0".to_string()),
+            ]
+        );
+        assert!(matches!(tokenize("1 + 0b12", Syntax::default()), Err(CalcError::SyntaxError { position: 4, .. })));
+        assert_eq!(tokenize("5±0.1 +/- 2", Syntax::default()).unwrap().iter().filter(|t| t.kind == TokenKind::PlusMinus).count(), 2);
+    }
+
+    #[test]
+    fn test_token_positions() {
+        let positions: Vec<usize> = tokenize(" 12 + 3", Syntax::default()).unwrap().into_iter().map(|t| t.position).collect();
+        assert_eq!(positions, vec![1, 4, 6]);
+    }
+
+    #[test]
+    fn test_precedence() {
+        assert_eq!(
+            parse("1 + 2 * 3").unwrap(),
+            Expr::Binary {
+                op: BinaryOp::Add,
+                left: num("1"),
+                right: Box::new(Expr::Binary { op: BinaryOp::Mul, left: num("2"), right: num("3") }),
+            }
+        );
+    }
+
+    #[test]
+    fn test_left_associativity() {
+        assert_eq!(
+            parse("8 - 4 - 2").unwrap(),
+            Expr::Binary {
+                op: BinaryOp::Sub,
+                left: Box::new(Expr::Binary { op: BinaryOp::Sub, left: num("8"), right: num("4") }),
+                right: num("2"),
+            }
+        );
+    }
+
+    #[test]
+    fn test_power_right_associativity() {
+        assert_eq!(
+            parse("2 ^ 3 ^ 2").unwrap(),
+            Expr::Binary {
+                op: BinaryOp::Pow,
+                left: num("2"),
+                right: Box::new(Expr::Binary { op: BinaryOp::Pow, left: num("3"), right: num("2") }),
+            }
+        );
+    }
+
+    #[test]
+    fn test_unary_minus_binds_looser_than_power() {
+        assert_eq!(
+            parse("-2^2").unwrap(),
+            Expr::Unary {
+                op: UnaryOp::Neg,
+                operand: Box::new(Expr::Binary { op: BinaryOp::Pow, left: num("2"), right: num("2") }),
+            }
+        );
+    }
+
+    #[test]
+    fn test_function_calls() {
+        assert_eq!(
+            parse("max(1, 2 + 3)").unwrap(),
+            Expr::Call {
+                name: "max".to_string(),
+                args: vec![
+                    Expr::Number("1".to_string()),
+                    Expr::Binary { op: BinaryOp::Add, left: num("2"), right: num("3") },
+                ],
+            }
+        );
+        assert_eq!(
+            parse("f()").unwrap(),
+            Expr::Call { name: "f".to_string(), args: vec![] }
+        );
+        assert!(matches!(parse("sin 1"), Err(CalcError::SyntaxError { position: 4, .. })));
+        assert_eq!(
+            parse("2 * pi").unwrap(),
+            Expr::Binary { op: BinaryOp::Mul, left: num("2"), right: Box::new(Expr::Variable("pi".to_string())) }
+        );
+        assert!(matches!(parse("max(1, 2"), Err(CalcError::SyntaxError { position: 8, .. })));
+        assert!(matches!(parse("max(1 2)"), Err(CalcError::SyntaxError { position: 6, .. })));
+    }
+
+    #[test]
+    fn test_list_literals() {
+        assert_eq!(
+            parse("[[1, 2], [x, -1]]").unwrap(),
+            Expr::List(vec![
+                Expr::List(vec![*num("1"), *num("2")]),
+                Expr::List(vec![
+                    Expr::Variable("x".to_string()),
+                    Expr::Unary { op: UnaryOp::Neg, operand: num("1") },
+                ]),
+            ])
+        );
+        assert_eq!(parse("[]").unwrap(), Expr::List(vec![]));
+        assert!(matches!(parse("[1, 2"), Err(CalcError::SyntaxError { position: 5, .. })));
+        assert!(matches!(parse("[1, 2)"), Err(CalcError::SyntaxError { position: 5, .. })));
+    }
+
+    #[test]
+    fn test_implicit_multiplication() {
+        assert_eq!(parse("60 km / 1.5 h").unwrap(), parse("(60 * km) / (1.5 * h)").unwrap());
+        assert_eq!(parse("2 x ^ 2").unwrap(), parse("2 * x ^ 2").unwrap());
+        assert_eq!(parse("1 / 2 m").unwrap().to_string(), "1 / (2 * m)");
+        assert_eq!(parse("2 sin(x)").unwrap(), parse("2 * sin(x)").unwrap());
+        assert_eq!(parse("2pi").unwrap(), parse("2 * pi").unwrap());
+        assert_eq!(parse("2(3 + 4)^2").unwrap(), parse("2 * (3 + 4) ^ 2").unwrap());
+        assert_eq!(parse("(a + b)(a - b)").unwrap(), parse("(a + b) * (a - b)").unwrap());
+        assert_eq!(parse("1 + (2)x / 4").unwrap(), parse("1 + 2 * x / 4").unwrap());
+        assert_eq!(parse("3!x").unwrap(), parse("3! * x").unwrap());
+        // 名前の後の '(' は関数呼び出し、数値同士は掛け算にしない
+        assert_eq!(parse("f(2)").unwrap(), Expr::Call { name: "f".to_string(), args: vec![*num("2")] });
+        assert!(matches!(parse("(1)2"), Err(CalcError::SyntaxError { position: 3, .. })));
+
+        let strict = Syntax { strict: true, ..Syntax::default() };
+        for input in ["2 sin(x)", "2pi", "2(3)", "(1)(2)", "60 km"] {
+            assert!(matches!(super::parse(input, strict), Err(CalcError::SyntaxError { .. })), "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_factorial() {
+        assert_eq!(parse("3!").unwrap(), Expr::Unary { op: UnaryOp::Factorial, operand: num("3") });
+        assert_eq!(parse("-3!").unwrap(), parse("-(3!)").unwrap());
+        assert_eq!(parse("2 ^ 3!").unwrap(), parse("2 ^ (3!)").unwrap());
+        assert_eq!(parse("3!!").unwrap().to_string(), "3!!");
+        assert!(matches!(parse("!3"), Err(CalcError::SyntaxError { position: 0, .. })));
+    }
+
+    #[test]
+    fn test_percent_suffix() {
+        let percent = |operand| Expr::Unary { op: UnaryOp::Percent, operand };
+        assert_eq!(
+            parse("200 * 15%").unwrap(),
+            Expr::Binary { op: BinaryOp::Mul, left: num("200"), right: Box::new(percent(num("15"))) }
+        );
+        assert_eq!(parse("max(5%, 7 % 3)").unwrap().to_string(), "max(5%, 7 % 3)");
+        assert_eq!(parse("(1 + 2)% * 3").unwrap().to_string(), "(1 + 2)% * 3");
+        assert_eq!(parse("7 % -3").unwrap(), parse("7 % (-3)").unwrap());
+        assert_eq!(parse("x%%").unwrap().to_string(), "x%%");
+    }
+
+    #[test]
+    fn test_modulo_and_floor_division() {
+        assert_eq!(parse("1 + 7 % 3 * 2").unwrap(), parse("1 + ((7 % 3) * 2)").unwrap());
+        assert_eq!(parse("7 // 2 / 3").unwrap(), parse("(7 // 2) / 3").unwrap());
+        assert_eq!(parse("a % (b // 2)").unwrap().to_string(), "a % (b // 2)");
+    }
+
+    #[test]
+    fn test_bitwise_operators() {
+        assert_eq!(parse("1 | 2 xor 3 & 4 << 5 + 6").unwrap(), parse("1 | (2 xor (3 & (4 << (5 + 6))))").unwrap());
+        assert_eq!(parse("2 ** 3").unwrap(), parse("2 ^ 3").unwrap());
+        assert_eq!(super::parse("6 ^ 3", Syntax { caret_xor: true, ..Syntax::default() }).unwrap(), parse("6 xor 3").unwrap());
+        assert_eq!(super::parse("2 ** 3 ^ 1", Syntax { caret_xor: true, ..Syntax::default() }).unwrap(), parse("(2 ^ 3) xor 1").unwrap());
+        assert_eq!(parse("~x >> 1").unwrap(), parse("(~x) >> 1").unwrap());
+        assert_eq!(parse("2 xor 3").unwrap().to_string(), "2 xor 3");
+    }
+
+    #[test]
+    fn test_comparison_operators() {
+        assert_eq!(parse("a & 1 == 0").unwrap(), parse("(a & 1) == 0").unwrap());
+        assert_eq!(parse("1 + 2 <= 3 << 1").unwrap(), parse("(1 + 2) <= (3 << 1)").unwrap());
+        assert_eq!(parse("x != 2").unwrap().to_string(), "x != 2");
+        assert_eq!(parse("a < b > c").unwrap().to_string(), "a < b > c");
+        assert!(matches!(parse_statement("x == 1").unwrap(), Statement::Expr(_)));
+    }
+
+    #[test]
+    fn test_conditional() {
+        let call = |input: &str| parse(input).unwrap().to_string();
+        assert_eq!(call("x > 0 ? x : -x"), "if(x > 0, x, -x)");
+        assert_eq!(call("a ? b : c ? d : e"), "if(a, b, if(c, d, e))");
+        assert_eq!(call("1 + (a ? 2 : 3) * 4"), "1 + if(a, 2, 3) * 4");
+        assert_eq!(call("a == 1 ? b + 1 : c"), "if(a == 1, b + 1, c)");
+        assert!(matches!(parse("a ? b"), Err(CalcError::SyntaxError { position: 5, hint: Some(_), .. })));
+        assert!(matches!(parse("a ? b , c"), Err(CalcError::SyntaxError { position: 6, .. })));
+        assert!(matches!(parse("1 + a ? 2 : 3"), Ok(Expr::Call { .. })));
+    }
+
+    #[test]
+    fn test_statements() {
+        assert_eq!(
+            parse_statement("x = 2").unwrap(),
+            Statement::Assign { name: "x".to_string(), value: Expr::Number("2".to_string()) }
+        );
+        assert_eq!(
+            parse_statement("f(x, y) = x").unwrap(),
+            Statement::FunctionDef {
+                name: "f".to_string(),
+                params: vec!["x".to_string(), "y".to_string()],
+                body: Expr::Variable("x".to_string()),
+            }
+        );
+        assert_eq!(parse_statement("1 + 2").unwrap(), Statement::Expr(parse("1 + 2").unwrap()));
+        assert!(matches!(parse_statement("f(1) = 2"), Err(CalcError::SyntaxError { position: 5, .. })));
+        assert!(matches!(parse_statement("1 + x = 2"), Err(CalcError::SyntaxError { position: 6, .. })));
+        assert!(matches!(parse_statement("x = "), Err(CalcError::SyntaxError { .. })));
+        assert!(parse("x = 1").is_err());
+    }
+
+    #[test]
+    fn test_display_round_trip() {
+        for source in ["1 + 2 * 3", "(1 + 2) * 3", "8 - (4 - 2)", "2 ^ 3 ^ 2", "(2 ^ 3) ^ 2", "-2 ^ 2", "(-2) ^ 2", "-(1 + x)", "max(1, f(x) / 2)", "[[1, 2], [3, 4]] * [x, 2 * y]", "~a & (b | 1 << 4)", "-n! + (n - 1)! * 2 ^ 3!"] {
+            let expr = parse(source).unwrap();
+            assert_eq!(expr.to_string(), source);
+            assert_eq!(parse(&expr.to_string()).unwrap(), expr);
+        }
+    }
+
+    #[test]
+    fn test_plus_minus() {
+        // 掛け算より強く、累乗の底にもなる
+        assert_eq!(parse("5 ± 0.1 * 3").unwrap(), parse("(5 ± 0.1) * 3").unwrap());
+        assert_eq!(parse("5 ± 0.1^2").unwrap(), parse("(5 ± 0.1)^2").unwrap());
+        assert_eq!(parse("(5 ± 0.1) * 3").unwrap().to_string(), "5 ± 0.1 * 3");
+    }
+
+    #[test]
+    fn test_decimal_comma() {
+        let comma = Syntax { decimal_comma: true, ..Syntax::default() };
+        let kinds: Vec<TokenKind> = tokenize("1.234.567,89e2 + 3,5", comma).unwrap().into_iter().map(|t| t.kind).collect();
+        assert_eq!(kinds, vec![TokenKind::Number("1234567.89e2".to_string()), TokenKind::Plus, TokenKind::Number("3.5".to_string())]);
+        assert_eq!(super::parse("max(1,5, 2)", comma).unwrap(), parse("max(1.5, 2)").unwrap());
+        // 3桁でないピリオドは区切りではない
+        assert!(matches!(super::parse("1.5", comma), Err(CalcError::SyntaxError { position: 1, hint: Some(_), .. })));
+        assert_eq!(tokenize("1,2", Syntax::default()).unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_si_prefixes() {
+        let si = Syntax { si_prefixes: true, ..Syntax::default() };
+        let kinds: Vec<TokenKind> = tokenize("4.7k * 100n + 3µ", si).unwrap().into_iter().map(|t| t.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                TokenKind::Number("4.7e3".to_string()),
+                TokenKind::Star,
+                TokenKind::Number("100e-9".to_string()),
+                TokenKind::Plus,
+                TokenKind::Number("3e-6".to_string()),
+            ]
+        );
+        // 名前の一部や指数付きの数は接頭辞として読まない
+        assert_eq!(super::parse("2km", si).unwrap(), parse("2 * km").unwrap());
+        assert_eq!(super::parse("1e3k", si).unwrap(), parse("1e3 * k").unwrap());
+        assert_eq!(parse("2k").unwrap(), parse("2 * k").unwrap());
+        assert_eq!(tokenize("2.2u", si).unwrap()[0].len, 4);
+    }
+
+    #[test]
+    fn test_split_statements() {
+        assert_eq!(split_statements("r = 3; pi * r^2"), vec!["r = 3", "pi * r^2"]);
+        assert_eq!(split_statements(" 1 ;; 2; "), vec!["1", "2"]);
+        assert_eq!(split_statements("2 + 3"), vec!["2 + 3"]);
+        assert_eq!(split_statements(" ; "), vec![" ; "]);
+    }
+
+    #[test]
+    fn test_is_identifier() {
+        for name in ["x", "_", "r_2", "θ"] {
+            assert!(is_identifier(name), "{}", name);
+        }
+        for name in ["", "2x", "a b", "a+b", "x;y", "f(x)"] {
+            assert!(!is_identifier(name), "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_incomplete_input() {
+        for input in ["1 +", "(1 + 2", "max(1,", "[[1, 2],", "f(x) =", "2 **", "sin((x)"] {
+            assert!(is_incomplete(input), "{}", input);
+        }
+        for input in ["1 + 2", "15%", "5!", "(1 + 2))", "", "1 + $"] {
+            assert!(!is_incomplete(input), "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_syntax_errors() {
+        assert!(matches!(parse(""), Err(CalcError::SyntaxError { position: 0, .. })));
+        assert!(matches!(parse("(1 + 2"), Err(CalcError::SyntaxError { position: 6, .. })));
+        assert!(matches!(parse("1 + 2)"), Err(CalcError::SyntaxError { position: 5, .. })));
+        assert!(matches!(parse("2 + * 3"), Err(CalcError::SyntaxError { position: 4, .. })));
+        assert!(matches!(parse("2 $ 3"), Err(CalcError::SyntaxError { position: 2, .. })));
+        assert!(matches!(parse("1..2"), Err(CalcError::SyntaxError { position: 0, .. })));
+        assert!(matches!(parse("2 3"), Err(CalcError::SyntaxError { position: 2, .. })));
+    }
+
+    #[test]
+    fn test_nesting_limit() {
+        // スタックを使い切る前に構文エラーにする
+        for input in ["(".repeat(10_000), "-".repeat(50_000) + "1", "2^".repeat(10_000) + "2", "f(".repeat(10_000)] {
+            match parse(&input) {
+                Err(CalcError::SyntaxError { message, .. }) => assert_eq!(message, "expression is nested too deeply"),
+                other => panic!("expected a syntax error, got {:?}", other.map(|_| ())),
+            }
+        }
+        let nested = format!("{}1{}", "(".repeat(100), ")".repeat(100));
+        assert_eq!(parse(&nested).unwrap(), Expr::Number("1".to_string()));
+        // 上限いっぱいの式は評価までできる
+        let deepest = format!("{}1", "-".repeat(MAX_DEPTH - 1));
+        assert!(crate::Evaluator::default().evaluate_str(&deepest).is_ok());
+        assert!(parse(&format!("-{}", deepest)).is_err());
+    }
+}
@@ -0,0 +1,149 @@
+// 2進・8進・16進の整数リテラルと表示
+use num_bigint::BigInt;
+
+use crate::number::Number;
+use crate::CalcError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+pub enum Radix {
+    /// Binary (0b1010)
+    Bin,
+    /// Octal (0o755)
+    Oct,
+    /// Decimal
+    #[default]
+    Dec,
+    /// Hexadecimal (0xff)
+    Hex,
+}
+
+pub const ALL: [Radix; 4] = [Radix::Bin, Radix::Oct, Radix::Dec, Radix::Hex];
+
+impl Radix {
+    pub fn base(self) -> u32 {
+        match self {
+            Radix::Bin => 2,
+            Radix::Oct => 8,
+            Radix::Dec => 10,
+            Radix::Hex => 16,
+        }
+    }
+
+    pub fn prefix(self) -> &'static str {
+        match self {
+            Radix::Bin => "0b",
+            Radix::Oct => "0o",
+            Radix::Dec => "",
+            Radix::Hex => "0x",
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Radix::Bin => "binary",
+            Radix::Oct => "octal",
+            Radix::Dec => "decimal",
+            Radix::Hex => "hexadecimal",
+        }
+    }
+
+    fn from_prefix(c: u8) -> Option<Radix> {
+        match c.to_ascii_lowercase() {
+            b'b' => Some(Radix::Bin),
+            b'o' => Some(Radix::Oct),
+            b'x' => Some(Radix::Hex),
+            _ => None,
+        }
+    }
+
+    pub fn format(self, value: &BigInt) -> String {
+        let digits = value.magnitude().to_str_radix(self.base());
+        let sign = if value.sign() == num_bigint::Sign::Minus { "-" } else { "" };
+        format!("{}{}{}", sign, self.prefix(), digits)
+    }
+
+    // 整数値ならこの基数で、そうでなければ通常どおり10進で表示
+    pub fn format_number(self, number: &Number) -> String {
+        match number.to_bigint() {
+            Some(value) if self != Radix::Dec => self.format(&value),
+            _ => number.to_string(),
+        }
+    }
+}
+
+// 0x・0b・0o で始まるリテラルの長さ（プレフィックスでなければ None）
+// 桁の区切りとして _ を使える（0xFFFF_FFFF）
+pub fn prefixed_len(input: &str) -> Option<usize> {
+    let bytes = input.as_bytes();
+    if bytes.len() < 2 || bytes[0] != b'0' {
+        return None;
+    }
+    Radix::from_prefix(bytes[1])?;
+    let digits = bytes[2..].iter().take_while(|b| b.is_ascii_alphanumeric() || **b == b'_').count();
+    Some(2 + digits)
+}
+
+// 0xFF のようなリテラルを整数として読む
+pub fn parse_prefixed(text: &str) -> Option<Result<BigInt, CalcError>> {
+    let bytes = text.as_bytes();
+    if bytes.len() < 2 || bytes[0] != b'0' {
+        return None;
+    }
+    let radix = Radix::from_prefix(bytes[1])?;
+    let digits: String = text[2..].chars().filter(|&c| c != '_').collect();
+    Some(
+        BigInt::parse_bytes(digits.as_bytes(), radix.base())
+            .ok_or_else(|| CalcError::InvalidExpression(format!("Invalid {} literal '{}'", radix.name(), text))),
+    )
+}
+
+// 10進または 0x などの付いた整数（先頭の - も可）
+pub fn parse_integer(text: &str) -> Result<BigInt, CalcError> {
+    let (negative, body) = match text.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, text),
+    };
+    let value = match parse_prefixed(body) {
+        Some(result) => result?,
+        None => body
+            .parse::<BigInt>()
+            .map_err(|_| CalcError::InvalidExpression(format!("Not an integer: '{}'", text)))?,
+    };
+    Ok(if negative { -value } else { value })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_prefixed() {
+        assert_eq!(parse_prefixed("0xFF").unwrap().unwrap(), BigInt::from(255));
+        assert_eq!(parse_prefixed("0b1010").unwrap().unwrap(), BigInt::from(10));
+        assert_eq!(parse_prefixed("0o755").unwrap().unwrap(), BigInt::from(493));
+        assert_eq!(parse_prefixed("0xffff_ffff").unwrap().unwrap(), BigInt::from(u32::MAX));
+        assert!(parse_prefixed("0b102").unwrap().is_err());
+        assert!(parse_prefixed("0x").unwrap().is_err());
+        assert!(parse_prefixed("12").is_none());
+        assert_eq!(prefixed_len("0x1F + 1"), Some(4));
+        assert_eq!(prefixed_len("0.5"), None);
+    }
+
+    #[test]
+    fn test_parse_integer() {
+        assert_eq!(parse_integer("-0x10").unwrap(), BigInt::from(-16));
+        assert_eq!(parse_integer("255").unwrap(), BigInt::from(255));
+        assert!(parse_integer("2.5").is_err());
+    }
+
+    #[test]
+    fn test_format() {
+        assert_eq!(Radix::Hex.format(&BigInt::from(255)), "0xff");
+        assert_eq!(Radix::Bin.format(&BigInt::from(-5)), "-0b101");
+        assert_eq!(Radix::Oct.format(&BigInt::from(493)), "0o755");
+        assert_eq!(Radix::Dec.format(&BigInt::from(42)), "42");
+        assert_eq!(Radix::Hex.format_number(&Number::Float(2.5)), "2.5");
+        assert_eq!(Radix::Hex.format_number(&Number::Float(16.0)), "0x10");
+    }
+}
@@ -0,0 +1,146 @@
+// 乱数生成（xoshiro256** をシードから初期化する）
+use std::collections::hash_map::RandomState;
+use std::f64::consts::PI;
+use std::hash::{BuildHasher, Hasher};
+
+use crate::CalcError;
+
+// 乱数の分布
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+pub enum Distribution {
+    /// Uniform real numbers in [min, max)
+    #[default]
+    Uniform,
+    /// Uniform integers in [min, max]
+    Int,
+    /// Normal distribution with --mean and --stddev
+    Normal,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Random {
+    state: [u64; 4],
+}
+
+impl Random {
+    // 同じシードからは常に同じ列を生成する
+    pub fn seeded(seed: u64) -> Random {
+        // SplitMix64 で状態を埋める（全て0の状態を避けるため）
+        let mut x = seed;
+        let mut next = || {
+            x = x.wrapping_add(0x9E37_79B9_7F4A_7C15);
+            let mut z = x;
+            z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+            z ^ (z >> 31)
+        };
+        Random { state: [next(), next(), next(), next()] }
+    }
+
+    // シードを指定しない場合は実行ごとに異なる列
+    pub fn from_entropy() -> Random {
+        Random::seeded(RandomState::new().build_hasher().finish())
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        let [s0, s1, s2, s3] = &mut self.state;
+        let result = s1.wrapping_mul(5).rotate_left(7).wrapping_mul(9);
+        let t = *s1 << 17;
+        *s2 ^= *s0;
+        *s3 ^= *s1;
+        *s1 ^= *s2;
+        *s0 ^= *s3;
+        *s2 ^= t;
+        *s3 = s3.rotate_left(45);
+        result
+    }
+
+    // [0, 1) の一様乱数
+    pub fn uniform(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    pub fn range(&mut self, min: f64, max: f64) -> Result<f64, CalcError> {
+        if !(max - min).is_finite() || max <= min {
+            return Err(CalcError::InvalidExpression(format!("Invalid range: {} to {}", min, max)));
+        }
+        Ok(min + (max - min) * self.uniform())
+    }
+
+    // [min, max] の整数（偏りが出ないよう範囲外の値は捨てる）
+    pub fn int(&mut self, min: i64, max: i64) -> Result<i64, CalcError> {
+        if min > max {
+            return Err(CalcError::InvalidExpression(format!("Invalid range: {} to {}", min, max)));
+        }
+        let span = max.abs_diff(min).wrapping_add(1);
+        if span == 0 {
+            // i64 の全範囲
+            return Ok(self.next_u64() as i64);
+        }
+        let limit = u64::MAX - u64::MAX % span;
+        loop {
+            let value = self.next_u64();
+            if value < limit {
+                return Ok(min.wrapping_add((value % span) as i64));
+            }
+        }
+    }
+
+    // 正規分布（Box-Muller 法）
+    pub fn normal(&mut self, mean: f64, stddev: f64) -> Result<f64, CalcError> {
+        if !stddev.is_finite() || stddev < 0.0 {
+            return Err(CalcError::InvalidExpression(format!(
+                "Standard deviation must be non-negative, got {}", stddev
+            )));
+        }
+        // log(0) を避けるため (0, 1] にする
+        let u1 = 1.0 - self.uniform();
+        let u2 = self.uniform();
+        Ok(mean + stddev * (-2.0 * u1.ln()).sqrt() * (2.0 * PI * u2).cos())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seed_is_reproducible() {
+        let mut a = Random::seeded(42);
+        let mut b = Random::seeded(42);
+        let mut c = Random::seeded(43);
+        let first: Vec<u64> = (0..5).map(|_| a.next_u64()).collect();
+        assert_eq!(first, (0..5).map(|_| b.next_u64()).collect::<Vec<_>>());
+        assert_ne!(first, (0..5).map(|_| c.next_u64()).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_ranges() {
+        let mut random = Random::seeded(1);
+        for _ in 0..1000 {
+            let x = random.range(-2.0, 3.0).unwrap();
+            assert!((-2.0..3.0).contains(&x));
+            let n = random.int(1, 6).unwrap();
+            assert!((1..=6).contains(&n));
+        }
+        assert_eq!(random.int(5, 5).unwrap(), 5);
+        random.int(i64::MIN, i64::MAX).unwrap();
+        assert!(random.int(2, 1).is_err());
+        assert!(random.range(1.0, 1.0).is_err());
+        assert!(random.normal(0.0, -1.0).is_err());
+    }
+
+    #[test]
+    fn test_distribution_moments() {
+        let mut random = Random::seeded(7);
+        let samples: Vec<f64> = (0..20_000).map(|_| random.normal(10.0, 2.0).unwrap()).collect();
+        let mean = samples.iter().sum::<f64>() / samples.len() as f64;
+        let variance = samples.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / samples.len() as f64;
+        assert!((mean - 10.0).abs() < 0.1);
+        assert!((variance.sqrt() - 2.0).abs() < 0.1);
+
+        let uniform = (0..20_000).map(|_| random.uniform()).sum::<f64>() / 20_000.0;
+        assert!((uniform - 0.5).abs() < 0.02);
+    }
+}
@@ -0,0 +1,221 @@
+// 差分テスト用の参照評価器（reference フィーチャ）
+//
+// 数値・+ - * / ^・単項の符号・括弧だけの式を、parser とは別に書いた構文解析器で読み、
+// BigDecimal で正確に計算する（割り算は 100 桁）
+// Evaluator の結果と比べて、文法が増えたときの構文解析や優先順位の退行を見つける
+//   優先順位: ^（右結合、指数に符号を付けられる） > 単項の - + > * / > + -
+use bigdecimal::{BigDecimal, One, ToPrimitive, Zero};
+use std::str::FromStr;
+
+use crate::CalcError;
+
+// 累乗で扱う指数の絶対値の上限
+pub const MAX_EXPONENT: i64 = 64;
+
+pub fn evaluate_str(input: &str) -> Result<BigDecimal, CalcError> {
+    let mut parser = Parser { chars: input.chars().collect(), pos: 0 };
+    let value = parser.expr()?;
+    parser.skip_spaces();
+    match parser.peek() {
+        None => Ok(value),
+        Some(c) => Err(parser.error(&format!("unexpected '{}'", c))),
+    }
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn skip_spaces(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.pos += 1;
+        }
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        self.skip_spaces();
+        if self.peek() == Some(c) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn error(&self, message: &str) -> CalcError {
+        CalcError::InvalidExpression(format!("reference: {} at {}", message, self.pos))
+    }
+
+    fn expr(&mut self) -> Result<BigDecimal, CalcError> {
+        let mut value = self.term()?;
+        loop {
+            if self.eat('+') {
+                value += self.term()?;
+            } else if self.eat('-') {
+                value -= self.term()?;
+            } else {
+                return Ok(value);
+            }
+        }
+    }
+
+    fn term(&mut self) -> Result<BigDecimal, CalcError> {
+        let mut value = self.unary()?;
+        loop {
+            if self.eat('*') {
+                value *= self.unary()?;
+            } else if self.eat('/') {
+                let divisor = self.unary()?;
+                if divisor.is_zero() {
+                    return Err(CalcError::DivisionByZero);
+                }
+                value = value / divisor;
+            } else {
+                return Ok(value);
+            }
+        }
+    }
+
+    fn unary(&mut self) -> Result<BigDecimal, CalcError> {
+        if self.eat('-') {
+            Ok(-self.unary()?)
+        } else if self.eat('+') {
+            self.unary()
+        } else {
+            self.power()
+        }
+    }
+
+    fn power(&mut self) -> Result<BigDecimal, CalcError> {
+        let base = self.atom()?;
+        if !self.eat('^') {
+            return Ok(base);
+        }
+        let exponent = self.unary()?;
+        if !exponent.is_integer() {
+            return Err(self.error("only integer exponents are supported"));
+        }
+        let exponent = exponent.to_i64().filter(|n| n.abs() <= MAX_EXPONENT).ok_or_else(|| self.error("exponent is too large"))?;
+        let mut result = BigDecimal::one();
+        for _ in 0..exponent.abs() {
+            result *= &base;
+        }
+        if exponent >= 0 {
+            Ok(result)
+        } else if result.is_zero() {
+            Err(CalcError::DivisionByZero)
+        } else {
+            Ok(BigDecimal::one() / result)
+        }
+    }
+
+    fn atom(&mut self) -> Result<BigDecimal, CalcError> {
+        if self.eat('(') {
+            let value = self.expr()?;
+            if !self.eat(')') {
+                return Err(self.error("expected ')'"));
+            }
+            return Ok(value);
+        }
+        self.skip_spaces();
+        let start = self.pos;
+        while self.peek().is_some_and(|c| c.is_ascii_digit() || c == '.') {
+            self.pos += 1;
+        }
+        let text: String = self.chars[start..self.pos].iter().collect();
+        if text.is_empty() {
+            return Err(self.error("expected a number"));
+        }
+        BigDecimal::from_str(&text).map_err(|_| self.error(&format!("invalid number '{}'", text)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::evaluator::Evaluator;
+    use crate::functions::AngleMode;
+    use crate::number::{NumberMode, DEFAULT_DIGITS};
+    use crate::parser::{self, Syntax};
+    use proptest::prelude::*;
+    use proptest::test_runner::RngSeed;
+
+    fn reference(input: &str) -> String {
+        evaluate_str(input).unwrap().normalized().to_string()
+    }
+
+    #[test]
+    fn test_reference_evaluator() {
+        assert_eq!(reference("2 + 3 * 4"), "14");
+        assert_eq!(reference("-2^2"), "-4");
+        assert_eq!(reference("2^3^2"), "512");
+        assert_eq!(reference("2^-1^2"), "0.5");
+        assert_eq!(reference("(0.1 + 0.2) * 10 - 3"), "0");
+        assert_eq!(reference("2 - -2"), "4");
+        assert!(matches!(evaluate_str("1 / (2 - 2)"), Err(CalcError::DivisionByZero)));
+        assert!(evaluate_str("2^0.5").is_err());
+        assert!(evaluate_str("(1 + 2").is_err());
+        assert!(evaluate_str("1 2").is_err());
+    }
+
+    // 0 から 99 の整数と小数第1位までの数
+    fn number() -> impl Strategy<Value = String> {
+        prop_oneof![(0u32..100).prop_map(|n| n.to_string()), (0u32..1000).prop_map(|n| format!("{}.{}", n / 10, n % 10))]
+    }
+
+    // 参照評価器の文法に収まる式（指数は 0 から 3 の整数）
+    fn expression() -> impl Strategy<Value = String> {
+        number().prop_recursive(4, 32, 2, |inner| {
+            prop_oneof![
+                (inner.clone(), prop::sample::select(vec!["+", "-", "*", "/"]), inner.clone())
+                    .prop_map(|(left, op, right)| format!("{} {} {}", left, op, right)),
+                inner.clone().prop_map(|expr| format!("({})", expr)),
+                inner.clone().prop_map(|expr| format!("-{}", expr)),
+                (inner, 0u32..4).prop_map(|(base, exponent)| format!("({})^{}", base, exponent)),
+            ]
+        })
+    }
+
+    // 毎回同じ式で試す
+    fn config() -> ProptestConfig {
+        ProptestConfig { cases: 256, failure_persistence: None, rng_seed: RngSeed::Fixed(1703), ..ProptestConfig::default() }
+    }
+
+    proptest! {
+        #![proptest_config(config())]
+
+        #[test]
+        fn decimal_mode_matches_reference(input in expression()) {
+            let evaluator = Evaluator::with_number_mode(AngleMode::Radians, NumberMode::Decimal, DEFAULT_DIGITS);
+            match evaluate_str(&input) {
+                Ok(expected) => {
+                    let actual = evaluator.eval_str(&input).unwrap().into_number().unwrap().to_bigdecimal(DEFAULT_DIGITS);
+                    let tolerance = expected.abs().max(BigDecimal::one()) * BigDecimal::from_str("1e-30").unwrap();
+                    prop_assert!((&actual - &expected).abs() <= tolerance, "{} = {}, expected {}", input, actual, expected);
+                }
+                Err(CalcError::DivisionByZero) => prop_assert!(evaluator.eval_str(&input).is_err(), "{}", input),
+                Err(e) => panic!("{}: {}", input, e),
+            }
+        }
+
+        #[test]
+        fn float_mode_matches_reference(input in expression()) {
+            let Ok(expected) = evaluate_str(&input) else { return Ok(()) };
+            let expected = expected.to_f64().unwrap();
+            let actual = Evaluator::default().eval_str(&input).unwrap().into_number().unwrap().to_f64();
+            prop_assert!((actual - expected).abs() <= 1e-6 * expected.abs().max(1.0), "{} = {}, expected {}", input, actual, expected);
+        }
+
+        #[test]
+        fn display_round_trips(input in expression()) {
+            let expr = parser::parse(&input, Syntax::default()).unwrap();
+            prop_assert_eq!(parser::parse(&expr.to_string(), Syntax::default()).unwrap(), expr);
+        }
+    }
+}
@@ -0,0 +1,517 @@
+// 方程式を解く（calc-cli solve）
+//
+// 両辺の差が未知数の1次式・2次式なら係数から解の公式で解き、
+// それ以外は区間を細かく区切って符号の変わる所を二分法で探す
+//   2*x + 5 = 13       x = 4
+//   x^2 + 2*x + 5 = 0  x = -1 + 2i, x = -1 - 2i
+//   cos(x) = x         x = 0.7390851332151607（数値解）
+//
+// find_root は区間の中の解を1つだけ、二分法かニュートン法で収束の様子とともに求める（calc-cli root）
+use std::ops::RangeInclusive;
+
+use crate::evaluator::{self, Evaluator};
+use crate::parser::{self, BinaryOp, Expr, TokenKind, UnaryOp};
+use crate::value::Value;
+use crate::CalcError;
+
+// 数値解を探す区間の既定値
+pub const DEFAULT_RANGE: RangeInclusive<f64> = -100.0..=100.0;
+
+// 数値解を探すときの区間の分割数
+const SCAN_STEPS: usize = 2000;
+
+// 多項式として扱う累乗の指数の上限
+const MAX_DEGREE: u32 = 64;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Root {
+    Real(f64),
+    Complex { re: f64, im: f64 },
+}
+
+// 解き方
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Method {
+    // 未知数を含まない（13 = 13 や 1 = 2）
+    Constant,
+    Linear,
+    Quadratic,
+    Numeric,
+}
+
+impl Method {
+    pub fn name(self) -> &'static str {
+        match self {
+            Method::Constant => "constant",
+            Method::Linear => "linear",
+            Method::Quadratic => "quadratic",
+            Method::Numeric => "numeric",
+        }
+    }
+}
+
+// find_root の解き方
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+pub enum RootMethod {
+    /// Bisection when f changes sign between the bounds, otherwise Newton's method
+    #[default]
+    Auto,
+    /// Bisection (needs a sign change between the bounds)
+    Bisection,
+    /// Newton's method starting from the middle of the bounds
+    Newton,
+}
+
+impl RootMethod {
+    pub fn name(self) -> &'static str {
+        match self {
+            RootMethod::Auto => "auto",
+            RootMethod::Bisection => "bisection",
+            RootMethod::Newton => "newton",
+        }
+    }
+}
+
+// 収束の様子
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Convergence {
+    pub root: f64,
+    // 実際に使った方法（Auto にはならない）
+    pub method: RootMethod,
+    pub iterations: usize,
+    // 誤差の見積もり（二分法は区間の幅の半分、ニュートン法は最後の修正量）
+    pub error: f64,
+    // 解での関数の値
+    pub residual: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Solution {
+    pub variable: String,
+    pub method: Method,
+    // 全ての値が解になる（x = x）
+    pub identity: bool,
+    pub roots: Vec<Root>,
+}
+
+// 「左辺 = 右辺」を解く（= がなければ「式 = 0」）。未知数を省くと式に1つだけある未知の名前を使う
+pub fn solve(evaluator: &Evaluator, equation: &str, variable: Option<&str>, range: RangeInclusive<f64>) -> Result<Solution, CalcError> {
+    let expr = parse_equation(evaluator, equation)?;
+    let variable = match variable {
+        Some(variable) => variable.to_string(),
+        None => find_unknown(evaluator, &expr)?,
+    };
+    let solution = |method, identity, roots| Solution { variable: variable.clone(), method, identity, roots };
+
+    let Some(coefficients) = polynomial(evaluator, &expr, &variable)? else {
+        return Ok(solution(Method::Numeric, false, numeric_roots(evaluator, &expr, &variable, range)));
+    };
+    Ok(match *coefficients.as_slice() {
+        [] => solution(Method::Constant, true, Vec::new()),
+        [_] => solution(Method::Constant, false, Vec::new()),
+        [b, a] => solution(Method::Linear, false, vec![Root::Real(-b / a + 0.0)]),
+        [c, b, a] => solution(Method::Quadratic, false, quadratic_roots(a, b, c)),
+        _ => solution(Method::Numeric, false, numeric_roots(evaluator, &expr, &variable, range)),
+    })
+}
+
+// 区間 [from, to] で「式 = 0」または方程式の解を1つ求める
+pub fn find_root(
+    evaluator: &Evaluator,
+    equation: &str,
+    variable: Option<&str>,
+    range: RangeInclusive<f64>,
+    method: RootMethod,
+    tolerance: f64,
+    max_iterations: usize,
+) -> Result<(String, Convergence), CalcError> {
+    let expr = parse_equation(evaluator, equation)?;
+    let variable = match variable {
+        Some(variable) => variable.to_string(),
+        None => find_unknown(evaluator, &expr)?,
+    };
+    let (from, to) = (*range.start(), *range.end());
+    let convergence = {
+        let f = function(evaluator, &expr, &variable);
+        let method = match method {
+            RootMethod::Auto if f(from)?.signum() != f(to)?.signum() => RootMethod::Bisection,
+            RootMethod::Auto => RootMethod::Newton,
+            other => other,
+        };
+        match method {
+            RootMethod::Bisection => bisection(&f, from, to, tolerance, max_iterations)?,
+            _ => newton(&f, from + (to - from) / 2.0, tolerance, max_iterations)?,
+        }
+    };
+    Ok((variable, convergence))
+}
+
+// 両辺の差の式にする
+fn parse_equation(evaluator: &Evaluator, equation: &str) -> Result<Expr, CalcError> {
+    let equals: Vec<usize> = parser::tokenize(equation, evaluator.syntax)?
+        .iter()
+        .filter(|token| token.kind == TokenKind::Equals)
+        .map(|token| token.position)
+        .collect();
+    match *equals.as_slice() {
+        [] => parser::parse(equation, evaluator.syntax),
+        [position] => {
+            let left = parser::parse(&equation[..position], evaluator.syntax)?;
+            let right = parser::parse(&equation[position + 1..], evaluator.syntax).map_err(|e| shift(e, position + 1))?;
+            Ok(Expr::Binary { op: BinaryOp::Sub, left: Box::new(left), right: Box::new(right) })
+        }
+        [_, second, ..] => Err(CalcError::SyntaxError {
+            position: second,
+            len: 1,
+            message: "an equation can contain only one '='".to_string(),
+            hint: None,
+        }),
+    }
+}
+
+// 右辺の構文エラーの位置を方程式全体での位置にする
+fn shift(error: CalcError, offset: usize) -> CalcError {
+    match error {
+        CalcError::SyntaxError { position, len, message, hint } => CalcError::SyntaxError { position: position + offset, len, message, hint },
+        other => other,
+    }
+}
+
+// 変数・定数・単位のどれでもない名前（出てきた順）
+fn unknowns(evaluator: &Evaluator, expr: &Expr, names: &mut Vec<String>) {
+    match expr {
+        Expr::Number(_) => {}
+        Expr::Variable(name) => {
+            let unknown = matches!(evaluator.evaluate(expr), Err(CalcError::UnknownVariable(_)));
+            if unknown && !names.contains(name) {
+                names.push(name.clone());
+            }
+        }
+        // sum と prod のループ変数は未知数ではない
+        Expr::Call { name, args } if let Some(variable) = evaluator::loop_variable(name, args) => {
+            let mut inner = Vec::new();
+            args[1..].iter().for_each(|arg| unknowns(evaluator, arg, &mut inner));
+            for name in inner {
+                if name != variable && !names.contains(&name) {
+                    names.push(name);
+                }
+            }
+        }
+        Expr::List(items) | Expr::Call { args: items, .. } => items.iter().for_each(|item| unknowns(evaluator, item, names)),
+        Expr::Unary { operand, .. } => unknowns(evaluator, operand, names),
+        Expr::Binary { left, right, .. } => {
+            unknowns(evaluator, left, names);
+            unknowns(evaluator, right, names);
+        }
+    }
+}
+
+pub fn find_unknown(evaluator: &Evaluator, expr: &Expr) -> Result<String, CalcError> {
+    let mut names = Vec::new();
+    unknowns(evaluator, expr, &mut names);
+    match names.len() {
+        0 => Err(CalcError::InvalidExpression("the equation has no unknown to solve for".to_string())),
+        1 => Ok(names.remove(0)),
+        _ => Err(CalcError::InvalidExpression(format!("the equation has several unknowns ({}); choose one to solve for", names.join(", ")))),
+    }
+}
+
+pub fn contains(expr: &Expr, variable: &str) -> bool {
+    match expr {
+        Expr::Number(_) => false,
+        Expr::Variable(name) => name == variable,
+        Expr::List(items) | Expr::Call { args: items, .. } => items.iter().any(|item| contains(item, variable)),
+        Expr::Unary { operand, .. } => contains(operand, variable),
+        Expr::Binary { left, right, .. } => contains(left, variable) || contains(right, variable),
+    }
+}
+
+// 未知数についての多項式の係数（定数項から順、最高次の係数は 0 でない）。多項式でなければ None
+fn polynomial(evaluator: &Evaluator, expr: &Expr, variable: &str) -> Result<Option<Vec<f64>>, CalcError> {
+    let coefficients = match raw_polynomial(evaluator, expr, variable)? {
+        Some(coefficients) => coefficients,
+        None => return Ok(None),
+    };
+    // 0.1 + 0.2 - 0.3 のような丸め誤差は 0 とみなす
+    let scale = coefficients.iter().fold(0.0_f64, |max, c| max.max(c.abs()));
+    let mut coefficients: Vec<f64> = coefficients.into_iter().map(|c| if c.abs() <= scale * 1e-12 { 0.0 } else { c }).collect();
+    while coefficients.last() == Some(&0.0) {
+        coefficients.pop();
+    }
+    Ok(Some(coefficients))
+}
+
+fn raw_polynomial(evaluator: &Evaluator, expr: &Expr, variable: &str) -> Result<Option<Vec<f64>>, CalcError> {
+    if !contains(expr, variable) {
+        return Ok(Some(vec![evaluator.evaluate(expr)?.into_number()?.to_f64()]));
+    }
+    let part = |expr: &Expr| raw_polynomial(evaluator, expr, variable);
+    Ok(match expr {
+        Expr::Variable(_) => Some(vec![0.0, 1.0]),
+        Expr::Unary { op: UnaryOp::Neg, operand } => part(operand)?.map(|p| p.iter().map(|c| -c).collect()),
+        Expr::Unary { op: UnaryOp::Plus, operand } => part(operand)?,
+        Expr::Binary { op: BinaryOp::Add, left, right } => part(left)?.zip(part(right)?).map(|(l, r)| add(&l, &r, 1.0)),
+        Expr::Binary { op: BinaryOp::Sub, left, right } => part(left)?.zip(part(right)?).map(|(l, r)| add(&l, &r, -1.0)),
+        Expr::Binary { op: BinaryOp::Mul, left, right } => part(left)?.zip(part(right)?).map(|(l, r)| multiply(&l, &r)),
+        Expr::Binary { op: BinaryOp::Div, left, right } if !contains(right, variable) => {
+            let divisor = evaluator.evaluate(right)?.into_number()?.to_f64();
+            if divisor == 0.0 {
+                return Err(CalcError::DivisionByZero);
+            }
+            part(left)?.map(|p| p.iter().map(|c| c / divisor).collect())
+        }
+        Expr::Binary { op: BinaryOp::Pow, left, right } if !contains(right, variable) => {
+            let exponent = evaluator.evaluate(right)?.into_number()?.to_f64();
+            if exponent.fract() != 0.0 || !(0.0..=f64::from(MAX_DEGREE)).contains(&exponent) {
+                return Ok(None);
+            }
+            part(left)?.map(|base| (0..exponent as u32).fold(vec![1.0], |power, _| multiply(&power, &base)))
+        }
+        _ => None,
+    })
+}
+
+// a + sign * b
+fn add(a: &[f64], b: &[f64], sign: f64) -> Vec<f64> {
+    (0..a.len().max(b.len()))
+        .map(|i| a.get(i).copied().unwrap_or(0.0) + sign * b.get(i).copied().unwrap_or(0.0))
+        .collect()
+}
+
+fn multiply(a: &[f64], b: &[f64]) -> Vec<f64> {
+    let mut product = vec![0.0; a.len() + b.len() - 1];
+    for (i, x) in a.iter().enumerate() {
+        for (j, y) in b.iter().enumerate() {
+            product[i + j] += x * y;
+        }
+    }
+    product
+}
+
+// ax^2 + bx + c = 0 の解（重解は1つ、判別式が負なら共役な複素数の組）
+// + 0.0 は -0 を 0 にする
+fn quadratic_roots(a: f64, b: f64, c: f64) -> Vec<Root> {
+    let discriminant = b * b - 4.0 * a * c;
+    if discriminant.abs() <= (b * b).max((4.0 * a * c).abs()) * 1e-12 {
+        return vec![Root::Real(-b / (2.0 * a) + 0.0)];
+    }
+    if discriminant < 0.0 {
+        let (re, im) = (-b / (2.0 * a) + 0.0, (-discriminant).sqrt() / (2.0 * a).abs());
+        return vec![Root::Complex { re, im }, Root::Complex { re, im: -im }];
+    }
+    // 桁落ちを避けるため、絶対値の大きい方の解から求める（b = 0 なら ±√(-c/a)）
+    let mut roots = if b == 0.0 {
+        let root = (-c / a).sqrt();
+        [-root, root]
+    } else {
+        let q = -(b + b.signum() * discriminant.sqrt()) / 2.0;
+        [q / a, c / q]
+    };
+    roots.sort_by(f64::total_cmp);
+    roots.into_iter().map(Root::Real).collect()
+}
+
+// 未知数の値から式の値を求める関数（積分・微分でも使う）
+pub fn function<'a>(evaluator: &'a Evaluator, expr: &'a Expr, variable: &'a str) -> impl Fn(f64) -> Result<f64, CalcError> + 'a {
+    move |x| {
+        let y = evaluator.evaluate_with(expr, variable, Value::Number(evaluator.number(x)))?.into_number()?.to_f64();
+        if !y.is_finite() {
+            return Err(CalcError::InvalidExpression(format!("the function is not finite at {} = {}", variable, x)));
+        }
+        Ok(y)
+    }
+}
+
+// 区間を細かく分けて、符号が変わる所と 0 になる所を探す
+fn numeric_roots(evaluator: &Evaluator, expr: &Expr, variable: &str, range: RangeInclusive<f64>) -> Vec<Root> {
+    let f = function(evaluator, expr, variable);
+    let (from, to) = (*range.start(), *range.end());
+    let step = (to - from) / SCAN_STEPS as f64;
+    let mut roots: Vec<f64> = Vec::new();
+    let mut previous: Option<(f64, f64)> = None;
+    for i in 0..=SCAN_STEPS {
+        let x = from + step * i as f64;
+        let y = f(x).ok();
+        match (previous, y) {
+            (_, Some(0.0)) => roots.push(x),
+            (Some((a, fa)), Some(fb)) if fa != 0.0 && fa.signum() != fb.signum() => {
+                // 1/x のような不連続点では符号が変わっても値が 0 に近づかない
+                if let Ok(convergence) = bisection(&f, a, x, 0.0, 200)
+                    && convergence.residual.abs() <= 1e-6
+                {
+                    roots.push(convergence.root);
+                }
+            }
+            _ => {}
+        }
+        previous = y.map(|y| (x, y));
+    }
+    roots.dedup_by(|a, b| (*a - *b).abs() <= 1e-9 * (1.0 + b.abs()));
+    roots.into_iter().map(Root::Real).collect()
+}
+
+// f(a) と f(b) の符号が異なる区間を、幅の半分が tolerance 以下になるまで二分法で絞り込む
+fn bisection(f: &impl Fn(f64) -> Result<f64, CalcError>, mut a: f64, mut b: f64, tolerance: f64, max_iterations: usize) -> Result<Convergence, CalcError> {
+    let mut fa = f(a)?;
+    let fb = f(b)?;
+    for (x, y) in [(a, fa), (b, fb)] {
+        if y == 0.0 {
+            return Ok(Convergence { root: x, method: RootMethod::Bisection, iterations: 0, error: 0.0, residual: 0.0 });
+        }
+    }
+    if fa.signum() == fb.signum() {
+        return Err(CalcError::InvalidExpression(format!(
+            "the function has the same sign at {} and {}; bisection needs a sign change",
+            a, b
+        )));
+    }
+    for iteration in 1..=max_iterations {
+        let middle = a + (b - a) / 2.0;
+        let fm = f(middle)?;
+        let error = (b - a) / 2.0;
+        // これ以上区間を狭められないときも止める
+        if fm == 0.0 || error <= tolerance.max(f64::EPSILON * middle.abs()) || middle == a || middle == b {
+            return Ok(Convergence { root: middle, method: RootMethod::Bisection, iterations: iteration, error, residual: fm });
+        }
+        if fm.signum() == fa.signum() {
+            (a, fa) = (middle, fm);
+        } else {
+            b = middle;
+        }
+    }
+    Err(not_converged(RootMethod::Bisection, max_iterations, a + (b - a) / 2.0))
+}
+
+// 導関数を中心差分で近似したニュートン法
+fn newton(f: &impl Fn(f64) -> Result<f64, CalcError>, start: f64, tolerance: f64, max_iterations: usize) -> Result<Convergence, CalcError> {
+    let mut x = start;
+    for iteration in 1..=max_iterations {
+        let fx = f(x)?;
+        if fx == 0.0 {
+            return Ok(Convergence { root: x, method: RootMethod::Newton, iterations: iteration - 1, error: 0.0, residual: 0.0 });
+        }
+        let h = 1e-6 * (1.0 + x.abs());
+        let slope = (f(x + h)? - f(x - h)?) / (2.0 * h);
+        if slope == 0.0 {
+            return Err(CalcError::InvalidExpression(format!("the derivative is zero at x = {}; try other bounds", x)));
+        }
+        let step = fx / slope;
+        x -= step;
+        if step.abs() <= tolerance.max(f64::EPSILON * x.abs()) {
+            return Ok(Convergence { root: x, method: RootMethod::Newton, iterations: iteration, error: step.abs(), residual: f(x)? });
+        }
+    }
+    Err(not_converged(RootMethod::Newton, max_iterations, x))
+}
+
+fn not_converged(method: RootMethod, iterations: usize, estimate: f64) -> CalcError {
+    CalcError::InvalidExpression(format!(
+        "{} did not converge in {} iterations (last estimate {})",
+        method.name(),
+        iterations,
+        estimate
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn roots(equation: &str) -> (Method, Vec<Root>) {
+        let solution = solve(&Evaluator::default(), equation, None, DEFAULT_RANGE).unwrap();
+        (solution.method, solution.roots)
+    }
+
+    fn real(equation: &str) -> Vec<f64> {
+        roots(equation)
+            .1
+            .into_iter()
+            .map(|root| match root {
+                Root::Real(x) => x,
+                other => panic!("complex root {:?}", other),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_linear_and_quadratic() {
+        assert_eq!(roots("2*x + 5 = 13"), (Method::Linear, vec![Root::Real(4.0)]));
+        assert_eq!(roots("(y - 1) / 2 = y"), (Method::Linear, vec![Root::Real(-1.0)]));
+        assert_eq!(roots("x^2 - x - 6"), (Method::Quadratic, vec![Root::Real(-2.0), Root::Real(3.0)]));
+        assert_eq!(roots("x^2 = 6*x - 9"), (Method::Quadratic, vec![Root::Real(3.0)]));
+        assert_eq!(roots("2*x^2 = 8"), (Method::Quadratic, vec![Root::Real(-2.0), Root::Real(2.0)]));
+        assert_eq!(
+            roots("x^2 + 2*x + 5 = 0"),
+            (Method::Quadratic, vec![Root::Complex { re: -1.0, im: 2.0 }, Root::Complex { re: -1.0, im: -2.0 }])
+        );
+        // 2次の項が打ち消し合えば1次方程式
+        assert_eq!(roots("x^2 + x = x^2 + 2"), (Method::Linear, vec![Root::Real(2.0)]));
+    }
+
+    #[test]
+    fn test_numeric() {
+        let (method, _) = roots("cos(x) = x");
+        assert_eq!(method, Method::Numeric);
+        let found = real("cos(x) = x");
+        assert_eq!(found.len(), 1);
+        assert!((found[0] - 0.7390851332151607).abs() < 1e-12);
+
+        let found = real("x^3 - 6*x^2 + 11*x - 6");
+        assert_eq!(found.len(), 3);
+        for (root, expected) in found.iter().zip([1.0, 2.0, 3.0]) {
+            assert!((root - expected).abs() < 1e-9);
+        }
+        // 不連続点は解にしない
+        assert!(real("1 / x = 0").is_empty());
+        // sum のループ変数は未知数に数えない
+        let found = real("sum(i, 1, 3, i * x) = 12");
+        assert!(found.len() == 1 && (found[0] - 2.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_find_root() {
+        let evaluator = Evaluator::default();
+        let find = |equation: &str, from: f64, to: f64, method| find_root(&evaluator, equation, None, from..=to, method, 1e-12, 100);
+
+        let (variable, convergence) = find("x^3 - 2", 0.0, 3.0, RootMethod::Auto).unwrap();
+        assert_eq!((variable.as_str(), convergence.method), ("x", RootMethod::Bisection));
+        assert!((convergence.root - 2f64.cbrt()).abs() < 1e-12);
+        assert!(convergence.error <= 1e-12);
+
+        let (_, convergence) = find("x^3 - 2", 0.0, 3.0, RootMethod::Newton).unwrap();
+        assert!((convergence.root - 2f64.cbrt()).abs() < 1e-12);
+        assert!(convergence.iterations < 10);
+
+        // 符号が変わらなければ自動でニュートン法にする
+        let (_, convergence) = find("(t - 1)^2", 0.0, 3.0, RootMethod::Auto).unwrap();
+        assert_eq!(convergence.method, RootMethod::Newton);
+        assert!((convergence.root - 1.0).abs() < 1e-5);
+
+        assert!(find("x^2 + 1", -1.0, 2.0, RootMethod::Bisection).is_err());
+        assert!(find("x^3 - 2", 0.0, 3.0, RootMethod::Newton).is_ok());
+        assert!(matches!(
+            find_root(&evaluator, "x^3 - 2", None, 0.0..=3.0, RootMethod::Bisection, 0.0, 5),
+            Err(CalcError::InvalidExpression(message)) if message.contains("did not converge")
+        ));
+        assert!(find("x^2 + 1", -1.0, 2.0, RootMethod::Newton).is_err());
+    }
+
+    #[test]
+    fn test_identity_and_errors() {
+        let evaluator = Evaluator::default();
+        let solution = solve(&evaluator, "x + x = 2*x", None, DEFAULT_RANGE).unwrap();
+        assert!(solution.identity);
+        assert_eq!(roots("x = x + 1"), (Method::Constant, Vec::new()));
+        assert!(matches!(solve(&evaluator, "a*x = 4", Some("a"), DEFAULT_RANGE), Err(CalcError::UnknownVariable(_))));
+        assert_eq!(solve(&evaluator, "2*x = 0", None, DEFAULT_RANGE).unwrap().roots, vec![Root::Real(0.0)]);
+
+        assert!(matches!(solve(&evaluator, "a*x = 4", None, DEFAULT_RANGE), Err(CalcError::InvalidExpression(_))));
+        assert!(matches!(solve(&evaluator, "1 + 1 = 2", None, DEFAULT_RANGE), Err(CalcError::InvalidExpression(_))));
+        assert!(matches!(
+            solve(&evaluator, "x = 1 = 2", None, DEFAULT_RANGE),
+            Err(CalcError::SyntaxError { position: 6, .. })
+        ));
+        assert!(matches!(solve(&evaluator, "x = 2 +", None, DEFAULT_RANGE), Err(CalcError::SyntaxError { position: 7, .. })));
+    }
+}
@@ -0,0 +1,153 @@
+// 数値の並びの統計量
+use crate::CalcError;
+
+// 既定で表示するパーセンタイル
+pub const DEFAULT_PERCENTILES: &[f64] = &[25.0, 50.0, 75.0, 90.0, 95.0, 99.0];
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Summary {
+    pub count: usize,
+    pub sum: f64,
+    pub mean: f64,
+    pub median: f64,
+    // 最も多く現れる値（全て1回ずつなら空）
+    pub mode: Vec<f64>,
+    // 標本分散（--population 指定時は母分散）。値が1つの標本分散は定義されない
+    pub variance: Option<f64>,
+    pub stddev: Option<f64>,
+    pub min: f64,
+    pub max: f64,
+    pub percentiles: Vec<Percentile>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Percentile {
+    pub percent: f64,
+    pub value: f64,
+}
+
+// 空白・カンマ・改行区切りの数値を読み取る
+pub fn parse_numbers(input: &str) -> Result<Vec<f64>, CalcError> {
+    input
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|token| !token.is_empty())
+        .map(|token| {
+            token
+                .parse::<f64>()
+                .ok()
+                .filter(|value| value.is_finite())
+                .ok_or_else(|| CalcError::InvalidExpression(format!("Not a number: '{}'", token)))
+        })
+        .collect()
+}
+
+pub fn summarize(values: &[f64], percents: &[f64], population: bool) -> Result<Summary, CalcError> {
+    if values.is_empty() {
+        return Err(CalcError::InvalidExpression("No numbers to summarize".to_string()));
+    }
+    if let Some(percent) = percents.iter().find(|p| !(0.0..=100.0).contains(*p)) {
+        return Err(CalcError::InvalidExpression(format!(
+            "Percentile must be between 0 and 100, got {}", percent
+        )));
+    }
+
+    let mut sorted = values.to_vec();
+    sorted.sort_by(f64::total_cmp);
+
+    let count = values.len();
+    let sum: f64 = values.iter().sum();
+    let mean = sum / count as f64;
+    let squares: f64 = values.iter().map(|x| (x - mean).powi(2)).sum();
+    let variance = match (population, count) {
+        (true, _) => Some(squares / count as f64),
+        (false, 1) => None,
+        (false, _) => Some(squares / (count - 1) as f64),
+    };
+
+    Ok(Summary {
+        count,
+        sum,
+        mean,
+        median: percentile(&sorted, 50.0),
+        mode: mode(&sorted),
+        variance,
+        stddev: variance.map(f64::sqrt),
+        min: sorted[0],
+        max: sorted[count - 1],
+        percentiles: percents
+            .iter()
+            .map(|&percent| Percentile { percent, value: percentile(&sorted, percent) })
+            .collect(),
+    })
+}
+
+// 線形補間によるパーセンタイル（ソート済みの値に対して）
+pub fn percentile(sorted: &[f64], percent: f64) -> f64 {
+    let rank = percent / 100.0 * (sorted.len() - 1) as f64;
+    let lower = rank.floor() as usize;
+    let upper = rank.ceil() as usize;
+    sorted[lower] + (sorted[upper] - sorted[lower]) * (rank - lower as f64)
+}
+
+// 最頻値（同数の場合は全て）
+fn mode(sorted: &[f64]) -> Vec<f64> {
+    let runs: Vec<(f64, usize)> = sorted
+        .chunk_by(|a, b| a == b)
+        .map(|run| (run[0], run.len()))
+        .collect();
+    let max_count = runs.iter().map(|&(_, count)| count).max().unwrap_or(0);
+    if max_count < 2 {
+        return Vec::new();
+    }
+    runs.into_iter().filter(|&(_, count)| count == max_count).map(|(value, _)| value).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_numbers() {
+        assert_eq!(parse_numbers("1, 2.5\n-3  4e1\n").unwrap(), vec![1.0, 2.5, -3.0, 40.0]);
+        assert!(parse_numbers("1 two 3").is_err());
+        assert!(parse_numbers("1 inf").is_err());
+        assert!(parse_numbers("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_summary() {
+        let summary = summarize(&[2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0], &[25.0], false).unwrap();
+        assert_eq!(summary.count, 8);
+        assert_eq!(summary.sum, 40.0);
+        assert_eq!(summary.mean, 5.0);
+        assert_eq!(summary.median, 4.5);
+        assert_eq!(summary.mode, vec![4.0]);
+        assert!((summary.variance.unwrap() - 32.0 / 7.0).abs() < 1e-12);
+        assert_eq!((summary.min, summary.max), (2.0, 9.0));
+        assert_eq!(summary.percentiles, vec![Percentile { percent: 25.0, value: 4.0 }]);
+
+        let population = summarize(&[2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0], &[], true).unwrap();
+        assert_eq!(population.stddev, Some(2.0));
+    }
+
+    #[test]
+    fn test_single_value_and_errors() {
+        let summary = summarize(&[3.0], &[90.0], false).unwrap();
+        assert_eq!(summary.variance, None);
+        assert_eq!(summary.percentiles[0].value, 3.0);
+        assert!(summary.mode.is_empty());
+        assert!(summarize(&[], &[], false).is_err());
+        assert!(summarize(&[1.0], &[101.0], false).is_err());
+    }
+
+    #[test]
+    fn test_percentile_interpolation() {
+        let sorted = [1.0, 2.0, 3.0, 4.0];
+        assert_eq!(percentile(&sorted, 0.0), 1.0);
+        assert_eq!(percentile(&sorted, 50.0), 2.5);
+        assert_eq!(percentile(&sorted, 100.0), 4.0);
+        assert_eq!(mode(&[1.0, 1.0, 2.0, 2.0, 3.0]), vec![1.0, 2.0]);
+    }
+}
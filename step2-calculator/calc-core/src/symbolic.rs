@@ -0,0 +1,338 @@
+// 記号微分（calc-cli derive）
+//
+// 構文木を微分の規則で書き換え、0 や 1 との演算などを簡単にする
+//   x^2 * sin(x)  →  2 * x * sin(x) + x ^ 2 * cos(x)
+// 微分する変数以外の名前は定数として扱う
+use crate::evaluator::Evaluator;
+use crate::functions::AngleMode;
+use crate::parser::{self, BinaryOp, Expr, UnaryOp};
+use crate::solver;
+use crate::CalcError;
+
+// 式を読んで導関数を求める（variable を省くと式の中の未知の名前を使う）
+pub fn derive(evaluator: &Evaluator, expression: &str, variable: Option<&str>) -> Result<(String, Expr), CalcError> {
+    let expr = parser::parse(expression, evaluator.syntax)?;
+    let variable = match variable {
+        Some(variable) => variable.to_string(),
+        None => solver::find_unknown(evaluator, &expr)?,
+    };
+    let derivative = derivative(&expr, &variable, evaluator.angle_mode)?;
+    Ok((variable, derivative))
+}
+
+// 簡単にした導関数
+pub fn derivative(expr: &Expr, variable: &str, angle_mode: AngleMode) -> Result<Expr, CalcError> {
+    Ok(simplify(&differentiate(expr, variable, angle_mode)?))
+}
+
+fn differentiate(expr: &Expr, variable: &str, angle_mode: AngleMode) -> Result<Expr, CalcError> {
+    if !solver::contains(expr, variable) {
+        return Ok(number(0.0));
+    }
+    let d = |expr: &Expr| differentiate(expr, variable, angle_mode);
+    Ok(match expr {
+        Expr::Variable(_) => number(1.0),
+        Expr::Unary { op: UnaryOp::Neg, operand } => negate(d(operand)?),
+        Expr::Unary { op: UnaryOp::Plus, operand } => d(operand)?,
+        Expr::Unary { op: UnaryOp::Percent, operand } => binary(BinaryOp::Div, d(operand)?, number(100.0)),
+        Expr::Binary { op: BinaryOp::Add, left, right } => binary(BinaryOp::Add, d(left)?, d(right)?),
+        Expr::Binary { op: BinaryOp::Sub, left, right } => binary(BinaryOp::Sub, d(left)?, d(right)?),
+        // (uv)' = u'v + uv'
+        Expr::Binary { op: BinaryOp::Mul, left, right } => binary(
+            BinaryOp::Add,
+            binary(BinaryOp::Mul, d(left)?, *right.clone()),
+            binary(BinaryOp::Mul, *left.clone(), d(right)?),
+        ),
+        Expr::Binary { op: BinaryOp::Div, left, right } if !solver::contains(right, variable) => binary(BinaryOp::Div, d(left)?, *right.clone()),
+        // (u/v)' = (u'v - uv') / v^2
+        Expr::Binary { op: BinaryOp::Div, left, right } => binary(
+            BinaryOp::Div,
+            binary(
+                BinaryOp::Sub,
+                binary(BinaryOp::Mul, d(left)?, *right.clone()),
+                binary(BinaryOp::Mul, *left.clone(), d(right)?),
+            ),
+            binary(BinaryOp::Pow, *right.clone(), number(2.0)),
+        ),
+        Expr::Binary { op: BinaryOp::Pow, left, right } => power(left, right, d(left)?, d(right)?, variable),
+        Expr::Call { name, args } => match args.as_slice() {
+            [argument] => chain(name, argument, d(argument)?, angle_mode)?,
+            // log(u, b) = ln(u) / ln(b)
+            // 選ばれた側の導関数（条件の境目は考えない）
+            [condition, then, otherwise] if name == "if" => {
+                Expr::Call { name: name.clone(), args: vec![condition.clone(), d(then)?, d(otherwise)?] }
+            }
+            [argument, base] if name == "log" => d(&binary(BinaryOp::Div, call("ln", argument.clone()), call("ln", base.clone())))?,
+            _ => return Err(cannot_differentiate(&format!("{}()", name))),
+        },
+        Expr::Unary { op, .. } => {
+            let symbol = match op {
+                UnaryOp::Not => "~",
+                _ => "!",
+            };
+            return Err(cannot_differentiate(&format!("'{}'", symbol)));
+        }
+        Expr::Binary { op, .. } => return Err(cannot_differentiate(&format!("'{}'", op.symbol()))),
+        Expr::Number(_) | Expr::List(_) => return Err(cannot_differentiate("a vector or matrix")),
+    })
+}
+
+// 累乗の微分（指数だけ、底だけ、両方に変数があるとき）
+fn power(base: &Expr, exponent: &Expr, d_base: Expr, d_exponent: Expr, variable: &str) -> Expr {
+    let u_to_v = binary(BinaryOp::Pow, base.clone(), exponent.clone());
+    if !solver::contains(exponent, variable) {
+        // (u^n)' = n * u^(n-1) * u'
+        let lowered = binary(BinaryOp::Pow, base.clone(), binary(BinaryOp::Sub, exponent.clone(), number(1.0)));
+        return binary(BinaryOp::Mul, binary(BinaryOp::Mul, exponent.clone(), lowered), d_base);
+    }
+    if !solver::contains(base, variable) {
+        // (a^v)' = a^v * ln(a) * v'
+        return binary(BinaryOp::Mul, binary(BinaryOp::Mul, u_to_v, call("ln", base.clone())), d_exponent);
+    }
+    // (u^v)' = u^v * (v' * ln(u) + v * u' / u)
+    let inner = binary(
+        BinaryOp::Add,
+        binary(BinaryOp::Mul, d_exponent, call("ln", base.clone())),
+        binary(BinaryOp::Div, binary(BinaryOp::Mul, exponent.clone(), d_base), base.clone()),
+    );
+    binary(BinaryOp::Mul, u_to_v, inner)
+}
+
+// 1引数の関数の合成関数の微分 f(u)' = u' * f'(u)
+fn chain(name: &str, u: &Expr, du: Expr, angle_mode: AngleMode) -> Result<Expr, CalcError> {
+    let square = |expr: Expr| binary(BinaryOp::Pow, expr, number(2.0));
+    let one_minus_square = || binary(BinaryOp::Sub, number(1.0), square(u.clone()));
+    // 度数法では角度をラジアンに直す係数が付く
+    let pi = || Expr::Variable("pi".to_string());
+    let (to_radians, from_radians) = match angle_mode {
+        AngleMode::Radians => (None, None),
+        AngleMode::Turns => (
+            Some(binary(BinaryOp::Mul, number(2.0), pi())),
+            Some(binary(BinaryOp::Div, number(1.0), binary(BinaryOp::Mul, number(2.0), pi()))),
+        ),
+        // 半回転が pi ラジアン
+        other => {
+            let half_turn = other.full_turn() / 2.0;
+            (Some(binary(BinaryOp::Div, pi(), number(half_turn))), Some(binary(BinaryOp::Div, number(half_turn), pi())))
+        }
+    };
+    let (outer, factor) = match name {
+        "sin" => (call("cos", u.clone()), to_radians),
+        "cos" => (negate(call("sin", u.clone())), to_radians),
+        "tan" => (binary(BinaryOp::Div, number(1.0), square(call("cos", u.clone()))), to_radians),
+        "asin" => (binary(BinaryOp::Div, number(1.0), call("sqrt", one_minus_square())), from_radians),
+        "acos" => (negate(binary(BinaryOp::Div, number(1.0), call("sqrt", one_minus_square()))), from_radians),
+        "atan" => (binary(BinaryOp::Div, number(1.0), binary(BinaryOp::Add, number(1.0), square(u.clone()))), from_radians),
+        "sinh" => (call("cosh", u.clone()), None),
+        "cosh" => (call("sinh", u.clone()), None),
+        "tanh" => (binary(BinaryOp::Div, number(1.0), square(call("cosh", u.clone()))), None),
+        "asinh" => (binary(BinaryOp::Div, number(1.0), call("sqrt", binary(BinaryOp::Add, square(u.clone()), number(1.0)))), None),
+        "acosh" => (binary(BinaryOp::Div, number(1.0), call("sqrt", binary(BinaryOp::Sub, square(u.clone()), number(1.0)))), None),
+        "atanh" => (binary(BinaryOp::Div, number(1.0), one_minus_square()), None),
+        "erf" | "erfc" => {
+            // 2/√π e^(-u²)（erfc は符号が逆）
+            let derivative = binary(
+                BinaryOp::Mul,
+                binary(BinaryOp::Div, number(2.0), call("sqrt", Expr::Variable("pi".to_string()))),
+                call("exp", negate(square(u.clone()))),
+            );
+            (if name == "erf" { derivative } else { negate(derivative) }, None)
+        }
+        "sqrt" => (binary(BinaryOp::Div, number(1.0), binary(BinaryOp::Mul, number(2.0), call("sqrt", u.clone()))), None),
+        "exp" => (call("exp", u.clone()), None),
+        "ln" => (binary(BinaryOp::Div, number(1.0), u.clone()), None),
+        "log" => (binary(BinaryOp::Div, number(1.0), binary(BinaryOp::Mul, u.clone(), call("ln", number(10.0)))), None),
+        "log2" => (binary(BinaryOp::Div, number(1.0), binary(BinaryOp::Mul, u.clone(), call("ln", number(2.0)))), None),
+        "abs" => (binary(BinaryOp::Div, u.clone(), call("abs", u.clone())), None),
+        _ => return Err(cannot_differentiate(&format!("{}()", name))),
+    };
+    let outer = match factor {
+        Some(factor) => binary(BinaryOp::Mul, outer, factor),
+        None => outer,
+    };
+    Ok(binary(BinaryOp::Mul, du, outer))
+}
+
+fn cannot_differentiate(what: &str) -> CalcError {
+    CalcError::InvalidExpression(format!("cannot differentiate {}", what))
+}
+
+// 負の数は符号と数値リテラルで表す
+fn number(value: f64) -> Expr {
+    if value < 0.0 {
+        negate(Expr::Number((-value).to_string()))
+    } else {
+        Expr::Number(value.to_string())
+    }
+}
+
+fn negate(expr: Expr) -> Expr {
+    Expr::Unary { op: UnaryOp::Neg, operand: Box::new(expr) }
+}
+
+fn binary(op: BinaryOp, left: Expr, right: Expr) -> Expr {
+    Expr::Binary { op, left: Box::new(left), right: Box::new(right) }
+}
+
+fn call(name: &str, argument: Expr) -> Expr {
+    Expr::Call { name: name.to_string(), args: vec![argument] }
+}
+
+// 数値リテラル（符号付き）の値
+fn constant(expr: &Expr) -> Option<f64> {
+    match expr {
+        Expr::Number(text) => text.parse().ok(),
+        Expr::Unary { op: UnaryOp::Neg, operand } => constant(operand).map(|value| -value),
+        _ => None,
+    }
+}
+
+// 整数になる定数の計算だけをまとめる（1/3 や 0.1 + 0.2 はそのまま残す）
+fn fold(op: BinaryOp, a: f64, b: f64) -> Option<Expr> {
+    let value = match op {
+        BinaryOp::Add => a + b,
+        BinaryOp::Sub => a - b,
+        BinaryOp::Mul => a * b,
+        BinaryOp::Div if b != 0.0 => a / b,
+        BinaryOp::Pow => a.powf(b),
+        _ => return None,
+    };
+    let exact = [a, b, value].iter().all(|x| x.fract() == 0.0 && x.abs() < 2f64.powi(53));
+    exact.then(|| number(value + 0.0))
+}
+
+// 下から順に簡単にする
+pub fn simplify(expr: &Expr) -> Expr {
+    match expr {
+        Expr::Number(_) | Expr::Variable(_) => expr.clone(),
+        Expr::List(items) => Expr::List(items.iter().map(simplify).collect()),
+        Expr::Call { name, args } => Expr::Call { name: name.clone(), args: args.iter().map(simplify).collect() },
+        Expr::Unary { op, operand } => simplify_unary(*op, simplify(operand)),
+        Expr::Binary { op, left, right } => simplify_binary(*op, simplify(left), simplify(right)),
+    }
+}
+
+fn simplify_unary(op: UnaryOp, operand: Expr) -> Expr {
+    match (op, operand) {
+        (UnaryOp::Plus, operand) => operand,
+        // -(-x) = x
+        (UnaryOp::Neg, Expr::Unary { op: UnaryOp::Neg, operand }) => *operand,
+        (UnaryOp::Neg, operand) if constant(&operand) == Some(0.0) => number(0.0),
+        (op, operand) => Expr::Unary { op, operand: Box::new(operand) },
+    }
+}
+
+fn simplify_binary(op: BinaryOp, left: Expr, right: Expr) -> Expr {
+    let (a, b) = (constant(&left), constant(&right));
+    if let (Some(a), Some(b)) = (a, b)
+        && let Some(folded) = fold(op, a, b)
+    {
+        return folded;
+    }
+    // 符号が付いた数値以外の式
+    let negated = |expr: &Expr| match expr {
+        Expr::Unary { op: UnaryOp::Neg, operand } if constant(operand).is_none() => Some((**operand).clone()),
+        _ => None,
+    };
+    match op {
+        BinaryOp::Add if a == Some(0.0) => right,
+        BinaryOp::Add | BinaryOp::Sub if b == Some(0.0) => left,
+        BinaryOp::Sub if a == Some(0.0) => simplify_unary(UnaryOp::Neg, right),
+        // x + -y = x - y、x - -y = x + y
+        BinaryOp::Add | BinaryOp::Sub if matches!(right, Expr::Unary { op: UnaryOp::Neg, .. }) => {
+            let Expr::Unary { operand, .. } = right else { unreachable!() };
+            let op = if op == BinaryOp::Add { BinaryOp::Sub } else { BinaryOp::Add };
+            simplify_binary(op, left, *operand)
+        }
+        BinaryOp::Mul if a == Some(0.0) || b == Some(0.0) => number(0.0),
+        BinaryOp::Mul if a == Some(1.0) => right,
+        BinaryOp::Mul | BinaryOp::Div if b == Some(1.0) => left,
+        // u / u = 1（商の微分や対数の微分で出てくる x / x など。定数どうしは上で計算済み）
+        BinaryOp::Div if a.is_none() && left == right => number(1.0),
+        BinaryOp::Mul if a == Some(-1.0) => simplify_unary(UnaryOp::Neg, right),
+        BinaryOp::Mul if b == Some(-1.0) => simplify_unary(UnaryOp::Neg, left),
+        // 定数は左に置く（x * 2 → 2 * x）
+        BinaryOp::Mul if b.is_some() && a.is_none() => simplify_binary(op, right, left),
+        // 2 * (3 * x) → 6 * x
+        BinaryOp::Mul if a.is_some() && matches!(&right, Expr::Binary { op: BinaryOp::Mul, left: inner, .. } if constant(inner).is_some()) => {
+            let Expr::Binary { left: inner, right: rest, .. } = right else { unreachable!() };
+            match constant(&inner).and_then(|c| fold(op, a.unwrap_or(1.0), c)) {
+                Some(product) => simplify_binary(op, product, *rest),
+                None => binary(op, left, binary(op, *inner, *rest)),
+            }
+        }
+        BinaryOp::Div if a == Some(0.0) => number(0.0),
+        // u * (1 / v) = u / v
+        BinaryOp::Mul if matches!(&right, Expr::Binary { op: BinaryOp::Div, left: one, .. } if constant(one) == Some(1.0)) => {
+            let Expr::Binary { right: denominator, .. } = right else { unreachable!() };
+            simplify_binary(BinaryOp::Div, left, *denominator)
+        }
+        // u * -v = -(u * v)
+        BinaryOp::Mul if negated(&right).is_some() => {
+            simplify_unary(UnaryOp::Neg, simplify_binary(op, left, negated(&right).unwrap_or(right)))
+        }
+        BinaryOp::Pow if b == Some(1.0) => left,
+        BinaryOp::Pow if b == Some(0.0) || a == Some(1.0) => number(1.0),
+        _ => binary(op, left, right),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::Syntax;
+
+    fn derive_str(input: &str) -> String {
+        let expr = parser::parse(input, Syntax::default()).unwrap();
+        derivative(&expr, "x", AngleMode::Radians).unwrap().to_string()
+    }
+
+    #[test]
+    fn test_derivative() {
+        assert_eq!(derive_str("x^2 * sin(x)"), "2 * x * sin(x) + x ^ 2 * cos(x)");
+        assert_eq!(derive_str("3 * x^4 - 2 * x + 7"), "12 * x ^ 3 - 2");
+        assert_eq!(derive_str("1 / x"), "-1 / x ^ 2");
+        assert_eq!(derive_str("exp(2 * x)"), "2 * exp(2 * x)");
+        assert_eq!(derive_str("cos(x)"), "-sin(x)");
+        assert_eq!(derive_str("ln(x^2 + 1)"), "2 * x / (x ^ 2 + 1)");
+        assert_eq!(derive_str("a * x"), "a");
+        assert_eq!(derive_str("a * exp(-k * x)"), "a * (-k * exp(-k * x))");
+        assert_eq!(derive_str("2^x"), "2 ^ x * ln(2)");
+        assert_eq!(derive_str("x^x"), "x ^ x * (ln(x) + 1)");
+        assert_eq!(derive_str("pi"), "0");
+        assert_eq!(derive_str("sinh(2 * x)"), "2 * cosh(2 * x)");
+        assert_eq!(derive_str("tanh(x)"), "1 / cosh(x) ^ 2");
+        assert_eq!(derive_str("erf(x)"), "2 / sqrt(pi) * exp(-x ^ 2)");
+        assert_eq!(derive_str("x > 0 ? x^2 : -x"), "if(x > 0, 2 * x, -1)");
+    }
+
+    #[test]
+    fn test_degrees_and_errors() {
+        let expr = parser::parse("sin(x)", Syntax::default()).unwrap();
+        assert_eq!(derivative(&expr, "x", AngleMode::Degrees).unwrap().to_string(), "cos(x) * (pi / 180)");
+        assert_eq!(derivative(&expr, "x", AngleMode::Gradians).unwrap().to_string(), "cos(x) * (pi / 200)");
+        assert_eq!(derivative(&expr, "x", AngleMode::Turns).unwrap().to_string(), "cos(x) * (2 * pi)");
+
+        let (variable, result) = derive(&Evaluator::default(), "t^3", None).unwrap();
+        assert_eq!((variable.as_str(), result.to_string().as_str()), ("t", "3 * t ^ 2"));
+        assert!(derive(&Evaluator::default(), "x * y", None).is_err());
+
+        for input in ["floor(x)", "x!", "x % 2 + x // 3", "max(x, 1)"] {
+            let expr = parser::parse(input, Syntax::default()).unwrap();
+            assert!(matches!(derivative(&expr, "x", AngleMode::Radians), Err(CalcError::InvalidExpression(_))), "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_simplify() {
+        let simplified = |input: &str| simplify(&parser::parse(input, Syntax::default()).unwrap()).to_string();
+        assert_eq!(simplified("0 + x * 1 - 0"), "x");
+        assert_eq!(simplified("x - -y"), "x + y");
+        assert_eq!(simplified("--x"), "x");
+        assert_eq!(simplified("x * 2 ^ 3"), "8 * x");
+        assert_eq!(simplified("1 / 3 + 0.1"), "1 / 3 + 0.1");
+        assert_eq!(simplified("x / x"), "1");
+        assert_eq!(simplified("(x^2 + 1) / (x^2 + 1) * y"), "y");
+        assert_eq!(simplified("x / (2 * x)"), "x / (2 * x)");
+    }
+}
@@ -0,0 +1,153 @@
+// 誤差付きの値（5.0 ± 0.1）
+//
+// 1次の誤差伝播: f(x, y, ...) の誤差は sqrt(Σ (∂f/∂x_i × σ_i)^2)
+// 入力の誤差は互いに独立とみなす（同じ変数どうしの x - x も 0 ± √2σ になる）
+use std::fmt;
+
+use crate::functions::{self, AngleMode};
+use crate::parser::BinaryOp;
+use crate::CalcError;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Uncertain {
+    pub value: f64,
+    // 標準不確かさ（0 以上）
+    pub error: f64,
+}
+
+impl Uncertain {
+    pub fn new(value: f64, error: f64) -> Result<Uncertain, CalcError> {
+        if error.is_nan() || error < 0.0 {
+            return Err(CalcError::InvalidExpression(format!("uncertainty must not be negative, got {}", error)));
+        }
+        Ok(Uncertain { value, error })
+    }
+
+    // 誤差のない値
+    pub fn exact(value: f64) -> Uncertain {
+        Uncertain { value, error: 0.0 }
+    }
+
+    pub fn neg(&self) -> Uncertain {
+        Uncertain { value: -self.value, error: self.error }
+    }
+
+    // 四則演算と累乗（偏微分は式で求める）
+    pub fn binary(op: BinaryOp, a: Uncertain, b: Uncertain) -> Result<Uncertain, CalcError> {
+        let (x, y) = (a.value, b.value);
+        // (値, ∂/∂x, ∂/∂y)
+        let (value, dx, dy) = match op {
+            BinaryOp::Add => (x + y, 1.0, 1.0),
+            BinaryOp::Sub => (x - y, 1.0, -1.0),
+            BinaryOp::Mul => (x * y, y, x),
+            BinaryOp::Div => {
+                if y == 0.0 {
+                    return Err(CalcError::DivisionByZero);
+                }
+                (x / y, 1.0 / y, -x / (y * y))
+            }
+            BinaryOp::Pow => {
+                let value = x.powf(y);
+                (value, y * x.powf(y - 1.0), value * x.ln())
+            }
+            _ => {
+                return Err(CalcError::InvalidExpression(format!(
+                    "Cannot apply '{}' to values with uncertainty", op.symbol()
+                )));
+            }
+        };
+        Ok(Uncertain { value, error: combine(&[(dx, a.error), (dy, b.error)]) })
+    }
+
+    // 組み込み関数（偏微分は中心差分で求める）
+    pub fn call(name: &str, args: &[Uncertain], angle_mode: AngleMode) -> Result<Uncertain, CalcError> {
+        let values: Vec<f64> = args.iter().map(|arg| arg.value).collect();
+        let value = functions::call(name, &values, angle_mode)?;
+        let mut terms = Vec::with_capacity(args.len());
+        for (i, arg) in args.iter().enumerate() {
+            if arg.error == 0.0 {
+                continue;
+            }
+            let at = |x: f64| {
+                let mut values = values.clone();
+                values[i] = x;
+                functions::call(name, &values, angle_mode)
+            };
+            let h = 1e-6 * arg.value.abs().max(1.0);
+            terms.push(((at(arg.value + h)? - at(arg.value - h)?) / (2.0 * h), arg.error));
+        }
+        Ok(Uncertain { value, error: combine(&terms) })
+    }
+
+    // 誤差を有効数字2桁にしたときの小数点以下の桁数（誤差が 0 や無限大なら None）
+    pub fn places(&self) -> Option<u64> {
+        if !(self.error > 0.0 && self.error.is_finite()) {
+            return None;
+        }
+        Some((1 - self.error.log10().floor() as i64).max(0) as u64)
+    }
+}
+
+// (偏微分, 誤差) の組から合成した誤差（誤差のない引数の偏微分は使わないので、無限大でもよい）
+fn combine(terms: &[(f64, f64)]) -> f64 {
+    terms
+        .iter()
+        .filter(|(_, error)| *error != 0.0)
+        .map(|(derivative, error)| (derivative * error).powi(2))
+        .sum::<f64>()
+        .sqrt()
+}
+
+impl fmt::Display for Uncertain {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.places() {
+            Some(places) => write!(f, "{:.*} ± {:.*}", places as usize, self.value, places as usize, self.error),
+            None => write!(f, "{} ± {}", self.value, self.error),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn uncertain(value: f64, error: f64) -> Uncertain {
+        Uncertain::new(value, error).unwrap()
+    }
+
+    fn assert_close(actual: Uncertain, value: f64, error: f64) {
+        assert!((actual.value - value).abs() < 1e-9, "{:?}", actual);
+        assert!((actual.error - error).abs() < 1e-6, "{:?}", actual);
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let (a, b) = (uncertain(5.0, 0.1), uncertain(3.2, 0.05));
+        assert_close(Uncertain::binary(BinaryOp::Add, a, b).unwrap(), 8.2, (0.01f64 + 0.0025).sqrt());
+        assert_close(Uncertain::binary(BinaryOp::Sub, a, b).unwrap(), 1.8, (0.01f64 + 0.0025).sqrt());
+        // 積と商は相対誤差の二乗和
+        let relative = ((0.1f64 / 5.0).powi(2) + (0.05f64 / 3.2).powi(2)).sqrt();
+        assert_close(Uncertain::binary(BinaryOp::Mul, a, b).unwrap(), 16.0, 16.0 * relative);
+        assert_close(Uncertain::binary(BinaryOp::Div, a, b).unwrap(), 5.0 / 3.2, 5.0 / 3.2 * relative);
+        assert_close(Uncertain::binary(BinaryOp::Pow, a, Uncertain::exact(2.0)).unwrap(), 25.0, 1.0);
+        assert!(matches!(Uncertain::binary(BinaryOp::Div, a, Uncertain::exact(0.0)), Err(CalcError::DivisionByZero)));
+        assert!(Uncertain::binary(BinaryOp::Lt, a, b).is_err());
+        assert!(Uncertain::new(1.0, -0.1).is_err());
+    }
+
+    #[test]
+    fn test_functions() {
+        assert_close(Uncertain::call("sqrt", &[uncertain(16.0, 0.8)], AngleMode::Radians).unwrap(), 4.0, 0.1);
+        assert_close(Uncertain::call("sin", &[uncertain(0.0, 0.01)], AngleMode::Radians).unwrap(), 0.0, 0.01);
+        let max = Uncertain::call("max", &[uncertain(1.0, 0.1), Uncertain::exact(0.0)], AngleMode::Radians).unwrap();
+        assert_close(max, 1.0, 0.1);
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(uncertain(16.0, 0.41231).to_string(), "16.00 ± 0.41");
+        assert_eq!(uncertain(1234.5, 56.0).to_string(), "1234 ± 56");
+        assert_eq!(uncertain(0.012344, 0.00021).to_string(), "0.01234 ± 0.00021");
+        assert_eq!(Uncertain::exact(2.5).to_string(), "2.5 ± 0");
+    }
+}
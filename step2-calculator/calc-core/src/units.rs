@@ -0,0 +1,297 @@
+// 単位の登録と単位付きの量の演算
+use std::fmt;
+
+use crate::CalcError;
+
+// 基本次元の指数（長さ・質量・時間・温度・情報量）
+pub type Dimension = [i32; 5];
+
+const LENGTH: Dimension = [1, 0, 0, 0, 0];
+const AREA: Dimension = [2, 0, 0, 0, 0];
+const VOLUME: Dimension = [3, 0, 0, 0, 0];
+const MASS: Dimension = [0, 1, 0, 0, 0];
+const TIME: Dimension = [0, 0, 1, 0, 0];
+const TEMPERATURE: Dimension = [0, 0, 0, 1, 0];
+const DATA: Dimension = [0, 0, 0, 0, 1];
+const SPEED: Dimension = [1, 0, -1, 0, 0];
+
+#[derive(Debug, PartialEq)]
+pub struct UnitDef {
+    pub name: &'static str,
+    pub aliases: &'static [&'static str],
+    pub category: &'static str,
+    pub dimension: Dimension,
+    // 基本単位（m, kg, s, K, bit）への換算: 基本単位の値 = 値 * factor + offset
+    pub factor: f64,
+    pub offset: f64,
+}
+
+const fn unit(
+    name: &'static str,
+    aliases: &'static [&'static str],
+    category: &'static str,
+    dimension: Dimension,
+    factor: f64,
+) -> UnitDef {
+    UnitDef { name, aliases, category, dimension, factor, offset: 0.0 }
+}
+
+pub const UNITS: &[UnitDef] = &[
+    unit("m", &["meter", "meters", "metre"], "length", LENGTH, 1.0),
+    unit("km", &["kilometer", "kilometers"], "length", LENGTH, 1000.0),
+    unit("cm", &["centimeter", "centimeters"], "length", LENGTH, 0.01),
+    unit("mm", &["millimeter", "millimeters"], "length", LENGTH, 0.001),
+    unit("um", &["micrometer", "micron"], "length", LENGTH, 1e-6),
+    unit("nm", &["nanometer"], "length", LENGTH, 1e-9),
+    unit("mi", &["mile", "miles"], "length", LENGTH, 1609.344),
+    unit("yd", &["yard", "yards"], "length", LENGTH, 0.9144),
+    unit("ft", &["foot", "feet"], "length", LENGTH, 0.3048),
+    unit("in", &["inch", "inches"], "length", LENGTH, 0.0254),
+    unit("nmi", &["nautical_mile"], "length", LENGTH, 1852.0),
+    unit("ha", &["hectare", "hectares"], "area", AREA, 10_000.0),
+    unit("acre", &["acres"], "area", AREA, 4_046.856_422_4),
+    unit("L", &["l", "liter", "liters", "litre"], "volume", VOLUME, 0.001),
+    unit("mL", &["ml", "milliliter", "milliliters"], "volume", VOLUME, 1e-6),
+    unit("gal", &["gallon", "gallons"], "volume", VOLUME, 0.003_785_411_784),
+    unit("qt", &["quart", "quarts"], "volume", VOLUME, 0.000_946_352_946),
+    unit("pt", &["pint", "pints"], "volume", VOLUME, 0.000_473_176_473),
+    unit("cup", &["cups"], "volume", VOLUME, 0.000_236_588_236_5),
+    unit("floz", &["fluid_ounce"], "volume", VOLUME, 0.000_029_573_529_562_5),
+    unit("kg", &["kilogram", "kilograms"], "mass", MASS, 1.0),
+    unit("g", &["gram", "grams"], "mass", MASS, 0.001),
+    unit("mg", &["milligram", "milligrams"], "mass", MASS, 1e-6),
+    unit("t", &["tonne", "tonnes"], "mass", MASS, 1000.0),
+    unit("lb", &["lbs", "pound", "pounds"], "mass", MASS, 0.453_592_37),
+    unit("oz", &["ounce", "ounces"], "mass", MASS, 0.028_349_523_125),
+    UnitDef { name: "K", aliases: &["kelvin"], category: "temperature", dimension: TEMPERATURE, factor: 1.0, offset: 0.0 },
+    UnitDef { name: "degC", aliases: &["C", "celsius"], category: "temperature", dimension: TEMPERATURE, factor: 1.0, offset: 273.15 },
+    UnitDef { name: "degF", aliases: &["F", "fahrenheit"], category: "temperature", dimension: TEMPERATURE, factor: 5.0 / 9.0, offset: 273.15 - 32.0 * 5.0 / 9.0 },
+    unit("s", &["sec", "second", "seconds"], "time", TIME, 1.0),
+    unit("ms", &["millisecond", "milliseconds"], "time", TIME, 0.001),
+    unit("us", &["microsecond", "microseconds"], "time", TIME, 1e-6),
+    unit("min", &["minute", "minutes"], "time", TIME, 60.0),
+    unit("h", &["hr", "hour", "hours"], "time", TIME, 3600.0),
+    unit("day", &["days", "d"], "time", TIME, 86_400.0),
+    unit("week", &["weeks", "wk"], "time", TIME, 604_800.0),
+    unit("year", &["years", "yr"], "time", TIME, 31_557_600.0),
+    unit("bit", &["bits"], "data", DATA, 1.0),
+    unit("B", &["byte", "bytes"], "data", DATA, 8.0),
+    unit("kB", &["KB"], "data", DATA, 8e3),
+    unit("MB", &[], "data", DATA, 8e6),
+    unit("GB", &[], "data", DATA, 8e9),
+    unit("TB", &[], "data", DATA, 8e12),
+    unit("KiB", &[], "data", DATA, 8.0 * 1024.0),
+    unit("MiB", &[], "data", DATA, 8.0 * 1024.0 * 1024.0),
+    unit("GiB", &[], "data", DATA, 8.0 * 1024.0 * 1024.0 * 1024.0),
+    unit("TiB", &[], "data", DATA, 8.0 * 1024.0 * 1024.0 * 1024.0 * 1024.0),
+    unit("kph", &["kmh"], "speed", SPEED, 1000.0 / 3600.0),
+    unit("mph", &[], "speed", SPEED, 1609.344 / 3600.0),
+    unit("knot", &["knots", "kn"], "speed", SPEED, 1852.0 / 3600.0),
+];
+
+pub fn lookup(name: &str) -> Option<&'static UnitDef> {
+    UNITS.iter().find(|u| u.name == name || u.aliases.contains(&name))
+}
+
+// 単位の積（km/h は [(km, 1), (h, -1)]）
+#[derive(Debug, Clone, PartialEq)]
+pub struct Unit {
+    factors: Vec<(&'static UnitDef, i32)>,
+}
+
+impl Unit {
+    pub fn single(def: &'static UnitDef) -> Unit {
+        Unit { factors: vec![(def, 1)] }
+    }
+
+    pub fn dimension(&self) -> Dimension {
+        let mut dimension = [0; 5];
+        for (def, power) in &self.factors {
+            for (total, base) in dimension.iter_mut().zip(def.dimension) {
+                *total += base * power;
+            }
+        }
+        dimension
+    }
+
+    pub fn is_dimensionless(&self) -> bool {
+        self.dimension() == [0; 5]
+    }
+
+    fn factor(&self) -> f64 {
+        self.factors.iter().map(|(def, power)| def.factor.powi(*power)).product()
+    }
+
+    // 温度のような原点のずれは単独の単位のときだけ考慮する
+    fn offset(&self) -> f64 {
+        match self.factors.as_slice() {
+            [(def, 1)] => def.offset,
+            _ => 0.0,
+        }
+    }
+
+    pub fn mul(&self, other: &Unit) -> Unit {
+        let mut factors = self.factors.clone();
+        for &(def, power) in &other.factors {
+            match factors.iter_mut().find(|(existing, _)| existing.name == def.name) {
+                Some((_, existing)) => *existing += power,
+                None => factors.push((def, power)),
+            }
+        }
+        factors.retain(|(_, power)| *power != 0);
+        Unit { factors }
+    }
+
+    pub fn powi(&self, exp: i32) -> Unit {
+        let factors = if exp == 0 {
+            Vec::new()
+        } else {
+            self.factors.iter().map(|&(def, power)| (def, power * exp)).collect()
+        };
+        Unit { factors }
+    }
+}
+
+impl fmt::Display for Unit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let format_factor = |def: &UnitDef, power: i32| {
+            if power == 1 { def.name.to_string() } else { format!("{}^{}", def.name, power) }
+        };
+        let numerator: Vec<String> = self.factors.iter().filter(|(_, p)| *p > 0).map(|(d, p)| format_factor(d, *p)).collect();
+        let denominator: Vec<String> = self.factors.iter().filter(|(_, p)| *p < 0).map(|(d, p)| format_factor(d, -p)).collect();
+
+        if numerator.is_empty() {
+            write!(f, "1")?;
+        } else {
+            write!(f, "{}", numerator.join("*"))?;
+        }
+        match denominator.len() {
+            0 => Ok(()),
+            1 => write!(f, "/{}", denominator[0]),
+            _ => write!(f, "/({})", denominator.join("*")),
+        }
+    }
+}
+
+// 単位付きの量
+#[derive(Debug, Clone, PartialEq)]
+pub struct Quantity {
+    pub value: f64,
+    pub unit: Unit,
+}
+
+impl Quantity {
+    pub fn convert(&self, target: &Unit) -> Result<Quantity, CalcError> {
+        if self.unit.dimension() != target.dimension() {
+            return Err(CalcError::DimensionMismatch(format!(
+                "cannot convert {} to {}", self.unit, target
+            )));
+        }
+        let base = self.value * self.unit.factor() + self.unit.offset();
+        let value = (base - target.offset()) / target.factor();
+        Ok(Quantity { value: round_significant(value), unit: target.clone() })
+    }
+
+    // 足し算・引き算は右辺を左辺の単位にそろえて行う
+    pub fn add(&self, other: &Quantity) -> Result<Quantity, CalcError> {
+        let other = other.convert(&self.unit)?;
+        Ok(Quantity { value: self.value + other.value, unit: self.unit.clone() })
+    }
+
+    pub fn sub(&self, other: &Quantity) -> Result<Quantity, CalcError> {
+        let other = other.convert(&self.unit)?;
+        Ok(Quantity { value: self.value - other.value, unit: self.unit.clone() })
+    }
+
+    pub fn mul(&self, other: &Quantity) -> Quantity {
+        Quantity { value: self.value * other.value, unit: self.unit.mul(&other.unit) }
+    }
+
+    pub fn div(&self, other: &Quantity) -> Result<Quantity, CalcError> {
+        if other.value == 0.0 {
+            return Err(CalcError::DivisionByZero);
+        }
+        Ok(Quantity { value: self.value / other.value, unit: self.unit.mul(&other.unit.powi(-1)) })
+    }
+
+    pub fn scale(&self, factor: f64) -> Quantity {
+        Quantity { value: self.value * factor, unit: self.unit.clone() }
+    }
+
+    pub fn powi(&self, exp: i32) -> Quantity {
+        Quantity { value: self.value.powi(exp), unit: self.unit.powi(exp) }
+    }
+
+    // km/m のように次元がなくなった量はただの数値になる
+    pub fn as_dimensionless(&self) -> Option<f64> {
+        self.unit.is_dimensionless().then(|| self.value * self.unit.factor())
+    }
+}
+
+// 換算係数の誤差（211.99999999999997 など）が出ないよう有効数字15桁に丸める
+fn round_significant(value: f64) -> f64 {
+    if !value.is_finite() {
+        return value;
+    }
+    format!("{:.14e}", value).parse().unwrap_or(value)
+}
+
+impl fmt::Display for Quantity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.value, self.unit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quantity(value: f64, name: &str) -> Quantity {
+        Quantity { value, unit: Unit::single(lookup(name).unwrap()) }
+    }
+
+    fn approx(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9 * b.abs().max(1.0)
+    }
+
+    #[test]
+    fn test_registry_names_are_unique() {
+        let mut names: Vec<&str> = UNITS.iter().flat_map(|u| std::iter::once(u.name).chain(u.aliases.iter().copied())).collect();
+        let total = names.len();
+        names.sort();
+        names.dedup();
+        assert_eq!(names.len(), total);
+    }
+
+    #[test]
+    fn test_convert() {
+        let km = quantity(5.0, "km");
+        assert!(approx(km.convert(&Unit::single(lookup("mi").unwrap())).unwrap().value, 3.106_855_961_186_67));
+        assert!(approx(quantity(1.0, "GiB").convert(&Unit::single(lookup("MB").unwrap())).unwrap().value, 1_073.741_824));
+        assert!(approx(quantity(1.0, "gal").convert(&Unit::single(lookup("L").unwrap())).unwrap().value, 3.785_411_784));
+        assert!(matches!(km.convert(&Unit::single(lookup("kg").unwrap())), Err(CalcError::DimensionMismatch(_))));
+    }
+
+    #[test]
+    fn test_temperature() {
+        let fahrenheit = Unit::single(lookup("F").unwrap());
+        assert_eq!(quantity(100.0, "C").convert(&fahrenheit).unwrap().value, 212.0);
+        assert!(approx(quantity(0.0, "K").convert(&Unit::single(lookup("degC").unwrap())).unwrap().value, -273.15));
+    }
+
+    #[test]
+    fn test_compound_units() {
+        let speed = quantity(60.0, "km").div(&quantity(1.5, "h")).unwrap();
+        assert_eq!(speed.to_string(), "40 km/h");
+        let mph = speed.convert(&Unit::single(lookup("mph").unwrap())).unwrap();
+        assert!(approx(mph.value, 24.854_847_689_493_39));
+
+        let area = quantity(3.0, "m").mul(&quantity(4.0, "m"));
+        assert_eq!(area.to_string(), "12 m^2");
+        assert!(approx(area.convert(&Unit::single(lookup("ha").unwrap())).unwrap().value, 0.0012));
+
+        assert_eq!(quantity(2.0, "km").div(&quantity(500.0, "m")).unwrap().as_dimensionless(), Some(4.0));
+        assert_eq!(quantity(1.0, "kg").div(&quantity(2.0, "m").powi(2)).unwrap().to_string(), "0.25 kg/m^2");
+        assert!(matches!(quantity(1.0, "m").div(&quantity(0.0, "s")), Err(CalcError::DivisionByZero)));
+    }
+}
@@ -0,0 +1,320 @@
+// 式の値（数値・ベクトル・行列・単位付きの量・誤差付きの値）
+use std::cmp::Ordering;
+use std::fmt;
+
+use crate::matrix::{self, Matrix};
+use crate::number::{BitOp, Number, NumberMode};
+use crate::parser::BinaryOp;
+use crate::uncertainty::Uncertain;
+use crate::units::Quantity;
+use crate::CalcError;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Number(Number),
+    Vector(Vec<Number>),
+    Matrix(Matrix),
+    Quantity(Quantity),
+    Uncertain(Uncertain),
+}
+
+impl From<Number> for Value {
+    fn from(number: Number) -> Self {
+        Value::Number(number)
+    }
+}
+
+// 次元のなくなった量（km/m など）はただの数値にする
+impl From<Quantity> for Value {
+    fn from(quantity: Quantity) -> Self {
+        match quantity.as_dimensionless() {
+            Some(value) => Value::Number(Number::Float(value)),
+            None => Value::Quantity(quantity),
+        }
+    }
+}
+
+impl Value {
+    // [..] リテラルの要素から値を作る（数値の並びはベクトル、ベクトルの並びは行列）
+    pub fn from_list(items: Vec<Value>) -> Result<Value, CalcError> {
+        if items.is_empty() {
+            return Err(CalcError::DimensionMismatch("a vector needs at least one element".to_string()));
+        }
+        if items.iter().all(|item| matches!(item, Value::Number(_))) {
+            let numbers = items.into_iter().map(Value::into_number).collect::<Result<Vec<Number>, CalcError>>()?;
+            return Ok(Value::Vector(numbers));
+        }
+        let rows = items
+            .into_iter()
+            .map(|item| match item {
+                Value::Vector(row) => Ok(row),
+                other => Err(CalcError::InvalidExpression(format!(
+                    "Matrix rows must be vectors, got {}", other.kind()
+                ))),
+            })
+            .collect::<Result<Vec<Vec<Number>>, CalcError>>()?;
+        Matrix::from_rows(rows).map(Value::Matrix)
+    }
+
+    pub fn into_number(self) -> Result<Number, CalcError> {
+        match self {
+            Value::Number(number) => Ok(number),
+            other => Err(CalcError::InvalidExpression(format!("Expected a number but got {}", other.kind()))),
+        }
+    }
+
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Value::Number(_) => "a number",
+            Value::Vector(_) => "a vector",
+            Value::Matrix(_) => "a matrix",
+            Value::Quantity(_) => "a quantity with units",
+            Value::Uncertain(_) => "a value with uncertainty",
+        }
+    }
+
+    pub fn neg(&self) -> Value {
+        match self {
+            Value::Number(number) => Value::Number(number.neg()),
+            Value::Vector(values) => Value::Vector(values.iter().map(Number::neg).collect()),
+            Value::Matrix(matrix) => Value::Matrix(matrix.map(|x| Ok(x.neg())).expect("negation cannot fail")),
+            Value::Quantity(quantity) => Value::Quantity(quantity.scale(-1.0)),
+            Value::Uncertain(uncertain) => Value::Uncertain(uncertain.neg()),
+        }
+    }
+
+    // 数値を別のモードにする（単位付きの量と誤差付きの値は f64 のまま）
+    pub fn convert(&self, mode: NumberMode, digits: u64) -> Value {
+        match self {
+            Value::Number(number) => Value::Number(number.convert(mode, digits)),
+            Value::Vector(values) => Value::Vector(values.iter().map(|value| value.convert(mode, digits)).collect()),
+            Value::Matrix(matrix) => {
+                Value::Matrix(matrix.map(|value| Ok(value.convert(mode, digits))).expect("conversion cannot fail"))
+            }
+            other => other.clone(),
+        }
+    }
+
+    pub fn not(&self) -> Result<Value, CalcError> {
+        match self {
+            Value::Number(number) => number.not().map(Value::Number),
+            other => Err(CalcError::InvalidExpression(format!("Cannot apply '~' to {}", other.kind()))),
+        }
+    }
+
+    // 二項演算（ベクトルと行列は型と大きさに応じて演算を選ぶ）
+    pub fn binary(op: BinaryOp, left: &Value, right: &Value, digits: u64) -> Result<Value, CalcError> {
+        use Value::{Matrix as M, Number as N, Quantity as Q, Uncertain as U, Vector as V};
+
+        let scalar = |a: &Number, b: &Number| scalar_op(op, a, b, digits);
+        match (op, left, right) {
+            (BinaryOp::PlusMinus, N(value), N(error)) => Uncertain::new(value.to_f64(), error.to_f64()).map(U),
+            (BinaryOp::PlusMinus, _, _) => Err(CalcError::InvalidExpression(format!(
+                "'±' expects two numbers, got {} and {}", left.kind(), right.kind()
+            ))),
+            (_, N(a), N(b)) => scalar(a, b).map(N),
+
+            (BinaryOp::Add | BinaryOp::Sub, V(a), V(b)) => {
+                if a.len() != b.len() {
+                    return Err(CalcError::DimensionMismatch(format!(
+                        "cannot combine vectors with {} and {} elements", a.len(), b.len()
+                    )));
+                }
+                a.iter().zip(b).map(|(x, y)| scalar(x, y)).collect::<Result<_, _>>().map(V)
+            }
+            (BinaryOp::Add, M(a), M(b)) => a.zip_with(b, "add", scalar).map(M),
+            (BinaryOp::Sub, M(a), M(b)) => a.zip_with(b, "subtract", scalar).map(M),
+
+            // スカラー倍とスカラーでの割り算
+            (BinaryOp::Mul, N(s), V(v)) | (BinaryOp::Mul | BinaryOp::Div, V(v), N(s)) => {
+                v.iter().map(|x| scalar_op(op, x, s, digits)).collect::<Result<_, _>>().map(V)
+            }
+            (BinaryOp::Mul, N(s), M(m)) | (BinaryOp::Mul | BinaryOp::Div, M(m), N(s)) => {
+                m.map(|x| scalar_op(op, x, s, digits)).map(M)
+            }
+
+            // ベクトルは右から掛けるときは列、左から掛けるときは行として扱う
+            (BinaryOp::Mul, M(a), M(b)) => a.mul(b, digits).map(M),
+            (BinaryOp::Mul, M(a), V(v)) => a.mul(&Matrix::column(v), digits).map(|m| V(m.into_elements())),
+            (BinaryOp::Mul, V(v), M(b)) => Matrix::row(v).mul(b, digits).map(|m| V(m.into_elements())),
+            (BinaryOp::Mul, V(_), V(_)) => Err(CalcError::InvalidExpression(
+                "Cannot multiply two vectors; use dot() or cross()".to_string()
+            )),
+
+            (BinaryOp::Pow, M(m), N(exp)) => {
+                let exp = exp.to_f64();
+                if exp.fract() != 0.0 || exp.abs() > i64::MAX as f64 {
+                    return Err(CalcError::InvalidExpression("Matrix powers must be integers".to_string()));
+                }
+                m.pow(exp as i64, digits).map(M)
+            }
+
+            // 単位付きの量は f64 で計算する
+            (BinaryOp::Add, Q(a), Q(b)) => a.add(b).map(Value::from),
+            (BinaryOp::Sub, Q(a), Q(b)) => a.sub(b).map(Value::from),
+            (BinaryOp::Mul, Q(a), Q(b)) => Ok(Value::from(a.mul(b))),
+            (BinaryOp::Div, Q(a), Q(b)) => a.div(b).map(Value::from),
+            (BinaryOp::Mul, Q(q), N(s)) | (BinaryOp::Mul, N(s), Q(q)) => Ok(Value::from(q.scale(s.to_f64()))),
+            (BinaryOp::Div, Q(q), N(s)) => {
+                if s.is_zero() {
+                    return Err(CalcError::DivisionByZero);
+                }
+                Ok(Value::from(q.scale(1.0 / s.to_f64())))
+            }
+            (BinaryOp::Div, N(s), Q(q)) => {
+                if q.value == 0.0 {
+                    return Err(CalcError::DivisionByZero);
+                }
+                Ok(Value::from(q.powi(-1).scale(s.to_f64())))
+            }
+            (BinaryOp::Pow, Q(q), N(exp)) => {
+                let exp = exp.to_f64();
+                if exp.fract() != 0.0 || exp.abs() > i32::MAX as f64 {
+                    return Err(CalcError::InvalidExpression("Powers of quantities must be integers".to_string()));
+                }
+                Ok(Value::from(q.powi(exp as i32)))
+            }
+
+            // 誤差付きの値は f64 で計算して誤差を伝播させる（数値は誤差 0 とみなす）
+            (_, U(a), U(b)) => Uncertain::binary(op, *a, *b).map(U),
+            (_, U(a), N(b)) => Uncertain::binary(op, *a, Uncertain::exact(b.to_f64())).map(U),
+            (_, N(a), U(b)) => Uncertain::binary(op, Uncertain::exact(a.to_f64()), *b).map(U),
+
+            _ => Err(CalcError::InvalidExpression(format!(
+                "Cannot apply '{}' to {} and {}", op.symbol(), left.kind(), right.kind()
+            ))),
+        }
+    }
+}
+
+fn scalar_op(op: BinaryOp, a: &Number, b: &Number, digits: u64) -> Result<Number, CalcError> {
+    match op {
+        BinaryOp::Add => a.add(b, digits),
+        BinaryOp::Sub => a.sub(b, digits),
+        BinaryOp::Mul => a.mul(b, digits),
+        BinaryOp::Div => a.div(b, digits),
+        BinaryOp::Mod => a.rem(b, digits),
+        BinaryOp::FloorDiv => a.floor_div(b, digits),
+        BinaryOp::Pow => a.pow(b, digits),
+        BinaryOp::And => a.bitwise(b, BitOp::And),
+        BinaryOp::Or => a.bitwise(b, BitOp::Or),
+        BinaryOp::Xor => a.bitwise(b, BitOp::Xor),
+        BinaryOp::Shl => a.bitwise(b, BitOp::Shl),
+        BinaryOp::Shr => a.bitwise(b, BitOp::Shr),
+        BinaryOp::PlusMinus => Err(CalcError::InvalidExpression("'±' expects two numbers".to_string())),
+        // 比較は真なら 1、偽なら 0
+        BinaryOp::Eq | BinaryOp::Ne | BinaryOp::Lt | BinaryOp::Le | BinaryOp::Gt | BinaryOp::Ge => {
+            let ordering = a.compare(b, digits);
+            let result = match op {
+                BinaryOp::Eq => ordering == Some(Ordering::Equal),
+                BinaryOp::Ne => ordering != Some(Ordering::Equal),
+                BinaryOp::Lt => ordering == Some(Ordering::Less),
+                BinaryOp::Le => matches!(ordering, Some(Ordering::Less | Ordering::Equal)),
+                BinaryOp::Gt => ordering == Some(Ordering::Greater),
+                _ => matches!(ordering, Some(Ordering::Greater | Ordering::Equal)),
+            };
+            Ok(if result { Number::one() } else { Number::zero() })
+        }
+    }
+}
+
+// 線形代数の組み込み関数（それ以外の名前は None）
+pub fn call_linear(name: &str, args: &[Value], digits: u64) -> Option<Result<Value, CalcError>> {
+    let result = match (name, args) {
+        ("transpose", [Value::Matrix(m)]) => Ok(Value::Matrix(m.transpose())),
+        // ベクトルには向きがないのでそのまま
+        ("transpose", [Value::Vector(v)]) => Ok(Value::Vector(v.clone())),
+        ("det", [Value::Matrix(m)]) => m.determinant(digits).map(Value::Number),
+        ("inverse", [Value::Matrix(m)]) => m.inverse(digits).map(Value::Matrix),
+        ("dot", [Value::Vector(a), Value::Vector(b)]) => matrix::dot(a, b, digits).map(Value::Number),
+        ("cross", [Value::Vector(a), Value::Vector(b)]) => matrix::cross(a, b, digits).map(Value::Vector),
+        ("transpose" | "det" | "inverse", [_]) => Err(CalcError::InvalidExpression(format!(
+            "{} expects a matrix", name
+        ))),
+        ("dot" | "cross", [_, _]) => Err(CalcError::InvalidExpression(format!(
+            "{} expects two vectors", name
+        ))),
+        ("transpose" | "det" | "inverse", _) => Err(CalcError::ArgumentCount {
+            name: name.to_string(),
+            expected: "1".to_string(),
+            found: args.len(),
+        }),
+        ("dot" | "cross", _) => Err(CalcError::ArgumentCount {
+            name: name.to_string(),
+            expected: "2".to_string(),
+            found: args.len(),
+        }),
+        _ => return None,
+    };
+    Some(result)
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Number(number) => write!(f, "{}", number),
+            Value::Vector(values) => matrix::write_vector(f, values),
+            Value::Matrix(matrix) => write!(f, "{}", matrix),
+            Value::Quantity(quantity) => write!(f, "{}", quantity),
+            Value::Uncertain(uncertain) => write!(f, "{}", uncertain),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::number::{NumberMode, DEFAULT_DIGITS};
+
+    fn vector(values: &[f64]) -> Value {
+        Value::Vector(values.iter().map(|v| Number::Float(*v)).collect())
+    }
+
+    fn number(value: f64) -> Value {
+        Value::Number(Number::Float(value))
+    }
+
+    #[test]
+    fn test_from_list() {
+        let m = Value::from_list(vec![vector(&[1.0, 2.0]), vector(&[3.0, 4.0])]).unwrap();
+        assert_eq!(m.to_string(), "[[1, 2], [3, 4]]");
+        assert!(matches!(
+            Value::from_list(vec![vector(&[1.0, 2.0]), vector(&[3.0])]),
+            Err(CalcError::DimensionMismatch(_))
+        ));
+        assert!(Value::from_list(vec![vector(&[1.0]), number(2.0)]).is_err());
+        assert!(Value::from_list(vec![]).is_err());
+    }
+
+    #[test]
+    fn test_vector_arithmetic() {
+        let a = vector(&[1.0, 2.0]);
+        let b = vector(&[3.0, 5.0]);
+        assert_eq!(Value::binary(BinaryOp::Add, &a, &b, DEFAULT_DIGITS).unwrap(), vector(&[4.0, 7.0]));
+        assert_eq!(Value::binary(BinaryOp::Mul, &number(2.0), &a, DEFAULT_DIGITS).unwrap(), vector(&[2.0, 4.0]));
+        assert_eq!(Value::binary(BinaryOp::Div, &b, &number(2.0), DEFAULT_DIGITS).unwrap(), vector(&[1.5, 2.5]));
+        assert!(Value::binary(BinaryOp::Mul, &a, &b, DEFAULT_DIGITS).is_err());
+        assert!(Value::binary(BinaryOp::Add, &a, &number(1.0), DEFAULT_DIGITS).is_err());
+    }
+
+    #[test]
+    fn test_matrix_times_vector() {
+        let m = Value::from_list(vec![vector(&[1.0, 2.0]), vector(&[3.0, 4.0])]).unwrap();
+        let v = vector(&[1.0, 1.0]);
+        assert_eq!(Value::binary(BinaryOp::Mul, &m, &v, DEFAULT_DIGITS).unwrap(), vector(&[3.0, 7.0]));
+        assert_eq!(Value::binary(BinaryOp::Mul, &v, &m, DEFAULT_DIGITS).unwrap(), vector(&[4.0, 6.0]));
+    }
+
+    #[test]
+    fn test_linear_functions() {
+        let m = Value::from_list(vec![vector(&[2.0, 0.0]), vector(&[0.0, 4.0])]).unwrap();
+        let inverse = call_linear("inverse", std::slice::from_ref(&m), DEFAULT_DIGITS).unwrap().unwrap();
+        assert_eq!(inverse.to_string(), "[[0.5, 0], [0, 0.25]]");
+        assert_eq!(call_linear("det", &[m], DEFAULT_DIGITS).unwrap().unwrap(), number(8.0));
+        assert!(call_linear("det", &[number(1.0)], DEFAULT_DIGITS).unwrap().is_err());
+        assert!(call_linear("sin", &[number(1.0)], DEFAULT_DIGITS).is_none());
+
+        let exact = Value::Number(Number::parse("1", NumberMode::Rational).unwrap());
+        assert!(call_linear("dot", &[exact], DEFAULT_DIGITS).unwrap().is_err());
+    }
+}
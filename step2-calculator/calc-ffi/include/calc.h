@@ -0,0 +1,44 @@
+/*
+ * calc-core の C インターフェース（cargo build --release -p calc-ffi で libcalc.so を作る）
+ *
+ *   CalcResult result = calc_evaluate("2 + 3 * 4");
+ *   if (result.status == CALC_OK) printf("%s\n", result.value);
+ *   else fprintf(stderr, "%s\n", result.error);
+ *   calc_result_free(result);
+ *
+ * value と error はライブラリが確保した文字列なので、free() ではなく calc_result_free で1回だけ解放する
+ *
+ * 評価のエラーと Rust のパニックは status で返す（深すぎる入れ子の式は CALC_ERROR_SYNTAX）
+ * メモリ不足のようにパニックにならない失敗では、プロセスが終了することがある
+ */
+#ifndef CALC_H
+#define CALC_H
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* status の値（calc-cli の終了コードと同じ番号） */
+#define CALC_OK 0
+#define CALC_ERROR_FAILURE 1 /* NULL や UTF-8 でない入力、内部のエラー */
+#define CALC_ERROR_SYNTAX 2
+#define CALC_ERROR_MATH 3
+#define CALC_ERROR_DIVISION_BY_ZERO 4
+#define CALC_ERROR_UNKNOWN_NAME 5
+#define CALC_ERROR_ARGUMENT_COUNT 6
+
+typedef struct {
+    int status;
+    char *value;   /* 成功したときの値の表示（失敗したときは NULL） */
+    double number; /* 数値の結果（数値でないときや失敗したときは NaN） */
+    char *error;   /* 失敗したときのメッセージ（成功したときは NULL） */
+} CalcResult;
+
+CalcResult calc_evaluate(const char *expression);
+void calc_result_free(CalcResult result);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
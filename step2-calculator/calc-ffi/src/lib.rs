@@ -0,0 +1,149 @@
+// calc-core を C から呼び出すための共有ライブラリ（Python の ctypes や Node の ffi から使う）
+//
+// cargo build --release -p calc-ffi で target/release/libcalc.so（macOS は libcalc.dylib、Windows は calc.dll）を作る
+//
+// 文字列の所有権
+//   calc_evaluate に渡した式はライブラリ内で複製するので、呼び出した側がいつ解放してもよい
+//   結果の value と error はライブラリが確保する。calc_result_free で1回だけ解放し、free() では解放しない
+// エラーの扱い
+//   評価のエラーもパニックも status で返す。深すぎる入れ子は構文解析の深さの上限で CALC_ERROR_SYNTAX になる
+//   ただしメモリ不足のようにパニックにならない失敗では、Rust の既定どおりプロセスが止まる
+use std::ffi::{CStr, CString, c_char, c_int};
+use std::panic;
+use std::ptr;
+
+use calc_core::{CalcError, Evaluator, Value};
+
+// 結果の状態（calc-cli の終了コードと同じ番号）
+pub const CALC_OK: c_int = 0;
+// NULL や UTF-8 でない入力、内部のエラー
+pub const CALC_ERROR_FAILURE: c_int = 1;
+pub const CALC_ERROR_SYNTAX: c_int = 2;
+pub const CALC_ERROR_MATH: c_int = 3;
+pub const CALC_ERROR_DIVISION_BY_ZERO: c_int = 4;
+pub const CALC_ERROR_UNKNOWN_NAME: c_int = 5;
+pub const CALC_ERROR_ARGUMENT_COUNT: c_int = 6;
+
+#[repr(C)]
+#[derive(Debug)]
+pub struct CalcResult {
+    pub status: c_int,
+    // 成功したときの値の表示（失敗したときは NULL）
+    pub value: *mut c_char,
+    // 数値の結果（ベクトル・行列・単位付きの量や失敗したときは NaN）
+    pub number: f64,
+    // 失敗したときのメッセージ（成功したときは NULL）
+    pub error: *mut c_char,
+}
+
+impl CalcResult {
+    fn error(status: c_int, message: &str) -> CalcResult {
+        CalcResult { status, value: ptr::null_mut(), number: f64::NAN, error: into_raw(message) }
+    }
+}
+
+/// 式を既定の設定（ラジアン・f64）で評価する
+///
+/// # Safety
+///
+/// `expression` は NULL か、NUL で終わる有効な文字列を指していること。
+/// 結果は `calc_result_free` で解放すること。
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn calc_evaluate(expression: *const c_char) -> CalcResult {
+    if expression.is_null() {
+        return CalcResult::error(CALC_ERROR_FAILURE, "expression is NULL");
+    }
+    let Ok(expression) = unsafe { CStr::from_ptr(expression) }.to_str() else {
+        return CalcResult::error(CALC_ERROR_FAILURE, "expression is not valid UTF-8");
+    };
+    // パニックを C の側へ伝えない
+    panic::catch_unwind(|| evaluate(expression)).unwrap_or_else(|_| CalcResult::error(CALC_ERROR_FAILURE, "internal error"))
+}
+
+/// `calc_evaluate` の結果の文字列を解放する
+///
+/// # Safety
+///
+/// `result` は `calc_evaluate` が返したもので、まだ解放していないこと。
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn calc_result_free(result: CalcResult) {
+    for text in [result.value, result.error] {
+        if !text.is_null() {
+            drop(unsafe { CString::from_raw(text) });
+        }
+    }
+}
+
+fn evaluate(expression: &str) -> CalcResult {
+    let evaluator = Evaluator::default();
+    match evaluator.evaluate_str(expression) {
+        Ok(value) => {
+            let number = match &value {
+                Value::Number(number) => number.to_f64(),
+                _ => f64::NAN,
+            };
+            CalcResult { status: CALC_OK, value: into_raw(&evaluator.format(&value)), number, error: ptr::null_mut() }
+        }
+        Err(e) => CalcResult::error(status(&e), &e.to_string()),
+    }
+}
+
+fn status(error: &CalcError) -> c_int {
+    match error {
+        CalcError::SyntaxError { .. } | CalcError::ParseError(_) => CALC_ERROR_SYNTAX,
+        CalcError::DivisionByZero => CALC_ERROR_DIVISION_BY_ZERO,
+        CalcError::UnknownVariable(_) | CalcError::UnknownFunction(_) | CalcError::UnknownOperation(_) => CALC_ERROR_UNKNOWN_NAME,
+        CalcError::ArgumentCount { .. } => CALC_ERROR_ARGUMENT_COUNT,
+        CalcError::InvalidExpression(_) | CalcError::RecursionLimit(_) | CalcError::StepLimit(_) | CalcError::DimensionMismatch(_) => CALC_ERROR_MATH,
+    }
+}
+
+// 途中の NUL は C の文字列に入れられないので取り除く
+fn into_raw(text: &str) -> *mut c_char {
+    CString::new(text.replace('\0', "")).unwrap_or_default().into_raw()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(pointer: *mut c_char) -> Option<String> {
+        (!pointer.is_null()).then(|| unsafe { CStr::from_ptr(pointer) }.to_string_lossy().into_owned())
+    }
+
+    // 結果を読んで解放する
+    fn call(expression: &CStr) -> (c_int, Option<String>, f64, Option<String>) {
+        let result = unsafe { calc_evaluate(expression.as_ptr()) };
+        let read = (result.status, text(result.value), result.number, text(result.error));
+        unsafe { calc_result_free(result) };
+        read
+    }
+
+    #[test]
+    fn test_evaluate() {
+        assert_eq!(call(c"2 + 3 * 4"), (CALC_OK, Some("14".to_string()), 14.0, None));
+        let (status, value, number, error) = call(c"[1, 2]");
+        assert_eq!((status, value, error), (CALC_OK, Some("[1, 2]".to_string()), None));
+        assert!(number.is_nan());
+    }
+
+    #[test]
+    fn test_errors() {
+        let status = |expression: &CStr| call(expression).0;
+        assert_eq!(status(c"2 +"), CALC_ERROR_SYNTAX);
+        assert_eq!(status(c"1 / 0"), CALC_ERROR_DIVISION_BY_ZERO);
+        assert_eq!(status(c"sqrt(-1)"), CALC_ERROR_MATH);
+        assert_eq!(status(c"foo"), CALC_ERROR_UNKNOWN_NAME);
+        assert_eq!(status(c"sin(1, 2)"), CALC_ERROR_ARGUMENT_COUNT);
+        assert_eq!(call(c"1 / 0").3, Some("Division by zero".to_string()));
+
+        let result = unsafe { calc_evaluate(ptr::null()) };
+        assert_eq!((result.status, text(result.value)), (CALC_ERROR_FAILURE, None));
+        unsafe { calc_result_free(result) };
+        assert_eq!(status(c"\xff"), CALC_ERROR_FAILURE);
+
+        // 深すぎる入れ子でもスタックを使い切らずにエラーを返す
+        let deep = std::ffi::CString::new("(".repeat(10_000)).unwrap();
+        assert_eq!(status(&deep), CALC_ERROR_SYNTAX);
+    }
+}
@@ -0,0 +1,27 @@
+# calc-cli のメッセージ（英語）
+# ヘルプは lib.rs の doc コメントの英語をそのまま使うので、ヘルプの訳は ja.ftl にだけ書く
+# error- で始まるものは CalcError の表示と同じ文言にする（英語では今までと同じ出力になる）
+
+error-division-by-zero = Division by zero
+error-invalid-expression = Invalid expression: { $detail }
+error-syntax = Syntax error at position { $position }: { $message }
+error-parse = Number parsing error: { $detail }
+error-unknown-operation = Unknown operation: { $name }
+error-unknown-variable = Unknown variable or constant: { $name }
+error-unknown-function = Unknown function: { $name }
+error-argument-count = Function '{ $name }' expects { $expected } argument(s), got { $found }
+error-recursion-limit = Recursion limit of { $limit } nested calls exceeded
+error-step-limit = Evaluation step limit of { $limit } exceeded
+error-dimension-mismatch = Dimension mismatch: { $detail }
+
+interactive-banner =
+    Calculator Interactive Mode
+    Enter mathematical expressions or 'quit' to exit
+    Examples: 2 + 3, 10 / 2, sqrt(16)
+goodbye = Goodbye!
+copied = Copied { $result }
+nothing-to-copy = No result to copy
+loaded = Loaded { $count } definition(s) from { $path }
+error-on-line = Error on line { $line }:
+summary = Summary: { $total } line(s), { $ok } ok, { $failed } failed
+lines-failed = { $count } line(s) failed
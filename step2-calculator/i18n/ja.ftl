@@ -0,0 +1,114 @@
+# calc-cli のメッセージ（日本語）
+# ID の決まりは my-cli-i18n の localize を参照。式の構文エラーの詳しい説明（$message）は英語のまま
+
+error-division-by-zero = ゼロで割ることはできません
+error-invalid-expression = 正しくない式です: { $detail }
+error-syntax = 位置 { $position } で構文エラー: { $message }
+error-parse = 数を読めません: { $detail }
+error-unknown-operation = 不明な演算です: { $name }
+error-unknown-variable = 不明な変数または定数です: { $name }
+error-unknown-function = 不明な関数です: { $name }
+error-argument-count = 関数 '{ $name }' の引数は { $expected } 個ですが、{ $found } 個あります
+error-recursion-limit = 呼び出しの入れ子が上限の { $limit } 段を超えました
+error-step-limit = 評価の手数が上限の { $limit } を超えました
+error-dimension-mismatch = 次元が合いません: { $detail }
+
+interactive-banner =
+    電卓の対話モード
+    数式を入力してください（'quit' で終了）
+    例: 2 + 3、10 / 2、sqrt(16)
+goodbye = さようなら！
+copied = { $result } をコピーしました
+nothing-to-copy = コピーする結果がありません
+loaded = { $path } から { $count } 個の定義を読み込みました
+error-on-line = { $line } 行目でエラー:
+summary = まとめ: { $total } 行、成功 { $ok }、失敗 { $failed }
+lines-failed = { $count } 行が失敗しました
+
+## ヘルプ
+
+about = シンプルな電卓 CLI ツール
+after-help =
+    終了ステータス:
+      0  成功
+      1  その他のエラー（ファイル、設定、batch の失敗した行）
+      2  式の構文エラー、またはコマンドラインの使い方の誤り
+      3  計算のエラー（定義域、オーバーフロー、演算できない組み合わせ）
+      4  ゼロ除算
+      5  不明な変数、定数、関数
+      6  関数の引数の数の誤り
+
+arg-angle = 三角関数の角度の単位（1回の呼び出しだけ別の単位にもできる。例: sin(0.25 turn)）
+arg-precision = 計算に使う数値の表現
+arg-digits = 割り切れない10進数の演算と to_decimal() で残す有効桁数
+arg-exact = 正確な分数で計算する（--precision rational と同じ。例: 1/3 + 1/6 = 1/2）
+arg-units = 式の中の不明な名前を単位として扱う（例: "60 km / 1.5 h" = 40 km/h）
+arg-bitwise = プログラマーモード: 整数演算で、^ は排他的論理和、** はべき乗
+arg-strict = '*' を省略した掛け算（2(3 + 4)、2pi、60 km など）を受け付けない
+arg-seed = rand()、randint()、rand サブコマンドのシード（毎回同じ結果になる）
+arg-places = 結果を表示する小数点以下の桁数（例: --places 2 なら 3.14）
+arg-significant = 結果を表示する有効数字の桁数（末尾の 0 があいまいになる値や、とても小さい値は指数表記にする）
+arg-notation = 結果の表記
+arg-rounding = --places、--significant、round(x, places) の最後の桁の丸め方
+arg-separators = 結果の整数部を3桁ごとに区切る（1,234,567）
+arg-decimal-comma = 式と結果の小数点にコンマを使う（3,14。引数は ", " で区切る）
+arg-locale = LOCALE の書き方で数を読み書きする（例: de_DE なら 1.234,5。"auto" は LC_ALL、LC_NUMERIC、LANG に従う）
+arg-lang = メッセージとヘルプの言語（en または ja。既定は LC_ALL、LC_MESSAGES、LANG から）
+arg-format = 出力形式（json と csv はスクリプト用に {"{"}expression, result, error{"}"} を出力する）
+arg-output-base = 整数の結果を表示する基数（0xff、0b1010、0o755 の形はいつでも入力できる）
+arg-quiet = 「式 =」を付けずに結果だけを表示する（テキスト出力）
+arg-color = 結果とエラーに色を付けるか（NO_COLOR があれば auto では付けない）
+arg-theme = 暗い背景と明るい背景の端末向けの配色
+arg-result-color = テーマの代わりに使う結果の色（例: "bold blue"、"bright-yellow"）
+arg-error-color = テーマの代わりに使うエラーメッセージの色
+arg-prompt = 対話モードのプロンプト（{"{"}angle{"}"}、{"{"}base{"}"}、{"{"}precision{"}"}、{"{"}digits{"}"} で今の設定を表示する）
+arg-copy = 最後の結果をクリップボードにコピーする
+arg-persist-memory = 対話モードのメモリー（m+、store）を ~/.calc_memory に残して次回も使う
+arg-verbose = 標準エラー出力のログを増やす: -v は info、-vv は debug、-vvv は trace（RUST_LOG があればそちらを優先）
+arg-log-format = 標準エラー出力のログの形式
+
+add-about = 足し算
+subtract-about = 2つの数を引き算する
+multiply-about = 2つの数を掛け算する
+divide-about = 2つの数を割り算する
+power-about = べき乗（a^b）を計算する
+square-root-about = 平方根を計算する
+eval-about = 数式を評価する
+interactive-about = 対話モード
+batch-about = ファイルまたは標準入力の1行に1つの式を評価する
+run-about = 計算スクリプトを実行する
+watch-about = ファイルが変わるたびに計算スクリプトを実行し直す
+serve-about = 式を評価する JSON API を HTTP で提供する
+ast-about = 式の構文木をテキスト、JSON（--format json）、Graphviz の DOT（--dot）で表示する
+currency-about = 金額を別の通貨に換算する
+check-about = 式を評価せずに構文だけを確かめる
+bench-about = 式の解析と評価にかかる時間を測る
+constants-about = 使える数学定数を一覧表示する
+convert-about = 値の単位を変換する
+units-about = convert と --units で使える単位を一覧表示する
+base-about = 整数を2進数、8進数、10進数、16進数で表示する
+rand-about = 乱数を作る
+percent-about = 百分率の計算
+numtheory-about = 整数の道具: 最大公約数、最小公倍数、素数判定、素因数分解
+matrix-about = 行列とベクトルの線形代数
+stats-about = 数の並びの要約統計量
+solve-about = 1つの未知数について方程式を解く
+root-about = 区間の中で関数の根を数値的に求める
+integrate-about = 関数の定積分（適応シンプソン法）
+diff-about = ある点での関数の微分係数（中心差分）
+derive-about = 関数の導関数を記号的に求める
+plot-about = 関数やデータ点を端末にプロットする
+completions-about = シェルの補完スクリプトを出力する
+docs-about = 全てのサブコマンドのドキュメントを生成する
+config-about = 設定ファイルに保存した既定値を表示・変更する
+history-about = 対話モードで入力した式を表示・検索・書き出しする
+var-about = 変数をセッションをまたいで残す（保存した変数は全てのコマンドと対話モードで定義される）
+version-about = バージョン、git のコミット、ビルド日、ターゲット、フィーチャを表示する（不具合の報告に添える）
+version-arg-json = JSON で表示する
+
+eval-arg-expression = 数式（例: "2 + 3 * 4"、"r = 3; pi * r^2"）
+eval-arg-load = 先にファイルから変数と関数の定義を読み込む
+arg-vars = 評価の前に変数を設定する（例: --var rate=0.05。繰り返せる。値は式でもよい）
+eval-arg-explain = 構文木と評価の各段階を表示する（2 + 3 * 4 → 2 + 12 → 14）
+eval-arg-each = ';' で区切った複数の文の結果を、最後だけでなく全て表示する
+eval-arg-time = 解析と評価にかかった時間を表示する（標準エラー出力）
@@ -0,0 +1,143 @@
+// 構文木の書き出し（calc-cli ast）
+//
+// JSON は外部のツール向け、DOT は Graphviz で図にするためのもの
+//   {"type": "binary", "op": "+", "left": {"type": "number", "value": "2"}, "right": ...}
+use serde_json::json;
+
+use calc_core::parser::{Expr, Statement, UnaryOp};
+
+fn unary_name(op: UnaryOp) -> &'static str {
+    match op {
+        UnaryOp::Neg => "neg",
+        UnaryOp::Plus => "plus",
+        UnaryOp::Not => "not",
+        UnaryOp::Factorial => "factorial",
+        UnaryOp::Percent => "percent",
+    }
+}
+
+pub fn statement_to_json(statement: &Statement) -> serde_json::Value {
+    match statement {
+        Statement::Expr(expr) => to_json(expr),
+        Statement::Assign { name, value } => json!({ "type": "assign", "name": name, "value": to_json(value) }),
+        Statement::FunctionDef { name, params, body } => {
+            json!({ "type": "function", "name": name, "params": params, "body": to_json(body) })
+        }
+    }
+}
+
+pub fn to_json(expr: &Expr) -> serde_json::Value {
+    match expr {
+        Expr::Number(text) => json!({ "type": "number", "value": text }),
+        Expr::Variable(name) => json!({ "type": "variable", "name": name }),
+        Expr::List(items) => json!({ "type": "list", "items": items.iter().map(to_json).collect::<Vec<_>>() }),
+        Expr::Call { name, args } => json!({ "type": "call", "name": name, "args": args.iter().map(to_json).collect::<Vec<_>>() }),
+        Expr::Unary { op, operand } => json!({ "type": "unary", "op": unary_name(*op), "operand": to_json(operand) }),
+        Expr::Binary { op, left, right } => {
+            json!({ "type": "binary", "op": op.symbol(), "left": to_json(left), "right": to_json(right) })
+        }
+    }
+}
+
+// Graphviz の有向グラフ（節点は n0, n1, ... の順に親から振る）
+pub fn statement_to_dot(statement: &Statement) -> String {
+    let mut graph = Dot::default();
+    match statement {
+        Statement::Expr(expr) => {
+            graph.add(expr);
+        }
+        Statement::Assign { name, value } => {
+            let root = graph.node(&format!("{} =", name));
+            let child = graph.add(value);
+            graph.edge(root, child);
+        }
+        Statement::FunctionDef { name, params, body } => {
+            let root = graph.node(&format!("{}({}) =", name, params.join(", ")));
+            let child = graph.add(body);
+            graph.edge(root, child);
+        }
+    }
+    format!("digraph ast {{\n  node [shape=box, fontname=\"monospace\"];\n{}}}\n", graph.lines.concat())
+}
+
+#[derive(Default)]
+struct Dot {
+    count: usize,
+    lines: Vec<String>,
+}
+
+impl Dot {
+    fn node(&mut self, label: &str) -> usize {
+        let id = self.count;
+        self.count += 1;
+        self.lines.push(format!("  n{} [label=\"{}\"];\n", id, label.replace('\\', "\\\\").replace('"', "\\\"")));
+        id
+    }
+
+    fn edge(&mut self, from: usize, to: usize) {
+        self.lines.push(format!("  n{} -> n{};\n", from, to));
+    }
+
+    // 部分木を加えて根の番号を返す
+    fn add(&mut self, expr: &Expr) -> usize {
+        let (label, children): (String, Vec<&Expr>) = match expr {
+            Expr::Number(text) => (text.clone(), Vec::new()),
+            Expr::Variable(name) => (name.clone(), Vec::new()),
+            Expr::List(items) => ("[ ]".to_string(), items.iter().collect()),
+            Expr::Call { name, args } => (format!("{}()", name), args.iter().collect()),
+            Expr::Unary { op, operand } => (unary_name(*op).to_string(), vec![operand]),
+            Expr::Binary { op, left, right } => (op.symbol().to_string(), vec![left, right]),
+        };
+        let id = self.node(&label);
+        for child in children {
+            let child = self.add(child);
+            self.edge(id, child);
+        }
+        id
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use calc_core::parser::{self, Syntax};
+
+    fn statement(input: &str) -> Statement {
+        parser::parse_statement(input, Syntax::default()).unwrap()
+    }
+
+    #[test]
+    fn test_to_json() {
+        assert_eq!(
+            statement_to_json(&statement("2 + 3 * x")),
+            json!({
+                "type": "binary", "op": "+",
+                "left": { "type": "number", "value": "2" },
+                "right": {
+                    "type": "binary", "op": "*",
+                    "left": { "type": "number", "value": "3" },
+                    "right": { "type": "variable", "name": "x" },
+                },
+            })
+        );
+        assert_eq!(
+            statement_to_json(&statement("f(x) = -x!")),
+            json!({
+                "type": "function", "name": "f", "params": ["x"],
+                "body": { "type": "unary", "op": "neg", "operand": {
+                    "type": "unary", "op": "factorial", "operand": { "type": "variable", "name": "x" },
+                } },
+            })
+        );
+        assert_eq!(statement_to_json(&statement("max([1], 2)"))["args"][0]["type"], "list");
+    }
+
+    #[test]
+    fn test_to_dot() {
+        assert_eq!(
+            statement_to_dot(&statement("r = sqrt(2)")),
+            "digraph ast {\n  node [shape=box, fontname=\"monospace\"];\n  n0 [label=\"r =\"];\n  n1 [label=\"sqrt()\"];\n  \
+n2 [label=\"2\"];\n  n1 -> n2;\n  n0 -> n1;\n}\n"
+        );
+    }
+}
@@ -0,0 +1,108 @@
+// 解析と評価にかかる時間の計測（eval --time と bench サブコマンド）
+use std::time::{Duration, Instant};
+
+use calc_core::evaluator::Evaluator;
+use calc_core::{parser, stats, CalcError};
+
+// 本番の計測の前に捨てる回数の上限（キャッシュなどを温める）
+const MAX_WARMUP: usize = 100;
+
+// 1回分の解析と評価の時間
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Timing {
+    pub parse: Duration,
+    pub evaluate: Duration,
+}
+
+impl Timing {
+    pub fn total(&self) -> Duration {
+        self.parse + self.evaluate
+    }
+}
+
+// 解析・評価・合計それぞれの時間の統計（秒）
+#[derive(Debug, Clone, PartialEq)]
+pub struct Report {
+    pub iterations: usize,
+    pub parse: Summary,
+    pub evaluate: Summary,
+    pub total: Summary,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Summary {
+    pub mean: f64,
+    pub p95: f64,
+    pub min: f64,
+}
+
+// expression を iterations 回解析して評価する（最初の結果がエラーなら計測しない）
+pub fn run(evaluator: &Evaluator, expression: &str, iterations: usize) -> Result<Report, CalcError> {
+    if iterations == 0 {
+        return Err(CalcError::InvalidExpression("iterations must be at least 1".to_string()));
+    }
+    for _ in 0..(iterations / 10).clamp(1, MAX_WARMUP) {
+        measure(evaluator, expression)?;
+    }
+    let timings = (0..iterations).map(|_| measure(evaluator, expression)).collect::<Result<Vec<Timing>, CalcError>>()?;
+    let summarize = |duration: fn(&Timing) -> Duration| -> Result<Summary, CalcError> {
+        let seconds: Vec<f64> = timings.iter().map(|timing| duration(timing).as_secs_f64()).collect();
+        let summary = stats::summarize(&seconds, &[95.0], true)?;
+        Ok(Summary { mean: summary.mean, p95: summary.percentiles[0].value, min: summary.min })
+    };
+    Ok(Report {
+        iterations,
+        parse: summarize(|timing| timing.parse)?,
+        evaluate: summarize(|timing| timing.evaluate)?,
+        total: summarize(Timing::total)?,
+    })
+}
+
+fn measure(evaluator: &Evaluator, expression: &str) -> Result<Timing, CalcError> {
+    let start = Instant::now();
+    let expr = parser::parse(expression, evaluator.syntax)?;
+    let parsed = Instant::now();
+    evaluator.evaluate(&expr)?;
+    Ok(Timing { parse: parsed - start, evaluate: parsed.elapsed() })
+}
+
+// 12.3µs・4.56ms・1.23s のように単位を選んで有効数字3桁で表す
+pub fn format_seconds(seconds: f64) -> String {
+    let (value, unit) = match seconds {
+        s if s >= 1.0 => (s, "s"),
+        s if s >= 1e-3 => (s * 1e3, "ms"),
+        s if s >= 1e-6 => (s * 1e6, "µs"),
+        s => (s * 1e9, "ns"),
+    };
+    let places = match value {
+        v if v >= 100.0 => 0,
+        v if v >= 10.0 => 1,
+        _ => 2,
+    };
+    format!("{:.*}{}", places, value, unit)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run() {
+        let evaluator = Evaluator::default();
+        let report = run(&evaluator, "sum(k, 1, 100, k^2)", 20).unwrap();
+        assert_eq!(report.iterations, 20);
+        assert!(report.total.min > 0.0);
+        assert!(report.parse.min <= report.parse.mean);
+        assert!(report.total.mean >= report.evaluate.mean);
+        assert!(matches!(run(&evaluator, "1 / 0", 5), Err(CalcError::DivisionByZero)));
+        assert!(run(&evaluator, "1", 0).is_err());
+    }
+
+    #[test]
+    fn test_format_seconds() {
+        assert_eq!(format_seconds(1.5), "1.50s");
+        assert_eq!(format_seconds(0.0123), "12.3ms");
+        assert_eq!(format_seconds(0.000_456_7), "457µs");
+        assert_eq!(format_seconds(3e-8), "30.0ns");
+    }
+}
@@ -0,0 +1,23 @@
+// 結果のクリップボードへのコピー（--copy と対話モードの copy）
+//
+// X11 と Wayland ではコピーしたプロセスが内容を渡す仕組みなので、終了すると内容が消える
+// そのため終了する直前のコピーでは、クリップボードマネージャなどが受け取るまで少し待つ
+#[cfg(all(unix, not(any(target_os = "macos", target_os = "android", target_os = "emscripten"))))]
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+
+// 終了前に内容が受け取られるのを待つ時間の上限
+#[cfg(all(unix, not(any(target_os = "macos", target_os = "android", target_os = "emscripten"))))]
+const HANDOFF_TIMEOUT: Duration = Duration::from_secs(2);
+
+// exiting はコピーの後すぐにプロセスが終了するとき true
+pub fn copy(text: &str, exiting: bool) -> Result<()> {
+    let mut clipboard = arboard::Clipboard::new().context("Clipboard is not available")?;
+    let set = clipboard.set();
+    #[cfg(all(unix, not(any(target_os = "macos", target_os = "android", target_os = "emscripten"))))]
+    let set = if exiting { arboard::SetExtLinux::wait_until(set, Instant::now() + HANDOFF_TIMEOUT) } else { set };
+    #[cfg(not(all(unix, not(any(target_os = "macos", target_os = "android", target_os = "emscripten")))))]
+    let _ = exiting;
+    set.text(text).context("Failed to copy to the clipboard")
+}
@@ -0,0 +1,219 @@
+// 端末での色付け（結果・入力のエコー・エラー）
+//
+// 色は --theme のプリセットに --result-color と --error-color を重ねて決める
+use std::fmt;
+use std::io::IsTerminal;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum ColorChoice {
+    /// Color when writing to a terminal and NO_COLOR is not set
+    #[default]
+    Auto,
+    /// Always color, even when piped
+    Always,
+    /// Never color
+    Never,
+}
+
+impl ColorChoice {
+    // 出力先ごとに色を付けるか決める
+    pub fn style(self, stream: &impl IsTerminal) -> Style {
+        let enabled = match self {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            // https://no-color.org/ : 空でない NO_COLOR があれば色を付けない
+            ColorChoice::Auto => std::env::var_os("NO_COLOR").is_none_or(|value| value.is_empty()) && stream.is_terminal(),
+        };
+        Style { enabled, ..Style::default() }
+    }
+}
+
+// 背景色に合わせた配色のプリセット
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum Theme {
+    /// Bright colors for dark backgrounds
+    #[default]
+    Dark,
+    /// Darker colors that stay readable on light backgrounds
+    Light,
+}
+
+impl Theme {
+    pub fn palette(self) -> Palette {
+        match self {
+            Theme::Dark => Palette::default(),
+            Theme::Light => Palette { result: Color::new(true, 34), error: Color::new(true, 31), ..Palette::default() },
+        }
+    }
+}
+
+// 文字の色と装飾（"bold green" や "bright-blue" のように書く）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Color {
+    pub bold: bool,
+    pub dim: bool,
+    pub underline: bool,
+    // 前景色の SGR コード（30-37, 90-97）
+    pub foreground: Option<u8>,
+}
+
+const COLOR_NAMES: [&str; 8] = ["black", "red", "green", "yellow", "blue", "magenta", "cyan", "white"];
+
+impl Color {
+    const fn new(bold: bool, foreground: u8) -> Color {
+        Color { bold, dim: false, underline: false, foreground: Some(foreground) }
+    }
+
+    const DIM: Color = Color { bold: false, dim: true, underline: false, foreground: None };
+
+    // SGR のパラメーター（"1;32" など。何もなければ空）
+    fn code(&self) -> String {
+        let mut codes = Vec::new();
+        if self.bold {
+            codes.push("1".to_string());
+        }
+        if self.dim {
+            codes.push("2".to_string());
+        }
+        if self.underline {
+            codes.push("4".to_string());
+        }
+        codes.extend(self.foreground.map(|code| code.to_string()));
+        codes.join(";")
+    }
+}
+
+impl FromStr for Color {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Color, String> {
+        let mut color = Color::default();
+        for word in text.split([' ', '+']).filter(|word| !word.is_empty()) {
+            let word = word.to_ascii_lowercase();
+            match word.as_str() {
+                "bold" => color.bold = true,
+                "dim" => color.dim = true,
+                "underline" => color.underline = true,
+                "plain" | "none" => {}
+                _ => {
+                    let (bright, name) = match word.strip_prefix("bright-") {
+                        Some(name) => (true, name),
+                        None => (false, word.as_str()),
+                    };
+                    let index = COLOR_NAMES.iter().position(|&color| color == name).ok_or_else(|| {
+                        format!("unknown color '{}' (use bold, dim, underline and {} or bright-<color>)", word, COLOR_NAMES.join(", "))
+                    })?;
+                    color.foreground = Some(if bright { 90 } else { 30 } + index as u8);
+                }
+            }
+        }
+        Ok(color)
+    }
+}
+
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut words = Vec::new();
+        for (set, word) in [(self.bold, "bold"), (self.dim, "dim"), (self.underline, "underline")] {
+            if set {
+                words.push(word.to_string());
+            }
+        }
+        if let Some(code) = self.foreground {
+            let (prefix, index) = if code >= 90 { ("bright-", code - 90) } else { ("", code - 30) };
+            words.push(format!("{}{}", prefix, COLOR_NAMES[index as usize]));
+        }
+        if words.is_empty() {
+            return write!(f, "plain");
+        }
+        write!(f, "{}", words.join(" "))
+    }
+}
+
+// 結果・エコー・エラーの色
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Palette {
+    pub result: Color,
+    pub echo: Color,
+    pub error: Color,
+}
+
+// 暗い背景向け（太字の緑・淡色・太字の赤）
+impl Default for Palette {
+    fn default() -> Palette {
+        Palette { result: Color::new(true, 32), echo: Color::DIM, error: Color::new(true, 31) }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Style {
+    pub enabled: bool,
+    pub palette: Palette,
+}
+
+impl Style {
+    pub fn with_palette(self, palette: Palette) -> Style {
+        Style { palette, ..self }
+    }
+
+    // 計算結果
+    pub fn result(&self, text: &str) -> String {
+        self.paint(&self.palette.result.code(), text)
+    }
+
+    // 入力した式のエコー
+    pub fn dim(&self, text: &str) -> String {
+        self.paint(&self.palette.echo.code(), text)
+    }
+
+    // エラー
+    pub fn error(&self, text: &str) -> String {
+        self.paint(&self.palette.error.code(), text)
+    }
+
+    // グラフの系列ごとの色（緑・青・黄・マゼンタ・シアン・赤の順に繰り返す）
+    pub fn series(&self, index: usize, text: &str) -> String {
+        const COLORS: [&str; 6] = ["32", "34", "33", "35", "36", "31"];
+        self.paint(COLORS[index % COLORS.len()], text)
+    }
+
+    fn paint(&self, code: &str, text: &str) -> String {
+        if self.enabled && !code.is_empty() {
+            format!("\x1b[{}m{}\x1b[0m", code, text)
+        } else {
+            text.to_string()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_paint() {
+        let on = Style { enabled: true, ..Style::default() };
+        assert_eq!(on.result("42"), "\x1b[1;32m42\x1b[0m");
+        assert_eq!(on.dim("1 + 2"), "\x1b[2m1 + 2\x1b[0m");
+        assert_eq!(on.error("Error:"), "\x1b[1;31mError:\x1b[0m");
+        assert_eq!(on.series(7, "⣿"), "\x1b[34m⣿\x1b[0m");
+        assert_eq!(Style::default().result("42"), "42");
+        assert_eq!(ColorChoice::Never.style(&std::io::stdout()), Style { enabled: false, ..Style::default() });
+        assert_eq!(ColorChoice::Always.style(&std::io::stdout()), Style { enabled: true, ..Style::default() });
+    }
+
+    #[test]
+    fn test_palette() {
+        let light = Style { enabled: true, palette: Theme::Light.palette() };
+        assert_eq!(light.result("42"), "\x1b[1;34m42\x1b[0m");
+        let plain = light.with_palette(Palette { result: "plain".parse().unwrap(), ..Palette::default() });
+        assert_eq!(plain.result("42"), "42");
+
+        let color: Color = "bold bright-cyan".parse().unwrap();
+        assert_eq!(color, Color { bold: true, foreground: Some(96), ..Color::default() });
+        assert_eq!(color.to_string(), "bold bright-cyan");
+        assert_eq!("Underline+Magenta".parse::<Color>().unwrap().code(), "4;35");
+        assert!("bold orange".parse::<Color>().is_err());
+    }
+}
@@ -0,0 +1,15 @@
+// サブコマンドの処理（run は Commands に合わせてここの関数を呼び分けるだけ）
+//
+// 関連するサブコマンドを1つのモジュールにまとめ、関数名はサブコマンドの名前に合わせる
+// watch と serve はそれぞれのモジュールの run を直接呼ぶ
+pub mod analysis;
+pub mod arithmetic;
+pub mod batch;
+pub mod conversion;
+pub mod data;
+pub mod eval;
+pub mod inspect;
+pub mod integers;
+pub mod interactive;
+pub mod meta;
+pub mod state;
@@ -0,0 +1,238 @@
+// 方程式・微積分・グラフのサブコマンド（calc-cli solve、root、integrate、diff、derive、plot）
+use std::ops::RangeInclusive;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use serde_json::json;
+
+use calc_core::evaluator::Evaluator;
+use calc_core::number::Number;
+use calc_core::{calculus, solver, symbolic, CalcError};
+use my_cli_errors::outln;
+
+use crate::output::Output;
+use crate::{diagnostic, plot, read_input, IntegrateArgs, PlotArgs, RootArgs};
+
+// 周期関数などで解がたくさん見つかったときに表示する数（--max-roots）
+pub const DEFAULT_MAX_ROOTS: usize = 20;
+
+pub fn solve(
+    evaluator: &Evaluator,
+    output: &Output,
+    equation: &str,
+    variable: Option<&str>,
+    range: RangeInclusive<f64>,
+    max_roots: usize,
+) -> Result<()> {
+    let result = solver::solve(evaluator, equation, variable, range.clone());
+    if output.is_text() {
+        let solution = result.map_err(|e| diagnostic::annotate(equation, e.into(), output.error_color))?;
+        print_solution(evaluator, output, &solution, range, max_roots);
+    } else {
+        output.json(equation, result.map(|solution| solution_to_json(evaluator, &solution, max_roots)))?;
+    }
+    Ok(())
+}
+
+pub fn root(evaluator: &Evaluator, output: &Output, args: RootArgs) -> Result<()> {
+    let RootArgs { function, from, to, method, tolerance, max_iterations, variable } = args;
+    let result = solver::find_root(evaluator, &function, variable.as_deref(), from..=to, method, tolerance, max_iterations);
+    if output.is_text() {
+        let (variable, convergence) = result.map_err(|e| diagnostic::annotate(&function, e.into(), output.error_color))?;
+        output.line(&variable, &evaluator.format_number(&Number::Float(convergence.root)));
+        if !output.quiet {
+            outln!(
+                "  {}: {} iteration(s), error <= {:.2e}, f({}) = {:.2e}",
+                convergence.method.name(),
+                convergence.iterations,
+                convergence.error,
+                variable,
+                convergence.residual
+            );
+        }
+    } else {
+        output.json(&function, result.map(|(variable, convergence)| json!({
+            "variable": variable,
+            "root": convergence.root,
+            "method": convergence.method.name(),
+            "iterations": convergence.iterations,
+            "error": convergence.error,
+            "residual": convergence.residual,
+        })))?;
+    }
+    Ok(())
+}
+
+pub fn integrate(evaluator: &Evaluator, output: &Output, args: IntegrateArgs) -> Result<()> {
+    let IntegrateArgs { function, from, to, tolerance, max_depth, variable } = args;
+    let result = evaluate_bound(evaluator, &from)
+        .and_then(|a| Ok((a, evaluate_bound(evaluator, &to)?)))
+        .and_then(|(a, b)| Ok(calculus::integrate(evaluator, &function, variable.as_deref(), a, b, tolerance, max_depth)?));
+    let label = |variable: &str| format!("integral of {} d{} from {} to {}", function, variable, from, to);
+    print_estimate(evaluator, output, &function, result.map(|(variable, estimate)| (label(&variable), estimate)))
+}
+
+pub fn diff(evaluator: &Evaluator, output: &Output, function: &str, at: &str, step: Option<f64>, variable: Option<&str>) -> Result<()> {
+    let result = evaluate_bound(evaluator, at)
+        .and_then(|x| Ok(calculus::differentiate(evaluator, function, variable, x, step)?));
+    let label = |variable: &str| format!("d/d{} {} at {}", variable, function, at);
+    print_estimate(evaluator, output, function, result.map(|(variable, estimate)| (label(&variable), estimate)))
+}
+
+pub fn derive(evaluator: &Evaluator, output: &Output, function: &str, wrt: Option<&str>) -> Result<()> {
+    let result = symbolic::derive(evaluator, function, wrt);
+    if output.is_text() {
+        let (variable, derivative) = result.map_err(|e| diagnostic::annotate(function, e.into(), output.error_color))?;
+        output.line(&format!("d/d{} {}", variable, function), &derivative.to_string());
+    } else {
+        output.json(function, result.map(|(variable, derivative)| json!({
+            "variable": variable,
+            "derivative": derivative.to_string(),
+        })))?;
+    }
+    Ok(())
+}
+
+// テキスト以外の形式では描かずに計算した点を出力する
+pub fn plot(evaluator: &Evaluator, output: &Output, args: PlotArgs) -> Result<()> {
+    let PlotArgs { functions, from, to, width, height, marker, points, variable } = args;
+    let mut canvas = plot::Plot { width, height, marker, x_range: plot::DEFAULT_RANGE };
+    let data = if points { Some(plot::parse_points(&read_input(Path::new("-"))?)?) } else { None };
+    // データだけのときは既定の範囲をデータに合わせる
+    if let Some(data) = data.as_ref().filter(|_| functions.is_empty()) {
+        let xs = data.points.iter().map(|&(x, _)| x);
+        canvas.x_range = (xs.clone().fold(f64::INFINITY, f64::min), xs.fold(f64::NEG_INFINITY, f64::max));
+    }
+    if let Some(from) = from {
+        canvas.x_range.0 = evaluate_bound(evaluator, &from)?;
+    }
+    if let Some(to) = to {
+        canvas.x_range.1 = evaluate_bound(evaluator, &to)?;
+    }
+    let (x0, x1) = canvas.x_range;
+    anyhow::ensure!(x0 < x1, "The x range is empty: {} to {}", x0, x1);
+    
+    let mut series = Vec::new();
+    for function in &functions {
+        let result = plot::sample(evaluator, function, variable.as_deref(), canvas.x_range, canvas.samples());
+        if output.is_text() {
+            series.push(result.map_err(|e| diagnostic::annotate(function, e.into(), output.error_color))?);
+        } else {
+            output.json(function, result.map(|series| json!({ "points": series.points })))?;
+        }
+    }
+    match data {
+        Some(data) if !output.is_text() => output.json(&data.label, Ok::<_, CalcError>(json!({ "points": data.points })))?,
+        Some(data) => series.push(data),
+        None => {}
+    }
+    if output.is_text() {
+        for line in canvas.render(&series, output.color)? {
+            outln!("{}", line);
+        }
+    }
+    Ok(())
+}
+
+// solve サブコマンドの解を「x = 解」の形で1行ずつ表示する
+fn print_solution(evaluator: &Evaluator, output: &Output, solution: &solver::Solution, range: RangeInclusive<f64>, max_roots: usize) {
+    let variable = &solution.variable;
+    if solution.identity {
+        outln!("Every value of {} is a solution", variable);
+    } else if solution.roots.is_empty() && solution.method == solver::Method::Numeric {
+        outln!("No real solution found for {} between {} and {}", variable, range.start(), range.end());
+    } else if solution.roots.is_empty() {
+        outln!("No solution");
+    }
+    let (roots, more) = shown_roots(&solution.roots, max_roots);
+    for root in roots {
+        output.line(variable, &format_root(evaluator, root));
+    }
+    if more > 0 && !output.quiet {
+        outln!("… {} more root(s) between {} and {} (use --max-roots to show them)", more, range.start(), range.end());
+    }
+}
+
+// 表示する解と、表示しない解の数（max_roots が 0 なら全て表示する）
+fn shown_roots(roots: &[solver::Root], max_roots: usize) -> (&[solver::Root], usize) {
+    let shown = if max_roots == 0 { roots.len() } else { roots.len().min(max_roots) };
+    (&roots[..shown], roots.len() - shown)
+}
+
+// 複素数の解は a + bi の形にする（実部が 0 なら bi）
+fn format_root(evaluator: &Evaluator, root: &solver::Root) -> String {
+    let format = |value: f64| evaluator.format_number(&Number::Float(value));
+    match *root {
+        solver::Root::Real(x) => format(x),
+        solver::Root::Complex { re: 0.0, im } => format!("{}i", format(im)),
+        solver::Root::Complex { re, im } => {
+            let sign = if im < 0.0 { "-" } else { "+" };
+            format!("{} {} {}i", format(re), sign, format(im.abs()))
+        }
+    }
+}
+
+fn solution_to_json(evaluator: &Evaluator, solution: &solver::Solution, max_roots: usize) -> serde_json::Value {
+    let (roots, more) = shown_roots(&solution.roots, max_roots);
+    let roots: Vec<serde_json::Value> = roots
+        .iter()
+        .map(|root| match *root {
+            solver::Root::Real(x) => json!(x),
+            solver::Root::Complex { re, im } => json!({ "re": re, "im": im, "text": format_root(evaluator, root) }),
+        })
+        .collect();
+    json!({
+        "variable": solution.variable,
+        "method": solution.method.name(),
+        "identity": solution.identity,
+        "roots": roots,
+        "more": more,
+    })
+}
+
+// --from pi のような数値の引数を式として評価する
+fn evaluate_bound(evaluator: &Evaluator, text: &str) -> Result<f64> {
+    let value = evaluator.evaluate_str(text).with_context(|| format!("Invalid bound '{}'", text))?;
+    Ok(value.into_number()?.to_f64())
+}
+
+// integrate と diff の結果を誤差の見積もりとともに表示する
+fn print_estimate(evaluator: &Evaluator, output: &Output, function: &str, result: Result<(String, calculus::Estimate)>) -> Result<()> {
+    if output.is_text() {
+        let (label, estimate) = result.map_err(|e| diagnostic::annotate(function, e, output.error_color))?;
+        output.line(&label, &evaluator.format_number(&Number::Float(estimate.value)));
+        if !output.quiet {
+            outln!("  error <= {:.2e}, {} evaluation(s)", estimate.error, estimate.evaluations);
+        }
+        return Ok(());
+    }
+    output.json(function, result.map(|(label, estimate)| json!({
+        "expression": label,
+        "value": estimate.value,
+        "error": estimate.error,
+        "evaluations": estimate.evaluations,
+    })))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_periodic_roots_are_capped() {
+        let evaluator = Evaluator::default();
+        // sin(x) = 0 は -100 から 100 の間に 63 個の解がある
+        let solution = solver::solve(&evaluator, "sin(x)", None, solver::DEFAULT_RANGE).unwrap();
+        assert_eq!(solution.roots.len(), 63);
+        let (roots, more) = shown_roots(&solution.roots, DEFAULT_MAX_ROOTS);
+        assert_eq!((roots.len(), more), (20, 43));
+        assert_eq!(roots, &solution.roots[..20]);
+        assert_eq!(shown_roots(&solution.roots, 0), (&solution.roots[..], 0));
+        assert_eq!(shown_roots(&solution.roots[..2], 5), (&solution.roots[..2], 0));
+
+        let json = solution_to_json(&evaluator, &solution, 3);
+        assert_eq!(json["roots"].as_array().unwrap().len(), 3);
+        assert_eq!(json["more"], 60);
+    }
+}
@@ -0,0 +1,128 @@
+// 四則演算・累乗・平方根と percent、rand サブコマンド
+//
+// どれも現在の数値モード（--precision、--exact）の数で計算する
+use anyhow::Result;
+use clap::ValueEnum;
+
+use calc_core::evaluator::Evaluator;
+use calc_core::number::Number;
+use calc_core::random;
+use calc_core::value::Value;
+use my_cli_errors::outln;
+
+use crate::output::{self, Output};
+use crate::{PercentCommand, RandArgs};
+
+pub fn add(evaluator: &Evaluator, output: &Output, a: f64, b: f64) -> Result<()> {
+    let result = evaluator.number(a).add(&evaluator.number(b), evaluator.digits);
+    output.value(evaluator, &format!("{} + {}", a, b), result.map(Value::Number))?;
+    Ok(())
+}
+
+pub fn subtract(evaluator: &Evaluator, output: &Output, a: f64, b: f64) -> Result<()> {
+    let result = evaluator.number(a).sub(&evaluator.number(b), evaluator.digits);
+    output.value(evaluator, &format!("{} - {}", a, b), result.map(Value::Number))?;
+    Ok(())
+}
+
+pub fn multiply(evaluator: &Evaluator, output: &Output, a: f64, b: f64) -> Result<()> {
+    let result = evaluator.number(a).mul(&evaluator.number(b), evaluator.digits);
+    output.value(evaluator, &format!("{} * {}", a, b), result.map(Value::Number))?;
+    Ok(())
+}
+
+pub fn divide(evaluator: &Evaluator, output: &Output, a: f64, b: f64) -> Result<()> {
+    let result = evaluator.number(a).div(&evaluator.number(b), evaluator.digits);
+    output.value(evaluator, &format!("{} / {}", a, b), result.map(Value::Number))?;
+    Ok(())
+}
+
+pub fn power(evaluator: &Evaluator, output: &Output, base: f64, exp: f64) -> Result<()> {
+    let result = evaluator.number(base).pow(&evaluator.number(exp), evaluator.digits);
+    output.value(evaluator, &format!("{}^{}", base, exp), result.map(Value::Number))?;
+    Ok(())
+}
+
+pub fn square_root(evaluator: &Evaluator, output: &Output, number: f64) -> Result<()> {
+    let result = evaluator.number(number).sqrt(evaluator.digits);
+    output.value(evaluator, &format!("√{}", number), result.map(Value::Number))?;
+    Ok(())
+}
+
+// --seed がなければ実行ごとに違う乱数にする
+pub fn rand(evaluator: &Evaluator, output: &Output, seed: Option<u64>, args: RandArgs) -> Result<()> {
+    let RandArgs { distribution, min, max, mean, stddev, count } = args;
+    let mut random = match seed {
+        Some(seed) => random::Random::seeded(seed),
+        None => random::Random::from_entropy(),
+    };
+    let result = (0..count)
+        .map(|_| random_number(&mut random, distribution, min, max, mean, stddev))
+        .collect::<Result<Vec<Number>>>();
+    if output.is_text() {
+        for value in result? {
+            outln!("{}", evaluator.format_number(&value));
+        }
+    } else {
+        let expression = format!("rand {}", distribution.to_possible_value().expect("no skipped variants").get_name());
+        output.json(&expression, result.map(|values| values.iter().map(output::number_to_json).collect()))?;
+    }
+    Ok(())
+}
+
+// rand サブコマンドの乱数を1つ生成する
+fn random_number(
+    random: &mut random::Random,
+    distribution: random::Distribution,
+    min: Option<f64>,
+    max: Option<f64>,
+    mean: f64,
+    stddev: f64,
+) -> Result<Number> {
+    Ok(match distribution {
+        random::Distribution::Uniform => Number::Float(random.range(min.unwrap_or(0.0), max.unwrap_or(1.0))?),
+        random::Distribution::Int => {
+            let bound = |value: Option<f64>, default: i64| match value {
+                Some(value) if value.fract() != 0.0 => anyhow::bail!("Integer bounds expected, got {}", value),
+                Some(value) => Ok(value as i64),
+                None => Ok(default),
+            };
+            Number::Integer(random.int(bound(min, 1)?, bound(max, 100)?)?.into())
+        }
+        random::Distribution::Normal => Number::Float(random.normal(mean, stddev)?),
+    })
+}
+
+// percent サブコマンド（現在の数値モードで計算する）
+pub fn percent(evaluator: &Evaluator, output: &Output, command: PercentCommand) -> Result<()> {
+    let digits = evaluator.digits;
+    let hundred = evaluator.number(100.0);
+    match command {
+        PercentCommand::Of { percent, value } => {
+            let result = evaluator.number(percent).mul(&evaluator.number(value), digits).and_then(|x| x.div(&hundred, digits));
+            output.value(evaluator, &format!("{}% of {}", percent, value), result.map(Value::Number))?;
+        }
+        PercentCommand::Change { from, to } => {
+            let start = evaluator.number(from);
+            let result = evaluator.number(to).sub(&start, digits)
+                .and_then(|x| x.div(&start, digits))
+                .and_then(|x| x.mul(&hundred, digits));
+            if output.is_text() {
+                let change = result?;
+                let sign = if change.to_f64() > 0.0 { "+" } else { "" };
+                outln!("{} -> {}: {}{}%", from, to, sign, evaluator.format_number(&change));
+            } else {
+                output.json(&format!("{} -> {}", from, to), result.map(|change| output::number_to_json(&change)))?;
+            }
+        }
+        PercentCommand::WhatPercent { part, whole } => {
+            let result = evaluator.number(part).mul(&hundred, digits).and_then(|x| x.div(&evaluator.number(whole), digits));
+            if output.is_text() {
+                outln!("{} is {}% of {}", part, evaluator.format_number(&result?), whole);
+            } else {
+                output.json(&format!("{} of {}", part, whole), result.map(|percent| output::number_to_json(&percent)))?;
+            }
+        }
+    }
+    Ok(())
+}
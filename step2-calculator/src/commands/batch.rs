@@ -0,0 +1,104 @@
+// ファイルや標準入力の行を順に実行するサブコマンド（calc-cli batch と run）
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use serde_json::json;
+
+use calc_core::evaluator::Evaluator;
+use my_cli_errors::outln;
+use my_cli_progress::Progress;
+
+use crate::output::{Output, OutputFormat, Record};
+use crate::{diagnostic, i18n, read_input, script};
+
+use super::eval::{define_variables, print_outcome};
+
+// 1行に1つの式を順に評価する（失敗した行があっても最後まで続ける）
+// summary なら最後に行ごとの結果をまとめて出力する
+pub fn batch(evaluator: &mut Evaluator, output: &Output, path: &Path, summary: bool, vars: &[(String, String)]) -> Result<()> {
+    define_variables(evaluator, vars)?;
+    let source = read_input(path)?;
+    let lines: Vec<(usize, &str)> = source
+        .lines()
+        .map(str::trim)
+        .enumerate()
+        .filter(|(_, input)| !input.is_empty() && !input.starts_with('#'))
+        .collect();
+    // 進捗は標準エラー出力に出す（--quiet と JSON / CSV では出さない）
+    let progress = Progress::bar(lines.len() as u64, path.display().to_string(), my_cli_progress::shown(output.quiet || !output.is_text()));
+    let mut report = script::Report::default();
+    for (index, input) in lines {
+        match evaluator.execute(input) {
+            Ok(outcome) => {
+                report.push(index + 1, input, None);
+                progress.suspend(|| print_outcome(evaluator, output, input, outcome));
+            }
+            Err(e) => {
+                report.push(index + 1, input, Some(e.to_string()));
+                progress.suspend(|| {
+                    if output.is_text() {
+                        let label = i18n::tr("error-on-line", &[("line", (index + 1).into())]);
+                        eprintln!("{} {}: {}", output.error_color.error(&label), input, i18n::calc_error(i18n::messages(), &e));
+                        if let Some(snippet) = diagnostic::render(input, &e, output.error_color) {
+                            eprintln!("{}", snippet);
+                        }
+                    } else {
+                        output.print(&Record::new(input, Err::<serde_json::Value, _>(e)));
+                    }
+                });
+            }
+        }
+        progress.inc(1);
+    }
+    progress.finish();
+    if summary {
+        print_report(output, &report);
+    }
+    if report.failed > 0 {
+        anyhow::bail!(i18n::tr("lines-failed", &[("count", report.failed.into())]));
+    }
+    Ok(())
+}
+
+
+// スクリプトの exit(コード) はそのまま終了コードにする
+pub fn run(evaluator: &mut Evaluator, output: &Output, path: &Path, keep_going: bool) -> Result<()> {
+    let source = read_input(path)?;
+    if !keep_going {
+        let code = script::run(evaluator, &source, &mut std::io::stdout().lock())
+            .with_context(|| format!("Failed to run {}", path.display()))?;
+        if code != 0 {
+            std::process::exit(code);
+        }
+        return Ok(());
+    }
+    let (code, report) = script::run_keep_going(evaluator, &source, &mut std::io::stdout().lock(), |line, statement, e| {
+        let label = i18n::tr("error-on-line", &[("line", line.into())]);
+        eprintln!("{} {}: {}", output.error_color.error(&label), statement, i18n::describe_chain(e));
+    });
+    print_report(output, &report);
+    if code != 0 {
+        std::process::exit(code);
+    }
+    if report.failed > 0 {
+        anyhow::bail!(i18n::tr("lines-failed", &[("count", report.failed.into())]));
+    }
+    Ok(())
+}
+
+// 行ごとの結果のまとめ
+// JSON では結果の行と区別できるように {"summary": ...} の1行にし、それ以外は標準エラー出力に表で出す
+fn print_report(output: &Output, report: &script::Report) {
+    if output.format == OutputFormat::Json {
+        outln!("{}", json!({ "summary": report }));
+        return;
+    }
+    let counts = [("total", report.total.into()), ("ok", report.ok.into()), ("failed", report.failed.into())];
+    eprintln!("{}", i18n::tr("summary", &counts));
+    for line in &report.lines {
+        match &line.error {
+            None => eprintln!("  {:>4}  ok      {}", line.line, line.input),
+            Some(error) => eprintln!("  {:>4}  {}  {}: {}", line.line, output.error_color.error("failed"), line.input, error),
+        }
+    }
+}
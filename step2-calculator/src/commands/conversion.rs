@@ -0,0 +1,123 @@
+// 通貨と単位の変換、単位と定数の一覧（calc-cli currency、convert、units、constants）
+use std::path::Path;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use serde_json::json;
+
+use calc_core::evaluator::Evaluator;
+use calc_core::format::NumberFormat;
+use calc_core::value::Value;
+use calc_core::{constants, units};
+use my_cli_errors::outln;
+
+use crate::output::{value_to_json, Output};
+use crate::{currency, rates_path, CurrencyArgs};
+
+pub fn currency(evaluator: &mut Evaluator, output: &Output, args: CurrencyArgs) -> Result<()> {
+    let CurrencyArgs { amount, currencies, rates, rates_url, max_age, refresh } = args;
+    let rates = load_rates(rates.as_deref(), rates_url.as_deref(), Duration::from_secs(max_age * 3600), refresh)?;
+    let currencies: Vec<String> = currencies.iter().map(|code| code.to_uppercase()).collect();
+    let (from, to) = match currencies.as_slice() {
+        [from, to] => (Some(from.as_str()), to.as_str()),
+        [to] => (None, to.as_str()),
+        _ => unreachable!("clap takes one or two currencies"),
+    };
+    let label = match from {
+        Some(from) => format!("{} {}", amount, from),
+        None => amount.clone(),
+    };
+    let result = currency::convert(evaluator, &rates, &amount, from, to);
+    if output.is_text() {
+        output.line(&label, &format!("{} {}", evaluator.format(&result?), to));
+    } else {
+        output.json(&label, result.map(|value| json!({ "value": value_to_json(&value), "currency": to })))?;
+    }
+    Ok(())
+}
+
+pub fn convert(evaluator: &Evaluator, output: &Output, value: f64, from: &str, to: &str) -> Result<()> {
+    let result = convert_units(value, from, to);
+    if output.is_text() {
+        let (quantity, result) = result?;
+        output.line(&quantity.to_string(), &evaluator.format(&Value::Quantity(result)));
+    } else {
+        let expression = format!("{} {} -> {}", value, from, to);
+        output.json(&expression, result.map(|(_, result)| value_to_json(&Value::Quantity(result))))?;
+    }
+    Ok(())
+}
+
+pub fn units(output: &Output) -> Result<()> {
+    if output.is_text() {
+        print_units();
+    } else {
+        let units = units::UNITS
+            .iter()
+            .map(|unit| json!({ "name": unit.name, "aliases": unit.aliases, "category": unit.category }))
+            .collect();
+        output.json::<anyhow::Error>("units", Ok(units))?;
+    }
+    Ok(())
+}
+
+pub fn constants(evaluator: &Evaluator, output: &Output) -> Result<()> {
+    if output.is_text() {
+        print_constants(&evaluator.number_format);
+    } else {
+        let constants = constants::CONSTANTS.iter().map(|c| (c.name.to_string(), json!(c.value))).collect();
+        output.json::<anyhow::Error>("constants", Ok(serde_json::Value::Object(constants)))?;
+    }
+    Ok(())
+}
+
+// currency のレート（--rates のファイル、--rates-url から取得したもの、保存してあるものの順）
+fn load_rates(file: Option<&Path>, url: Option<&str>, max_age: Duration, refresh: bool) -> Result<currency::Rates> {
+    if let Some(path) = file {
+        return currency::Rates::load(path);
+    }
+    let cache = rates_path().context("Could not find the home directory to cache exchange rates")?;
+    match url {
+        Some(url) => currency::Rates::fetch(url, &cache, max_age, refresh),
+        None if cache.exists() => currency::Rates::load(&cache),
+        None => anyhow::bail!("No exchange rates: pass --rates FILE or --rates-url URL (or set CALC_RATES_URL)"),
+    }
+}
+
+// km/h や m^2 のような単位の式を読む
+// convert サブコマンドの変換前と変換後の量
+fn convert_units(value: f64, from: &str, to: &str) -> Result<(units::Quantity, units::Quantity)> {
+    let quantity = units::Quantity { value, unit: parse_unit(from)? };
+    let result = quantity.convert(&parse_unit(to)?)?;
+    Ok((quantity, result))
+}
+
+fn parse_unit(text: &str) -> Result<units::Unit> {
+    let mut evaluator = Evaluator::default();
+    evaluator.units = true;
+    match evaluator.evaluate_str(text) {
+        Ok(Value::Quantity(quantity)) if quantity.value == 1.0 => Ok(quantity.unit),
+        _ => anyhow::bail!("Unknown unit: '{}' (see `calc-cli units`)", text),
+    }
+}
+
+fn print_units() {
+    let mut category = "";
+    for unit in units::UNITS {
+        if unit.category != category {
+            category = unit.category;
+            outln!("{}:", category);
+        }
+        if unit.aliases.is_empty() {
+            outln!("  {}", unit.name);
+        } else {
+            outln!("  {:<6} ({})", unit.name, unit.aliases.join(", "));
+        }
+    }
+}
+
+fn print_constants(format: &NumberFormat) {
+    for constant in constants::CONSTANTS {
+        outln!("{:<4} = {:<20} {}", constant.name, format.format_f64(constant.value), constant.description);
+    }
+}
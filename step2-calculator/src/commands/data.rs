@@ -0,0 +1,167 @@
+// 数の並びを扱うサブコマンド（calc-cli matrix と stats）
+//
+// どちらも --file で CSV や空白区切りの数を読める（"-" は標準入力）
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+
+use calc_core::evaluator::Evaluator;
+use calc_core::format::NumberFormat;
+use calc_core::number::Number;
+use calc_core::parser::BinaryOp;
+use calc_core::value::{self, Value};
+use calc_core::{matrix, stats};
+use my_cli_errors::outln;
+
+use crate::output::Output;
+use crate::{csv, read_input, MatrixOperation};
+
+pub fn matrix(
+    evaluator: &Evaluator,
+    output: &Output,
+    operation: MatrixOperation,
+    mut operands: Vec<String>,
+    file: Option<PathBuf>,
+    column: &[csv::Column],
+) -> Result<()> {
+    let result = file
+        .as_deref()
+        .map(|path| read_matrix(evaluator, path, column))
+        .transpose()
+        .and_then(|first| apply_operation(evaluator, operation, first, &operands));
+    if let Some(path) = &file {
+        operands.insert(0, path.display().to_string());
+    }
+    if output.is_text() {
+        match result? {
+            Value::Matrix(matrix) => outln!("{}", matrix.to_grid(|number| evaluator.format_number(number))),
+            other => outln!("{}", evaluator.format(&other)),
+        }
+    } else {
+        let name = operation.to_possible_value().expect("no skipped variants");
+        output.value(evaluator, &format!("{} {}", name.get_name(), operands.join(" ")), result)?;
+    }
+    Ok(())
+}
+
+pub fn stats(
+    evaluator: &Evaluator,
+    output: &Output,
+    numbers: Vec<f64>,
+    file: Option<&Path>,
+    column: Option<&csv::Column>,
+    percentiles: &[f64],
+    population: bool,
+) -> Result<()> {
+    let result = read_numbers(numbers, file, column)
+        .and_then(|numbers| Ok(stats::summarize(&numbers, percentiles, population)?));
+    if output.is_text() {
+        print_summary(&result?, &evaluator.number_format);
+    } else {
+        output.json("stats", result.and_then(|summary| Ok(serde_json::to_value(summary)?)))?;
+    }
+    Ok(())
+}
+
+// matrix サブコマンドの計算
+// first は CSV ファイルから読んだ最初の被演算子
+fn apply_operation(evaluator: &Evaluator, operation: MatrixOperation, first: Option<Value>, operands: &[String]) -> Result<Value> {
+    let values = first
+        .map(Ok)
+        .into_iter()
+        .chain(operands.iter().map(|operand| evaluator.evaluate_str(operand).with_context(|| format!("Invalid operand: {}", operand))))
+        .collect::<Result<Vec<Value>>>()?;
+    
+    let (name, fold_op) = match operation {
+        MatrixOperation::Show => ("show", None),
+        MatrixOperation::Det => ("det", None),
+        MatrixOperation::Inverse => ("inverse", None),
+        MatrixOperation::Transpose => ("transpose", None),
+        MatrixOperation::Add => ("add", Some(BinaryOp::Add)),
+        MatrixOperation::Subtract => ("subtract", Some(BinaryOp::Sub)),
+        MatrixOperation::Multiply => ("multiply", Some(BinaryOp::Mul)),
+        MatrixOperation::Dot => ("dot", None),
+        MatrixOperation::Cross => ("cross", None),
+    };
+    
+    match (operation, fold_op) {
+        (MatrixOperation::Show, _) => match <[Value; 1]>::try_from(values) {
+            Ok([value]) => Ok(value),
+            Err(values) => anyhow::bail!("show expects 1 operand, got {}", values.len()),
+        },
+        // 左から順に演算をたたみ込む
+        (_, Some(op)) => {
+            if values.len() < 2 {
+                anyhow::bail!("{} expects at least 2 operands, got {}", name, values.len());
+            }
+            let mut values = values.into_iter();
+            let first = values.next().expect("at least two operands");
+            let result = values.try_fold(first, |acc, value| Value::binary(op, &acc, &value, evaluator.digits))?;
+            Ok(result)
+        }
+        (_, None) => Ok(value::call_linear(name, &values, evaluator.digits)
+            .expect("matrix operations are linear algebra functions")?),
+    }
+}
+
+// stats の入力（引数もファイルもなければ標準入力から読む）
+fn read_numbers(mut numbers: Vec<f64>, file: Option<&Path>, column: Option<&csv::Column>) -> Result<Vec<f64>> {
+    let source = match file {
+        Some(path) => Some(path),
+        None if numbers.is_empty() || column.is_some() => Some(Path::new("-")),
+        None => None,
+    };
+    if let Some(path) = source {
+        let input = read_input(path)?;
+        match column {
+            Some(column) => {
+                let table = csv::Table::parse(&input).with_context(|| format!("Invalid CSV in {}", path.display()))?;
+                numbers.extend(table.column(column).with_context(|| format!("Invalid CSV in {}", path.display()))?);
+            }
+            None => numbers.extend(stats::parse_numbers(&input)?),
+        }
+    }
+    Ok(numbers)
+}
+
+// CSV の列を行列にする（1列だけならベクトル）
+fn read_matrix(evaluator: &Evaluator, path: &Path, columns: &[csv::Column]) -> Result<Value> {
+    let rows = csv::Table::parse(&read_input(path)?)
+        .and_then(|table| table.select(columns))
+        .with_context(|| format!("Invalid CSV in {}", path.display()))?;
+    let rows = rows
+        .iter()
+        .map(|row| row.iter().map(|field| Number::parse(field, evaluator.number_mode)).collect::<Result<Vec<_>, _>>())
+        .collect::<Result<Vec<_>, _>>()
+        .with_context(|| format!("Invalid CSV in {}", path.display()))?;
+    if rows.is_empty() {
+        anyhow::bail!("No rows in {}", path.display());
+    }
+    if rows[0].len() == 1 {
+        return Ok(Value::Vector(rows.into_iter().flatten().collect()));
+    }
+    Ok(Value::Matrix(matrix::Matrix::from_rows(rows)?))
+}
+
+fn print_summary(summary: &stats::Summary, format: &NumberFormat) {
+    let optional = |value: Option<f64>| value.map_or("n/a".to_string(), |v| format.format_f64(v));
+    let mode = if summary.mode.is_empty() {
+        "(none)".to_string()
+    } else {
+        summary.mode.iter().map(|&v| format.format_f64(v)).collect::<Vec<_>>().join(", ")
+    };
+    
+    outln!("{:<10}{}", "count", summary.count);
+    outln!("{:<10}{}", "sum", format.format_f64(summary.sum));
+    outln!("{:<10}{}", "mean", format.format_f64(summary.mean));
+    outln!("{:<10}{}", "median", format.format_f64(summary.median));
+    outln!("{:<10}{}", "mode", mode);
+    outln!("{:<10}{}", "variance", optional(summary.variance));
+    outln!("{:<10}{}", "stddev", optional(summary.stddev));
+    outln!("{:<10}{}", "min", format.format_f64(summary.min));
+    outln!("{:<10}{}", "max", format.format_f64(summary.max));
+    for percentile in &summary.percentiles {
+        outln!("{:<10}{}", format!("p{}", percentile.percent), format.format_f64(percentile.value));
+    }
+}
@@ -0,0 +1,206 @@
+// 式の評価（calc-cli eval と calc-cli -）
+//
+// 文の実行と結果の表示、--load と --var の読み込みは interactive と batch でも使う
+use std::path::Path;
+use std::time::Instant;
+
+use anyhow::{Context, Result};
+use serde_json::json;
+
+use calc_core::evaluator::{Evaluator, Outcome};
+use calc_core::{explain, parser, CalcError};
+use my_cli_errors::outln;
+
+use crate::output::{value_to_json, Output, Record};
+use crate::{bench, diagnostic, read_input, EvalArgs};
+
+pub fn eval(evaluator: &mut Evaluator, output: &Output, args: EvalArgs) -> Result<()> {
+    let EvalArgs { expression, load, vars, explain, each, time } = args;
+    // 「;」で区切った文を左から実行し、最後の文の結果を表示する
+    let statements = parser::split_statements(&expression);
+    let (last, earlier) = statements.split_last().expect("split_statements returns at least one statement");
+    if explain {
+        for path in &load {
+            load_definitions(evaluator, path)?;
+        }
+        define_variables(evaluator, &vars)?;
+        // 説明するのは最後の文だけ
+        run_statements(evaluator, output, earlier, each, &mut bench::Timing::default())?;
+        return print_explanation(evaluator, output, last).map_err(|e| {
+            if output.is_text() { diagnostic::annotate(last, e, output.error_color) } else { e }
+        });
+    }
+    let prepared = load
+        .iter()
+        .try_for_each(|path| load_definitions(evaluator, path).map(|_| ()))
+        .and_then(|()| define_variables(evaluator, &vars));
+    if let Err(e) = prepared {
+        output.value(evaluator, &expression, Err(e))?;
+        return Ok(());
+    }
+    let mut timing = bench::Timing::default();
+    run_statements(evaluator, output, earlier, each, &mut timing)?;
+    run_statements(evaluator, output, &[last], true, &mut timing)?;
+    if time {
+        eprintln!(
+            "time: parse {} + evaluate {} = {}",
+            bench::format_seconds(timing.parse.as_secs_f64()),
+            bench::format_seconds(timing.evaluate.as_secs_f64()),
+            bench::format_seconds(timing.total().as_secs_f64()),
+        );
+    }
+    Ok(())
+}
+
+// 行をつないで1つの式にする（エコーとエラー位置の表示も1行になる）
+pub fn stdin(evaluator: &mut Evaluator, output: &Output, vars: &[(String, String)]) -> Result<()> {
+    let input = read_input(Path::new("-"))?;
+    let expression = input.lines().map(str::trim).filter(|line| !line.is_empty()).collect::<Vec<_>>().join(" ");
+    if expression.is_empty() {
+        anyhow::bail!("No expression on standard input");
+    }
+    define_variables(evaluator, vars)?;
+    let statements = parser::split_statements(&expression);
+    let (last, earlier) = statements.split_last().expect("split_statements returns at least one statement");
+    let mut timing = bench::Timing::default();
+    run_statements(evaluator, output, earlier, false, &mut timing)?;
+    run_statements(evaluator, output, &[last], true, &mut timing)
+}
+
+// eval の文を順に実行する（show なら結果を表示し、そうでなければ ans だけ更新する）
+// 解析と実行にかかった時間を timing に足していく
+pub fn run_statements(
+    evaluator: &mut Evaluator,
+    output: &Output,
+    statements: &[&str],
+    show: bool,
+    timing: &mut bench::Timing,
+) -> Result<()> {
+    for statement in statements {
+        let start = Instant::now();
+        let parsed = parser::parse_statement(statement, evaluator.syntax);
+        let parse_end = Instant::now();
+        let result = parsed.and_then(|parsed| evaluator.execute_statement(parsed));
+        let (parse, evaluate) = (parse_end - start, parse_end.elapsed());
+        timing.parse += parse;
+        timing.evaluate += evaluate;
+        tracing::debug!(statement, ?parse, ?evaluate, "evaluated");
+        match result {
+            Ok(outcome) if show => print_outcome(evaluator, output, statement, outcome),
+            Ok(Outcome::Value(value)) => evaluator.set_last_result(value),
+            Ok(_) => {}
+            Err(e) => {
+                let annotate = |e: CalcError| {
+                    if output.is_text() { diagnostic::annotate(statement, e.into(), output.error_color) } else { e.into() }
+                };
+                return output.value(evaluator, statement, Err(e)).map_err(annotate);
+            }
+        }
+    }
+    Ok(())
+}
+
+pub fn print_outcome(evaluator: &mut Evaluator, output: &Output, input: &str, outcome: Outcome) {
+    match outcome {
+        Outcome::Value(result) => {
+            let _ = output.value::<CalcError>(evaluator, input, Ok(result.clone()));
+            evaluator.set_last_result(result);
+        }
+        Outcome::Assigned { name, value } if output.is_text() => output.line(&name, &evaluator.format(&value)),
+        Outcome::Assigned { value, .. } => output.print(&Record::new::<CalcError>(input, Ok(value_to_json(&value)))),
+        Outcome::Defined { name, params } if output.is_text() => outln!("Defined {}({})", name, params.join(", ")),
+        Outcome::Defined { name, params } => {
+            output.print(&Record::new::<CalcError>(input, Ok(json!(format!("{}({})", name, params.join(", "))))));
+        }
+    }
+}
+
+// 履歴に残す文の結果（代入は代入した値、関数定義は f(x) の形）
+pub fn outcome_text(evaluator: &Evaluator, outcome: &Outcome) -> String {
+    match outcome {
+        Outcome::Value(value) | Outcome::Assigned { value, .. } => evaluator.format(value),
+        Outcome::Defined { name, params } => format!("{}({})", name, params.join(", ")),
+    }
+}
+
+// 構文木と評価の各段階を表示する（JSON・CSV では結果の記録に steps を含める）
+pub fn print_explanation(evaluator: &Evaluator, output: &Output, expression: &str) -> Result<()> {
+    let explanation = parser::parse(expression, evaluator.syntax).and_then(|expr| {
+        let steps = explain::steps(evaluator, &expr)?;
+        Ok((expr, steps))
+    });
+    if !output.is_text() {
+        let result = explanation.and_then(|(expr, steps)| {
+            let value = evaluator.evaluate(&expr)?;
+            Ok(json!({ "steps": steps, "result": value_to_json(&value) }))
+        });
+        return Ok(output.json(expression, result)?);
+    }
+    let (expr, steps) = explanation?;
+    outln!("Parse tree:");
+    for line in explain::tree(&expr).lines() {
+        outln!("  {}", line);
+    }
+    outln!("Steps:");
+    for (i, step) in steps.iter().enumerate() {
+        outln!("  {} {}", if i == 0 { " " } else { "=" }, step);
+    }
+    Ok(())
+}
+
+// ファイルから変数・関数定義を読み込む（空行と # で始まる行は無視）
+pub fn load_definitions(evaluator: &mut Evaluator, path: &Path) -> Result<usize> {
+    let source = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    
+    let mut count = 0;
+    for (index, line) in source.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        evaluator.execute(line)
+            .with_context(|| format!("{}:{}: {}", path.display(), index + 1, line))?;
+        count += 1;
+    }
+    tracing::debug!("loaded {} definition(s) from {}", count, path.display());
+    Ok(count)
+}
+
+// --var の NAME=VALUE（VALUE は式として後で評価する）
+pub fn parse_variable(text: &str) -> Result<(String, String), String> {
+    let (name, value) = text.split_once('=').ok_or_else(|| format!("expected NAME=VALUE, got '{}'", text))?;
+    let (name, value) = (name.trim(), value.trim());
+    let mut chars = name.chars();
+    let valid = chars.next().is_some_and(|c| c.is_alphabetic() || c == '_') && chars.all(|c| c.is_alphanumeric() || c == '_');
+    if !valid {
+        return Err(format!("invalid variable name '{}'", name));
+    }
+    if value.is_empty() {
+        return Err(format!("missing value for '{}'", name));
+    }
+    Ok((name.to_string(), value.to_string()))
+}
+
+// --var で渡された変数を順に定義する（後の値で前の変数を使える）
+pub fn define_variables(evaluator: &mut Evaluator, vars: &[(String, String)]) -> Result<()> {
+    for (name, value) in vars {
+        evaluator.execute(&format!("{} = {}", name, value))
+            .with_context(|| format!("--var {}={}", name, value))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_variable() {
+        assert_eq!(parse_variable("rate=0.05"), Ok(("rate".to_string(), "0.05".to_string())));
+        assert_eq!(parse_variable(" n = 2 * pi "), Ok(("n".to_string(), "2 * pi".to_string())));
+        assert!(parse_variable("rate").is_err());
+        assert!(parse_variable("2x=1").is_err());
+        assert!(parse_variable("x=").is_err());
+    }
+}
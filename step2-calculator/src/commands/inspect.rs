@@ -0,0 +1,108 @@
+// 式を評価せずに調べるサブコマンド（calc-cli ast、check、bench）
+use anyhow::Result;
+use serde_json::json;
+
+use calc_core::errors::EXIT_PARSE_ERROR;
+use calc_core::evaluator::Evaluator;
+use calc_core::{explain, parser, CalcError};
+use my_cli_errors::{out, outln};
+
+use crate::output::{Output, Record};
+use crate::{ast, bench, diagnostic};
+
+// 構文木をテキスト、JSON（--format json）か Graphviz の DOT（--dot）で表示する
+pub fn ast(evaluator: &Evaluator, output: &Output, expression: &str, dot: bool) -> Result<()> {
+    let statement = parser::parse_statement(expression, evaluator.syntax);
+    if dot {
+        let statement = statement.map_err(|e| diagnostic::annotate(expression, e.into(), output.error_color))?;
+        out!("{}", ast::statement_to_dot(&statement));
+    } else if output.is_text() {
+        match statement.map_err(|e| diagnostic::annotate(expression, e.into(), output.error_color))? {
+            parser::Statement::Expr(expr) => outln!("{}", explain::tree(&expr)),
+            parser::Statement::Assign { name, value } => outln!("{} =\n{}", name, explain::tree(&value)),
+            parser::Statement::FunctionDef { name, params, body } => {
+                outln!("{}({}) =\n{}", name, params.join(", "), explain::tree(&body));
+            }
+        }
+    } else {
+        output.json(expression, statement.map(|statement| ast::statement_to_json(&statement)))?;
+    }
+    Ok(())
+}
+
+// 正しくない式が1つでもあれば構文エラーの終了コードで終了する
+pub fn check(evaluator: &Evaluator, output: &Output, expressions: &[String]) {
+    let invalid = expressions.iter().filter(|expression| !check_syntax(evaluator, output, expression)).count();
+    if invalid > 0 {
+        std::process::exit(EXIT_PARSE_ERROR);
+    }
+}
+
+pub fn bench(evaluator: &mut Evaluator, output: &Output, expression: &str, iterations: usize, vars: &[(String, String)]) -> Result<()> {
+    super::eval::define_variables(evaluator, vars)?;
+    let report = bench::run(evaluator, expression, iterations).map_err(|e| {
+        if output.is_text() { diagnostic::annotate(expression, e.into(), output.error_color) } else { e.into() }
+    });
+    if output.is_text() {
+        print_bench_report(&report?);
+    } else {
+        let report = report.map(|report| {
+            let summary = |summary: &bench::Summary| json!({ "mean": summary.mean, "p95": summary.p95, "min": summary.min });
+            json!({
+                "iterations": report.iterations,
+                "parse": summary(&report.parse),
+                "evaluate": summary(&report.evaluate),
+                "total": summary(&report.total),
+            })
+        });
+        output.json(expression, report)?;
+    }
+    Ok(())
+}
+
+
+// 構文だけを確認して結果を表示する（正しければ true）
+fn check_syntax(evaluator: &Evaluator, output: &Output, expression: &str) -> bool {
+    let error = match parser::parse_statement(expression, evaluator.syntax) {
+        Ok(_) => {
+            if output.is_text() {
+                outln!("{}: {}", output.color.dim(expression), output.color.result("ok"));
+            } else {
+                output.print(&Record::new::<CalcError>(expression, Ok(json!({ "valid": true }))));
+            }
+            return true;
+        }
+        Err(e) => e,
+    };
+    if output.is_text() {
+        outln!("{}: {}", output.color.dim(expression), error);
+        if let Some(snippet) = diagnostic::render(expression, &error, output.color) {
+            outln!("{}", snippet);
+        }
+    } else {
+        let result = match &error {
+            CalcError::SyntaxError { position, len, hint, .. } => {
+                json!({ "valid": false, "position": position, "length": len, "hint": hint })
+            }
+            _ => json!({ "valid": false }),
+        };
+        output.print(&Record { expression: expression.to_string(), result, error: Some(error.to_string()) });
+    }
+    false
+}
+
+// 時間は秒で、表示用に µs や ms にする
+fn print_bench_report(report: &bench::Report) {
+    outln!("{:<10}{}", "runs", report.iterations);
+    outln!("{:<10}{:>10}{:>10}{:>10}", "", "mean", "p95", "min");
+    for (label, summary) in [("parse", &report.parse), ("evaluate", &report.evaluate), ("total", &report.total)] {
+        outln!(
+            "{:<10}{:>10}{:>10}{:>10}",
+            label,
+            bench::format_seconds(summary.mean),
+            bench::format_seconds(summary.p95),
+            bench::format_seconds(summary.min),
+        );
+    }
+}
+
@@ -0,0 +1,97 @@
+// 整数のサブコマンド（calc-cli numtheory と base）
+use anyhow::{Context, Result};
+use serde_json::json;
+
+use calc_core::evaluator::Evaluator;
+use calc_core::number::Number;
+use calc_core::numtheory;
+use calc_core::radix::{self, Radix};
+use calc_core::value::Value;
+use my_cli_errors::outln;
+
+use crate::output::Output;
+use crate::NumtheoryCommand;
+
+// gcd、lcm、素数判定と素因数分解
+pub fn numtheory(evaluator: &Evaluator, output: &Output, command: NumtheoryCommand) -> Result<()> {
+    match command {
+        NumtheoryCommand::Gcd { numbers } => {
+            let result = parse_integers(&numbers)
+                .map(|values| values.iter().skip(1).fold(values[0].clone(), |acc, n| numtheory::gcd(&acc, n)));
+            output.value(evaluator, &format!("gcd({})", numbers.join(", ")), result.map(|n| Value::Number(Number::Integer(n))))?;
+        }
+        NumtheoryCommand::Lcm { numbers } => {
+            let result = parse_integers(&numbers)
+                .map(|values| values.iter().skip(1).fold(values[0].clone(), |acc, n| numtheory::lcm(&acc, n)));
+            output.value(evaluator, &format!("lcm({})", numbers.join(", ")), result.map(|n| Value::Number(Number::Integer(n))))?;
+        }
+        NumtheoryCommand::Isprime { n } => {
+            let result = radix::parse_integer(&n);
+            if !output.is_text() {
+                output.json(&format!("isprime({})", n), result.map(|value| json!(numtheory::is_prime(&value))))?;
+                return Ok(());
+            }
+            let value = result?;
+            if output.quiet {
+                outln!("{}", numtheory::is_prime(&value));
+            } else if numtheory::is_prime(&value) {
+                outln!("{} is prime", n);
+            } else if let Ok(value) = u64::try_from(&value)
+                && value > 1
+            {
+                outln!("{} is not prime ({})", n, numtheory::format_factors(&numtheory::factor(value)));
+            } else {
+                outln!("{} is not prime", n);
+            }
+        }
+        NumtheoryCommand::Factor { n } => {
+            let result = radix::parse_integer(&n).map_err(anyhow::Error::from).and_then(|value| {
+                let value = u64::try_from(&value)
+                    .ok()
+                    .filter(|&value| value >= 1)
+                    .with_context(|| format!("factor expects an integer between 1 and {}, got {}", u64::MAX, n))?;
+                Ok(numtheory::factor(value))
+            });
+            if !output.is_text() {
+                let factors = result.map(|factors| {
+                    factors.iter().map(|&(prime, exponent)| json!({ "prime": prime, "exponent": exponent })).collect()
+                });
+                output.json(&format!("factor({})", n), factors)?;
+            } else {
+                let factors = result?;
+                if factors.is_empty() {
+                    outln!("{} has no prime factors", n);
+                } else {
+                    output.line(&n, &numtheory::format_factors(&factors));
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn parse_integers(texts: &[String]) -> Result<Vec<num_bigint::BigInt>> {
+    texts.iter().map(|text| Ok(radix::parse_integer(text)?)).collect()
+}
+
+// --to がなければ全ての基数で表示する
+pub fn base(output: &Output, value: &str, to: Option<Radix>) -> Result<()> {
+    let result = radix::parse_integer(value);
+    if output.is_text() {
+        let integer = result?;
+        match to {
+            Some(radix) => output.line(value, &radix.format(&integer)),
+            None => {
+                for radix in radix::ALL {
+                    outln!("{:<12} {}", radix.name(), radix.format(&integer));
+                }
+            }
+        }
+    } else {
+        output.json(value, result.map(|integer| match to {
+            Some(radix) => json!(radix.format(&integer)),
+            None => radix::ALL.iter().map(|radix| (radix.name().to_string(), json!(radix.format(&integer)))).collect(),
+        }))?;
+    }
+    Ok(())
+}
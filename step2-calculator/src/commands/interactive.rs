@@ -0,0 +1,444 @@
+// インタラクティブモード（calc-cli interactive）
+//
+// 行編集と履歴は rustyline で扱い、help や mode などのコマンドと文の実行を1行ずつ振り分ける
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use rustyline::Editor;
+use rustyline::error::ReadlineError;
+use rustyline::history::{DefaultHistory, History};
+
+use calc_core::evaluator::Evaluator;
+use calc_core::number::{Number, NumberMode};
+use calc_core::value::Value;
+use calc_core::{constants, functions, parser, CalcError};
+use my_cli_errors::outln;
+
+use crate::completion::CalcHelper;
+use crate::output::{number_to_json, Output, Record};
+use crate::{clipboard, diagnostic, history, history_path, i18n, memory, memory_path};
+
+use super::eval::{load_definitions, outcome_text, print_explanation, print_outcome};
+
+// --persist-memory ならメモリーのレジスタを ~/.calc_memory から読み、終了時に保存する
+pub fn run(evaluator: &mut Evaluator, output: &Output, prompt: &str, persist_memory: bool) -> Result<()> {
+    // --format json/csv では結果の行だけを出力する
+    if output.is_text() {
+        outln!("{}", i18n::tr("interactive-banner", &[]));
+    }
+    
+    // 行編集（矢印キー、Ctrl-R 検索）と履歴の永続化
+    let mut editor: Editor<CalcHelper, DefaultHistory> = Editor::new()?;
+    editor.set_helper(Some(CalcHelper::default()));
+    let history_path = history_path();
+    let saved = match &history_path {
+        Some(path) => history::read_history_file(path).unwrap_or_else(|e| {
+            tracing::warn!("{:#}", e);
+            Vec::new()
+        }),
+        None => Vec::new(),
+    };
+    let mut entries = Vec::new();
+    for entry in saved {
+        record(&mut editor, &mut entries, entry)?;
+    }
+    let memory_path = if persist_memory { memory_path() } else { None };
+    let mut memory = match &memory_path {
+        Some(path) => memory::Memory::load(path)?,
+        None => memory::Memory::default(),
+    };
+    
+    loop {
+        // Tab補完の候補に現在の変数・関数名を反映
+        if let Some(helper) = editor.helper_mut() {
+            helper.update_names(evaluator);
+        }
+        
+        let line = match editor.readline(&render_prompt(prompt, evaluator)) {
+            Ok(line) => line,
+            Err(ReadlineError::Interrupted) => continue, // Ctrl-C は入力中の行を破棄
+            Err(ReadlineError::Eof) => {
+                if output.is_text() {
+                    outln!("{}", i18n::tr("goodbye", &[]));
+                }
+                break;
+            }
+            Err(e) => return Err(e.into()),
+        };
+        
+        let line = continue_lines(line, || editor.readline("  ... "))?;
+        
+        let input = line.trim();
+        
+        if input.is_empty() {
+            continue;
+        }
+        
+        // !! と !N を以前の入力に置き換える
+        let inputs: Vec<String> = editor.history().iter().cloned().collect();
+        let input = match history::expand_recall(input, &inputs) {
+            Ok(Some(recalled)) => {
+                outln!("{}", recalled);
+                recalled
+            }
+            Ok(None) => input.to_string(),
+            Err(e) => {
+                output.error(e);
+                continue;
+            }
+        };
+        let input = input.as_str();
+        
+        record(&mut editor, &mut entries, history::Entry::new(input))?;
+        
+        if input == "quit" || input == "exit" {
+            if output.is_text() {
+                outln!("{}", i18n::tr("goodbye", &[]));
+            }
+            break;
+        }
+        
+        if input == "help" {
+            print_help();
+            continue;
+        }
+        
+        if input == "history" {
+            history::print_entries(&entries, None);
+            continue;
+        }
+        
+        if input == "vars" {
+            print_definitions(evaluator);
+            continue;
+        }
+        
+        if input == "copy" {
+            match output.last_result() {
+                Some(result) => match clipboard::copy(&result, false) {
+                    Ok(()) => outln!("{}", i18n::tr("copied", &[("result", result.into())])),
+                    Err(e) => output.error(i18n::describe_chain(&e)),
+                },
+                None => output.error(i18n::tr("nothing-to-copy", &[])),
+            }
+            continue;
+        }
+        
+        if input == "mode" || input.starts_with("mode ") {
+            if let Err(e) = run_mode_command(evaluator, output, input["mode".len()..].trim()) {
+                output.error(i18n::describe_chain(&e));
+            }
+            continue;
+        }
+        
+        if let Some(command) = memory::parse_command(input) {
+            if let Err(e) = command.and_then(|command| run_memory_command(evaluator, output, &mut memory, command)) {
+                output.error(i18n::describe_chain(&e));
+            }
+            continue;
+        }
+        
+        if let Some(expression) = input.strip_prefix("explain ") {
+            if let Err(e) = print_explanation(evaluator, output, expression) {
+                output.error(i18n::describe_chain(&e));
+            }
+            continue;
+        }
+        
+        if let Some(path) = input.strip_prefix("load ") {
+            match load_definitions(evaluator, Path::new(path.trim())) {
+                Ok(count) => outln!("{}", i18n::tr("loaded", &[("count", count.into()), ("path", path.trim().into())])),
+                Err(e) => output.error(i18n::describe_chain(&e)),
+            }
+            continue;
+        }
+        
+        // 文（式・代入・関数定義）として実行し、結果かエラーを履歴に残す
+        let result = evaluator.execute(input);
+        if let Some(entry) = entries.last_mut().filter(|entry| entry.input == input) {
+            entry.result = result.as_ref().ok().map(|outcome| outcome_text(evaluator, outcome));
+            entry.error = result.as_ref().err().map(ToString::to_string);
+        }
+        match result {
+            Ok(outcome) => print_outcome(evaluator, output, input, outcome),
+            Err(e) if output.is_text() => {
+                output.error(i18n::calc_error(i18n::messages(), &e));
+                if let Some(snippet) = diagnostic::render(input, &e, output.color) {
+                    outln!("{}", snippet);
+                }
+            }
+            Err(e) => output.print(&Record::new(input, Err::<serde_json::Value, _>(e))),
+        }
+    }
+    
+    if let Some(path) = &history_path
+        && let Err(e) = history::write_history_file(path, &entries)
+    {
+        tracing::warn!("failed to save history: {:#}", e);
+    }
+    if let Some(path) = &memory_path {
+        memory.save(path)?;
+    }
+    
+    Ok(())
+}
+
+// rustyline の履歴に入力を足し、entries も同じ番号になるように揃える
+// （連続した同じ入力は足されず、上限を超えると古いものから消える）
+fn record(editor: &mut Editor<CalcHelper, DefaultHistory>, entries: &mut Vec<history::Entry>, entry: history::Entry) -> Result<()> {
+    if editor.add_history_entry(entry.input.as_str())? {
+        entries.push(entry);
+        let dropped = entries.len().saturating_sub(editor.history().len());
+        entries.drain(..dropped);
+    }
+    Ok(())
+}
+
+// 演算子で終わる行や閉じていない括弧は次の行に続ける（空行で打ち切る）
+// m+ などの REPL のコマンドは式として読まないので続けない
+fn continue_lines(mut line: String, mut readline: impl FnMut() -> rustyline::Result<String>) -> rustyline::Result<String> {
+    if is_command(line.trim()) {
+        return Ok(line);
+    }
+    while parser::is_incomplete(&line) {
+        match readline() {
+            Ok(next) if next.trim().is_empty() => break,
+            Ok(next) => {
+                line.push(' ');
+                line.push_str(next.trim());
+            }
+            Err(ReadlineError::Interrupted) => {
+                line.clear();
+                break;
+            }
+            Err(ReadlineError::Eof) => break,
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(line)
+}
+
+// 文としてではなく run で振り分けるコマンド（explain の後ろは式なので続けてよい）
+fn is_command(input: &str) -> bool {
+    matches!(input, "quit" | "exit" | "help" | "history" | "vars" | "copy" | "mode")
+        || input.starts_with("mode ")
+        || input.starts_with("load ")
+        || memory::parse_command(input).is_some()
+}
+
+// mode float|decimal|rational|integer で数値のモードを切り替え、ans を新しいモードで表示し直す
+// 引数がなければ今のモードを表示する
+fn run_mode_command(evaluator: &mut Evaluator, output: &Output, name: &str) -> Result<()> {
+    let mode_name = |mode: NumberMode| mode.to_possible_value().expect("no skipped variants").get_name().to_string();
+    if name.is_empty() {
+        let names: Vec<String> = NumberMode::value_variants().iter().map(|&mode| mode_name(mode)).collect();
+        outln!("Number mode: {} (switch with mode {})", mode_name(evaluator.number_mode), names.join("|"));
+        return Ok(());
+    }
+    let mode = NumberMode::from_str(name, true).map_err(|e| anyhow::anyhow!("{} (expected float, decimal, rational or integer)", e))?;
+    evaluator.set_number_mode(mode);
+    if output.is_text() {
+        outln!("Number mode: {}", mode_name(mode));
+    }
+    if let Some((_, ans)) = evaluator.variables().into_iter().find(|(name, _)| *name == "ans") {
+        output.value::<CalcError>(evaluator, "ans", Ok(ans.clone()))?;
+    }
+    Ok(())
+}
+
+// m+ や store などのメモリーのコマンド（呼び出した値は ans にもなる）
+// レジスタの値は今の数値のモードにそろえて足し・表示する
+fn run_memory_command(evaluator: &mut Evaluator, output: &Output, memory: &mut memory::Memory, command: memory::Command) -> Result<()> {
+    let print = |name: &str, value: &Number| {
+        if output.is_text() {
+            output.line(name, &evaluator.format_number(value));
+        } else {
+            output.print(&Record::new::<CalcError>(name, Ok(number_to_json(value))));
+        }
+    };
+    let last_result = || -> Result<Number> {
+        let value = evaluator.evaluate_str("ans").map_err(|_| anyhow::anyhow!("No previous result; calculate something first"))?;
+        Ok(value.into_number()?)
+    };
+    let current = |value: &Number| value.convert(evaluator.number_mode, evaluator.digits);
+    match command {
+        memory::Command::Add(sign) => {
+            let value = if sign < 0.0 { last_result()?.neg() } else { last_result()? };
+            if let Some(register) = memory.get(memory::MAIN).map(current) {
+                memory.set(memory::MAIN, register);
+            }
+            print(memory::MAIN, &memory.add(memory::MAIN, &value, evaluator.digits)?);
+        }
+        memory::Command::Store { name, expression } => {
+            let value = match expression {
+                Some(expression) => evaluator.evaluate_str(expression)?.into_number()?,
+                None => last_result()?,
+            };
+            print(name, &value);
+            memory.set(name, value);
+        }
+        memory::Command::Recall(name) => {
+            let value = memory.get(name).map(current).with_context(|| format!("Register {} is empty", name))?;
+            print(name, &value);
+            evaluator.set_last_result(Value::Number(value));
+        }
+        memory::Command::Clear(name) => {
+            memory.clear(name);
+            if output.is_text() {
+                outln!("Cleared {}", name);
+            }
+        }
+        memory::Command::List => {
+            let mut empty = true;
+            for (name, value) in memory.registers() {
+                print(name, &current(value));
+                empty = false;
+            }
+            if empty && output.is_text() {
+                outln!("Memory is empty");
+            }
+        }
+        memory::Command::ClearAll => {
+            memory.clear_all();
+            if output.is_text() {
+                outln!("Cleared all registers");
+            }
+        }
+    }
+    Ok(())
+}
+
+// --prompt の {angle} などを現在の設定で置き換える
+fn render_prompt(template: &str, evaluator: &Evaluator) -> String {
+    let name = |value: Option<clap::builder::PossibleValue>| value.map_or_else(String::new, |value| value.get_name().to_string());
+    template
+        .replace("{angle}", &name(evaluator.angle_mode.to_possible_value()))
+        .replace("{base}", &name(evaluator.output_radix.to_possible_value()))
+        .replace("{precision}", &name(evaluator.number_mode.to_possible_value()))
+        .replace("{digits}", &evaluator.digits.to_string())
+}
+
+fn print_definitions(evaluator: &Evaluator) {
+    let variables = evaluator.variables();
+    let functions = evaluator.functions();
+    if variables.is_empty() && functions.is_empty() {
+        outln!("No variables or functions defined");
+        return;
+    }
+    for (name, value) in variables {
+        outln!("  {} = {}", name, value);
+    }
+    for (name, function) in functions {
+        outln!("  {}({}) = {}", name, function.params.join(", "), function.body);
+    }
+}
+
+fn print_help() {
+    outln!("Available operations:");
+    outln!("  Basic: +, -, *, /, % (remainder), // (floor division), ^ (or **), ( )");
+    outln!("  Factorial: n! (exact for integers)");
+    outln!("  Implicit multiplication: 2(3 + 4), 2pi, 3x, (a + b)(a - b) (disable with --strict)");
+    outln!("  Percent: 200 * 15% (a % b with an operand after it is the remainder)");
+    outln!("  Bitwise (integers): &, |, xor, ~, <<, >> (with --bitwise, ^ is xor)");
+    outln!("  Comparison: ==, !=, <, <=, >, >= (1 if true, 0 if false)");
+    outln!("  Conditional: x > 0 ? x : -x, if(cond, a, b) (only the chosen side is evaluated)");
+    outln!("  Uncertainty: 5.0±0.1 * 3.2±0.05 (or +/-; errors propagate to first order, inputs independent)");
+    outln!("  Random: rand(), randint(1, 6) (use --seed N for repeatable results)");
+    outln!("  Vectors and matrices: [1, 2, 3], [[1, 2], [3, 4]]");
+    outln!("  Integer literals: 0xff, 0b1010, 0o755");
+    outln!("  Decimal comma (with --decimal-comma or --locale de_DE): 1.234,5 * 2, max(1,5, 2)");
+    outln!("  Units (with --units): 60 km / 1.5 h, 3 ft * 2 ft (see `calc-cli units`)");
+    outln!("  Angle units: sin(0.25 turn), cos(200 grad), tan(45 deg), asin(1) / deg (rad, deg, grad, turn in any --angle mode)");
+    outln!("  Functions:");
+    for function in functions::FUNCTIONS {
+        outln!("    {:<16} {}", function.signature, function.description);
+    }
+    outln!("  Constants: {}", constants::CONSTANTS.iter().map(|c| c.name).collect::<Vec<_>>().join(", "));
+    outln!("  Variables: ans, _ (previous result), name = expr");
+    outln!("  Functions: f(x, y) = expr");
+    outln!("  Commands: help, vars, load <file>, explain <expr>, history, copy (last result to the clipboard), quit, exit");
+    outln!("  Number mode: mode shows it, mode float|decimal|rational|integer switches it and converts variables and ans");
+    outln!("  Memory: m+, m- (add/subtract the last result), mr, mc, store <name> [expr], recall <name>,");
+    outln!("          memory, memory clear (--persist-memory keeps registers between sessions)");
+    outln!("  History: !! repeats the last input, !N repeats entry N");
+    outln!("  A line ending with an operator or an open parenthesis continues on the next line");
+    outln!("  Press Tab to complete function, constant, variable and command names");
+    outln!("Examples:");
+    outln!("  2 + 3");
+    outln!("  10 / 2");
+    outln!("  sqrt(16)");
+    outln!("  max(2, 3) * sin(0.5)");
+    outln!("  2 * pi * 3");
+    outln!("  ans * 1.08");
+    outln!("  r = 3");
+    outln!("  area(r) = pi * r^2");
+    outln!("  fact(n) = n <= 1 ? 1 : n * fact(n - 1)");
+    outln!("  -5 + 3");
+    outln!("  (2 + 3) * 4");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use calc_core::functions::AngleMode;
+    use calc_core::radix::Radix;
+
+    #[test]
+    fn test_render_prompt() {
+        let mut evaluator = Evaluator::with_number_mode(AngleMode::Degrees, NumberMode::Decimal, 30);
+        evaluator.output_radix = Radix::Hex;
+        assert_eq!(render_prompt("calc> ", &evaluator), "calc> ");
+        assert_eq!(render_prompt("[{angle} {base} {precision}:{digits}] ", &evaluator), "[deg hex decimal:30] ");
+    }
+
+    #[test]
+    fn test_memory_commands_are_not_continued() {
+        // 行の続きを読む代わりに残りの行を順に返す
+        fn read(lines: &[&str]) -> String {
+            let mut rest = lines[1..].iter();
+            continue_lines(lines[0].to_string(), || rest.next().map(|line| line.to_string()).ok_or(ReadlineError::Eof)).unwrap()
+        }
+        assert_eq!(read(&["1 + 2 *", "3"]), "1 + 2 * 3");
+        assert_eq!(read(&["(1 +", "2)", "m+"]), "(1 + 2)");
+        assert_eq!(read(&["m+", "2"]), "m+");
+        assert_eq!(read(&["M-", "2"]), "M-");
+        assert_eq!(read(&["mr", "+ 1"]), "mr");
+        assert_eq!(read(&["load defs/", "x"]), "load defs/");
+
+        // 読んだ行をそのまま run と同じようにメモリーのコマンドとして実行する
+        let mut evaluator = Evaluator::new(AngleMode::Radians);
+        let output = Output::default();
+        let mut memory = memory::Memory::default();
+        let input = read(&["2 +", "3"]);
+        let outcome = evaluator.execute(&input).unwrap();
+        print_outcome(&mut evaluator, &output, &input, outcome);
+        for line in [["m+", "1"], ["m+", "1"], ["mr", "* 2"]] {
+            let input = read(&line);
+            let command = memory::parse_command(input.trim()).unwrap().unwrap();
+            run_memory_command(&mut evaluator, &output, &mut memory, command).unwrap();
+        }
+        assert_eq!(memory.get(memory::MAIN).map(ToString::to_string).as_deref(), Some("10"));
+        assert_eq!(evaluator.evaluate_str("ans").unwrap().to_string(), "10");
+    }
+
+    #[test]
+    fn test_memory_keeps_exact_values() {
+        // 10進数と分数のモードでは 0.1 m+ 0.2 m+ mr が正確に 0.3 になる
+        for (mode, expected) in [(NumberMode::Float, "0.30000000000000004"), (NumberMode::Decimal, "0.3"), (NumberMode::Rational, "3/10")] {
+            let mut evaluator = Evaluator::with_number_mode(AngleMode::Radians, mode, 30);
+            let output = Output::default();
+            let mut memory = memory::Memory::default();
+            for input in ["0.1", "m+", "0.2", "m+", "mr"] {
+                match memory::parse_command(input) {
+                    Some(command) => run_memory_command(&mut evaluator, &output, &mut memory, command.unwrap()).unwrap(),
+                    None => {
+                        let outcome = evaluator.execute(input).unwrap();
+                        print_outcome(&mut evaluator, &output, input, outcome);
+                    }
+                }
+            }
+            assert_eq!(evaluator.evaluate_str("ans").unwrap().to_string(), expected, "{:?}", mode);
+        }
+    }
+}
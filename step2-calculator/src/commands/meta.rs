@@ -0,0 +1,98 @@
+// calc-cli 自体についてのサブコマンド（completions、docs、version、config）
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use clap::CommandFactory;
+use serde_json::json;
+use my_cli_errors::{out, outln};
+
+use crate::output::{Output, OutputFormat};
+use crate::{build_info, config, docs, Cli, ConfigCommand};
+
+pub fn completions(shell: clap_complete::Shell) {
+    let mut command = Cli::command();
+    let name = command.get_name().to_string();
+    clap_complete::generate(shell, &mut command, name, &mut std::io::stdout());
+}
+
+// --man と --out-dir ならサブコマンドごとのページをファイルに書く
+pub fn docs(man: bool, out_dir: Option<PathBuf>) -> Result<()> {
+    let mut command = Cli::command();
+    match (man, out_dir) {
+        (true, Some(dir)) => {
+            let count = docs::write_man_pages(&mut command, &dir)
+                .with_context(|| format!("Failed to write man pages to {}", dir.display()))?;
+            outln!("Wrote {} man page(s) to {}", count, dir.display());
+        }
+        (true, None) => docs::man_page(command, &mut std::io::stdout())?,
+        (false, _) => out!("{}", docs::markdown(&mut command)),
+    }
+    Ok(())
+}
+
+pub fn version(output: &Output, json: bool) {
+    let info = build_info();
+    if json || output.format == OutputFormat::Json {
+        outln!("{}", info.json());
+    } else {
+        out!("{}", info.text());
+    }
+}
+
+// 書き換えるのはこのツールの設定ファイルだけで、list は全ての層を重ねた値を表示する
+pub fn config(
+    output: &Output,
+    mut config: config::Config,
+    layered: &[my_cli_config::Setting],
+    path: Option<&Path>,
+    command: ConfigCommand,
+) -> Result<()> {
+    let path = path.context("Could not determine the config directory")?;
+    match command {
+        // 他の層（.my-cli.toml など）の値も、どこで設定したかと一緒に表示する
+        ConfigCommand::List => {
+            let settings: Vec<(&str, Option<String>, &str)> = config::KEYS
+                .iter()
+                .map(|&key| match layered.iter().find(|setting| setting.key == key) {
+                    Some(setting) => (key, Some(setting.value.clone()), setting.layer.name()),
+                    None => (key, config::builtin_default(key), "default"),
+                })
+                .collect();
+            if output.is_text() {
+                for (key, value, source) in settings {
+                    outln!("{:<12} = {:<10} ({})", key, value.unwrap_or_else(|| "-".to_string()), source);
+                }
+            } else {
+                let settings = settings.into_iter().map(|(key, value, _)| (key.to_string(), json!(value))).collect();
+                output.json::<anyhow::Error>("config", Ok(serde_json::Value::Object(settings)))?;
+            }
+        }
+        ConfigCommand::Get { key } => {
+            let result = config::validate_key(&key).map(|()| config.get(&key));
+            if output.is_text() {
+                match result? {
+                    Some(value) => outln!("{}", value),
+                    None => outln!("{} is not set (default: {})", key,
+                        config::builtin_default(&key).unwrap_or_else(|| "none".to_string())),
+                }
+            } else {
+                output.json(&key, result.map(|value| json!(value)))?;
+            }
+        }
+        ConfigCommand::Set { key, value } => {
+            config.set(&key, &value)?;
+            config.save(path)?;
+            outln!("Set {} = {} in {}", key, value, path.display());
+        }
+        ConfigCommand::Unset { key } => {
+            if config.unset(&key)? {
+                config.save(path)?;
+                outln!("Removed {} from {}", key, path.display());
+            } else {
+                outln!("{} is not set in {}", key, path.display());
+            }
+        }
+        ConfigCommand::Path => outln!("{}", path.display()),
+    }
+    Ok(())
+}
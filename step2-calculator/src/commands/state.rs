@@ -0,0 +1,141 @@
+// 保存した入力と変数のサブコマンド（calc-cli history と var）
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use serde_json::json;
+
+use calc_core::evaluator::{Evaluator, Outcome};
+use my_cli_errors::{out, outln};
+
+use crate::output::{Output, OutputFormat};
+use crate::{completion, diagnostic, history, history_path, memory, vars, HistoryCommand, VarCommand};
+
+use super::eval::outcome_text;
+
+// サブコマンドがなければ最近の入力を番号付きで表示する
+pub fn history(evaluator: &mut Evaluator, output: &Output, command: Option<HistoryCommand>, last: Option<usize>) -> Result<()> {
+    let path = history_path().context("Could not determine the home directory");
+    match command {
+        Some(HistoryCommand::Export { last, evaluate }) => {
+            let entries = history::read_history_file(&path?)?;
+            let skip = last.map_or(0, |last| entries.len().saturating_sub(last));
+            // 保存した結果とエラーを書き出す。--evaluate なら結果を持たない古い履歴だけ計算し直す
+            // （変数の代入を再現するため、最初の入力から順に実行する）
+            let replayed = if evaluate { replay_history(evaluator, &entries) } else { vec![None; entries.len()] };
+            let rows: Vec<history::Exported> = entries
+                .iter()
+                .zip(replayed)
+                .enumerate()
+                .skip(skip)
+                .map(|(index, (entry, replayed))| history::Exported { number: index + 1, entry: &entry.input, result: entry.outcome().or(replayed) })
+                .collect();
+            match output.format {
+                OutputFormat::Json => outln!("{}", serde_json::to_string_pretty(&history::to_json(&rows))?),
+                OutputFormat::Text | OutputFormat::Csv => out!("{}", history::to_csv(&rows)),
+            }
+        }
+        Some(HistoryCommand::Search { text, ignore_case }) => {
+            let entries = history::read_history_file(&path?)?;
+            let matches = history::search(&entries, &text, ignore_case);
+            if output.is_text() {
+                history::print_matches(&matches);
+            } else {
+                let rows: Vec<history::Exported> = matches
+                    .iter()
+                    .map(|(number, entry)| history::Exported { number: *number, entry: &entry.input, result: entry.outcome() })
+                    .collect();
+                output.json::<anyhow::Error>("history search", Ok(history::to_json(&rows)))?;
+            }
+        }
+        None => {
+            let result = path.and_then(|path| history::read_history_file(&path));
+            if output.is_text() {
+                history::print_entries(&result?, last);
+            } else {
+                // 保存してある結果とエラーも含める（history export と同じ形）
+                output.json("history", result.map(|entries| {
+                    let skip = last.map_or(0, |last| entries.len().saturating_sub(last));
+                    let rows: Vec<history::Exported> = entries
+                        .iter()
+                        .enumerate()
+                        .skip(skip)
+                        .map(|(index, entry)| history::Exported { number: index + 1, entry: &entry.input, result: entry.outcome() })
+                        .collect();
+                    history::to_json(&rows)
+                }))?;
+            }
+        }
+    }
+    Ok(())
+}
+
+// var サブコマンド（path は ~/.calc_vars）
+pub fn var(evaluator: &mut Evaluator, output: &Output, path: Option<&Path>, command: VarCommand) -> Result<()> {
+    let path = path.context("Could not determine the home directory")?;
+    let mut saved = vars::Vars::load(path)?;
+    match command {
+        VarCommand::Set { name, value } => {
+            // 他の保存した変数を使えるように、先に定義しておく
+            saved.define(evaluator)?;
+            let statement = format!("{} = {}", name, value);
+            let value = match evaluator.execute(&statement) {
+                Ok(Outcome::Assigned { value, .. }) => value,
+                Ok(_) => anyhow::bail!("Invalid variable name '{}'", name),
+                Err(e) => return Err(diagnostic::annotate(&statement, e.into(), output.error_color)),
+            };
+            saved.set(&name, &value.to_string());
+            saved.save(path)?;
+            output.value::<anyhow::Error>(evaluator, &name, Ok(value))?;
+        }
+        VarCommand::Get { name } => {
+            let result = saved.evaluate(&name, evaluator);
+            output.value(evaluator, &name, result)?;
+        }
+        VarCommand::List => {
+            if output.is_text() {
+                if saved.iter().next().is_none() {
+                    outln!("No saved variables ({})", path.display());
+                }
+                for (name, value) in saved.iter() {
+                    outln!("{} = {}", name, value);
+                }
+            } else {
+                let values = saved.iter().map(|(name, value)| (name.to_string(), json!(value))).collect();
+                output.json::<anyhow::Error>("vars", Ok(serde_json::Value::Object(values)))?;
+            }
+        }
+        VarCommand::Unset { name } => {
+            if !saved.unset(&name) {
+                anyhow::bail!("Variable '{}' is not saved", name);
+            }
+            saved.save(path)?;
+            outln!("Removed {} from {}", name, path.display());
+        }
+    }
+    Ok(())
+}
+
+// 履歴の入力を順に実行した結果（対話モードのコマンドなど式でない入力は None）
+fn replay_history(evaluator: &mut Evaluator, entries: &[history::Entry]) -> Vec<Option<Result<String, String>>> {
+    entries
+        .iter()
+        .map(|entry| entry.input.as_str())
+        .map(|entry| {
+            let word = entry.split_whitespace().next().unwrap_or_default();
+            if completion::REPL_COMMANDS.contains(&word) || word == "explain" || memory::parse_command(entry).is_some() {
+                return None;
+            }
+            let result = match evaluator.execute(entry) {
+                Ok(outcome) => {
+                    let text = outcome_text(evaluator, &outcome);
+                    if let Outcome::Value(value) = outcome {
+                        evaluator.set_last_result(value);
+                    }
+                    Ok(text)
+                }
+                Err(e) => Err(e.to_string()),
+            };
+            Some(result)
+        })
+        .collect()
+}
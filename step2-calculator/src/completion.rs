@@ -0,0 +1,123 @@
+// インタラクティブモードのTab補完
+use rustyline::completion::{Completer, Pair};
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::validate::Validator;
+use rustyline::{Context, Helper};
+
+use calc_core::constants;
+use calc_core::evaluator::Evaluator;
+use calc_core::functions;
+
+// 行頭でのみ補完するREPLコマンド
+pub const REPL_COMMANDS: &[&str] = &["help", "vars", "copy", "load", "history", "mode", "store", "recall", "memory", "quit", "exit"];
+
+#[derive(Default)]
+pub struct CalcHelper {
+    variables: Vec<String>,
+    functions: Vec<String>,
+}
+
+impl CalcHelper {
+    // 評価器で定義済みの変数・関数名を取り込む
+    pub fn update_names(&mut self, evaluator: &Evaluator) {
+        self.variables = evaluator.variables().into_iter().map(|(name, _)| name.to_string()).collect();
+        self.functions = evaluator.functions().into_iter().map(|(name, _)| name.to_string()).collect();
+    }
+
+    // カーソル位置の単語に対する候補と、置き換え開始位置を返す
+    fn candidates(&self, line: &str, pos: usize) -> (usize, Vec<Pair>) {
+        let start = line[..pos]
+            .char_indices()
+            .rev()
+            .take_while(|&(_, c)| c.is_alphanumeric() || c == '_')
+            .last()
+            .map(|(i, _)| i)
+            .unwrap_or(pos);
+        let prefix = &line[start..pos];
+
+        let mut pairs = Vec::new();
+        let mut push = |name: &str, replacement: String| {
+            if name.starts_with(prefix) && !pairs.iter().any(|p: &Pair| p.replacement == replacement) {
+                pairs.push(Pair { display: name.to_string(), replacement });
+            }
+        };
+
+        if line[..start].trim().is_empty() {
+            for command in REPL_COMMANDS {
+                push(command, command.to_string());
+            }
+        }
+        for function in functions::FUNCTIONS {
+            push(function.name, format!("{}(", function.name));
+        }
+        for name in &self.functions {
+            push(name, format!("{}(", name));
+        }
+        for constant in constants::CONSTANTS {
+            push(constant.name, constant.name.to_string());
+        }
+        for name in &self.variables {
+            push(name, name.clone());
+        }
+
+        pairs.sort_by(|a, b| a.display.cmp(&b.display));
+        (start, pairs)
+    }
+}
+
+impl Completer for CalcHelper {
+    type Candidate = Pair;
+
+    fn complete(&self, line: &str, pos: usize, _ctx: &Context<'_>) -> rustyline::Result<(usize, Vec<Pair>)> {
+        Ok(self.candidates(line, pos))
+    }
+}
+
+impl Hinter for CalcHelper {
+    type Hint = String;
+}
+
+impl Highlighter for CalcHelper {}
+
+impl Validator for CalcHelper {}
+
+impl Helper for CalcHelper {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn replacements(helper: &CalcHelper, line: &str) -> (usize, Vec<String>) {
+        let (start, pairs) = helper.candidates(line, line.len());
+        (start, pairs.into_iter().map(|p| p.replacement).collect())
+    }
+
+    #[test]
+    fn test_completes_functions_and_constants() {
+        let helper = CalcHelper::default();
+        assert_eq!(replacements(&helper, "2 * si"), (4, vec!["sin(".to_string(), "sinh(".to_string()]));
+        assert_eq!(replacements(&helper, "p"), (0, vec!["phi".to_string(), "pi".to_string(), "prod(".to_string()]));
+    }
+
+    #[test]
+    fn test_commands_only_at_line_start() {
+        let helper = CalcHelper::default();
+        assert_eq!(replacements(&helper, "  he"), (2, vec!["help".to_string()]));
+        assert!(replacements(&helper, "1 + he").1.is_empty());
+    }
+
+    #[test]
+    fn test_completes_user_definitions() {
+        let mut evaluator = Evaluator::default();
+        evaluator.execute("rate = 0.08").unwrap();
+        evaluator.execute("ramp(x) = x").unwrap();
+        let mut helper = CalcHelper::default();
+        helper.update_names(&evaluator);
+
+        assert_eq!(
+            replacements(&helper, "100 * ra"),
+            (6, vec!["ramp(".to_string(), "rand(".to_string(), "randint(".to_string(), "rate".to_string()])
+        );
+    }
+}
@@ -0,0 +1,211 @@
+// 設定ファイル（~/.config/calc-cli/config.toml）
+//
+// 優先順位: コマンドラインのフラグ > 環境変数（CALC_ANGLE など） > 設定ファイル > 組み込みの既定値
+// 設定ファイルは my-cli の他のツールと同じく重ねて読む（my-cli-config）
+//   プロジェクトの .my-cli.toml の [calc] > このファイル > ~/.config/my-cli/config.toml の [calc] > /etc/my-cli/config.toml の [calc]
+// config サブコマンドで書き換えるのはこのファイルだけ
+// 設定ファイルの値は clap の既定値として渡すので、値の検証もフラグと同じ規則で行われる
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, bail, Context, Result};
+use clap::{Arg, Command, CommandFactory};
+use my_cli_config::{Section, Setting};
+use toml_edit::{DocumentMut, Item, Value};
+
+// 設定できるキー（全体のオプションの --long 名と同じ）
+pub const KEYS: &[&str] = &[
+    "angle",
+    "precision",
+    "digits",
+    "units",
+    "strict",
+    "places",
+    "significant",
+    "notation",
+    "rounding",
+    "separators",
+    "decimal-comma",
+    "locale",
+    "format",
+    "output-base",
+    "color",
+    "theme",
+    "result-color",
+    "error-color",
+    "prompt",
+    "persist-memory",
+];
+
+// 共通の設定ファイルでは [calc] の表に書く
+pub const SECTION: Section = Section { tool: "calc", keys: KEYS };
+
+#[derive(Debug, Default)]
+pub struct Config {
+    document: DocumentMut,
+}
+
+impl Config {
+    // CALC_CONFIG があればそのファイル、なければ ~/.config/calc-cli/config.toml
+    pub fn default_path() -> Option<PathBuf> {
+        match std::env::var_os("CALC_CONFIG") {
+            Some(path) => Some(PathBuf::from(path)),
+            None => dirs::config_dir().map(|dir| dir.join("calc-cli").join("config.toml")),
+        }
+    }
+
+    // ファイルがなければ空の設定
+    pub fn load(path: &Path) -> Result<Config> {
+        let contents = match std::fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Config::default()),
+            Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path.display())),
+        };
+        let config = Config::parse(&contents).with_context(|| format!("Invalid config file {}", path.display()))?;
+        Ok(config)
+    }
+
+    pub fn parse(contents: &str) -> Result<Config> {
+        let config = Config { document: contents.parse()? };
+        for (key, _) in config.document.iter() {
+            validate_key(key)?;
+        }
+        for (key, value) in config.values() {
+            validate(key, &value)?;
+        }
+        Ok(config)
+    }
+
+    // コメントや書式は残したまま書き出す
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+        }
+        std::fs::write(path, self.document.to_string()).with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    pub fn get(&self, key: &str) -> Option<String> {
+        self.document.get(key).and_then(Item::as_value).map(value_text)
+    }
+
+    pub fn set(&mut self, key: &str, value: &str) -> Result<()> {
+        validate(key, value)?;
+        // 数値と真偽値は TOML の型で書く（digits = 30, units = true）
+        let item = if let Ok(flag) = value.parse::<bool>() {
+            Value::from(flag)
+        } else if let Ok(number) = value.parse::<i64>() {
+            Value::from(number)
+        } else {
+            Value::from(value)
+        };
+        self.document[key] = Item::Value(item);
+        Ok(())
+    }
+
+    // 削除したら true
+    pub fn unset(&mut self, key: &str) -> Result<bool> {
+        validate_key(key)?;
+        Ok(self.document.remove(key).is_some())
+    }
+
+    // 設定されているキーと値（KEYS の順）
+    pub fn values(&self) -> Vec<(&'static str, String)> {
+        KEYS.iter().filter_map(|&key| self.get(key).map(|value| (key, value))).collect()
+    }
+
+}
+
+// 全ての層の設定ファイルを重ねた値（path はこのツールの設定ファイル）
+pub fn layered(path: Option<PathBuf>) -> Result<Vec<Setting>> {
+    SECTION
+        .load(&SECTION.files(path), &crate::Cli::command())
+        .map_err(|e| anyhow!(e.message).context(format!("Invalid config file {}", e.path.display())))
+}
+
+// 組み込みの既定値（フラグは false）
+pub fn builtin_default(key: &str) -> Option<String> {
+    let mut command = crate::Cli::command();
+    command.build();
+    let arg = find_arg(&command, key)?;
+    let value = arg.get_default_values().first()?;
+    Some(value.to_string_lossy().into_owned())
+}
+
+// 値をオプションと同じ規則で検証する
+pub fn validate(key: &str, value: &str) -> Result<()> {
+    validate_key(key)?;
+    my_cli_config::validate(&crate::Cli::command(), key, value).map_err(|message| anyhow!(message))
+}
+
+// 設定できるキーか
+pub fn validate_key(key: &str) -> Result<()> {
+    if !KEYS.contains(&key) {
+        bail!("unknown key '{}' (known keys: {})", key, KEYS.join(", "));
+    }
+    Ok(())
+}
+
+fn find_arg<'a>(command: &'a Command, key: &str) -> Option<&'a Arg> {
+    command.get_arguments().find(|arg| arg.get_long() == Some(key))
+}
+
+// 文字列は引用符なしで表示する
+fn value_text(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.value().clone(),
+        other => other.clone().decorated("", "").to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::FromArgMatches;
+    use my_cli_config::Layer;
+
+    #[test]
+    fn test_parse_and_set() {
+        let mut config = Config::parse("# defaults\nangle = \"deg\"\ndigits = 30\n").unwrap();
+        assert_eq!(config.get("angle"), Some("deg".to_string()));
+        assert_eq!(config.get("digits"), Some("30".to_string()));
+        assert_eq!(config.get("format"), None);
+
+        config.set("units", "true").unwrap();
+        config.set("output-base", "hex").unwrap();
+        assert!(config.unset("digits").unwrap());
+        assert_eq!(config.document.to_string(), "# defaults\nangle = \"deg\"\nunits = true\noutput-base = \"hex\"\n");
+        assert_eq!(config.values(), vec![("angle", "deg".to_string()), ("units", "true".to_string()), ("output-base", "hex".to_string())]);
+    }
+
+    #[test]
+    fn test_invalid_values() {
+        let mut config = Config::default();
+        assert!(config.set("angle", "gradians").is_err());
+        assert!(config.set("digits", "0").is_err());
+        assert!(config.set("units", "yes please").is_err());
+        assert!(config.set("seed", "1").is_err());
+        assert!(Config::parse("precison = \"decimal\"").is_err());
+        assert!(Config::parse("angle = ").is_err());
+    }
+
+    #[test]
+    fn test_precedence() {
+        let file = |layer| my_cli_config::File { layer, path: PathBuf::from("config.toml") };
+        let settings = |contents: &str, layer| SECTION.parse(contents, &file(layer), &crate::Cli::command()).unwrap();
+        let parse = |settings: &[Setting], args: &[&str]| {
+            let matches = my_cli_config::apply(crate::Cli::command(), settings).try_get_matches_from(args).unwrap();
+            crate::Cli::from_arg_matches(&matches).unwrap()
+        };
+        let mut layers = settings("angle = \"deg\"\ndigits = 30\n", Layer::Tool);
+        let cli = parse(&layers, &["calc-cli", "eval", "1"]);
+        assert_eq!((cli.angle, cli.digits), (calc_core::functions::AngleMode::Degrees, 30));
+        let cli = parse(&layers, &["calc-cli", "--angle", "rad", "eval", "1"]);
+        assert_eq!((cli.angle, cli.digits), (calc_core::functions::AngleMode::Radians, 30));
+        assert_eq!(builtin_default("digits"), Some("50".to_string()));
+
+        // 共通の設定ファイルでは [calc] の表だけを読む
+        layers.extend(settings("[hello]\nname = \"Alice\"\n\n[calc]\ndigits = 12\n", Layer::Project));
+        let cli = parse(&layers, &["calc-cli", "eval", "1"]);
+        assert_eq!((cli.angle, cli.digits), (calc_core::functions::AngleMode::Degrees, 12));
+        assert!(SECTION.parse("angle = \"deg\"\n", &file(Layer::User), &crate::Cli::command()).is_err());
+    }
+}
@@ -0,0 +1,212 @@
+// CSV ファイルの列を読む（stats / matrix の --file data.csv --column 2）
+//
+// 列は1から数えた番号か見出しの名前で指定する
+// 1行目に数値でない欄があれば見出し行とみなし、データには含めない
+use std::str::FromStr;
+
+use anyhow::{bail, Context, Result};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Column {
+    // 1から数えた番号
+    Index(usize),
+    Name(String),
+}
+
+impl FromStr for Column {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Column, String> {
+        let s = s.trim();
+        if s.is_empty() {
+            return Err("column must be a number or a header name".to_string());
+        }
+        match s.parse::<usize>() {
+            Ok(0) => Err("column numbers start at 1".to_string()),
+            Ok(index) => Ok(Column::Index(index)),
+            Err(_) => Ok(Column::Name(s.to_string())),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Row {
+    // エラーメッセージ用の行番号（1から）
+    pub line: usize,
+    pub fields: Vec<String>,
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct Table {
+    pub header: Option<Vec<String>>,
+    pub rows: Vec<Row>,
+}
+
+impl Table {
+    pub fn parse(input: &str) -> Result<Table> {
+        let mut rows = parse_rows(input)?;
+        let header = match rows.first() {
+            Some(first) if first.fields.iter().any(|field| !field.is_empty() && field.parse::<f64>().is_err()) => {
+                Some(rows.remove(0).fields)
+            }
+            _ => None,
+        };
+        Ok(Table { header, rows })
+    }
+
+    // 0から数えた列の位置
+    pub fn position(&self, column: &Column) -> Result<usize> {
+        match column {
+            Column::Index(index) => Ok(index - 1),
+            Column::Name(name) => {
+                let header = self.header.as_ref().with_context(|| format!("No header row to find column '{}'", name))?;
+                header
+                    .iter()
+                    .position(|field| field == name)
+                    .with_context(|| format!("No column named '{}' (columns: {})", name, header.join(", ")))
+            }
+        }
+    }
+
+    // 1列の値（空欄は欠損値として飛ばす）
+    pub fn column(&self, column: &Column) -> Result<Vec<f64>> {
+        let position = self.position(column)?;
+        let mut values = Vec::new();
+        for row in &self.rows {
+            let field = row.fields.get(position).map_or("", String::as_str);
+            if !field.is_empty() {
+                values.push(parse_f64(field, row.line)?);
+            }
+        }
+        Ok(values)
+    }
+
+    // 指定した列（空なら全ての列）を行ごとに取り出す
+    // 行列にするので空欄はエラーにする（全て空欄の行は飛ばす）
+    pub fn select(&self, columns: &[Column]) -> Result<Vec<Vec<String>>> {
+        let positions = if columns.is_empty() {
+            let width = self.rows.iter().map(|row| row.fields.len()).max().unwrap_or(0);
+            (0..width).collect()
+        } else {
+            columns.iter().map(|column| self.position(column)).collect::<Result<Vec<_>>>()?
+        };
+        let mut selected = Vec::new();
+        for row in &self.rows {
+            let fields: Vec<&str> = positions.iter().map(|&i| row.fields.get(i).map_or("", String::as_str)).collect();
+            if fields.iter().all(|field| field.is_empty()) {
+                continue;
+            }
+            if let Some(i) = fields.iter().position(|field| field.is_empty()) {
+                bail!("line {}: column {} is empty", row.line, positions[i] + 1);
+            }
+            selected.push(fields.into_iter().map(str::to_string).collect());
+        }
+        Ok(selected)
+    }
+}
+
+fn parse_f64(field: &str, line: usize) -> Result<f64> {
+    match field.parse::<f64>() {
+        Ok(value) if value.is_finite() => Ok(value),
+        _ => bail!("line {}: not a number: '{}'", line, field),
+    }
+}
+
+// 「"」で囲んだ欄（中の「""」は「"」、改行も含められる）に対応する
+// 欄の前後の空白は取り除き、空行は飛ばす
+fn parse_rows(input: &str) -> Result<Vec<Row>> {
+    let mut rows = Vec::new();
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut line = 1;
+    let mut start = 1;
+    let mut chars = input.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted => {
+                if chars.peek() == Some(&'"') {
+                    chars.next();
+                    field.push('"');
+                } else {
+                    quoted = false;
+                }
+            }
+            '"' if field.trim().is_empty() => {
+                field.clear();
+                quoted = true;
+            }
+            ',' if !quoted => fields.push(std::mem::take(&mut field).trim().to_string()),
+            '\n' if !quoted => {
+                fields.push(std::mem::take(&mut field).trim().to_string());
+                push_row(&mut rows, start, std::mem::take(&mut fields));
+                line += 1;
+                start = line;
+            }
+            '\r' if !quoted && chars.peek() == Some(&'\n') => {}
+            _ => {
+                if c == '\n' {
+                    line += 1;
+                }
+                field.push(c);
+            }
+        }
+    }
+    if quoted {
+        bail!("line {}: unterminated quoted field", start);
+    }
+    fields.push(field.trim().to_string());
+    push_row(&mut rows, start, fields);
+    Ok(rows)
+}
+
+fn push_row(rows: &mut Vec<Row>, line: usize, fields: Vec<String>) {
+    if fields.iter().any(|field| !field.is_empty()) {
+        rows.push(Row { line, fields });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_rows() {
+        let rows = parse_rows("a, \"b, c\"\r\n\n1,\"say \"\"hi\"\"\"\n\"x\ny\",2").unwrap();
+        assert_eq!(
+            rows,
+            vec![
+                Row { line: 1, fields: vec!["a".into(), "b, c".into()] },
+                Row { line: 3, fields: vec!["1".into(), "say \"hi\"".into()] },
+                Row { line: 4, fields: vec!["x\ny".into(), "2".into()] },
+            ]
+        );
+        assert!(parse_rows("1,\"2\n3").is_err());
+    }
+
+    #[test]
+    fn test_column() {
+        let table = Table::parse("name,price,qty\napple,1.5,3\npear,,2\nfig,2.25,x\n").unwrap();
+        assert_eq!(table.header, Some(vec!["name".into(), "price".into(), "qty".into()]));
+        assert_eq!(table.column(&"price".parse().unwrap()).unwrap(), vec![1.5, 2.25]);
+        assert_eq!(table.column(&"2".parse().unwrap()).unwrap(), vec![1.5, 2.25]);
+        assert_eq!(table.column(&Column::Index(3)).unwrap_err().to_string(), "line 4: not a number: 'x'");
+        assert!(table.column(&Column::Name("cost".into())).is_err());
+        assert!("0".parse::<Column>().is_err());
+
+        // 見出し行がなければ1行目もデータ
+        let table = Table::parse("1,2\n3,4").unwrap();
+        assert_eq!(table.header, None);
+        assert_eq!(table.column(&Column::Index(1)).unwrap(), vec![1.0, 3.0]);
+        assert!(table.column(&Column::Name("a".into())).is_err());
+    }
+
+    #[test]
+    fn test_select() {
+        let table = Table::parse("x,y,z\n1,2,3\n,,\n4,5,6\n").unwrap();
+        assert_eq!(table.select(&[]).unwrap(), vec![vec!["1", "2", "3"], vec!["4", "5", "6"]]);
+        assert_eq!(table.select(&[Column::Name("z".into()), Column::Index(1)]).unwrap(), vec![vec!["3", "1"], vec!["6", "4"]]);
+        let table = Table::parse("1,2\n3,\n").unwrap();
+        assert_eq!(table.select(&[]).unwrap_err().to_string(), "line 2: column 2 is empty");
+    }
+}
@@ -0,0 +1,236 @@
+// 通貨の換算（calc-cli currency 100 USD EUR --rates rates.json）
+//
+// レートは {"base": "USD", "rates": {"EUR": 0.92, "JPY": 151.3}} の JSON（base_code でもよい）
+// --rates-url から取得したものは ~/.calc_rates.json に保存し、--max-age の間は取得し直さない
+// 式の中では通貨コードと記号が使える（"$100 + 20 EUR"、"€5.50 * 3"）
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+
+use calc_core::evaluator::Evaluator;
+use calc_core::number::Number;
+use calc_core::parser::{self, BinaryOp, Expr};
+use calc_core::value::Value;
+
+// 記号と通貨コード
+pub const SYMBOLS: &[(char, &str)] = &[
+    ('$', "USD"),
+    ('€', "EUR"),
+    ('£', "GBP"),
+    ('¥', "JPY"),
+    ('₩', "KRW"),
+    ('₹', "INR"),
+    ('₽', "RUB"),
+    ('₺', "TRY"),
+    ('₫', "VND"),
+    ('₱', "PHP"),
+    ('₪', "ILS"),
+    ('฿', "THB"),
+];
+
+#[derive(Debug, Deserialize)]
+struct RatesFile {
+    #[serde(alias = "base_code")]
+    base: String,
+    rates: BTreeMap<String, f64>,
+}
+
+// base の 1 単位が何単位になるか
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rates {
+    pub base: String,
+    rates: BTreeMap<String, f64>,
+}
+
+impl Rates {
+    pub fn parse(json: &str) -> Result<Rates> {
+        let file: RatesFile = serde_json::from_str(json)?;
+        let base = file.base.to_uppercase();
+        let mut rates = BTreeMap::new();
+        for (code, rate) in file.rates {
+            if !(rate.is_finite() && rate > 0.0) {
+                bail!("rate for {} must be a positive number, got {}", code, rate);
+            }
+            rates.insert(code.to_uppercase(), rate);
+        }
+        rates.insert(base.clone(), 1.0);
+        Ok(Rates { base, rates })
+    }
+
+    pub fn load(path: &Path) -> Result<Rates> {
+        let json = std::fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+        Rates::parse(&json).with_context(|| format!("Invalid rates file {}", path.display()))
+    }
+
+    // cache が max_age より新しければそれを使い、古ければ url から取得して保存し直す
+    // 取得に失敗したら古いキャッシュでも使う
+    pub fn fetch(url: &str, cache: &Path, max_age: Duration, refresh: bool) -> Result<Rates> {
+        let age = std::fs::metadata(cache)
+            .and_then(|metadata| metadata.modified())
+            .ok()
+            .and_then(|modified| SystemTime::now().duration_since(modified).ok());
+        if !refresh && age.is_some_and(|age| age <= max_age) {
+            tracing::debug!("using the cached rates in {}", cache.display());
+            return Rates::load(cache);
+        }
+        tracing::info!("fetching exchange rates from {}", url);
+        let fetched = ureq::get(url)
+            .timeout(Duration::from_secs(10))
+            .call()
+            .map_err(anyhow::Error::from)
+            .and_then(|response| Ok(response.into_string()?))
+            .with_context(|| format!("Failed to fetch exchange rates from {}", url));
+        match fetched {
+            Ok(json) => {
+                let rates = Rates::parse(&json).with_context(|| format!("Invalid exchange rates from {}", url))?;
+                std::fs::write(cache, &json).with_context(|| format!("Failed to write {}", cache.display()))?;
+                Ok(rates)
+            }
+            Err(e) if age.is_some() => {
+                tracing::warn!("{:#}; using the cached rates in {}", e, cache.display());
+                Rates::load(cache)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn contains(&self, code: &str) -> bool {
+        self.rates.contains_key(code)
+    }
+
+    fn rate(&self, code: &str) -> Result<f64> {
+        match self.rates.get(code) {
+            Some(&rate) => Ok(rate),
+            None => bail!("Unknown currency '{}' (no rate against {})", code, self.base),
+        }
+    }
+
+    // 通貨コードを target で表した 1 単位の値の変数にする（USD は 1 USD が何 target か）
+    pub fn define(&self, evaluator: &mut Evaluator, target: &str) -> Result<()> {
+        let to = self.rate(target)?;
+        for (code, &rate) in &self.rates {
+            let number = |rate: f64| Number::parse(&rate.to_string(), evaluator.number_mode).map(Value::Number);
+            let value = Value::binary(BinaryOp::Div, &number(to)?, &number(rate)?, evaluator.digits)?;
+            evaluator.set_variable(code, value);
+        }
+        Ok(())
+    }
+}
+
+// 金額の式を target の金額にする
+// from があれば式の値をその通貨の金額とし、なければ式の中に通貨（コードか記号）が必要
+pub fn convert(evaluator: &mut Evaluator, rates: &Rates, amount: &str, from: Option<&str>, target: &str) -> Result<Value> {
+    rates.define(evaluator, target)?;
+    let amount = replace_symbols(amount);
+    let expression = match from {
+        Some(from) => {
+            rates.rate(from)?;
+            format!("({}) * {}", amount, from)
+        }
+        None => amount,
+    };
+    let expr = parser::parse(&expression, evaluator.syntax)?;
+    if from.is_none() && !mentions_currency(&expr, rates) {
+        bail!("Give the currency of '{}', e.g. calc-cli currency {} USD {}", expression, expression, target);
+    }
+    Ok(evaluator.evaluate(&expr)?)
+}
+
+// 記号を通貨コードの掛け算にする
+// 数・変数・括弧の前の記号は前に（$100 → USD*100）、それ以外は後ろに付ける（100€ → 100*EUR）
+pub fn replace_symbols(expression: &str) -> String {
+    let mut result = String::new();
+    let mut chars = expression.chars().peekable();
+    while let Some(c) = chars.next() {
+        let Some(&(_, code)) = SYMBOLS.iter().find(|(symbol, _)| *symbol == c) else {
+            result.push(c);
+            continue;
+        };
+        let rest: String = chars.clone().collect();
+        match rest.trim_start().chars().next() {
+            Some(next) if next.is_alphanumeric() || next == '(' || next == '.' => {
+                result.push_str(code);
+                result.push('*');
+            }
+            _ => {
+                result.push('*');
+                result.push_str(code);
+            }
+        }
+    }
+    result
+}
+
+fn mentions_currency(expr: &Expr, rates: &Rates) -> bool {
+    match expr {
+        Expr::Number(_) => false,
+        Expr::Variable(name) => rates.contains(name),
+        Expr::List(items) | Expr::Call { args: items, .. } => items.iter().any(|item| mentions_currency(item, rates)),
+        Expr::Unary { operand, .. } => mentions_currency(operand, rates),
+        Expr::Binary { left, right, .. } => mentions_currency(left, rates) || mentions_currency(right, rates),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use calc_core::number::NumberMode;
+
+    const RATES: &str = r#"{"base": "USD", "rates": {"EUR": 0.8, "JPY": 150, "gbp": 0.75}}"#;
+
+    fn convert_str(amount: &str, from: Option<&str>, target: &str) -> Result<String> {
+        let mut evaluator = Evaluator::default();
+        let value = convert(&mut evaluator, &Rates::parse(RATES)?, amount, from, target)?;
+        Ok(evaluator.format(&value))
+    }
+
+    #[test]
+    fn test_parse() {
+        let rates = Rates::parse(RATES).unwrap();
+        assert_eq!(rates.rates.keys().collect::<Vec<_>>(), vec!["EUR", "GBP", "JPY", "USD"]);
+        assert!(Rates::parse(r#"{"base_code": "EUR", "rates": {"USD": 1.25}}"#).unwrap().contains("EUR"));
+        assert!(Rates::parse(r#"{"base": "USD", "rates": {"EUR": 0}}"#).is_err());
+        assert!(Rates::parse(r#"{"rates": {}}"#).is_err());
+    }
+
+    #[test]
+    fn test_convert() {
+        assert_eq!(convert_str("100", Some("USD"), "EUR").unwrap(), "80");
+        assert_eq!(convert_str("40", Some("EUR"), "JPY").unwrap(), "7500");
+        assert_eq!(convert_str("$100 + 20 EUR", None, "EUR").unwrap(), "100");
+        assert_eq!(convert_str("€8 * 2", None, "USD").unwrap(), "20");
+        assert_eq!(convert_str("(1 + 2)£", None, "GBP").unwrap(), "3");
+        assert!(convert_str("100", None, "EUR").is_err());
+        assert!(convert_str("100", Some("XYZ"), "EUR").is_err());
+        assert!(convert_str("100", Some("USD"), "XYZ").is_err());
+
+        // 10進数モードでは正確に計算する
+        let mut evaluator = Evaluator::with_number_mode(Default::default(), NumberMode::Decimal, 30);
+        let value = convert(&mut evaluator, &Rates::parse(RATES).unwrap(), "0.1 USD + 0.2 USD", None, "USD").unwrap();
+        assert_eq!(value.to_string(), "0.3");
+    }
+
+    #[test]
+    fn test_replace_symbols() {
+        assert_eq!(replace_symbols("$100 + €(2 + 3)"), "USD*100 + EUR*(2 + 3)");
+        assert_eq!(replace_symbols("100€ - 5 ¥"), "100*EUR - 5 *JPY");
+        assert_eq!(replace_symbols("2 * $.5"), "2 * USD*.5");
+    }
+
+    #[test]
+    fn test_fetch_uses_fresh_cache() {
+        let cache = std::env::temp_dir().join(format!("calc-rates-test-{}.json", std::process::id()));
+        std::fs::write(&cache, RATES).unwrap();
+        // キャッシュが新しいので取得しない
+        let rates = Rates::fetch("http://127.0.0.1:9/unreachable", &cache, Duration::from_secs(3600), false).unwrap();
+        // 取得に失敗しても古いキャッシュを使う
+        let stale = Rates::fetch("http://127.0.0.1:9/unreachable", &cache, Duration::ZERO, true).unwrap();
+        std::fs::remove_file(&cache).unwrap();
+        assert_eq!(rates, Rates::parse(RATES).unwrap());
+        assert_eq!(stale, rates);
+        assert!(Rates::fetch("http://127.0.0.1:9/unreachable", &cache, Duration::from_secs(3600), false).is_err());
+    }
+}
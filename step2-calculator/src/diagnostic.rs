@@ -0,0 +1,90 @@
+// 構文エラーの位置を入力の下に ^ で示す
+//
+//   2+*3
+//     ^
+//   hint: ...
+use calc_core::CalcError;
+
+use crate::color::Style;
+
+// 構文エラーでなければ None
+pub fn render(input: &str, error: &CalcError, style: Style) -> Option<String> {
+    let CalcError::SyntaxError { position, len, hint, .. } = error else {
+        return None;
+    };
+    let end = position.checked_add(*len)?;
+    if end > input.len() || !input.is_char_boundary(*position) || !input.is_char_boundary(end) {
+        return None;
+    }
+    // 複数行の入力ではエラーのある行だけを表示する
+    let line_start = input[..*position].rfind('\n').map_or(0, |index| index + 1);
+    let line_end = input[*position..].find('\n').map_or(input.len(), |index| position + index);
+    let column = input[line_start..*position].chars().count();
+    let width = input[*position..end.min(line_end)].chars().count().max(1);
+
+    let mut text = format!("  {}\n  {}{}", &input[line_start..line_end], " ".repeat(column), style.error(&"^".repeat(width)));
+    if let Some(hint) = hint {
+        text.push_str(&format!("\n  hint: {}", hint));
+    }
+    Some(text)
+}
+
+// 入力の表示を付けたエラー（終了コードのために元のエラーも持つ）
+#[derive(Debug, thiserror::Error)]
+#[error("{error}\n{snippet}")]
+pub struct Annotated {
+    pub error: CalcError,
+    pub snippet: String,
+}
+
+// 式の評価で出たエラーに入力の表示を付ける（ファイルの読み込みなど文脈付きのエラーはそのまま）
+pub fn annotate(input: &str, error: anyhow::Error, style: Style) -> anyhow::Error {
+    if error.chain().count() > 1 {
+        return error;
+    }
+    match error.downcast::<CalcError>() {
+        Ok(error) => match render(input, &error, style) {
+            Some(snippet) => Annotated { error, snippet }.into(),
+            None => error.into(),
+        },
+        Err(error) => error,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use calc_core::evaluator::Evaluator;
+
+    fn diagnose(input: &str) -> String {
+        let error = Evaluator::default().evaluate_str(input).unwrap_err();
+        format!("{}\n{}", error, render(input, &error, Style::default()).unwrap())
+    }
+
+    #[test]
+    fn test_render() {
+        assert_eq!(diagnose("2+*3"), "Syntax error at position 2: expected a number after '+' but found '*'\n  2+*3\n    ^");
+        assert_eq!(
+            diagnose("(1 + 2"),
+            "Syntax error at position 6: expected ')' but reached end of input\n  (1 + 2\n        ^\n  hint: the '(' at position 0 is never closed"
+        );
+        assert_eq!(
+            diagnose("max(1 22)"),
+            "Syntax error at position 6: expected ',' or ')' but found number 22\n  max(1 22)\n        ^^\n  hint: insert an operator such as '*' between the two values"
+        );
+        assert_eq!(diagnose("1 + 2)"), "Syntax error at position 5: unexpected ')'\n  1 + 2)\n       ^\n  hint: this ')' has no matching '('");
+        assert_eq!(
+            diagnose("3 × 4"),
+            "Syntax error at position 2: unexpected character '×'\n  3 × 4\n    ^\n  hint: use '*' for multiplication"
+        );
+        assert_eq!(diagnose("2 ** "), "Syntax error at position 5: expected a number after '**' but reached end of input\n  2 ** \n       ^");
+    }
+
+    #[test]
+    fn test_other_errors() {
+        let error = Evaluator::default().evaluate_str("1 / 0").unwrap_err();
+        assert_eq!(render("1 / 0", &error, Style::default()), None);
+        let error = anyhow::Error::from(Evaluator::default().evaluate_str("1 +").unwrap_err());
+        assert_eq!(annotate("1 +", error, Style::default()).to_string().lines().count(), 3);
+    }
+}
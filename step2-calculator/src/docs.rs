@@ -0,0 +1,184 @@
+// clap の定義からドキュメントを生成する（calc-cli docs。my-cli docs からも使う）
+use std::fmt::Write;
+use std::io;
+use std::path::Path;
+
+use clap::{Arg, Command};
+
+// 全サブコマンドを1つの Markdown 文書にする
+pub fn markdown(command: &mut Command) -> String {
+    command.build();
+    let mut out = String::new();
+    write_command(&mut out, command, command.get_name(), 1);
+    out
+}
+
+// manページを1ページ出力する
+pub fn man_page(command: Command, out: &mut impl io::Write) -> io::Result<()> {
+    clap_mangen::Man::new(command).render(out)
+}
+
+// calc-cli.1 と各サブコマンドの calc-cli-add.1 などを書き出し、書いたファイル数を返す
+pub fn write_man_pages(command: &mut Command, dir: &Path) -> io::Result<usize> {
+    command.build();
+    std::fs::create_dir_all(dir)?;
+    let mut count = 0;
+    // build() 後のサブコマンドには calc-cli-add のような表示名が付いている
+    let mut pending = vec![command.clone()];
+    while let Some(command) = pending.pop() {
+        pending.extend(command.get_subcommands().filter(|sub| !sub.is_hide_set() && sub.get_name() != "help").cloned());
+        let name = command.get_display_name().unwrap_or(command.get_name()).to_string();
+        let mut file = std::fs::File::create(dir.join(format!("{}.1", name)))?;
+        man_page(command, &mut file)?;
+        count += 1;
+    }
+    Ok(count)
+}
+
+fn write_command(out: &mut String, command: &Command, path: &str, level: usize) {
+    let _ = writeln!(out, "{} {}\n", "#".repeat(level), path);
+    if let Some(about) = command.get_long_about().or(command.get_about()) {
+        let _ = writeln!(out, "{}\n", about);
+    }
+    let usage = command.clone().render_usage().to_string();
+    let usage = usage.trim_start_matches("Usage: ");
+    let _ = writeln!(out, "**Usage:** `{}`\n", usage);
+
+    let subcommands: Vec<&Command> = command
+        .get_subcommands()
+        .filter(|sub| !sub.is_hide_set() && sub.get_name() != "help")
+        .collect();
+    if !subcommands.is_empty() {
+        let _ = writeln!(out, "**Commands:**\n");
+        for sub in &subcommands {
+            let sub_path = format!("{} {}", path, sub.get_name());
+            let about = sub.get_about().map(|about| format!(": {}", about)).unwrap_or_default();
+            let _ = writeln!(out, "- [`{}`](#{}){}", sub.get_name(), anchor(&sub_path), about);
+        }
+        out.push('\n');
+    }
+
+    // 全体のオプションは最上位にだけ書く
+    let top_level = level == 1;
+    let positionals: Vec<&Arg> = command.get_positionals().filter(|arg| !arg.is_hide_set()).collect();
+    let options: Vec<&Arg> = command
+        .get_arguments()
+        .filter(|arg| !arg.is_positional() && !arg.is_hide_set() && (top_level || !arg.is_global_set()))
+        .filter(|arg| !matches!(arg.get_id().as_str(), "help" | "version"))
+        .collect();
+    if !positionals.is_empty() {
+        let _ = writeln!(out, "**Arguments:**\n");
+        for arg in positionals {
+            let name = arg.get_value_names().and_then(|names| names.first()).map_or(arg.get_id().to_string(), |name| name.to_string());
+            let _ = writeln!(out, "- `<{}>`{}", name, describe(arg));
+        }
+        out.push('\n');
+    }
+    if !options.is_empty() {
+        let _ = writeln!(out, "**Options:**\n");
+        for arg in options {
+            let _ = writeln!(out, "- `{}`{}", flag(arg), describe(arg));
+        }
+        out.push('\n');
+    }
+    if let Some(after_help) = command.get_after_long_help().or(command.get_after_help()) {
+        let _ = writeln!(out, "```text\n{}\n```\n", after_help);
+    }
+
+    for sub in subcommands {
+        write_command(out, sub, &format!("{} {}", path, sub.get_name()), (level + 1).min(6));
+    }
+}
+
+// -n, --count <COUNT> の形式
+fn flag(arg: &Arg) -> String {
+    let mut names = Vec::new();
+    if let Some(short) = arg.get_short() {
+        names.push(format!("-{}", short));
+    }
+    if let Some(long) = arg.get_long() {
+        names.push(format!("--{}", long));
+    }
+    let mut flag = names.join(", ");
+    if arg.get_action().takes_values() {
+        let value = arg.get_value_names().and_then(|names| names.first()).map_or(arg.get_id().to_string().to_uppercase(), |name| name.to_string());
+        let _ = write!(flag, " <{}>", value);
+    }
+    flag
+}
+
+// 説明・選択肢・既定値
+fn describe(arg: &Arg) -> String {
+    let mut text = arg.get_help().map(|help| format!(": {}", help)).unwrap_or_default();
+    let values: Vec<String> = arg
+        .get_possible_values()
+        .iter()
+        .filter(|value| !value.is_hide_set())
+        .map(|value| format!("`{}`", value.get_name()))
+        .collect();
+    if !values.is_empty() && arg.get_action().takes_values() {
+        let _ = write!(text, " (possible values: {})", values.join(", "));
+    }
+    let defaults: Vec<String> = arg.get_default_values().iter().map(|value| value.to_string_lossy().into_owned()).collect();
+    if !defaults.is_empty() && arg.get_action().takes_values() {
+        let _ = write!(text, " [default: `{}`]", defaults.join(","));
+    }
+    text
+}
+
+// GitHub の見出しのアンカー（calc-cli percent of → calc-cli-percent-of）
+fn anchor(heading: &str) -> String {
+    heading
+        .chars()
+        .filter_map(|c| match c {
+            ' ' => Some('-'),
+            c if c.is_alphanumeric() || c == '-' || c == '_' => Some(c.to_ascii_lowercase()),
+            _ => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::{Arg, ArgAction};
+
+    fn command() -> Command {
+        Command::new("tool")
+            .about("A tool")
+            .arg(Arg::new("verbose").long("verbose").short('v').global(true).action(ArgAction::SetTrue).help("Talk more"))
+            .subcommand(
+                Command::new("add")
+                    .about("Add numbers")
+                    .arg(Arg::new("a").help("First number"))
+                    .arg(Arg::new("mode").long("mode").value_parser(["fast", "exact"]).default_value("fast").help("How to add")),
+            )
+            .subcommand(Command::new("stats").about("Statistics").subcommand(Command::new("mean").about("Average")))
+    }
+
+    #[test]
+    fn test_markdown() {
+        let markdown = markdown(&mut command());
+        assert!(markdown.starts_with("# tool\n\nA tool\n\n**Usage:** `tool [OPTIONS] [COMMAND]`\n"));
+        assert!(markdown.contains("- [`add`](#tool-add): Add numbers\n"));
+        assert!(markdown.contains("- `-v, --verbose`: Talk more\n"));
+        assert!(markdown.contains("## tool add\n"));
+        assert!(markdown.contains("- `<a>`: First number\n"));
+        assert!(markdown.contains("- `--mode <MODE>`: How to add (possible values: `fast`, `exact`) [default: `fast`]\n"));
+        assert!(markdown.contains("### tool stats mean\n"));
+        // 全体のオプションはサブコマンドでは繰り返さない
+        assert_eq!(markdown.matches("--verbose`").count(), 1);
+    }
+
+    #[test]
+    fn test_man_pages() {
+        let mut page = Vec::new();
+        man_page(command(), &mut page).unwrap();
+        assert!(String::from_utf8(page).unwrap().starts_with(".ie \\n(.g .ds Aq \\(aq"));
+
+        let dir = std::env::temp_dir().join(format!("calc-cli-docs-{}", std::process::id()));
+        assert_eq!(write_man_pages(&mut command(), &dir).unwrap(), 4);
+        assert!(dir.join("tool-stats-mean.1").exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,259 @@
+// 入力履歴の読み込み・検索・書き出しと !N / !! による呼び出し
+//
+// 履歴ファイルは1行に1件の JSON で、入力と一緒にその結果かエラーを持つ
+// 以前の rustyline 形式（#V2 の後に入力だけが並ぶ）のファイルも読める
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::path::Path;
+use my_cli_errors::outln;
+
+use crate::output::csv_escape;
+
+// rustyline が保存していた履歴ファイルの先頭行
+const HISTORY_HEADER: &str = "#V2";
+
+// 履歴の1件（結果もエラーもないのは help などのコマンドか、結果を記録する前のファイルの入力）
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Entry {
+    pub input: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl Entry {
+    pub fn new(input: &str) -> Self {
+        Entry { input: input.to_string(), ..Entry::default() }
+    }
+
+    // 書き出し用の結果（Ok は結果、Err はエラー）
+    pub fn outcome(&self) -> Option<Result<String, String>> {
+        match (&self.result, &self.error) {
+            (Some(result), _) => Some(Ok(result.clone())),
+            (None, Some(error)) => Some(Err(error.clone())),
+            (None, None) => None,
+        }
+    }
+}
+
+// 履歴ファイルを読み込む（ファイルがなければ空）
+pub fn read_history_file(path: &Path) -> Result<Vec<Entry>> {
+    match std::fs::read_to_string(path) {
+        Ok(contents) => Ok(parse_history(&contents)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(e).with_context(|| format!("Failed to read {}", path.display())),
+    }
+}
+
+pub fn write_history_file(path: &Path, entries: &[Entry]) -> Result<()> {
+    let mut contents = String::new();
+    for entry in entries {
+        contents.push_str(&serde_json::to_string(entry)?);
+        contents.push('\n');
+    }
+    std::fs::write(path, contents).with_context(|| format!("Failed to write {}", path.display()))
+}
+
+// JSON として読めない行は rustyline 形式の入力として扱う
+fn parse_history(contents: &str) -> Vec<Entry> {
+    contents
+        .lines()
+        .filter(|line| *line != HISTORY_HEADER && !line.is_empty())
+        .map(|line| serde_json::from_str(line).unwrap_or_else(|_| Entry::new(&unescape(line))))
+        .collect()
+}
+
+// rustyline の形式では \ と改行がエスケープされている
+fn unescape(line: &str) -> String {
+    let mut result = String::with_capacity(line.len());
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => result.push('\n'),
+            Some(other) => result.push(other),
+            None => result.push('\\'),
+        }
+    }
+    result
+}
+
+// 番号付きで履歴を入力と結果（またはエラー）の形で表示（番号は1から、!N で指定する番号と同じ）
+pub fn print_entries(entries: &[Entry], last: Option<usize>) {
+    let skip = last.map_or(0, |last| entries.len().saturating_sub(last));
+    for (index, entry) in entries.iter().enumerate().skip(skip) {
+        outln!("{:>5}  {}", index + 1, format_entry(entry));
+    }
+}
+
+fn format_entry(entry: &Entry) -> String {
+    match entry.outcome() {
+        Some(Ok(result)) => format!("{} -> {}", entry.input, result),
+        Some(Err(error)) => format!("{} -> error: {}", entry.input, error),
+        None => entry.input.clone(),
+    }
+}
+
+// text を含む履歴を番号（!N の番号と同じ）付きで返す（入力だけを探す）
+pub fn search<'a>(entries: &'a [Entry], text: &str, ignore_case: bool) -> Vec<(usize, &'a Entry)> {
+    let text = if ignore_case { text.to_lowercase() } else { text.to_string() };
+    entries
+        .iter()
+        .enumerate()
+        .filter(|(_, entry)| if ignore_case { entry.input.to_lowercase().contains(&text) } else { entry.input.contains(&text) })
+        .map(|(index, entry)| (index + 1, entry))
+        .collect()
+}
+
+// 保存してある結果かエラーも表示する
+pub fn print_matches(matches: &[(usize, &Entry)]) {
+    for (number, entry) in matches {
+        outln!("{:>5}  {}", number, format_entry(entry));
+    }
+}
+
+// 書き出す1件（result は保存した結果かエラー。対話モードのコマンドや、結果を持たない古い履歴は None）
+#[derive(Debug, Clone, PartialEq)]
+pub struct Exported<'a> {
+    pub number: usize,
+    pub entry: &'a str,
+    pub result: Option<Result<String, String>>,
+}
+
+// number,entry,result,error の見出し付きの CSV
+pub fn to_csv(rows: &[Exported]) -> String {
+    let mut csv = String::from("number,entry,result,error\n");
+    for row in rows {
+        let (result, error) = match &row.result {
+            Some(Ok(result)) => (result.as_str(), ""),
+            Some(Err(error)) => ("", error.as_str()),
+            None => ("", ""),
+        };
+        csv.push_str(&format!("{},{},{},{}\n", row.number, csv_escape(row.entry), csv_escape(result), csv_escape(error)));
+    }
+    csv
+}
+
+// {number, entry, result, error} の配列
+pub fn to_json(rows: &[Exported]) -> serde_json::Value {
+    rows.iter()
+        .map(|row| {
+            let (result, error) = match &row.result {
+                Some(Ok(result)) => (json!(result), json!(null)),
+                Some(Err(error)) => (json!(null), json!(error)),
+                None => (json!(null), json!(null)),
+            };
+            json!({ "number": row.number, "entry": row.entry, "result": result, "error": error })
+        })
+        .collect()
+}
+
+// !! と !N を履歴の内容に展開する（それ以外の入力は None）
+pub fn expand_recall(input: &str, entries: &[String]) -> Result<Option<String>> {
+    let Some(event) = input.strip_prefix('!') else {
+        return Ok(None);
+    };
+
+    let entry = if event == "!" {
+        entries.last()
+    } else {
+        let number: usize = event
+            .parse()
+            .with_context(|| format!("Invalid history reference: {}", input))?;
+        number.checked_sub(1).and_then(|index| entries.get(index))
+    };
+
+    match entry {
+        Some(entry) => Ok(Some(entry.clone())),
+        None => bail!("No such history entry: {}", input),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entries() -> Vec<String> {
+        vec!["1 + 2".to_string(), "x = 3".to_string(), "x * 2".to_string()]
+    }
+
+    #[test]
+    fn test_parse_history_file() {
+        let contents = "#V2\n1 + 2\nload a\\\\b.txt\n\nx = 3\n";
+        let inputs: Vec<String> = parse_history(contents).into_iter().map(|entry| entry.input).collect();
+        assert_eq!(inputs, vec!["1 + 2", "load a\\b.txt", "x = 3"]);
+        assert!(parse_history(contents).iter().all(|entry| entry.outcome().is_none()));
+    }
+
+    #[test]
+    fn test_history_results() {
+        let entries = vec![
+            Entry { result: Some("3".to_string()), ..Entry::new("1 + 2") },
+            Entry { error: Some("Division by zero".to_string()), ..Entry::new("1 / 0") },
+            Entry::new("help"),
+        ];
+        let path = std::env::temp_dir().join(format!("calc-history-test-{}", std::process::id()));
+        write_history_file(&path, &entries).unwrap();
+        let loaded = read_history_file(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.unwrap(), entries);
+        // 結果を持たない JSON の行も読める
+        assert_eq!(parse_history("{\"input\": \"x = 3\"}\n"), vec![Entry::new("x = 3")]);
+        assert_eq!(format_entry(&entries[0]), "1 + 2 -> 3");
+        assert_eq!(format_entry(&entries[1]), "1 / 0 -> error: Division by zero");
+        assert_eq!(format_entry(&entries[2]), "help");
+    }
+
+    #[test]
+    fn test_search() {
+        let mut entries: Vec<Entry> = entries().iter().map(|input| Entry::new(input)).collect();
+        entries[0].result = Some("3".to_string());
+        entries[1].result = Some("3".to_string());
+        let inputs = |matches: Vec<(usize, &Entry)>| matches.into_iter().map(|(number, entry)| (number, entry.input.clone())).collect::<Vec<_>>();
+        assert_eq!(inputs(search(&entries, "x", false)), vec![(2, "x = 3".to_string()), (3, "x * 2".to_string())]);
+        assert_eq!(inputs(search(&entries, "X", false)), vec![]);
+        // 結果は探さないが、見つけた履歴は結果も持つ
+        assert_eq!(search(&entries, "X =", true), vec![(2, &entries[1])]);
+        assert_eq!(inputs(search(&entries, "3", false)), vec![(2, "x = 3".to_string())]);
+    }
+
+    #[test]
+    fn test_export() {
+        let rows = vec![
+            Exported { number: 1, entry: "max(1, 2)", result: Some(Ok("2".to_string())) },
+            Exported { number: 2, entry: "1 / 0", result: Some(Err("Division by zero".to_string())) },
+            Exported { number: 3, entry: "help", result: None },
+        ];
+        assert_eq!(to_csv(&rows[..1]), "number,entry,result,error\n1,\"max(1, 2)\",2,\n");
+        assert_eq!(
+            to_csv(&rows),
+            "number,entry,result,error\n1,\"max(1, 2)\",2,\n2,1 / 0,,Division by zero\n3,help,,\n"
+        );
+        let json = to_json(&rows);
+        assert_eq!(json[0], json!({ "number": 1, "entry": "max(1, 2)", "result": "2", "error": null }));
+        assert_eq!(json[1], json!({ "number": 2, "entry": "1 / 0", "result": null, "error": "Division by zero" }));
+        assert_eq!(json[2], json!({ "number": 3, "entry": "help", "result": null, "error": null }));
+    }
+
+    #[test]
+    fn test_expand_recall() {
+        let entries = entries();
+        assert_eq!(expand_recall("!!", &entries).unwrap(), Some("x * 2".to_string()));
+        assert_eq!(expand_recall("!1", &entries).unwrap(), Some("1 + 2".to_string()));
+        assert_eq!(expand_recall("1 + 2", &entries).unwrap(), None);
+    }
+
+    #[test]
+    fn test_expand_recall_errors() {
+        let entries = entries();
+        assert!(expand_recall("!0", &entries).is_err());
+        assert!(expand_recall("!4", &entries).is_err());
+        assert!(expand_recall("!x", &entries).is_err());
+        assert!(expand_recall("!!", &[]).is_err());
+    }
+}
@@ -0,0 +1,127 @@
+// calc-cli のメッセージ（i18n/*.ftl）
+//
+// 表示の言語は --lang（なければ CALC_LANG、LC_ALL、LC_MESSAGES、LANG）で選ぶ
+// 訳すのはテキスト出力だけで、--format json と csv の error は英語のまま（スクリプトで比べられるように）
+use std::error::Error;
+use std::fmt;
+use std::sync::OnceLock;
+
+use calc_core::CalcError;
+use my_cli_i18n::{FluentValue, Messages};
+
+use crate::diagnostic::Annotated;
+
+const CATALOGS: &[(&str, &str)] = &[("en", include_str!("../i18n/en.ftl")), ("ja", include_str!("../i18n/ja.ftl"))];
+pub const LANGUAGES: &[&str] = &["en", "ja"];
+
+static MESSAGES: OnceLock<Messages> = OnceLock::new();
+
+// 引数を読む前に1回だけ呼ぶ（lang は --lang の値）
+pub fn init(lang: Option<&str>) -> &'static Messages {
+    let lang = lang.map(str::to_string).or_else(|| std::env::var("CALC_LANG").ok());
+    MESSAGES.get_or_init(|| Messages::new(CATALOGS, &my_cli_i18n::detect(lang.as_deref(), LANGUAGES)))
+}
+
+// init の前（テストなど）は英語
+pub fn messages() -> &'static Messages {
+    MESSAGES.get_or_init(|| Messages::new(CATALOGS, "en"))
+}
+
+pub fn tr(id: &str, args: &[(&str, FluentValue)]) -> String {
+    messages().format(id, args)
+}
+
+// CalcError の表示（英語では Display と同じ）
+pub fn calc_error(messages: &Messages, error: &CalcError) -> String {
+    let text = |value: &dyn fmt::Display| FluentValue::from(value.to_string());
+    let (id, args) = match error {
+        CalcError::DivisionByZero => ("error-division-by-zero", vec![]),
+        CalcError::InvalidExpression(detail) => ("error-invalid-expression", vec![("detail", text(detail))]),
+        CalcError::SyntaxError { position, message, .. } => ("error-syntax", vec![("position", text(position)), ("message", text(message))]),
+        CalcError::ParseError(detail) => ("error-parse", vec![("detail", text(detail))]),
+        CalcError::UnknownOperation(name) => ("error-unknown-operation", vec![("name", text(name))]),
+        CalcError::UnknownVariable(name) => ("error-unknown-variable", vec![("name", text(name))]),
+        CalcError::UnknownFunction(name) => ("error-unknown-function", vec![("name", text(name))]),
+        CalcError::ArgumentCount { name, expected, found } => {
+            ("error-argument-count", vec![("name", text(name)), ("expected", text(expected)), ("found", text(found))])
+        }
+        CalcError::RecursionLimit(limit) => ("error-recursion-limit", vec![("limit", text(limit))]),
+        CalcError::StepLimit(limit) => ("error-step-limit", vec![("limit", text(limit))]),
+        CalcError::DimensionMismatch(detail) => ("error-dimension-mismatch", vec![("detail", text(detail))]),
+    };
+    messages.format(id, &args)
+}
+
+// エラー1つ分の表示（CalcError と入力の表示付きのものだけを訳す）
+pub fn describe(error: &(dyn Error + 'static)) -> String {
+    if let Some(error) = error.downcast_ref::<CalcError>() {
+        return calc_error(messages(), error);
+    }
+    if let Some(annotated) = error.downcast_ref::<Annotated>() {
+        return format!("{}\n{}", calc_error(messages(), &annotated.error), annotated.snippet);
+    }
+    error.to_string()
+}
+
+// 原因までつないだ表示（anyhow の {:#} と同じ形）
+pub fn describe_chain(error: &anyhow::Error) -> String {
+    error.chain().map(describe).collect::<Vec<_>>().join(": ")
+}
+
+// 表示を訳したエラーの連鎖（my_cli_errors::render に渡す）
+#[derive(Debug)]
+pub struct Translated {
+    text: String,
+    source: Option<Box<Translated>>,
+}
+
+impl Translated {
+    pub fn new(error: &anyhow::Error) -> Translated {
+        let mut causes = error.chain().rev();
+        let root = Translated { text: causes.next().map(describe).unwrap_or_default(), source: None };
+        causes.fold(root, |source, cause| Translated { text: describe(cause), source: Some(Box::new(source)) })
+    }
+}
+
+impl fmt::Display for Translated {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.text)
+    }
+}
+
+impl Error for Translated {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.source.as_deref().map(|source| source as &(dyn Error + 'static))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use calc_core::evaluator::Evaluator;
+
+    #[test]
+    fn test_calc_error() {
+        let en = Messages::new(CATALOGS, "en");
+        let ja = Messages::new(CATALOGS, "ja");
+        // 英語は今までの表示と同じ
+        for input in ["1 / 0", "2 +* 3", "foo + 1", "bar(1)", "sqrt(1, 2)", "[1, 2] + [1, 2, 3]"] {
+            let error = Evaluator::default().evaluate_str(input).unwrap_err();
+            assert_eq!(calc_error(&en, &error), error.to_string(), "{}", input);
+            assert_ne!(calc_error(&ja, &error), error.to_string(), "{}", input);
+        }
+        let error = Evaluator::default().evaluate_str("foo + 1").unwrap_err();
+        assert_eq!(calc_error(&ja, &error), "不明な変数または定数です: foo");
+        assert_eq!(ja.format("summary", &[("total", 3.into()), ("ok", 2.into()), ("failed", 1.into())]), "まとめ: 3 行、成功 2、失敗 1");
+    }
+
+    #[test]
+    fn test_describe() {
+        let error = anyhow::Error::from(CalcError::DivisionByZero).context("Failed to run a.calc");
+        assert_eq!(describe_chain(&error), "Failed to run a.calc: Division by zero");
+        let translated = Translated::new(&error);
+        assert_eq!(translated.to_string(), "Failed to run a.calc");
+        assert_eq!(translated.source().unwrap().to_string(), "Division by zero");
+        assert!(translated.source().unwrap().source().is_none());
+    }
+}
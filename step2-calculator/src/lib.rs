@@ -0,0 +1,1212 @@
+use clap::parser::ValueSource;
+use clap::{Args, CommandFactory, FromArgMatches, Parser, Subcommand, ValueEnum};
+use anyhow::{Context, Result};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+mod ast;
+mod bench;
+mod clipboard;
+mod color;
+mod commands;
+mod completion;
+mod config;
+mod csv;
+mod currency;
+mod diagnostic;
+pub mod docs;
+mod history;
+mod i18n;
+mod memory;
+mod output;
+mod plot;
+mod script;
+mod serve;
+mod vars;
+mod watch;
+
+use calc_core::evaluator::Evaluator;
+use calc_core::format::{self, Notation, NumberFormat, Rounding};
+use calc_core::functions::AngleMode;
+use calc_core::number::{NumberMode, DEFAULT_DIGITS};
+use calc_core::radix::Radix;
+use calc_core::{calculus, random, solver, stats, CalcError};
+use color::{ColorChoice, Theme};
+use commands::eval::parse_variable;
+use commands::{analysis, arithmetic, batch, conversion, data, eval, inspect, integers, interactive, meta, state};
+use my_cli_errors::{outln, CliError};
+use output::{Output, OutputFormat};
+
+// 終了コード（--help の Exit status と合わせる）
+// 計算エラーの終了コードは calc_core::errors にある
+
+// エラーの原因に CalcError があればその終了コード
+fn exit_code(error: &anyhow::Error) -> i32 {
+    my_cli_errors::exit_code(&**error, |cause| {
+        cause
+            .downcast_ref::<CalcError>()
+            .or_else(|| cause.downcast_ref::<diagnostic::Annotated>().map(|annotated| &annotated.error))
+            .map(CliError::exit_code)
+    })
+}
+
+// CLIコマンド構造体
+#[derive(Parser)]
+#[command(name = "calc-cli")]
+#[command(about = "A simple calculator CLI tool")]
+#[command(version)]
+#[command(after_help = "Exit status:\n  \
+0  success\n  \
+1  other errors (files, config, failed batch lines)\n  \
+2  syntax error in an expression, or invalid command-line usage\n  \
+3  math error (domain, overflow, incompatible operands)\n  \
+4  division by zero\n  \
+5  unknown variable, constant or function\n  \
+6  wrong number of function arguments")]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Commands>,
+    
+    /// Angle unit used by trigonometric functions (a single call can use another one, e.g. sin(0.25 turn))
+    #[arg(long, global = true, env = "CALC_ANGLE", value_enum, default_value_t = AngleMode::Radians)]
+    angle: AngleMode,
+    
+    /// Numeric backend used for calculations
+    #[arg(long, global = true, env = "CALC_PRECISION", value_enum, default_value_t = NumberMode::Float)]
+    precision: NumberMode,
+    
+    /// Significant digits kept by inexact decimal operations and to_decimal()
+    #[arg(long, global = true, env = "CALC_DIGITS", value_name = "N", default_value_t = DEFAULT_DIGITS,
+          value_parser = clap::value_parser!(u64).range(1..))]
+    digits: u64,
+    
+    /// Use exact fractions (same as --precision rational), e.g. 1/3 + 1/6 = 1/2
+    #[arg(long, global = true, conflicts_with = "precision")]
+    exact: bool,
+    
+    /// Treat unknown names as units in expressions, e.g. "60 km / 1.5 h" = 40 km/h
+    #[arg(long, global = true, env = "CALC_UNITS")]
+    units: bool,
+    
+    /// Programmer mode: integer arithmetic where ^ is xor and ** is power
+    #[arg(long, global = true, conflicts_with_all = ["precision", "exact"])]
+    bitwise: bool,
+    
+    /// Require an explicit '*': reject implicit multiplication such as 2(3 + 4), 2pi and 60 km
+    #[arg(long, global = true, env = "CALC_STRICT")]
+    strict: bool,
+    
+    /// Seed for rand(), randint() and the rand subcommand (reproducible results)
+    #[arg(long, global = true, value_name = "N")]
+    seed: Option<u64>,
+    
+    /// Decimal places shown in results, e.g. --places 2 prints 3.14
+    #[arg(long, global = true, env = "CALC_PLACES", value_name = "N", conflicts_with = "significant")]
+    places: Option<u64>,
+    
+    /// Significant figures shown in results; values whose trailing zeros would be ambiguous (12000) or
+    /// that are very small switch to scientific notation (1.2e4, 3.4e-7)
+    #[arg(long, visible_alias = "sig-figs", global = true, env = "CALC_SIGNIFICANT", value_name = "N", value_parser = clap::value_parser!(u64).range(1..))]
+    significant: Option<u64>,
+    
+    /// Notation used to print results
+    #[arg(long, global = true, env = "CALC_NOTATION", value_enum, default_value_t = Notation::Fixed)]
+    notation: Notation,
+    
+    /// How --places, --significant and round(x, places) round the last digit
+    #[arg(long, global = true, env = "CALC_ROUNDING", value_enum, value_name = "MODE", default_value_t = Rounding::HalfUp)]
+    rounding: Rounding,
+    
+    /// Group the integer digits of results with thousands separators (1,234,567)
+    #[arg(long, global = true, env = "CALC_SEPARATORS")]
+    separators: bool,
+    
+    /// Use a comma as the decimal point in expressions and results (3,14); separate arguments with ", "
+    #[arg(long, global = true, env = "CALC_DECIMAL_COMMA")]
+    decimal_comma: bool,
+    
+    /// Read and print numbers as in LOCALE, e.g. de_DE uses 1.234,5 ("auto" follows LC_ALL, LC_NUMERIC or LANG)
+    #[arg(long, global = true, env = "CALC_LOCALE", value_name = "LOCALE")]
+    locale: Option<String>,
+    
+    /// Language of messages and help: en or ja (default: from LC_ALL, LC_MESSAGES or LANG)
+    #[arg(long, global = true, env = "CALC_LANG", value_name = "LANG")]
+    lang: Option<String>,
+    
+    /// Output format; json and csv report {expression, result, error} for scripts
+    #[arg(long, global = true, env = "CALC_FORMAT", value_enum, default_value_t = OutputFormat::Text)]
+    format: OutputFormat,
+    
+    /// Base used to print integer results (0xff, 0b1010, 0o755 literals are always accepted)
+    #[arg(long, global = true, env = "CALC_OUTPUT_BASE", value_enum, value_name = "BASE", default_value_t = Radix::Dec)]
+    output_base: Radix,
+    
+    /// Print only the result, without the "expression =" echo (text output)
+    #[arg(short, long, global = true)]
+    quiet: bool,
+    
+    /// When to color results and errors (NO_COLOR disables auto)
+    #[arg(long, global = true, env = "CALC_COLOR", value_enum, value_name = "WHEN", default_value_t = ColorChoice::Auto)]
+    color: ColorChoice,
+    
+    /// Color preset for dark or light terminal backgrounds
+    #[arg(long, global = true, env = "CALC_THEME", value_enum, default_value_t = Theme::Dark)]
+    theme: Theme,
+    
+    /// Color of results instead of the theme's, e.g. "bold blue" or "bright-yellow"
+    #[arg(long, global = true, env = "CALC_RESULT_COLOR", value_name = "COLOR")]
+    result_color: Option<color::Color>,
+    
+    /// Color of error messages instead of the theme's
+    #[arg(long, global = true, env = "CALC_ERROR_COLOR", value_name = "COLOR")]
+    error_color: Option<color::Color>,
+    
+    /// Interactive prompt; {angle}, {base}, {precision} and {digits} show the current settings
+    #[arg(long, global = true, env = "CALC_PROMPT", value_name = "TEXT", default_value = "calc> ")]
+    prompt: String,
+    
+    /// Copy the last result to the system clipboard
+    #[arg(long, global = true)]
+    copy: bool,
+    
+    /// Keep the interactive memory registers (m+, store) in ~/.calc_memory between sessions
+    #[arg(long, global = true, env = "CALC_PERSIST_MEMORY")]
+    persist_memory: bool,
+    
+    // -v と --log-format（my-cli 共通）
+    #[command(flatten)]
+    log: my_cli_log::LogArgs,
+}
+
+impl Cli {
+    // テーマの配色に --result-color と --error-color を重ねる
+    fn palette(&self) -> color::Palette {
+        let theme = self.theme.palette();
+        color::Palette {
+            result: self.result_color.unwrap_or(theme.result),
+            error: self.error_color.unwrap_or(theme.error),
+            ..theme
+        }
+    }
+}
+
+#[derive(Subcommand)]
+enum Commands {
+    /// Basic arithmetic operations
+    #[command(alias = "a")]
+    Add {
+        /// First number
+        a: f64,
+        /// Second number
+        b: f64,
+    },
+    
+    /// Subtract two numbers
+    #[command(alias = "s")]
+    Subtract {
+        /// First number
+        a: f64,
+        /// Second number to subtract
+        b: f64,
+    },
+    
+    /// Multiply two numbers
+    #[command(alias = "m")]
+    Multiply {
+        /// First number
+        a: f64,
+        /// Second number
+        b: f64,
+    },
+    
+    /// Divide two numbers
+    #[command(alias = "d")]
+    Divide {
+        /// Dividend
+        a: f64,
+        /// Divisor
+        b: f64,
+    },
+    
+    /// Calculate power (a^b)
+    #[command(alias = "p")]
+    Power {
+        /// Base
+        base: f64,
+        /// Exponent
+        exp: f64,
+    },
+    
+    /// Calculate square root
+    #[command(alias = "sqrt")]
+    SquareRoot {
+        /// Number to calculate square root
+        number: f64,
+    },
+    
+    /// Evaluate mathematical expression
+    #[command(alias = "e")]
+    Eval(EvalArgs),
+    
+    /// Evaluate an expression read from standard input
+    #[command(name = "-", after_help = "The whole input is one expression (line breaks count as spaces); separate \
+statements with ';'. Only the last result is printed; add -q to print just the value.\n\n\
+Examples:\n  echo \"2+3*4\" | calc-cli -\n  echo \"r = 3; pi * r^2\" | calc-cli -q -\n  \
+echo \"sqrt(2)\" | calc-cli --format json -")]
+    Stdin {
+        /// Set a variable before evaluating, e.g. --var rate=0.05 (repeatable)
+        #[arg(long = "var", value_name = "NAME=VALUE", value_parser = parse_variable)]
+        vars: Vec<(String, String)>,
+    },
+    
+    /// Interactive mode
+    #[command(alias = "i")]
+    Interactive,
+    
+    /// Evaluate one expression per line from a file or standard input
+    #[command(after_help = "Variables and functions defined on one line are visible on the following lines.\nBlank lines and lines starting with # are skipped. Failed lines are reported and skipped; \
+the command exits with an error at the end if any line failed.\n\nExamples:\n  calc-cli batch expressions.txt\n  \
+printf 'x = 2\\nx ^ 10\\n' | calc-cli batch\n  calc-cli --format json batch --summary expressions.txt")]
+    Batch {
+        /// File with one expression per line ("-" for standard input)
+        #[arg(value_name = "FILE", default_value = "-")]
+        file: PathBuf,
+        
+        /// Finish with a summary of every line's status and the ok/failed counts (JSON with --format json)
+        #[arg(long)]
+        summary: bool,
+        
+        /// Set a variable before the first line, e.g. --var rate=0.05 (repeatable)
+        #[arg(long = "var", value_name = "NAME=VALUE", value_parser = parse_variable)]
+        vars: Vec<(String, String)>,
+    },
+    
+    /// Run a calculation script
+    #[command(after_help = "Scripts hold one statement per line; # starts a comment.\n  rate = 0.05 / 12                 variables and functions as in interactive mode\n  print(\"total:\", total)          print strings and values\n  exit(1) if total > budget        stop with an exit code, optionally on a condition")]
+    Run {
+        /// Script file ("-" for standard input)
+        #[arg(value_name = "FILE")]
+        file: PathBuf,
+        
+        /// Continue after failed lines and finish with a summary of every line's status (JSON with --format json)
+        #[arg(long, short = 'k')]
+        keep_going: bool,
+    },
+    
+    /// Run a calculation script again whenever the file changes
+    #[command(after_help = "Each run starts from a clean state with the global options (--angle, --places, ...) applied.\n\
+The screen is cleared before each run when printing to a terminal. Press Ctrl-C to stop.\n\n\
+Examples:\n  calc-cli watch loan.calc\n  calc-cli --places 2 watch --interval 200 budget.calc")]
+    Watch {
+        /// Script file to watch
+        #[arg(value_name = "FILE")]
+        file: PathBuf,
+        
+        /// How often to check the file for changes, in milliseconds
+        #[arg(long, value_name = "MS", default_value_t = 500, value_parser = clap::value_parser!(u64).range(10..))]
+        interval: u64,
+        
+        /// Keep the previous output instead of clearing the screen
+        #[arg(long)]
+        no_clear: bool,
+    },
+    
+    /// Serve a JSON API for evaluating expressions over HTTP
+    #[command(after_help = "Endpoints:\n  POST /eval    {\"expression\": \"x * 2\", \"variables\": {\"x\": 21}}\n  \
+GET  /health  {\"status\": \"ok\"}\n\n\
+Each request starts from a clean state with the global options and saved variables applied. \
+Errors are returned with status 422 and an error object with kind, message and, for syntax errors, position.\n\n\
+Examples:\n  calc-cli serve --port 8080\n  \
+curl -d '{\"expression\": \"sqrt(x)\", \"variables\": {\"x\": 2}}' localhost:8080/eval")]
+    Serve {
+        /// Port to listen on
+        #[arg(short, long, default_value_t = 8080)]
+        port: u16,
+        
+        /// Address to listen on (use 0.0.0.0 to accept connections from other machines)
+        #[arg(long, default_value = "127.0.0.1")]
+        host: String,
+    },
+    
+    /// Print the parse tree of an expression as text, JSON (--format json) or Graphviz DOT (--dot)
+    #[command(after_help = "Assignments and function definitions are accepted too; nothing is evaluated.\n\n\
+Examples:\n  calc-cli ast \"2 + 3 * 4\"\n  calc-cli --format json ast \"f(x) = x^2 + 1\"\n  \
+calc-cli ast --dot \"-b + sqrt(b^2 - 4*a*c)\" | dot -Tsvg > tree.svg")]
+    Ast {
+        /// Expression to parse
+        #[arg(allow_hyphen_values = true)]
+        expression: String,
+        
+        /// Write a Graphviz digraph instead
+        #[arg(long)]
+        dot: bool,
+    },
+    
+    /// Convert an amount between currencies
+    #[command(allow_negative_numbers = true)]
+    #[command(after_help = "Rates come from --rates FILE, or are fetched from --rates-url (or CALC_RATES_URL) and cached in \
+~/.calc_rates.json; without either, the cached rates are used. The file holds {\"base\": \"USD\", \"rates\": {\"EUR\": 0.92, ...}}.\n\
+The amount is an expression; currency codes and symbols ($ € £ ¥ ...) in it are converted too.\n\n\
+Examples:\n  calc-cli currency 100 USD EUR --rates rates.json\n  calc-cli currency \"$100 + 20 EUR\" JPY\n  \
+calc-cli --places 2 currency \"3 * 4.99\" GBP USD --rates-url https://open.er-api.com/v6/latest/USD")]
+    Currency(CurrencyArgs),
+    
+    /// Check the syntax of expressions without evaluating them
+    #[command(after_help = "Assignments and function definitions are accepted too. Exits with an error if any \
+expression is invalid (status 2); use --format json for machine-readable positions.\n\n\
+Examples:\n  calc-cli check \"2 + 3 * 4\"\n  calc-cli --format json check \"2 +* 3\" \"f(x) = x^2\"")]
+    Check {
+        /// Expressions to check
+        #[arg(required = true, allow_hyphen_values = true)]
+        expressions: Vec<String>,
+    },
+    
+    /// Measure how long an expression takes to parse and evaluate
+    #[command(after_help = "A few warm-up runs are discarded first. Reports the mean, 95th percentile and fastest \
+time of parsing, evaluation and both together.\n\n\
+Examples:\n  calc-cli bench \"sum(k, 1, 1000, k^2)\"\n  calc-cli --precision decimal --digits 200 bench -n 100 \"sqrt(2)\"")]
+    Bench {
+        /// Expression to measure
+        #[arg(allow_hyphen_values = true)]
+        expression: String,
+        
+        /// Number of measured runs
+        #[arg(short = 'n', long, value_name = "N", default_value_t = 1000)]
+        iterations: usize,
+        
+        /// Set a variable first, e.g. --var x=2 (repeatable)
+        #[arg(long = "var", value_name = "NAME=VALUE", value_parser = parse_variable)]
+        vars: Vec<(String, String)>,
+    },
+    
+    /// List available mathematical constants
+    Constants,
+    
+    /// Convert a value between units
+    #[command(allow_negative_numbers = true)]
+    #[command(after_help = "Examples:\n  calc-cli convert 5 km mi\n  calc-cli convert 100 degC degF\n  calc-cli convert 60 mph km/h")]
+    Convert {
+        /// Value to convert
+        value: f64,
+        /// Unit of the value (e.g. km, GiB, m^2, km/h)
+        from: String,
+        /// Unit to convert to
+        to: String,
+    },
+    
+    /// List units known to convert and --units
+    Units,
+    
+    /// Show an integer in binary, octal, decimal and hexadecimal
+    #[command(allow_negative_numbers = true)]
+    #[command(after_help = "Examples:\n  calc-cli base 255 --to hex\n  calc-cli base 0b1010")]
+    Base {
+        /// Integer to convert (decimal or 0x/0b/0o literal)
+        value: String,
+        
+        /// Print only this base
+        #[arg(long, value_enum)]
+        to: Option<Radix>,
+    },
+    
+    /// Generate random numbers
+    #[command(allow_negative_numbers = true)]
+    #[command(after_help = "Examples:\n  calc-cli rand\n  calc-cli rand int --min 1 --max 6 -n 10\n  calc-cli rand normal --mean 100 --stddev 15 --seed 42")]
+    Rand(RandArgs),
+    
+    /// Percentage calculations
+    #[command(after_help = "Examples:\n  calc-cli percent of 15 200\n  calc-cli percent change 50 75\n  calc-cli percent what-percent 30 200")]
+    Percent {
+        #[command(subcommand)]
+        command: PercentCommand,
+    },
+    
+    /// Integer utilities: gcd, lcm, primality and factorization
+    #[command(after_help = "Examples:\n  calc-cli numtheory gcd 12 18\n  calc-cli numtheory factor 360")]
+    Numtheory {
+        #[command(subcommand)]
+        command: NumtheoryCommand,
+    },
+    
+    /// Linear algebra on matrices and vectors
+    #[command(after_help = "Examples:\n  calc-cli matrix det \"[[1, 2], [3, 4]]\"\n  calc-cli matrix multiply \"[[1, 2], [3, 4]]\" \"[5, 6]\"\n  \
+calc-cli matrix multiply --file data.csv --column x,y \"[1, 2]\"")]
+    Matrix {
+        /// Operation to apply
+        #[arg(value_enum)]
+        operation: MatrixOperation,
+        
+        /// Matrix or vector expressions, e.g. "[[1, 2], [3, 4]]" or "[1, 2, 3]"
+        #[arg(required_unless_present = "file")]
+        operands: Vec<String>,
+        
+        /// Read the first operand from a CSV file ("-" for standard input); a non-numeric first row is a header
+        #[arg(long, short, value_name = "FILE")]
+        file: Option<PathBuf>,
+        
+        /// CSV columns to use, by number from 1 or header name, comma-separated (default: all)
+        #[arg(long, short, value_delimiter = ',', requires = "file")]
+        column: Vec<csv::Column>,
+    },
+    
+    /// Summary statistics for a list of numbers
+    #[command(allow_negative_numbers = true)]
+    Stats {
+        /// Numbers to summarize (read from --file or standard input when omitted)
+        numbers: Vec<f64>,
+        
+        /// Read whitespace- or comma-separated numbers from a file ("-" for standard input)
+        #[arg(long, short, value_name = "FILE")]
+        file: Option<PathBuf>,
+        
+        /// Read the input as CSV and summarize one column, by number from 1 or header name
+        #[arg(long, short)]
+        column: Option<csv::Column>,
+        
+        /// Percentiles to report, comma-separated
+        #[arg(long, value_delimiter = ',', default_values_t = stats::DEFAULT_PERCENTILES.to_vec())]
+        percentiles: Vec<f64>,
+        
+        /// Use population variance instead of sample variance
+        #[arg(long)]
+        population: bool,
+    },
+    
+    /// Solve an equation for one unknown
+    #[command(allow_negative_numbers = true)]
+    #[command(after_help = "Linear and quadratic equations are solved with their formulas (complex roots print as a + bi). \
+Other equations are solved numerically by looking for sign changes between --from and --to; \
+periodic equations such as sin(x) = 0 can have many roots there, and only the first --max-roots are printed.\n\n\
+Examples:\n  calc-cli solve \"2*x + 5 = 13\"\n  calc-cli solve \"x^2 + 2*x + 5 = 0\"\n  calc-cli solve \"cos(t) = t\" --from 0 --to 1\n  calc-cli solve \"a*x = 4\" --for a")]
+    Solve {
+        /// Equation such as "2*x + 5 = 13"; without "=" the expression is set equal to 0
+        equation: String,
+        
+        /// Unknown to solve for (default: the only unknown name in the equation)
+        #[arg(long = "for", value_name = "NAME")]
+        variable: Option<String>,
+        
+        /// Start of the interval searched for numeric solutions
+        #[arg(long, default_value_t = *solver::DEFAULT_RANGE.start())]
+        from: f64,
+        
+        /// End of the interval searched for numeric solutions
+        #[arg(long, default_value_t = *solver::DEFAULT_RANGE.end())]
+        to: f64,
+        
+        /// Most roots to print; the rest are only counted (0 prints all)
+        #[arg(long, value_name = "N", default_value_t = analysis::DEFAULT_MAX_ROOTS)]
+        max_roots: usize,
+    },
+    
+    /// Find a root of a function numerically within an interval
+    #[command(allow_negative_numbers = true)]
+    #[command(after_help = "Prints the root followed by the method, the number of iterations, the error estimate \
+and the function value at the root.\n\n\
+Examples:\n  calc-cli root \"x^3 - 2\" --from 0 --to 3\n  calc-cli root \"cos(x) = x\" --from 0 --to 1 --method newton\n  calc-cli root \"x^2 - 2\" --from 0 --to 2 --tolerance 1e-6")]
+    Root(RootArgs),
+    
+    /// Definite integral of a function (adaptive Simpson's rule)
+    #[command(allow_negative_numbers = true)]
+    #[command(after_help = "Bounds may be expressions such as pi or 2*e. Prints the integral followed by the \
+error estimate and the number of function evaluations.\n\n\
+Examples:\n  calc-cli integrate \"sin(x)\" --from 0 --to pi\n  calc-cli integrate \"exp(-t^2)\" --from -5 --to 5 --tolerance 1e-12")]
+    Integrate(IntegrateArgs),
+    
+    /// Derivative of a function at a point (central difference)
+    #[command(allow_negative_numbers = true)]
+    #[command(after_help = "The central difference is taken with step h and h/2 and combined by Richardson \
+extrapolation; their difference gives the error estimate.\n\n\
+Examples:\n  calc-cli diff \"x^2\" --at 3\n  calc-cli diff \"sin(x)\" --at pi/4 --step 1e-4")]
+    Diff {
+        /// Function to differentiate, such as "x^2"
+        function: String,
+        
+        /// Point at which to differentiate (an expression)
+        #[arg(long)]
+        at: String,
+        
+        /// Step h of the central difference (default: 0.001 scaled by the size of --at)
+        #[arg(long)]
+        step: Option<f64>,
+        
+        /// Variable to differentiate with respect to (default: the only unknown name in the function)
+        #[arg(long = "for", value_name = "NAME")]
+        variable: Option<String>,
+    },
+    
+    /// Symbolic derivative of a function
+    #[command(after_help = "Applies the sum, product, quotient, power and chain rules and simplifies the result. \
+Names other than the variable are treated as constants.\n\n\
+Examples:\n  calc-cli derive \"x^2 * sin(x)\" --wrt x\n  calc-cli derive \"a * exp(-k * t)\" --wrt t")]
+    Derive {
+        /// Function to differentiate, such as "x^2 * sin(x)"
+        function: String,
+        
+        /// Variable to differentiate with respect to (default: the only unknown name in the function)
+        #[arg(long, value_name = "NAME")]
+        wrt: Option<String>,
+    },
+    
+    /// Plot functions or data points in the terminal
+    #[command(allow_negative_numbers = true)]
+    #[command(after_help = "Functions are drawn as lines; with --points, \"x y\" pairs (or single y values) are read \
+from standard input and drawn as dots. The y range fits the values in the x range. With several functions, \
+each gets its own color and a legend line.\n\n\
+Examples:\n  calc-cli plot \"sin(x)\" --from -6.28 --to 6.28 --width 80 --height 24\n  calc-cli plot \"x^2\" \"2*x + 1\" --from -3 --to 3 --marker block\n  seq 1 20 | calc-cli plot --points")]
+    Plot(PlotArgs),
+    
+    /// Print a shell completion script
+    #[command(after_help = "Examples:\n  calc-cli completions bash > ~/.local/share/bash-completion/completions/calc-cli\n  calc-cli completions zsh > ~/.zfunc/_calc-cli\n  calc-cli completions fish > ~/.config/fish/completions/calc-cli.fish\n  calc-cli completions powershell >> $PROFILE")]
+    Completions {
+        /// Shell to generate completions for
+        #[arg(value_enum)]
+        shell: clap_complete::Shell,
+    },
+    
+    /// Generate documentation for all subcommands
+    #[command(after_help = "Examples:\n  calc-cli docs --markdown > docs/calc-cli.md\n  calc-cli docs --man > calc-cli.1\n  calc-cli docs --man --out-dir man/")]
+    #[command(group = clap::ArgGroup::new("kind").args(["man", "markdown"]).required(true))]
+    Docs {
+        /// Print a man page (roff)
+        #[arg(long)]
+        man: bool,
+        
+        /// Print Markdown with a section for every subcommand
+        #[arg(long)]
+        markdown: bool,
+        
+        /// Write one man page per subcommand into this directory instead of printing
+        #[arg(long, value_name = "DIR", conflicts_with = "markdown")]
+        out_dir: Option<PathBuf>,
+    },
+    
+    /// Show or change defaults stored in the config file
+    #[command(after_help = "Settings apply to every run; environment variables (CALC_ANGLE, CALC_FORMAT, ...) \
+and command-line flags take precedence. Set CALC_CONFIG to use another file.\n\n\
+Examples:\n  calc-cli config set angle deg\n  calc-cli config get precision\n  calc-cli config list")]
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommand,
+    },
+    
+    /// Show, search or export expressions entered in interactive mode
+    #[command(args_conflicts_with_subcommands = true, after_help = "Examples:\n  calc-cli history --last 20\n  \
+calc-cli history search -i sqrt\n  calc-cli history export > history.csv\n  \
+calc-cli history export --format json")]
+    History {
+        #[command(subcommand)]
+        command: Option<HistoryCommand>,
+        
+        /// Show only the most recent N entries
+        #[arg(long, value_name = "N")]
+        last: Option<usize>,
+    },
+    
+    /// Keep variables across sessions; saved variables are defined in every command and in interactive mode
+    #[command(after_help = "Variables are stored in ~/.calc_vars. --var and --load take precedence over saved values.\n\n\
+Examples:\n  calc-cli var set rate 0.0825\n  calc-cli eval \"1200 * (1 + rate)\"\n  calc-cli var list")]
+    Var {
+        #[command(subcommand)]
+        command: VarCommand,
+    },
+    
+    /// Print the version, git commit, build date, target and enabled features (attach this to bug reports)
+    Version {
+        /// Print JSON (same as --format json)
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+#[derive(Args)]
+struct EvalArgs {
+    /// Mathematical expression (e.g., "2 + 3 * 4", "r = 3; pi * r^2")
+    expression: String,
+    
+    /// Load variable and function definitions from a file first
+    #[arg(long = "load", value_name = "FILE")]
+    load: Vec<PathBuf>,
+    
+    /// Set a variable before evaluating, e.g. --var rate=0.05 (repeatable; the value may be an expression)
+    #[arg(long = "var", value_name = "NAME=VALUE", value_parser = parse_variable)]
+    vars: Vec<(String, String)>,
+    
+    /// Print the parse tree and each evaluation step (2 + 3 * 4 → 2 + 12 → 14)
+    #[arg(long)]
+    explain: bool,
+    
+    /// With several statements separated by ';', print every result instead of only the last
+    #[arg(long)]
+    each: bool,
+    
+    /// Report how long parsing and evaluation took (on standard error)
+    #[arg(long, conflicts_with = "explain")]
+    time: bool,
+}
+
+#[derive(Args)]
+struct CurrencyArgs {
+    /// Amount, e.g. 100 or "$100 + 20 EUR"
+    amount: String,
+    
+    /// Currency of the amount and the one to convert to, or only the latter when the amount names its currencies
+    #[arg(value_name = "CURRENCY", num_args = 1..=2, required = true)]
+    currencies: Vec<String>,
+    
+    /// JSON file with exchange rates
+    #[arg(long, value_name = "FILE")]
+    rates: Option<PathBuf>,
+    
+    /// URL to fetch exchange rates from (same JSON as --rates)
+    #[arg(long, env = "CALC_RATES_URL", value_name = "URL")]
+    rates_url: Option<String>,
+    
+    /// Fetch the rates again once the cached ones are older than this many hours
+    #[arg(long, value_name = "HOURS", default_value_t = 24)]
+    max_age: u64,
+    
+    /// Fetch the rates even if the cached ones are recent
+    #[arg(long)]
+    refresh: bool,
+}
+
+#[derive(Args)]
+struct RandArgs {
+    /// Distribution to sample from
+    #[arg(value_enum, default_value_t = random::Distribution::Uniform)]
+    distribution: random::Distribution,
+    
+    /// Lower bound (default 0 for uniform, 1 for int)
+    #[arg(long)]
+    min: Option<f64>,
+    
+    /// Upper bound, exclusive for uniform and inclusive for int (default 1 for uniform, 100 for int)
+    #[arg(long)]
+    max: Option<f64>,
+    
+    /// Mean of the normal distribution
+    #[arg(long, default_value_t = 0.0)]
+    mean: f64,
+    
+    /// Standard deviation of the normal distribution
+    #[arg(long, default_value_t = 1.0)]
+    stddev: f64,
+    
+    /// How many numbers to generate
+    #[arg(long, short = 'n', default_value_t = 1)]
+    count: usize,
+}
+
+#[derive(Args)]
+struct RootArgs {
+    /// Function whose root is wanted, such as "x^3 - 2" (or an equation such as "cos(x) = x")
+    function: String,
+    
+    /// Lower bound of the interval
+    #[arg(long)]
+    from: f64,
+    
+    /// Upper bound of the interval
+    #[arg(long)]
+    to: f64,
+    
+    /// Root-finding method
+    #[arg(long, value_enum, default_value_t = solver::RootMethod::Auto)]
+    method: solver::RootMethod,
+    
+    /// Stop when the error estimate is at most this value
+    #[arg(long, default_value_t = 1e-12)]
+    tolerance: f64,
+    
+    /// Give up after this many iterations
+    #[arg(long, value_name = "N", default_value_t = 100)]
+    max_iterations: usize,
+    
+    /// Variable of the function (default: the only unknown name in it)
+    #[arg(long = "for", value_name = "NAME")]
+    variable: Option<String>,
+}
+
+#[derive(Args)]
+struct IntegrateArgs {
+    /// Function to integrate, such as "sin(x)"
+    function: String,
+    
+    /// Lower bound (an expression)
+    #[arg(long)]
+    from: String,
+    
+    /// Upper bound (an expression)
+    #[arg(long)]
+    to: String,
+    
+    /// Target absolute error
+    #[arg(long, default_value_t = calculus::DEFAULT_TOLERANCE)]
+    tolerance: f64,
+    
+    /// Maximum number of times an interval is halved
+    #[arg(long, value_name = "N", default_value_t = calculus::DEFAULT_MAX_DEPTH)]
+    max_depth: usize,
+    
+    /// Variable of integration (default: the only unknown name in the function)
+    #[arg(long = "for", value_name = "NAME")]
+    variable: Option<String>,
+}
+
+#[derive(Args)]
+struct PlotArgs {
+    /// Functions to plot, such as "sin(x)"
+    #[arg(required_unless_present = "points")]
+    functions: Vec<String>,
+    
+    /// Left end of the x range (an expression; default -10, or the data's smallest x)
+    #[arg(long)]
+    from: Option<String>,
+    
+    /// Right end of the x range (an expression; default 10, or the data's largest x)
+    #[arg(long)]
+    to: Option<String>,
+    
+    /// Width of the plot in characters, including the axis labels
+    #[arg(long, default_value_t = 80)]
+    width: usize,
+    
+    /// Height of the plot in lines, including the x axis labels
+    #[arg(long, default_value_t = 24)]
+    height: usize,
+    
+    /// Characters used to draw
+    #[arg(long, value_enum, default_value_t = plot::Marker::Braille)]
+    marker: plot::Marker,
+    
+    /// Also plot "x y" points read from standard input
+    #[arg(long)]
+    points: bool,
+    
+    /// Variable of the functions (default: the only unknown name in each function)
+    #[arg(long = "for", value_name = "NAME")]
+    variable: Option<String>,
+}
+
+#[derive(Subcommand)]
+enum VarCommand {
+    /// Evaluate an expression and save the result under a name
+    Set {
+        /// Variable name
+        name: String,
+        /// Expression for the value (may use other saved variables)
+        #[arg(allow_hyphen_values = true)]
+        value: String,
+    },
+    
+    /// Print the value of a saved variable
+    Get {
+        /// Variable name
+        name: String,
+    },
+    
+    /// Show every saved variable
+    List,
+    
+    /// Remove a saved variable
+    Unset {
+        /// Variable name
+        name: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum HistoryCommand {
+    /// Write the history with each entry's result or error as CSV, or as a JSON array with --format json
+    Export {
+        /// Export only the most recent N entries
+        #[arg(long, value_name = "N")]
+        last: Option<usize>,
+        
+        /// Evaluate again, in a new session, the entries saved without a result by older versions
+        #[arg(long)]
+        evaluate: bool,
+    },
+    
+    /// Show entries that contain TEXT, numbered as for !N
+    Search {
+        /// Text to look for
+        #[arg(allow_hyphen_values = true)]
+        text: String,
+        
+        /// Ignore upper and lower case
+        #[arg(short, long)]
+        ignore_case: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum PercentCommand {
+    /// P percent of a value
+    #[command(allow_negative_numbers = true)]
+    Of {
+        /// Percentage
+        percent: f64,
+        /// Value to take the percentage of
+        value: f64,
+    },
+    
+    /// Percent change from one value to another
+    #[command(allow_negative_numbers = true)]
+    Change {
+        /// Original value
+        from: f64,
+        /// New value
+        to: f64,
+    },
+    
+    /// What percent a part is of a whole
+    #[command(allow_negative_numbers = true)]
+    WhatPercent {
+        /// Part
+        part: f64,
+        /// Whole
+        whole: f64,
+    },
+}
+
+#[derive(Subcommand)]
+enum NumtheoryCommand {
+    /// Greatest common divisor
+    Gcd {
+        /// Integers (decimal or 0x/0b/0o literals)
+        #[arg(required = true, num_args = 2.., allow_negative_numbers = true)]
+        numbers: Vec<String>,
+    },
+    
+    /// Least common multiple
+    Lcm {
+        /// Integers (decimal or 0x/0b/0o literals)
+        #[arg(required = true, num_args = 2.., allow_negative_numbers = true)]
+        numbers: Vec<String>,
+    },
+    
+    /// Check whether an integer is prime
+    Isprime {
+        /// Integer to test
+        n: String,
+    },
+    
+    /// Prime factorization
+    Factor {
+        /// Integer to factor (up to 2^64 - 1)
+        n: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum ConfigCommand {
+    /// Show every setting with its value and where it comes from
+    List,
+    
+    /// Print the value of a setting
+    Get {
+        /// Setting name (same as the global option, e.g. angle or output-base)
+        key: String,
+    },
+    
+    /// Store a value in the config file
+    Set {
+        /// Setting name (same as the global option, e.g. angle or output-base)
+        key: String,
+        /// Value, checked like the command-line option
+        value: String,
+    },
+    
+    /// Remove a setting from the config file
+    Unset {
+        /// Setting name
+        key: String,
+    },
+    
+    /// Print the location of the config file
+    Path,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum MatrixOperation {
+    /// Evaluate and pretty-print a single matrix or vector
+    Show,
+    /// Determinant of a square matrix
+    Det,
+    /// Inverse of a square matrix
+    Inverse,
+    /// Transpose of a matrix
+    Transpose,
+    /// Sum of the operands
+    Add,
+    /// First operand minus the rest
+    Subtract,
+    /// Product of the operands from left to right
+    Multiply,
+    /// Dot product of two vectors
+    Dot,
+    /// Cross product of two 3-element vectors
+    Cross,
+}
+
+// calc-cli のコマンドの定義（my-cli completions と my-cli docs で使う）
+pub fn command() -> clap::Command {
+    Cli::command()
+}
+
+// calc-cli の入口（args の最初は使い方に表示するコマンド名。my-cli calc からも呼び出す）
+pub fn main_from<I, T>(args: I)
+where
+    I: IntoIterator<Item = T>,
+    T: Into<std::ffi::OsString> + Clone,
+{
+    // ヘルプとメッセージの言語（ヘルプは引数を読む途中で表示するので、先に --lang を探す）
+    let args: Vec<std::ffi::OsString> = args.into_iter().map(Into::into).collect();
+    let messages = i18n::init(my_cli_i18n::lang_arg(&args, &["--lang"]).as_deref());
+    let config_path = config::Config::default_path();
+    let config = match &config_path {
+        Some(path) => config::Config::load(path),
+        None => Ok(config::Config::default()),
+    };
+    // config サブコマンドで書き換えるこのツールの設定ファイルと、全ての層を重ねた値
+    let loaded = config.and_then(|file| Ok((file, config::layered(config_path.clone())?)));
+    let (config, settings) = loaded.unwrap_or_else(|e| exit_with_error(ColorChoice::Auto.style(&std::io::stderr()), &e));
+    let matches = my_cli_config::apply(my_cli_i18n::localize(Cli::command(), messages), &settings).get_matches_from(args);
+    let mut cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    my_cli_log::init(&cli.log);
+    my_cli_config::trace(&settings);
+    // 設定ファイルの --places と --significant は、もう一方をフラグで指定したら使わない
+    if is_explicit(&matches, "significant") && !is_explicit(&matches, "places") {
+        cli.places = None;
+    }
+    if is_explicit(&matches, "places") && !is_explicit(&matches, "significant") {
+        cli.significant = None;
+    }
+    let error_color = cli.color.style(&std::io::stderr()).with_palette(cli.palette());
+    if let Err(e) = run(cli, config, &settings, config_path) {
+        exit_with_error(error_color, &e);
+    }
+}
+
+// my-cli 共通の形式（anyhow のエラーを main から返したときと同じ）で表示し、種類ごとの終了コードで終了する
+// パイプの先が閉じられて書けなくなったときは何も言わずに正常終了する
+fn exit_with_error(color: color::Style, error: &anyhow::Error) -> ! {
+    if my_cli_errors::is_broken_pipe(&**error) {
+        std::process::exit(my_cli_errors::EXIT_SUCCESS);
+    }
+    eprintln!("{}", my_cli_errors::render(&i18n::Translated::new(error), None, |label| color.error(label)));
+    std::process::exit(exit_code(error));
+}
+
+fn run(cli: Cli, config: config::Config, settings: &[my_cli_config::Setting], config_path: Option<PathBuf>) -> Result<()> {
+    let number_mode = match (cli.exact, cli.bitwise) {
+        (true, _) => NumberMode::Rational,
+        (_, true) => NumberMode::Integer,
+        _ => cli.precision,
+    };
+    let decimal_comma = cli.decimal_comma || cli.locale.as_deref().is_some_and(|locale| format::uses_decimal_comma(&locale_name(locale)));
+    let mut evaluator = Evaluator::with_number_mode(cli.angle, number_mode, cli.digits);
+    evaluator.units = cli.units;
+    evaluator.output_radix = cli.output_base;
+    evaluator.number_format = NumberFormat {
+        places: cli.places,
+        significant: cli.significant,
+        notation: cli.notation,
+        separators: cli.separators,
+        rounding: cli.rounding,
+        decimal_comma,
+    };
+    evaluator.syntax.caret_xor = cli.bitwise;
+    evaluator.syntax.strict = cli.strict;
+    evaluator.syntax.decimal_comma = decimal_comma;
+    // --units では 200m をメートルとして読むので、接頭辞の入力は単位を使わないときだけ
+    evaluator.syntax.si_prefixes = cli.notation == Notation::Si && !cli.units;
+    if let Some(seed) = cli.seed {
+        evaluator.seed(seed);
+    }
+    let mut output = Output::new(cli.format);
+    output.color = cli.color.style(&std::io::stdout()).with_palette(cli.palette());
+    output.error_color = cli.color.style(&std::io::stderr()).with_palette(cli.palette());
+    output.quiet = cli.quiet;
+
+    // 保存した変数は --load や --var より先に定義して、そちらで上書きできるようにする
+    let vars_path = vars_path();
+    if !matches!(cli.command, Some(Commands::Var { .. })) && let Some(path) = &vars_path {
+        vars::Vars::load(path)?.define(&mut evaluator)?;
+    }
+    
+    let copy = cli.copy;
+    match cli.command {
+        Some(Commands::Add { a, b }) => arithmetic::add(&evaluator, &output, a, b)?,
+        Some(Commands::Subtract { a, b }) => arithmetic::subtract(&evaluator, &output, a, b)?,
+        Some(Commands::Multiply { a, b }) => arithmetic::multiply(&evaluator, &output, a, b)?,
+        Some(Commands::Divide { a, b }) => arithmetic::divide(&evaluator, &output, a, b)?,
+        Some(Commands::Power { base, exp }) => arithmetic::power(&evaluator, &output, base, exp)?,
+        Some(Commands::SquareRoot { number }) => arithmetic::square_root(&evaluator, &output, number)?,
+        Some(Commands::Eval(args)) => eval::eval(&mut evaluator, &output, args)?,
+        Some(Commands::Stdin { vars }) => eval::stdin(&mut evaluator, &output, &vars)?,
+        Some(Commands::Interactive) => interactive::run(&mut evaluator, &output, &cli.prompt, cli.persist_memory)?,
+        Some(Commands::Batch { file, summary, vars }) => batch::batch(&mut evaluator, &output, &file, summary, &vars)?,
+        Some(Commands::Run { file, keep_going }) => batch::run(&mut evaluator, &output, &file, keep_going)?,
+        Some(Commands::Watch { file, interval, no_clear }) => {
+            watch::run(&evaluator, &file, Duration::from_millis(interval), !no_clear, output.error_color)?;
+        }
+        Some(Commands::Serve { port, host }) => serve::run(&evaluator, &host, port)?,
+        Some(Commands::Ast { expression, dot }) => inspect::ast(&evaluator, &output, &expression, dot)?,
+        Some(Commands::Currency(args)) => conversion::currency(&mut evaluator, &output, args)?,
+        Some(Commands::Check { expressions }) => inspect::check(&evaluator, &output, &expressions),
+        Some(Commands::Bench { expression, iterations, vars }) => inspect::bench(&mut evaluator, &output, &expression, iterations, &vars)?,
+        Some(Commands::Constants) => conversion::constants(&evaluator, &output)?,
+        Some(Commands::Convert { value, from, to }) => conversion::convert(&evaluator, &output, value, &from, &to)?,
+        Some(Commands::Units) => conversion::units(&output)?,
+        Some(Commands::Base { value, to }) => integers::base(&output, &value, to)?,
+        Some(Commands::Rand(args)) => arithmetic::rand(&evaluator, &output, cli.seed, args)?,
+        Some(Commands::Percent { command }) => arithmetic::percent(&evaluator, &output, command)?,
+        Some(Commands::Numtheory { command }) => integers::numtheory(&evaluator, &output, command)?,
+        Some(Commands::Matrix { operation, operands, file, column }) => data::matrix(&evaluator, &output, operation, operands, file, &column)?,
+        Some(Commands::Stats { numbers, file, column, percentiles, population }) => {
+            data::stats(&evaluator, &output, numbers, file.as_deref(), column.as_ref(), &percentiles, population)?;
+        }
+        Some(Commands::Solve { equation, variable, from, to, max_roots }) => {
+            analysis::solve(&evaluator, &output, &equation, variable.as_deref(), from..=to, max_roots)?;
+        }
+        Some(Commands::Root(args)) => analysis::root(&evaluator, &output, args)?,
+        Some(Commands::Integrate(args)) => analysis::integrate(&evaluator, &output, args)?,
+        Some(Commands::Diff { function, at, step, variable }) => {
+            analysis::diff(&evaluator, &output, &function, &at, step, variable.as_deref())?;
+        }
+        Some(Commands::Derive { function, wrt }) => analysis::derive(&evaluator, &output, &function, wrt.as_deref())?,
+        Some(Commands::Plot(args)) => analysis::plot(&evaluator, &output, args)?,
+        Some(Commands::Completions { shell }) => meta::completions(shell),
+        Some(Commands::Docs { man, out_dir, .. }) => meta::docs(man, out_dir)?,
+        Some(Commands::Version { json }) => meta::version(&output, json),
+        Some(Commands::Config { command }) => meta::config(&output, config, settings, config_path.as_deref(), command)?,
+        Some(Commands::History { command, last }) => state::history(&mut evaluator, &output, command, last)?,
+        Some(Commands::Var { command }) => state::var(&mut evaluator, &output, vars_path.as_deref(), command)?,
+        None => {
+            outln!("No command provided. Use --help for usage information.");
+            outln!("Quick examples:");
+            outln!("  calc-cli add 10 5");
+            outln!("  calc-cli eval \"2 + 3 * 4\"");
+            outln!("  echo \"2 + 3 * 4\" | calc-cli -");
+            outln!("  calc-cli interactive");
+        }
+    }
+
+    if copy {
+        let result = output.last_result().context("No result to copy")?;
+        clipboard::copy(&result, true)?;
+    }
+    Ok(())
+}
+
+// フラグか環境変数で指定された値か
+fn is_explicit(matches: &clap::ArgMatches, id: &str) -> bool {
+    matches!(matches.value_source(id), Some(ValueSource::CommandLine | ValueSource::EnvVariable))
+}
+
+// ファイルの内容を読む（"-" は標準入力）
+fn read_input(path: &Path) -> Result<String> {
+    if path == Path::new("-") {
+        let mut input = String::new();
+        std::io::stdin().read_to_string(&mut input).context("Failed to read standard input")?;
+        Ok(input)
+    } else {
+        std::fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))
+    }
+}
+
+// --locale の名前（auto なら LC_ALL、LC_NUMERIC、LANG のうち最初に設定されているもの）
+fn locale_name(locale: &str) -> String {
+    if locale != "auto" {
+        return locale.to_string();
+    }
+    ["LC_ALL", "LC_NUMERIC", "LANG"]
+        .iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .find(|value| !value.is_empty())
+        .unwrap_or_default()
+}
+
+// my-cli doctor で調べる設定ファイル（弱い順）
+pub fn config_files() -> Vec<my_cli_config::File> {
+    config::SECTION.files(config::Config::default_path())
+}
+
+// 設定ファイルを全て読んで、キーと値を確かめる
+pub fn check_config() -> std::result::Result<(), my_cli_config::ConfigError> {
+    config::SECTION.load(&config_files(), &Cli::command()).map(|_| ())
+}
+
+// 読み書きするファイル（説明と場所）
+pub fn state_files() -> Vec<(&'static str, PathBuf)> {
+    [
+        ("interactive history", history_path()),
+        ("memory registers (--persist-memory)", memory_path()),
+        ("saved variables (var)", vars_path()),
+        ("exchange rate cache (currency)", rates_path()),
+    ]
+    .into_iter()
+    .filter_map(|(description, path)| path.map(|path| (description, path)))
+    .collect()
+}
+
+// version サブコマンドの内容（計算エンジンの calc-core のフィーチャも出す）
+pub fn build_info() -> my_cli_build_info::BuildInfo {
+    my_cli_build_info::build_info!().with_crates([my_cli_build_info::Crate::new("calc-core", calc_core::VERSION, calc_core::features())])
+}
+
+// 履歴ファイルのパス（~/.calc_history）
+fn history_path() -> Option<PathBuf> {
+    dirs::home_dir().map(|home| home.join(".calc_history"))
+}
+
+// メモリーのレジスタを保存するファイル（~/.calc_memory）
+fn memory_path() -> Option<PathBuf> {
+    dirs::home_dir().map(|home| home.join(".calc_memory"))
+}
+
+// 取得した為替レートを保存するファイル（~/.calc_rates.json）
+fn rates_path() -> Option<PathBuf> {
+    dirs::home_dir().map(|home| home.join(".calc_rates.json"))
+}
+
+// var で保存する変数のファイル（~/.calc_vars）
+fn vars_path() -> Option<PathBuf> {
+    dirs::home_dir().map(|home| home.join(".calc_vars"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use calc_core::errors::{EXIT_ARGUMENT_COUNT, EXIT_DIVISION_BY_ZERO, EXIT_MATH_ERROR, EXIT_PARSE_ERROR, EXIT_UNKNOWN_NAME};
+    use my_cli_errors::EXIT_FAILURE;
+
+    #[test]
+    fn test_exit_codes() {
+        let code = |expr: &str| exit_code(&anyhow::Error::from(Evaluator::default().evaluate_str(expr).unwrap_err()));
+        assert_eq!(code("2 +* 3"), EXIT_PARSE_ERROR);
+        assert_eq!(code("sqrt(-1)"), EXIT_MATH_ERROR);
+        assert_eq!(code("1 / 0"), EXIT_DIVISION_BY_ZERO);
+        assert_eq!(code("foo + 1"), EXIT_UNKNOWN_NAME);
+        assert_eq!(code("sin(1, 2)"), EXIT_ARGUMENT_COUNT);
+        // 文脈を付けても原因の種類で決まる
+        let error = anyhow::Error::from(CalcError::DivisionByZero).context("line 3: 1 / 0");
+        assert_eq!(exit_code(&error), EXIT_DIVISION_BY_ZERO);
+        assert_eq!(exit_code(&anyhow::anyhow!("Failed to read file")), EXIT_FAILURE);
+        let error = diagnostic::annotate("1 +", Evaluator::default().evaluate_str("1 +").unwrap_err().into(), Default::default());
+        assert_eq!(exit_code(&error), EXIT_PARSE_ERROR);
+    }
+
+    #[test]
+    fn test_cli_definition() {
+        // 補完スクリプトの生成にも使う定義に矛盾がないこと
+        Cli::command().debug_assert();
+        let mut completions = Vec::new();
+        clap_complete::generate(clap_complete::Shell::Bash, &mut Cli::command(), "calc-cli", &mut completions);
+        assert!(String::from_utf8(completions).unwrap().contains("completions"));
+    }
+}
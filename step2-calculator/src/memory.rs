@@ -0,0 +1,214 @@
+// 電卓のメモリー（対話モードの m+ m- mr mc と名前付きのレジスタ）
+//
+//   m+ / m-            直前の結果をメモリー M に足す・引く
+//   mr / mc            M を呼び出す・消す
+//   store NAME [式]    直前の結果（式があればその値）をレジスタに入れる
+//   recall NAME        レジスタの値を呼び出す（ans にもなる）
+//   memory [clear]     全てのレジスタを表示する・消す
+// レジスタは今の数値のモードの値をそのまま持つ（10進数や分数のモードで 0.1 m+ 0.2 m+ が正確に 0.3 になる）
+// --persist-memory では ~/.calc_memory に保存して次回の起動で読み込む
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use toml_edit::DocumentMut;
+
+use calc_core::number::{Number, NumberMode, DEFAULT_DIGITS};
+use calc_core::CalcError;
+
+// m+ などが使うレジスタ
+pub const MAIN: &str = "M";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Command<'a> {
+    Add(f64),
+    Recall(&'a str),
+    Clear(&'a str),
+    Store { name: &'a str, expression: Option<&'a str> },
+    List,
+    ClearAll,
+}
+
+// メモリーのコマンドでなければ None
+pub fn parse_command(input: &str) -> Option<Result<Command<'_>>> {
+    let (word, rest) = input.split_once(char::is_whitespace).unwrap_or((input, ""));
+    let rest = rest.trim();
+    let command = match (word.to_ascii_lowercase().as_str(), rest) {
+        ("m+", "") => Command::Add(1.0),
+        ("m-", "") => Command::Add(-1.0),
+        ("mr", "") => Command::Recall(MAIN),
+        ("mc", "") => Command::Clear(MAIN),
+        ("memory", "") => Command::List,
+        ("memory", "clear") => Command::ClearAll,
+        ("recall", name) => return Some(register_name(name).map(Command::Recall)),
+        ("store", rest) => {
+            let (name, expression) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+            let expression = Some(expression.trim()).filter(|expression| !expression.is_empty());
+            return Some(register_name(name).map(|name| Command::Store { name, expression }));
+        }
+        _ => return None,
+    };
+    Some(Ok(command))
+}
+
+fn register_name(name: &str) -> Result<&str> {
+    let mut chars = name.chars();
+    let valid = chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if !valid {
+        bail!("Invalid register name '{}' (use letters, digits and _)", name);
+    }
+    Ok(name)
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct Memory {
+    registers: BTreeMap<String, Number>,
+}
+
+impl Memory {
+    // ファイルがなければ空
+    pub fn load(path: &Path) -> Result<Memory> {
+        let contents = match std::fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Memory::default()),
+            Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path.display())),
+        };
+        Memory::parse(&contents).with_context(|| format!("Invalid memory file {}", path.display()))
+    }
+
+    // 「名前 = 数値」の TOML（10進数・分数・整数は "0.3" や "1/3" の文字列）
+    pub fn parse(contents: &str) -> Result<Memory> {
+        let document: DocumentMut = contents.parse()?;
+        let mut memory = Memory::default();
+        for (name, item) in document.iter() {
+            let value = match (item.as_float(), item.as_integer(), item.as_str()) {
+                (Some(value), _, _) => Number::Float(value),
+                (_, Some(value), _) => Number::Integer(value.into()),
+                (_, _, Some(text)) => parse_number(text).with_context(|| format!("register '{}' is not a number", name))?,
+                _ => bail!("register '{}' is not a number", name),
+            };
+            memory.set(register_name(name)?, value);
+        }
+        Ok(memory)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let mut document = DocumentMut::new();
+        for (name, value) in &self.registers {
+            document[name] = match value {
+                Number::Float(value) => toml_edit::value(*value),
+                exact => toml_edit::value(exact.to_string()),
+            };
+        }
+        std::fs::write(path, document.to_string()).with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Number> {
+        self.registers.get(name)
+    }
+
+    pub fn set(&mut self, name: &str, value: Number) {
+        self.registers.insert(name.to_string(), value);
+    }
+
+    // 空のレジスタは 0 として足し、足した後の値を返す
+    pub fn add(&mut self, name: &str, value: &Number, digits: u64) -> Result<Number, CalcError> {
+        let total = match self.get(name) {
+            Some(current) => current.add(value, digits)?,
+            None => value.clone(),
+        };
+        self.set(name, total.clone());
+        Ok(total)
+    }
+
+    // 消したら true
+    pub fn clear(&mut self, name: &str) -> bool {
+        self.registers.remove(name).is_some()
+    }
+
+    pub fn clear_all(&mut self) {
+        self.registers.clear();
+    }
+
+    pub fn registers(&self) -> impl Iterator<Item = (&str, &Number)> {
+        self.registers.iter().map(|(name, value)| (name.as_str(), value))
+    }
+}
+
+// 保存した文字列の値（分数は「分子/分母」）
+fn parse_number(text: &str) -> Result<Number, CalcError> {
+    match text.split_once('/') {
+        Some((numer, denom)) => {
+            let numer = Number::parse(numer.trim(), NumberMode::Rational)?;
+            numer.div(&Number::parse(denom.trim(), NumberMode::Rational)?, DEFAULT_DIGITS)
+        }
+        None => Number::parse(text.trim(), NumberMode::Decimal),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_command() {
+        fn parse(input: &str) -> Option<Command<'_>> {
+            parse_command(input).map(|result| result.unwrap())
+        }
+        assert_eq!(parse("m+"), Some(Command::Add(1.0)));
+        assert_eq!(parse("M-"), Some(Command::Add(-1.0)));
+        assert_eq!(parse("mr"), Some(Command::Recall(MAIN)));
+        assert_eq!(parse("recall tax"), Some(Command::Recall("tax")));
+        assert_eq!(parse("store tax"), Some(Command::Store { name: "tax", expression: None }));
+        assert_eq!(parse("store tax 8 / 100"), Some(Command::Store { name: "tax", expression: Some("8 / 100") }));
+        assert_eq!(parse("memory clear"), Some(Command::ClearAll));
+        // 式として読むものはコマンドにしない
+        assert_eq!(parse("m + 1"), None);
+        assert_eq!(parse("mr * 2"), None);
+        assert!(parse_command("store 1x").unwrap().is_err());
+        assert!(parse_command("recall").unwrap().is_err());
+    }
+
+    #[test]
+    fn test_registers_and_file() {
+        let number = |text: &str, mode| Number::parse(text, mode).unwrap();
+        let mut memory = Memory::default();
+        assert_eq!(memory.add(MAIN, &number("5", NumberMode::Float), DEFAULT_DIGITS).unwrap(), Number::Float(5.0));
+        assert_eq!(memory.add(MAIN, &number("-2", NumberMode::Float), DEFAULT_DIGITS).unwrap(), Number::Float(3.0));
+        memory.set("tax", number("1.08", NumberMode::Float));
+        assert!(memory.clear("tax"));
+        assert!(!memory.clear("tax"));
+        memory.set("rate", number("2", NumberMode::Float));
+        // 10進数と分数は正確な値のまま保存する
+        memory.set("tenth", number("0.1", NumberMode::Decimal));
+        let third = number("1", NumberMode::Rational).div(&number("3", NumberMode::Rational), DEFAULT_DIGITS).unwrap();
+        memory.set("third", third.clone());
+
+        let path = std::env::temp_dir().join(format!("calc-memory-test-{}.toml", std::process::id()));
+        memory.save(&path).unwrap();
+        let loaded = Memory::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.get(MAIN), Some(&Number::Float(3.0)));
+        assert_eq!(loaded.get("tenth"), Some(&number("0.1", NumberMode::Decimal)));
+        assert_eq!(loaded.get("third"), Some(&third));
+        assert_eq!(loaded.registers().map(|(name, _)| name).collect::<Vec<_>>(), vec![MAIN, "rate", "tenth", "third"]);
+
+        assert_eq!(Memory::parse("x = 3").unwrap().get("x"), Some(&Number::Integer(3.into())));
+        assert_eq!(Memory::parse("x = 0.5").unwrap().get("x"), Some(&Number::Float(0.5)));
+        assert!(Memory::parse("x = \"a\"").is_err());
+        assert!(Memory::parse("x = true").is_err());
+        assert_eq!(Memory::load(Path::new("/nonexistent/calc_memory")).unwrap(), Memory::default());
+    }
+
+    #[test]
+    fn test_exact_sums() {
+        // 0.1 m+ 0.2 m+ が 0.3 になる（f64 では 0.30000000000000004）
+        for mode in [NumberMode::Decimal, NumberMode::Rational] {
+            let mut memory = Memory::default();
+            memory.add(MAIN, &Number::parse("0.1", mode).unwrap(), DEFAULT_DIGITS).unwrap();
+            let total = memory.add(MAIN, &Number::parse("0.2", mode).unwrap(), DEFAULT_DIGITS).unwrap();
+            assert_eq!(total, Number::parse("0.3", mode).unwrap());
+        }
+    }
+}
@@ -0,0 +1,225 @@
+// 結果の出力形式（テキスト・JSON・CSV）
+use std::cell::{Cell, RefCell};
+use std::fmt::Display;
+
+use serde::Serialize;
+use serde_json::json;
+
+use calc_core::evaluator::Evaluator;
+use calc_core::number::Number;
+use calc_core::value::Value;
+use my_cli_errors::outln;
+
+use crate::color::Style;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum OutputFormat {
+    /// Human-readable text
+    #[default]
+    Text,
+    /// One JSON object per result: {"expression": ..., "result": ..., "error": null}
+    Json,
+    /// CSV rows with expression,result,error columns
+    Csv,
+}
+
+// 1件の結果（JSON ではこのまま1行に出力する）
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Record {
+    pub expression: String,
+    pub result: serde_json::Value,
+    pub error: Option<String>,
+}
+
+impl Record {
+    pub fn new<E: Display>(expression: &str, result: Result<serde_json::Value, E>) -> Record {
+        match result {
+            Ok(result) => Record { expression: expression.to_string(), result, error: None },
+            Err(e) => Record { expression: expression.to_string(), result: serde_json::Value::Null, error: Some(e.to_string()) },
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Output {
+    pub format: OutputFormat,
+    // 標準出力と標準エラー出力の色
+    pub color: Style,
+    pub error_color: Style,
+    // 「式 =」を付けずに結果だけを出力する（-q）
+    pub quiet: bool,
+    // CSV の見出し行を出力済みか
+    header_written: Cell<bool>,
+    // 最後に出力した結果（--copy と対話モードの copy でコピーする）
+    last_result: RefCell<Option<String>>,
+}
+
+impl Output {
+    pub fn new(format: OutputFormat) -> Output {
+        Output { format, ..Output::default() }
+    }
+
+    pub fn is_text(&self) -> bool {
+        self.format == OutputFormat::Text
+    }
+
+    pub fn last_result(&self) -> Option<String> {
+        self.last_result.borrow().clone()
+    }
+
+    // 「式 = 結果」を出力する（エラーは JSON・CSV でも出力してから返す）
+    pub fn value<E: Display>(&self, evaluator: &Evaluator, expression: &str, result: Result<Value, E>) -> Result<(), E> {
+        if self.is_text() {
+            self.line(expression, &evaluator.format(&result?));
+            return Ok(());
+        }
+        self.json(expression, result.map(|value| value_to_json(&value)))
+    }
+
+    // JSON の値で表した結果を出力する（テキスト形式では呼ばない）
+    pub fn json<E: Display>(&self, expression: &str, result: Result<serde_json::Value, E>) -> Result<(), E> {
+        match result {
+            Ok(value) => {
+                self.print(&Record::new::<E>(expression, Ok(value)));
+                Ok(())
+            }
+            Err(e) => {
+                self.print(&Record::new(expression, Err::<serde_json::Value, _>(&e)));
+                Err(e)
+            }
+        }
+    }
+
+    // 「式 = 結果」の1行（テキスト形式）
+    pub fn line(&self, expression: &str, result: &str) {
+        self.last_result.replace(Some(result.to_string()));
+        if self.quiet {
+            outln!("{}", self.color.result(result));
+        } else {
+            outln!("{} = {}", self.color.dim(expression), self.color.result(result));
+        }
+    }
+
+    // 対話モードなどで標準出力に出すエラー
+    pub fn error(&self, message: impl Display) {
+        outln!("{} {}", self.color.error("Error:"), message);
+    }
+
+    pub fn print(&self, record: &Record) {
+        if record.error.is_none() {
+            self.last_result.replace(Some(csv_cell(&record.result)));
+        }
+        match self.format {
+            OutputFormat::Text => match &record.error {
+                Some(error) => self.error(error),
+                None => self.line(&record.expression, &csv_cell(&record.result)),
+            },
+            OutputFormat::Json => outln!("{}", serde_json::to_string(record).unwrap_or_default()),
+            OutputFormat::Csv => {
+                if !self.header_written.replace(true) {
+                    outln!("expression,result,error");
+                }
+                for row in csv_rows(record) {
+                    outln!("{}", row);
+                }
+            }
+        }
+    }
+}
+
+// 値を JSON にする（f64 と i64 に収まる整数は数値、それ以外の正確な値は文字列）
+pub fn value_to_json(value: &Value) -> serde_json::Value {
+    match value {
+        Value::Number(number) => number_to_json(number),
+        Value::Vector(values) => values.iter().map(number_to_json).collect(),
+        Value::Matrix(matrix) => matrix.rows().map(|row| row.iter().map(number_to_json).collect::<serde_json::Value>()).collect(),
+        Value::Quantity(quantity) => json!({ "value": quantity.value, "unit": quantity.unit.to_string() }),
+        Value::Uncertain(uncertain) => json!({ "value": uncertain.value, "uncertainty": uncertain.error }),
+    }
+}
+
+pub fn number_to_json(number: &Number) -> serde_json::Value {
+    match number {
+        Number::Float(value) => serde_json::Number::from_f64(*value)
+            .map_or_else(|| json!(value.to_string()), serde_json::Value::Number),
+        Number::Integer(value) => i64::try_from(value).map_or_else(|_| json!(value.to_string()), |value| json!(value)),
+        other => json!(other.to_string()),
+    }
+}
+
+// 配列は要素ごと、オブジェクトはキーごとに1行にする
+fn csv_rows(record: &Record) -> Vec<String> {
+    let row = |expression: &str, result: &serde_json::Value| {
+        let error = record.error.as_deref().unwrap_or("");
+        format!("{},{},{}", csv_escape(expression), csv_escape(&csv_cell(result)), csv_escape(error))
+    };
+    match &record.result {
+        serde_json::Value::Array(items) => items.iter().map(|item| row(&record.expression, item)).collect(),
+        serde_json::Value::Object(fields) => fields.iter().map(|(key, value)| row(key, value)).collect(),
+        other => vec![row(&record.expression, other)],
+    }
+}
+
+fn csv_cell(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::Null => String::new(),
+        serde_json::Value::String(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
+
+pub fn csv_escape(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use calc_core::number::NumberMode;
+
+    #[test]
+    fn test_value_to_json() {
+        let evaluator = Evaluator::default();
+        assert_eq!(value_to_json(&evaluator.evaluate_str("1 / 4").unwrap()), json!(0.25));
+        assert_eq!(value_to_json(&evaluator.evaluate_str("[[1, 2], [3, 4]]").unwrap()), json!([[1.0, 2.0], [3.0, 4.0]]));
+
+        let integer = |text: &str| Number::parse(text, NumberMode::Integer).unwrap();
+        assert_eq!(number_to_json(&integer("42")), json!(42));
+        assert_eq!(number_to_json(&integer("123456789012345678901234567890")), json!("123456789012345678901234567890"));
+        assert_eq!(number_to_json(&Number::parse("0.1", NumberMode::Decimal).unwrap()), json!("0.1"));
+        assert_eq!(number_to_json(&Number::Float(f64::INFINITY)), json!("inf"));
+    }
+
+    #[test]
+    fn test_record() {
+        let ok = Record::new::<String>("1 + 2", Ok(json!(3)));
+        assert_eq!(serde_json::to_string(&ok).unwrap(), r#"{"expression":"1 + 2","result":3,"error":null}"#);
+        let error = Record::new("1 / 0", Err::<serde_json::Value, _>("Division by zero"));
+        assert_eq!(serde_json::to_string(&error).unwrap(), r#"{"expression":"1 / 0","result":null,"error":"Division by zero"}"#);
+    }
+
+    #[test]
+    fn test_last_result() {
+        let output = Output::new(OutputFormat::Json);
+        assert_eq!(output.last_result(), None);
+        output.print(&Record::new::<String>("1 / 3", Ok(json!("1/3"))));
+        output.print(&Record::new("1 / 0", Err::<serde_json::Value, _>("Division by zero")));
+        assert_eq!(output.last_result().as_deref(), Some("1/3"));
+    }
+
+    #[test]
+    fn test_csv_rows() {
+        let scalar = Record::new::<String>("max(1, 2)", Ok(json!(2)));
+        assert_eq!(csv_rows(&scalar), vec!["\"max(1, 2)\",2,"]);
+        let array = Record::new::<String>("rand", Ok(json!([1, 2])));
+        assert_eq!(csv_rows(&array), vec!["rand,1,", "rand,2,"]);
+        let object = Record::new::<String>("stats", Ok(json!({ "mean": 2.5, "mode": [1, 2] })));
+        assert_eq!(csv_rows(&object), vec!["mean,2.5,", "mode,\"[1,2]\","]);
+        let error = Record::new("x", Err::<serde_json::Value, _>("Unknown variable or constant: x"));
+        assert_eq!(csv_rows(&error), vec!["x,,Unknown variable or constant: x"]);
+    }
+}
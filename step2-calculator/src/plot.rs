@@ -0,0 +1,299 @@
+// 端末へのグラフの描画（calc-cli plot）
+//
+// 1文字を点字（braille）では横2×縦4、ブロックでは上下2つの点に分けて描く
+// y = 0 と x = 0 の位置には点のない所に軸の罫線を引く
+use calc_core::evaluator::Evaluator;
+use calc_core::{parser, solver, CalcError};
+
+use crate::color::Style;
+
+// x の範囲を指定しないときの既定値
+pub const DEFAULT_RANGE: (f64, f64) = (-10.0, 10.0);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum Marker {
+    /// Braille dots, 2×4 per character
+    #[default]
+    Braille,
+    /// Half blocks, 1×2 per character
+    Block,
+    /// Plain ASCII asterisks, one per character
+    Ascii,
+}
+
+impl Marker {
+    // 1文字あたりの点の数（横, 縦）
+    fn resolution(self) -> (usize, usize) {
+        match self {
+            Marker::Braille => (2, 4),
+            Marker::Block => (1, 2),
+            Marker::Ascii => (1, 1),
+        }
+    }
+
+    // 文字の中の (dx, dy) の点を表すビット
+    fn bit(self, dx: usize, dy: usize) -> u8 {
+        const BRAILLE: [[u8; 2]; 4] = [[0x01, 0x08], [0x02, 0x10], [0x04, 0x20], [0x40, 0x80]];
+        match self {
+            Marker::Braille => BRAILLE[dy][dx],
+            Marker::Block => 1 << dy,
+            Marker::Ascii => 1,
+        }
+    }
+
+    fn glyph(self, bits: u8) -> char {
+        match self {
+            Marker::Braille => char::from_u32(0x2800 + u32::from(bits)).unwrap_or(' '),
+            Marker::Block => match bits {
+                1 => '▀',
+                2 => '▄',
+                _ => '█',
+            },
+            Marker::Ascii => '*',
+        }
+    }
+
+    // 軸の罫線（横, 縦, 交点, 目盛り）
+    fn axes(self) -> [char; 4] {
+        match self {
+            Marker::Ascii => ['-', '|', '+', '+'],
+            _ => ['─', '│', '┼', '┤'],
+        }
+    }
+}
+
+// 描く点の並び（関数は隣り合う点を線で結び、データは点だけを打つ）
+#[derive(Debug, Clone)]
+pub struct Series {
+    pub label: String,
+    pub points: Vec<(f64, f64)>,
+    pub connect: bool,
+}
+
+// グラフ全体の大きさ（文字数）と x の範囲
+#[derive(Debug, Clone, Copy)]
+pub struct Plot {
+    pub width: usize,
+    pub height: usize,
+    pub marker: Marker,
+    pub x_range: (f64, f64),
+}
+
+impl Plot {
+    // 目盛りの数値と軸を除いた描画領域の点の数（横, 縦）
+    fn dots(&self, margin: usize) -> (usize, usize) {
+        let (rx, ry) = self.marker.resolution();
+        (self.width.saturating_sub(margin + 1) * rx, self.height.saturating_sub(2) * ry)
+    }
+
+    // 関数を計算する x の数（描画領域の横の点の数以上）
+    pub fn samples(&self) -> usize {
+        self.width * self.marker.resolution().0
+    }
+
+    pub fn render(&self, series: &[Series], style: Style) -> Result<Vec<String>, CalcError> {
+        let (x0, x1) = self.x_range;
+        let (y0, y1) = y_range(series, self.x_range)
+            .ok_or_else(|| CalcError::InvalidExpression("nothing to plot: no finite value in the range".to_string()))?;
+        let (top, bottom) = (label(y1), label(y0));
+        let margin = top.chars().count().max(bottom.chars().count());
+        let (rx, ry) = self.marker.resolution();
+        let (columns, rows) = (self.width.saturating_sub(margin + 1), self.height.saturating_sub(2));
+        let (dots_x, dots_y) = self.dots(margin);
+        if columns < 2 || rows < 2 {
+            return Err(CalcError::InvalidExpression(format!("the plot is too small ({}x{})", self.width, self.height)));
+        }
+
+        // 点の位置（左上が (0, 0)）
+        let to_dot = |(x, y): (f64, f64)| -> Option<(usize, usize)> {
+            if !(x0..=x1).contains(&x) || !(y0..=y1).contains(&y) {
+                return None;
+            }
+            let column = ((x - x0) / (x1 - x0) * (dots_x - 1) as f64).round() as usize;
+            let row = ((y1 - y) / (y1 - y0) * (dots_y - 1) as f64).round() as usize;
+            Some((column, row))
+        };
+        let mut bits = vec![vec![0u8; columns]; rows];
+        // 文字ごとに最後に描いた系列（色分けに使う）
+        let mut owners = vec![vec![0usize; columns]; rows];
+        for (index, series) in series.iter().enumerate() {
+            let mut set = |(column, row): (usize, usize)| {
+                bits[row / ry][column / rx] |= self.marker.bit(column % rx, row % ry);
+                owners[row / ry][column / rx] = index;
+            };
+            let mut previous: Option<(usize, usize)> = None;
+            for &point in &series.points {
+                let dot = to_dot(point);
+                if let Some(dot) = dot {
+                    match previous {
+                        // 隣の列の点との差が高さの半分を超えるときは不連続とみなして結ばない（1/x の x = 0 など）
+                        Some((column, row)) if series.connect && (column.abs_diff(dot.0) > 1 || row.abs_diff(dot.1) <= dots_y / 2) => {
+                            line((column, row), dot).into_iter().for_each(&mut set)
+                        }
+                        _ => set(dot),
+                    }
+                }
+                previous = dot;
+            }
+        }
+
+        let [horizontal, vertical, cross, tick] = self.marker.axes();
+        let axis_row = (0.0 >= y0 && 0.0 <= y1).then(|| to_dot((x0, 0.0)).map(|(_, row)| row / ry)).flatten();
+        // 左端の列には目盛りの軸があるので引かない
+        let axis_column = (0.0 > x0 && 0.0 <= x1).then(|| to_dot((0.0, y0)).map(|(column, _)| column / rx)).flatten().filter(|&c| c > 0);
+        let mut lines = Vec::new();
+        for (r, cells) in bits.iter().enumerate() {
+            let (text, edge) = match r {
+                0 => (top.as_str(), tick),
+                r if r + 1 == rows => (bottom.as_str(), tick),
+                _ => ("", vertical),
+            };
+            let mut line = format!("{:>margin$}{}", text, edge);
+            for (c, &cell) in cells.iter().enumerate() {
+                let glyph = match (cell, Some(r) == axis_row, Some(c) == axis_column) {
+                    (0, true, true) => cross.to_string(),
+                    (0, true, false) => horizontal.to_string(),
+                    (0, false, true) => vertical.to_string(),
+                    (0, false, false) => " ".to_string(),
+                    _ => style.series(owners[r][c], &self.marker.glyph(cell).to_string()),
+                };
+                line.push_str(&glyph);
+            }
+            lines.push(line);
+        }
+        let bottom_edge = if self.marker == Marker::Ascii { '+' } else { '└' };
+        lines.push(format!("{:margin$}{}{}", "", bottom_edge, horizontal.to_string().repeat(columns)));
+        let (left, right) = (label(x0), label(x1));
+        let gap = columns.saturating_sub(left.chars().count() + right.chars().count()).max(1);
+        lines.push(format!("{:margin$} {}{}{}", "", left, " ".repeat(gap), right));
+
+        // 複数の系列は凡例を付ける
+        if series.len() > 1 {
+            for (index, series) in series.iter().enumerate() {
+                lines.push(format!("  {} {}", style.series(index, &self.marker.glyph(u8::MAX).to_string()), series.label));
+            }
+        }
+        Ok(lines)
+    }
+}
+
+// 関数を x の範囲で count 個の点で計算する（定義されない所は NaN にして線を切る）
+pub fn sample(evaluator: &Evaluator, expression: &str, variable: Option<&str>, range: (f64, f64), count: usize) -> Result<Series, CalcError> {
+    let expr = parser::parse(expression, evaluator.syntax)?;
+    let variable = match variable {
+        Some(variable) => variable.to_string(),
+        None => solver::find_unknown(evaluator, &expr)?,
+    };
+    let f = solver::function(evaluator, &expr, &variable);
+    let (from, to) = range;
+    let count = count.max(2);
+    let mut points = Vec::with_capacity(count);
+    for i in 0..count {
+        let x = from + (to - from) * i as f64 / (count - 1) as f64;
+        let y = match f(x) {
+            Ok(y) => y,
+            Err(CalcError::DivisionByZero | CalcError::InvalidExpression(_)) => f64::NAN,
+            Err(e) => return Err(e),
+        };
+        points.push((x, y));
+    }
+    Ok(Series { label: expression.to_string(), points, connect: true })
+}
+
+// 1行に「x y」「x,y」または y だけが並んだデータ（y だけなら x は 0 から数えた点の番号）
+pub fn parse_points(input: &str) -> Result<Series, CalcError> {
+    let mut points = Vec::new();
+    for (number, line) in input.lines().enumerate() {
+        let fields: Vec<&str> = line.split(|c: char| c == ',' || c.is_whitespace()).filter(|field| !field.is_empty()).collect();
+        let values = fields.iter().map(|field| field.parse::<f64>()).collect::<Result<Vec<f64>, _>>();
+        let point = match values.as_deref() {
+            Ok([]) => continue,
+            Ok([y]) => (points.len() as f64, *y),
+            Ok([x, y]) => (*x, *y),
+            _ => return Err(CalcError::InvalidExpression(format!("line {}: expected 'x y' or a single number, got '{}'", number + 1, line.trim()))),
+        };
+        points.push(point);
+    }
+    if points.is_empty() {
+        return Err(CalcError::InvalidExpression("no points to plot".to_string()));
+    }
+    Ok(Series { label: "points".to_string(), points, connect: false })
+}
+
+// 範囲内の有限な y の最小値と最大値（幅がなければ上下に 1 広げる）
+fn y_range(series: &[Series], (x0, x1): (f64, f64)) -> Option<(f64, f64)> {
+    let ys = series
+        .iter()
+        .flat_map(|series| series.points.iter())
+        .filter(|(x, y)| (x0..=x1).contains(x) && y.is_finite())
+        .map(|&(_, y)| y);
+    let (min, max) = ys.fold(None, |range: Option<(f64, f64)>, y| match range {
+        Some((min, max)) => Some((min.min(y), max.max(y))),
+        None => Some((y, y)),
+    })?;
+    Some(if min == max { (min - 1.0, max + 1.0) } else { (min, max) })
+}
+
+// 2点を結ぶ線上の点
+fn line((c0, r0): (usize, usize), (c1, r1): (usize, usize)) -> Vec<(usize, usize)> {
+    let steps = c0.abs_diff(c1).max(r0.abs_diff(r1)).max(1);
+    let lerp = |a: usize, b: usize, t: f64| (a as f64 + (b as f64 - a as f64) * t).round() as usize;
+    (0..=steps)
+        .map(|k| {
+            let t = k as f64 / steps as f64;
+            (lerp(c0, c1, t), lerp(r0, r1, t))
+        })
+        .collect()
+}
+
+// 目盛りの数値（小数点以下4桁までで末尾の 0 は省く）
+fn label(value: f64) -> String {
+    let text = format!("{:.4}", value);
+    let text = text.trim_end_matches('0').trim_end_matches('.');
+    if text == "-0" { "0".to_string() } else { text.to_string() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plot(marker: Marker, width: usize, height: usize, x_range: (f64, f64)) -> Plot {
+        Plot { width, height, marker, x_range }
+    }
+
+    #[test]
+    fn test_render_line() {
+        let series = Series { label: "x".to_string(), points: vec![(0.0, 0.0), (2.0, 2.0)], connect: true };
+        let lines = plot(Marker::Ascii, 5, 5, (0.0, 2.0)).render(&[series], Style::default()).unwrap();
+        assert_eq!(lines, vec!["2+  *", " | * ", "0+*--", " +---", "  0 2"]);
+    }
+
+    #[test]
+    fn test_braille_and_block() {
+        assert_eq!(Marker::Braille.glyph(Marker::Braille.bit(0, 0) | Marker::Braille.bit(1, 3)), '⢁');
+        assert_eq!(Marker::Block.glyph(Marker::Block.bit(0, 1)), '▄');
+
+        let evaluator = Evaluator::default();
+        let sine = plot(Marker::Braille, 40, 10, (-3.2, 3.2));
+        let series = sample(&evaluator, "sin(x)", None, sine.x_range, sine.samples()).unwrap();
+        let lines = sine.render(&[series], Style::default()).unwrap();
+        assert_eq!(lines.len(), 10);
+        assert!(lines.iter().all(|line| line.chars().count() <= 40));
+        assert!(lines[0].contains('┤') && lines[7].contains('┤') && lines[7].starts_with('-'));
+    }
+
+    #[test]
+    fn test_sample_and_points() {
+        let evaluator = Evaluator::default();
+        let series = sample(&evaluator, "1 / t", None, (-1.0, 1.0), 3).unwrap();
+        assert_eq!(series.points[0], (-1.0, -1.0));
+        assert!(series.points[1].1.is_nan());
+        assert!(matches!(sample(&evaluator, "x * y", None, (0.0, 1.0), 3), Err(CalcError::InvalidExpression(_))));
+        assert!(matches!(sample(&evaluator, "foo(x)", None, (0.0, 1.0), 3), Err(CalcError::UnknownFunction(_))));
+
+        let points = parse_points("1 2\n3,4\n\n5\n").unwrap();
+        assert_eq!(points.points, vec![(1.0, 2.0), (3.0, 4.0), (2.0, 5.0)]);
+        assert!(parse_points("1 2 3").is_err());
+        assert!(parse_points("").is_err());
+    }
+}
@@ -0,0 +1,323 @@
+// スクリプトファイルの実行（calc-cli run）
+//
+// 1行に1つの文を書き、# から行末まではコメントとして無視する
+//   print(式や "文字列", ...)   値を空白区切りで表示する
+//   exit / exit(コード)        スクリプトを終了する
+//   文 if 条件                  条件が 0 以外のときだけ文を実行する
+// それ以外の行は代入・関数定義・式として評価する（式の値は表示せず ans に入れる）
+// --keep-going では失敗した行を飛ばして最後まで実行し、行ごとの結果をまとめる
+use std::io::Write;
+
+use anyhow::{bail, Context, Result};
+use serde::Serialize;
+
+use calc_core::evaluator::{Evaluator, Outcome};
+use calc_core::value::Value;
+
+// 行を実行した後の流れ
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Flow {
+    Continue,
+    Exit(i32),
+}
+
+// 実行した行の結果（batch --summary と run --keep-going の最後に出力する）
+#[derive(Debug, Default, PartialEq, Serialize)]
+pub struct Report {
+    pub total: usize,
+    pub ok: usize,
+    pub failed: usize,
+    pub lines: Vec<LineStatus>,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct LineStatus {
+    pub line: usize,
+    pub input: String,
+    pub status: Status,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+    Ok,
+    Failed,
+}
+
+impl Report {
+    // error が None なら成功
+    pub fn push(&mut self, line: usize, input: &str, error: Option<String>) {
+        self.total += 1;
+        let status = if error.is_some() {
+            self.failed += 1;
+            Status::Failed
+        } else {
+            self.ok += 1;
+            Status::Ok
+        };
+        self.lines.push(LineStatus { line, input: input.to_string(), status, error });
+    }
+}
+
+// スクリプトを実行して終了コードを返す（最後まで実行したら 0）
+pub fn run(evaluator: &mut Evaluator, source: &str, out: &mut impl Write) -> Result<i32> {
+    for (line, statement) in statements(source) {
+        let flow = run_line(evaluator, statement, out).with_context(|| format!("line {}: {}", line, statement))?;
+        if let Flow::Exit(code) = flow {
+            return Ok(code);
+        }
+    }
+    Ok(0)
+}
+
+// 失敗した行があっても最後（または exit）まで実行する
+// 失敗した行は on_error に (行番号, 文, エラー) で渡す
+pub fn run_keep_going(
+    evaluator: &mut Evaluator,
+    source: &str,
+    out: &mut impl Write,
+    mut on_error: impl FnMut(usize, &str, &anyhow::Error),
+) -> (i32, Report) {
+    let mut report = Report::default();
+    for (line, statement) in statements(source) {
+        match run_line(evaluator, statement, out) {
+            Ok(flow) => {
+                report.push(line, statement, None);
+                if let Flow::Exit(code) = flow {
+                    return (code, report);
+                }
+            }
+            Err(e) => {
+                on_error(line, statement, &e);
+                report.push(line, statement, Some(format!("{:#}", e)));
+            }
+        }
+    }
+    (0, report)
+}
+
+// コメントと空行を除いた (行番号, 文)
+fn statements(source: &str) -> impl Iterator<Item = (usize, &str)> {
+    source
+        .lines()
+        .enumerate()
+        .map(|(index, line)| (index + 1, strip_comment(line).trim()))
+        .filter(|(_, statement)| !statement.is_empty())
+}
+
+fn run_line(evaluator: &mut Evaluator, line: &str, out: &mut impl Write) -> Result<Flow> {
+    let statement = match split_condition(line) {
+        Some((statement, condition)) => {
+            if statement.is_empty() {
+                bail!("expected a statement before 'if'");
+            }
+            if !is_true(&evaluator.evaluate_str(condition)?)? {
+                return Ok(Flow::Continue);
+            }
+            statement
+        }
+        None => line,
+    };
+
+    if let Some(args) = call_args(statement, "print") {
+        let parts = split_args(args)
+            .into_iter()
+            .map(|arg| match arg.strip_prefix('"').and_then(|rest| rest.strip_suffix('"')) {
+                Some(text) => Ok(text.to_string()),
+                None => Ok(evaluator.format(&evaluator.evaluate_str(arg)?)),
+            })
+            .collect::<Result<Vec<String>>>()?;
+        writeln!(out, "{}", parts.join(" "))?;
+        return Ok(Flow::Continue);
+    }
+
+    if statement == "exit" {
+        return Ok(Flow::Exit(0));
+    }
+    if let Some(arg) = call_args(statement, "exit") {
+        let code = evaluator.evaluate_str(arg)?;
+        let code = match &code {
+            Value::Number(number) => number.to_bigint().and_then(|code| i32::try_from(code).ok()),
+            _ => None,
+        };
+        return match code {
+            Some(code) => Ok(Flow::Exit(code)),
+            None => bail!("exit code must be an integer"),
+        };
+    }
+
+    if let Outcome::Value(value) = evaluator.execute(statement)? {
+        evaluator.set_last_result(value);
+    }
+    Ok(Flow::Continue)
+}
+
+// 条件は 0 以外の数値なら真
+fn is_true(value: &Value) -> Result<bool> {
+    match value {
+        Value::Number(number) => Ok(!number.is_zero()),
+        other => bail!("condition must be a number, got {}", other.kind()),
+    }
+}
+
+// name(...) の括弧の中身
+fn call_args<'a>(statement: &'a str, name: &str) -> Option<&'a str> {
+    let inner = statement.strip_prefix(name)?.trim_start().strip_prefix('(')?.strip_suffix(')')?;
+    // print(a) + print(b) のように括弧が途中で閉じるものは対象外
+    let mut depth = 0i32;
+    for (c, quoted) in scan(inner) {
+        if quoted {
+            continue;
+        }
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            _ => {}
+        }
+        if depth < 0 {
+            return None;
+        }
+    }
+    Some(inner)
+}
+
+// 文字列の中かどうかを付けて1文字ずつ返す
+fn scan(text: &str) -> impl Iterator<Item = (char, bool)> + '_ {
+    let mut quoted = false;
+    text.chars().map(move |c| {
+        if c == '"' {
+            quoted = !quoted;
+            (c, true)
+        } else {
+            (c, quoted)
+        }
+    })
+}
+
+// 文字列の外にある # から行末を取り除く
+fn strip_comment(line: &str) -> &str {
+    let end = line
+        .char_indices()
+        .zip(scan(line))
+        .find(|&(_, (c, quoted))| c == '#' && !quoted)
+        .map_or(line.len(), |((index, _), _)| index);
+    &line[..end]
+}
+
+// 括弧と文字列の外にある最後の " if " で文と条件に分ける
+fn split_condition(line: &str) -> Option<(&str, &str)> {
+    let mut depth = 0i32;
+    let mut split = None;
+    for ((index, _), (c, quoted)) in line.char_indices().zip(scan(line)) {
+        if quoted {
+            continue;
+        }
+        match c {
+            '(' | '[' => depth += 1,
+            ')' | ']' => depth -= 1,
+            _ => {}
+        }
+        let rest = &line[index..];
+        let word_start = index == 0 || line[..index].ends_with(char::is_whitespace);
+        if depth == 0 && word_start && rest.starts_with("if") && rest[2..].starts_with(char::is_whitespace) {
+            split = Some(index);
+        }
+    }
+    split.map(|index| (line[..index].trim(), line[index + 2..].trim()))
+}
+
+// 括弧と文字列の外にあるカンマで引数を分ける
+fn split_args(args: &str) -> Vec<&str> {
+    if args.trim().is_empty() {
+        return Vec::new();
+    }
+    let mut parts = Vec::new();
+    let mut depth = 0i32;
+    let mut start = 0;
+    for ((index, _), (c, quoted)) in args.char_indices().zip(scan(args)) {
+        if quoted {
+            continue;
+        }
+        match c {
+            '(' | '[' => depth += 1,
+            ')' | ']' => depth -= 1,
+            ',' if depth == 0 => {
+                parts.push(args[start..index].trim());
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(args[start..].trim());
+    parts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run_script(source: &str) -> Result<(i32, String)> {
+        let mut evaluator = Evaluator::default();
+        let mut out = Vec::new();
+        let code = run(&mut evaluator, source, &mut out)?;
+        Ok((code, String::from_utf8(out).unwrap()))
+    }
+
+    #[test]
+    fn test_print_and_definitions() {
+        let source = "# loan payment\nrate = 0.05 / 12  # monthly\npayment(p, n) = p * rate / (1 - (1 + rate)^-n)\n\nprint(\"payment:\", round(payment(1000, 12)), \"per month, #1\")\nprint()\n";
+        assert_eq!(run_script(source).unwrap(), (0, "payment: 86 per month, #1\n\n".to_string()));
+    }
+
+    #[test]
+    fn test_conditional_exit() {
+        let source = "x = 3\nprint(\"big\") if x > 2\nprint(\"small\") if x <= 2\nexit(2) if x == 3\nprint(\"unreachable\")";
+        assert_eq!(run_script(source).unwrap(), (2, "big\n".to_string()));
+        assert_eq!(run_script("exit\nprint(1)").unwrap(), (0, String::new()));
+        assert_eq!(run_script("2 * 21\nprint(ans)").unwrap(), (0, "42\n".to_string()));
+    }
+
+    #[test]
+    fn test_errors_report_line() {
+        let error = run_script("x = 1\n\nprint(y)").unwrap_err();
+        assert_eq!(format!("{:#}", error), "line 3: print(y): Unknown variable or constant: y");
+        assert!(run_script("exit(1.5)").is_err());
+        assert!(run_script("if x > 1").is_err());
+        assert!(run_script("print(1) if [1, 2]").is_err());
+    }
+
+    #[test]
+    fn test_keep_going() {
+        let mut evaluator = Evaluator::default();
+        let mut out = Vec::new();
+        let mut errors = Vec::new();
+        let source = "x = 1\nprint(y)  # typo\n\nprint(x + 1)\n1 / 0\nexit(3) if x == 1\nprint(\"unreachable\")";
+        let (code, report) = run_keep_going(&mut evaluator, source, &mut out, |line, _, _| errors.push(line));
+        assert_eq!((code, String::from_utf8(out).unwrap()), (3, "2\n".to_string()));
+        assert_eq!(errors, vec![2, 5]);
+        assert_eq!((report.total, report.ok, report.failed), (5, 3, 2));
+        assert_eq!(
+            report.lines[1],
+            LineStatus {
+                line: 2,
+                input: "print(y)".to_string(),
+                status: Status::Failed,
+                error: Some("Unknown variable or constant: y".to_string()),
+            }
+        );
+        assert_eq!(
+            serde_json::to_value(&report.lines[0]).unwrap(),
+            serde_json::json!({ "line": 1, "input": "x = 1", "status": "ok", "error": null })
+        );
+    }
+
+    #[test]
+    fn test_splitting() {
+        assert_eq!(split_condition("print(\"what if\") if x"), Some(("print(\"what if\")", "x")));
+        assert_eq!(split_condition("diff = 1"), None);
+        assert_eq!(split_args("\"a, b\", max(1, 2), [3, 4]"), vec!["\"a, b\"", "max(1, 2)", "[3, 4]"]);
+        assert_eq!(call_args("print(1) + print(2)", "print"), None);
+        assert_eq!(strip_comment("print(\"#1\") # note"), "print(\"#1\") ");
+    }
+}
@@ -0,0 +1,220 @@
+// 式を評価する HTTP サーバー（calc-cli serve）
+//
+//   POST /eval  {"expression": "x * 2", "variables": {"x": 21}}
+//     200 {"expression": ..., "result": 42, "text": "42", "error": null}
+//     422 {"expression": ..., "result": null, "text": null, "error": {"kind": "syntax", "message": ..., ...}}
+//   GET /health {"status": "ok"}
+// 変数の名前は識別子だけを受け付け、値は式として評価してから束縛する（名前や値に文を紛れ込ませられない）
+// 式と変数の値は MAX_EXPRESSION バイトまで（入れ子の深さは構文解析の上限で抑える）
+// 1件ずつ処理するので、計算量は評価するノードの数を MAX_STEPS までにして抑える（超えたら 422 の limit）
+// リクエストごとに起動時の状態（--angle などの設定と保存した変数）から評価するので、変数は次のリクエストに残らない
+// ブラウザから呼べるように CORS を許可する
+use std::collections::BTreeMap;
+use std::io::Read;
+
+use anyhow::Result;
+use serde::Deserialize;
+use serde_json::json;
+use tiny_http::{Header, Method, Response, Server};
+
+use calc_core::evaluator::{Evaluator, Outcome};
+use calc_core::{parser, CalcError};
+
+use crate::output::value_to_json;
+
+// これより大きい本文は読まない
+const MAX_BODY: u64 = 1 << 20;
+// 式と変数の値の長さの上限（バイト）
+const MAX_EXPRESSION: usize = 10_000;
+// 1件のリクエストで評価できる構文木のノードの数（変数の値も含む）
+const MAX_STEPS: u64 = 1_000_000;
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct EvalRequest {
+    expression: String,
+    // 値は数値か式の文字列
+    #[serde(default)]
+    variables: BTreeMap<String, serde_json::Value>,
+}
+
+// Ctrl-C で止めるまで1件ずつ処理する
+pub fn run(evaluator: &Evaluator, host: &str, port: u16) -> Result<()> {
+    let server = Server::http((host, port)).map_err(|e| anyhow::anyhow!("Failed to listen on {}:{}: {}", host, port, e))?;
+    eprintln!("Listening on http://{}:{} (POST /eval, Ctrl-C to stop)", host, port);
+    for mut request in server.incoming_requests() {
+        let mut body = String::new();
+        let (status, json) = match request.as_reader().take(MAX_BODY).read_to_string(&mut body) {
+            Ok(_) => handle(evaluator, request.method(), request.url(), &body),
+            Err(e) => (400, json!({ "error": format!("Failed to read the request body: {}", e) })),
+        };
+        tracing::info!(method = %request.method(), url = request.url(), status, "request");
+        let body = if json.is_null() { String::new() } else { json.to_string() };
+        let response = Response::from_string(body)
+            .with_status_code(status)
+            .with_header(header("Content-Type", "application/json"))
+            .with_header(header("Access-Control-Allow-Origin", "*"))
+            .with_header(header("Access-Control-Allow-Methods", "GET, POST, OPTIONS"))
+            .with_header(header("Access-Control-Allow-Headers", "Content-Type"));
+        if let Err(e) = request.respond(response) {
+            tracing::warn!("failed to send a response: {}", e);
+        }
+    }
+    Ok(())
+}
+
+fn header(name: &str, value: &str) -> Header {
+    Header::from_bytes(name, value).expect("header names and values are ASCII")
+}
+
+// (ステータスコード, 本文) を返す
+pub fn handle(evaluator: &Evaluator, method: &Method, url: &str, body: &str) -> (u16, serde_json::Value) {
+    let path = url.split('?').next().unwrap_or(url);
+    match (method, path) {
+        (Method::Post, "/eval") => {}
+        (Method::Get, "/health") => return (200, json!({ "status": "ok" })),
+        // CORS のプリフライト
+        (Method::Options, _) => return (204, serde_json::Value::Null),
+        (_, "/eval" | "/health") => return (405, json!({ "error": format!("Method {} not allowed for {}", method, path) })),
+        _ => return (404, json!({ "error": format!("Not found: {}", path) })),
+    }
+    let request: EvalRequest = match serde_json::from_str(body) {
+        Ok(request) => request,
+        Err(e) => return (400, json!({ "error": format!("Invalid request: {}", e) })),
+    };
+    let mut evaluator = evaluator.clone();
+    evaluator.limit_steps(MAX_STEPS);
+    match evaluate(&mut evaluator, &request) {
+        Ok((result, text)) => (200, json!({ "expression": request.expression, "result": result, "text": text, "error": null })),
+        Err(error) => (422, json!({ "expression": request.expression, "result": null, "text": null, "error": error })),
+    }
+}
+
+// 変数を定義してから「;」で区切った文を順に実行し、最後の値を返す
+fn evaluate(evaluator: &mut Evaluator, request: &EvalRequest) -> Result<(serde_json::Value, String), serde_json::Value> {
+    check_length("expression", &request.expression)?;
+    for (name, value) in &request.variables {
+        if !parser::is_identifier(name) {
+            return Err(json!({ "kind": "request", "message": format!("invalid variable name '{}'", name), "variable": name }));
+        }
+        let text = match value {
+            serde_json::Value::String(text) => text.clone(),
+            serde_json::Value::Number(number) => number.to_string(),
+            other => return Err(json!({ "kind": "request", "message": format!("variable '{}' must be a number or a string, got {}", name, other) })),
+        };
+        check_length(&format!("variable '{}'", name), &text)?;
+        // 値は代入文にせず式として評価する（"1; f(x) = x" のような値は構文エラーになる）
+        let defined = evaluator.evaluate_str(&text).and_then(|value| evaluator.define_variable(name, value));
+        if let Err(e) = defined {
+            let mut error = error_json(&e);
+            error["variable"] = json!(name);
+            return Err(error);
+        }
+    }
+    let mut last = None;
+    for statement in parser::split_statements(&request.expression) {
+        let value = match evaluator.execute(statement).map_err(|e| error_json(&e))? {
+            Outcome::Value(value) | Outcome::Assigned { value, .. } => Some(value),
+            Outcome::Defined { .. } => None,
+        };
+        last = value.map(|value| (value_to_json(&value), evaluator.format(&value)));
+    }
+    last.ok_or_else(|| json!({ "kind": "request", "message": "the last statement defines a function and has no value" }))
+}
+
+fn check_length(what: &str, text: &str) -> Result<(), serde_json::Value> {
+    if text.len() > MAX_EXPRESSION {
+        return Err(json!({ "kind": "request", "message": format!("{} is longer than {} bytes", what, MAX_EXPRESSION) }));
+    }
+    Ok(())
+}
+
+// 構文エラーには位置とヒントを付ける
+fn error_json(error: &CalcError) -> serde_json::Value {
+    let kind = match error {
+        CalcError::SyntaxError { .. } | CalcError::ParseError(_) => "syntax",
+        CalcError::DivisionByZero => "division_by_zero",
+        CalcError::UnknownVariable(_) | CalcError::UnknownFunction(_) | CalcError::UnknownOperation(_) => "unknown_name",
+        CalcError::ArgumentCount { .. } => "argument_count",
+        CalcError::InvalidExpression(_) | CalcError::RecursionLimit(_) | CalcError::DimensionMismatch(_) => "math",
+        CalcError::StepLimit(_) => "limit",
+    };
+    let mut json = json!({ "kind": kind, "message": error.to_string() });
+    if let CalcError::SyntaxError { position, len, hint, .. } = error {
+        json["position"] = json!(position);
+        json["length"] = json!(len);
+        json["hint"] = json!(hint);
+    }
+    json
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn post(body: &str) -> (u16, serde_json::Value) {
+        handle(&Evaluator::default(), &Method::Post, "/eval", body)
+    }
+
+    #[test]
+    fn test_eval() {
+        let (status, json) = post(r#"{"expression": "x * y", "variables": {"x": 6, "y": "3 + 4"}}"#);
+        assert_eq!(status, 200);
+        assert_eq!(json, json!({ "expression": "x * y", "result": 42.0, "text": "42", "error": null }));
+        let (status, json) = post(r#"{"expression": "f(n) = n^2; a = f(3); a + 1"}"#);
+        assert_eq!((status, &json["text"]), (200, &json!("10")));
+        // 前のリクエストの変数は残らない
+        let (status, json) = post(r#"{"expression": "a"}"#);
+        assert_eq!((status, &json["error"]["kind"]), (422, &json!("unknown_name")));
+    }
+
+    #[test]
+    fn test_errors() {
+        let (status, json) = post(r#"{"expression": "2 +* 3"}"#);
+        assert_eq!(status, 422);
+        assert_eq!(json["error"]["kind"], "syntax");
+        assert_eq!(json["error"]["position"], 3);
+        assert_eq!(post(r#"{"expression": "1 / 0"}"#).1["error"]["kind"], "division_by_zero");
+        let (status, json) = post(r#"{"expression": "x", "variables": {"x": "1 +"}}"#);
+        assert_eq!((status, &json["error"]["variable"]), (422, &json!("x")));
+        assert_eq!(post(r#"{"expression": "1", "variables": {"x": true}}"#).1["error"]["kind"], "request");
+
+        // 入れ子が深すぎる式はスタックを使い切らずに構文エラーになる
+        let deep = json!({ "expression": "(".repeat(5_000) }).to_string();
+        assert_eq!(post(&deep).1["error"]["kind"], "syntax");
+        let long = json!({ "expression": "1+".repeat(MAX_EXPRESSION) + "1" }).to_string();
+        assert_eq!(post(&long).1["error"]["kind"], "request");
+
+        assert_eq!(post("not json").0, 400);
+        assert_eq!(post(r#"{"expr": "1"}"#).0, 400);
+        assert_eq!(handle(&Evaluator::default(), &Method::Get, "/eval", "").0, 405);
+        assert_eq!(handle(&Evaluator::default(), &Method::Get, "/health?x=1", "").0, 200);
+        assert_eq!(handle(&Evaluator::default(), &Method::Post, "/other", "").0, 404);
+    }
+
+    #[test]
+    fn test_step_limit() {
+        // 短くて浅くても指数的に増える計算は上限ですぐに打ち切る
+        let start = std::time::Instant::now();
+        let (status, json) = post(r#"{"expression": "f(n) = if(n < 1, 0, f(n - 1) + f(n - 1)); f(40)"}"#);
+        assert_eq!((status, &json["error"]["kind"]), (422, &json!("limit")));
+        assert!(start.elapsed() < std::time::Duration::from_secs(10), "{:?}", start.elapsed());
+        let (status, json) = post(r#"{"expression": "x", "variables": {"x": "sum(i, 1, 1e7, i)"}}"#);
+        assert_eq!((status, &json["error"]["kind"], &json["error"]["variable"]), (422, &json!("limit"), &json!("x")));
+        // 次のリクエストは数え直す
+        assert_eq!(post(r#"{"expression": "sum(i, 1, 1000, i)"}"#).1["text"], "500500");
+    }
+
+    #[test]
+    fn test_variables_are_not_code() {
+        // 名前や値に書いた文は実行しない
+        let (status, json) = post(r#"{"expression": "1", "variables": {"f(x)": "x"}}"#);
+        assert_eq!((status, &json["error"]["kind"], &json["error"]["variable"]), (422, &json!("request"), &json!("f(x)")));
+        let (status, json) = post(r#"{"expression": "f(2)", "variables": {"x": "1; f(y) = y"}}"#);
+        assert_eq!((status, &json["error"]["kind"]), (422, &json!("syntax")));
+        assert_eq!(post(r#"{"expression": "a", "variables": {"a+b": 1}}"#).1["error"]["kind"], "request");
+        assert_eq!(post(r#"{"expression": "1", "variables": {"pi": 3}}"#).1["error"]["kind"], "math");
+        let long = json!({ "expression": "x", "variables": { "x": "1+".repeat(MAX_EXPRESSION) + "1" } }).to_string();
+        assert_eq!(post(&long).1["error"]["kind"], "request");
+    }
+}
@@ -0,0 +1,126 @@
+// セッションをまたいで使う変数（calc-cli var set/get/list/unset）
+//
+// ~/.calc_vars に「名前 = "値"」の TOML で保存し、起動時に評価器へ読み込む
+// 値は設定したときに評価した結果を文字列で持つ（rand() などを毎回評価し直さない）
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use toml_edit::DocumentMut;
+
+use calc_core::evaluator::Evaluator;
+use calc_core::parser::{self, Syntax};
+use calc_core::value::Value;
+
+#[derive(Debug, Default, PartialEq)]
+pub struct Vars {
+    values: BTreeMap<String, String>,
+}
+
+impl Vars {
+    // ファイルがなければ空
+    pub fn load(path: &Path) -> Result<Vars> {
+        let contents = match std::fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vars::default()),
+            Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path.display())),
+        };
+        Vars::parse(&contents).with_context(|| format!("Invalid variables file {}", path.display()))
+    }
+
+    pub fn parse(contents: &str) -> Result<Vars> {
+        let document: DocumentMut = contents.parse()?;
+        let mut vars = Vars::default();
+        for (name, item) in document.iter() {
+            let value = item.as_str().with_context(|| format!("variable '{}' is not a string", name))?;
+            vars.set(name, value);
+        }
+        Ok(vars)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let mut document = DocumentMut::new();
+        for (name, value) in &self.values {
+            document[name] = toml_edit::value(value.as_str());
+        }
+        std::fs::write(path, document.to_string()).with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.values.get(name).map(String::as_str)
+    }
+
+    pub fn set(&mut self, name: &str, value: &str) {
+        self.values.insert(name.to_string(), value.to_string());
+    }
+
+    // 消したら true
+    pub fn unset(&mut self, name: &str) -> bool {
+        self.values.remove(name).is_some()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.values.iter().map(|(name, value)| (name.as_str(), value.as_str()))
+    }
+
+    // 保存した値を評価する
+    // 値は「.」区切りの小数で保存しているので、--decimal-comma でも通常の構文で読む
+    pub fn evaluate(&self, name: &str, evaluator: &Evaluator) -> Result<Value> {
+        let text = self.get(name).with_context(|| format!("Variable '{}' is not saved", name))?;
+        parser::parse(text, Syntax::default())
+            .and_then(|expr| evaluator.evaluate(&expr))
+            .with_context(|| format!("saved variable {} = {}", name, text))
+    }
+
+    // 評価器の変数にする
+    pub fn define(&self, evaluator: &mut Evaluator) -> Result<()> {
+        for name in self.values.keys() {
+            let value = self.evaluate(name, evaluator)?;
+            evaluator.set_variable(name, value);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file() {
+        let mut vars = Vars::default();
+        vars.set("rate", "0.0825");
+        vars.set("fx", "151.3");
+        vars.set("old", "1");
+        assert!(vars.unset("old"));
+        assert!(!vars.unset("old"));
+
+        let path = std::env::temp_dir().join(format!("calc-vars-test-{}.toml", std::process::id()));
+        vars.save(&path).unwrap();
+        let loaded = Vars::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded, vars);
+        assert_eq!(loaded.iter().collect::<Vec<_>>(), vec![("fx", "151.3"), ("rate", "0.0825")]);
+
+        assert!(Vars::parse("x = 3").is_err());
+        assert_eq!(Vars::load(Path::new("/nonexistent/calc_vars")).unwrap(), Vars::default());
+    }
+
+    #[test]
+    fn test_define() {
+        let mut vars = Vars::default();
+        vars.set("rate", "0.0825");
+        vars.set("size", "[1, 2]");
+        let mut evaluator = Evaluator::default();
+        evaluator.syntax.decimal_comma = true;
+        vars.define(&mut evaluator).unwrap();
+        evaluator.syntax = Syntax::default();
+        assert_eq!(evaluator.evaluate_str("rate * 2").unwrap().to_string(), "0.165");
+        assert_eq!(evaluator.evaluate_str("size").unwrap().to_string(), "[1, 2]");
+        assert_eq!(vars.evaluate("rate", &evaluator).unwrap().to_string(), "0.0825");
+        assert!(vars.evaluate("missing", &evaluator).is_err());
+
+        vars.set("bad", "1 +");
+        assert!(vars.define(&mut Evaluator::default()).is_err());
+    }
+}
@@ -0,0 +1,90 @@
+// スクリプトの変更を監視して実行し直す（calc-cli watch）
+//
+// 変更は更新時刻と大きさを一定間隔で調べて見つける
+// 毎回 --angle などの設定だけを引き継いだ新しい状態で実行するので、前回の変数は残らない
+use std::io::{IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use anyhow::{Context, Result};
+
+use calc_core::evaluator::Evaluator;
+
+use crate::color::Style;
+use crate::script;
+
+// 画面を消してカーソルを左上に戻す
+const CLEAR_SCREEN: &str = "\x1b[2J\x1b[H";
+
+// 前回調べたときのファイルの状態
+#[derive(Debug)]
+pub struct Watcher {
+    path: PathBuf,
+    last: Option<(SystemTime, u64)>,
+}
+
+impl Watcher {
+    pub fn new(path: &Path) -> Watcher {
+        Watcher { path: path.to_path_buf(), last: None }
+    }
+
+    // 前回から変わったか（初回は true。消えたファイルは変わっていないとみなす）
+    pub fn changed(&mut self) -> bool {
+        let Ok(metadata) = std::fs::metadata(&self.path) else {
+            return false;
+        };
+        let current = Some((metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH), metadata.len()));
+        if current == self.last {
+            return false;
+        }
+        self.last = current;
+        true
+    }
+}
+
+// Ctrl-C で止めるまで続ける
+pub fn run(evaluator: &Evaluator, path: &Path, interval: Duration, clear: bool, error_color: Style) -> Result<()> {
+    // 最初に読めなければ監視しても意味がないので終了する
+    std::fs::metadata(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let clear = clear && std::io::stdout().is_terminal();
+    let mut watcher = Watcher::new(path);
+    loop {
+        if watcher.changed() {
+            tracing::debug!("{} changed, running it again", path.display());
+            let mut out = std::io::stdout().lock();
+            if clear {
+                write!(out, "{}", CLEAR_SCREEN)?;
+            }
+            writeln!(out, "# {} (watching for changes, Ctrl-C to stop)", path.display())?;
+            let result = std::fs::read_to_string(path)
+                .with_context(|| format!("Failed to read {}", path.display()))
+                .and_then(|source| script::run(&mut evaluator.clone(), &source, &mut out));
+            match result {
+                Ok(0) => {}
+                Ok(code) => writeln!(out, "# exited with code {}", code)?,
+                Err(e) => eprintln!("{} {:#}", error_color.error("Error:"), e),
+            }
+            out.flush()?;
+        }
+        std::thread::sleep(interval);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_changed() {
+        let path = std::env::temp_dir().join(format!("calc-watch-test-{}.calc", std::process::id()));
+        std::fs::write(&path, "x = 1\n").unwrap();
+        let mut watcher = Watcher::new(&path);
+        assert!(watcher.changed());
+        assert!(!watcher.changed());
+        // 更新時刻の分解能が粗くても大きさで気づく
+        std::fs::write(&path, "x = 12\n").unwrap();
+        assert!(watcher.changed());
+        std::fs::remove_file(&path).unwrap();
+        assert!(!watcher.changed());
+    }
+}